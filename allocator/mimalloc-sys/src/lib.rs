@@ -17,6 +17,19 @@ extern "C" {
     /// p: the pointer to the memory to free or nullptr
     pub fn mi_free(p: *mut c_void);
     pub fn mi_realloc_aligned(p: *mut c_void, newsize: usize, alignment: usize) -> *mut c_void;
+
+    /// Get statistics about the process. Any of the pointers may be nullptr.
+    /// current_commit, peak_commit: the bytes of memory committed by the process now, and at most
+    pub fn mi_process_info(
+        elapsed_msecs: *mut usize,
+        user_msecs: *mut usize,
+        system_msecs: *mut usize,
+        current_rss: *mut usize,
+        peak_rss: *mut usize,
+        current_commit: *mut usize,
+        peak_commit: *mut usize,
+        page_faults: *mut usize,
+    );
 }
 
 #[cfg(test)]
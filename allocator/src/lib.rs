@@ -19,3 +19,23 @@ macro_rules! assign_global {
         static GLOBAL: allocator::mimalloc::Mimalloc = allocator::mimalloc::Mimalloc;
     };
 }
+
+/// Returns the bytes of memory committed by the process now, and the most it has committed at
+/// once, if the platform supports the global allocator.
+#[must_use]
+pub fn committed_memory() -> Option<(usize, usize)> {
+    #[cfg(not(any(
+        target_family = "wasm",
+        all(target_family = "windows", target_arch = "aarch64")
+    )))]
+    {
+        Some(mimalloc::committed_memory())
+    }
+    #[cfg(any(
+        target_family = "wasm",
+        all(target_family = "windows", target_arch = "aarch64")
+    ))]
+    {
+        None
+    }
+}
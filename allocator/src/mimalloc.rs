@@ -4,7 +4,9 @@
 use core::alloc::{GlobalAlloc, Layout};
 use core::ffi::c_void;
 
-use mimalloc_sys::{mi_free, mi_malloc_aligned, mi_realloc_aligned, mi_zalloc_aligned};
+use mimalloc_sys::{
+    mi_free, mi_malloc_aligned, mi_process_info, mi_realloc_aligned, mi_zalloc_aligned,
+};
 
 pub struct Mimalloc;
 
@@ -33,6 +35,25 @@ unsafe impl GlobalAlloc for Mimalloc {
     }
 }
 
+/// Returns the bytes of memory committed by the process now, and the most it has committed at once.
+#[must_use]
+pub fn committed_memory() -> (usize, usize) {
+    let (mut current, mut peak) = (0, 0);
+    unsafe {
+        mi_process_info(
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+            core::ptr::addr_of_mut!(current),
+            core::ptr::addr_of_mut!(peak),
+            core::ptr::null_mut(),
+        );
+    }
+    (current, peak)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,6 +85,22 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn committed_memory_includes_allocations() -> Result<(), Box<dyn Error>> {
+        let layout = Layout::from_size_align(64 * 1024 * 1024, 8)?;
+        let alloc = Mimalloc;
+
+        unsafe {
+            let ptr = alloc.alloc(layout);
+            assert!(!ptr.cast::<c_void>().is_null());
+            let (current, peak) = committed_memory();
+            assert!(current > 0);
+            assert!(peak >= current);
+            alloc.dealloc(ptr, layout);
+        }
+        Ok(())
+    }
+
     #[test]
     fn large_chunks_of_memory_can_be_allocated_and_freed() -> Result<(), Box<dyn Error>> {
         let layout = Layout::from_size_align(2 * 1024 * 1024 * 1024, 8)?;
@@ -189,6 +189,41 @@ pub fn large_nested_iteration(c: &mut Criterion) {
     });
 }
 
+pub fn big_int_arithmetic(c: &mut Criterion) {
+    c.bench_function("BigInt arithmetic evaluation", |b| {
+        let sources = SourceMap::new(
+            [("none".into(), "".into())],
+            Some(
+                indoc! {"{
+                    open Microsoft.Quantum.Convert;
+                    open Microsoft.Quantum.Math;
+                    mutable sum = 0L;
+                    for i in 1..200 {
+                        let a = IntAsBigInt(i);
+                        set sum += ExpModL(a, 1000L, 1000003L);
+                        set sum += GreatestCommonDivisorL(a * 7919L, 1000003L * a);
+                    }
+                    sum + FactorialL(100)
+                }"}
+                .into(),
+            ),
+        );
+        let mut evaluator = Interpreter::new(
+            true,
+            sources,
+            PackageType::Exe,
+            RuntimeCapabilityFlags::all(),
+            LanguageFeatures::default(),
+        )
+        .expect("code should compile");
+        b.iter(move || {
+            let mut out = Vec::new();
+            let mut rec = GenericReceiver::new(&mut out);
+            assert!(evaluator.eval_entry(&mut rec).is_ok());
+        });
+    });
+}
+
 criterion_group!(
     benches,
     teleport,
@@ -198,5 +233,6 @@ criterion_group!(
     array_update,
     array_literal,
     large_nested_iteration,
+    big_int_arithmetic,
 );
 criterion_main!(benches);
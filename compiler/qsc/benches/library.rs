@@ -10,7 +10,10 @@ use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags};
 pub fn library(c: &mut Criterion) {
     let store = PackageStore::new(compile::core());
     c.bench_function("Standard library", |b| {
-        b.iter(|| compile::std(&store, RuntimeCapabilityFlags::all()));
+        b.iter(|| {
+            compile::clear_library_cache();
+            compile::std(&store, RuntimeCapabilityFlags::all())
+        });
     });
 }
 
@@ -0,0 +1,71 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+allocator::assign_global!();
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use indoc::indoc;
+use qsc::{
+    interpret::{Interpreter, SimulatorKind},
+    PackageType,
+};
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_eval::output::GenericReceiver;
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+
+/// Layers of rotations and entangling gates that spread the state over every basis state, which
+/// is the worst case for the sparse simulator.
+const ENTANGLED: &str = indoc! {"
+    namespace Test {
+        open Microsoft.Quantum.Convert;
+
+        @EntryPoint()
+        operation Main() : Result[] {
+            use qs = Qubit[18];
+            for layer in 1..6 {
+                for q in qs {
+                    H(q);
+                    Rz(0.1 * IntAsDouble(layer), q);
+                }
+                for i in 0..Length(qs) - 2 {
+                    CNOT(qs[i], qs[i + 1]);
+                }
+            }
+            MResetEachZ(qs)
+        }
+    }
+"};
+
+fn entangled(c: &mut Criterion, name: &str, kind: SimulatorKind) {
+    let mut group = c.benchmark_group("Entangled state simulation");
+    group.sample_size(10);
+    group.bench_function(name, |b| {
+        let sources = SourceMap::new([("entangled.qs".into(), ENTANGLED.into())], None);
+        let mut evaluator = Interpreter::new(
+            true,
+            sources,
+            PackageType::Exe,
+            RuntimeCapabilityFlags::all(),
+            LanguageFeatures::default(),
+        )
+        .expect("code should compile");
+        evaluator.set_simulator(kind);
+        b.iter(move || {
+            let mut out = Vec::new();
+            let mut rec = GenericReceiver::new(&mut out);
+            assert!(evaluator.eval_entry(&mut rec).is_ok());
+        });
+    });
+    group.finish();
+}
+
+pub fn entangled_sparse(c: &mut Criterion) {
+    entangled(c, "sparse simulator", SimulatorKind::Sparse);
+}
+
+pub fn entangled_dense(c: &mut Criterion) {
+    entangled(c, "dense simulator", SimulatorKind::Dense);
+}
+
+criterion_group!(benches, entangled_sparse, entangled_dense);
+criterion_main!(benches);
@@ -7,6 +7,7 @@ use qsc::{compile, CompileUnit};
 use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags};
 use std::{
     alloc::{GlobalAlloc, Layout, System},
+    rc::Rc,
     sync::atomic::{AtomicU64, Ordering},
 };
 
@@ -46,7 +47,7 @@ impl<A: GlobalAlloc> AllocationCounter<A> {
 static ALLOCATOR: AllocationCounter<System> = AllocationCounter::new(System);
 
 #[must_use]
-pub fn compile_stdlib() -> CompileUnit {
+pub fn compile_stdlib() -> Rc<CompileUnit> {
     let store = PackageStore::new(compile::core());
     compile::std(&store, RuntimeCapabilityFlags::all())
 }
@@ -3,31 +3,53 @@
 
 allocator::assign_global!();
 
-use clap::{crate_version, ArgGroup, Parser, ValueEnum};
+use clap::{crate_version, ArgGroup, Args, Parser, Subcommand, ValueEnum};
 use log::info;
-use miette::{Context, IntoDiagnostic, Report};
-use qsc::compile::compile;
+use miette::{miette, Context, Diagnostic, IntoDiagnostic, Report, Severity};
+use qsc::{
+    compile::{compile_with_parse_cache, compile_with_passes},
+    hints::{
+        get_call_graph_for_sources, get_requirements_for_sources, CallGraph, CallableRequirements,
+    },
+    interpret::{CircuitEntryPoint, Interpreter},
+    linter::{run_lints, Lint, LintConfig, LintLevel},
+    target::Profile,
+    ParseCache,
+};
 use qsc_codegen::qir_base;
-use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_data_structures::{
+    language_features::LanguageFeatures,
+    line_column::{Encoding, Position},
+};
 use qsc_frontend::{
     compile::{PackageStore, RuntimeCapabilityFlags, SourceContents, SourceMap, SourceName},
     error::WithSource,
 };
 use qsc_hir::hir::{Package, PackageId};
-use qsc_passes::PackageType;
+use qsc_passes::{PackageType, PassContext};
 use qsc_project::{FileSystem, Manifest, StdFs};
+use serde_json::json;
 use std::{
-    concat, fs,
+    concat,
+    fmt::Write,
+    fs,
     io::{self, Read},
     path::{Path, PathBuf},
     process::ExitCode,
+    str::FromStr,
     string::String,
+    thread,
+    time::Duration,
 };
 
 #[derive(Debug, Parser)]
 #[command(version = concat!(crate_version!(), " (", env!("QSHARP_GIT_HASH"), ")"), arg_required_else_help(false))]
 #[clap(group(ArgGroup::new("input").args(["entry", "sources"]).required(false).multiple(true)))]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Disable automatic inclusion of the standard library.
     #[arg(long)]
     nostdlib: bool,
@@ -59,17 +81,148 @@ struct Cli {
     /// Language features to compile with
     #[arg(short, long)]
     features: Vec<String>,
+
+    /// Flags to set for `@Config` attributes, in addition to the ones in the project manifest
+    #[arg(long)]
+    config: Vec<String>,
+
+    /// Format to print errors and lints in.
+    #[arg(long, value_enum, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Report the runtime capabilities each callable needs, along with the constructs that need capabilities beyond
+    /// the target profile. Fails if there are any such constructs.
+    Capabilities(CapabilitiesArgs),
+    /// Print the call graph of a program, annotated with the runtime capabilities each callable needs. Callables
+    /// that introduce capabilities none of their callees need are highlighted.
+    CallGraph(CallGraphArgs),
+    /// Generate the circuit diagram of a program by running it with a circuit builder instead of a
+    /// simulator, and print it to standard output.
+    Circuit(CircuitArgs),
+    /// Check a program for errors and lints without emitting anything.
+    Check(CheckArgs),
+}
+
+#[derive(Debug, Args)]
+struct CapabilitiesArgs {
+    /// Q# source files, or the directory or manifest of a project, to analyze. Defaults to the project of the
+    /// current directory.
+    #[arg()]
+    inputs: Vec<PathBuf>,
+
+    /// Target profile to check the callables against.
+    #[arg(long, default_value = "unrestricted")]
+    profile: String,
+
+    /// Print the report as JSON.
+    #[arg(long)]
+    json: bool,
+
+    /// Language features to compile with
+    #[arg(short, long)]
+    features: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+struct CallGraphArgs {
+    /// Q# source files, or the directory or manifest of a project, to analyze. Defaults to the project of the
+    /// current directory.
+    #[arg()]
+    inputs: Vec<PathBuf>,
+
+    /// Format to print the call graph in.
+    #[arg(long, value_enum, default_value_t = CallGraphFormat::Dot)]
+    format: CallGraphFormat,
+
+    /// Language features to compile with
+    #[arg(short, long)]
+    features: Vec<String>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum CallGraphFormat {
+    Dot,
+    Json,
+}
+
+#[derive(Debug, Args)]
+struct CheckArgs {
+    /// Q# source files, or the directory or manifest of a project, to check. Defaults to the project of the
+    /// current directory.
+    #[arg()]
+    inputs: Vec<PathBuf>,
+
+    /// Keep running, and check again whenever a source changes.
+    #[arg(long)]
+    watch: bool,
+
+    /// Format to print errors and lints in. With `json`, a summary line follows each check.
+    #[arg(long, value_enum, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
+
+    /// Language features to compile with
+    #[arg(short, long)]
+    features: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+struct CircuitArgs {
+    /// Q# source files, or the directory or manifest of a project, to generate the circuit of.
+    /// Defaults to the project of the current directory.
+    #[arg()]
+    inputs: Vec<PathBuf>,
+
+    /// Entry expression to generate the circuit of, instead of the entry point of the program.
+    #[arg(short, long)]
+    entry: Option<String>,
+
+    /// Format to print the circuit in.
+    #[arg(long, value_enum, default_value_t = CircuitFormat::Text)]
+    format: CircuitFormat,
+
+    /// Language features to compile with
+    #[arg(short, long)]
+    features: Vec<String>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum CircuitFormat {
+    Text,
+    Json,
+    Qasm,
+    Svg,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum Emit {
     Hir,
     Qir,
+    /// The runtime capabilities needed by the public callables of a library.
+    Capabilities,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum MessageFormat {
+    /// Rendered diagnostics for reading in a terminal.
+    Human,
+    /// One JSON object per line, for tools.
+    Json,
 }
 
 fn main() -> miette::Result<ExitCode> {
     env_logger::init();
     let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Capabilities(args)) => return report_capabilities(args),
+        Some(Command::CallGraph(args)) => return print_call_graph(args),
+        Some(Command::Circuit(args)) => return generate_circuit(args),
+        Some(Command::Check(args)) => return check(&args),
+        None => {}
+    }
+
     let mut store = PackageStore::new(qsc::compile::core());
     let mut dependencies = Vec::new();
 
@@ -84,6 +237,8 @@ fn main() -> miette::Result<ExitCode> {
     }
 
     let mut features = LanguageFeatures::from_iter(cli.features);
+    let mut config = cli.config;
+    let mut lint_config: Option<Vec<LintConfig>> = None;
 
     let mut sources = cli
         .sources
@@ -103,19 +258,42 @@ fn main() -> miette::Result<ExitCode> {
             features.merge(LanguageFeatures::from_iter(
                 manifest.manifest.language_features,
             ));
+            config.extend(manifest.manifest.config);
+            lint_config = Some(manifest.manifest.lints);
         }
     }
 
     let entry = cli.entry.unwrap_or_default();
-    let sources = SourceMap::new(sources, Some(entry.into()));
-    let (unit, errors) = compile(
+    let sources =
+        SourceMap::new(sources, Some(entry.into())).with_config(config.into_iter().map(Into::into));
+    // Callables that can't be reached from the entry point never appear in the emitted QIR,
+    // so they are removed before lowering, and constants are folded so fewer expressions
+    // are treated as dynamic.
+    let mut passes = PassContext::new(capabilities);
+    passes.set_dead_code_elimination(PackageType::Exe, true);
+    passes.set_constant_folding(true);
+    let (unit, errors) = compile_with_passes(
         &store,
         &dependencies,
         sources,
         package_type,
         capabilities,
         features,
+        &mut passes,
     );
+    // Lints at the error level fail the build.
+    let lints = run_lints(&store, &unit, lint_config.as_deref());
+    let lint_failed = lints
+        .iter()
+        .any(|lint| matches!(lint.level, LintLevel::Error | LintLevel::ForceError));
+    for lint in lints {
+        print_diagnostic(
+            &unit.sources,
+            WithSource::from_map(&unit.sources, lint),
+            cli.message_format,
+        );
+    }
+
     let package_id = store.insert(unit);
     let unit = store.get(package_id).expect("package should be in store");
 
@@ -123,25 +301,450 @@ fn main() -> miette::Result<ExitCode> {
     for emit in &cli.emit {
         match emit {
             Emit::Hir => emit_hir(&unit.package, out_dir)?,
-            Emit::Qir => {
+            Emit::Capabilities => {
                 if errors.is_empty() {
+                    emit_capabilities(out_dir, &store, package_id)?;
+                }
+            }
+            Emit::Qir => {
+                if errors.is_empty() && !lint_failed {
                     emit_qir(out_dir, &store, package_id)?;
                 }
             }
         }
     }
 
-    if errors.is_empty() {
+    if errors.is_empty() && !lint_failed {
         Ok(ExitCode::SUCCESS)
     } else {
         for error in errors {
-            eprintln!("{:?}", Report::new(error));
+            print_diagnostic(&unit.sources, error, cli.message_format);
         }
 
         Ok(ExitCode::FAILURE)
     }
 }
 
+fn report_capabilities(args: CapabilitiesArgs) -> miette::Result<ExitCode> {
+    let profile = Profile::from_str(&args.profile)
+        .map_err(|()| miette!("unknown target profile `{}`", args.profile))?;
+    let mut features = LanguageFeatures::from_iter(args.features);
+    let sources = load_sources(&args.inputs, &mut features)?.into_source_map();
+    let callables = match get_requirements_for_sources(sources.clone(), features, profile.into()) {
+        Ok(callables) => callables,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{:?}", Report::new(error));
+            }
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    if args.json {
+        println!("{}", capabilities_json(&sources, profile, &callables));
+    } else {
+        print!("{}", capabilities_text(&sources, profile, &callables));
+    }
+
+    if callables
+        .iter()
+        .all(|callable| callable.unsupported.is_empty())
+    {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn print_call_graph(args: CallGraphArgs) -> miette::Result<ExitCode> {
+    let mut features = LanguageFeatures::from_iter(args.features);
+    let sources = load_sources(&args.inputs, &mut features)?.into_source_map();
+    let graph = match get_call_graph_for_sources(sources, features) {
+        Ok(graph) => graph,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{:?}", Report::new(error));
+            }
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    match args.format {
+        CallGraphFormat::Dot => print!("{}", graph.to_dot()),
+        CallGraphFormat::Json => println!("{}", call_graph_json(&graph)),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// How long watch mode waits between looking for changes to the sources.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+fn check(args: &CheckArgs) -> miette::Result<ExitCode> {
+    // The standard library is compiled once, and the parse cache keeps the sources that didn't
+    // change from being parsed again when checking in watch mode.
+    let capabilities = RuntimeCapabilityFlags::all();
+    let mut store = PackageStore::new(qsc::compile::core());
+    let dependencies = [store.insert(qsc::compile::std(&store, capabilities))];
+    let mut parse_cache = ParseCache::default();
+    let mut checked = None;
+    loop {
+        let mut features = LanguageFeatures::from_iter(&args.features);
+        let loaded = load_sources(&args.inputs, &mut features);
+        let LoadedSources {
+            sources,
+            lints: lint_config,
+            config,
+        } = match loaded {
+            Ok(loaded) => loaded,
+            // A file may be missing for a moment while an editor saves it.
+            Err(error) if args.watch => {
+                eprintln!("{error:?}");
+                thread::sleep(WATCH_INTERVAL);
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+
+        let input = (sources, features, lint_config, config);
+        if checked.as_ref() != Some(&input) {
+            let (sources, features, lint_config, config) = input.clone();
+            let unit_sources =
+                SourceMap::new(sources, None).with_config(config.into_iter().map(Into::into));
+            let (unit, errors) = compile_with_parse_cache(
+                &store,
+                &dependencies,
+                unit_sources,
+                &mut parse_cache,
+                false,
+                PackageType::Lib,
+                capabilities,
+                features,
+            );
+            let lints = run_lints(&store, &unit, Some(lint_config.as_slice()));
+            let passed = report_check(&unit.sources, errors, lints, args.message_format);
+            if !args.watch {
+                return Ok(if passed {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::FAILURE
+                });
+            }
+            checked = Some(input);
+        }
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+/// Prints the errors and lints of a check, followed by a summary, and returns whether the check
+/// passed.
+fn report_check(
+    sources: &SourceMap,
+    errors: Vec<qsc::compile::Error>,
+    lints: Vec<Lint>,
+    format: MessageFormat,
+) -> bool {
+    let lint_errors = lints
+        .iter()
+        .filter(|lint| matches!(lint.level, LintLevel::Error | LintLevel::ForceError))
+        .count();
+    let error_count = errors.len() + lint_errors;
+    let warning_count = lints.len() - lint_errors;
+
+    for error in errors {
+        print_diagnostic(sources, error, format);
+    }
+    for lint in lints {
+        print_diagnostic(sources, WithSource::from_map(sources, lint), format);
+    }
+
+    match format {
+        MessageFormat::Human => {
+            eprintln!("check finished: {error_count} error(s), {warning_count} warning(s)");
+        }
+        MessageFormat::Json => println!(
+            "{}",
+            json!({
+                "kind": "summary",
+                "errors": error_count,
+                "warnings": warning_count,
+            })
+        ),
+    }
+    error_count == 0
+}
+
+fn print_diagnostic(
+    sources: &SourceMap,
+    diagnostic: impl Diagnostic + Send + Sync + 'static,
+    format: MessageFormat,
+) {
+    match format {
+        MessageFormat::Human => eprintln!("{:?}", Report::new(diagnostic)),
+        MessageFormat::Json => println!("{}", diagnostic_json(sources, &diagnostic)),
+    }
+}
+
+/// The stable JSON schema of a diagnostic. Lines and columns are one-based, and columns count
+/// UTF-8 bytes like the offsets do.
+fn diagnostic_json(sources: &SourceMap, diagnostic: &dyn Diagnostic) -> serde_json::Value {
+    let severity = match diagnostic.severity().unwrap_or(Severity::Error) {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Advice => "advice",
+    };
+    let spans = diagnostic
+        .labels()
+        .into_iter()
+        .flatten()
+        .filter_map(|label| {
+            let lo = u32::try_from(label.offset()).ok()?;
+            let hi = u32::try_from(label.offset() + label.len()).ok()?;
+            let source = sources.find_by_offset(lo)?;
+            let (lo, hi) = (lo - source.offset, hi - source.offset);
+            let start = Position::from_utf8_byte_offset(Encoding::Utf8, &source.contents, lo);
+            let end = Position::from_utf8_byte_offset(Encoding::Utf8, &source.contents, hi);
+            Some(json!({
+                "source": source.name.as_ref(),
+                "lo": lo,
+                "hi": hi,
+                "line": start.line + 1,
+                "column": start.column + 1,
+                "endLine": end.line + 1,
+                "endColumn": end.column + 1,
+                "label": label.label(),
+            }))
+        })
+        .collect::<Vec<_>>();
+    let related = diagnostic
+        .related()
+        .into_iter()
+        .flatten()
+        .map(|related| diagnostic_json(sources, related))
+        .collect::<Vec<_>>();
+    json!({
+        "kind": "diagnostic",
+        "severity": severity,
+        "code": diagnostic.code().map(|code| code.to_string()),
+        "message": diagnostic.to_string(),
+        "help": diagnostic.help().map(|help| help.to_string()),
+        "spans": spans,
+        "related": related,
+    })
+}
+
+fn generate_circuit(args: CircuitArgs) -> miette::Result<ExitCode> {
+    let mut features = LanguageFeatures::from_iter(args.features);
+    let sources = load_sources(&args.inputs, &mut features)?.into_source_map();
+    let entry = match args.entry {
+        Some(entry) => CircuitEntryPoint::EntryExpr(entry),
+        None => CircuitEntryPoint::EntryPoint,
+    };
+
+    let circuit = Interpreter::new(
+        true,
+        sources,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all(),
+        features,
+    )
+    .and_then(|mut interpreter| interpreter.circuit(entry, false, false));
+    let circuit = match circuit {
+        Ok(circuit) => circuit,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{:?}", Report::new(error));
+            }
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    match args.format {
+        CircuitFormat::Text => print!("{circuit}"),
+        CircuitFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&circuit).into_diagnostic()?
+        ),
+        CircuitFormat::Qasm => print!("{}", circuit.to_qasm().into_diagnostic()?),
+        CircuitFormat::Svg => print!("{}", circuit.to_svg()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The sources of a subcommand, along with the lint configuration and `@Config` flags of their
+/// project.
+struct LoadedSources {
+    sources: Vec<(SourceName, SourceContents)>,
+    lints: Vec<LintConfig>,
+    config: Vec<String>,
+}
+
+impl LoadedSources {
+    /// The source map of the sources, with the `@Config` flags of their project set.
+    fn into_source_map(self) -> SourceMap {
+        SourceMap::new(self.sources, None).with_config(self.config.into_iter().map(Into::into))
+    }
+}
+
+/// Reads the given source files, or loads the project at the given directory or manifest, or the
+/// project of the current directory when there are no inputs.
+fn load_sources(
+    inputs: &[PathBuf],
+    features: &mut LanguageFeatures,
+) -> miette::Result<LoadedSources> {
+    match inputs {
+        [] => load_project(None, features),
+        [path] if path.is_dir() || path.ends_with("qsharp.json") => {
+            load_project(Some(path.clone()), features)
+        }
+        paths => Ok(LoadedSources {
+            sources: paths
+                .iter()
+                .map(read_source)
+                .collect::<miette::Result<_>>()?,
+            lints: Vec::new(),
+            config: Vec::new(),
+        }),
+    }
+}
+
+fn load_project(
+    path: Option<PathBuf>,
+    features: &mut LanguageFeatures,
+) -> miette::Result<LoadedSources> {
+    let manifest = Manifest::load(path)?.ok_or_else(|| miette!("could not find a Q# project"))?;
+    let project = StdFs.load_project(&manifest)?;
+    features.merge(LanguageFeatures::from_iter(
+        manifest.manifest.language_features,
+    ));
+    Ok(LoadedSources {
+        sources: project.sources,
+        lints: manifest.manifest.lints,
+        config: manifest.manifest.config,
+    })
+}
+
+fn capabilities_text(
+    sources: &SourceMap,
+    profile: Profile,
+    callables: &[CallableRequirements],
+) -> String {
+    let mut text = format!("profile: {}\n", profile.to_str());
+    for callable in callables {
+        let (source, line, column) = location(sources, callable.span.lo);
+        let _ = writeln!(
+            text,
+            "{} ({source}:{line}:{column}): {}",
+            callable.name,
+            flag_names(callable.capabilities).join(" | ")
+        );
+        for construct in &callable.unsupported {
+            let (source, line, column) = location(sources, construct.span.lo);
+            let _ = writeln!(
+                text,
+                "    {source}:{line}:{column}: unsupported {}",
+                flag_names(construct.capabilities).join(" | ")
+            );
+        }
+    }
+    text
+}
+
+fn capabilities_json(
+    sources: &SourceMap,
+    profile: Profile,
+    callables: &[CallableRequirements],
+) -> serde_json::Value {
+    let callables = callables
+        .iter()
+        .map(|callable| {
+            let (source, line, column) = location(sources, callable.span.lo);
+            let unsupported = callable
+                .unsupported
+                .iter()
+                .map(|construct| {
+                    let (source, line, column) = location(sources, construct.span.lo);
+                    json!({
+                        "source": source,
+                        "line": line,
+                        "column": column,
+                        "capabilities": flag_names(construct.capabilities),
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "name": &*callable.name,
+                "source": source,
+                "line": line,
+                "column": column,
+                "capabilities": flag_names(callable.capabilities),
+                "unsupported": unsupported,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "profile": profile.to_str(),
+        "callables": callables,
+    })
+}
+
+fn call_graph_json(graph: &CallGraph) -> serde_json::Value {
+    let nodes = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            json!({
+                "name": &*node.name,
+                "package": usize::from(node.package),
+                "span": json!({ "lo": node.span.lo, "hi": node.span.hi }),
+                "capabilities": flag_names(node.capabilities),
+                "introduced": flag_names(node.introduced),
+                "runtimeFeatures": node
+                    .runtime_features
+                    .iter_names()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+    let edges = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            json!({
+                "caller": edge.caller,
+                "callee": edge.callee,
+                "spans": edge
+                    .spans
+                    .iter()
+                    .map(|span| json!({ "lo": span.lo, "hi": span.hi }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "nodes": nodes,
+        "edges": edges,
+    })
+}
+
+/// The name of the source an offset is in, along with the one-based line and column of the offset.
+fn location(sources: &SourceMap, offset: u32) -> (&str, u32, u32) {
+    let source = sources
+        .find_by_offset(offset)
+        .expect("offset should be in a source");
+    let position =
+        Position::from_utf8_byte_offset(Encoding::Utf8, &source.contents, offset - source.offset);
+    (source.name.as_ref(), position.line + 1, position.column + 1)
+}
+
+fn flag_names(flags: RuntimeCapabilityFlags) -> Vec<&'static str> {
+    if flags.is_empty() {
+        vec!["none"]
+    } else {
+        flags.iter_names().map(|(name, _)| name).collect()
+    }
+}
+
 fn read_source(path: impl AsRef<Path>) -> miette::Result<(SourceName, SourceContents)> {
     let path = path.as_ref();
     if path.as_os_str() == "-" {
@@ -172,6 +775,23 @@ fn emit_hir(package: &Package, dir: impl AsRef<Path>) -> miette::Result<()> {
         .with_context(|| format!("could not emit HIR file `{}`", path.display()))
 }
 
+fn emit_capabilities(
+    out_dir: &Path,
+    store: &PackageStore,
+    package_id: PackageId,
+) -> miette::Result<()> {
+    let path = out_dir.join("capabilities.txt");
+    let summary = qsc::hints::get_library_summary(store, package_id);
+    info!(
+        "Writing capabilities output file to: {}",
+        path.to_str().unwrap_or_default()
+    );
+    let profile = qsc::target::Profile::closest(summary.capabilities);
+    fs::write(&path, format!("profile: {}\n{summary}", profile.to_str()))
+        .into_diagnostic()
+        .with_context(|| format!("could not emit capabilities file `{}`", path.display()))
+}
+
 fn emit_qir(out_dir: &Path, store: &PackageStore, package_id: PackageId) -> Result<(), Report> {
     let path = out_dir.join("qir.ll");
     let result = qir_base::generate_qir(store, package_id);
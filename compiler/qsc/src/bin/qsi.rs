@@ -52,6 +52,10 @@ struct Cli {
     /// Language features to compile with
     #[arg(short, long)]
     features: Vec<String>,
+
+    /// Flags to set for `@Config` attributes, in addition to the ones in the project manifest
+    #[arg(long)]
+    config: Vec<String>,
 }
 
 struct TerminalReceiver;
@@ -71,6 +75,19 @@ impl Receiver for TerminalReceiver {
         Ok(())
     }
 
+    fn matrix(&mut self, matrix: Vec<Vec<Complex64>>) -> Result<(), output::Error> {
+        println!("Matrix:");
+        for row in matrix {
+            let row = row
+                .iter()
+                .map(|entry| format!("[{}, {}]", entry.re, entry.im))
+                .collect::<Vec<_>>();
+            println!("{}", row.join(" "));
+        }
+
+        Ok(())
+    }
+
     fn message(&mut self, msg: &str) -> Result<(), output::Error> {
         println!("{msg}");
         Ok(())
@@ -86,6 +103,7 @@ fn main() -> miette::Result<ExitCode> {
         .collect::<miette::Result<Vec<_>>>()?;
 
     let mut features = LanguageFeatures::from_iter(cli.features);
+    let mut config = cli.config;
 
     if sources.is_empty() {
         let fs = StdFs;
@@ -99,12 +117,14 @@ fn main() -> miette::Result<ExitCode> {
             features.merge(LanguageFeatures::from_iter(
                 manifest.manifest.language_features,
             ));
+            config.extend(manifest.manifest.config);
         }
     }
     if cli.exec {
         let mut interpreter = match Interpreter::new(
             !cli.nostdlib,
-            SourceMap::new(sources, cli.entry.map(std::convert::Into::into)),
+            SourceMap::new(sources, cli.entry.map(std::convert::Into::into))
+                .with_config(config.into_iter().map(Into::into)),
             PackageType::Exe,
             RuntimeCapabilityFlags::all(),
             features,
@@ -124,7 +144,7 @@ fn main() -> miette::Result<ExitCode> {
 
     let mut interpreter = match Interpreter::new(
         !cli.nostdlib,
-        SourceMap::new(sources, None),
+        SourceMap::new(sources, None).with_config(config.into_iter().map(Into::into)),
         PackageType::Lib,
         RuntimeCapabilityFlags::all(),
         features,
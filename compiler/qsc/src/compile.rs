@@ -1,14 +1,20 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+#[cfg(test)]
+mod tests;
+
 use miette::{Diagnostic, Report};
 use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_eval::debug::map_hir_package_to_fir;
 use qsc_frontend::{
-    compile::{CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceMap},
+    compile::{CompileUnit, PackageStore, ParseCache, RuntimeCapabilityFlags, SourceMap},
     error::WithSource,
 };
-use qsc_hir::hir::PackageId;
-use qsc_passes::{run_core_passes, run_default_passes, PackageType};
+use qsc_hir::hir::{self, PackageId};
+use qsc_passes::{run_core_passes, run_default_passes, PackageType, PassContext};
+use qsc_rca::Analyzer;
+use std::{cell::RefCell, rc::Rc};
 use thiserror::Error;
 
 pub type Error = WithSource<ErrorKind>;
@@ -31,6 +37,20 @@ pub enum ErrorKind {
     /// `Lint` variant represents lints generated during the linting stage. These diagnostics are
     /// typically emited from the language server and happens after all other compilation passes.
     Lint(#[from] qsc_linter::Lint),
+
+    /// `Capability` variant represents warnings for constructs that need runtime capabilities the target doesn't
+    /// support. These diagnostics come from the runtime capabilities analysis, which the language server runs on
+    /// packages that target restricted profiles.
+    Capability(#[from] qsc_rca::UnsupportedConstruct),
+
+    /// `CallCycle` variant represents warnings for operations that call each other in a cycle, which need runtime
+    /// capabilities the target doesn't support. Like `Capability`, these come from the runtime capabilities analysis.
+    CallCycle(#[from] qsc_rca::CallCycle),
+
+    /// `TargetProfile` variant represents errors for constructs in callables with the `@TargetProfile` attribute that
+    /// need runtime capabilities the declared profile doesn't support. These come from the runtime capabilities
+    /// analysis, which runs after the passes whenever a callable declares its profile.
+    TargetProfile(#[from] qsc_rca::ProfileViolation),
 }
 
 #[must_use]
@@ -42,34 +62,196 @@ pub fn compile(
     capabilities: RuntimeCapabilityFlags,
     language_features: LanguageFeatures,
 ) -> (CompileUnit, Vec<Error>) {
-    let mut unit = qsc_frontend::compile::compile(
+    compile_with_passes(
+        store,
+        dependencies,
+        sources,
+        package_type,
+        capabilities,
+        language_features,
+        &mut PassContext::new(capabilities),
+    )
+}
+
+/// Compiles the sources like [`compile`], running the default passes with the given pass context
+/// so that callers can opt into passes such as dead code elimination.
+#[must_use]
+pub fn compile_with_passes(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    sources: SourceMap,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+    passes: &mut PassContext,
+) -> (CompileUnit, Vec<Error>) {
+    let unit = qsc_frontend::compile::compile(
+        store,
+        dependencies,
+        sources,
+        capabilities,
+        language_features,
+    );
+    run_passes(store, unit, package_type, passes)
+}
+
+/// Compiles the sources like [`compile`], parsing them starting from the ASTs in the cache so that
+/// a source that was edited since the last compilation only has the item around the edit parsed
+/// again. Statements outside of namespaces are only accepted when `top_level_stmts` is set.
+#[must_use]
+#[allow(clippy::too_many_arguments)]
+pub fn compile_with_parse_cache(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    sources: SourceMap,
+    parse_cache: &mut ParseCache,
+    top_level_stmts: bool,
+    package_type: PackageType,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+) -> (CompileUnit, Vec<Error>) {
+    let unit = qsc_frontend::compile::compile_with_parse_cache(
         store,
         dependencies,
         sources,
+        parse_cache,
+        top_level_stmts,
         capabilities,
         language_features,
     );
+    run_passes(
+        store,
+        unit,
+        package_type,
+        &mut PassContext::new(capabilities),
+    )
+}
+
+fn run_passes(
+    store: &PackageStore,
+    mut unit: CompileUnit,
+    package_type: PackageType,
+    passes: &mut PassContext,
+) -> (CompileUnit, Vec<Error>) {
     let mut errors = Vec::new();
     for error in unit.errors.drain(..) {
         errors.push(WithSource::from_map(&unit.sources, error.into()));
     }
 
     if errors.is_empty() {
-        for error in run_default_passes(store.core(), &mut unit, package_type, capabilities) {
+        for error in passes.run_default_passes(
+            &mut unit.package,
+            &mut unit.assigner,
+            store.core(),
+            package_type,
+        ) {
             errors.push(WithSource::from_map(&unit.sources, error.into()));
         }
     }
 
+    if errors.is_empty() {
+        errors = check_target_profiles(store, &unit);
+    }
+
     (unit, errors)
 }
 
-/// Compiles the core library.
+/// Checks the callables that declare the profile they run on with the `@TargetProfile` attribute against the runtime
+/// capabilities analysis. The analysis needs every package lowered, so it only runs when the attribute is used.
+fn check_target_profiles(store: &PackageStore, unit: &CompileUnit) -> Vec<Error> {
+    let declares_profile = unit.package.items.values().any(|item| {
+        item.attrs
+            .iter()
+            .any(|attr| matches!(attr, hir::Attr::TargetProfile(_)))
+    });
+    if !declares_profile {
+        return Vec::new();
+    }
+
+    let mut fir_store = crate::hints::lower(store);
+    // The unit isn't in the store yet, so it is lowered under an id that no package in the store has.
+    let package_id = map_hir_package_to_fir(PackageId::from(
+        store
+            .iter()
+            .map(|(id, _)| usize::from(id) + 1)
+            .max()
+            .unwrap_or_default(),
+    ));
+    fir_store.insert(
+        package_id,
+        qsc_eval::lower::Lowerer::new().lower_package(&unit.package),
+    );
+    let compute_properties = Analyzer::init(&fir_store).analyze_all();
+    qsc_rca::profile_violations(&fir_store, &compute_properties, package_id)
+        .into_iter()
+        .map(|violation| WithSource::from_map(&unit.sources, violation.into()))
+        .collect()
+}
+
+/// The standard library that sources are compiled against, alongside the core library.
+#[derive(Clone, Debug, Default)]
+pub enum StdLib {
+    /// The standard library that ships with the compiler.
+    #[default]
+    Builtin,
+    /// No standard library, which makes for faster startup when only the core library is needed.
+    None,
+    /// A standard library compiled from the given sources in place of the builtin one.
+    Custom(SourceMap),
+}
+
+impl From<bool> for StdLib {
+    fn from(include_std: bool) -> Self {
+        if include_std {
+            Self::Builtin
+        } else {
+            Self::None
+        }
+    }
+}
+
+thread_local! {
+    /// The libraries compiled so far on this thread. Compiling the core and standard libraries
+    /// from source takes up most of the time it takes to create a compiler or interpreter, so
+    /// later ones share the compiled packages instead.
+    static LIBRARIES: RefCell<Libraries> = RefCell::default();
+}
+
+/// The most standard libraries kept on a thread. There is one for each combination of core
+/// library and capabilities in use, and the one used least recently is dropped first.
+const MAX_CACHED_STD_LIBS: usize = 4;
+
+#[derive(Default)]
+struct Libraries {
+    core: Option<Rc<CompileUnit>>,
+    /// The standard libraries along with the core library and capabilities they were compiled
+    /// with, ordered from least to most recently used.
+    std: Vec<(Rc<CompileUnit>, RuntimeCapabilityFlags, Rc<CompileUnit>)>,
+}
+
+/// Compiles the core library, or shares it if it was already compiled on this thread.
 ///
 /// # Panics
 ///
 /// Panics if the core library does not compile without errors.
 #[must_use]
-pub fn core() -> CompileUnit {
+pub fn core() -> Rc<CompileUnit> {
+    LIBRARIES.with(|libraries| {
+        libraries
+            .borrow_mut()
+            .core
+            .get_or_insert_with(|| Rc::new(compile_core()))
+            .clone()
+    })
+}
+
+/// Drops the libraries compiled on this thread, so that the next ones are compiled from source.
+/// Stores that the libraries were inserted into keep them.
+pub fn clear_library_cache() {
+    LIBRARIES.with(|libraries| *libraries.borrow_mut() = Libraries::default());
+}
+
+fn compile_core() -> CompileUnit {
     let mut unit = qsc_frontend::compile::core();
     let pass_errors = run_core_passes(&mut unit);
     if pass_errors.is_empty() {
@@ -84,13 +266,42 @@ pub fn core() -> CompileUnit {
     }
 }
 
-/// Compiles the standard library.
+/// Compiles the standard library against the core library in the store, or shares it if it was
+/// already compiled on this thread against the same core library for the same capabilities.
 ///
 /// # Panics
 ///
 /// Panics if the standard library does not compile without errors.
 #[must_use]
-pub fn std(store: &PackageStore, capabilities: RuntimeCapabilityFlags) -> CompileUnit {
+pub fn std(store: &PackageStore, capabilities: RuntimeCapabilityFlags) -> Rc<CompileUnit> {
+    let core = store
+        .get_shared(PackageId::CORE)
+        .expect("store should contain the core library");
+    LIBRARIES.with(|libraries| {
+        let mut libraries = libraries.borrow_mut();
+        if let Some(index) = libraries
+            .std
+            .iter()
+            .position(|(std_core, std_capabilities, _)| {
+                Rc::ptr_eq(std_core, &core) && *std_capabilities == capabilities
+            })
+        {
+            let entry = libraries.std.remove(index);
+            let unit = entry.2.clone();
+            libraries.std.push(entry);
+            return unit;
+        }
+
+        let unit = Rc::new(compile_std(store, capabilities));
+        if libraries.std.len() == MAX_CACHED_STD_LIBS {
+            libraries.std.remove(0);
+        }
+        libraries.std.push((core, capabilities, unit.clone()));
+        unit
+    })
+}
+
+fn compile_std(store: &PackageStore, capabilities: RuntimeCapabilityFlags) -> CompileUnit {
     let mut unit = qsc_frontend::compile::std(store, capabilities);
     let pass_errors = run_default_passes(store.core(), &mut unit, PackageType::Lib, capabilities);
     if pass_errors.is_empty() {
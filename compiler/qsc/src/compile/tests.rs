@@ -0,0 +1,41 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{clear_library_cache, compile_core, core};
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags};
+use std::rc::Rc;
+
+#[test]
+fn libraries_are_shared_between_stores() {
+    clear_library_cache();
+    let store = PackageStore::new(core());
+    let other_store = PackageStore::new(core());
+    let std_lib = super::std(&store, RuntimeCapabilityFlags::all());
+    assert!(Rc::ptr_eq(
+        &std_lib,
+        &super::std(&other_store, RuntimeCapabilityFlags::all())
+    ));
+    assert!(!Rc::ptr_eq(
+        &std_lib,
+        &super::std(&store, RuntimeCapabilityFlags::empty())
+    ));
+}
+
+#[test]
+fn std_compiled_against_other_core_is_not_shared() {
+    clear_library_cache();
+    let std_lib = super::std(&PackageStore::new(core()), RuntimeCapabilityFlags::all());
+    let other_store = PackageStore::new(compile_core());
+    assert!(!Rc::ptr_eq(
+        &std_lib,
+        &super::std(&other_store, RuntimeCapabilityFlags::all())
+    ));
+}
+
+#[test]
+fn cleared_libraries_are_compiled_again() {
+    clear_library_cache();
+    let core_lib = core();
+    clear_library_cache();
+    assert!(!Rc::ptr_eq(&core_lib, &core()));
+}
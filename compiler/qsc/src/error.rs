@@ -1,8 +1,13 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+use crate::location::Location;
 use miette::Diagnostic;
-use qsc_frontend::compile::PackageStore;
+use qsc_data_structures::{
+    line_column::{Encoding, Range},
+    span::Span,
+};
+use qsc_frontend::compile::{SourceContents, SourceMap, SourceName};
 use std::fmt::{self, Debug, Display, Formatter};
 use thiserror::Error;
 
@@ -12,28 +17,96 @@ pub use qsc_frontend::error::WithSource;
 pub struct WithStack<E> {
     error: E,
     stack_trace: Option<String>,
+    stack_frames: Vec<CallFrame>,
 }
 
 impl<E> WithStack<E> {
-    pub(super) fn new(error: E, stack_trace: Option<String>) -> Self {
-        WithStack { error, stack_trace }
+    pub(super) fn new(error: E, stack_trace: Option<String>, stack_frames: Vec<CallFrame>) -> Self {
+        WithStack {
+            error,
+            stack_trace,
+            stack_frames,
+        }
     }
 
     pub(super) fn stack_trace(&self) -> &Option<String> {
         &self.stack_trace
     }
 
+    pub(super) fn stack_frames(&self) -> &[CallFrame] {
+        &self.stack_frames
+    }
+
     pub fn error(&self) -> &E {
         &self.error
     }
 }
 
+impl<E: Diagnostic + Send + Sync> WithStack<WithSource<E>> {
+    /// Sets how the columns of the rendered error are counted.
+    #[must_use]
+    pub fn with_position_encoding(self, position_encoding: Encoding) -> Self {
+        WithStack {
+            error: self.error.with_position_encoding(position_encoding),
+            stack_trace: self.stack_trace,
+            stack_frames: self.stack_frames,
+        }
+    }
+}
+
 impl<E: Display> Display for WithStack<E> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         std::fmt::Display::fmt(&self.error, f)
     }
 }
 
+/// A frame of the Q# call stack at the point where a runtime error occurred.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallFrame {
+    name: String,
+    functor: String,
+    call_site: Option<(SourceName, SourceContents, Span)>,
+}
+
+impl CallFrame {
+    pub(super) fn new(
+        name: String,
+        functor: String,
+        call_site: Option<(SourceName, SourceContents, Span)>,
+    ) -> Self {
+        CallFrame {
+            name,
+            functor,
+            call_site,
+        }
+    }
+
+    /// The name of the called callable, qualified by its namespace.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The functors the callable was called with, such as `Adjoint`, or an empty string for its
+    /// body.
+    #[must_use]
+    pub fn functor(&self) -> &str {
+        &self.functor
+    }
+
+    /// The location of the call, or `None` if the call has no source, such as the calls that
+    /// release qubits at the end of their scope.
+    #[must_use]
+    pub fn location(&self, position_encoding: Encoding) -> Option<Location> {
+        self.call_site
+            .as_ref()
+            .map(|(source, contents, span)| Location {
+                source: source.clone(),
+                range: Range::from_span(position_encoding, contents, span),
+            })
+    }
+}
+
 // #[diagnostic(transparent)] does not seem to work with generics
 impl<E: Diagnostic> Diagnostic for WithStack<E> {
     fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
@@ -71,15 +144,13 @@ impl<E: Diagnostic> Diagnostic for WithStack<E> {
 
 pub(super) fn from_eval(
     error: qsc_eval::Error,
-    store: &PackageStore,
+    sources: &SourceMap,
     stack_trace: Option<String>,
+    stack_frames: Vec<CallFrame>,
 ) -> WithStack<WithSource<qsc_eval::Error>> {
-    let span = error.span();
-
-    let sources = &store
-        .get(span.package)
-        .expect("expected to find package id in store")
-        .sources;
-
-    WithStack::new(WithSource::from_map(sources, error), stack_trace)
+    WithStack::new(
+        WithSource::from_map(sources, error),
+        stack_trace,
+        stack_frames,
+    )
 }
@@ -0,0 +1,285 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Queries over the runtime capabilities analysis of compiled packages.
+//!
+//! The packages are lowered for the analysis, so every package in the store must have compiled without errors that
+//! prevent it, see `is_analyzable`. The `*_for_sources` functions compile the sources as a library that depends on
+//! the standard library, without restricting the runtime capabilities, so that the results show what the sources need
+//! instead of errors.
+
+#[cfg(test)]
+mod tests;
+
+pub use qsc_rca::{
+    CallCycle, CallGraph, CallGraphEdge, CallGraphNode, CallableRequirements, CallableSummary,
+    ComputeInfo, Hint, HintKind, LibrarySummary, RuntimeFeatureFlags, UnsupportedConstruct,
+};
+
+use crate::{
+    compile::{self, Error, ErrorKind},
+    target::Profile,
+};
+use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
+use qsc_eval::{debug::map_hir_package_to_fir, lower::Lowerer};
+use qsc_fir::fir;
+use qsc_frontend::compile::{PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::PackageId;
+use qsc_passes::PackageType;
+use qsc_rca::{Analyzer, PackageStoreComputeProperties};
+
+/// The runtime capabilities analysis of the packages of a store. Lowering and analyzing the packages is the expensive
+/// part of the queries in this module, so callers that answer many of them for the same packages, such as the
+/// language service, keep the analysis around instead of calling the free functions.
+#[derive(Debug)]
+pub struct Analysis {
+    fir_store: fir::PackageStore,
+    compute_properties: PackageStoreComputeProperties,
+}
+
+impl Analysis {
+    /// Lowers and analyzes every package in the store.
+    #[must_use]
+    pub fn new(package_store: &PackageStore) -> Self {
+        let fir_store = lower(package_store);
+        let compute_properties = Analyzer::init(&fir_store).analyze_all();
+        Self {
+            fir_store,
+            compute_properties,
+        }
+    }
+
+    /// Produces hints with the analysis results for the bindings and calls of the given package.
+    /// The spans of the hints are relative to the sources of that package.
+    #[must_use]
+    pub fn hints(&self, package_id: PackageId) -> Vec<Hint> {
+        qsc_rca::hints(
+            &self.fir_store,
+            &self.compute_properties,
+            map_hir_package_to_fir(package_id),
+        )
+    }
+
+    /// Produces the runtime capabilities needed by each callable of the given package, along with the constructs that
+    /// need capabilities beyond `target`. The spans are relative to the sources of that package.
+    #[must_use]
+    pub fn requirements(
+        &self,
+        package_id: PackageId,
+        target: RuntimeCapabilityFlags,
+    ) -> Vec<CallableRequirements> {
+        qsc_rca::requirements(
+            &self.fir_store,
+            &self.compute_properties,
+            map_hir_package_to_fir(package_id),
+            target,
+        )
+    }
+
+    /// Determines which predefined profiles the given package can run on, answering with the least capable one.
+    #[must_use]
+    pub fn profile_fit(&self, package_id: PackageId) -> ProfileFit {
+        let required = qsc_rca::package_capabilities(
+            &self.fir_store,
+            &self.compute_properties,
+            map_hir_package_to_fir(package_id),
+        );
+        let profile = Profile::closest(required);
+        ProfileFit {
+            required,
+            profile,
+            unused: RuntimeCapabilityFlags::from(profile) - required,
+        }
+    }
+
+    /// Summarizes the runtime capabilities needed by the public callables of the given library package.
+    #[must_use]
+    pub fn library_summary(&self, package_id: PackageId) -> LibrarySummary {
+        qsc_rca::library_summary(
+            &self.fir_store,
+            &self.compute_properties,
+            map_hir_package_to_fir(package_id),
+        )
+    }
+
+    /// Produces the call graph of the callables of the given package, annotated with the runtime capabilities each of
+    /// them needs.
+    #[must_use]
+    pub fn call_graph(&self, package_id: PackageId) -> CallGraph {
+        qsc_rca::call_graph(
+            &self.fir_store,
+            &self.compute_properties,
+            map_hir_package_to_fir(package_id),
+        )
+    }
+
+    /// Produces the analysis results for the innermost expression or statement of the given package that covers
+    /// `span`, which is relative to the sources of that package.
+    #[must_use]
+    pub fn compute_info(&self, package_id: PackageId, span: Span) -> Option<ComputeInfo> {
+        qsc_rca::compute_info(
+            &self.fir_store,
+            &self.compute_properties,
+            map_hir_package_to_fir(package_id),
+            span,
+        )
+    }
+
+    /// Produces the call cycles among the callables of the given package. The spans are relative to the sources of
+    /// that package.
+    #[must_use]
+    pub fn call_cycles(&self, package_id: PackageId) -> Vec<CallCycle> {
+        qsc_rca::call_cycles(&self.fir_store, map_hir_package_to_fir(package_id))
+    }
+}
+
+/// Produces hints with the runtime capabilities analysis results for the bindings and calls of the given package.
+/// The spans of the hints are relative to the sources of that package.
+#[must_use]
+pub fn get_hints(package_store: &PackageStore, package_id: PackageId) -> Vec<Hint> {
+    Analysis::new(package_store).hints(package_id)
+}
+
+/// Produces the runtime capabilities needed by each callable of the given package, along with the constructs that
+/// need capabilities beyond `target`. The spans are relative to the sources of that package.
+#[must_use]
+pub fn get_requirements(
+    package_store: &PackageStore,
+    package_id: PackageId,
+    target: RuntimeCapabilityFlags,
+) -> Vec<CallableRequirements> {
+    Analysis::new(package_store).requirements(package_id, target)
+}
+
+/// The runtime capabilities a package needs, and the predefined profile that comes closest to them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProfileFit {
+    /// The minimal runtime capabilities needed to run the package.
+    pub required: RuntimeCapabilityFlags,
+    /// The least capable predefined profile that supports the required capabilities.
+    pub profile: Profile,
+    /// The capabilities of the profile that the package doesn't need.
+    pub unused: RuntimeCapabilityFlags,
+}
+
+/// Determines which predefined profiles the given package can run on, answering with the least capable one.
+#[must_use]
+pub fn get_profile_fit(package_store: &PackageStore, package_id: PackageId) -> ProfileFit {
+    Analysis::new(package_store).profile_fit(package_id)
+}
+
+/// Summarizes the runtime capabilities needed by the public callables of the given library package, so that library
+/// authors can document and check the targets the library supports.
+#[must_use]
+pub fn get_library_summary(package_store: &PackageStore, package_id: PackageId) -> LibrarySummary {
+    Analysis::new(package_store).library_summary(package_id)
+}
+
+/// Produces the call graph of the callables of the given package, annotated with the runtime capabilities each of them
+/// needs.
+#[must_use]
+pub fn get_call_graph(package_store: &PackageStore, package_id: PackageId) -> CallGraph {
+    Analysis::new(package_store).call_graph(package_id)
+}
+
+pub(crate) fn lower(package_store: &PackageStore) -> fir::PackageStore {
+    let mut lowerer = Lowerer::new();
+    let mut fir_store = fir::PackageStore::new();
+    for (id, unit) in package_store {
+        fir_store.insert(
+            map_hir_package_to_fir(id),
+            lowerer.lower_package(&unit.package),
+        );
+    }
+    fir_store
+}
+
+/// Compiles the sources and produces their hints.
+///
+/// # Errors
+///
+/// Returns the compilation errors if the sources can't be analyzed.
+pub fn get_hints_for_sources(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+) -> Result<Vec<Hint>, Vec<Error>> {
+    let (store, package_id) = compile_for_analysis(sources, language_features)?;
+    Ok(get_hints(&store, package_id))
+}
+
+/// Compiles the sources and produces the requirements of their callables, reporting the constructs that need
+/// capabilities beyond `target`.
+///
+/// # Errors
+///
+/// Returns the compilation errors if the sources can't be analyzed.
+pub fn get_requirements_for_sources(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+    target: RuntimeCapabilityFlags,
+) -> Result<Vec<CallableRequirements>, Vec<Error>> {
+    let (store, package_id) = compile_for_analysis(sources, language_features)?;
+    Ok(get_requirements(&store, package_id, target))
+}
+
+/// Compiles the sources and determines the least capable predefined profile they can run on.
+///
+/// # Errors
+///
+/// Returns the compilation errors if the sources can't be analyzed.
+pub fn get_profile_fit_for_sources(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+) -> Result<ProfileFit, Vec<Error>> {
+    let (store, package_id) = compile_for_analysis(sources, language_features)?;
+    Ok(get_profile_fit(&store, package_id))
+}
+
+/// Compiles the sources and produces the call graph of their callables.
+///
+/// # Errors
+///
+/// Returns the compilation errors if the sources can't be analyzed.
+pub fn get_call_graph_for_sources(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+) -> Result<CallGraph, Vec<Error>> {
+    let (store, package_id) = compile_for_analysis(sources, language_features)?;
+    Ok(get_call_graph(&store, package_id))
+}
+
+fn compile_for_analysis(
+    sources: SourceMap,
+    language_features: LanguageFeatures,
+) -> Result<(PackageStore, PackageId), Vec<Error>> {
+    let capabilities = RuntimeCapabilityFlags::all();
+    let mut store = PackageStore::new(compile::core());
+    let std_id = store.insert(compile::std(&store, capabilities));
+    let (unit, errors) = compile::compile(
+        &store,
+        &[std_id],
+        sources,
+        PackageType::Lib,
+        capabilities,
+        language_features,
+    );
+    if !is_analyzable(&errors) {
+        return Err(errors);
+    }
+
+    let package_id = store.insert(unit);
+    Ok((store, package_id))
+}
+
+/// Whether a package that compiled with the given errors can be analyzed for hints. Lints, and pass errors that don't
+/// leave constructs behind that can't be lowered, such as a missing entry point, don't prevent the analysis.
+#[must_use]
+pub fn is_analyzable(errors: &[Error]) -> bool {
+    errors.iter().all(|error| {
+        !matches!(
+            error.error(),
+            ErrorKind::Frontend(_)
+                | ErrorKind::Pass(qsc_passes::Error::ConjInvert(_) | qsc_passes::Error::SpecGen(_))
+        )
+    })
+}
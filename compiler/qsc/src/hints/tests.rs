@@ -0,0 +1,68 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::get_profile_fit_for_sources;
+use crate::{LanguageFeatures, SourceMap};
+use expect_test::{expect, Expect};
+
+fn check_profile_fit(source: &str, expect: &Expect) {
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let fit = get_profile_fit_for_sources(sources, LanguageFeatures::default())
+        .expect("sources should compile");
+    expect.assert_debug_eq(&fit);
+}
+
+#[test]
+fn static_program_fits_base() {
+    check_profile_fit(
+        "namespace Test {
+            operation Main() : Result {
+                use q = Qubit();
+                H(q);
+                MResetZ(q)
+            }
+        }",
+        &expect![[r#"
+            ProfileFit {
+                required: RuntimeCapabilityFlags(
+                    0x0,
+                ),
+                profile: Base,
+                unused: RuntimeCapabilityFlags(
+                    0x0,
+                ),
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn dynamic_program_needs_more_than_base() {
+    check_profile_fit(
+        "namespace Test {
+            operation Main() : Int {
+                use q = Qubit();
+                mutable count = 0;
+                for _ in 1..10 {
+                    if MResetZ(q) == One {
+                        set count += 1;
+                    }
+                }
+                count
+            }
+        }",
+        &expect![[r#"
+            ProfileFit {
+                required: RuntimeCapabilityFlags(
+                    ForwardBranching | IntegerComputations,
+                ),
+                profile: Unrestricted,
+                unused: RuntimeCapabilityFlags(
+                    FloatingPointComputations | BackwardsBranching | HigherLevelConstructs,
+                ),
+            }
+        "#]],
+    );
+}
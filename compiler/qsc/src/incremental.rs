@@ -1,7 +1,8 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::compile::{self, compile, core, std};
+use crate::compile::{self, compile_with_passes, core, std, StdLib};
+use ::std::sync::Arc;
 use miette::Diagnostic;
 use qsc_data_structures::language_features::LanguageFeatures;
 use qsc_frontend::{
@@ -10,7 +11,7 @@ use qsc_frontend::{
     incremental::Increment,
 };
 use qsc_hir::hir::PackageId;
-use qsc_passes::{PackageType, PassContext};
+use qsc_passes::{PackageType, PassConfig, PassContext};
 
 /// An incremental Q# compiler.
 pub struct Compiler {
@@ -24,6 +25,12 @@ pub struct Compiler {
     passes: PassContext,
     /// The frontend incremental compiler.
     frontend: qsc_frontend::incremental::Compiler,
+    /// The packages that fragments can refer to, along with the settings they are compiled with,
+    /// kept so that the compiler can be forked.
+    dependencies: Vec<PackageId>,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+    config: Vec<Arc<str>>,
 }
 
 /// An incremental compiler error.
@@ -40,22 +47,68 @@ impl Compiler {
         capabilities: RuntimeCapabilityFlags,
         language_features: LanguageFeatures,
     ) -> Result<Self, Errors> {
+        Self::new_with_passes(
+            include_std.into(),
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+            PassConfig::default(),
+        )
+    }
+
+    /// Creates a new incremental compiler like [`Compiler::new`], compiling the sources against
+    /// the given standard library and running the optional passes enabled in the given
+    /// configuration on the sources and on the fragments and entry expressions compiled later.
+    /// # Errors
+    /// If compiling the standard library or the sources fails, compiler errors are returned.
+    pub fn new_with_passes(
+        std_lib: StdLib,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: RuntimeCapabilityFlags,
+        language_features: LanguageFeatures,
+        passes: PassConfig,
+    ) -> Result<Self, Errors> {
+        let pass_context = || PassContext::with_config(capabilities, passes);
         let core = core();
         let mut store = PackageStore::new(core);
         let mut dependencies = Vec::new();
-        if include_std {
-            let std = std(&store, capabilities);
-            let id = store.insert(std);
-            dependencies.push(id);
+        match std_lib {
+            StdLib::Builtin => {
+                let std = std(&store, capabilities);
+                let id = store.insert(std);
+                dependencies.push(id);
+            }
+            StdLib::None => {}
+            StdLib::Custom(std_sources) => {
+                let (std, errors) = compile_with_passes(
+                    &store,
+                    &[PackageId::CORE],
+                    std_sources,
+                    PackageType::Lib,
+                    capabilities,
+                    language_features,
+                    &mut pass_context(),
+                );
+                if !errors.is_empty() {
+                    return Err(errors);
+                }
+                let id = store.insert(std);
+                dependencies.push(id);
+            }
         }
 
-        let (unit, errors) = compile(
+        // Fragments are compiled with the same flags as the sources.
+        let config = sources.config().to_vec();
+        let (unit, errors) = compile_with_passes(
             &store,
             &dependencies,
             sources,
             package_type,
             capabilities,
             language_features,
+            &mut pass_context(),
         );
         if !errors.is_empty() {
             return Err(errors);
@@ -66,20 +119,51 @@ impl Compiler {
 
         let frontend = qsc_frontend::incremental::Compiler::new(
             &store,
-            dependencies,
+            dependencies.iter().copied(),
             capabilities,
             language_features,
-        );
+        )
+        .with_config(config.iter().cloned());
         let store = store.open();
 
         Ok(Self {
             store,
             source_package_id,
             frontend,
-            passes: PassContext::new(capabilities),
+            passes: pass_context(),
+            dependencies,
+            capabilities,
+            language_features,
+            config,
         })
     }
 
+    /// Creates a new incremental compiler that shares the compiled libraries and sources of this
+    /// one, so nothing is compiled again. The fragments compiled by this compiler are not carried
+    /// over.
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        let store = self.store.share_dependencies();
+        let frontend = qsc_frontend::incremental::Compiler::new(
+            &store,
+            self.dependencies.iter().copied(),
+            self.capabilities,
+            self.language_features,
+        )
+        .with_config(self.config.iter().cloned());
+
+        Self {
+            store: store.open(),
+            source_package_id: self.source_package_id,
+            frontend,
+            passes: PassContext::with_config(self.capabilities, self.pass_config()),
+            dependencies: self.dependencies.clone(),
+            capabilities: self.capabilities,
+            language_features: self.language_features,
+            config: self.config.clone(),
+        }
+    }
+
     /// Compiles Q# fragments. Fragments are Q# code that can contain
     /// top-level statements as well as namespaces. A notebook cell
     /// or an interpreter entry is an example of fragments.
@@ -199,6 +283,12 @@ impl Compiler {
         self.source_package_id
     }
 
+    /// Returns the optional passes run on the sources, fragments and entry expressions.
+    #[must_use]
+    pub fn pass_config(&self) -> PassConfig {
+        self.passes.config()
+    }
+
     /// Consumes the incremental compiler and returns an immutable package store.
     /// This method can be used to finalize the compilation.
     #[must_use]
@@ -1,7 +1,17 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+mod benchmark;
+mod compilation_stats;
 mod debug;
+mod dump;
+mod dump_operation;
+mod entry_point;
+mod equivalence;
+mod replay;
+mod shots;
+mod statistics;
+mod test_runner;
 
 #[cfg(test)]
 mod tests;
@@ -12,39 +22,59 @@ mod debugger_tests;
 #[cfg(test)]
 mod circuit_tests;
 
-use std::rc::Rc;
+use std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+    rc::Rc,
+    sync::Arc,
+    time::Instant,
+};
 
 pub use qsc_eval::{
+    backend::{ForcedOutcomes, GateSet, IntrinsicCallback, SimulatorKind},
     debug::Frame,
     output::{self, GenericReceiver},
     val::Closure,
     val::Range as ValueRange,
     val::Result,
     val::Value,
-    StepAction, StepResult,
+    Limits, StepAction, StepResult,
 };
 
+pub use benchmark::{Benchmark, Stats};
+pub use compilation_stats::{PackageStats, RcaStats};
+pub use dump::DumpFormat;
+pub use equivalence::EquivalenceMethod;
+pub use qsc_rca::Advice;
+pub use replay::Recording;
+pub use shots::{MeasurementStatistics, ShotFailure, ShotRunner, Shots};
+pub use statistics::{AmplitudeSample, RunStatistics};
+pub use test_runner::TestResult;
+
 use crate::{
-    error::{self, WithStack},
+    compile::StdLib,
+    error::{self, CallFrame, WithStack},
     incremental::Compiler,
-    location::Location,
+    location::{Location, QSHARP_LIBRARY_URI_SCHEME},
 };
-use debug::format_call_stack;
+use benchmark::Profiler;
+use debug::{call_frames, format_call_stack, SourcesLookup};
 use miette::Diagnostic;
 use num_bigint::BigUint;
-use num_complex::Complex;
+use num_complex::{Complex, Complex64};
 use qsc_circuit::{
     operations::entry_expr_for_qubit_operation, Builder as CircuitBuilder, Circuit,
     Config as CircuitConfig,
 };
 use qsc_codegen::qir_base::BaseProfSim;
 use qsc_data_structures::{
+    index_map::IndexMap,
     language_features::LanguageFeatures,
     line_column::{Encoding, Range},
     span::Span,
 };
 use qsc_eval::{
-    backend::{Backend, Chain as BackendChain, SparseSim},
+    backend::{Backend, Chain as BackendChain, Decomposer, IntrinsicCallbacks, Simulator},
     debug::{map_fir_package_to_hir, map_hir_package_to_fir},
     output::Receiver,
     val, Env, State, VariableInfo,
@@ -55,11 +85,15 @@ use qsc_fir::{
     visit::{self, Visitor},
 };
 use qsc_frontend::{
-    compile::{CompileUnit, PackageStore, RuntimeCapabilityFlags, Source, SourceMap},
+    compile::{CompileUnit, RuntimeCapabilityFlags, Source, SourceMap},
     error::WithSource,
 };
-use qsc_passes::PackageType;
-use rustc_hash::FxHashSet;
+use qsc_partial_eval::partially_evaluate_callables;
+use qsc_passes::{PackageType, PassConfig};
+use qsc_rca::{advise, call_graph_of_packages, Analyzer, CallGraph, PackageStoreComputeProperties};
+use replay::Recorder;
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
+use statistics::Monitor;
 use thiserror::Error;
 
 impl Error {
@@ -70,6 +104,28 @@ impl Error {
             _ => &None,
         }
     }
+
+    /// The frames of the Q# call stack at the point where a runtime error occurred, innermost
+    /// first. Errors that don't occur at runtime, or occur outside of any callable, have none.
+    #[must_use]
+    pub fn stack_frames(&self) -> &[CallFrame] {
+        match &self {
+            Error::Eval(err) => err.stack_frames(),
+            _ => &[],
+        }
+    }
+
+    /// Sets how the columns of the rendered error are counted, which are utf-16 code units
+    /// by default.
+    #[must_use]
+    pub fn with_position_encoding(self, position_encoding: Encoding) -> Self {
+        match self {
+            Error::Compile(err) => Error::Compile(err.with_position_encoding(position_encoding)),
+            Error::Pass(err) => Error::Pass(err.with_position_encoding(position_encoding)),
+            Error::Eval(err) => Error::Eval(err.with_position_encoding(position_encoding)),
+            _ => self,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Diagnostic, Error)]
@@ -95,12 +151,48 @@ pub enum Error {
         "provide the name of a callable or a lambda expression that only takes qubits as parameters"
     ))]
     NoCircuitForOperation,
+    #[error("measurement {0} does not match the replayed recording")]
+    #[diagnostic(code("Qsc.Interpret.ReplayDiverged"))]
+    #[diagnostic(help(
+        "the program, its inputs or the standard library may have changed since the recording was made"
+    ))]
+    ReplayDiverged(usize),
+    #[error("operation measures qubits, so it has no unitary matrix")]
+    #[diagnostic(code("Qsc.Interpret.MeasuringOperation"))]
+    #[diagnostic(help(
+        "provide an operation that only applies unitary gates to the qubits it is given"
+    ))]
+    MeasuringOperation,
+    #[error("multiple entry points found: {0}")]
+    #[diagnostic(code("Qsc.Interpret.AmbiguousEntryPoint"))]
+    #[diagnostic(help("only one callable should be annotated with the entry point attribute"))]
+    AmbiguousEntryPoint(String),
+    #[error("entry point takes {0} arguments but {1} were given")]
+    #[diagnostic(code("Qsc.Interpret.EntryPointArgCount"))]
+    EntryPointArgCount(usize, usize),
+    #[error("argument `{0}` of the entry point should be of type `{1}`")]
+    #[diagnostic(code("Qsc.Interpret.EntryPointArgType"))]
+    EntryPointArgType(String, String),
+    #[error("shots can't run on other threads while intrinsic callbacks are registered")]
+    #[diagnostic(code("Qsc.Interpret.ShotsWithCallbacks"))]
+    #[diagnostic(help(
+        "the callbacks only exist on the thread that registered them, so run each shot on the interpreter instead"
+    ))]
+    ShotsWithCallbacks,
 }
 
 /// A Q# interpreter.
 pub struct Interpreter {
     /// The incremental Q# compiler.
     compiler: Compiler,
+    /// The standard library the sources were compiled against.
+    std: StdLib,
+    /// The initial sources passed in when creating the interpreter.
+    sources: SourceMap,
+    /// The package type of the initial sources.
+    package_type: PackageType,
+    /// The language features used for compilation.
+    language_features: LanguageFeatures,
     /// The runtime capabilities used for compilation.
     capabilities: RuntimeCapabilityFlags,
     /// The number of lines that have so far been compiled.
@@ -119,15 +211,42 @@ pub struct Interpreter {
     /// This ID is valid both for the FIR store and the `PackageStore`.
     source_package: PackageId,
     /// The default simulator backend.
-    sim: BackendChain<SparseSim, CircuitBuilder>,
+    sim: BackendChain<Simulator, CircuitBuilder>,
     /// The quantum seed, if any. This is cached here so that it can be used in calls to
     /// `run_internal` which use a passed instance of the simulator instead of the one above.
     quantum_seed: Option<u64>,
     /// The classical seed, if any. This needs to be passed to the evaluator for use in intrinsic
     /// calls that produce classical random numbers.
     classical_seed: Option<u64>,
+    /// The limits on the resources used by programs run by the interpreter.
+    limits: Limits,
+    /// The kind of simulator that programs run on.
+    simulator: SimulatorKind,
+    /// The measurement outcomes forced on the simulators of runs started from now on.
+    forced_outcomes: ForcedOutcomes,
+    /// The callbacks that simulate custom intrinsics.
+    intrinsics: IntrinsicCallbacks,
+    /// The resources the simulator used in the most recent run.
+    last_run_statistics: Option<RunStatistics>,
     /// The evaluator environment.
     env: Env,
+    /// A copy of the source package with the classical fragments of its callables evaluated at compile time, which
+    /// QIR is generated from. The source package that is run and debugged keeps its statements.
+    folded_source_package: Option<fir::Package>,
+    /// The compute properties the callables were partially evaluated with, so that only the fragments package is
+    /// analyzed again when QIR is generated for later fragments.
+    compute_properties: Option<PackageStoreComputeProperties>,
+    /// Key/value pairs that are embedded into the artifacts produced by the interpreter,
+    /// such as QIR and circuits.
+    metadata: BTreeMap<String, String>,
+    /// The native gates of the target that QIR and circuits are generated for.
+    gate_set: GateSet,
+    /// A hash of the contents of the program compiled so far, made up of the sources and the
+    /// fragments, which changes with every incremental update.
+    package_hash: u64,
+    /// The QIR generated by `qirgen`, keyed on the hash of the program, the entry expression,
+    /// the metadata, the gate set and whether debug info was included.
+    qir_cache: FxHashMap<u64, String>,
 }
 
 pub type InterpretResult = std::result::Result<Value, Vec<Error>>;
@@ -143,12 +262,74 @@ impl Interpreter {
         capabilities: RuntimeCapabilityFlags,
         language_features: LanguageFeatures,
     ) -> std::result::Result<Self, Vec<Error>> {
-        let mut lowerer = qsc_eval::lower::Lowerer::new();
-        let mut fir_store = fir::PackageStore::new();
+        Self::new_with_passes(
+            std.into(),
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+            PassConfig::default(),
+        )
+    }
 
-        let compiler = Compiler::new(std, sources, package_type, capabilities, language_features)
-            .map_err(into_errors)?;
+    /// Creates a new interpreter like [`Interpreter::new`], compiling the sources against the
+    /// given standard library, which may be left out or replaced with a custom one, and running
+    /// the optional passes enabled in the given configuration.
+    /// # Errors
+    /// If compiling the standard library or the sources fails, compiler errors are returned.
+    pub fn new_with_passes(
+        std: StdLib,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: RuntimeCapabilityFlags,
+        language_features: LanguageFeatures,
+        passes: PassConfig,
+    ) -> std::result::Result<Self, Vec<Error>> {
+        let compiler = Compiler::new_with_passes(
+            std.clone(),
+            sources.clone(),
+            package_type,
+            capabilities,
+            language_features,
+            passes,
+        )
+        .map_err(into_errors)?;
+
+        Ok(Self::from_compiler(
+            compiler,
+            std,
+            sources,
+            package_type,
+            capabilities,
+            language_features,
+        ))
+    }
 
+    /// Creates a new interpreter that shares the compiled libraries and sources of this one, so
+    /// they aren't compiled again. The fragments evaluated by this interpreter and its settings,
+    /// such as seeds, limits and the simulator, are not carried over.
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        Self::from_compiler(
+            self.compiler.fork(),
+            self.std.clone(),
+            self.sources.clone(),
+            self.package_type,
+            self.capabilities,
+            self.language_features,
+        )
+    }
+
+    fn from_compiler(
+        compiler: Compiler,
+        std: StdLib,
+        sources: SourceMap,
+        package_type: PackageType,
+        capabilities: RuntimeCapabilityFlags,
+        language_features: LanguageFeatures,
+    ) -> Self {
+        let mut lowerer = qsc_eval::lower::Lowerer::new();
+        let mut fir_store = fir::PackageStore::new();
         for (id, unit) in compiler.package_store() {
             fir_store.insert(
                 map_hir_package_to_fir(id),
@@ -159,15 +340,26 @@ impl Interpreter {
         let source_package_id = compiler.source_package_id();
         let package_id = compiler.package_id();
 
-        Ok(Self {
+        let mut hasher = FxHasher::default();
+        for source in sources.iter() {
+            source.name.hash(&mut hasher);
+            source.contents.hash(&mut hasher);
+        }
+        let package_hash = hasher.finish();
+
+        Self {
             compiler,
+            std,
+            sources,
+            package_type,
+            language_features,
             lines: 0,
             capabilities,
             fir_store,
             lowerer,
             env: Env::default(),
             sim: BackendChain::new(
-                SparseSim::new(),
+                Simulator::new(SimulatorKind::default()),
                 CircuitBuilder::new(CircuitConfig {
                     // When using in conjunction with the simulator,
                     // the circuit builder should *not* perform base profile
@@ -177,13 +369,27 @@ impl Interpreter {
                     // will still respect the selected profile. This also
                     // matches the behavior of the simulator.
                     base_profile: false,
+                    // The circuit traced alongside the simulator shows the
+                    // gates exactly as they were applied.
+                    optimize: false,
                 }),
             ),
             quantum_seed: None,
             classical_seed: None,
+            limits: Limits::default(),
+            simulator: SimulatorKind::default(),
+            forced_outcomes: ForcedOutcomes::default(),
+            intrinsics: IntrinsicCallbacks::default(),
+            last_run_statistics: None,
+            folded_source_package: None,
+            compute_properties: None,
+            metadata: BTreeMap::new(),
+            gate_set: GateSet::default(),
+            package_hash,
+            qir_cache: FxHashMap::default(),
             package: map_hir_package_to_fir(package_id),
             source_package: map_hir_package_to_fir(source_package_id),
-        })
+        }
     }
 
     pub fn set_quantum_seed(&mut self, seed: Option<u64>) {
@@ -194,6 +400,114 @@ impl Interpreter {
     pub fn set_classical_seed(&mut self, seed: Option<u64>) {
         self.classical_seed = seed;
     }
+
+    /// The optional passes run on the sources, fragments and entry expressions.
+    #[must_use]
+    pub fn pass_config(&self) -> PassConfig {
+        self.compiler.pass_config()
+    }
+
+    /// Maps a span of the fragments and entry expressions compiled so far, such as the span of a
+    /// label in an error they raised, to the fragment that contains it and the lines and columns
+    /// within that fragment.
+    #[must_use]
+    pub fn fragment_location(&self, span: Span, position_encoding: Encoding) -> Option<Location> {
+        let unit = self
+            .compiler
+            .package_store()
+            .get(self.compiler.package_id())
+            .expect("open package should be in store");
+        Location::from_source_map(span, &unit.sources, position_encoding)
+    }
+
+    /// Sets the limits on the qubits, call depth and statements of the programs run from now on.
+    /// A program that exceeds a limit fails with a runtime error.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Sets the kind of simulator that programs run on. The current simulator is replaced with a
+    /// new one, so this should be called before any qubits are allocated.
+    pub fn set_simulator(&mut self, kind: SimulatorKind) {
+        self.simulator = kind;
+        self.sim.main = self.new_sim();
+        self.sim.main.set_seed(self.quantum_seed);
+    }
+
+    /// Forces the outcomes of measurements, so that tests can exercise both branches of
+    /// measurement-conditioned logic. The outcomes apply to the current simulator, consuming the
+    /// sequence as it is measured, and to each run started from now on.
+    pub fn set_forced_outcomes(&mut self, forced_outcomes: ForcedOutcomes) {
+        self.sim.main.set_forced_outcomes(forced_outcomes.clone());
+        self.forced_outcomes = forced_outcomes;
+    }
+
+    /// Registers the callback that simulates the custom intrinsic with the given name, which is
+    /// the QIR name given by its `@CustomIntrinsic` attribute if it has one, or the name of the
+    /// intrinsic callable otherwise. The callback replaces any previously registered for the name.
+    pub fn register_intrinsic(&mut self, name: &str, callback: IntrinsicCallback) {
+        self.intrinsics.insert(name.into(), callback);
+        self.sim.main.set_intrinsics(self.intrinsics.clone());
+    }
+
+    /// Declares a Q# function with the given name, parameters and return type that is implemented
+    /// by the given callback, so that programs can call classical code outside of Q#, such as an
+    /// optimizer, while they are simulated. The parameters are pairs of names and Q# types, and
+    /// the callback is called with an argument for each parameter.
+    /// # Errors
+    /// If the declaration of the function fails to compile, the compiler errors are returned.
+    pub fn register_function(
+        &mut self,
+        name: &str,
+        params: &[(String, String)],
+        output: &str,
+        callback: impl Fn(Vec<Value>) -> std::result::Result<Value, String> + 'static,
+    ) -> std::result::Result<(), Vec<Error>> {
+        let params_decl = params
+            .iter()
+            .map(|(name, ty)| format!("{name} : {ty}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut sink = std::io::sink();
+        let mut out = GenericReceiver::new(&mut sink);
+        self.eval_fragments(
+            &mut out,
+            &format!("function {name}({params_decl}) : {output} {{ body intrinsic; }}"),
+        )?;
+
+        // A single parameter is passed to the intrinsic as is, and several as a tuple.
+        let arity = params.len();
+        self.register_intrinsic(
+            name,
+            Rc::new(move |arg| match arg {
+                Value::Tuple(items) if arity != 1 => callback(items.to_vec()),
+                arg => callback(vec![arg]),
+            }),
+        );
+        Ok(())
+    }
+
+    /// Sets the key/value pairs that are embedded into the QIR and circuits generated
+    /// from now on, so that they can be correlated with the experiment they belong to.
+    pub fn set_metadata(&mut self, metadata: BTreeMap<String, String>) {
+        self.metadata = metadata;
+    }
+
+    #[must_use]
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// Sets the native gates of the target, which the intrinsics in the QIR and circuits
+    /// generated from now on are decomposed into.
+    pub fn set_gate_set(&mut self, gate_set: GateSet) {
+        self.gate_set = gate_set;
+    }
+
+    #[must_use]
+    pub fn gate_set(&self) -> GateSet {
+        self.gate_set
+    }
     /// Executes the entry expression until the end of execution.
     /// # Errors
     /// Returns a vector of errors if evaluating the entry point fails.
@@ -205,6 +519,7 @@ impl Interpreter {
         eval(
             self.source_package,
             self.classical_seed,
+            self.limits,
             graph,
             self.compiler.package_store(),
             &self.fir_store,
@@ -228,6 +543,7 @@ impl Interpreter {
         eval(
             self.source_package,
             self.classical_seed,
+            self.limits,
             graph,
             self.compiler.package_store(),
             &self.fir_store,
@@ -237,7 +553,7 @@ impl Interpreter {
         )
     }
 
-    fn get_entry_exec_graph(&self) -> std::result::Result<Rc<[ExecGraphNode]>, Vec<Error>> {
+    fn get_entry_exec_graph(&self) -> std::result::Result<Arc<[ExecGraphNode]>, Vec<Error>> {
         let unit = self.fir_store.get(self.source_package);
         if unit.entry.is_some() {
             return Ok(unit.entry_exec_graph.clone());
@@ -254,26 +570,12 @@ impl Interpreter {
         receiver: &mut impl Receiver,
         fragments: &str,
     ) -> InterpretResult {
-        let label = self.next_line_label();
-
-        let increment = self
-            .compiler
-            .compile_fragments_fail_fast(&label, fragments)
-            .map_err(into_errors)?;
-
-        let (_, graph) = self.lower(&increment);
-
-        // Updating the compiler state with the new AST/HIR nodes
-        // is not necessary for the interpreter to function, as all
-        // the state required for evaluation already exists in the
-        // FIR store. It could potentially save some memory
-        // *not* to do hold on to the AST/HIR, but it is done
-        // here to keep the package stores consistent.
-        self.compiler.update(increment);
+        let graph = self.compile_fragments(fragments)?;
 
         eval(
             self.package,
             self.classical_seed,
+            self.limits,
             graph.into(),
             self.compiler.package_store(),
             &self.fir_store,
@@ -283,6 +585,59 @@ impl Interpreter {
         )
     }
 
+    /// Runs the callable with the `@EntryPoint()` attribute in the sources or fragments with the
+    /// given arguments, like [`Interpreter::run`] does for an entry expression. The arguments are
+    /// checked against the parameters of the entry point before it's called.
+    /// # Errors
+    /// If there isn't exactly one entry point, or the arguments don't match its parameters,
+    /// an error is returned.
+    pub fn run_entry(
+        &mut self,
+        receiver: &mut impl Receiver,
+        args: Vec<Value>,
+    ) -> std::result::Result<InterpretResult, Vec<Error>> {
+        let store = self.compiler.package_store();
+        let mut entry_points = [
+            self.compiler.source_package_id(),
+            self.compiler.package_id(),
+        ]
+        .into_iter()
+        .flat_map(|id| {
+            entry_point::find_entry_points(
+                &store.get(id).expect("package should be in store").package,
+            )
+        })
+        .collect::<Vec<_>>();
+        let entry = match entry_points.len() {
+            0 => return Err(vec![Error::NoEntryPoint]),
+            1 => entry_points.remove(0),
+            _ => {
+                let names = entry_points
+                    .iter()
+                    .map(|entry| entry.name.as_str())
+                    .collect::<Vec<_>>();
+                return Err(vec![Error::AmbiguousEntryPoint(names.join(", "))]);
+            }
+        };
+
+        if args.len() != entry.params.len() {
+            return Err(vec![Error::EntryPointArgCount(
+                entry.params.len(),
+                args.len(),
+            )]);
+        }
+        let args = args
+            .into_iter()
+            .zip(&entry.params)
+            .map(|(arg, (name, ty))| {
+                entry_point::bind_arg(arg, ty)
+                    .ok_or_else(|| vec![Error::EntryPointArgType(name.clone(), ty.to_string())])
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        self.run(receiver, &entry_point::entry_expr(&entry.name, &args))
+    }
+
     /// Runs the given entry expression on a new instance of the environment and simulator,
     /// but using the current compilation.
     pub fn run(
@@ -290,7 +645,175 @@ impl Interpreter {
         receiver: &mut impl Receiver,
         expr: &str,
     ) -> std::result::Result<InterpretResult, Vec<Error>> {
-        self.run_with_sim(&mut SparseSim::new(), receiver, expr)
+        let mut sim = Monitor::new(self.new_sim(), self.simulator);
+        let result = self.run_with_sim(&mut sim, receiver, expr)?;
+        self.last_run_statistics = Some(sim.into_statistics());
+        Ok(result)
+    }
+
+    /// The resources the simulator used in the most recent call to `run` or `run_recorded`,
+    /// including a run that failed at runtime.
+    #[must_use]
+    pub fn last_run_statistics(&self) -> Option<&RunStatistics> {
+        self.last_run_statistics.as_ref()
+    }
+
+    /// The size of each of the packages compiled so far.
+    #[must_use]
+    pub fn package_stats(&self) -> Vec<PackageStats> {
+        compilation_stats::package_stats(&self.fir_store, self.source_package, self.package)
+    }
+
+    /// Runs the runtime capabilities analysis over the packages compiled so far, and reports how
+    /// long it took along with what it found for the callables of the source package.
+    #[must_use]
+    pub fn rca_stats(&self) -> RcaStats {
+        compilation_stats::rca_stats(&self.fir_store, self.source_package)
+    }
+
+    /// Produces the call graph of the callables declared in the sources and the fragments evaluated
+    /// so far, along with the library callables they refer to. The edges carry the spans of the
+    /// references, which can be located with [`Interpreter::resolve_source`].
+    #[must_use]
+    pub fn call_graph(&self) -> CallGraph {
+        let compute_properties = Analyzer::init(&self.fir_store).analyze_all();
+        call_graph_of_packages(
+            &self.fir_store,
+            &compute_properties,
+            &[self.source_package, self.package],
+        )
+    }
+
+    /// Runs the given entry expression like `run`, and records the outcome of every measurement
+    /// along with the seeds of the run. Unless a seed is set on the interpreter, a random one is
+    /// used and recorded.
+    ///
+    /// When a recording of a previous run is given, the run replays it by reusing its seeds,
+    /// and fails with [`Error::ReplayDiverged`] if any measurement outcome differs from it.
+    /// The recording is returned even if the run fails, so that the failure can be replayed.
+    pub fn run_recorded(
+        &mut self,
+        receiver: &mut impl Receiver,
+        expr: &str,
+        replay: Option<&Recording>,
+    ) -> std::result::Result<(InterpretResult, Recording), Vec<Error>> {
+        let graph = self.compile_entry_expr(expr)?;
+
+        let quantum_seed = replay.map_or_else(
+            || self.quantum_seed.unwrap_or_else(test_runner::random_seed),
+            |recording| recording.quantum_seed,
+        );
+        let classical_seed = replay.map_or_else(
+            || self.classical_seed.unwrap_or_else(test_runner::random_seed),
+            |recording| recording.classical_seed,
+        );
+        let mut sim = Recorder::new(
+            Monitor::new(self.new_sim(), self.simulator),
+            replay.map(|recording| recording.measurements.as_slice()),
+        );
+        sim.set_seed(Some(quantum_seed));
+
+        let mut result = eval(
+            self.package,
+            Some(classical_seed),
+            self.limits,
+            graph.into(),
+            self.compiler.package_store(),
+            &self.fir_store,
+            &mut Env::default(),
+            &mut sim,
+            receiver,
+        );
+        if let Some(index) = sim.divergence() {
+            let error = Error::ReplayDiverged(index);
+            match &mut result {
+                Ok(_) => result = Err(vec![error]),
+                Err(errors) => errors.push(error),
+            }
+        }
+
+        self.last_run_statistics = Some(sim.backend.into_statistics());
+        Ok((
+            result,
+            Recording {
+                quantum_seed,
+                classical_seed,
+                measurements: sim.measurements,
+            },
+        ))
+    }
+
+    /// Computes the unitary matrix of the given operation, which takes an array of `num_qubits`
+    /// qubits, by simulating it on a new instance of the simulator. Rows and columns are indexed
+    /// by basis states in the same order as state dumps.
+    /// # Errors
+    /// Returns the errors from compiling or running the operation, or
+    /// [`Error::MeasuringOperation`] if the operation makes a measurement.
+    pub fn dump_operation(
+        &mut self,
+        operation: &str,
+        num_qubits: usize,
+    ) -> std::result::Result<Vec<Vec<Complex64>>, Vec<Error>> {
+        let state = self.run_unitary(&dump_operation::entry_expr(operation, num_qubits))?;
+        Ok(dump_operation::matrix_from_state(state, num_qubits))
+    }
+
+    /// Checks whether the two given operations, which take an array of `num_qubits` qubits,
+    /// implement the same unitary up to a global phase, by simulating them with the given method.
+    /// # Errors
+    /// Returns the errors from compiling or running either operation, or
+    /// [`Error::MeasuringOperation`] if either operation makes a measurement.
+    pub fn check_equivalence(
+        &mut self,
+        operation_a: &str,
+        operation_b: &str,
+        num_qubits: usize,
+        method: EquivalenceMethod,
+    ) -> std::result::Result<bool, Vec<Error>> {
+        match method {
+            EquivalenceMethod::Matrix => {
+                let a = self.dump_operation(operation_a, num_qubits)?;
+                let b = self.dump_operation(operation_b, num_qubits)?;
+                Ok(equivalence::matrices_equivalent(&a, &b))
+            }
+            EquivalenceMethod::Sampling(samples) => {
+                let seed = self.quantum_seed.unwrap_or_else(test_runner::random_seed);
+                let mut overlaps = Vec::new();
+                for sample in 0..samples {
+                    let angles = equivalence::random_angles(seed, sample, num_qubits);
+                    let a = self.run_unitary(&equivalence::random_state_expr(
+                        operation_a,
+                        num_qubits,
+                        &angles,
+                    ))?;
+                    let b = self.run_unitary(&equivalence::random_state_expr(
+                        operation_b,
+                        num_qubits,
+                        &angles,
+                    ))?;
+                    overlaps.push(equivalence::overlap(&a, &b));
+                }
+                Ok(equivalence::overlaps_equivalent(&overlaps))
+            }
+        }
+    }
+
+    /// Runs the given entry expression, which applies an operation and ends by dumping the
+    /// state, on a new instance of the simulator and returns the dumped state.
+    fn run_unitary(
+        &mut self,
+        expr: &str,
+    ) -> std::result::Result<Vec<(BigUint, Complex64)>, Vec<Error>> {
+        let mut sim = Recorder::new(Simulator::new(self.simulator), None);
+        let mut receiver = dump_operation::StateCapture::default();
+        self.run_with_sim(&mut sim, &mut receiver, expr)??;
+        if !sim.measurements.is_empty() {
+            return Err(vec![Error::MeasuringOperation]);
+        }
+
+        Ok(receiver
+            .state
+            .expect("entry expression should dump the state"))
     }
 
     /// Gets the current quantum state of the simulator.
@@ -300,23 +823,182 @@ impl Interpreter {
 
     /// Get the current circuit representation of the program.
     pub fn get_circuit(&self) -> Circuit {
-        self.sim.chained.snapshot()
+        self.finish_circuit(self.sim.chained.snapshot())
     }
 
     /// Performs QIR codegen using the given entry expression on a new instance of the environment
     /// and simulator but using the current compilation.
+    /// The generated QIR is cached, so generating it again for the same entry expression returns
+    /// the cached QIR until the program is updated with new fragments or the metadata changes.
     pub fn qirgen(&mut self, expr: &str) -> std::result::Result<String, Vec<Error>> {
+        self.qirgen_cached(expr, false)
+    }
+
+    /// Performs QIR codegen like `qirgen`, attaching the Q# source location of the call that each
+    /// instruction comes from as debug metadata.
+    pub fn qirgen_with_debug_info(
+        &mut self,
+        expr: &str,
+    ) -> std::result::Result<String, Vec<Error>> {
+        self.qirgen_cached(expr, true)
+    }
+
+    fn qirgen_cached(
+        &mut self,
+        expr: &str,
+        debug_info: bool,
+    ) -> std::result::Result<String, Vec<Error>> {
         if self.capabilities != RuntimeCapabilityFlags::empty() {
             return Err(vec![Error::UnsupportedRuntimeCapabilities]);
         }
 
-        let mut sim = BaseProfSim::new();
+        let mut hasher = FxHasher::default();
+        self.package_hash.hash(&mut hasher);
+        expr.hash(&mut hasher);
+        self.metadata.hash(&mut hasher);
+        self.gate_set.hash(&mut hasher);
+        debug_info.hash(&mut hasher);
+        let key = hasher.finish();
+        if let Some(qir) = self.qir_cache.get(&key) {
+            return Ok(qir.clone());
+        }
+
+        let qir = self.qirgen_uncached(expr, debug_info)?;
+        self.qir_cache.insert(key, qir.clone());
+        Ok(qir)
+    }
+
+    fn qirgen_uncached(
+        &mut self,
+        expr: &str,
+        debug_info: bool,
+    ) -> std::result::Result<String, Vec<Error>> {
+        let graph = self.compile_entry_expr(expr)?;
+        let mut sim = Decomposer::new(BaseProfSim::new(), self.gate_set);
+        sim.backend.set_metadata(self.metadata.clone());
+        sim.backend.set_debug_info(debug_info);
+        if self.quantum_seed.is_some() {
+            sim.set_seed(self.quantum_seed);
+        }
         let mut stdout = std::io::sink();
         let mut out = GenericReceiver::new(&mut stdout);
 
-        let val = self.run_with_sim(&mut sim, &mut out, expr)??;
+        // The folded packages only stand in for the ones that are run and debugged while QIR is generated.
+        let folded = self.fold_program();
+        let unfolded = self.swap_packages(folded);
+        let val = eval(
+            self.package,
+            self.classical_seed,
+            self.limits,
+            graph.into(),
+            self.compiler.package_store(),
+            &self.fir_store,
+            &mut Env::default(),
+            &mut sim,
+            &mut out,
+        );
+        let [(_, folded_source_package), _] = self.swap_packages(unfolded);
+        self.folded_source_package = Some(folded_source_package);
+        let val = val?;
+
+        // Generated calls, such as the ones that release qubits, have no span to locate.
+        Ok(sim.backend.finish_with_sources(&val, |span| {
+            (span.span != Span::default())
+                .then(|| self.resolve_source(usize::from(span.package), span.span))
+                .flatten()
+        }))
+    }
+
+    /// Proposes rewrites for the constructs reachable from the given entry expression that raise the
+    /// runtime capabilities the program needs, such as branching on measurement results or loops with
+    /// dynamic conditions.
+    /// # Errors
+    /// If the entry expression fails to compile, errors are returned.
+    pub fn advise(
+        &mut self,
+        expr: &str,
+    ) -> std::result::Result<Vec<WithSource<Advice>>, Vec<Error>> {
+        self.compile_entry_expr(expr)?;
+
+        let compute_properties = Analyzer::init(&self.fir_store).analyze_all();
+        let package_store = self.compiler.package_store();
+        let sources = [self.source_package, self.package]
+            .into_iter()
+            .filter_map(|id| {
+                package_store
+                    .get(map_fir_package_to_hir(id))
+                    .map(|unit| (id, &unit.sources))
+            })
+            .collect::<IndexMap<_, _>>();
+
+        Ok(
+            advise(&self.fir_store, &compute_properties, self.package, &sources)
+                .into_iter()
+                .map(|(package, advice)| {
+                    WithSource::from_map(
+                        sources
+                            .get(package)
+                            .expect("advice should be for an advised package"),
+                        advice,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Produces copies of the source package and the fragments package with the classical fragments of their
+    /// callables evaluated at compile time, so that code generation only needs to deal with quantum operations and
+    /// dynamic computations. The source package never changes, so it is only folded the first time.
+    fn fold_program(&mut self) -> [(PackageId, fir::Package); 2] {
+        // The core and standard libraries and the source package never change either, so after the first analysis
+        // only the fragments package is analyzed again.
+        let compute_properties = match self.compute_properties.take() {
+            Some(mut compute_properties) => {
+                compute_properties.get_mut(self.package).clear();
+                Analyzer::init_with_compute_properties(&self.fir_store, compute_properties)
+                    .analyze_package(self.package)
+            }
+            None => Analyzer::init(&self.fir_store).analyze_all(),
+        };
+        let folded_source_package = match self.folded_source_package.take() {
+            Some(package) => package,
+            None => self.fold_package(self.source_package, &compute_properties),
+        };
+        let folded_package = self.fold_package(self.package, &compute_properties);
+        self.compute_properties = Some(compute_properties);
+        [
+            (self.source_package, folded_source_package),
+            (self.package, folded_package),
+        ]
+    }
+
+    /// Partially evaluates the callables of a copy of the package, leaving the package in the store untouched.
+    fn fold_package(
+        &mut self,
+        package_id: PackageId,
+        compute_properties: &PackageStoreComputeProperties,
+    ) -> fir::Package {
+        let package = self.fir_store.get(package_id).clone();
+        partially_evaluate_callables(
+            package_id,
+            |_| true,
+            &mut self.fir_store,
+            compute_properties,
+        );
+        std::mem::replace(self.fir_store.get_mut(package_id), package)
+    }
 
-        Ok(sim.finish(&val))
+    /// Replaces the given packages in the store, returning the ones they replaced.
+    fn swap_packages(
+        &mut self,
+        packages: [(PackageId, fir::Package); 2],
+    ) -> [(PackageId, fir::Package); 2] {
+        packages.map(|(package_id, package)| {
+            (
+                package_id,
+                std::mem::replace(self.fir_store.get_mut(package_id), package),
+            )
+        })
     }
 
     /// Generates a circuit representation for the program.
@@ -326,15 +1008,31 @@ impl Interpreter {
     ///
     /// An operation can be specified by its name or a lambda expression that only takes qubits.
     /// e.g. `Sample.Main` , `qs => H(qs[0])`
+    ///
+    /// When `optimize` is set, adjacent gates that cancel each other out are removed and
+    /// consecutive rotations on the same qubits are merged.
+    ///
+    /// The program is traced without running the simulator, so the circuit is the same regardless
+    /// of measurement outcomes. Both branches of an `if` whose condition compares a measurement
+    /// result are traced and shown as a group of gates controlled by the measurement result. Other
+    /// comparisons of measurement results can't be traced and are an error, unless `simulate` is
+    /// set, in which case the program is run on the simulator instead and the circuit shows the
+    /// gates applied on the path that was taken.
     pub fn circuit(
         &mut self,
         entry: CircuitEntryPoint,
+        optimize: bool,
+        simulate: bool,
     ) -> std::result::Result<Circuit, Vec<Error>> {
         let mut sink = std::io::sink();
         let mut out = GenericReceiver::new(&mut sink);
-        let mut sim = CircuitBuilder::new(CircuitConfig {
-            base_profile: self.capabilities.is_empty(),
-        });
+        let mut builder = Decomposer::new(
+            CircuitBuilder::new(CircuitConfig {
+                base_profile: self.capabilities.is_empty(),
+                optimize,
+            }),
+            self.gate_set,
+        );
 
         let entry_expr = match entry {
             CircuitEntryPoint::Operation(operation_expr) => {
@@ -377,13 +1075,93 @@ impl Interpreter {
             CircuitEntryPoint::EntryPoint => None,
         };
 
-        let val = if let Some(entry_expr) = entry_expr {
-            self.run_with_sim(&mut sim, &mut out, &entry_expr)?
-        } else {
-            self.eval_entry_with_sim(&mut sim, &mut out)
-        }?;
+        let (package, graph) = match entry_expr {
+            Some(entry_expr) => (self.package, self.compile_entry_expr(&entry_expr)?.into()),
+            None => (self.source_package, self.get_entry_exec_graph()?),
+        };
+
+        match eval(
+            package,
+            self.classical_seed,
+            self.limits,
+            graph.clone(),
+            self.compiler.package_store(),
+            &self.fir_store,
+            &mut Env::default(),
+            &mut builder,
+            &mut out,
+        ) {
+            Ok(val) => return Ok(self.finish_circuit(builder.backend.finish(&val))),
+            // A result comparison that isn't the condition of an if-expression, or
+            // whose branches produce a value, needs the simulator to be traced.
+            Err(errors) if simulate && errors.iter().any(is_result_comparison_error) => {}
+            Err(errors) => return Err(errors),
+        }
+
+        let mut main = Simulator::new(self.simulator);
+        main.set_intrinsics(self.intrinsics.clone());
+        let mut sim = BackendChain::new(
+            main,
+            Decomposer::new(
+                CircuitBuilder::new(CircuitConfig {
+                    // As with the circuit traced by the interpreter, base profile
+                    // decompositions are not performed alongside the simulator.
+                    base_profile: false,
+                    optimize,
+                }),
+                self.gate_set,
+            ),
+        );
+        if self.quantum_seed.is_some() {
+            sim.set_seed(self.quantum_seed);
+        }
+        let val = eval(
+            package,
+            self.classical_seed,
+            self.limits,
+            graph,
+            self.compiler.package_store(),
+            &self.fir_store,
+            &mut Env::default(),
+            &mut sim,
+            &mut out,
+        )?;
+        Ok(self.finish_circuit(sim.chained.backend.finish(&val)))
+    }
+
+    /// Adds the metadata of the session to a circuit, and resolves the source locations of its
+    /// operations.
+    fn finish_circuit(&self, mut circuit: Circuit) -> Circuit {
+        circuit.resolve_sources(|package, span| self.resolve_source(package, span));
+        Circuit {
+            metadata: self.metadata.clone(),
+            ..circuit
+        }
+    }
 
-        Ok(sim.finish(&val))
+    /// Resolves a span in a package to the name of its source and its zero-based line and column.
+    /// Columns are counted in utf-16 code units, as editors count them, and the sources of library
+    /// packages are named with the library URI scheme.
+    #[must_use]
+    pub fn resolve_source(&self, package: usize, span: Span) -> Option<(String, u32, u32)> {
+        let package = crate::hir::PackageId::from(package);
+        let source = self
+            .compiler
+            .package_store()
+            .get(package)?
+            .sources
+            .find_by_offset(span.lo)?;
+        // Entry expressions aren't kept in the sources, so calls made from them can't be located.
+        source.contents.get(..(span.hi - source.offset) as usize)?;
+        let start =
+            Range::from_span(Encoding::Utf16, &source.contents, &(span - source.offset)).start;
+        let user_packages = [self.package, self.source_package].map(map_fir_package_to_hir);
+        let file = if user_packages.contains(&package) {
+            source.name.to_string()
+        } else {
+            format!("{QSHARP_LIBRARY_URI_SCHEME}:{}", source.name)
+        };
+        Some((file, start.line, start.column))
     }
 
     /// Runs the given entry expression on the given simulator with a new instance of the environment
@@ -403,6 +1181,7 @@ impl Interpreter {
         Ok(eval(
             self.package,
             self.classical_seed,
+            self.limits,
             graph.into(),
             self.compiler.package_store(),
             &self.fir_store,
@@ -412,6 +1191,178 @@ impl Interpreter {
         ))
     }
 
+    /// Calls the given callable with the given arguments `repetitions` times on the given simulator,
+    /// with a new instance of the environment but using the current compilation. The calls run in
+    /// a loop, so the entry expression doesn't grow with the number of repetitions. The arguments
+    /// are checked against the parameters of the callable when the entry expression is compiled.
+    pub fn run_call_with_sim(
+        &mut self,
+        sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
+        receiver: &mut impl Receiver,
+        callable: &str,
+        args: &[Value],
+        repetitions: u32,
+    ) -> std::result::Result<InterpretResult, Vec<Error>> {
+        let call = entry_point::entry_expr(callable, args);
+        self.run_with_sim(
+            sim,
+            receiver,
+            &format!("for _ in 1..{repetitions} {{ let _ = {call}; }}"),
+        )
+    }
+
+    /// Compiles and runs the given entry expression the given number of times, each time on a new
+    /// instance of the environment and simulator, and collects timing statistics and gate counts.
+    /// Output from the program is discarded.
+    /// # Errors
+    /// Returns the errors from the first repetition that fails to compile or run.
+    pub fn benchmark(
+        &mut self,
+        expr: &str,
+        repetitions: u32,
+    ) -> std::result::Result<Benchmark, Vec<Error>> {
+        let mut compile = Vec::new();
+        let mut classical = Vec::new();
+        let mut simulator = Vec::new();
+        let mut total = Vec::new();
+        let mut gate_counts = BTreeMap::<&'static str, u64>::new();
+        let mut out = std::io::sink();
+        for _ in 0..repetitions {
+            let start = Instant::now();
+            let graph = self.compile_entry_expr(expr)?;
+            let compiled = Instant::now();
+
+            let mut sim = Profiler::new(Simulator::new(self.simulator));
+            if self.quantum_seed.is_some() {
+                sim.set_seed(self.quantum_seed);
+            }
+            eval(
+                self.package,
+                self.classical_seed,
+                self.limits,
+                graph.into(),
+                self.compiler.package_store(),
+                &self.fir_store,
+                &mut Env::default(),
+                &mut sim,
+                &mut GenericReceiver::new(&mut out),
+            )?;
+            let evaluated = compiled.elapsed();
+
+            compile.push(compiled - start);
+            classical.push(evaluated.saturating_sub(sim.elapsed));
+            simulator.push(sim.elapsed);
+            total.push(start.elapsed());
+            for (gate, count) in sim.gate_counts {
+                *gate_counts.entry(gate).or_default() += count;
+            }
+        }
+
+        Ok(Benchmark {
+            repetitions,
+            compile: Stats::from_samples(&compile),
+            classical: Stats::from_samples(&classical),
+            simulator: Stats::from_samples(&simulator),
+            total: Stats::from_samples(&total),
+            #[allow(clippy::cast_precision_loss)]
+            gate_counts: gate_counts
+                .into_iter()
+                .map(|(gate, count)| (gate, count as f64 / f64::from(repetitions)))
+                .collect(),
+        })
+    }
+
+    /// The namespace-qualified names of the callables with the `@Test()` attribute in the
+    /// source package, in source order.
+    #[must_use]
+    pub fn tests(&self) -> Vec<String> {
+        let unit = self
+            .compiler
+            .package_store()
+            .get(self.compiler.source_package_id())
+            .expect("source package should be in the package store");
+        test_runner::discover_tests(&unit.package)
+    }
+
+    /// Runs each test in the source package on a new instance of the environment and simulator.
+    /// A test passes if it returns without a runtime error. Each test is seeded with the quantum
+    /// seed if one is set, or with a random seed otherwise, and the seed is reported so that a
+    /// failing run can be reproduced.
+    pub fn run_tests(&mut self) -> Vec<TestResult> {
+        self.tests()
+            .into_iter()
+            .map(|name| {
+                let seed = self.quantum_seed.unwrap_or_else(test_runner::random_seed);
+                let mut out = Vec::new();
+                let errors = match self.compile_entry_expr(&format!("{name}()")) {
+                    Ok(graph) => {
+                        let mut sim = self.new_sim();
+                        sim.set_seed(Some(seed));
+                        eval(
+                            self.package,
+                            Some(self.classical_seed.unwrap_or(seed)),
+                            self.limits,
+                            graph.into(),
+                            self.compiler.package_store(),
+                            &self.fir_store,
+                            &mut Env::default(),
+                            &mut sim,
+                            &mut GenericReceiver::new(&mut out),
+                        )
+                        .err()
+                        .unwrap_or_default()
+                    }
+                    Err(errors) => errors,
+                };
+                TestResult {
+                    name,
+                    seed,
+                    output: String::from_utf8_lossy(&out).into_owned(),
+                    errors,
+                }
+            })
+            .collect()
+    }
+
+    /// A new simulator of the kind and with the forced measurement outcomes of the interpreter.
+    fn new_sim(&self) -> Simulator {
+        let mut sim = Simulator::new(self.simulator);
+        sim.set_forced_outcomes(self.forced_outcomes.clone());
+        sim.set_intrinsics(self.intrinsics.clone());
+        sim
+    }
+
+    fn compile_fragments(
+        &mut self,
+        fragments: &str,
+    ) -> std::result::Result<Vec<ExecGraphNode>, Vec<Error>> {
+        let label = self.next_line_label();
+
+        let increment = self
+            .compiler
+            .compile_fragments_fail_fast(&label, fragments)
+            .map_err(into_errors)?;
+
+        let (_, graph) = self.lower(&increment);
+
+        // Updating the compiler state with the new AST/HIR nodes
+        // is not necessary for the interpreter to function, as all
+        // the state required for evaluation already exists in the
+        // FIR store. It could potentially save some memory
+        // *not* to do hold on to the AST/HIR, but it is done
+        // here to keep the package stores consistent.
+        self.compiler.update(increment);
+
+        // The program has changed, so QIR generated for the previous program is stale.
+        let mut hasher = FxHasher::default();
+        self.package_hash.hash(&mut hasher);
+        fragments.hash(&mut hasher);
+        self.package_hash = hasher.finish();
+        self.qir_cache.clear();
+
+        Ok(graph)
+    }
+
     fn compile_entry_expr(
         &mut self,
         expr: &str,
@@ -461,6 +1412,14 @@ impl Interpreter {
     }
 }
 
+fn is_result_comparison_error(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::Eval(error)
+            if matches!(error.error().error(), qsc_eval::Error::ResultComparisonUnsupported(_))
+    )
+}
+
 /// Describes the entry point for circuit generation.
 pub enum CircuitEntryPoint {
     /// An operation. This must be a callable name or a lambda
@@ -491,12 +1450,15 @@ impl Debugger {
         position_encoding: Encoding,
         language_features: LanguageFeatures,
     ) -> std::result::Result<Self, Vec<Error>> {
-        let interpreter = Interpreter::new(
-            true,
+        // Stepping through the program shows the bindings of the source, which the optional
+        // passes, like constant folding, would remove.
+        let interpreter = Interpreter::new_with_passes(
+            StdLib::Builtin,
             sources,
             PackageType::Exe,
             capabilities,
             language_features,
+            PassConfig::default(),
         )?;
         let source_package_id = interpreter.source_package;
         let unit = interpreter.fir_store.get(source_package_id);
@@ -627,8 +1589,9 @@ impl Debugger {
 fn eval(
     package: PackageId,
     classical_seed: Option<u64>,
-    exec_graph: Rc<[ExecGraphNode]>,
-    package_store: &PackageStore,
+    limits: Limits,
+    exec_graph: Arc<[ExecGraphNode]>,
+    sources: &impl SourcesLookup,
     fir_store: &fir::PackageStore,
     env: &mut Env,
     sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
@@ -637,13 +1600,14 @@ fn eval(
     qsc_eval::eval(
         package,
         classical_seed,
+        limits,
         exec_graph,
         fir_store,
         env,
         sim,
         receiver,
     )
-    .map_err(|(error, call_stack)| eval_error(package_store, fir_store, call_stack, error))
+    .map_err(|(error, call_stack)| eval_error(sources, fir_store, call_stack, error))
 }
 
 /// Represents a stack frame for debugging.
@@ -750,23 +1714,22 @@ impl<'a> Visitor<'a> for BreakpointCollector<'a> {
 }
 
 fn eval_error(
-    package_store: &PackageStore,
+    sources: &impl SourcesLookup,
     fir_store: &fir::PackageStore,
     call_stack: Vec<Frame>,
     error: qsc_eval::Error,
 ) -> Vec<Error> {
+    let stack_frames = call_frames(sources, fir_store, &call_stack);
     let stack_trace = if call_stack.is_empty() {
         None
     } else {
-        Some(format_call_stack(
-            package_store,
-            fir_store,
-            call_stack,
-            &error,
-        ))
+        Some(format_call_stack(sources, fir_store, call_stack, &error))
     };
+    let error_sources = sources
+        .get_sources(error.span().package)
+        .expect("expected to find package id in store");
 
-    vec![error::from_eval(error, package_store, stack_trace).into()]
+    vec![error::from_eval(error, error_sources, stack_trace, stack_frames).into()]
 }
 
 fn into_errors(errors: Vec<crate::compile::Error>) -> Vec<Error> {
@@ -0,0 +1,248 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_eval::{backend::Backend, val::Value, PackageSpan};
+use qsc_fir::fir::CustomIntrinsic;
+use std::{
+    collections::BTreeMap,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+/// The results of running an entry expression repeatedly.
+#[derive(Clone, Debug)]
+pub struct Benchmark {
+    /// The number of times the entry expression was compiled and run.
+    pub repetitions: u32,
+    /// The time spent compiling the entry expression.
+    pub compile: Stats,
+    /// The time spent evaluating the program, excluding the time spent in the simulator.
+    pub classical: Stats,
+    /// The time spent in the simulator applying gates, measuring and managing qubits.
+    pub simulator: Stats,
+    /// The total time of each repetition.
+    pub total: Stats,
+    /// The average number of times each gate was applied per repetition, keyed by the name of
+    /// the backend intrinsic, such as `cx` or `mresetz`.
+    pub gate_counts: BTreeMap<&'static str, f64>,
+}
+
+/// Wall-time statistics over the repetitions of a benchmark.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub mean: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub std_dev: Duration,
+}
+
+impl Stats {
+    #[must_use]
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let len = samples.len() as f64;
+        let mean = samples.iter().map(Duration::as_secs_f64).sum::<f64>() / len;
+        let variance = samples
+            .iter()
+            .map(|sample| (sample.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / len;
+        Self {
+            mean: Duration::from_secs_f64(mean),
+            min: samples.iter().min().copied().unwrap_or_default(),
+            max: samples.iter().max().copied().unwrap_or_default(),
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// A backend that measures the time spent in the wrapped backend and counts the gates applied to it.
+pub(super) struct Profiler<B> {
+    pub backend: B,
+    pub elapsed: Duration,
+    pub gate_counts: BTreeMap<&'static str, u64>,
+}
+
+impl<B: Backend> Profiler<B> {
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            elapsed: Duration::ZERO,
+            gate_counts: BTreeMap::new(),
+        }
+    }
+
+    fn time<T>(&mut self, f: impl FnOnce(&mut B) -> T) -> T {
+        let start = Instant::now();
+        let result = f(&mut self.backend);
+        self.elapsed += start.elapsed();
+        result
+    }
+
+    fn gate<T>(&mut self, name: &'static str, f: impl FnOnce(&mut B) -> T) -> T {
+        *self.gate_counts.entry(name).or_default() += 1;
+        self.time(f)
+    }
+}
+
+impl<B: Backend> Backend for Profiler<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.gate("ccx", |b| b.ccx(ctl0, ctl1, q));
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.gate("cx", |b| b.cx(ctl, q));
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.gate("cy", |b| b.cy(ctl, q));
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.gate("cz", |b| b.cz(ctl, q));
+    }
+
+    fn h(&mut self, q: usize) {
+        self.gate("h", |b| b.h(q));
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.gate("m", |b| b.m(q))
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.gate("mresetz", |b| b.mresetz(q))
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.gate("reset", |b| b.reset(q));
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.gate("rx", |b| b.rx(theta, q));
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gate("rxx", |b| b.rxx(theta, q0, q1));
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.gate("ry", |b| b.ry(theta, q));
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gate("ryy", |b| b.ryy(theta, q0, q1));
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.gate("rz", |b| b.rz(theta, q));
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.gate("rzz", |b| b.rzz(theta, q0, q1));
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.gate("sadj", |b| b.sadj(q));
+    }
+
+    fn s(&mut self, q: usize) {
+        self.gate("s", |b| b.s(q));
+    }
+
+    fn sx(&mut self, q: usize) {
+        self.gate("sx", |b| b.sx(q));
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.gate("swap", |b| b.swap(q0, q1));
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.gate("tadj", |b| b.tadj(q));
+    }
+
+    fn t(&mut self, q: usize) {
+        self.gate("t", |b| b.t(q));
+    }
+
+    fn x(&mut self, q: usize) {
+        self.gate("x", |b| b.x(q));
+    }
+
+    fn y(&mut self, q: usize) {
+        self.gate("y", |b| b.y(q));
+    }
+
+    fn z(&mut self, q: usize) {
+        self.gate("z", |b| b.z(q));
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.time(Backend::qubit_allocate)
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.time(|b| b.qubit_release(q));
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.time(Backend::capture_quantum_state)
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.time(|b| b.qubit_is_zero(q))
+    }
+
+    fn qubit_borrow(&mut self) -> usize {
+        self.time(Backend::qubit_borrow)
+    }
+
+    fn qubit_return(&mut self, q: usize) -> bool {
+        self.time(|b| b.qubit_return(q))
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.time(|b| b.custom_intrinsic(name, arg))
+    }
+
+    fn target_intrinsic(
+        &mut self,
+        intrinsic: &CustomIntrinsic,
+        arg: Value,
+    ) -> Option<Result<Value, String>> {
+        self.time(|b| b.target_intrinsic(intrinsic, arg))
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn supports_conditionals(&self) -> bool {
+        self.backend.supports_conditionals()
+    }
+
+    fn begin_conditional(&mut self, result: usize, value: bool) {
+        self.backend.begin_conditional(result, value);
+    }
+
+    fn end_conditional(&mut self) {
+        self.backend.end_conditional();
+    }
+
+    fn records_call_sites(&self) -> bool {
+        self.backend.records_call_sites()
+    }
+
+    fn set_call_site(&mut self, span: PackageSpan, callable: Rc<str>) {
+        self.backend.set_call_site(span, callable);
+    }
+}
@@ -39,7 +39,7 @@ fn empty() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     expect![].assert_eq(&circ.to_string());
@@ -61,7 +61,7 @@ fn one_gate() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -86,7 +86,7 @@ fn rotation_gate() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     // The wire isn't visible here since the gate label is longer
@@ -115,7 +115,7 @@ fn classical_for_loop() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, false, false)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -142,7 +142,7 @@ fn m_base_profile() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -171,7 +171,7 @@ fn m_unrestricted_profile() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -199,7 +199,7 @@ fn mresetz_unrestricted_profile() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -227,7 +227,7 @@ fn mresetz_base_profile() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -265,7 +265,7 @@ fn unrestricted_profile_result_comparison() {
     interpreter.set_quantum_seed(Some(2));
 
     let circuit_err = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect_err("circuit should return error")
         .pop()
         .expect("error should exist");
@@ -301,6 +301,150 @@ fn unrestricted_profile_result_comparison() {
     .assert_eq(&circuit.to_string());
 }
 
+#[test]
+fn result_comparison_is_simulated_when_requested() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                open Microsoft.Quantum.Measurement;
+                @EntryPoint()
+                operation Main() : Result[] {
+                    use q1 = Qubit();
+                    use q2 = Qubit();
+                    H(q1);
+                    H(q2);
+                    let r1 = M(q1);
+                    let r2 = M(q2);
+                    if (r1 == r2) {
+                        X(q1);
+                    }
+                    ResetAll([q1, q2]);
+                    [r1, r2]
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+
+    interpreter.set_quantum_seed(Some(2));
+
+    // The comparison can't be traced statically, so the program is
+    // simulated and the circuit shows the path taken with the given seed.
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, true, true)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0    ── H ──── M ──── X ─── |0〉 ─
+                         ╘═════════════════
+        q_1    ── H ──── M ─── |0〉 ────────
+                         ╘═════════════════
+    "]]
+    .assert_eq(&circ.to_string());
+}
+
+#[test]
+fn static_program_is_traced_without_simulation() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result {
+                    use q = Qubit();
+                    X(q);
+                    M(q)
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+
+    // Releasing a qubit that is not in the |0〉 state would fail in the
+    // simulator, but the program doesn't depend on measurement results
+    // so it is traced without one.
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0    ── X ──── M ──
+                         ╘═══
+    "]]
+    .assert_eq(&circ.to_string());
+}
+
+#[test]
+fn branches_on_measurement_are_traced_as_conditionals() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q1 = Qubit();
+                    use q2 = Qubit();
+                    H(q1);
+                    let r = M(q1);
+                    if r == One {
+                        X(q2);
+                    } else {
+                        Z(q2);
+                    }
+                    if r != One {
+                        H(q2);
+                    }
+                    Reset(q1);
+                    Reset(q2);
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, false, false)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0    ── H ──── M ─── |0〉 ──────────────────────
+                         ╘═════ ● ════ ○ ════ ○ ═════════
+        q_1    ──────────────── X ──── Z ──── H ─── |0〉 ─
+    "]]
+    .assert_eq(&circ.to_string());
+}
+
+#[test]
+fn branch_that_updates_a_variable_is_simulated() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    mutable flipped = false;
+                    if M(q) == Zero {
+                        X(q);
+                        set flipped = true;
+                    }
+                    if flipped {
+                        X(q);
+                    }
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, false, true)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0    ── M ──── X ──── X ──
+                  ╘═════════════════
+    "]]
+    .assert_eq(&circ.to_string());
+}
+
 #[test]
 fn custom_intrinsic() {
     let mut interpreter = interpreter(
@@ -320,7 +464,7 @@ fn custom_intrinsic() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -349,7 +493,7 @@ fn custom_intrinsic_classical_arg() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     // A custom intrinsic that doesn't take qubits just doesn't
@@ -380,7 +524,7 @@ fn custom_intrinsic_one_classical_arg() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     // A custom intrinsic that doesn't take qubits just doesn't
@@ -418,7 +562,7 @@ fn custom_intrinsic_mixed_args() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::EntryPoint)
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
         .expect("circuit generation should succeed");
 
     // This is one gate that spans ten target wires, even though the
@@ -458,7 +602,11 @@ fn operation_with_qubits() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::Operation("Test.Test".into()))
+        .circuit(
+            CircuitEntryPoint::Operation("Test.Test".into()),
+            true,
+            false,
+        )
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -488,7 +636,11 @@ fn operation_with_qubits_base_profile() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::Operation("Test.Test".into()))
+        .circuit(
+            CircuitEntryPoint::Operation("Test.Test".into()),
+            true,
+            false,
+        )
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -535,7 +687,11 @@ fn operation_with_qubit_arrays() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::Operation("Test.Test".into()))
+        .circuit(
+            CircuitEntryPoint::Operation("Test.Test".into()),
+            true,
+            false,
+        )
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -587,7 +743,11 @@ fn adjoint_operation() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::Operation("Adjoint Test.Foo".into()))
+        .circuit(
+            CircuitEntryPoint::Operation("Adjoint Test.Foo".into()),
+            true,
+            false,
+        )
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -608,7 +768,11 @@ fn lambda() {
     );
 
     let circ = interpreter
-        .circuit(CircuitEntryPoint::Operation("q => H(q)".into()))
+        .circuit(
+            CircuitEntryPoint::Operation("q => H(q)".into()),
+            true,
+            false,
+        )
         .expect("circuit generation should succeed");
 
     expect![[r"
@@ -649,7 +813,11 @@ fn controlled_operation() {
     );
 
     let circ_err = interpreter
-        .circuit(CircuitEntryPoint::Operation("Controlled Test.SWAP".into()))
+        .circuit(
+            CircuitEntryPoint::Operation("Controlled Test.SWAP".into()),
+            true,
+            false,
+        )
         .expect_err("circuit generation should fail");
 
     // Controlled operations are not supported at the moment.
@@ -680,7 +848,11 @@ fn internal_operation() {
     );
 
     let circ_err = interpreter
-        .circuit(CircuitEntryPoint::Operation("Test.Test".into()))
+        .circuit(
+            CircuitEntryPoint::Operation("Test.Test".into()),
+            true,
+            false,
+        )
         .expect_err("circuit generation should fail");
 
     expect![[r#"
@@ -697,8 +869,9 @@ fn internal_operation() {
                     error: Frontend(
                         Error(
                             Resolve(
-                                NotFound(
+                                NotExported(
                                     "Test",
+                                    "Test.Test",
                                     Span {
                                         lo: 5,
                                         hi: 9,
@@ -707,6 +880,7 @@ fn internal_operation() {
                             ),
                         ),
                     ),
+                    position_encoding: Utf16,
                 },
             ),
         ]
@@ -729,7 +903,11 @@ fn operation_with_non_qubit_args() {
     );
 
     let circ_err = interpreter
-        .circuit(CircuitEntryPoint::Operation("Test.Test".into()))
+        .circuit(
+            CircuitEntryPoint::Operation("Test.Test".into()),
+            true,
+            false,
+        )
         .expect_err("circuit generation should fail");
 
     expect![[r"
@@ -740,6 +918,199 @@ fn operation_with_non_qubit_args() {
     .assert_debug_eq(&circ_err);
 }
 
+#[test]
+fn adjacent_inverse_gates_cancel() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    H(q0);
+                    X(q1);
+                    CNOT(q0, q1);
+                    CNOT(q0, q1);
+                    X(q1);
+                    S(q0);
+                    Adjoint S(q0);
+                    Y(q0);
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0    ── H ──── Y ──
+        q_1    ──────────────
+    "]]
+    .assert_eq(&circ.to_string());
+}
+
+#[test]
+fn consecutive_rotations_merge() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    Rz(0.25, q);
+                    Rz(0.5, q);
+                    Rx(0.5, q);
+                    Rx(-0.5, q);
+                    Rz(0.25, q);
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0     rz(1.0000)
+    "]]
+    .assert_eq(&circ.to_string());
+}
+
+#[test]
+fn gates_separated_on_the_same_qubit_do_not_cancel() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use (q0, q1) = (Qubit(), Qubit());
+                    X(q0);
+                    CNOT(q0, q1);
+                    X(q0);
+                    H(q1);
+                    CNOT(q1, q0);
+                    H(q1);
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0    ── X ──── ● ──── X ──── X ─────────
+        q_1    ───────── X ──── H ──── ● ──── H ──
+    "]]
+    .assert_eq(&circ.to_string());
+}
+
+#[test]
+fn unoptimized_circuit_keeps_gate_sequence() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    H(q);
+                    H(q);
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, false, false)
+        .expect("circuit generation should succeed");
+
+    expect![[r"
+        q_0    ── H ──── H ──
+    "]]
+    .assert_eq(&circ.to_string());
+}
+
+#[test]
+fn circuit_includes_metadata() {
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    H(q);
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+    interpreter.set_metadata([("experiment".to_string(), "bell-1".to_string())].into());
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, true, false)
+        .expect("circuit generation should succeed");
+
+    expect![[r#"
+        {
+            "experiment": "bell-1",
+        }
+    "#]]
+    .assert_debug_eq(&circ.metadata);
+}
+
+#[test]
+fn operations_record_source_locations() {
+    use std::fmt::Write;
+
+    let mut interpreter = interpreter(
+        r"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Result {
+                    use qs = Qubit[2];
+                    H(qs[0]);
+                    ApplyToEach(X, qs);
+                    M(qs[1])
+                }
+            }
+        ",
+        Profile::Unrestricted,
+    );
+
+    let circ = interpreter
+        .circuit(CircuitEntryPoint::EntryPoint, false, false)
+        .expect("circuit generation should succeed");
+
+    // Gates applied by library operations are attributed to the call from user code.
+    let sources = circ
+        .operations
+        .iter()
+        .fold(String::new(), |mut sources, op| {
+            let source = op.source.as_ref().expect("operation should have a source");
+            writeln!(
+                sources,
+                "{} {}:{}:{} {}",
+                op.gate, source.file, source.line, source.column, source.callable
+            )
+            .expect("writing to string should succeed");
+            sources
+        });
+    expect![[r"
+        H test.qs:5:20 H
+        X test.qs:6:20 ApplyToEach
+        X test.qs:6:20 ApplyToEach
+        Measure test.qs:7:20 M
+    "]]
+    .assert_eq(&sources);
+}
+
 /// Tests that invoke circuit generation throught the debugger.
 mod debugger_stepping {
     use super::Debugger;
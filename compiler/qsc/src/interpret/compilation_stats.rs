@@ -0,0 +1,92 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use qsc_fir::fir::{self, PackageId};
+use qsc_rca::{Analyzer, ComputeKind, ItemComputeProperties};
+use std::time::{Duration, Instant};
+
+/// The size of a package of an interpreter, to see what a compilation spends its time on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PackageStats {
+    /// The role of the package: `core`, `std` or `source` for the sources the interpreter was
+    /// created with, and `fragments` for the code evaluated since.
+    pub name: &'static str,
+    pub items: usize,
+    pub callables: usize,
+    pub blocks: usize,
+    pub stmts: usize,
+    pub exprs: usize,
+}
+
+/// The result of running the runtime capabilities analysis over the packages of an interpreter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RcaStats {
+    /// The time the analysis of all of the packages took.
+    pub duration: Duration,
+    /// The number of callables of the source package.
+    pub callables: usize,
+    /// The number of callables of the source package whose body is quantum.
+    pub quantum_callables: usize,
+    /// The number of callables of the source package whose body uses runtime features, and so
+    /// can't run on targets with the base profile.
+    pub runtime_feature_callables: usize,
+}
+
+pub(super) fn package_stats(
+    fir_store: &fir::PackageStore,
+    source_package: PackageId,
+    package: PackageId,
+) -> Vec<PackageStats> {
+    fir_store
+        .iter()
+        .map(|(id, unit)| {
+            let name = if id == PackageId::CORE {
+                "core"
+            } else if id == source_package {
+                "source"
+            } else if id == package {
+                "fragments"
+            } else {
+                "std"
+            };
+            PackageStats {
+                name,
+                items: unit.items.iter().count(),
+                callables: unit
+                    .items
+                    .values()
+                    .filter(|item| matches!(item.kind, fir::ItemKind::Callable(_)))
+                    .count(),
+                blocks: unit.blocks.iter().count(),
+                stmts: unit.stmts.iter().count(),
+                exprs: unit.exprs.iter().count(),
+            }
+        })
+        .collect()
+}
+
+pub(super) fn rca_stats(fir_store: &fir::PackageStore, source_package: PackageId) -> RcaStats {
+    let start = Instant::now();
+    let compute_properties = Analyzer::init(fir_store).analyze_all();
+    let duration = start.elapsed();
+
+    let mut stats = RcaStats {
+        duration,
+        callables: 0,
+        quantum_callables: 0,
+        runtime_feature_callables: 0,
+    };
+    for item in compute_properties.get(source_package).items.values() {
+        let ItemComputeProperties::Callable(callable) = item else {
+            continue;
+        };
+        stats.callables += 1;
+        if let ComputeKind::Quantum(properties) = &callable.body.inherent {
+            stats.quantum_callables += 1;
+            if !properties.runtime_features.is_empty() {
+                stats.runtime_feature_callables += 1;
+            }
+        }
+    }
+    stats
+}
@@ -4,15 +4,36 @@
 #[cfg(test)]
 mod tests;
 
+use crate::error::CallFrame;
+use qsc_data_structures::span::Span;
 use qsc_eval::debug::{map_fir_package_to_hir, Frame};
-use qsc_fir::fir::{Global, PackageStoreLookup, StoreItemId};
-use qsc_frontend::compile::PackageStore;
+use qsc_fir::fir::{Global, Item, ItemKind, PackageStoreLookup, StoreItemId};
+use qsc_frontend::compile::{PackageStore, SourceMap};
 use qsc_hir::hir;
-use qsc_hir::hir::{Item, ItemKind};
+use rustc_hash::FxHashMap;
+
+/// Looks up the sources of the compiled packages, which the call stacks and errors of a run
+/// refer to.
+pub(crate) trait SourcesLookup {
+    /// Gets the sources of a package.
+    fn get_sources(&self, package: hir::PackageId) -> Option<&SourceMap>;
+}
+
+impl SourcesLookup for PackageStore {
+    fn get_sources(&self, package: hir::PackageId) -> Option<&SourceMap> {
+        self.get(package).map(|unit| &unit.sources)
+    }
+}
+
+impl SourcesLookup for FxHashMap<hir::PackageId, SourceMap> {
+    fn get_sources(&self, package: hir::PackageId) -> Option<&SourceMap> {
+        self.get(&package)
+    }
+}
 
 #[must_use]
 pub(crate) fn format_call_stack(
-    store: &PackageStore,
+    sources: &impl SourcesLookup,
     globals: &impl PackageStoreLookup,
     frames: Vec<Frame>,
     error: &dyn std::error::Error,
@@ -24,60 +45,111 @@ pub(crate) fn format_call_stack(
     let mut frames = frames;
     frames.reverse();
 
+    // Deep recursion repeats the same frame many times, so consecutive repeats are collapsed
+    // into a single line.
+    let mut previous = String::new();
+    let mut repeats = 0;
     for frame in frames {
         let Some(Global::Callable(call)) = globals.get_global(frame.id) else {
             panic!("missing global");
         };
 
-        trace.push_str("    at ");
+        let mut line = "    at ".to_string();
         if frame.functor.adjoint {
-            trace.push_str("Adjoint ");
+            line.push_str("Adjoint ");
         }
         if frame.functor.controlled > 0 {
-            trace.push_str(&format!("Controlled({}) ", frame.functor.controlled));
+            line.push_str(&format!("Controlled({}) ", frame.functor.controlled));
         }
-        if let Some(item) = get_item_parent(store, frame.id) {
-            if let Some(ns) = get_ns_name(&item) {
-                trace.push_str(&format!("{ns}."));
+        if let Some(item) = get_item_parent(globals, frame.id) {
+            if let Some(ns) = get_ns_name(item) {
+                line.push_str(&format!("{ns}."));
             }
         }
-        trace.push_str(&format!("{}", call.name.name));
+        line.push_str(&format!("{}", call.name.name));
 
-        let name = get_item_file_name(store, frame.id);
-        trace.push_str(&format!(
+        let name = get_item_file_name(sources, globals, frame.id);
+        line.push_str(&format!(
             " in {}",
             name.unwrap_or("<expression>".to_string())
         ));
 
-        trace.push('\n');
+        line.push('\n');
+        if line == previous {
+            repeats += 1;
+        } else {
+            push_repeats(&mut trace, repeats);
+            repeats = 0;
+            trace.push_str(&line);
+            previous = line;
+        }
     }
+    push_repeats(&mut trace, repeats);
     trace
 }
 
+/// Resolves the frames of a call stack into the frames reported with a runtime error, innermost
+/// first like the formatted call stack.
 #[must_use]
-fn get_item_parent(store: &PackageStore, id: StoreItemId) -> Option<Item> {
-    let package = map_fir_package_to_hir(id.package);
-    let item = hir::LocalItemId::from(usize::from(id.item));
-    store.get(package).and_then(|unit| {
-        let item = unit.package.items.get(item)?;
-        if let Some(parent) = item.parent {
-            let parent = unit.package.items.get(parent)?;
-            Some(parent.clone())
-        } else {
-            None
-        }
-    })
+pub(crate) fn call_frames(
+    sources: &impl SourcesLookup,
+    globals: &impl PackageStoreLookup,
+    frames: &[Frame],
+) -> Vec<CallFrame> {
+    frames
+        .iter()
+        .rev()
+        .map(|frame| {
+            let Some(Global::Callable(call)) = globals.get_global(frame.id) else {
+                panic!("missing global");
+            };
+            let name = match get_item_parent(globals, frame.id).and_then(get_ns_name) {
+                Some(ns) => format!("{ns}.{}", call.name.name),
+                None => call.name.name.to_string(),
+            };
+            // Calls without a span are generated, such as the release of qubits.
+            let call_site = (frame.span != Span::default())
+                .then(|| sources.get_sources(map_fir_package_to_hir(frame.caller)))
+                .flatten()
+                .and_then(|sources| sources.find_by_offset(frame.span.lo))
+                .map(|source| {
+                    (
+                        source.name.clone(),
+                        source.contents.clone(),
+                        frame.span - source.offset,
+                    )
+                });
+            CallFrame::new(name, frame.functor.to_string(), call_site)
+        })
+        .collect()
+}
+
+fn push_repeats(trace: &mut String, repeats: usize) {
+    if repeats > 0 {
+        trace.push_str(&format!("    ... repeated {repeats} more times\n"));
+    }
 }
 
 #[must_use]
-fn get_item_file_name(store: &PackageStore, id: StoreItemId) -> Option<String> {
-    let package = map_fir_package_to_hir(id.package);
-    let item = hir::LocalItemId::from(usize::from(id.item));
-    store.get(package).and_then(|unit| {
-        let item = unit.package.items.get(item)?;
-        let source = unit.sources.find_by_offset(item.span.lo);
-        source.map(|s| s.name.to_string())
-    })
+fn get_item_parent(globals: &impl PackageStoreLookup, id: StoreItemId) -> Option<&Item> {
+    let parent = globals.get_item(id).parent?;
+    Some(globals.get_item(StoreItemId {
+        package: id.package,
+        item: parent,
+    }))
+}
+
+#[must_use]
+fn get_item_file_name(
+    sources: &impl SourcesLookup,
+    globals: &impl PackageStoreLookup,
+    id: StoreItemId,
+) -> Option<String> {
+    let item = globals.get_item(id);
+    let source = sources
+        .get_sources(map_fir_package_to_hir(id.package))?
+        .find_by_offset(item.span.lo);
+    source.map(|s| s.name.to_string())
 }
 
 #[must_use]
@@ -5,13 +5,13 @@
 
 use indoc::indoc;
 use miette::Result;
-use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_data_structures::{language_features::LanguageFeatures, line_column::Encoding};
 use qsc_eval::{output::CursorReceiver, val::Value};
 use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
 use qsc_passes::PackageType;
 use std::io::Cursor;
 
-use crate::interpret::{Error, InterpretResult, Interpreter};
+use crate::interpret::{Error, InterpretResult, Interpreter, Limits};
 
 fn line(interpreter: &mut Interpreter, line: impl AsRef<str>) -> (InterpretResult, String) {
     let mut cursor = Cursor::new(Vec::<u8>::new());
@@ -170,3 +170,102 @@ fn stack_traces_can_cross_file_and_entry_boundaries() {
         }
     }
 }
+
+#[test]
+fn stack_traces_collapse_repeated_frames() {
+    let mut interpreter = Interpreter::new(
+        true,
+        SourceMap::default(),
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("Failed to compile base environment.");
+    interpreter.set_limits(Limits {
+        max_call_depth: Some(50),
+        ..Limits::default()
+    });
+
+    let (result, _) = line(
+        &mut interpreter,
+        "function Count(n : Int) : Int { n == 0 ? 0 | 1 + Count(n - 1) }",
+    );
+    result.expect("code should compile");
+
+    let (result, _output) = line(&mut interpreter, "Count(100)");
+
+    match result {
+        Ok(_) => panic!("Expected error"),
+        Err(e) => {
+            let stack_trace = e[0]
+                .stack_trace()
+                .as_ref()
+                .expect("code should have a valid stack trace");
+            assert!(stack_trace.starts_with(indoc! {r#"
+                Error: call depth limit exceeded: cannot nest more than 50 calls
+                Call stack:
+                    at Count in line_0
+                    ... repeated "#}));
+            assert!(stack_trace.ends_with(" more times\n"));
+            assert_eq!(stack_trace.lines().count(), 4);
+        }
+    }
+}
+
+#[test]
+fn runtime_errors_have_stack_frames() {
+    let source = indoc! { r#"
+        namespace Test {
+            operation A(input : Int) : Unit is Adj {
+                body ... {
+                    B(input)
+                }
+                adjoint self;
+            }
+
+            function B(input : Int) : Unit {
+                let _ = [1, 2][input];
+            }
+        }
+        "#};
+
+    let source_map = SourceMap::new([("1.qs".into(), source.into())], None);
+    let mut interpreter = Interpreter::new(
+        true,
+        source_map,
+        PackageType::Lib,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("Failed to compile base environment.");
+
+    let (result, _output) = line(&mut interpreter, "Adjoint Test.A(5)");
+
+    match result {
+        Ok(_) => panic!("Expected error"),
+        Err(e) => {
+            let frames = e[0]
+                .stack_frames()
+                .iter()
+                .map(|frame| {
+                    let location = frame
+                        .location(Encoding::Utf8)
+                        .expect("frame should have a location");
+                    (
+                        frame.name().to_string(),
+                        frame.functor().to_string(),
+                        location.source.to_string(),
+                        location.range.start.line,
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                frames,
+                [
+                    ("Test.B".into(), String::new(), "1.qs".into(), 3),
+                    ("Test.A".into(), "Adjoint".into(), "line_0".into(), 0),
+                ]
+            );
+        }
+    }
+}
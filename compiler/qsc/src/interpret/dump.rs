@@ -0,0 +1,112 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{into_errors, Error, Interpreter};
+use qsc_frontend::{compile::SourceMap, error::WithSource};
+use serde_json::{json, Value};
+use std::fmt::Display;
+
+/// The representation of a dump of an intermediate stage of the compiler.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// The pretty-printed tree, one node per line, with the children of a node indented below it.
+    #[default]
+    Text,
+    /// The same tree as a JSON array of the root nodes, where each node is an object with the
+    /// text of its line as `node` and its children as `children`.
+    Json,
+}
+
+impl Interpreter {
+    /// Parses the given source, which may contain namespaces and top-level statements, and dumps
+    /// its AST without resolving names or checking types.
+    /// # Errors
+    /// If the source has syntax errors, they are returned.
+    pub fn dump_ast(&self, source: &str, format: DumpFormat) -> Result<String, Vec<Error>> {
+        let sources = SourceMap::new([("<source>".into(), source.into())], None);
+        let (package, errors) = qsc_frontend::compile::parse(&sources, self.language_features);
+        if !errors.is_empty() {
+            return Err(into_errors(
+                errors
+                    .into_iter()
+                    .map(|error| WithSource::from_map(&sources, error.into()))
+                    .collect(),
+            ));
+        }
+
+        Ok(dump([&package], format))
+    }
+
+    /// Dumps the HIR of the source package followed by the HIR of the fragments and entry
+    /// expressions compiled since, after the passes have run.
+    #[must_use]
+    pub fn dump_hir(&self, format: DumpFormat) -> String {
+        let store = self.compiler.package_store();
+        let packages = [
+            self.compiler.source_package_id(),
+            self.compiler.package_id(),
+        ]
+        .map(|id| &store.get(id).expect("package should be in store").package);
+        dump(packages, format)
+    }
+
+    /// Dumps the FIR of the source package followed by the FIR of the fragments and entry
+    /// expressions compiled since, as they are evaluated.
+    #[must_use]
+    pub fn dump_fir(&self, format: DumpFormat) -> String {
+        let packages = [self.source_package, self.package].map(|id| self.fir_store.get(id));
+        dump(packages, format)
+    }
+}
+
+fn dump(packages: impl IntoIterator<Item = impl Display>, format: DumpFormat) -> String {
+    let text = packages
+        .into_iter()
+        .map(|package| package.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    match format {
+        DumpFormat::Text => text,
+        DumpFormat::Json => Value::Array(to_tree(&text)).to_string(),
+    }
+}
+
+/// Turns the indented lines of a pretty-printed tree into JSON nodes.
+fn to_tree(text: &str) -> Vec<Value> {
+    struct Node {
+        indent: usize,
+        label: String,
+        children: Vec<Value>,
+    }
+
+    fn close(node: Node) -> Value {
+        let Node {
+            label, children, ..
+        } = node;
+        json!({ "node": label, "children": children })
+    }
+
+    let mut roots = Vec::new();
+    let mut open: Vec<Node> = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let indent = line.len() - line.trim_start().len();
+        while open.last().is_some_and(|node| node.indent >= indent) {
+            let node = close(open.pop().expect("node should be open"));
+            open.last_mut()
+                .map_or(&mut roots, |parent| &mut parent.children)
+                .push(node);
+        }
+        open.push(Node {
+            indent,
+            label: line.trim().to_string(),
+            children: Vec::new(),
+        });
+    }
+    while let Some(node) = open.pop() {
+        let node = close(node);
+        open.last_mut()
+            .map_or(&mut roots, |parent| &mut parent.children)
+            .push(node);
+    }
+    roots
+}
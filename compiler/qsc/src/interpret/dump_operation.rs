@@ -0,0 +1,70 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use qsc_eval::output::{Error, Receiver};
+
+/// Generates the entry expression that prepares each target qubit in a Bell pair with an extra
+/// qubit, applies the operation to the targets and dumps the state. The amplitudes of the dump
+/// are then the entries of the matrix of the operation, scaled down by the square root of its
+/// dimension.
+pub(super) fn entry_expr(operation: &str, num_qubits: usize) -> String {
+    format!(
+        "{{
+            let op = {operation};
+            use (targets, extra) = (Qubit[{num_qubits}], Qubit[{num_qubits}]);
+            for i in 0..{num_qubits} - 1 {{
+                H(targets[i]);
+                CNOT(targets[i], extra[i]);
+            }}
+            operation ApplyOp(op : (Qubit[] => Unit), targets : Qubit[]) : Unit {{ op(targets); }}
+            ApplyOp(op, targets);
+            Microsoft.Quantum.Diagnostics.DumpMachine();
+            ResetAll(targets + extra);
+        }}"
+    )
+}
+
+/// Reads the matrix of the operation off the state dumped by the entry expression, where the
+/// targets make up the high bits of each basis state and select the row, and the extra qubits
+/// make up the low bits and select the column.
+pub(super) fn matrix_from_state(
+    state: Vec<(BigUint, Complex64)>,
+    num_qubits: usize,
+) -> Vec<Vec<Complex64>> {
+    let dimension = 1 << num_qubits;
+    #[allow(clippy::cast_precision_loss)]
+    let factor = (dimension as f64).sqrt();
+    let mut matrix = vec![vec![Complex64::default(); dimension]; dimension];
+    for (id, amplitude) in state {
+        let id = usize::try_from(&id).expect("basis state should fit in usize");
+        matrix[id >> num_qubits][id & (dimension - 1)] = amplitude * factor;
+    }
+    matrix
+}
+
+/// A receiver that keeps the last state dumped and discards any other output.
+#[derive(Default)]
+pub(super) struct StateCapture {
+    pub state: Option<Vec<(BigUint, Complex64)>>,
+}
+
+impl Receiver for StateCapture {
+    fn state(
+        &mut self,
+        state: Vec<(BigUint, Complex64)>,
+        _qubit_count: usize,
+    ) -> Result<(), Error> {
+        self.state = Some(state);
+        Ok(())
+    }
+
+    fn matrix(&mut self, _matrix: Vec<Vec<Complex64>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn message(&mut self, _msg: &str) -> Result<(), Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use num_bigint::BigInt;
+use qsc_eval::val::Value;
+use qsc_hir::{
+    hir::{Attr, ItemKind, Package, Pat, PatKind},
+    ty::{Prim, Ty},
+};
+use std::{fmt::Write, rc::Rc};
+
+/// A callable with the `@EntryPoint()` attribute, along with the parameters it takes.
+pub(super) struct EntryPoint {
+    /// The name the callable is called by, which is namespace-qualified unless the callable was
+    /// declared at the top level of a fragment.
+    pub name: String,
+    pub params: Vec<(String, Ty)>,
+}
+
+/// The callables with the `@EntryPoint()` attribute in the package. Callables declared inside
+/// other callables can't be called by name and are skipped.
+pub(super) fn find_entry_points(package: &Package) -> Vec<EntryPoint> {
+    package
+        .items
+        .values()
+        .filter(|item| item.attrs.contains(&Attr::EntryPoint))
+        .filter_map(|item| {
+            let ItemKind::Callable(decl) = &item.kind else {
+                return None;
+            };
+            let name = match item.parent {
+                None => decl.name.name.to_string(),
+                Some(parent) => match &package.items.get(parent)?.kind {
+                    ItemKind::Namespace(namespace, _) => {
+                        format!("{}.{}", namespace.name, decl.name.name)
+                    }
+                    _ => return None,
+                },
+            };
+            Some(EntryPoint {
+                name,
+                params: params(&decl.input),
+            })
+        })
+        .collect()
+}
+
+fn params(input: &Pat) -> Vec<(String, Ty)> {
+    match &input.kind {
+        PatKind::Tuple(items) => items.iter().map(param).collect(),
+        _ => vec![param(input)],
+    }
+}
+
+fn param(pat: &Pat) -> (String, Ty) {
+    let name = match &pat.kind {
+        PatKind::Bind(ident) => ident.name.to_string(),
+        PatKind::Discard | PatKind::Tuple(_) | PatKind::Err => "_".to_string(),
+    };
+    (name, pat.ty.clone())
+}
+
+/// Checks the argument against the type of its parameter, returning the argument as a value of
+/// that type. Integers are widened to big integers where a `BigInt` is expected. Doubles that
+/// aren't finite have no literal to pass them with, so they are rejected.
+pub(super) fn bind_arg(arg: Value, ty: &Ty) -> Option<Value> {
+    match (arg, ty) {
+        (Value::Double(value), _) if !value.is_finite() => None,
+        (Value::Int(value), Ty::Prim(Prim::BigInt)) => Some(Value::BigInt(BigInt::from(value))),
+        (
+            arg @ (Value::BigInt(_)
+            | Value::Bool(_)
+            | Value::Double(_)
+            | Value::Int(_)
+            | Value::Pauli(_)
+            | Value::Result(_)
+            | Value::String(_)),
+            Ty::Prim(prim),
+        ) if prim_of(&arg) == Some(*prim) => Some(arg),
+        (Value::Range(range), Ty::Prim(Prim::Range)) => Some(Value::Range(range)),
+        (Value::Array(items), Ty::Array(item_ty)) => {
+            let items = items
+                .iter()
+                .map(|item| bind_arg(item.clone(), item_ty))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Value::Array(Rc::new(items)))
+        }
+        (Value::Tuple(items), Ty::Tuple(item_tys)) if items.len() == item_tys.len() => {
+            let items = items
+                .iter()
+                .zip(item_tys)
+                .map(|(item, item_ty)| bind_arg(item.clone(), item_ty))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Value::Tuple(items.into()))
+        }
+        _ => None,
+    }
+}
+
+fn prim_of(value: &Value) -> Option<Prim> {
+    match value {
+        Value::BigInt(_) => Some(Prim::BigInt),
+        Value::Bool(_) => Some(Prim::Bool),
+        Value::Double(_) => Some(Prim::Double),
+        Value::Int(_) => Some(Prim::Int),
+        Value::Pauli(_) => Some(Prim::Pauli),
+        Value::Result(_) => Some(Prim::Result),
+        Value::String(_) => Some(Prim::String),
+        _ => None,
+    }
+}
+
+/// Generates the expression that calls the callable with the bound arguments.
+pub(super) fn entry_expr(name: &str, args: &[Value]) -> String {
+    let mut expr = format!("{name}(");
+    for (index, arg) in args.iter().enumerate() {
+        if index > 0 {
+            expr.push_str(", ");
+        }
+        write_literal(&mut expr, arg);
+    }
+    expr.push(')');
+    expr
+}
+
+/// Writes the value as a Q# literal. Only values that [`bind_arg`] accepts are expected.
+fn write_literal(out: &mut String, value: &Value) {
+    match value {
+        Value::BigInt(value) => {
+            let _ = write!(out, "{value}L");
+        }
+        Value::String(value) => {
+            out.push('"');
+            for c in value.chars() {
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    '\n' => out.push_str("\\n"),
+                    '\r' => out.push_str("\\r"),
+                    '\t' => out.push_str("\\t"),
+                    c => out.push(c),
+                }
+            }
+            out.push('"');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(", ");
+                }
+                write_literal(out, item);
+            }
+            out.push(']');
+        }
+        Value::Tuple(items) => {
+            out.push('(');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(", ");
+                }
+                write_literal(out, item);
+            }
+            if items.len() == 1 {
+                out.push(',');
+            }
+            out.push(')');
+        }
+        value => {
+            let _ = write!(out, "{value}");
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::shots::shot_seed;
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use rustc_hash::FxHashMap;
+use std::{f64::consts::TAU, fmt::Write};
+
+/// The largest difference between amplitudes that are considered equal, which absorbs the
+/// rounding errors of the simulator.
+const TOLERANCE: f64 = 1e-6;
+
+/// How two operations are compared when checking whether they are equivalent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EquivalenceMethod {
+    /// Compares the full matrices of the operations, which takes memory exponential in twice
+    /// the number of qubits.
+    Matrix,
+    /// Applies both operations to the given number of random states and compares the resulting
+    /// states, which only takes memory exponential in the number of qubits. Operations that
+    /// differ are told apart with high probability.
+    Sampling(u32),
+}
+
+/// Whether the matrices are equal up to a global phase, which is read off the largest entry of
+/// the first matrix.
+pub(super) fn matrices_equivalent(a: &[Vec<Complex64>], b: &[Vec<Complex64>]) -> bool {
+    let entries = || a.iter().flatten().zip(b.iter().flatten());
+    let Some((a_max, b_max)) = entries().max_by(|(x, _), (y, _)| x.norm().total_cmp(&y.norm()))
+    else {
+        return true;
+    };
+    if b_max.norm() < TOLERANCE {
+        return false;
+    }
+    let phase = b_max / a_max;
+    let phase = phase / phase.norm();
+    entries().all(|(a, b)| (b - phase * a).norm() < TOLERANCE)
+}
+
+/// The angles of the rotations that prepare the random state of the given sample, two per
+/// qubit for each of the two layers of rotations.
+pub(super) fn random_angles(seed: u64, sample: u32, num_qubits: usize) -> Vec<f64> {
+    let seed = shot_seed(seed, sample);
+    (0..4 * num_qubits)
+        .map(|index| {
+            let bits = shot_seed(seed, u32::try_from(index).expect("index should fit in u32"));
+            // The top 53 bits make up a uniformly distributed double in [0, 1).
+            #[allow(clippy::cast_precision_loss)]
+            let unit = (bits >> 11) as f64 / (1u64 << 53) as f64;
+            unit * TAU
+        })
+        .collect()
+}
+
+/// Generates the entry expression that prepares a random state with a layer of rotations, a
+/// chain of `CNOT` gates and another layer of rotations, applies the operation to it and dumps
+/// the resulting state.
+pub(super) fn random_state_expr(operation: &str, num_qubits: usize, angles: &[f64]) -> String {
+    let angles = angles.iter().fold(String::new(), |mut output, angle| {
+        if !output.is_empty() {
+            output.push_str(", ");
+        }
+        let _ = write!(output, "{angle:.17}");
+        output
+    });
+    format!(
+        "{{
+            let op = {operation};
+            let angles = [{angles}];
+            use qs = Qubit[{num_qubits}];
+            for i in 0..{num_qubits} - 1 {{
+                Ry(angles[4 * i], qs[i]);
+                Rz(angles[4 * i + 1], qs[i]);
+            }}
+            for i in 0..{num_qubits} - 2 {{
+                CNOT(qs[i], qs[i + 1]);
+            }}
+            for i in 0..{num_qubits} - 1 {{
+                Ry(angles[4 * i + 2], qs[i]);
+                Rz(angles[4 * i + 3], qs[i]);
+            }}
+            operation ApplyOp(op : (Qubit[] => Unit), targets : Qubit[]) : Unit {{ op(targets); }}
+            ApplyOp(op, qs);
+            Microsoft.Quantum.Diagnostics.DumpMachine();
+            ResetAll(qs);
+        }}"
+    )
+}
+
+/// The inner product of the two states.
+pub(super) fn overlap(a: &[(BigUint, Complex64)], b: &[(BigUint, Complex64)]) -> Complex64 {
+    let a = a.iter().cloned().collect::<FxHashMap<_, _>>();
+    b.iter()
+        .filter_map(|(id, amplitude)| a.get(id).map(|other| other.conj() * amplitude))
+        .sum()
+}
+
+/// Whether the overlaps of the states produced by two operations show that the operations are
+/// equal up to a global phase. Each state must be the same up to a phase, and the phase must be
+/// the same for every state, or the operations differ by a relative phase.
+pub(super) fn overlaps_equivalent(overlaps: &[Complex64]) -> bool {
+    let Some(first) = overlaps.first() else {
+        return true;
+    };
+    overlaps.iter().all(|overlap| {
+        (overlap.norm() - 1.0).abs() < TOLERANCE && (overlap - first).norm() < TOLERANCE
+    })
+}
@@ -0,0 +1,224 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_eval::{backend::Backend, val::Value, PackageSpan};
+use qsc_fir::fir::CustomIntrinsic;
+use std::rc::Rc;
+
+/// The measurement outcomes of a run, along with the seeds it ran with. Running the same program
+/// with a recording replays the run, so that a probabilistic failure observed once can be
+/// reproduced.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// The seed of the simulator.
+    pub quantum_seed: u64,
+    /// The seed of the classical random number generator.
+    pub classical_seed: u64,
+    /// The outcome of every measurement in the order they were made, where `true` is `One`.
+    pub measurements: Vec<bool>,
+}
+
+/// A backend that records the outcomes of the measurements made on the wrapped backend, and
+/// compares them with the outcomes of a previous run when replaying it.
+pub(super) struct Recorder<'a, B> {
+    pub backend: B,
+    pub measurements: Vec<bool>,
+    /// The qubit of each of the measurements.
+    pub qubits: Vec<usize>,
+    /// The index of the first measurement whose outcome differs from the replayed recording.
+    pub diverged_at: Option<usize>,
+    replay: Option<&'a [bool]>,
+}
+
+impl<'a, B: Backend<ResultType = bool>> Recorder<'a, B> {
+    pub fn new(backend: B, replay: Option<&'a [bool]>) -> Self {
+        Self {
+            backend,
+            measurements: Vec::new(),
+            qubits: Vec::new(),
+            diverged_at: None,
+            replay,
+        }
+    }
+
+    /// The index of the first measurement that differs from the replayed recording, including
+    /// measurements that are missing from either run.
+    pub fn divergence(&self) -> Option<usize> {
+        let replay = self.replay?;
+        self.diverged_at.or_else(|| {
+            (self.measurements.len() != replay.len())
+                .then(|| self.measurements.len().min(replay.len()))
+        })
+    }
+
+    fn record(&mut self, q: usize, outcome: bool) -> bool {
+        if let Some(replay) = self.replay {
+            if self.diverged_at.is_none() && replay.get(self.measurements.len()) != Some(&outcome) {
+                self.diverged_at = Some(self.measurements.len());
+            }
+        }
+        self.measurements.push(outcome);
+        self.qubits.push(q);
+        outcome
+    }
+}
+
+impl<B: Backend<ResultType = bool>> Backend for Recorder<'_, B> {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.backend.ccx(ctl0, ctl1, q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.backend.cx(ctl, q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.backend.cy(ctl, q);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.backend.cz(ctl, q);
+    }
+
+    fn h(&mut self, q: usize) {
+        self.backend.h(q);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        let outcome = self.backend.m(q);
+        self.record(q, outcome)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        let outcome = self.backend.mresetz(q);
+        self.record(q, outcome)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.backend.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.backend.rx(theta, q);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rxx(theta, q0, q1);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.backend.ry(theta, q);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.ryy(theta, q0, q1);
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.backend.rz(theta, q);
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.backend.rzz(theta, q0, q1);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.backend.sadj(q);
+    }
+
+    fn s(&mut self, q: usize) {
+        self.backend.s(q);
+    }
+
+    fn sx(&mut self, q: usize) {
+        self.backend.sx(q);
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.backend.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.backend.tadj(q);
+    }
+
+    fn t(&mut self, q: usize) {
+        self.backend.t(q);
+    }
+
+    fn x(&mut self, q: usize) {
+        self.backend.x(q);
+    }
+
+    fn y(&mut self, q: usize) {
+        self.backend.y(q);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.backend.z(q);
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.backend.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.backend.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.backend.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.backend.qubit_is_zero(q)
+    }
+
+    fn qubit_borrow(&mut self) -> usize {
+        self.backend.qubit_borrow()
+    }
+
+    fn qubit_return(&mut self, q: usize) -> bool {
+        self.backend.qubit_return(q)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.backend.custom_intrinsic(name, arg)
+    }
+
+    fn target_intrinsic(
+        &mut self,
+        intrinsic: &CustomIntrinsic,
+        arg: Value,
+    ) -> Option<Result<Value, String>> {
+        self.backend.target_intrinsic(intrinsic, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn supports_conditionals(&self) -> bool {
+        self.backend.supports_conditionals()
+    }
+
+    fn begin_conditional(&mut self, result: usize, value: bool) {
+        self.backend.begin_conditional(result, value);
+    }
+
+    fn end_conditional(&mut self) {
+        self.backend.end_conditional();
+    }
+
+    fn records_call_sites(&self) -> bool {
+        self.backend.records_call_sites()
+    }
+
+    fn set_call_site(&mut self, span: PackageSpan, callable: Rc<str>) {
+        self.backend.set_call_site(span, callable);
+    }
+}
@@ -0,0 +1,325 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{eval, replay::Recorder, test_runner, Error, Interpreter};
+use qsc_eval::{
+    backend::{Backend, ForcedOutcomes, Simulator, SimulatorKind},
+    output::GenericReceiver,
+    Env, Limits,
+};
+use qsc_fir::fir::{self, ExecGraphNode, PackageId};
+use qsc_frontend::compile::SourceMap;
+use qsc_hir::hir;
+use rustc_hash::FxHashMap;
+use std::{collections::BTreeMap, num::NonZeroUsize, sync::Arc, thread};
+
+/// The aggregated results of running an entry expression for a number of shots.
+#[derive(Clone, Debug, Default)]
+pub struct Shots {
+    /// The seed the seeds of the shots were derived from.
+    pub seed: u64,
+    /// The number of shots that returned each value, keyed by the displayed value.
+    pub counts: BTreeMap<String, u32>,
+    /// The shots that failed, in shot order.
+    pub failures: Vec<ShotFailure>,
+    /// The statistics of the measurement outcomes of the shots that succeeded, if the runner
+    /// records them.
+    pub measurement_statistics: Option<MeasurementStatistics>,
+}
+
+/// Statistics of the outcome of each measured qubit over a number of shots, where the outcome of
+/// a qubit in a shot is that of the last measurement of the qubit in the shot. Qubits are
+/// identified by the ids the simulator allocated them with, which are reused once a qubit is
+/// released.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeasurementStatistics {
+    /// The ids of the qubits measured in any of the shots, in increasing order.
+    pub qubits: Vec<usize>,
+    /// The number of shots in which each qubit was measured.
+    pub shots: Vec<u32>,
+    /// The probability that each qubit is measured as `One`, over the shots that measure it.
+    pub marginals: Vec<f64>,
+    /// The probability that both qubits of each pair are measured as `One`, over the shots that
+    /// measure both, or NaN if no shot does.
+    pub joint: Vec<Vec<f64>>,
+    /// The Pearson correlation of the outcomes of each pair of qubits, over the shots that
+    /// measure both, or NaN if either qubit always has the same outcome in those shots.
+    pub correlations: Vec<Vec<f64>>,
+}
+
+impl MeasurementStatistics {
+    /// Computes the statistics from the outcome of each qubit measured in each shot.
+    fn new(shots: &[Vec<(usize, bool)>]) -> Self {
+        let mut qubits = shots
+            .iter()
+            .flat_map(|outcomes| outcomes.iter().map(|(q, _)| *q))
+            .collect::<Vec<_>>();
+        qubits.sort_unstable();
+        qubits.dedup();
+
+        // For each pair of qubits, the number of shots that measure both, and of those, the
+        // number where the first qubit is `One` and where both are.
+        let n = qubits.len();
+        let mut both_measured = vec![vec![0u32; n]; n];
+        let mut first_one = vec![vec![0u32; n]; n];
+        let mut both_one = vec![vec![0u32; n]; n];
+        for outcomes in shots {
+            let outcomes = outcomes
+                .iter()
+                .map(|(q, outcome)| {
+                    let index = qubits.binary_search(q).expect("qubit should be collected");
+                    (index, *outcome)
+                })
+                .collect::<Vec<_>>();
+            for &(i, outcome_i) in &outcomes {
+                for &(j, outcome_j) in &outcomes {
+                    both_measured[i][j] += 1;
+                    first_one[i][j] += u32::from(outcome_i);
+                    both_one[i][j] += u32::from(outcome_i && outcome_j);
+                }
+            }
+        }
+
+        let shots = (0..n).map(|i| both_measured[i][i]).collect::<Vec<_>>();
+        let marginals = (0..n)
+            .map(|i| f64::from(first_one[i][i]) / f64::from(shots[i]))
+            .collect();
+        let joint = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| f64::from(both_one[i][j]) / f64::from(both_measured[i][j]))
+                    .collect()
+            })
+            .collect();
+        let correlations = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        let count = f64::from(both_measured[i][j]);
+                        let ones_first = f64::from(first_one[i][j]);
+                        let ones_second = f64::from(first_one[j][i]);
+                        let covariance =
+                            count * f64::from(both_one[i][j]) - ones_first * ones_second;
+                        let variance =
+                            ones_first * (count - ones_first) * ones_second * (count - ones_second);
+                        if variance > 0.0 {
+                            covariance / variance.sqrt()
+                        } else {
+                            f64::NAN
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            qubits,
+            shots,
+            marginals,
+            joint,
+            correlations,
+        }
+    }
+}
+
+/// The displayed value a shot returned along with the outcome of each qubit it measured, or the
+/// errors that failed it.
+type ShotResult = std::result::Result<(String, Vec<(usize, bool)>), Vec<Error>>;
+
+/// A shot that failed at runtime.
+#[derive(Clone, Debug)]
+pub struct ShotFailure {
+    /// The index of the shot.
+    pub shot: u32,
+    /// The seed of the simulator and of the classical random number generator, which reproduces
+    /// the shot when set on the interpreter.
+    pub seed: u64,
+    /// The errors that failed the shot.
+    pub errors: Vec<Error>,
+}
+
+/// Runs the shots of an entry expression on several threads, which share a copy of the compiled
+/// program of the interpreter the runner was created from. Each thread only creates its own
+/// simulator and environment for every shot.
+///
+/// Each shot runs with a seed derived from the seed of the runner and the index of the shot, so
+/// the results only depend on the seed and the number of shots, not on the number of threads.
+#[derive(Clone, Debug)]
+pub struct ShotRunner {
+    program: Arc<Program>,
+    seed: u64,
+    limits: Limits,
+    simulator: SimulatorKind,
+    forced_outcomes: ForcedOutcomes,
+    measurement_statistics: bool,
+}
+
+/// A compiled program along with the entry expression to run it with.
+#[derive(Debug)]
+struct Program {
+    /// The package of the entry expression.
+    package: PackageId,
+    fir_store: fir::PackageStore,
+    /// The sources of each package, which runtime errors refer to.
+    sources: FxHashMap<hir::PackageId, SourceMap>,
+    graph: Arc<[ExecGraphNode]>,
+}
+
+impl Interpreter {
+    /// Creates a runner for the shots of the given entry expression, with the program, seed,
+    /// limits, simulator and forced measurement outcomes of the interpreter. Unless a quantum
+    /// seed is set on the interpreter, a random one is used.
+    /// # Errors
+    /// If the entry expression fails to compile, an error is returned. Callbacks registered with
+    /// [`Interpreter::register_intrinsic`] or [`Interpreter::register_function`] can't be called
+    /// from other threads, so an error is also returned if any are registered.
+    pub fn shot_runner(&mut self, expr: &str) -> std::result::Result<ShotRunner, Vec<Error>> {
+        if !self.intrinsics.is_empty() {
+            return Err(vec![Error::ShotsWithCallbacks]);
+        }
+        let graph = self.compile_entry_expr(expr)?;
+        let mut fir_store = fir::PackageStore::new();
+        for (id, package) in &self.fir_store {
+            fir_store.insert(id, package.clone());
+        }
+        let sources = self
+            .compiler
+            .package_store()
+            .iter()
+            .map(|(id, unit)| (id, unit.sources.clone()))
+            .collect();
+        Ok(ShotRunner {
+            program: Arc::new(Program {
+                package: self.package,
+                fir_store,
+                sources,
+                graph: graph.into(),
+            }),
+            seed: self.quantum_seed.unwrap_or_else(test_runner::random_seed),
+            limits: self.limits,
+            simulator: self.simulator,
+            forced_outcomes: self.forced_outcomes.clone(),
+            measurement_statistics: false,
+        })
+    }
+
+    /// Runs the given entry expression for the given number of shots on up to the given number
+    /// of threads, each shot with a new instance of the environment and simulator.
+    /// # Errors
+    /// If the entry expression fails to compile, or callbacks are registered, an error is
+    /// returned.
+    pub fn run_shots(
+        &mut self,
+        expr: &str,
+        shots: u32,
+        workers: NonZeroUsize,
+    ) -> std::result::Result<Shots, Vec<Error>> {
+        Ok(self.shot_runner(expr)?.run(shots, workers))
+    }
+}
+
+impl ShotRunner {
+    /// Sets whether to record the outcome of each measured qubit in each shot and compute
+    /// [`MeasurementStatistics`] from them.
+    pub fn set_measurement_statistics(&mut self, enabled: bool) {
+        self.measurement_statistics = enabled;
+    }
+
+    /// Runs the shots on up to the given number of threads, distributing the shots among them in
+    /// turn. Output of the program, such as messages and state dumps, is discarded.
+    #[must_use]
+    pub fn run(&self, shots: u32, workers: NonZeroUsize) -> Shots {
+        let workers = u32::try_from(workers.get())
+            .unwrap_or(u32::MAX)
+            .clamp(1, shots.max(1));
+        let mut results = thread::scope(|scope| {
+            let handles = (0..workers)
+                .map(|worker| {
+                    scope.spawn(move || self.run_worker((worker..shots).step_by(workers as usize)))
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("shot worker should not panic"))
+                .collect::<Vec<_>>()
+        });
+        results.sort_by_key(|(shot, _)| *shot);
+
+        let mut aggregated = Shots {
+            seed: self.seed,
+            ..Shots::default()
+        };
+        let mut outcomes = vec![];
+        for (shot, result) in results {
+            match result {
+                Ok((value, shot_outcomes)) => {
+                    *aggregated.counts.entry(value).or_default() += 1;
+                    outcomes.push(shot_outcomes);
+                }
+                Err(errors) => aggregated.failures.push(ShotFailure {
+                    shot,
+                    seed: shot_seed(self.seed, shot),
+                    errors,
+                }),
+            }
+        }
+        if self.measurement_statistics {
+            aggregated.measurement_statistics = Some(MeasurementStatistics::new(&outcomes));
+        }
+        aggregated
+    }
+
+    fn run_worker(&self, shots: impl Iterator<Item = u32>) -> Vec<(u32, ShotResult)> {
+        let program = &*self.program;
+        let mut out = std::io::sink();
+        let mut receiver = GenericReceiver::new(&mut out);
+        shots
+            .map(|shot| {
+                let seed = shot_seed(self.seed, shot);
+                let mut simulator = Simulator::new(self.simulator);
+                simulator.set_forced_outcomes(self.forced_outcomes.clone());
+                let mut sim = Recorder::new(simulator, None);
+                sim.set_seed(Some(seed));
+                let result = eval(
+                    program.package,
+                    Some(seed),
+                    self.limits,
+                    program.graph.clone(),
+                    &program.sources,
+                    &program.fir_store,
+                    &mut Env::default(),
+                    &mut sim,
+                    &mut receiver,
+                );
+                (
+                    shot,
+                    result.map(|value| (value.to_string(), self.outcomes(&sim))),
+                )
+            })
+            .collect()
+    }
+
+    /// The outcome of the last measurement of each qubit measured in a shot, if the runner records
+    /// them.
+    fn outcomes<B>(&self, sim: &Recorder<'_, B>) -> Vec<(usize, bool)> {
+        if !self.measurement_statistics {
+            return Vec::new();
+        }
+        sim.qubits
+            .iter()
+            .copied()
+            .zip(sim.measurements.iter().copied())
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// The seed of the given shot, derived from the seed of the runner with the `SplitMix64`
+/// finalizer so that consecutive shots get unrelated random number streams.
+pub(super) fn shot_seed(seed: u64, shot: u32) -> u64 {
+    let mut z = seed.wrapping_add((u64::from(shot) + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
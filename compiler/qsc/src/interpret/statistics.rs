@@ -0,0 +1,381 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_circuit::{LifetimeTracker, QubitLifetime};
+use qsc_eval::{
+    backend::{Backend, SimulatorKind},
+    val::Value,
+    PackageSpan,
+};
+use qsc_fir::fir::CustomIntrinsic;
+use rustc_hash::FxHashSet;
+use std::rc::Rc;
+
+/// The most samples kept for a run. Once reached, every other sample is dropped and samples are
+/// taken half as often, so that long runs keep an even spread of samples at a bounded cost.
+const MAX_SAMPLES: usize = 1024;
+
+/// The resources the simulator used over a run, to anticipate whether a larger instance of a
+/// program will fit in memory.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunStatistics {
+    /// The largest number of qubits allocated at once.
+    pub qubit_high_water_mark: usize,
+    /// The largest number of qubits that held state at once. A qubit holds state from its first
+    /// operation until it is reset, such as after a measurement, or released, so this is the
+    /// number of qubits the run needs if reset qubits are reused for later allocations.
+    pub reused_qubit_high_water_mark: usize,
+    /// The largest number of nonzero amplitudes sampled.
+    pub peak_amplitudes: usize,
+    /// The estimated peak memory in bytes taken up by the state of the simulator.
+    pub peak_memory: usize,
+    /// The number of nonzero amplitudes over the run, sampled before the operations that
+    /// collapse the state, which is when it is usually largest.
+    pub samples: Vec<AmplitudeSample>,
+    /// The lifetime of each allocation of a qubit, in the order of allocation, with times that
+    /// count the operations applied before.
+    pub qubit_lifetimes: Vec<QubitLifetime>,
+}
+
+/// The size of the state of the simulator at a point in a run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AmplitudeSample {
+    /// The number of operations applied to the simulator before the sample was taken.
+    pub operation: u64,
+    /// The number of qubits allocated.
+    pub qubits: usize,
+    /// The number of nonzero amplitudes.
+    pub amplitudes: usize,
+}
+
+/// A backend that tracks the qubits allocated on the wrapped backend and samples the size of its
+/// state.
+pub(super) struct Monitor<B> {
+    pub backend: B,
+    kind: SimulatorKind,
+    operations: u64,
+    qubits: usize,
+    /// The qubits that have been operated on since they were allocated or last reset.
+    active: FxHashSet<usize>,
+    lifetimes: LifetimeTracker,
+    /// The number of times a sample could have been taken, of which every `stride`-th one is.
+    sample_points: u64,
+    next_sample: u64,
+    stride: u64,
+    statistics: RunStatistics,
+}
+
+impl<B: Backend> Monitor<B> {
+    pub fn new(backend: B, kind: SimulatorKind) -> Self {
+        Self {
+            backend,
+            kind,
+            operations: 0,
+            qubits: 0,
+            active: FxHashSet::default(),
+            lifetimes: LifetimeTracker::default(),
+            sample_points: 0,
+            next_sample: 1,
+            stride: 1,
+            statistics: RunStatistics::default(),
+        }
+    }
+
+    pub fn into_statistics(mut self) -> RunStatistics {
+        self.statistics.qubit_lifetimes = self.lifetimes.finish();
+        self.statistics
+    }
+
+    fn op<T>(&mut self, f: impl FnOnce(&mut B) -> T) -> T {
+        self.operations += 1;
+        f(&mut self.backend)
+    }
+
+    /// Marks the given qubits as holding state.
+    fn touch(&mut self, qubits: &[usize]) {
+        self.lifetimes.operate(qubits, self.operations);
+        self.active.extend(qubits);
+        let statistics = &mut self.statistics;
+        statistics.reused_qubit_high_water_mark = statistics
+            .reused_qubit_high_water_mark
+            .max(self.active.len());
+    }
+
+    /// Marks the given qubit as back in the zero state, so that it can be reused.
+    fn idle(&mut self, q: usize) {
+        self.active.remove(&q);
+    }
+
+    fn count_allocation(&mut self) {
+        self.qubits += 1;
+        let statistics = &mut self.statistics;
+        statistics.qubit_high_water_mark = statistics.qubit_high_water_mark.max(self.qubits);
+        // The dense simulator doubles its state on every allocation, whatever the amplitudes.
+        statistics.peak_memory = statistics
+            .peak_memory
+            .max(self.kind.estimated_memory(self.qubits, 1));
+    }
+
+    /// Samples the state before applying an operation that collapses it.
+    fn collapse<T>(&mut self, f: impl FnOnce(&mut B) -> T) -> T {
+        self.sample();
+        self.op(f)
+    }
+
+    fn sample(&mut self) {
+        self.sample_points += 1;
+        if self.sample_points < self.next_sample {
+            return;
+        }
+
+        let amplitudes = self.backend.capture_quantum_state().0.len();
+        let statistics = &mut self.statistics;
+        statistics.peak_amplitudes = statistics.peak_amplitudes.max(amplitudes);
+        statistics.peak_memory = statistics
+            .peak_memory
+            .max(self.kind.estimated_memory(self.qubits, amplitudes));
+        statistics.samples.push(AmplitudeSample {
+            operation: self.operations,
+            qubits: self.qubits,
+            amplitudes,
+        });
+
+        if statistics.samples.len() == MAX_SAMPLES {
+            // Keep the samples at the multiples of the new stride.
+            let mut index = 0;
+            statistics.samples.retain(|_| {
+                index += 1;
+                index % 2 == 0
+            });
+            self.stride *= 2;
+        }
+        self.next_sample = self.sample_points + self.stride;
+    }
+}
+
+impl<B: Backend> Backend for Monitor<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.touch(&[ctl0, ctl1, q]);
+        self.op(|b| b.ccx(ctl0, ctl1, q));
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.touch(&[ctl, q]);
+        self.op(|b| b.cx(ctl, q));
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        self.touch(&[ctl, q]);
+        self.op(|b| b.cy(ctl, q));
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.touch(&[ctl, q]);
+        self.op(|b| b.cz(ctl, q));
+    }
+
+    fn h(&mut self, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.h(q));
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.touch(&[q]);
+        self.collapse(|b| b.m(q))
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.touch(&[q]);
+        let result = self.collapse(|b| b.mresetz(q));
+        self.idle(q);
+        result
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.lifetimes.operate(&[q], self.operations);
+        self.collapse(|b| b.reset(q));
+        self.idle(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.rx(theta, q));
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.touch(&[q0, q1]);
+        self.op(|b| b.rxx(theta, q0, q1));
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.ry(theta, q));
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.touch(&[q0, q1]);
+        self.op(|b| b.ryy(theta, q0, q1));
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.rz(theta, q));
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.touch(&[q0, q1]);
+        self.op(|b| b.rzz(theta, q0, q1));
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.sadj(q));
+    }
+
+    fn s(&mut self, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.s(q));
+    }
+
+    fn sx(&mut self, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.sx(q));
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.touch(&[q0, q1]);
+        self.op(|b| b.swap(q0, q1));
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.tadj(q));
+    }
+
+    fn t(&mut self, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.t(q));
+    }
+
+    fn x(&mut self, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.x(q));
+    }
+
+    fn y(&mut self, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.y(q));
+    }
+
+    fn z(&mut self, q: usize) {
+        self.touch(&[q]);
+        self.op(|b| b.z(q));
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.count_allocation();
+        let time = self.operations;
+        let q = self.op(Backend::qubit_allocate);
+        self.lifetimes.allocate(q, time);
+        q
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        let time = self.operations;
+        self.collapse(|b| b.qubit_release(q));
+        self.qubits -= 1;
+        self.idle(q);
+        self.lifetimes.release(q, time);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.backend.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.backend.qubit_is_zero(q)
+    }
+
+    fn qubit_borrow(&mut self) -> usize {
+        self.count_allocation();
+        let time = self.operations;
+        let q = self.op(Backend::qubit_borrow);
+        self.lifetimes.allocate(q, time);
+        q
+    }
+
+    fn qubit_return(&mut self, q: usize) -> bool {
+        let time = self.operations;
+        let returned = self.collapse(|b| b.qubit_return(q));
+        if returned {
+            self.qubits -= 1;
+            self.idle(q);
+            self.lifetimes.release(q, time);
+        }
+        returned
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        let mut qubits = vec![];
+        qubit_args(&arg, &mut qubits);
+        self.touch(&qubits);
+        self.backend.custom_intrinsic(name, arg)
+    }
+
+    fn target_intrinsic(
+        &mut self,
+        intrinsic: &CustomIntrinsic,
+        arg: Value,
+    ) -> Option<Result<Value, String>> {
+        let mut qubits = vec![];
+        qubit_args(&arg, &mut qubits);
+        self.touch(&qubits);
+        self.backend.target_intrinsic(intrinsic, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn supports_conditionals(&self) -> bool {
+        self.backend.supports_conditionals()
+    }
+
+    fn begin_conditional(&mut self, result: usize, value: bool) {
+        self.backend.begin_conditional(result, value);
+    }
+
+    fn end_conditional(&mut self) {
+        self.backend.end_conditional();
+    }
+
+    fn records_call_sites(&self) -> bool {
+        self.backend.records_call_sites()
+    }
+
+    fn set_call_site(&mut self, span: PackageSpan, callable: Rc<str>) {
+        self.backend.set_call_site(span, callable);
+    }
+}
+
+/// Collects the qubits in the argument of an intrinsic.
+fn qubit_args(arg: &Value, qubits: &mut Vec<usize>) {
+    match arg {
+        Value::Qubit(q) => qubits.push(q.0),
+        Value::Array(vals) => {
+            for val in vals.iter() {
+                qubit_args(val, qubits);
+            }
+        }
+        Value::Tuple(vals) => {
+            for val in vals.iter() {
+                qubit_args(val, qubits);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::Error;
+use qsc_hir::hir::{Attr, ItemKind, Package};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// The result of running a test.
+#[derive(Clone, Debug)]
+pub struct TestResult {
+    /// The namespace-qualified name of the test callable.
+    pub name: String,
+    /// The seed of the simulator and of the classical random number generator, which reproduces
+    /// the run of the test when set on the interpreter.
+    pub seed: u64,
+    /// The output of the test, such as messages and state dumps.
+    pub output: String,
+    /// The errors that failed the test, if it failed to compile or failed at runtime.
+    pub errors: Vec<Error>,
+}
+
+impl TestResult {
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// The namespace-qualified names of the callables with the `@Test()` attribute in the package,
+/// in source order. Callables declared inside other callables can't be called by name and are
+/// skipped.
+pub(super) fn discover_tests(package: &Package) -> Vec<String> {
+    let mut tests = package
+        .items
+        .values()
+        .filter(|item| item.attrs.contains(&Attr::Test))
+        .filter_map(|item| {
+            let ItemKind::Callable(decl) = &item.kind else {
+                return None;
+            };
+            match &package.items.get(item.parent?)?.kind {
+                ItemKind::Namespace(namespace, _) => Some((
+                    decl.span.lo,
+                    format!("{}.{}", namespace.name, decl.name.name),
+                )),
+                _ => None,
+            }
+        })
+        .collect::<Vec<_>>();
+    tests.sort_by_key(|(lo, _)| *lo);
+    tests.into_iter().map(|(_, name)| name).collect()
+}
+
+/// A seed for a test or recorded run when the interpreter has none set.
+pub(super) fn random_seed() -> u64 {
+    // The hashers are seeded randomly per instance, which is enough randomness to vary test runs
+    // without depending on a random number generator crate.
+    RandomState::new().build_hasher().finish()
+}
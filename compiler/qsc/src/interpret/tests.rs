@@ -4,14 +4,21 @@
 #![allow(clippy::needless_raw_string_hashes)]
 
 mod given_interpreter {
-    use crate::interpret::{Error, InterpretResult, Interpreter};
+    use crate::interpret::{
+        DumpFormat, EquivalenceMethod, Error, ForcedOutcomes, GateSet, InterpretResult,
+        Interpreter, Limits, SimulatorKind,
+    };
     use expect_test::Expect;
     use miette::Diagnostic;
-    use qsc_data_structures::language_features::LanguageFeatures;
+    use qsc_data_structures::{
+        language_features::LanguageFeatures, line_column::Encoding, span::Span,
+    };
     use qsc_eval::{output::CursorReceiver, val::Value};
     use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
     use qsc_passes::PackageType;
-    use std::{fmt::Write, io::Cursor, iter, str::from_utf8};
+    use std::{
+        cell::Cell, fmt::Write, io::Cursor, iter, num::NonZeroUsize, rc::Rc, str::from_utf8,
+    };
 
     fn line(interpreter: &mut Interpreter, line: &str) -> (InterpretResult, String) {
         let mut cursor = Cursor::new(Vec::<u8>::new());
@@ -644,6 +651,320 @@ mod given_interpreter {
             "#]].assert_eq(&res);
         }
 
+        #[test]
+        fn qirgen_is_cached_until_program_changes() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::empty(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let (result, output) = line(
+                &mut interpreter,
+                "operation Foo() : Result { use q = Qubit(); MResetZ(q) }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let first = interpreter.qirgen("Foo()").expect("expected success");
+            let second = interpreter.qirgen("Foo()").expect("expected success");
+            assert_eq!(first, second);
+            assert_eq!(interpreter.qir_cache.len(), 1);
+
+            interpreter.set_metadata([("experiment".to_string(), "1".to_string())].into());
+            let with_metadata = interpreter.qirgen("Foo()").expect("expected success");
+            assert_ne!(first, with_metadata);
+            assert_eq!(interpreter.qir_cache.len(), 2);
+
+            let (result, output) = line(&mut interpreter, "operation Bar() : Unit {}");
+            is_only_value(&result, &output, &Value::unit());
+            assert!(interpreter.qir_cache.is_empty());
+            let regenerated = interpreter.qirgen("Foo()").expect("expected success");
+            assert_eq!(with_metadata, regenerated);
+        }
+
+        #[test]
+        fn qirgen_leaves_the_program_that_is_run_unfolded() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::empty(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {"
+                    operation Foo() : Result {
+                        let n = 1 + 2;
+                        use q = Qubit();
+                        if n == 3 {
+                            X(q);
+                        }
+                        MResetZ(q)
+                    }
+                "},
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let exprs = |interpreter: &Interpreter| {
+                interpreter
+                    .fir_store
+                    .get(interpreter.package)
+                    .exprs
+                    .iter()
+                    .map(|(_, expr)| expr.to_string())
+                    .collect::<Vec<_>>()
+            };
+            let unfolded = exprs(&interpreter);
+            interpreter.qirgen("Foo()").expect("expected success");
+            // Generating QIR adds the entry expression, but leaves the expressions of `Foo` as they were.
+            assert_eq!(unfolded, exprs(&interpreter)[..unfolded.len()]);
+            let (result, output) = line(&mut interpreter, "Foo()");
+            is_only_value(&result, &output, &Value::RESULT_ONE);
+        }
+
+        #[test]
+        fn qirgen_with_debug_info_attaches_source_locations() {
+            let source = indoc! {"
+                namespace Test {
+                    operation Main() : Result {
+                        use q = Qubit();
+                        H(q);
+                        X(q);
+                        H(q);
+                        MResetZ(q)
+                    }
+                }
+            "};
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::new([("test.qs".into(), source.into())], None),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::empty(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let res = interpreter
+                .qirgen_with_debug_info("Test.Main()")
+                .expect("expected success");
+            expect![[r#"
+                %Result = type opaque
+                %Qubit = type opaque
+
+                define void @ENTRYPOINT__main() #0 !dbg !12 {
+                  call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*)), !dbg !6
+                  call void @__quantum__qis__x__body(%Qubit* inttoptr (i64 0 to %Qubit*)), !dbg !7
+                  call void @__quantum__qis__h__body(%Qubit* inttoptr (i64 0 to %Qubit*)), !dbg !8
+                  call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+                  call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+                  ret void
+                }
+
+                declare void @__quantum__qis__ccx__body(%Qubit*, %Qubit*, %Qubit*)
+                declare void @__quantum__qis__cx__body(%Qubit*, %Qubit*)
+                declare void @__quantum__qis__cy__body(%Qubit*, %Qubit*)
+                declare void @__quantum__qis__cz__body(%Qubit*, %Qubit*)
+                declare void @__quantum__qis__rx__body(double, %Qubit*)
+                declare void @__quantum__qis__rxx__body(double, %Qubit*, %Qubit*)
+                declare void @__quantum__qis__ry__body(double, %Qubit*)
+                declare void @__quantum__qis__ryy__body(double, %Qubit*, %Qubit*)
+                declare void @__quantum__qis__rz__body(double, %Qubit*)
+                declare void @__quantum__qis__rzz__body(double, %Qubit*, %Qubit*)
+                declare void @__quantum__qis__h__body(%Qubit*)
+                declare void @__quantum__qis__s__body(%Qubit*)
+                declare void @__quantum__qis__s__adj(%Qubit*)
+                declare void @__quantum__qis__t__body(%Qubit*)
+                declare void @__quantum__qis__t__adj(%Qubit*)
+                declare void @__quantum__qis__x__body(%Qubit*)
+                declare void @__quantum__qis__y__body(%Qubit*)
+                declare void @__quantum__qis__z__body(%Qubit*)
+                declare void @__quantum__qis__swap__body(%Qubit*, %Qubit*)
+                declare void @__quantum__qis__mz__body(%Qubit*, %Result* writeonly) #1
+                declare void @__quantum__rt__result_record_output(%Result*, i8*)
+                declare void @__quantum__rt__array_record_output(i64, i8*)
+                declare void @__quantum__rt__tuple_record_output(i64, i8*)
+
+                attributes #0 = { "entry_point" "output_labeling_schema" "qir_profiles"="base_profile" "required_num_qubits"="1" "required_num_results"="1" }
+                attributes #1 = { "irreversible" }
+
+                ; module flags
+
+                !llvm.module.flags = !{!0, !1, !2, !3, !4}
+
+                !0 = !{i32 1, !"qir_major_version", i32 1}
+                !1 = !{i32 7, !"qir_minor_version", i32 0}
+                !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+                !3 = !{i32 1, !"dynamic_result_management", i1 false}
+                !4 = !{i32 2, !"Debug Info Version", i32 3}
+                !5 = !DILocation(line: 3, column: 9, scope: !16)
+                !6 = !DILocation(line: 4, column: 9, scope: !16)
+                !7 = !DILocation(line: 5, column: 9, scope: !16)
+                !8 = !DILocation(line: 6, column: 9, scope: !16)
+                !9 = !DILocation(line: 7, column: 9, scope: !16)
+                !10 = !DILocation(line: 0, column: 0, scope: !12)
+                !llvm.dbg.cu = !{!11}
+                !11 = distinct !DICompileUnit(language: DW_LANG_C99, file: !15, producer: "qsc", isOptimized: false, runtimeVersion: 0, emissionKind: LineTablesOnly)
+                !12 = distinct !DISubprogram(name: "ENTRYPOINT__main", scope: !15, file: !15, type: !14, spFlags: DISPFlagDefinition, unit: !11)
+                !13 = !{null}
+                !14 = !DISubroutineType(types: !13)
+                !15 = !DIFile(filename: "test.qs", directory: "")
+                !16 = distinct !DILexicalBlockFile(scope: !12, file: !15, discriminator: 0)
+            "#]].assert_eq(&res);
+
+            let without_debug_info = interpreter.qirgen("Test.Main()").expect("expected success");
+            assert!(!without_debug_info.contains("!dbg"));
+            assert_eq!(interpreter.qir_cache.len(), 2);
+        }
+
+        #[test]
+        fn qirgen_decomposes_into_target_gate_set() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::empty(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            interpreter.set_gate_set(GateSet::RzSxCx);
+            let res = interpreter
+                .qirgen("{ use (q0, q1) = (Qubit(), Qubit()); H(q0); CZ(q0, q1); MResetZ(q1) }")
+                .expect("expected success");
+            let body = res
+                .split("ret void")
+                .next()
+                .expect("QIR should have an entry point body");
+            assert!(body.contains("@__quantum__qis__rz__body"));
+            assert!(body.contains("@__quantum__qis__sx__body"));
+            assert!(body.contains("@__quantum__qis__cx__body"));
+            assert!(!body.contains("@__quantum__qis__h__body"));
+            assert!(!body.contains("@__quantum__qis__cz__body"));
+            assert!(res.contains("declare void @__quantum__qis__sx__body(%Qubit*)"));
+        }
+
+        #[test]
+        fn registered_function_is_called_with_its_arguments() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .register_function(
+                    "Add",
+                    &[
+                        ("a".to_string(), "Int".to_string()),
+                        ("b".to_string(), "Int".to_string()),
+                    ],
+                    "Int",
+                    |args| match args.as_slice() {
+                        [Value::Int(a), Value::Int(b)] => Ok(Value::Int(a + b)),
+                        _ => Err("expected two integers".to_string()),
+                    },
+                )
+                .expect("function should be registered");
+            interpreter
+                .register_function(
+                    "Fail",
+                    &[("x".to_string(), "Double".to_string())],
+                    "Double",
+                    |_| Err("optimizer diverged".to_string()),
+                )
+                .expect("function should be registered");
+
+            let (result, output) = line(&mut interpreter, "Add(2, 3) * 2");
+            is_only_value(&result, &output, &Value::Int(10));
+            let (result, _) = line(&mut interpreter, "Fail(1.0)");
+            let errors = result.expect_err("failing callback should fail the call");
+            let message = iter::successors(std::error::Error::source(&errors[0]), |&s| s.source())
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(": ");
+            assert!(message.contains("optimizer diverged"), "{message}");
+        }
+
+        #[test]
+        fn custom_intrinsic_is_simulated_by_callback_and_declared_in_qir() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::empty(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {r#"
+                    @CustomIntrinsic("__quantum__qis__pulse__body", "Pulse")
+                    operation Pulse(amplitude : Double, q : Qubit) : Unit {
+                        body intrinsic;
+                    }
+                "#},
+            );
+            is_only_value(&result, &output, &Value::unit());
+
+            let (result, _) = line(&mut interpreter, "{ use q = Qubit(); Pulse(0.5, q); }");
+            assert!(result.is_err(), "intrinsic without a callback should fail");
+
+            let calls = Rc::new(Cell::new(0));
+            let counter = Rc::clone(&calls);
+            interpreter.register_intrinsic(
+                "__quantum__qis__pulse__body",
+                Rc::new(move |_| {
+                    counter.set(counter.get() + 1);
+                    Ok(Value::unit())
+                }),
+            );
+            let (result, output) = line(&mut interpreter, "{ use q = Qubit(); Pulse(0.5, q); }");
+            is_only_value(&result, &output, &Value::unit());
+            assert_eq!(calls.get(), 1);
+
+            let qir = interpreter
+                .qirgen("{ use q = Qubit(); Pulse(0.5, q); MResetZ(q) }")
+                .expect("expected success");
+            assert!(qir.contains(
+                "call void @__quantum__qis__pulse__body(double 0.5, %Qubit* inttoptr (i64 0 to %Qubit*))"
+            ));
+            assert!(qir.contains("declare void @__quantum__qis__pulse__body(double, %Qubit*)"));
+        }
+
+        #[test]
+        fn qirgen_includes_metadata_as_module_flags() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::empty(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            interpreter.set_metadata(
+                [
+                    ("experiment".to_string(), "bell-1".to_string()),
+                    ("note".to_string(), "say \"hi\"".to_string()),
+                ]
+                .into(),
+            );
+            let res = interpreter
+                .qirgen("{ use q = Qubit(); MResetZ(q) }")
+                .expect("expected success");
+            let flags = res
+                .split("; module flags")
+                .nth(1)
+                .expect("QIR should have module flags");
+            expect![[r#"
+
+
+                !llvm.module.flags = !{!0, !1, !2, !3, !4, !5}
+
+                !0 = !{i32 1, !"qir_major_version", i32 1}
+                !1 = !{i32 7, !"qir_minor_version", i32 0}
+                !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+                !3 = !{i32 1, !"dynamic_result_management", i1 false}
+                !4 = !{i32 1, !"user.experiment", !"bell-1"}
+                !5 = !{i32 1, !"user.note", !"say \22hi\22"}
+            "#]]
+            .assert_eq(flags);
+        }
+
         #[test]
         fn qirgen_entry_expr_in_block() {
             let mut interpreter = Interpreter::new(
@@ -971,6 +1292,41 @@ mod given_interpreter {
             "#]].assert_eq(&res);
         }
 
+        #[test]
+        fn qirgen_borrowed_qubit_is_allocated_in_base_profile() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::empty(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let (result, output) = line(
+                &mut interpreter,
+                "operation Foo() : Result { use q = Qubit(); borrow b = Qubit(); CNOT(b, q); CNOT(b, q); MResetZ(q) }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let res = interpreter.qirgen("Foo()").expect("expected success");
+            let entry = res
+                .split("declare")
+                .next()
+                .expect("QIR should have an entry point");
+            expect![[r#"
+                %Result = type opaque
+                %Qubit = type opaque
+
+                define void @ENTRYPOINT__main() #0 {
+                  call void @__quantum__qis__cx__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Qubit* inttoptr (i64 0 to %Qubit*))
+                  call void @__quantum__qis__cx__body(%Qubit* inttoptr (i64 1 to %Qubit*), %Qubit* inttoptr (i64 0 to %Qubit*))
+                  call void @__quantum__qis__mz__body(%Qubit* inttoptr (i64 0 to %Qubit*), %Result* inttoptr (i64 0 to %Result*)) #1
+                  call void @__quantum__rt__result_record_output(%Result* inttoptr (i64 0 to %Result*), i8* null)
+                  ret void
+                }
+
+            "#]].assert_eq(entry);
+        }
+
         #[test]
         fn qirgen_entry_expr_profile_incompatible() {
             let mut interpreter = Interpreter::new(
@@ -1059,135 +1415,1101 @@ mod given_interpreter {
         }
 
         #[test]
-        fn run_output_merged() {
+        fn benchmark_counts_gates_per_repetition() {
             let mut interpreter = get_interpreter();
             let (result, output) = line(
                 &mut interpreter,
-                r#"operation Foo() : Unit { Message("hello!") }"#,
+                indoc! {r#"
+                    operation Foo() : Result {
+                        use qs = Qubit[2];
+                        H(qs[0]);
+                        CNOT(qs[0], qs[1]);
+                        Message("discarded");
+                        ResetAll(qs);
+                        Zero
+                    }
+                "#},
             );
             is_only_value(&result, &output, &Value::unit());
-            for _ in 0..4 {
-                let (result, output) = run(&mut interpreter, "Foo()");
-                is_unit_with_output(
-                    &result.expect("compilation should succeed"),
-                    &output,
-                    "hello!",
-                );
-            }
+            let benchmark = interpreter
+                .benchmark("Foo()", 3)
+                .expect("benchmark should succeed");
+            assert_eq!(benchmark.repetitions, 3);
+            assert!(benchmark.total.min <= benchmark.total.max);
+            expect![[r#"
+                {
+                    "cx": 1.0,
+                    "h": 1.0,
+                    "reset": 2.0,
+                }
+            "#]]
+            .assert_debug_eq(&benchmark.gate_counts);
         }
 
         #[test]
-        fn base_prof_non_result_return() {
-            let mut interpreter = Interpreter::new(
-                true,
-                SourceMap::default(),
-                PackageType::Lib,
-                RuntimeCapabilityFlags::empty(),
-                LanguageFeatures::default(),
-            )
-            .expect("interpreter should be created");
-            let (result, output) = line(&mut interpreter, "123");
-            is_only_value(&result, &output, &Value::Int(123));
+        fn benchmark_compile_error() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .benchmark("Foo()", 3)
+                .expect_err("benchmark should fail");
         }
-    }
-
-    fn get_interpreter() -> Interpreter {
-        Interpreter::new(
-            true,
-            SourceMap::default(),
-            PackageType::Lib,
-            RuntimeCapabilityFlags::all(),
-            LanguageFeatures::default(),
-        )
-        .expect("interpreter should be created")
-    }
 
-    fn is_only_value(result: &InterpretResult, output: &str, value: &Value) {
-        assert_eq!("", output);
+        #[test]
+        fn qubit_limit_fails_allocation() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_limits(Limits {
+                max_qubits: Some(2),
+                ..Limits::default()
+            });
+            let (result, output) = line(
+                &mut interpreter,
+                "operation Foo() : Unit { use qs = Qubit[2]; use q = Qubit(); }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = run(&mut interpreter, "Foo()");
+            is_only_error(
+                &result.expect("compilation should succeed"),
+                &output,
+                &expect![[r#"
+                    runtime error: qubit limit exceeded: cannot allocate more than 2 qubits
+                      allocating this qubit exceeds the limit [line_0] [use q = Qubit();]
+                "#]],
+            );
+        }
 
-        match result {
-            Ok(v) => assert_eq!(value, v),
-            Err(e) => panic!("Expected {value:?}, got {e:?}"),
+        #[test]
+        fn qubit_limit_counts_released_qubits() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_limits(Limits {
+                max_qubits: Some(1),
+                ..Limits::default()
+            });
+            let (result, output) = line(
+                &mut interpreter,
+                "operation Foo() : Unit { for _ in 1..3 { use q = Qubit(); } }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = run(&mut interpreter, "Foo()");
+            is_only_value(
+                &result.expect("compilation should succeed"),
+                &output,
+                &Value::unit(),
+            );
         }
-    }
 
-    fn is_unit_with_output_eval_entry(
-        result: &Result<Value, Vec<crate::interpret::Error>>,
-        output: &str,
-        expected_output: &str,
-    ) {
-        assert_eq!(expected_output, output);
+        #[test]
+        fn call_depth_limit_fails_recursion() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_limits(Limits {
+                max_call_depth: Some(10),
+                ..Limits::default()
+            });
+            let (result, output) = line(
+                &mut interpreter,
+                "function Count(n : Int) : Int { n == 0 ? 0 | 1 + Count(n - 1) }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = run(&mut interpreter, "Count(5)");
+            is_only_value(
+                &result.expect("compilation should succeed"),
+                &output,
+                &Value::Int(5),
+            );
+            let (result, output) = run(&mut interpreter, "Count(20)");
+            is_only_error(
+                &result.expect("compilation should succeed"),
+                &output,
+                &expect![[r#"
+                    runtime error: call depth limit exceeded: cannot nest more than 10 calls
+                      this call exceeds the limit [line_0] [Count]
+                "#]],
+            );
+        }
+
+        #[test]
+        fn default_limits_stop_unbounded_recursion() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                "function Deep(n : Int) : Int { Deep(n + 1) }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = run(&mut interpreter, "Deep(0)");
+            is_only_error(
+                &result.expect("compilation should succeed"),
+                &output,
+                &expect![[r#"
+                    runtime error: call depth limit exceeded: cannot nest more than 10000 calls
+                      this call exceeds the limit [line_0] [Deep]
+                "#]],
+            );
+        }
+
+        #[test]
+        fn statement_limit_fails_infinite_loop() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_limits(Limits {
+                max_statements: Some(100),
+                ..Limits::default()
+            });
+            let (result, output) = line(
+                &mut interpreter,
+                "function Spin() : Unit { mutable i = 0; while true { set i += 1; } }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = run(&mut interpreter, "Spin()");
+            is_only_error(
+                &result.expect("compilation should succeed"),
+                &output,
+                &expect![[r#"
+                    runtime error: statement limit exceeded: cannot execute more than 100 statements
+                      executing this statement exceeds the limit [line_0] [set i += 1;]
+                "#]],
+            );
+        }
+
+        #[test]
+        fn output_limit_fails_message_flood() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_limits(Limits {
+                max_outputs: Some(2),
+                ..Limits::default()
+            });
+            let (result, output) = line(
+                &mut interpreter,
+                "function Flood() : Unit { while true { Message(\"spam\"); } }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = run(&mut interpreter, "Flood()");
+            is_error(
+                &result
+                    .expect("compilation should succeed")
+                    .expect_err("run should fail"),
+                &expect![[r#"
+                    runtime error: output limit exceeded: cannot print more than 2 messages and dumps
+                      this output exceeds the limit [line_0] [Message("spam")]
+                "#]],
+            );
+            expect![[r#"
+                spam
+                spam"#]]
+            .assert_eq(&output);
+        }
+
+        #[test]
+        fn time_limit_fails_infinite_loop() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_limits(Limits {
+                max_duration: Some(std::time::Duration::ZERO),
+                ..Limits::default()
+            });
+            let (result, output) = line(
+                &mut interpreter,
+                "function Spin() : Unit { mutable i = 0; while true { set i += 1; } }",
+            );
+            is_only_value(&result, &output, &Value::unit());
+            let (result, output) = run(&mut interpreter, "Spin()");
+            is_only_error(
+                &result.expect("compilation should succeed"),
+                &output,
+                &expect![[r#"
+                    runtime error: time limit exceeded: cannot run for longer than 0ns
+                      the limit was exceeded while executing this statement [line_0] [set i += 1;]
+                "#]],
+            );
+        }
+
+        #[test]
+        fn forced_outcomes_select_measurement_results() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {"
+                    operation Measure() : (Result, Result, Result) {
+                        use (a, b) = (Qubit(), Qubit());
+                        H(a);
+                        let r1 = M(a);
+                        let r2 = M(a);
+                        let r3 = MResetZ(b);
+                        Reset(a);
+                        (r1, r2, r3)
+                    }
+                "},
+            );
+            is_only_value(&result, &output, &Value::unit());
+            interpreter.set_forced_outcomes(ForcedOutcomes {
+                sequence: [Some(true), None].into(),
+                qubits: [(1, true)].into_iter().collect(),
+            });
+            for _ in 0..2 {
+                let (result, output) = run(&mut interpreter, "Measure()");
+                let value = result
+                    .expect("compilation should succeed")
+                    .expect("run should succeed");
+                assert_eq!(output, "");
+                expect!["(One, One, One)"].assert_eq(&value.to_string());
+            }
+
+            interpreter.set_forced_outcomes(ForcedOutcomes {
+                sequence: [Some(false)].into(),
+                ..ForcedOutcomes::default()
+            });
+            let (result, _) = run(&mut interpreter, "Measure()");
+            let value = result
+                .expect("compilation should succeed")
+                .expect("run should succeed");
+            expect!["(Zero, Zero, Zero)"].assert_eq(&value.to_string());
+        }
+
+        #[test]
+        fn dense_simulator_runs_program() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_simulator(SimulatorKind::Dense);
+            // The global phase of the dumped state depends on the outcome of the reset.
+            interpreter.set_quantum_seed(Some(42));
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {"
+                    {
+                        use qs = Qubit[3];
+                        H(qs[0]);
+                        CNOT(qs[0], qs[2]);
+                        Rx(1.0, qs[1]);
+                        Reset(qs[1]);
+                        Microsoft.Quantum.Diagnostics.DumpMachine();
+                        let r = M(qs[0]) == M(qs[2]);
+                        ResetAll(qs);
+                        r
+                    }
+                "},
+            );
+            assert_eq!(result.expect("line should succeed"), Value::Bool(true));
+            expect![[r#"
+                STATE:
+                |000⟩: 0.7071067811865475+0i
+                |101⟩: 0.7071067811865475+0i"#]]
+            .assert_eq(&output);
+        }
+
+        #[test]
+        fn run_reports_simulator_statistics() {
+            let mut interpreter = get_interpreter();
+            assert!(interpreter.last_run_statistics().is_none());
+            let (result, _) = run(
+                &mut interpreter,
+                "{ use qs = Qubit[3]; ApplyToEach(H, qs); let rs = MResetEachZ(qs); }",
+            );
+            result
+                .expect("compilation should succeed")
+                .expect("run should succeed");
+            let statistics = interpreter
+                .last_run_statistics()
+                .expect("statistics should be reported after a run");
+            assert_eq!(statistics.qubit_high_water_mark, 3);
+            assert_eq!(statistics.reused_qubit_high_water_mark, 3);
+            assert_eq!(statistics.peak_amplitudes, 8);
+            assert_eq!(
+                statistics
+                    .samples
+                    .iter()
+                    .map(|sample| (sample.qubits, sample.amplitudes))
+                    .collect::<Vec<_>>(),
+                [(3, 8), (3, 4), (3, 2), (3, 1), (2, 1), (1, 1)]
+            );
+            assert_eq!(
+                statistics.peak_memory,
+                SimulatorKind::Sparse.estimated_memory(3, 8)
+            );
+
+            interpreter.set_simulator(SimulatorKind::Dense);
+            let (result, _) = run(&mut interpreter, "{ use qs = Qubit[4]; }");
+            result
+                .expect("compilation should succeed")
+                .expect("run should succeed");
+            let statistics = interpreter
+                .last_run_statistics()
+                .expect("statistics should be reported after a run");
+            assert_eq!(statistics.qubit_high_water_mark, 4);
+            assert_eq!(statistics.peak_memory, 16 * 16);
+        }
+
+        #[test]
+        fn run_reports_reused_qubit_high_water_mark() {
+            let mut interpreter = get_interpreter();
+            let (result, _) = run(
+                &mut interpreter,
+                "{ use qs = Qubit[3]; H(qs[0]); let r = MResetZ(qs[0]); X(qs[1]); Reset(qs[1]); CNOT(qs[2], qs[0]); }",
+            );
+            result
+                .expect("compilation should succeed")
+                .expect("run should succeed");
+            let statistics = interpreter
+                .last_run_statistics()
+                .expect("statistics should be reported after a run");
+            assert_eq!(statistics.qubit_high_water_mark, 3);
+            assert_eq!(statistics.reused_qubit_high_water_mark, 2);
+        }
+
+        #[test]
+        fn run_reports_qubit_lifetimes() {
+            let mut interpreter = get_interpreter();
+            let (result, _) = run(
+                &mut interpreter,
+                "{ use qs = Qubit[2]; H(qs[0]); X(qs[0]); CNOT(qs[0], qs[1]); ResetAll(qs); }",
+            );
+            result
+                .expect("compilation should succeed")
+                .expect("run should succeed");
+            let statistics = interpreter
+                .last_run_statistics()
+                .expect("statistics should be reported after a run");
+            let lifetimes = &statistics.qubit_lifetimes;
+            assert_eq!(
+                lifetimes.iter().map(|l| l.qubit).collect::<Vec<_>>(),
+                [0, 1]
+            );
+            // Both qubits are allocated before the gates, which start at operation 2.
+            assert_eq!(lifetimes[0].allocated, 0);
+            assert_eq!(lifetimes[0].idle.first(), Some(&(1..2)));
+            assert_eq!(lifetimes[0].partners, [(4, 1)]);
+            assert_eq!(lifetimes[1].allocated, 1);
+            assert_eq!(lifetimes[1].idle.first(), Some(&(2..4)));
+            assert_eq!(lifetimes[1].partners, [(4, 0)]);
+            assert!(lifetimes.iter().all(|l| l.released.is_some()));
+        }
+
+        #[test]
+        fn dump_operation_returns_unitary_matrix() {
+            let mut interpreter = get_interpreter();
+            let format = |matrix: Vec<Vec<num_complex::Complex64>>| {
+                matrix
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|entry| format!("{:.4}", entry.re))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+
+            let matrix = interpreter
+                .dump_operation("qs => H(qs[0])", 1)
+                .expect("operation should have a matrix");
+            expect![[r#"
+                0.7071 0.7071
+                0.7071 -0.7071"#]]
+            .assert_eq(&format(matrix));
+
+            let matrix = interpreter
+                .dump_operation("qs => CNOT(qs[0], qs[1])", 2)
+                .expect("operation should have a matrix");
+            expect![[r#"
+                1.0000 0.0000 0.0000 0.0000
+                0.0000 1.0000 0.0000 0.0000
+                0.0000 0.0000 0.0000 1.0000
+                0.0000 0.0000 1.0000 0.0000"#]]
+            .assert_eq(&format(matrix));
+        }
+
+        #[test]
+        fn dump_operation_fails_for_measuring_operation() {
+            let mut interpreter = get_interpreter();
+            let errors = interpreter
+                .dump_operation("qs => { let _ = M(qs[0]); }", 1)
+                .expect_err("measuring operation should not have a matrix");
+            is_error(
+                &errors,
+                &expect![[r#"
+                    operation measures qubits, so it has no unitary matrix
+                "#]],
+            );
+        }
+
+        #[test]
+        fn check_equivalence_ignores_global_phase() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_quantum_seed(Some(7));
+            for method in [EquivalenceMethod::Matrix, EquivalenceMethod::Sampling(4)] {
+                let mut check = |a: &str, b: &str, num_qubits| {
+                    interpreter
+                        .check_equivalence(a, b, num_qubits, method)
+                        .expect("operations should be comparable")
+                };
+                assert!(check(
+                    "qs => { H(qs[0]); Z(qs[0]); H(qs[0]); }",
+                    "qs => X(qs[0])",
+                    1
+                ));
+                assert!(check(
+                    "qs => Rz(3.141592653589793, qs[0])",
+                    "qs => Z(qs[0])",
+                    1
+                ));
+                assert!(check(
+                    "qs => { H(qs[1]); CZ(qs[0], qs[1]); H(qs[1]); }",
+                    "qs => CNOT(qs[0], qs[1])",
+                    2
+                ));
+                assert!(!check("qs => Z(qs[0])", "qs => ()", 1));
+                assert!(!check(
+                    "qs => CNOT(qs[0], qs[1])",
+                    "qs => CNOT(qs[1], qs[0])",
+                    2
+                ));
+            }
+        }
+
+        #[test]
+        fn dump_ast_parses_source_without_compiling_it() {
+            let interpreter = get_interpreter();
+            let ast = interpreter
+                .dump_ast("let x = Undefined(1);", DumpFormat::Text)
+                .expect("source should parse");
+            assert!(ast.starts_with("Package"));
+            assert!(ast.contains("\"Undefined\""));
+
+            let json = interpreter
+                .dump_ast("let x = 1;", DumpFormat::Json)
+                .expect("source should parse");
+            let roots: serde_json::Value =
+                serde_json::from_str(&json).expect("dump should be valid JSON");
+            let package = &roots[0];
+            assert!(package["node"]
+                .as_str()
+                .is_some_and(|node| node.starts_with("Package")));
+            assert!(package["children"][0]["node"]
+                .as_str()
+                .is_some_and(|node| node.starts_with("Stmt")));
+
+            let errors = interpreter
+                .dump_ast("let x = ;", DumpFormat::Text)
+                .expect_err("source should not parse");
+            assert!(!errors.is_empty());
+        }
+
+        #[test]
+        fn fragment_location_maps_error_span_into_its_fragment() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(&mut interpreter, "let x = 1;");
+            is_only_value(&result, &output, &Value::unit());
+            let (result, _) = line(&mut interpreter, "let y = 2;\nlet z = x + w;");
+            let errors = result.expect_err("fragment should fail to compile");
+            let offset = errors[0]
+                .labels()
+                .and_then(|mut labels| labels.next())
+                .expect("error should have a label")
+                .offset();
+            let span = Span {
+                lo: u32::try_from(offset).expect("offset should fit into u32"),
+                hi: u32::try_from(offset + 1).expect("offset should fit into u32"),
+            };
+            let location = interpreter
+                .fragment_location(span, Encoding::Utf8)
+                .expect("span should be in a fragment");
+            assert_eq!(location.source.as_ref(), "line_1");
+            assert_eq!(
+                (location.range.start.line, location.range.start.column),
+                (1, 12)
+            );
+        }
+
+        #[test]
+        fn dump_hir_and_fir_include_fragments() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(&mut interpreter, "function Answer() : Int { 42 }");
+            is_only_value(&result, &output, &Value::unit());
+            assert!(interpreter.dump_hir(DumpFormat::Text).contains("Answer"));
+            let json = interpreter.dump_fir(DumpFormat::Json);
+            let roots: serde_json::Value =
+                serde_json::from_str(&json).expect("dump should be valid JSON");
+            assert_eq!(roots.as_array().map(Vec::len), Some(2));
+        }
+
+        #[test]
+        fn run_shots_is_independent_of_worker_count() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_quantum_seed(Some(7));
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {"
+                    operation Flip(shouldFail : Bool) : Result {
+                        use q = Qubit();
+                        H(q);
+                        let r = MResetZ(q);
+                        if shouldFail and r == One {
+                            fail \"one\";
+                        }
+                        r
+                    }
+                "},
+            );
+            is_only_value(&result, &output, &Value::unit());
+
+            let shots = |interpreter: &mut Interpreter, workers| {
+                interpreter
+                    .run_shots(
+                        "Flip(false)",
+                        64,
+                        NonZeroUsize::new(workers).expect("workers should be nonzero"),
+                    )
+                    .expect("entry expression should compile")
+            };
+            let single = shots(&mut interpreter, 1);
+            let parallel = shots(&mut interpreter, 4);
+            assert_eq!(single.counts, parallel.counts);
+            assert_eq!(single.counts.values().sum::<u32>(), 64);
+            assert_eq!(single.counts.len(), 2);
+
+            let failing = interpreter
+                .run_shots(
+                    "Flip(true)",
+                    16,
+                    NonZeroUsize::new(3).expect("workers should be nonzero"),
+                )
+                .expect("entry expression should compile");
+            assert_eq!(
+                failing.counts.get("Zero").copied().unwrap_or_default() as usize
+                    + failing.failures.len(),
+                16
+            );
+            let failure = failing.failures.first().expect("some shot should fail");
+            interpreter.set_quantum_seed(Some(failure.seed));
+            interpreter.set_classical_seed(Some(failure.seed));
+            let (result, _) = run(&mut interpreter, "Flip(true)");
+            result
+                .expect("compilation should succeed")
+                .expect_err("replaying the failed shot should fail");
+        }
+
+        #[test]
+        fn run_shots_measurement_statistics() {
+            let mut interpreter = get_interpreter();
+            interpreter.set_quantum_seed(Some(7));
+            let (result, output) = line(
+                &mut interpreter,
+                indoc! {"
+                    operation Bell() : (Result, Result, Result) {
+                        use qs = Qubit[3];
+                        H(qs[0]);
+                        CNOT(qs[0], qs[1]);
+                        X(qs[2]);
+                        let r0 = M(qs[0]);
+                        let r1 = M(qs[1]);
+                        let r2 = M(qs[2]);
+                        ResetAll(qs);
+                        (r0, r1, r2)
+                    }
+                "},
+            );
+            is_only_value(&result, &output, &Value::unit());
+
+            let mut runner = interpreter
+                .shot_runner("Bell()")
+                .expect("entry expression should compile");
+            let workers = NonZeroUsize::new(2).expect("workers should be nonzero");
+            assert!(runner.run(8, workers).measurement_statistics.is_none());
+
+            runner.set_measurement_statistics(true);
+            let stats = runner
+                .run(64, workers)
+                .measurement_statistics
+                .expect("statistics should be recorded");
+            assert_eq!(stats.qubits, [0, 1, 2]);
+            assert_eq!(stats.shots, [64, 64, 64]);
+            assert!(stats.marginals[0] > 0.0 && stats.marginals[0] < 1.0);
+            assert!((stats.marginals[0] - stats.marginals[1]).abs() < f64::EPSILON);
+            assert!((stats.marginals[2] - 1.0).abs() < f64::EPSILON);
+            assert!((stats.joint[0][1] - stats.marginals[0]).abs() < f64::EPSILON);
+            assert!((stats.correlations[0][1] - 1.0).abs() < f64::EPSILON);
+            assert!(stats.correlations[0][2].is_nan());
+        }
+
+        #[test]
+        fn run_shots_fails_while_callbacks_are_registered() {
+            let mut interpreter = get_interpreter();
+            interpreter
+                .register_function("Answer", &[], "Int", |_| Ok(Value::Int(42)))
+                .expect("function should be registered");
+            let errors = interpreter
+                .run_shots("Answer()", 4, NonZeroUsize::MIN)
+                .expect_err("shots should not run with callbacks");
+            is_error(
+                &errors,
+                &expect![[r#"
+                    shots can't run on other threads while intrinsic callbacks are registered
+                "#]],
+            );
+        }
+
+        #[test]
+        fn run_output_merged() {
+            let mut interpreter = get_interpreter();
+            let (result, output) = line(
+                &mut interpreter,
+                r#"operation Foo() : Unit { Message("hello!") }"#,
+            );
+            is_only_value(&result, &output, &Value::unit());
+            for _ in 0..4 {
+                let (result, output) = run(&mut interpreter, "Foo()");
+                is_unit_with_output(
+                    &result.expect("compilation should succeed"),
+                    &output,
+                    "hello!",
+                );
+            }
+        }
+
+        #[test]
+        fn base_prof_non_result_return() {
+            let mut interpreter = Interpreter::new(
+                true,
+                SourceMap::default(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::empty(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let (result, output) = line(&mut interpreter, "123");
+            is_only_value(&result, &output, &Value::Int(123));
+        }
+    }
+
+    fn get_interpreter() -> Interpreter {
+        Interpreter::new(
+            true,
+            SourceMap::default(),
+            PackageType::Lib,
+            RuntimeCapabilityFlags::all(),
+            LanguageFeatures::default(),
+        )
+        .expect("interpreter should be created")
+    }
+
+    fn is_only_value(result: &InterpretResult, output: &str, value: &Value) {
+        assert_eq!("", output);
+
+        match result {
+            Ok(v) => assert_eq!(value, v),
+            Err(e) => panic!("Expected {value:?}, got {e:?}"),
+        }
+    }
+
+    fn is_unit_with_output_eval_entry(
+        result: &Result<Value, Vec<crate::interpret::Error>>,
+        output: &str,
+        expected_output: &str,
+    ) {
+        assert_eq!(expected_output, output);
+
+        match result {
+            Ok(value) => assert_eq!(Value::unit(), *value),
+            Err(e) => panic!("Expected unit value, got {e:?}"),
+        }
+    }
+
+    fn is_unit_with_output(result: &InterpretResult, output: &str, expected_output: &str) {
+        assert_eq!(expected_output, output);
+
+        match result {
+            Ok(value) => assert_eq!(Value::unit(), *value),
+            Err(e) => panic!("Expected unit value, got {e:?}"),
+        }
+    }
+
+    fn is_only_error<E>(result: &Result<Value, Vec<E>>, output: &str, expected_errors: &Expect)
+    where
+        E: Diagnostic,
+    {
+        assert_eq!("", output);
+
+        match result {
+            Ok(value) => panic!("Expected error , got {value:?}"),
+            Err(errors) => is_error(errors, expected_errors),
+        }
+    }
+
+    fn is_error<E>(errors: &Vec<E>, expected_errors: &Expect)
+    where
+        E: Diagnostic,
+    {
+        let mut actual = String::new();
+        for error in errors {
+            write!(actual, "{error}").expect("writing should succeed");
+            for s in iter::successors(error.source(), |&s| s.source()) {
+                write!(actual, ": {s}").expect("writing should succeed");
+            }
+            for label in error.labels().into_iter().flatten() {
+                let span = error
+                    .source_code()
+                    .expect("expected valid source code")
+                    .read_span(label.inner(), 0, 0)
+                    .expect("expected to be able to read span");
+
+                write!(
+                    actual,
+                    "\n  {} [{}] [{}]",
+                    label.label().unwrap_or(""),
+                    span.name().expect("expected source file name"),
+                    from_utf8(span.data()).expect("expected valid utf-8 string"),
+                )
+                .expect("writing should succeed");
+            }
+            writeln!(actual).expect("writing should succeed");
+        }
+
+        expected_errors.assert_eq(&actual);
+    }
+
+    #[cfg(test)]
+    mod with_sources {
+        use std::sync::Arc;
+
+        use super::*;
+        use crate::compile::StdLib;
+        use crate::interpret::Debugger;
+        use crate::line_column::Encoding;
+        use expect_test::expect;
+        use indoc::indoc;
+        use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+        use qsc_passes::{PackageType, PassConfig};
+
+        #[test]
+        fn optional_passes_are_disabled_by_default() {
+            let source = "namespace Test { @EntryPoint() operation Main() : Unit {} }";
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let interpreter = Interpreter::new(
+                true,
+                sources.clone(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            assert_eq!(interpreter.pass_config(), PassConfig::default());
+
+            let debugger = Debugger::new(
+                sources,
+                RuntimeCapabilityFlags::all(),
+                Encoding::Utf8,
+                LanguageFeatures::default(),
+            )
+            .expect("debugger should be created");
+            assert_eq!(debugger.interpreter.pass_config(), PassConfig::default());
+        }
+
+        #[test]
+        fn optional_passes_are_configurable() {
+            let source = indoc! { r#"
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Int {
+                    let x = 2 + 3;
+                    x
+                }
+                operation Unused() : Unit {}
+            }"#};
+
+            let passes = PassConfig {
+                constant_folding: false,
+                dead_code_elimination_exe: true,
+                dead_code_elimination_lib: false,
+            };
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new_with_passes(
+                StdLib::Builtin,
+                sources,
+                PackageType::Exe,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+                passes,
+            )
+            .expect("interpreter should be created");
+            assert_eq!(interpreter.pass_config(), passes);
+
+            let (result, output) = entry(&mut interpreter);
+            is_only_value(&result, &output, &Value::Int(5));
+            let shots = interpreter
+                .run_shots("Test.Main()", 2, NonZeroUsize::MIN)
+                .expect("entry expression should compile");
+            assert_eq!(shots.counts.get("5"), Some(&2));
+        }
+
+        #[test]
+        fn compilation_stats_describe_source_package() {
+            let source = indoc! { r#"
+            namespace Test {
+                function Double(x : Int) : Int {
+                    x * 2
+                }
+                operation Measure() : Bool {
+                    use q = Qubit();
+                    M(q) == One
+                }
+            }"#};
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+
+            let packages = interpreter.package_stats();
+            for name in ["core", "std", "source"] {
+                assert!(packages.iter().any(|package| package.name == name));
+            }
+            let source = packages
+                .iter()
+                .find(|package| package.name == "source")
+                .expect("source package should have stats");
+            assert_eq!(source.callables, 2);
+            assert!(source.exprs > 0);
+
+            let rca = interpreter.rca_stats();
+            assert_eq!(rca.callables, 2);
+            assert_eq!(rca.quantum_callables, 1);
+        }
+
+        #[test]
+        fn fork_shares_compiled_sources_without_fragments() {
+            let source = indoc! { r#"
+            namespace Test {
+                function Answer() : Int {
+                    42
+                }
+            }"#};
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let (result, output) = line(&mut interpreter, "function Local() : Int { 1 }");
+            is_only_value(&result, &output, &Value::unit());
+
+            let mut fork = interpreter.fork();
+            let source_package = interpreter.compiler.source_package_id();
+            assert!(std::ptr::eq(
+                interpreter
+                    .compiler
+                    .package_store()
+                    .get(source_package)
+                    .expect("store should have the source package"),
+                fork.compiler
+                    .package_store()
+                    .get(source_package)
+                    .expect("store should have the source package"),
+            ));
+            let (result, output) = line(&mut fork, "Test.Answer()");
+            is_only_value(&result, &output, &Value::Int(42));
+            let (result, output) = line(&mut fork, "Local()");
+            is_only_error(
+                &result,
+                &output,
+                &expect![[r#"
+                    name error: `Local` not found
+                       [line_1] [Local]
+                    type error: insufficient type information to infer type
+                       [line_1] [Local()]
+                "#]],
+            );
+        }
+
+        #[test]
+        fn call_graph_locates_calls_between_callables() {
+            let source = indoc! { r#"
+            namespace Test {
+                function Double(x : Int) : Int {
+                    x * 2
+                }
+                function Quadruple(x : Int) : Int {
+                    Double(Double(x))
+                }
+                function Unused() : Unit {}
+            }"#};
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
 
-        match result {
-            Ok(value) => assert_eq!(Value::unit(), *value),
-            Err(e) => panic!("Expected unit value, got {e:?}"),
+            let graph = interpreter.call_graph();
+            let names: Vec<_> = graph.nodes.iter().map(|node| &*node.name).collect();
+            assert_eq!(names, ["Test.Double", "Test.Quadruple", "Test.Unused"]);
+            assert_eq!(graph.edges.len(), 1);
+            let edge = &graph.edges[0];
+            assert_eq!((edge.caller, edge.callee), (1, 0));
+            let calls: Vec<_> = edge
+                .spans
+                .iter()
+                .map(|span| {
+                    interpreter
+                        .resolve_source(usize::from(graph.nodes[1].package), *span)
+                        .expect("call should be located")
+                })
+                .collect();
+            assert_eq!(
+                calls,
+                [("test".to_string(), 5, 8), ("test".to_string(), 5, 15)]
+            );
         }
-    }
 
-    fn is_unit_with_output(result: &InterpretResult, output: &str, expected_output: &str) {
-        assert_eq!(expected_output, output);
-
-        match result {
-            Ok(value) => assert_eq!(Value::unit(), *value),
-            Err(e) => panic!("Expected unit value, got {e:?}"),
+        #[test]
+        fn declared_target_profile_is_checked_when_compiling() {
+            let source = indoc! { r#"
+            namespace Test {
+                @TargetProfile("Base")
+                operation Correct() : Unit {
+                    use q = Qubit();
+                    if M(q) == One {
+                        X(q);
+                    }
+                }
+            }"#};
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let Err(errors) = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            ) else {
+                panic!("profile violation should fail compilation");
+            };
+            is_error(
+                &errors,
+                &expect![[r#"
+                    this construct needs runtime capabilities the declared target profile doesn't support: ForwardBranching
+                       [test] [if M(q) == One {
+                                X(q);
+                            }]
+                    this construct needs runtime capabilities the declared target profile doesn't support: ForwardBranching
+                       [test] [M(q) == One]
+                    this construct needs runtime capabilities the declared target profile doesn't support: ForwardBranching
+                       [test] [X(q)]
+                "#]],
+            );
         }
-    }
 
-    fn is_only_error<E>(result: &Result<Value, Vec<E>>, output: &str, expected_errors: &Expect)
-    where
-        E: Diagnostic,
-    {
-        assert_eq!("", output);
+        #[test]
+        fn custom_std_replaces_builtin_std() {
+            let std = SourceMap::new(
+                [(
+                    "std.qs".into(),
+                    "namespace Custom { function Answer() : Int { 42 } }".into(),
+                )],
+                None,
+            );
+            let mut interpreter = Interpreter::new_with_passes(
+                StdLib::Custom(std),
+                SourceMap::default(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+                PassConfig::default(),
+            )
+            .expect("interpreter should be created");
 
-        match result {
-            Ok(value) => panic!("Expected error , got {value:?}"),
-            Err(errors) => is_error(errors, expected_errors),
+            let (result, output) = line(&mut interpreter, "Custom.Answer()");
+            is_only_value(&result, &output, &Value::Int(42));
+            let (result, _) = line(&mut interpreter, "Microsoft.Quantum.Math.PI()");
+            assert!(result.is_err(), "builtin std should not be available");
         }
-    }
-
-    fn is_error<E>(errors: &Vec<E>, expected_errors: &Expect)
-    where
-        E: Diagnostic,
-    {
-        let mut actual = String::new();
-        for error in errors {
-            write!(actual, "{error}").expect("writing should succeed");
-            for s in iter::successors(error.source(), |&s| s.source()) {
-                write!(actual, ": {s}").expect("writing should succeed");
-            }
-            for label in error.labels().into_iter().flatten() {
-                let span = error
-                    .source_code()
-                    .expect("expected valid source code")
-                    .read_span(label.inner(), 0, 0)
-                    .expect("expected to be able to read span");
 
-                write!(
-                    actual,
-                    "\n  {} [{}] [{}]",
-                    label.label().unwrap_or(""),
-                    span.name().expect("expected source file name"),
-                    from_utf8(span.data()).expect("expected valid utf-8 string"),
-                )
-                .expect("writing should succeed");
-            }
-            writeln!(actual).expect("writing should succeed");
+        #[test]
+        fn custom_std_errors_are_returned() {
+            let std = SourceMap::new(
+                [(
+                    "std.qs".into(),
+                    "namespace Custom { function Answer() : Int { true } }".into(),
+                )],
+                None,
+            );
+            let result = Interpreter::new_with_passes(
+                StdLib::Custom(std),
+                SourceMap::default(),
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+                PassConfig::default(),
+            );
+            assert!(result.is_err(), "custom std should fail to compile");
         }
 
-        expected_errors.assert_eq(&actual);
-    }
+        #[test]
+        fn entry_point_runs_with_checked_arguments() {
+            let source = indoc! { r#"
+            namespace Test {
+                @EntryPoint()
+                operation Main(n : Int, xs : Double[], big : BigInt, name : String) : String {
+                    $"{name}: {n} {Length(xs)} {big}"
+                }
+            }"#};
 
-    #[cfg(test)]
-    mod with_sources {
-        use std::sync::Arc;
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
 
-        use super::*;
-        use crate::interpret::Debugger;
-        use crate::line_column::Encoding;
-        use expect_test::expect;
-        use indoc::indoc;
-        use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
-        use qsc_passes::PackageType;
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let args = vec![
+                Value::Int(3),
+                Value::Array(vec![Value::Double(0.5), Value::Double(1.0)].into()),
+                Value::Int(7),
+                Value::String("say \"hi\"".into()),
+            ];
+            let result = interpreter
+                .run_entry(&mut receiver, args)
+                .expect("arguments should match the entry point");
+            is_only_value(
+                &result,
+                &receiver.dump(),
+                &Value::String("say \"hi\": 3 2 7".into()),
+            );
+
+            let errors = interpreter
+                .run_entry(&mut receiver, vec![Value::Int(3)])
+                .expect_err("argument count should not match");
+            is_error(
+                &errors,
+                &expect![[r#"
+                    entry point takes 4 arguments but 1 were given
+                "#]],
+            );
+
+            let args = vec![
+                Value::Int(3),
+                Value::Array(vec![Value::Int(1)].into()),
+                Value::Int(7),
+                Value::String("".into()),
+            ];
+            let errors = interpreter
+                .run_entry(&mut receiver, args)
+                .expect_err("argument type should not match");
+            is_error(
+                &errors,
+                &expect![[r#"
+                    argument `xs` of the entry point should be of type `Double[]`
+                "#]],
+            );
+        }
 
         #[test]
         fn entry_expr_is_executed() {
@@ -1372,5 +2694,250 @@ mod given_interpreter {
                 "#]],
             );
         }
+
+        #[test]
+        fn advise_suggests_rewrites_for_dynamic_constructs() {
+            let source = indoc! { r#"
+            namespace Test {
+                operation Main() : Int {
+                    use (control, target) = (Qubit(), Qubit());
+                    H(control);
+                    let r = M(control);
+                    if r == One {
+                        X(target);
+                    }
+                    mutable n = 0;
+                    while M(target) == One {
+                        set n += 1;
+                    }
+                    let values = [0, size = n];
+                    Length(values)
+                }
+            }"#};
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let advice = interpreter
+                .advise("Test.Main()")
+                .expect("advise should succeed");
+            is_error(
+                &advice,
+                &expect![[r#"
+                    branching on a stored measurement result requires forward branching
+                       [test] [if r == One {
+                                X(target);
+                            }]
+                    a loop with a dynamic condition requires backwards branching
+                       [test] [while M(target) == One {
+                                set n += 1;
+                            }]
+                    an array sized by a dynamic value requires higher-level constructs
+                       [test] [[0, size = n]]
+                "#]],
+            );
+            let mut after = String::new();
+            for advice in &advice {
+                writeln!(after, "{}", advice.error().after()).expect("writing should succeed");
+            }
+            expect![[r#"
+                CNOT(control, target);
+                for _ in 1..MaxIterations {
+                    if M(target) == One {
+                        set n += 1;
+                    }
+                }
+                [0, size = MaxSize]
+            "#]]
+            .assert_eq(&after);
+        }
+
+        #[test]
+        fn advise_ignores_static_constructs() {
+            let source = indoc! { r#"
+            namespace Test {
+                operation Main() : Int {
+                    mutable n = 0;
+                    while n < 3 {
+                        set n += 1;
+                    }
+                    let values = [0, size = n];
+                    Length(values)
+                }
+            }"#};
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let advice = interpreter
+                .advise("Test.Main()")
+                .expect("advise should succeed");
+            is_error(&advice, &expect![""]);
+        }
+
+        #[test]
+        fn run_tests_reports_pass_and_fail() {
+            let source = indoc! { r#"
+            namespace Test {
+                @Test()
+                operation Passes() : Unit {
+                    use q = Qubit();
+                    Message("passing");
+                }
+                function Helper() : Int { 2 }
+                @Test()
+                function Fails() : Unit {
+                    if Helper() != 3 {
+                        fail "expected three";
+                    }
+                }
+            }"#};
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            interpreter.set_quantum_seed(Some(42));
+            expect![[r#"
+                [
+                    "Test.Passes",
+                    "Test.Fails",
+                ]
+            "#]]
+            .assert_debug_eq(&interpreter.tests());
+
+            let results = interpreter.run_tests();
+            assert_eq!(results.len(), 2);
+            assert!(results[0].passed());
+            assert_eq!(results[0].output, "passing\n");
+            assert!(!results[1].passed());
+            assert!(results.iter().all(|result| result.seed == 42));
+            is_error(
+                &results[1].errors,
+                &expect![[r#"
+                    runtime error: program failed: expected three
+                      explicit fail [test] [fail "expected three"]
+                "#]],
+            );
+        }
+
+        #[test]
+        fn run_tests_without_tests() {
+            let sources = SourceMap::new(
+                [(
+                    "test".into(),
+                    "namespace Test { operation Main() : Unit {} }".into(),
+                )],
+                None,
+            );
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            assert!(interpreter.run_tests().is_empty());
+        }
+
+        #[test]
+        fn replay_reproduces_recorded_measurements() {
+            let source = indoc! { r#"
+            namespace Test {
+                operation Main() : Result[] {
+                    use qs = Qubit[8];
+                    ApplyToEach(H, qs);
+                    if MResetZ(qs[0]) == One {
+                        X(qs[1]);
+                    }
+                    MResetEachZ(qs[1...])
+                }
+            }"#};
+            let sources = SourceMap::new([("test".into(), source.into())], None);
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let (result, recording) = interpreter
+                .run_recorded(&mut receiver, "Test.Main()", None)
+                .expect("entry expression should compile");
+            let value = result.expect("run should succeed");
+            assert_eq!(recording.measurements.len(), 8);
+
+            let (replayed, replayed_recording) = interpreter
+                .run_recorded(&mut receiver, "Test.Main()", Some(&recording))
+                .expect("entry expression should compile");
+            assert_eq!(replayed.expect("replay should succeed"), value);
+            assert_eq!(replayed_recording, recording);
+        }
+
+        #[test]
+        fn replay_fails_when_measurement_differs() {
+            let sources = SourceMap::new(
+                [(
+                    "test".into(),
+                    "namespace Test { operation Main() : Result { use q = Qubit(); MResetZ(q) } }"
+                        .into(),
+                )],
+                None,
+            );
+            let mut interpreter = Interpreter::new(
+                true,
+                sources,
+                PackageType::Lib,
+                RuntimeCapabilityFlags::all(),
+                LanguageFeatures::default(),
+            )
+            .expect("interpreter should be created");
+            let mut cursor = Cursor::new(Vec::<u8>::new());
+            let mut receiver = CursorReceiver::new(&mut cursor);
+            let (_, mut recording) = interpreter
+                .run_recorded(&mut receiver, "Test.Main()", None)
+                .expect("entry expression should compile");
+            assert_eq!(recording.measurements, vec![false]);
+
+            recording.measurements = vec![true];
+            let (result, _) = interpreter
+                .run_recorded(&mut receiver, "Test.Main()", Some(&recording))
+                .expect("entry expression should compile");
+            is_error(
+                &result.expect_err("replay should fail"),
+                &expect![[r#"
+                measurement 0 does not match the replayed recording
+            "#]],
+            );
+
+            recording.measurements = vec![false, false];
+            let (result, _) = interpreter
+                .run_recorded(&mut receiver, "Test.Main()", Some(&recording))
+                .expect("entry expression should compile");
+            is_error(
+                &result.expect_err("replay should fail"),
+                &expect![[r#"
+                measurement 1 does not match the replayed recording
+            "#]],
+            );
+        }
     }
 }
@@ -3,15 +3,18 @@
 
 pub mod compile;
 pub mod error;
+pub mod hints;
 pub mod incremental;
 pub mod interpret;
 pub mod location;
 pub mod target;
+pub mod timing;
 
 pub use qsc_formatter::formatter;
 
 pub use qsc_frontend::compile::{
-    CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceContents, SourceMap, SourceName,
+    CompileUnit, PackageStore, ParseCache, RuntimeCapabilityFlags, SourceContents, SourceMap,
+    SourceName,
 };
 
 pub mod resolve {
@@ -36,7 +39,13 @@ pub mod project {
 
 pub use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
 
-pub use qsc_passes::{PackageType, PassContext};
+pub use qsc_passes::{PackageType, PassConfig, PassContext};
+
+pub mod language_features {
+    pub use qsc_data_structures::language_features::{
+        LanguageFeatureInfo, Stability, UnknownLanguageFeature, KNOWN_LANGUAGE_FEATURES,
+    };
+}
 
 pub mod line_column {
     pub use qsc_data_structures::line_column::{Encoding, Position, Range};
@@ -48,11 +57,16 @@ pub use qsc_eval::{
 };
 
 pub mod linter {
-    pub use qsc_linter::{run_lints, LintConfig, LintKind, LintLevel};
+    pub use qsc_linter::{
+        apply_fixes, fixable_lints, run_lints, Lint, LintConfig, LintKind, LintLevel, TextEdit,
+    };
 }
 
 pub use qsc_doc_gen::{display, generate_docs};
 
 pub mod circuit {
-    pub use qsc_circuit::{operations::*, Circuit, Operation};
+    pub use qsc_circuit::{
+        operations::*, Circuit, ConditionalRender, DisplayOptions, Operation, QasmError,
+        QubitLifetime, Register, SourceLocation,
+    };
 }
@@ -7,7 +7,7 @@ use qsc_data_structures::{
     line_column::{Encoding, Range},
     span::Span,
 };
-use qsc_frontend::compile::PackageStore;
+use qsc_frontend::compile::{PackageStore, SourceMap};
 use qsc_hir::hir::PackageId;
 
 pub const QSHARP_LIBRARY_URI_SCHEME: &str = "qsharp-library-source";
@@ -54,6 +54,28 @@ impl Location {
             range: Range::from_span(position_encoding, &source.contents, &(span - source.offset)),
         }
     }
+
+    /// Creates a [`Location`] from a SourceMap-relative span, such as the span of a label in a
+    /// compiler diagnostic.
+    ///
+    /// The span is resolved against the source that contains its start, so spans in fragments
+    /// compiled incrementally under a shared source name (such as `<entry>` or `rca-test`) map
+    /// to lines and columns within their own fragment. An end offset past the end of that source
+    /// maps to the end of the source.
+    ///
+    /// Returns `None` if the source map has no source for the span.
+    #[must_use]
+    pub fn from_source_map(
+        span: Span,
+        sources: &SourceMap,
+        position_encoding: Encoding,
+    ) -> Option<Self> {
+        let source = sources.find_by_offset(span.lo)?;
+        Some(Location {
+            source: source.name.clone(),
+            range: Range::from_span(position_encoding, &source.contents, &(span - source.offset)),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -232,6 +254,65 @@ mod tests {
         .assert_debug_eq(&location);
     }
 
+    #[test]
+    fn from_source_map_fragment_span() {
+        let mut sources = SourceMap::default();
+        sources.push("rca-test".into(), "let a = 1;".into());
+        let offset = sources.push("rca-test".into(), "let b = 2;\nlet c = a + b;".into());
+
+        let location = Location::from_source_map(
+            Span {
+                lo: offset + 15,
+                hi: offset + 16,
+            },
+            &sources,
+            Encoding::Utf8,
+        );
+
+        expect![[r#"
+            Some(
+                Location {
+                    source: "rca-test",
+                    range: Range {
+                        start: Position {
+                            line: 1,
+                            column: 4,
+                        },
+                        end: Position {
+                            line: 1,
+                            column: 5,
+                        },
+                    },
+                },
+            )
+        "#]]
+        .assert_debug_eq(&location);
+    }
+
+    #[test]
+    fn from_source_map_multi_byte_span() {
+        let sources = SourceMap::new(
+            [("foo.qs".into(), "let s = \"é\"; let x = 1;".into())],
+            None,
+        );
+        let span = Span { lo: 18, hi: 19 };
+
+        let utf8 = Location::from_source_map(span, &sources, Encoding::Utf8)
+            .expect("source should exist for span");
+        let utf16 = Location::from_source_map(span, &sources, Encoding::Utf16)
+            .expect("source should exist for span");
+
+        assert_eq!((utf8.range.start.column, utf8.range.end.column), (18, 19));
+        assert_eq!((utf16.range.start.column, utf16.range.end.column), (17, 18));
+    }
+
+    #[test]
+    fn from_source_map_without_sources() {
+        let location =
+            Location::from_source_map(Span { lo: 0, hi: 1 }, &SourceMap::default(), Encoding::Utf8);
+        assert_eq!(location, None);
+    }
+
     fn compile_package() -> (PackageStore, PackageId, PackageId) {
         let mut store = PackageStore::new(compile::core());
         let mut dependencies = Vec::new();
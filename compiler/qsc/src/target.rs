@@ -12,6 +12,18 @@ pub enum Profile {
 }
 
 impl Profile {
+    /// The predefined profiles, from the least to the most capable.
+    pub const ALL: [Self; 2] = [Self::Base, Self::Unrestricted];
+
+    /// The least capable predefined profile that supports all of the given runtime capabilities.
+    #[must_use]
+    pub fn closest(required: RuntimeCapabilityFlags) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|profile| RuntimeCapabilityFlags::from(*profile).contains(required))
+            .unwrap_or(Self::Unrestricted)
+    }
+
     #[must_use]
     pub fn to_str(&self) -> &'static str {
         match self {
@@ -0,0 +1,131 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Reports how long the `tracing` spans of the compiler and the evaluator take. The compiler
+//! enters a span for each of its phases (`parse`, `resolve`, `typeck`, `lower`, `passes`, `rca`
+//! and `codegen`), and the evaluator one for each evaluation (`eval`), so that the time of a build
+//! can be broken down by where it goes.
+
+#[cfg(test)]
+mod tests;
+
+use rustc_hash::FxHashMap;
+use std::sync::{Mutex, MutexGuard};
+use tracing::{
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+
+/// A subscriber that calls `report` with the name of a span and the milliseconds it was entered
+/// for, each time the span is exited. Time is measured with `clock`, which returns milliseconds
+/// since an arbitrary starting point, so that hosts without a system clock can provide their own.
+pub struct SpanTimer<F> {
+    clock: fn() -> f64,
+    report: F,
+    spans: Mutex<Spans>,
+}
+
+#[derive(Default)]
+struct Spans {
+    next_id: u64,
+    by_id: FxHashMap<u64, SpanTiming>,
+}
+
+struct SpanTiming {
+    name: &'static str,
+    entered_at: Option<f64>,
+    refs: usize,
+}
+
+impl<F> SpanTimer<F>
+where
+    F: Fn(&str, f64) + Send + Sync + 'static,
+{
+    #[must_use]
+    pub fn new(clock: fn() -> f64, report: F) -> Self {
+        Self {
+            clock,
+            report,
+            spans: Mutex::default(),
+        }
+    }
+
+    fn spans(&self) -> MutexGuard<'_, Spans> {
+        self.spans
+            .lock()
+            .expect("span timings lock should not be poisoned")
+    }
+}
+
+impl<F> Subscriber for SpanTimer<F>
+where
+    F: Fn(&str, f64) + Send + Sync + 'static,
+{
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.is_span()
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut spans = self.spans();
+        // Span ids can't be zero.
+        spans.next_id += 1;
+        let id = spans.next_id;
+        spans.by_id.insert(
+            id,
+            SpanTiming {
+                name: span.metadata().name(),
+                entered_at: None,
+                refs: 1,
+            },
+        );
+        Id::from_u64(id)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, span: &Id) {
+        let now = (self.clock)();
+        if let Some(timing) = self.spans().by_id.get_mut(&span.into_u64()) {
+            timing.entered_at = Some(now);
+        }
+    }
+
+    fn exit(&self, span: &Id) {
+        let now = (self.clock)();
+        let elapsed = self
+            .spans()
+            .by_id
+            .get_mut(&span.into_u64())
+            .and_then(|timing| Some((timing.name, now - timing.entered_at.take()?)));
+        // The lock is released before reporting, so that the report can enter spans of its own.
+        if let Some((name, elapsed)) = elapsed {
+            (self.report)(name, elapsed);
+        }
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(timing) = self.spans().by_id.get_mut(&span.into_u64()) {
+            timing.refs += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let mut spans = self.spans();
+        let id = span.into_u64();
+        let Some(timing) = spans.by_id.get_mut(&id) else {
+            return false;
+        };
+        timing.refs -= 1;
+        if timing.refs == 0 {
+            spans.by_id.remove(&id);
+            true
+        } else {
+            false
+        }
+    }
+}
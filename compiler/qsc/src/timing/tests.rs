@@ -0,0 +1,62 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::SpanTimer;
+use crate::{
+    compile, LanguageFeatures, PackageStore, PackageType, RuntimeCapabilityFlags, SourceMap,
+};
+use std::sync::{Arc, Mutex};
+use tracing::info_span;
+
+fn zero() -> f64 {
+    0.0
+}
+
+fn record_spans(f: impl FnOnce()) -> Vec<String> {
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let reported = names.clone();
+    let timer = SpanTimer::new(zero, move |name: &str, elapsed: f64| {
+        assert!(elapsed >= 0.0);
+        reported
+            .lock()
+            .expect("lock should not be poisoned")
+            .push(name.to_string());
+    });
+    tracing::subscriber::with_default(timer, f);
+    let names = names.lock().expect("lock should not be poisoned");
+    names.clone()
+}
+
+#[test]
+fn nested_spans_reported_on_exit() {
+    let names = record_spans(|| {
+        info_span!("outer").in_scope(|| {
+            info_span!("inner").in_scope(|| {});
+        });
+    });
+    assert_eq!(names, ["inner", "outer"]);
+}
+
+#[test]
+fn compile_reports_phases() {
+    let names = record_spans(|| {
+        let sources = SourceMap::new(
+            [(
+                "test.qs".into(),
+                "namespace Test { function Foo() : Unit {} }".into(),
+            )],
+            None,
+        );
+        let _ = compile::compile(
+            &PackageStore::new(compile::core()),
+            &[],
+            sources,
+            PackageType::Lib,
+            RuntimeCapabilityFlags::all(),
+            LanguageFeatures::default(),
+        );
+    });
+    for phase in ["parse", "resolve", "typeck", "lower", "passes"] {
+        assert!(names.iter().any(|name| name == phase), "missing {phase}");
+    }
+}
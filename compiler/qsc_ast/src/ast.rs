@@ -262,6 +262,8 @@ pub enum ItemKind {
     Open(Box<Ident>, Option<Box<Ident>>),
     /// A `newtype` declaration.
     Ty(Box<Ident>, Box<TyDef>),
+    /// An `export` declaration, naming items of the namespace that are visible to other packages.
+    Export(Box<[Box<Ident>]>),
 }
 
 impl Display for ItemKind {
@@ -274,6 +276,12 @@ impl Display for ItemKind {
                 None => write!(f, "Open ({name})")?,
             },
             ItemKind::Ty(name, t) => write!(f, "New Type ({name}): {t}")?,
+            ItemKind::Export(names) => {
+                write!(f, "Export")?;
+                for name in &**names {
+                    write!(f, " ({name})")?;
+                }
+            }
         }
         Ok(())
     }
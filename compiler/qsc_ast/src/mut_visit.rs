@@ -111,6 +111,7 @@ pub fn walk_item(vis: &mut impl MutVisitor, item: &mut Item) {
             vis.visit_ident(ident);
             vis.visit_ty_def(def);
         }
+        ItemKind::Export(names) => names.iter_mut().for_each(|n| vis.visit_ident(n)),
     }
 }
 
@@ -101,6 +101,7 @@ pub fn walk_item<'a>(vis: &mut impl Visitor<'a>, item: &'a Item) {
             vis.visit_ident(ident);
             vis.visit_ty_def(def);
         }
+        ItemKind::Export(names) => names.iter().for_each(|n| vis.visit_ident(n)),
     }
 }
 
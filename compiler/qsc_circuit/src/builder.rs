@@ -2,21 +2,39 @@
 // Licensed under the MIT License.
 
 use crate::{
-    circuit::{Circuit, Operation, Register},
+    circuit::{Circuit, ConditionalRender, Operation, Register, SourceLocation},
     Config,
 };
 use num_bigint::BigUint;
 use num_complex::Complex;
 use qsc_codegen::remapper::{HardwareId, Remapper};
 use qsc_data_structures::index_map::IndexMap;
-use qsc_eval::{backend::Backend, val::Value};
-use std::{fmt::Write, mem::take, rc::Rc};
+use qsc_eval::{backend::Backend, val::Value, PackageSpan};
+use qsc_fir::fir::CustomIntrinsic;
+use std::{collections::BTreeSet, fmt::Write, mem::take, rc::Rc};
 
 /// Backend implementation that builds a circuit representation.
 pub struct Builder {
     circuit: Circuit,
+    /// The angle of each operation in the circuit that is a rotation, used to merge
+    /// consecutive rotations when optimizing.
+    angles: Vec<Option<f64>>,
     config: Config,
     remapper: Remapper,
+    /// The classical register of each measurement result, by result id.
+    result_registers: IndexMap<usize, Register>,
+    /// The operations collected for each conditional that is currently being traced,
+    /// innermost last.
+    conditionals: Vec<Conditional>,
+    /// The call that the operations of the current intrinsic come from, and the name of the
+    /// callable it calls.
+    call_site: Option<(PackageSpan, Rc<str>)>,
+}
+
+struct Conditional {
+    control: Register,
+    render: ConditionalRender,
+    operations: Vec<Operation>,
 }
 
 impl Backend for Builder {
@@ -65,6 +83,8 @@ impl Backend for Builder {
             let id = self.remapper.m(q);
 
             self.push_gate(measurement_gate(mapped_q.0, res_id));
+            self.result_registers
+                .insert(id, Register::classical(mapped_q.0, res_id));
             id
         }
     }
@@ -86,6 +106,8 @@ impl Backend for Builder {
             // a measurement and a reset gate.
             self.push_gate(measurement_gate(mapped_q.0, res_id));
             self.push_gate(gate(KET_ZERO, [mapped_q]));
+            self.result_registers
+                .insert(id, Register::classical(mapped_q.0, res_id));
             id
         }
     }
@@ -101,35 +123,35 @@ impl Backend for Builder {
 
     fn rx(&mut self, theta: f64, q: usize) {
         let q = self.map(q);
-        self.push_gate(rotation_gate("rx", theta, [q]));
+        self.push_rotation("rx", theta, [q]);
     }
 
     fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
         let q0 = self.map(q0);
         let q1 = self.map(q1);
-        self.push_gate(rotation_gate("rxx", theta, [q0, q1]));
+        self.push_rotation("rxx", theta, [q0, q1]);
     }
 
     fn ry(&mut self, theta: f64, q: usize) {
         let q = self.map(q);
-        self.push_gate(rotation_gate("ry", theta, [q]));
+        self.push_rotation("ry", theta, [q]);
     }
 
     fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
         let q0 = self.map(q0);
         let q1 = self.map(q1);
-        self.push_gate(rotation_gate("ryy", theta, [q0, q1]));
+        self.push_rotation("ryy", theta, [q0, q1]);
     }
 
     fn rz(&mut self, theta: f64, q: usize) {
         let q = self.map(q);
-        self.push_gate(rotation_gate("rz", theta, [q]));
+        self.push_rotation("rz", theta, [q]);
     }
 
     fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
         let q0 = self.map(q0);
         let q1 = self.map(q1);
-        self.push_gate(rotation_gate("rzz", theta, [q0, q1]));
+        self.push_rotation("rzz", theta, [q0, q1]);
     }
 
     fn sadj(&mut self, q: usize) {
@@ -142,6 +164,11 @@ impl Backend for Builder {
         self.push_gate(gate("S", [q]));
     }
 
+    fn sx(&mut self, q: usize) {
+        let q = self.map(q);
+        self.push_gate(gate("SX", [q]));
+    }
+
     fn swap(&mut self, q0: usize, q1: usize) {
         let q0 = self.map(q0);
         let q1 = self.map(q1);
@@ -208,6 +235,85 @@ impl Backend for Builder {
         ));
         Some(Ok(Value::unit()))
     }
+
+    fn target_intrinsic(
+        &mut self,
+        intrinsic: &CustomIntrinsic,
+        arg: Value,
+    ) -> Option<Result<Value, String>> {
+        let glyph = intrinsic.glyph.as_deref().unwrap_or(&intrinsic.qir_name);
+        self.custom_intrinsic(glyph, arg)
+    }
+
+    fn supports_conditionals(&self) -> bool {
+        // Base Profile measurements are deferred, so their results can't be branched on.
+        !self.config.base_profile
+    }
+
+    fn begin_conditional(&mut self, result: usize, value: bool) {
+        let control = self
+            .result_registers
+            .get(result)
+            .expect("result should have been measured")
+            .clone();
+        self.conditionals.push(Conditional {
+            control,
+            render: if value {
+                ConditionalRender::OnOne
+            } else {
+                ConditionalRender::OnZero
+            },
+            operations: Vec::new(),
+        });
+    }
+
+    fn end_conditional(&mut self) {
+        let conditional = self
+            .conditionals
+            .pop()
+            .expect("conditional should have been started");
+        if conditional.operations.is_empty() {
+            return;
+        }
+        let children = conditional.operations.into_iter().map(|mut op| {
+            op.conditional_render = Some(conditional.render);
+            op
+        });
+
+        // The branches of the same `if` end up as children of a single group.
+        let at_top_level = self.conditionals.is_empty();
+        let siblings = match self.conditionals.last_mut() {
+            Some(parent) => &mut parent.operations,
+            None => &mut self.circuit.operations,
+        };
+        let same_condition = siblings.last().is_some_and(|last| {
+            last.is_conditional && last.controls == [conditional.control.clone()]
+        });
+        if !same_condition {
+            siblings.push(conditional_group(conditional.control));
+            if at_top_level {
+                self.angles.push(None);
+            }
+        }
+        let group = siblings.last_mut().expect("group should be present");
+        group.children.extend(children);
+        group.targets = group
+            .children
+            .iter()
+            .flat_map(qubits)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(Register::quantum)
+            .collect();
+    }
+
+    fn records_call_sites(&self) -> bool {
+        true
+    }
+
+    fn set_call_site(&mut self, span: PackageSpan, callable: Rc<str>) {
+        self.call_site = Some((span, callable));
+    }
 }
 
 impl Builder {
@@ -215,8 +321,12 @@ impl Builder {
     pub fn new(config: Config) -> Self {
         Builder {
             circuit: Circuit::default(),
+            angles: Vec::new(),
             config,
             remapper: Remapper::default(),
+            result_registers: IndexMap::default(),
+            conditionals: Vec::new(),
+            call_site: None,
         }
     }
 
@@ -232,12 +342,94 @@ impl Builder {
         self.finish_circuit(circuit)
     }
 
+    /// The source location of the operations of the current intrinsic. The file and position
+    /// are filled in later by [`Circuit::resolve_sources`].
+    fn source(&self) -> Option<SourceLocation> {
+        self.call_site
+            .as_ref()
+            .map(|(span, callable)| SourceLocation {
+                package: usize::from(span.package),
+                span: span.span,
+                callable: callable.to_string(),
+                file: String::new(),
+                line: 0,
+                column: 0,
+            })
+    }
+
     fn map(&mut self, qubit: usize) -> HardwareId {
         self.remapper.map(qubit)
     }
 
-    fn push_gate(&mut self, gate: Operation) {
+    fn push_gate(&mut self, mut gate: Operation) {
+        gate.source = self.source();
+        if let Some(conditional) = self.conditionals.last_mut() {
+            conditional.operations.push(gate);
+            return;
+        }
+        if self.config.optimize {
+            if let Some(prev) = self.previous_on_same_qubits(&gate) {
+                if is_inverse(&self.circuit.operations[prev], &gate) {
+                    self.remove_operation(prev);
+                    return;
+                }
+            }
+        }
+        self.circuit.operations.push(gate);
+        self.angles.push(None);
+    }
+
+    fn push_rotation<const N: usize>(&mut self, name: &str, theta: f64, targets: [HardwareId; N]) {
+        let mut gate = rotation_gate(name, theta, targets);
+        gate.source = self.source();
+        if let Some(conditional) = self.conditionals.last_mut() {
+            conditional.operations.push(gate);
+            return;
+        }
+        if self.config.optimize {
+            if let Some(prev) = self.previous_on_same_qubits(&gate) {
+                if let (Some(prev_theta), true) = (
+                    self.angles[prev],
+                    self.circuit.operations[prev].gate == gate.gate,
+                ) {
+                    let theta = prev_theta + theta;
+                    if theta.abs() < f64::EPSILON {
+                        self.remove_operation(prev);
+                    } else {
+                        // The merged rotation keeps the source of the first one.
+                        let source = self.circuit.operations[prev].source.take();
+                        self.circuit.operations[prev] = Operation {
+                            source,
+                            ..rotation_gate(name, theta, targets)
+                        };
+                        self.angles[prev] = Some(theta);
+                    }
+                    return;
+                }
+            }
+        }
         self.circuit.operations.push(gate);
+        self.angles.push(Some(theta));
+    }
+
+    /// Finds the last operation that acts on any of the qubits of the given operation,
+    /// if it acts on exactly the same qubits. Only such an operation can be combined with
+    /// the given one, since nothing else is applied to those qubits in between.
+    fn previous_on_same_qubits(&self, op: &Operation) -> Option<usize> {
+        let op_qubits = qubits(op);
+        let (index, prev) = self
+            .circuit
+            .operations
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, prev)| qubits(prev).iter().any(|q| op_qubits.contains(q)))?;
+        (prev.controls == op.controls && prev.targets == op.targets).then_some(index)
+    }
+
+    fn remove_operation(&mut self, index: usize) {
+        self.circuit.operations.remove(index);
+        self.angles.remove(index);
     }
 
     fn num_measurements_by_qubit(&self) -> IndexMap<usize, usize> {
@@ -365,18 +557,47 @@ impl Builder {
 }
 
 #[allow(clippy::unicode_not_nfc)]
-static KET_ZERO: &str = "|0〉";
+pub(crate) static KET_ZERO: &str = "|0〉";
+
+/// Gates that are their own inverse, with or without controls.
+const SELF_INVERSE_GATES: [&str; 6] = ["H", "X", "Y", "Z", "CX", "SWAP"];
+
+/// Gates whose adjoint is their inverse.
+const ADJOINTABLE_GATES: [&str; 2] = ["S", "T"];
+
+/// Whether applying `second` right after `first`, on the same qubits, has no effect.
+fn is_inverse(first: &Operation, second: &Operation) -> bool {
+    if first.gate != second.gate || first.display_args.is_some() || second.display_args.is_some() {
+        return false;
+    }
+
+    let gate = first.gate.as_str();
+    (SELF_INVERSE_GATES.contains(&gate) && !first.is_adjoint && !second.is_adjoint)
+        || (ADJOINTABLE_GATES.contains(&gate) && first.is_adjoint != second.is_adjoint)
+}
+
+fn qubits(op: &Operation) -> Vec<usize> {
+    op.controls
+        .iter()
+        .chain(op.targets.iter())
+        .map(|register| register.q_id)
+        .collect()
+}
 
 fn gate<const N: usize>(name: &str, targets: [HardwareId; N]) -> Operation {
     Operation {
         gate: name.into(),
         display_args: None,
+        params: vec![],
         is_controlled: false,
         is_adjoint: false,
         is_measurement: false,
         controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
+        is_conditional: false,
+        conditional_render: None,
+        source: None,
     }
 }
 
@@ -384,12 +605,16 @@ fn adjoint_gate<const N: usize>(name: &str, targets: [HardwareId; N]) -> Operati
     Operation {
         gate: name.into(),
         display_args: None,
+        params: vec![],
         is_controlled: false,
         is_adjoint: true,
         is_measurement: false,
         controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
+        is_conditional: false,
+        conditional_render: None,
+        source: None,
     }
 }
 
@@ -401,12 +626,34 @@ fn controlled_gate<const M: usize, const N: usize>(
     Operation {
         gate: name.into(),
         display_args: None,
+        params: vec![],
         is_controlled: true,
         is_adjoint: false,
         is_measurement: false,
         controls: controls.iter().map(|q| Register::quantum(q.0)).collect(),
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
+        is_conditional: false,
+        conditional_render: None,
+        source: None,
+    }
+}
+
+/// A group of operations that run depending on the value of the `control` register.
+fn conditional_group(control: Register) -> Operation {
+    Operation {
+        gate: String::new(),
+        display_args: None,
+        params: vec![],
+        is_controlled: false,
+        is_adjoint: false,
+        is_measurement: false,
+        controls: vec![control],
+        targets: vec![],
+        children: vec![],
+        is_conditional: true,
+        conditional_render: None,
+        source: None,
     }
 }
 
@@ -414,12 +661,16 @@ fn measurement_gate(qubit: usize, result: usize) -> Operation {
     Operation {
         gate: "Measure".into(),
         display_args: None,
+        params: vec![],
         is_controlled: false,
         is_adjoint: false,
         is_measurement: true,
         controls: vec![Register::quantum(qubit)],
         targets: vec![Register::classical(qubit, result)],
         children: vec![],
+        is_conditional: false,
+        conditional_render: None,
+        source: None,
     }
 }
 
@@ -427,12 +678,16 @@ fn rotation_gate<const N: usize>(name: &str, theta: f64, targets: [HardwareId; N
     Operation {
         gate: name.into(),
         display_args: Some(format!("{theta:.4}")),
+        params: vec![theta],
         is_controlled: false,
         is_adjoint: false,
         is_measurement: false,
         controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
+        is_conditional: false,
+        conditional_render: None,
+        source: None,
     }
 }
 
@@ -440,11 +695,15 @@ fn custom_gate(name: &str, targets: &[HardwareId], display_args: Option<String>)
     Operation {
         gate: name.into(),
         display_args,
+        params: vec![],
         is_controlled: false,
         is_adjoint: false,
         is_measurement: false,
         controls: vec![],
         targets: targets.iter().map(|q| Register::quantum(q.0)).collect(),
         children: vec![],
+        is_conditional: false,
+        conditional_render: None,
+        source: None,
     }
 }
@@ -1,12 +1,20 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+mod qasm;
+mod svg;
 #[cfg(test)]
 mod tests;
 
+use crate::{
+    builder::KET_ZERO,
+    lifetimes::{LifetimeTracker, QubitLifetime},
+};
+pub use qasm::QasmError;
+use qsc_data_structures::span::Span;
 use rustc_hash::FxHashMap;
 use serde::Serialize;
-use std::{fmt::Display, fmt::Write, ops::Not, vec};
+use std::{collections::BTreeMap, fmt::Display, fmt::Write, ops::Not, ops::Range, vec};
 
 /// Representation of a quantum circuit.
 /// Implementation of <https://github.com/microsoft/quantum-viz.js/wiki/API-schema-reference>
@@ -14,6 +22,9 @@ use std::{fmt::Display, fmt::Write, ops::Not, vec};
 pub struct Circuit {
     pub operations: Vec<Operation>,
     pub qubits: Vec<Qubit>,
+    /// User-provided key/value pairs that identify the experiment the circuit belongs to.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Serialize, Debug, PartialEq)]
@@ -23,6 +34,10 @@ pub struct Operation {
     #[serde(rename = "displayArgs")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_args: Option<String>,
+    /// The exact values of the parameters of the gate, such as rotation angles, which are only
+    /// shown rounded in `display_args`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<f64>,
     #[serde(rename = "isControlled")]
     #[serde(skip_serializing_if = "Not::not")]
     pub is_controlled: bool,
@@ -37,6 +52,63 @@ pub struct Operation {
     pub targets: Vec<Register>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<Operation>,
+    /// Set on a group of operations that only run for some values of the classical
+    /// registers in `controls`. Each child carries its own `conditional_render`.
+    #[serde(rename = "isConditional")]
+    #[serde(skip_serializing_if = "Not::not")]
+    pub is_conditional: bool,
+    #[serde(rename = "conditionalRender")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conditional_render: Option<ConditionalRender>,
+    /// The call in the Q# source that the operation comes from, if it was traced by the evaluator.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceLocation>,
+}
+
+/// The call in the Q# source that an operation comes from, such as a call to `H` in user code.
+/// The builder records the span of the call, and [`Circuit::resolve_sources`] maps it to the
+/// source file and position that viewers show.
+#[derive(Clone, Serialize, Debug, PartialEq)]
+pub struct SourceLocation {
+    /// The package that contains the call.
+    #[serde(skip)]
+    pub package: usize,
+    /// The span of the call in the sources of its package.
+    #[serde(skip)]
+    pub span: Span,
+    /// The name of the callable that is called.
+    pub callable: String,
+    /// The name of the source file that contains the call.
+    pub file: String,
+    /// The zero-based line of the start of the call.
+    pub line: u32,
+    /// The zero-based column of the start of the call.
+    pub column: u32,
+}
+
+/// The measurement outcome for which a child of a conditional group runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConditionalRender {
+    OnZero = 1,
+    OnOne = 2,
+}
+
+impl Serialize for ConditionalRender {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl ConditionalRender {
+    fn glyph(self) -> &'static str {
+        match self {
+            ConditionalRender::OnZero => "○",
+            ConditionalRender::OnOne => "●",
+        }
+    }
 }
 
 const QUANTUM_REGISTER: usize = 0;
@@ -81,9 +153,424 @@ pub struct Qubit {
 pub struct Config {
     /// Perform Base Profile decompositions
     pub base_profile: bool,
+    /// Cancel adjacent inverse gates and merge consecutive rotations on the same qubits
+    pub optimize: bool,
 }
 
-type ObjectsByColumn = FxHashMap<usize, String>;
+/// Options for rendering a circuit as text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// Size each column to fit its widest gate instead of using a fixed width,
+    /// and collapse runs of idle qubit wires into a single line.
+    pub compact: bool,
+    /// Wrap the diagram into blocks of columns so that no line is wider than this
+    /// many characters, unless a single column doesn't fit.
+    pub max_width: Option<usize>,
+}
+
+/// Renders a circuit as text with the given [`DisplayOptions`].
+pub struct CircuitDisplay<'a> {
+    circuit: &'a Circuit,
+    options: DisplayOptions,
+}
+
+impl Circuit {
+    #[must_use]
+    pub fn display(&self, options: DisplayOptions) -> CircuitDisplay<'_> {
+        CircuitDisplay {
+            circuit: self,
+            options,
+        }
+    }
+
+    /// Fills in the file, line and column of the source location of every operation, including
+    /// the children of conditional groups, from the package and span of the call. Locations that
+    /// `resolve` can't map to a source file are removed.
+    pub fn resolve_sources(&mut self, resolve: impl Fn(usize, Span) -> Option<(String, u32, u32)>) {
+        fn resolve_all(
+            operations: &mut [Operation],
+            resolve: &impl Fn(usize, Span) -> Option<(String, u32, u32)>,
+        ) {
+            for op in operations {
+                op.source = op.source.take().and_then(|mut source| {
+                    (source.file, source.line, source.column) =
+                        resolve(source.package, source.span)?;
+                    Some(source)
+                });
+                resolve_all(&mut op.children, resolve);
+            }
+        }
+        resolve_all(&mut self.operations, &resolve);
+    }
+
+    /// Returns the part of the circuit with the operations whose time step is in `steps`, on all
+    /// of the qubits. Like the columns of the diagram, an operation's time step is the first one
+    /// after every operation that comes before it on the wires it spans, and each operation,
+    /// including a conditional group as a whole, takes one time step.
+    #[must_use]
+    pub fn slice(&self, steps: Range<usize>) -> Circuit {
+        let operations = self
+            .operations
+            .iter()
+            .zip(self.time_steps())
+            .filter(|(_, step)| steps.contains(step))
+            .map(|(op, _)| op.clone())
+            .collect();
+        Circuit {
+            operations,
+            qubits: self.qubits.clone(),
+            metadata: self.metadata.clone(),
+        }
+    }
+
+    /// Returns the part of the circuit on the qubits with the given ids. Operations that also act
+    /// on other qubits, such as a CNOT from a selected qubit to another one, are left out, since
+    /// they can't be drawn without those qubits. Ids that aren't in the circuit are ignored.
+    #[must_use]
+    pub fn filter_qubits(&self, ids: &[usize]) -> Circuit {
+        let operations = self
+            .operations
+            .iter()
+            .filter(|op| {
+                let mut registers = vec![];
+                op_registers(op, &mut registers);
+                registers.iter().all(|reg| ids.contains(&reg.q_id))
+            })
+            .cloned()
+            .collect();
+        let qubits = self
+            .qubits
+            .iter()
+            .filter(|q| ids.contains(&q.id))
+            .cloned()
+            .collect();
+        Circuit {
+            operations,
+            qubits,
+            metadata: self.metadata.clone(),
+        }
+    }
+
+    /// Returns an equivalent circuit on fewer qubits, where a qubit that has been reset, such as
+    /// after a measurement, is reused for a qubit whose first operation comes after the reset.
+    /// Qubits start out in the zero state, so a reset qubit can stand in for a new one. Measurement
+    /// results move with the operations that produce them, and qubits without any operations are
+    /// left out.
+    #[must_use]
+    pub fn reuse_qubits(&self) -> Circuit {
+        // Split the operations on each qubit into segments that end with a reset of the qubit,
+        // keeping the segment of each qubit an operation belongs to.
+        let mut segments: Vec<Range<usize>> = vec![];
+        let mut ends_with_reset = vec![];
+        let mut open_segments = FxHashMap::default();
+        let mut op_segments = vec![];
+        for (index, op) in self.operations.iter().enumerate() {
+            let mut registers = vec![];
+            op_registers(op, &mut registers);
+            let mut qubit_segments = FxHashMap::default();
+            for reg in registers.iter().filter(|reg| reg.c_id.is_none()) {
+                let segment = *open_segments.entry(reg.q_id).or_insert_with(|| {
+                    segments.push(index..index + 1);
+                    ends_with_reset.push(false);
+                    segments.len() - 1
+                });
+                segments[segment].end = index + 1;
+                qubit_segments.insert(reg.q_id, segment);
+            }
+            if is_reset(op) {
+                if let Some(segment) = open_segments.remove(&op.targets[0].q_id) {
+                    ends_with_reset[segment] = true;
+                }
+            }
+            op_segments.push(qubit_segments);
+        }
+
+        // Segments start in operation order, so placing each one on the first qubit that is free
+        // by then uses as few qubits as the overlapping segments need. A qubit only becomes free
+        // again once it is reset.
+        let mut qubit_ends: Vec<usize> = vec![];
+        let segment_qubits = segments
+            .iter()
+            .zip(ends_with_reset)
+            .map(|(segment, reset)| {
+                let end = if reset { segment.end } else { usize::MAX };
+                if let Some(qubit) = qubit_ends.iter().position(|e| *e <= segment.start) {
+                    qubit_ends[qubit] = end;
+                    qubit
+                } else {
+                    qubit_ends.push(end);
+                    qubit_ends.len() - 1
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut results = FxHashMap::default();
+        let mut num_results = vec![0; qubit_ends.len()];
+        let operations = self
+            .operations
+            .iter()
+            .zip(op_segments)
+            .map(|(op, qubit_segments)| {
+                let qubits = qubit_segments
+                    .into_iter()
+                    .map(|(q_id, segment)| (q_id, segment_qubits[segment]))
+                    .collect();
+                let mut op = op.clone();
+                remap_registers(&mut op, &qubits, &mut results, &mut num_results);
+                op
+            })
+            .collect();
+        let qubits = num_results
+            .into_iter()
+            .enumerate()
+            .map(|(id, num_children)| Qubit { id, num_children })
+            .collect();
+        Circuit {
+            operations,
+            qubits,
+            metadata: self.metadata.clone(),
+        }
+    }
+
+    /// The lifetime of each qubit of the circuit, in the order of the qubits, with times that are
+    /// the time steps of [`Circuit::slice`]. A qubit counts as allocated from its first operation
+    /// until the time step after its last one, and qubits without any operations have an empty
+    /// lifetime at the start. Operations in a conditional group act on the qubits of each child on
+    /// its own.
+    #[must_use]
+    pub fn qubit_lifetimes(&self) -> Vec<QubitLifetime> {
+        fn operate(tracker: &mut LifetimeTracker, op: &Operation, step: u64) {
+            if op.is_conditional {
+                for child in &op.children {
+                    operate(tracker, child, step);
+                }
+                return;
+            }
+            let mut registers = vec![];
+            op_registers(op, &mut registers);
+            let mut qubits = registers
+                .iter()
+                .filter(|reg| reg.c_id.is_none())
+                .map(|reg| reg.q_id)
+                .collect::<Vec<_>>();
+            qubits.dedup();
+            for q in &qubits {
+                tracker.allocate(*q, step);
+            }
+            tracker.operate(&qubits, step);
+        }
+
+        let mut tracker = LifetimeTracker::default();
+        let mut last_steps = FxHashMap::default();
+        for (op, step) in self.operations.iter().zip(self.time_steps()) {
+            let step = step as u64;
+            operate(&mut tracker, op, step);
+            let mut registers = vec![];
+            op_registers(op, &mut registers);
+            for reg in registers.iter().filter(|reg| reg.c_id.is_none()) {
+                last_steps.insert(reg.q_id, step);
+            }
+        }
+        for q in &self.qubits {
+            match last_steps.get(&q.id) {
+                Some(last_step) => tracker.release(q.id, last_step + 1),
+                None => {
+                    tracker.allocate(q.id, 0);
+                    tracker.release(q.id, 0);
+                }
+            }
+        }
+
+        let mut lifetimes = tracker.finish();
+        lifetimes.sort_by_key(|lifetime| {
+            self.qubits
+                .iter()
+                .position(|q| q.id == lifetime.qubit)
+                .unwrap_or(usize::MAX)
+        });
+        lifetimes
+    }
+
+    /// The zero-based time step of each top-level operation, as used by [`Circuit::slice`].
+    fn time_steps(&self) -> Vec<usize> {
+        let mut register_to_row = FxHashMap::default();
+        for q in &self.qubits {
+            register_to_row.insert((q.id, None), register_to_row.len());
+            for i in 0..q.num_children {
+                register_to_row.insert((q.id, Some(i)), register_to_row.len());
+            }
+        }
+
+        let mut next_step = vec![0; register_to_row.len()];
+        self.operations
+            .iter()
+            .map(|op| {
+                let mut registers = vec![];
+                op_registers(op, &mut registers);
+                let rows = registers
+                    .iter()
+                    .filter_map(|reg| register_to_row.get(&(reg.q_id, reg.c_id)).copied());
+                let (Some(begin), Some(end)) = (rows.clone().min(), rows.max()) else {
+                    return 0;
+                };
+                let step = next_step[begin..=end]
+                    .iter()
+                    .copied()
+                    .max()
+                    .unwrap_or_default();
+                next_step[begin..=end].fill(step + 1);
+                step
+            })
+            .collect()
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![];
+
+        // Maintain a mapping from from Registers in the Circuit schema
+        // to row in the diagram
+        let mut register_to_row = FxHashMap::default();
+
+        // Initialize all qubit and classical wires
+        for q in &self.qubits {
+            rows.push(Row {
+                wire: Wire::Qubit { q_id: q.id },
+                objects: FxHashMap::default(),
+                next_column: 1,
+            });
+
+            register_to_row.insert((q.id, None), rows.len() - 1);
+
+            for i in 0..q.num_children {
+                rows.push(Row {
+                    wire: Wire::Classical { start_column: None },
+                    objects: FxHashMap::default(),
+                    next_column: 1,
+                });
+
+                register_to_row.insert((q.id, Some(i)), rows.len() - 1);
+            }
+        }
+
+        for o in &self.operations {
+            add_operation(&mut rows, &register_to_row, o, &[]);
+        }
+
+        rows
+    }
+}
+
+impl Display for Circuit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.display(DisplayOptions::default()).fmt(f)
+    }
+}
+
+impl Display for CircuitDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = self.circuit.rows();
+
+        // Find the end column for the whole circuit so that
+        // all qubit wires will extend until the end
+        let end_column = rows
+            .iter()
+            .max_by_key(|r| r.next_column)
+            .map_or(1, |r| r.next_column);
+
+        let lines = if self.options.compact {
+            elide_idle_qubits(&rows)
+        } else {
+            rows.iter().map(Line::Row).collect()
+        };
+
+        // Column 0 holds the qubit labels
+        let mut widths = vec![COLUMN_WIDTH; end_column];
+        if self.options.compact {
+            widths[0] = lines
+                .iter()
+                .filter_map(|line| match line {
+                    Line::Row(row) => Some(row.label().chars().count() + 1),
+                    Line::Elided { .. } => None,
+                })
+                .max()
+                .unwrap_or_default();
+            // Leave room for a piece of wire on each side of the widest object
+            for (column, width) in widths.iter_mut().enumerate().skip(1) {
+                *width = rows
+                    .iter()
+                    .filter_map(|row| row.objects.get(&column))
+                    .map(|object| object.width() + 2)
+                    .fold(MIN_COMPACT_COLUMN_WIDTH, usize::max);
+            }
+        }
+
+        // Split the columns into blocks that fit in the maximum width
+        let mut block_starts = vec![1];
+        let mut block_width = widths[0];
+        for (column, width) in widths.iter().enumerate().skip(1) {
+            if self
+                .options
+                .max_width
+                .is_some_and(|max_width| block_width + width > max_width)
+                && block_starts.last().is_some_and(|start| column > *start)
+            {
+                block_starts.push(column);
+                block_width = widths[0];
+            }
+            block_width += width;
+        }
+        let block_ends = block_starts.iter().skip(1).copied().chain([end_column]);
+
+        // Draw the diagram
+        for (i, (start, end)) in block_starts.iter().copied().zip(block_ends).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for line in &lines {
+                match line {
+                    Line::Row(row) => row.fmt(f, &widths, start..end)?,
+                    Line::Elided { first, last } => writeln!(f, "q_{first} … q_{last}")?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A line of the diagram. In compact mode, consecutive qubits without any
+/// operations are drawn as a single line.
+enum Line<'a> {
+    Row(&'a Row),
+    Elided { first: usize, last: usize },
+}
+
+fn elide_idle_qubits(rows: &[Row]) -> Vec<Line<'_>> {
+    let idle_qubit = |row: &Row| match row.wire {
+        Wire::Qubit { q_id } if row.objects.is_empty() => Some(q_id),
+        _ => None,
+    };
+    let mut lines = vec![];
+    let mut rows = rows.iter().peekable();
+    while let Some(row) = rows.next() {
+        let Some(first) = idle_qubit(row) else {
+            lines.push(Line::Row(row));
+            continue;
+        };
+        let mut last = None;
+        while let Some(q_id) = rows.peek().and_then(|next| idle_qubit(next)) {
+            rows.next();
+            last = Some(q_id);
+        }
+        match last {
+            Some(last) => lines.push(Line::Elided { first, last }),
+            None => lines.push(Line::Row(row)),
+        }
+    }
+    lines
+}
+
+type ObjectsByColumn = FxHashMap<usize, Object>;
 
 struct Row {
     wire: Wire,
@@ -96,14 +583,43 @@ enum Wire {
     Classical { start_column: Option<usize> },
 }
 
+/// An object drawn in a column of a row, centered on the wire and padded on
+/// each side to the width of the column.
+struct Object {
+    left: char,
+    text: String,
+    right: char,
+}
+
+impl Object {
+    fn new(fill: char, text: impl Into<String>) -> Self {
+        Self {
+            left: fill,
+            text: text.into(),
+            right: fill,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn fmt(&self, s: &mut String, width: usize) {
+        let padding = width.saturating_sub(self.width());
+        push_repeated(s, self.left, padding / 2);
+        s.push_str(&self.text);
+        push_repeated(s, self.right, padding - padding / 2);
+    }
+}
+
 impl Row {
     fn add_object(&mut self, column: usize, object: &str) {
         match &mut self.wire {
             Wire::Qubit { .. } => {
-                self.add(column, fmt_on_qubit_wire(object));
+                self.add(column, Object::new(QUBIT_WIRE, format!(" {object} ")));
             }
             Wire::Classical { .. } => {
-                self.add(column, fmt_on_classical_wire(object));
+                self.add(column, Object::new(CLASSICAL_WIRE, format!(" {object} ")));
             }
         };
     }
@@ -123,12 +639,12 @@ impl Row {
     fn add_vertical(&mut self, column: usize) {
         if self.objects.get(&column).is_none() {
             match self.wire {
-                Wire::Qubit { .. } => self.add(column, QUBIT_WIRE_CROSS),
+                Wire::Qubit { .. } => self.add(column, Object::new(QUBIT_WIRE, "┼")),
                 Wire::Classical { start_column } => {
                     if start_column.is_some() {
-                        self.add(column, CLASSICAL_WIRE_CROSS);
+                        self.add(column, Object::new(CLASSICAL_WIRE, "╪"));
                     } else {
-                        self.add(column, VERTICAL);
+                        self.add(column, Object::new(BLANK, "│"));
                     }
                 }
             }
@@ -138,12 +654,12 @@ impl Row {
     fn add_dashed_vertical(&mut self, column: usize) {
         if self.objects.get(&column).is_none() {
             match self.wire {
-                Wire::Qubit { .. } => self.add(column, QUBIT_WIRE_DASHED_CROSS),
+                Wire::Qubit { .. } => self.add(column, Object::new(QUBIT_WIRE, "┆")),
                 Wire::Classical { start_column } => {
                     if start_column.is_some() {
-                        self.add(column, CLASSICAL_WIRE_DASHED_CROSS);
+                        self.add(column, Object::new(CLASSICAL_WIRE, "┆"));
                     } else {
-                        self.add(column, VERTICAL_DASHED);
+                        self.add(column, Object::new(BLANK, "┆"));
                     }
                 }
             }
@@ -151,43 +667,54 @@ impl Row {
     }
 
     fn start_classical(&mut self, column: usize) {
-        self.add(column, CLASSICAL_WIRE_START);
+        self.add(
+            column,
+            Object {
+                left: BLANK,
+                text: "╘".to_string(),
+                right: CLASSICAL_WIRE,
+            },
+        );
         if let Wire::Classical { start_column } = &mut self.wire {
             start_column.replace(column);
         }
     }
 
-    fn add(&mut self, column: usize, v: impl Into<String>) {
-        self.objects.insert(column, v.into());
+    fn add(&mut self, column: usize, object: Object) {
+        self.objects.insert(column, object);
         self.next_column = column + 1;
     }
 
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>, end_column: usize) -> std::fmt::Result {
+    fn label(&self) -> String {
+        match self.wire {
+            Wire::Qubit { q_id } => format!("q_{q_id}"),
+            Wire::Classical { .. } => String::new(),
+        }
+    }
+
+    /// Draws the given columns of the row, preceded by the label column.
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        widths: &[usize],
+        columns: Range<usize>,
+    ) -> std::fmt::Result {
         // Temporary string so we can trim whitespace at the end
-        let mut s = String::new();
-        match &self.wire {
-            Wire::Qubit { q_id: label } => {
-                s.write_str(&fmt_qubit_label(*label))?;
-                for column in 1..end_column {
-                    let val = self.objects.get(&column);
-                    if let Some(v) = val {
-                        s.write_str(v)?;
-                    } else {
-                        s.write_str(QUBIT_WIRE)?;
-                    }
-                }
-            }
-            Wire::Classical { start_column } => {
-                for column in 0..end_column {
-                    let val = self.objects.get(&column);
-                    if let Some(v) = val {
-                        s.write_str(v)?;
-                    } else if start_column.map_or(false, |s| column > s) {
-                        s.write_str(CLASSICAL_WIRE)?;
+        let mut s = format!("{: <width$}", self.label(), width = widths[0]);
+        for column in columns {
+            let fill = match self.wire {
+                Wire::Qubit { .. } => QUBIT_WIRE,
+                Wire::Classical { start_column } => {
+                    if start_column.map_or(false, |s| column > s) {
+                        CLASSICAL_WIRE
                     } else {
-                        s.write_str(BLANK)?;
+                        BLANK
                     }
                 }
+            };
+            match self.objects.get(&column) {
+                Some(object) => object.fmt(&mut s, widths[column]),
+                None => push_repeated(&mut s, fill, widths[column]),
             }
         }
         writeln!(f, "{}", s.trim_end())?;
@@ -195,152 +722,161 @@ impl Row {
     }
 }
 
-const COLUMN_WIDTH: usize = 7;
-const QUBIT_WIRE: &str = "───────";
-const CLASSICAL_WIRE: &str = "═══════";
-const QUBIT_WIRE_CROSS: &str = "───┼───";
-const CLASSICAL_WIRE_CROSS: &str = "═══╪═══";
-const CLASSICAL_WIRE_START: &str = "   ╘═══";
-const QUBIT_WIRE_DASHED_CROSS: &str = "───┆───";
-const CLASSICAL_WIRE_DASHED_CROSS: &str = "═══┆═══";
-const VERTICAL_DASHED: &str = "   ┆   ";
-const VERTICAL: &str = "   │   ";
-const BLANK: &str = "       ";
-
-/// "q_0  "
-#[allow(clippy::doc_markdown)]
-fn fmt_qubit_label(id: usize) -> String {
-    let rest = COLUMN_WIDTH - 2;
-    format!("q_{id: <rest$}")
+fn push_repeated(s: &mut String, c: char, count: usize) {
+    for _ in 0..count {
+        s.push(c);
+    }
 }
 
-/// "── A ──"
-fn fmt_on_qubit_wire(obj: &str) -> String {
-    format!("{:─^COLUMN_WIDTH$}", format!(" {obj} "))
+/// Collects the registers that an operation and its children act on.
+fn op_registers<'a>(op: &'a Operation, registers: &mut Vec<&'a Register>) {
+    registers.extend(op.controls.iter().chain(&op.targets));
+    for child in &op.children {
+        op_registers(child, registers);
+    }
 }
 
-/// "══ A ══"
-fn fmt_on_classical_wire(obj: &str) -> String {
-    format!("{:═^COLUMN_WIDTH$}", format!(" {obj} "))
+/// Whether the operation resets a single qubit to the zero state.
+fn is_reset(op: &Operation) -> bool {
+    op.gate == KET_ZERO && op.controls.is_empty() && !op.is_conditional && op.targets.len() == 1
 }
 
-impl Display for Circuit {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut rows = vec![];
-
-        // Maintain a mapping from from Registers in the Circuit schema
-        // to row in the diagram
-        let mut register_to_row = FxHashMap::default();
-
-        // Initialize all qubit and classical wires
-        for q in &self.qubits {
-            rows.push(Row {
-                wire: Wire::Qubit { q_id: q.id },
-                objects: FxHashMap::default(),
-                next_column: 1,
-            });
-
-            register_to_row.insert((q.id, None), rows.len() - 1);
-
-            for i in 0..q.num_children {
-                rows.push(Row {
-                    wire: Wire::Classical { start_column: None },
-                    objects: FxHashMap::default(),
-                    next_column: 1,
-                });
-
-                register_to_row.insert((q.id, Some(i)), rows.len() - 1);
+/// Moves the registers of an operation and its children to the qubits they are reused on, where
+/// `qubits` maps the qubits of the operation to their new ids. The results of measurements get
+/// the next free result of their new qubit, and `results` keeps where each one moved to for the
+/// operations that are conditioned on it.
+fn remap_registers(
+    op: &mut Operation,
+    qubits: &FxHashMap<usize, usize>,
+    results: &mut FxHashMap<(usize, usize), Register>,
+    num_results: &mut [usize],
+) {
+    if op.is_measurement {
+        for target in &op.targets {
+            if let (Some(c_id), Some(&q_id)) = (target.c_id, qubits.get(&target.q_id)) {
+                results.insert(
+                    (target.q_id, c_id),
+                    Register::classical(q_id, num_results[q_id]),
+                );
+                num_results[q_id] += 1;
             }
         }
+    }
+    for reg in op.controls.iter_mut().chain(&mut op.targets) {
+        let moved = match reg.c_id {
+            None => qubits.get(&reg.q_id).copied().map(Register::quantum),
+            Some(c_id) => results.get(&(reg.q_id, c_id)).cloned(),
+        };
+        if let Some(moved) = moved {
+            *reg = moved;
+        }
+    }
+    for child in &mut op.children {
+        remap_registers(child, qubits, results, num_results);
+    }
+}
 
-        for o in &self.operations {
-            // Row indexes for the targets for this operation
-            let targets = o
-                .targets
-                .iter()
-                .filter_map(|reg| {
-                    let reg = (reg.q_id, reg.c_id);
-                    register_to_row.get(&reg).cloned()
-                })
-                .collect::<Vec<_>>();
+const COLUMN_WIDTH: usize = 7;
+const MIN_COMPACT_COLUMN_WIDTH: usize = 3;
+const QUBIT_WIRE: char = '─';
+const CLASSICAL_WIRE: char = '═';
+const BLANK: char = ' ';
+
+/// Adds an operation to the diagram. `conditions` holds the classical rows, and the glyph to
+/// draw on each, for the conditional groups that the operation is nested in.
+fn add_operation(
+    rows: &mut [Row],
+    register_to_row: &FxHashMap<(usize, Option<usize>), usize>,
+    o: &Operation,
+    conditions: &[(usize, &str)],
+) {
+    let rows_of = |registers: &[Register]| {
+        registers
+            .iter()
+            .filter_map(|reg| {
+                let reg = (reg.q_id, reg.c_id);
+                register_to_row.get(&reg).copied()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if o.is_conditional {
+        // Draw each child of the group as if it were controlled on the classical registers
+        let control_rows = rows_of(&o.controls);
+        for child in &o.children {
+            let mut child_conditions = conditions.to_vec();
+            if let Some(render) = child.conditional_render {
+                child_conditions.extend(control_rows.iter().map(|row| (*row, render.glyph())));
+            }
+            add_operation(rows, register_to_row, child, &child_conditions);
+        }
+        return;
+    }
 
-            // Row indexes for the controls for this operation
-            let controls = o
-                .controls
-                .iter()
-                .filter_map(|reg| {
-                    let reg = (reg.q_id, reg.c_id);
-                    register_to_row.get(&reg).cloned()
-                })
-                .collect::<Vec<_>>();
+    // Row indexes for the targets for this operation
+    let targets = rows_of(&o.targets);
+
+    // Row indexes for the controls for this operation
+    let controls = rows_of(&o.controls);
+
+    let mut all_rows = targets.clone();
+    all_rows.extend(controls.iter());
+    all_rows.extend(conditions.iter().map(|(row, _)| row));
+    all_rows.sort_unstable();
+    // We'll need to know the entire range of rows for this operation so we can
+    // figure out the starting column and also so we can draw any
+    // vertical lines that cross wires.
+    let (begin, end) = all_rows.split_first().map_or((0, 0), |(first, tail)| {
+        (*first, tail.last().unwrap_or(first) + 1)
+    });
+
+    // The starting column - the first available column in all
+    // the rows that this operation spans.
+    let mut column = 1;
+    for row in &rows[begin..end] {
+        if row.next_column > column {
+            column = row.next_column;
+        }
+    }
 
-            let mut all_rows = targets.clone();
-            all_rows.extend(controls.iter());
-            all_rows.sort_unstable();
-            // We'll need to know the entire range of rows for this operation so we can
-            // figure out the starting column and also so we can draw any
-            // vertical lines that cross wires.
-            let (begin, end) = all_rows.split_first().map_or((0, 0), |(first, tail)| {
-                (*first, tail.last().unwrap_or(first) + 1)
-            });
+    // Add the operation to the diagram
+    for i in targets {
+        let row = &mut rows[i];
+        if matches!(row.wire, Wire::Classical { .. }) && o.is_measurement {
+            row.start_classical(column);
+        } else {
+            row.add_gate(column, &o.gate, o.display_args.as_deref(), o.is_adjoint);
+        };
+    }
 
-            // The starting column - the first available column in all
-            // the rows that this operation spans.
-            let mut column = 1;
-            for row in &rows[begin..end] {
-                if row.next_column > column {
-                    column = row.next_column;
-                }
-            }
+    for (i, glyph) in conditions {
+        rows[*i].add_object(column, glyph);
+    }
 
-            // Add the operation to the diagram
-            for i in targets {
+    if o.is_controlled || o.is_measurement || !conditions.is_empty() {
+        if o.is_controlled || o.is_measurement {
+            for i in controls {
                 let row = &mut rows[i];
-                if matches!(row.wire, Wire::Classical { .. }) && o.is_measurement {
-                    row.start_classical(column);
+                if matches!(row.wire, Wire::Qubit { .. }) && o.is_measurement {
+                    row.add_object(column, "M");
                 } else {
-                    row.add_gate(column, &o.gate, o.display_args.as_deref(), o.is_adjoint);
+                    row.add_object(column, "●");
                 };
             }
-
-            if o.is_controlled || o.is_measurement {
-                for i in controls {
-                    let row = &mut rows[i];
-                    if matches!(row.wire, Wire::Qubit { .. }) && o.is_measurement {
-                        row.add_object(column, "M");
-                    } else {
-                        row.add_object(column, "●");
-                    };
-                }
-
-                // If we have a control wire, draw vertical lines spanning all
-                // control and target wires and crossing any in between
-                // (vertical lines may overlap if there are multiple controls/targets,
-                // this is ok in practice)
-                for row in &mut rows[begin..end] {
-                    row.add_vertical(column);
-                }
-            } else {
-                // No control wire. Draw dashed vertical lines to connect
-                // target wires if there are multiple targets
-                for row in &mut rows[begin..end] {
-                    row.add_dashed_vertical(column);
-                }
-            }
         }
 
-        // Find the end column for the whole circuit so that
-        // all qubit wires will extend until the end
-        let end_column = rows
-            .iter()
-            .max_by_key(|r| r.next_column)
-            .map_or(1, |r| r.next_column);
-
-        // Draw the diagram
-        for row in rows {
-            row.fmt(f, end_column)?;
+        // If we have a control wire, draw vertical lines spanning all
+        // control and target wires and crossing any in between
+        // (vertical lines may overlap if there are multiple controls/targets,
+        // this is ok in practice)
+        for row in &mut rows[begin..end] {
+            row.add_vertical(column);
+        }
+    } else {
+        // No control wire. Draw dashed vertical lines to connect
+        // target wires if there are multiple targets
+        for row in &mut rows[begin..end] {
+            row.add_dashed_vertical(column);
         }
-
-        Ok(())
     }
 }
@@ -0,0 +1,189 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Rendering of circuits as OpenQASM 3 programs. Every qubit of the circuit becomes an element of
+//! the register `q`, and every measurement result an element of the register `c`, in the order of
+//! their qubits.
+
+#[cfg(test)]
+mod tests;
+
+use super::{Circuit, ConditionalRender, Operation, Register};
+use crate::builder::KET_ZERO;
+use rustc_hash::FxHashMap;
+use std::fmt::{self, Display, Formatter, Write};
+
+/// An operation of a circuit that has no equivalent in OpenQASM, such as a custom intrinsic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QasmError {
+    pub gate: String,
+}
+
+impl Display for QasmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "gate `{}` can't be converted to OpenQASM", self.gate)
+    }
+}
+
+impl std::error::Error for QasmError {}
+
+/// Definitions of the two-qubit rotations, which aren't part of the standard gates of OpenQASM.
+const GATE_DEFINITIONS: [(&str, &str); 3] = [
+    (
+        "rxx",
+        "gate rxx(theta) a, b { h a; h b; cx a, b; rz(theta) b; cx a, b; h b; h a; }",
+    ),
+    (
+        "ryy",
+        "gate ryy(theta) a, b { rx(pi/2) a; rx(pi/2) b; cx a, b; rz(theta) b; cx a, b; rx(-pi/2) b; rx(-pi/2) a; }",
+    ),
+    (
+        "rzz",
+        "gate rzz(theta) a, b { cx a, b; rz(theta) b; cx a, b; }",
+    ),
+];
+
+impl Circuit {
+    /// Renders the circuit as an OpenQASM 3 program.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for the first operation that has no equivalent in OpenQASM.
+    pub fn to_qasm(&self) -> Result<String, QasmError> {
+        let mut bits = FxHashMap::default();
+        for qubit in &self.qubits {
+            for result in 0..qubit.num_children {
+                let bit = bits.len();
+                bits.insert((qubit.id, result), bit);
+            }
+        }
+        let writer = Writer { bits };
+
+        let mut body = String::new();
+        for operation in &self.operations {
+            writer.write_operation(&mut body, operation, 0)?;
+        }
+
+        let mut program = "OPENQASM 3.0;\ninclude \"stdgates.inc\";\n".to_string();
+        for (name, definition) in GATE_DEFINITIONS {
+            if self.operations.iter().any(|op| uses_gate(op, name)) {
+                let _ = writeln!(program, "{definition}");
+            }
+        }
+        let qubit_count = self
+            .qubits
+            .iter()
+            .map(|q| q.id + 1)
+            .max()
+            .unwrap_or_default();
+        if qubit_count > 0 {
+            let _ = writeln!(program, "qubit[{qubit_count}] q;");
+        }
+        if !writer.bits.is_empty() {
+            let _ = writeln!(program, "bit[{}] c;", writer.bits.len());
+        }
+        program.push_str(&body);
+        Ok(program)
+    }
+}
+
+fn uses_gate(operation: &Operation, name: &str) -> bool {
+    operation.gate == name || operation.children.iter().any(|op| uses_gate(op, name))
+}
+
+struct Writer {
+    /// The index into the bit register of each measurement result, by qubit and result id.
+    bits: FxHashMap<(usize, usize), usize>,
+}
+
+impl Writer {
+    fn write_operation(
+        &self,
+        s: &mut String,
+        operation: &Operation,
+        indent: usize,
+    ) -> Result<(), QasmError> {
+        let unsupported = || QasmError {
+            gate: operation.gate.clone(),
+        };
+
+        if operation.is_conditional {
+            let [control] = operation.controls.as_slice() else {
+                return Err(unsupported());
+            };
+            let bit = self.bit(control).ok_or_else(unsupported)?;
+            // Consecutive children that run for the same outcome share a block.
+            let mut children = operation.children.iter().peekable();
+            while let Some(first) = children.next() {
+                let render = first.conditional_render;
+                let value = match render {
+                    Some(ConditionalRender::OnZero) => 0,
+                    Some(ConditionalRender::OnOne) => 1,
+                    None => return Err(unsupported()),
+                };
+                let _ = writeln!(s, "{:indent$}if (c[{bit}] == {value}) {{", "");
+                self.write_operation(s, first, indent + 4)?;
+                while let Some(child) = children.next_if(|op| op.conditional_render == render) {
+                    self.write_operation(s, child, indent + 4)?;
+                }
+                let _ = writeln!(s, "{:indent$}}}", "");
+            }
+            return Ok(());
+        }
+
+        if operation.is_measurement {
+            let ([qubit], [result]) = (operation.controls.as_slice(), operation.targets.as_slice())
+            else {
+                return Err(unsupported());
+            };
+            let bit = self.bit(result).ok_or_else(unsupported)?;
+            let _ = writeln!(s, "{:indent$}c[{bit}] = measure q[{}];", "", qubit.q_id);
+            return Ok(());
+        }
+
+        let (mut name, is_rotation) = match operation.gate.as_str() {
+            // Controlled X gates with two controls are named `CX` by the circuit builder.
+            "X" | "CX" => ("x".to_string(), false),
+            "Y" => ("y".to_string(), false),
+            "Z" => ("z".to_string(), false),
+            "H" => ("h".to_string(), false),
+            "S" => ("s".to_string(), false),
+            "T" => ("t".to_string(), false),
+            "SX" => ("sx".to_string(), false),
+            "SWAP" => ("swap".to_string(), false),
+            gate @ ("rx" | "ry" | "rz" | "rxx" | "ryy" | "rzz") => (gate.to_string(), true),
+            gate if gate == KET_ZERO => ("reset".to_string(), false),
+            _ => return Err(unsupported()),
+        };
+        if is_rotation {
+            let theta = operation.params.first().ok_or_else(unsupported)?;
+            let _ = write!(name, "({theta})");
+        }
+        if operation.is_adjoint {
+            name = match name.as_str() {
+                "s" | "t" => format!("{name}dg"),
+                _ => format!("inv @ {name}"),
+            };
+        }
+        name = match (operation.controls.len(), name.as_str()) {
+            (0, _) => name,
+            (1, "x" | "y" | "z") => format!("c{name}"),
+            (2, "x") => "ccx".to_string(),
+            (1, _) => format!("ctrl @ {name}"),
+            (controls, _) => format!("ctrl({controls}) @ {name}"),
+        };
+
+        let qubits = operation
+            .controls
+            .iter()
+            .chain(&operation.targets)
+            .map(|register| format!("q[{}]", register.q_id))
+            .collect::<Vec<_>>();
+        let _ = writeln!(s, "{:indent$}{name} {};", "", qubits.join(", "));
+        Ok(())
+    }
+
+    fn bit(&self, register: &Register) -> Option<usize> {
+        self.bits.get(&(register.q_id, register.c_id?)).copied()
+    }
+}
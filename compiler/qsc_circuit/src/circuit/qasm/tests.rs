@@ -0,0 +1,174 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::QasmError;
+use crate::{
+    builder::KET_ZERO,
+    circuit::{Circuit, ConditionalRender, Operation, Qubit, Register},
+};
+use expect_test::expect;
+
+fn gate(name: &str, controls: &[usize], targets: &[usize]) -> Operation {
+    Operation {
+        gate: name.to_string(),
+        display_args: None,
+        params: vec![],
+        is_controlled: !controls.is_empty(),
+        is_adjoint: false,
+        is_measurement: false,
+        controls: controls.iter().copied().map(Register::quantum).collect(),
+        targets: targets.iter().copied().map(Register::quantum).collect(),
+        children: vec![],
+        is_conditional: false,
+        conditional_render: None,
+        source: None,
+    }
+}
+
+fn measurement(qubit: usize, result: usize) -> Operation {
+    Operation {
+        is_measurement: true,
+        controls: vec![Register::quantum(qubit)],
+        targets: vec![Register::classical(qubit, result)],
+        ..gate("Measure", &[], &[])
+    }
+}
+
+fn qubits(num_children: &[usize]) -> Vec<Qubit> {
+    num_children
+        .iter()
+        .enumerate()
+        .map(|(id, num_children)| Qubit {
+            id,
+            num_children: *num_children,
+        })
+        .collect()
+}
+
+#[test]
+fn bell() {
+    let c = Circuit {
+        operations: vec![
+            gate("H", &[], &[0]),
+            gate("X", &[0], &[1]),
+            measurement(0, 0),
+            measurement(1, 0),
+        ],
+        qubits: qubits(&[1, 1]),
+        ..Default::default()
+    };
+
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        qubit[2] q;
+        bit[2] c;
+        h q[0];
+        cx q[0], q[1];
+        c[0] = measure q[0];
+        c[1] = measure q[1];
+    "#]]
+    .assert_eq(&c.to_qasm().expect("circuit should convert to OpenQASM"));
+}
+
+#[test]
+fn rotations_adjoints_and_resets() {
+    let c = Circuit {
+        operations: vec![
+            Operation {
+                params: vec![1.0],
+                ..gate("rzz", &[], &[0, 1])
+            },
+            Operation {
+                params: vec![0.5],
+                ..gate("rx", &[], &[0])
+            },
+            Operation {
+                is_adjoint: true,
+                ..gate("S", &[], &[1])
+            },
+            Operation {
+                is_adjoint: true,
+                ..gate("SX", &[], &[0])
+            },
+            gate("CX", &[0, 1], &[2]),
+            gate(KET_ZERO, &[], &[2]),
+        ],
+        qubits: qubits(&[0, 0, 0]),
+        ..Default::default()
+    };
+
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        gate rzz(theta) a, b { cx a, b; rz(theta) b; cx a, b; }
+        qubit[3] q;
+        rzz(1) q[0], q[1];
+        rx(0.5) q[0];
+        sdg q[1];
+        inv @ sx q[0];
+        ccx q[0], q[1], q[2];
+        reset q[2];
+    "#]]
+    .assert_eq(&c.to_qasm().expect("circuit should convert to OpenQASM"));
+}
+
+#[test]
+fn conditional_group() {
+    let child = |name: &str, render| Operation {
+        conditional_render: Some(render),
+        source: None,
+        ..gate(name, &[], &[1])
+    };
+    let c = Circuit {
+        operations: vec![
+            measurement(0, 0),
+            Operation {
+                controls: vec![Register::classical(0, 0)],
+                children: vec![
+                    child("X", ConditionalRender::OnOne),
+                    child("Y", ConditionalRender::OnOne),
+                    child("Z", ConditionalRender::OnZero),
+                ],
+                is_conditional: true,
+                ..gate("", &[], &[1])
+            },
+        ],
+        qubits: qubits(&[1, 0]),
+        ..Default::default()
+    };
+
+    expect![[r#"
+        OPENQASM 3.0;
+        include "stdgates.inc";
+        qubit[2] q;
+        bit[1] c;
+        c[0] = measure q[0];
+        if (c[0] == 1) {
+            x q[1];
+            y q[1];
+        }
+        if (c[0] == 0) {
+            z q[1];
+        }
+    "#]]
+    .assert_eq(&c.to_qasm().expect("circuit should convert to OpenQASM"));
+}
+
+#[test]
+fn custom_gate_is_unsupported() {
+    let c = Circuit {
+        operations: vec![gate("H", &[], &[0]), gate("Foo", &[], &[0])],
+        qubits: qubits(&[0]),
+        ..Default::default()
+    };
+
+    let error = c.to_qasm().expect_err("custom gate should not convert");
+    assert_eq!(
+        error,
+        QasmError {
+            gate: "Foo".to_string()
+        }
+    );
+    expect!["gate `Foo` can't be converted to OpenQASM"].assert_eq(&error.to_string());
+}
@@ -0,0 +1,288 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Rendering of circuits as SVG images. The layout follows the text rendering: each qubit gets a
+//! wire, followed by a wire for each of its measurement results, and each operation is placed in
+//! the first column where all of the wires it spans are free.
+
+#[cfg(test)]
+mod tests;
+
+use super::{Circuit, ConditionalRender, Operation, Register};
+use rustc_hash::FxHashMap;
+use std::fmt::Write;
+
+const LABEL_WIDTH: usize = 50;
+const ROW_HEIGHT: usize = 40;
+const GATE_HEIGHT: usize = 30;
+const MIN_GATE_WIDTH: usize = 30;
+const CHAR_WIDTH: usize = 8;
+const COLUMN_PADDING: usize = 10;
+const DOT_RADIUS: usize = 5;
+
+impl Circuit {
+    /// Renders the circuit as an SVG image.
+    #[must_use]
+    pub fn to_svg(&self) -> String {
+        let mut layout = Layout::default();
+        for qubit in &self.qubits {
+            layout.rows.push(Wire::Qubit(qubit.id));
+            layout
+                .register_to_row
+                .insert((qubit.id, None), layout.rows.len() - 1);
+            for result in 0..qubit.num_children {
+                layout.rows.push(Wire::Classical { start_column: None });
+                layout
+                    .register_to_row
+                    .insert((qubit.id, Some(result)), layout.rows.len() - 1);
+            }
+        }
+        layout.next_column = vec![0; layout.rows.len()];
+        for operation in &self.operations {
+            layout.add_operation(operation, &[]);
+        }
+        layout.render()
+    }
+}
+
+/// A wire of the diagram. Classical wires start at the column of the measurement that sets them.
+enum Wire {
+    Qubit(usize),
+    Classical { start_column: Option<usize> },
+}
+
+enum Shape {
+    Gate(String),
+    Measure,
+    Control,
+    Condition(ConditionalRender),
+}
+
+/// A vertical line that connects the wires an operation spans.
+struct Connector {
+    column: usize,
+    top: usize,
+    bottom: usize,
+    dashed: bool,
+}
+
+#[derive(Default)]
+struct Layout {
+    rows: Vec<Wire>,
+    register_to_row: FxHashMap<(usize, Option<usize>), usize>,
+    /// The first free column of each row.
+    next_column: Vec<usize>,
+    /// The shapes drawn on the wires, by row and column.
+    shapes: Vec<(usize, usize, Shape)>,
+    connectors: Vec<Connector>,
+}
+
+impl Layout {
+    fn rows_of(&self, registers: &[Register]) -> Vec<usize> {
+        registers
+            .iter()
+            .filter_map(|reg| self.register_to_row.get(&(reg.q_id, reg.c_id)).copied())
+            .collect()
+    }
+
+    /// Adds an operation to the diagram. `conditions` holds the classical rows, and the outcome
+    /// drawn on each, for the conditional groups that the operation is nested in.
+    fn add_operation(&mut self, operation: &Operation, conditions: &[(usize, ConditionalRender)]) {
+        if operation.is_conditional {
+            let control_rows = self.rows_of(&operation.controls);
+            for child in &operation.children {
+                let mut child_conditions = conditions.to_vec();
+                if let Some(render) = child.conditional_render {
+                    child_conditions.extend(control_rows.iter().map(|row| (*row, render)));
+                }
+                self.add_operation(child, &child_conditions);
+            }
+            return;
+        }
+
+        let targets = self.rows_of(&operation.targets);
+        let controls = self.rows_of(&operation.controls);
+        let spanned = targets
+            .iter()
+            .chain(&controls)
+            .chain(conditions.iter().map(|(row, _)| row))
+            .copied()
+            .collect::<Vec<_>>();
+        let (Some(top), Some(bottom)) = (spanned.iter().min(), spanned.iter().max()) else {
+            return;
+        };
+        let (top, bottom) = (*top, *bottom);
+        let column = self.next_column[top..=bottom]
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or_default();
+        self.next_column[top..=bottom].fill(column + 1);
+
+        for row in targets {
+            match &mut self.rows[row] {
+                Wire::Classical { start_column } if operation.is_measurement => {
+                    start_column.get_or_insert(column);
+                }
+                _ => self
+                    .shapes
+                    .push((row, column, Shape::Gate(label(operation)))),
+            }
+        }
+        for row in controls {
+            let shape = if operation.is_measurement {
+                Shape::Measure
+            } else {
+                Shape::Control
+            };
+            self.shapes.push((row, column, shape));
+        }
+        for (row, render) in conditions {
+            self.shapes.push((*row, column, Shape::Condition(*render)));
+        }
+        if top < bottom {
+            self.connectors.push(Connector {
+                column,
+                top,
+                bottom,
+                dashed: !operation.is_controlled
+                    && !operation.is_measurement
+                    && conditions.is_empty(),
+            });
+        }
+    }
+
+    fn render(&self) -> String {
+        let column_count = self.next_column.iter().copied().max().unwrap_or_default();
+        let mut widths = vec![MIN_GATE_WIDTH + COLUMN_PADDING; column_count];
+        for (_, column, shape) in &self.shapes {
+            if let Shape::Gate(label) = shape {
+                widths[*column] = widths[*column].max(gate_width(label) + COLUMN_PADDING);
+            }
+        }
+        let mut centers = Vec::with_capacity(column_count);
+        let mut x = LABEL_WIDTH;
+        for width in &widths {
+            centers.push(x + width / 2);
+            x += width;
+        }
+        let width = x + COLUMN_PADDING;
+        let height = self.rows.len() * ROW_HEIGHT;
+        let y = |row: usize| row * ROW_HEIGHT + ROW_HEIGHT / 2;
+
+        let mut s = String::new();
+        let _ = writeln!(
+            s,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="14">"#
+        );
+
+        for (row, wire) in self.rows.iter().enumerate() {
+            let y = y(row);
+            match wire {
+                Wire::Qubit(q_id) => {
+                    let _ = writeln!(
+                        s,
+                        r#"<text x="5" y="{y}" dominant-baseline="middle">q_{q_id}</text>"#
+                    );
+                    let _ = writeln!(
+                        s,
+                        r#"<line x1="{LABEL_WIDTH}" y1="{y}" x2="{width}" y2="{y}" stroke="black"/>"#
+                    );
+                }
+                Wire::Classical {
+                    start_column: Some(column),
+                } => {
+                    let x = centers[*column];
+                    for y in [y - 2, y + 2] {
+                        let _ = writeln!(
+                            s,
+                            r#"<line x1="{x}" y1="{y}" x2="{width}" y2="{y}" stroke="black"/>"#
+                        );
+                    }
+                }
+                Wire::Classical { start_column: None } => {}
+            }
+        }
+
+        for connector in &self.connectors {
+            let x = centers[connector.column];
+            let dash = if connector.dashed {
+                r#" stroke-dasharray="4""#
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                s,
+                r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="black"{dash}/>"#,
+                y(connector.top),
+                y(connector.bottom)
+            );
+        }
+
+        for (row, column, shape) in &self.shapes {
+            let (x, y) = (centers[*column], y(*row));
+            match shape {
+                Shape::Gate(label) => gate(&mut s, x, y, label),
+                Shape::Measure => gate(&mut s, x, y, "M"),
+                Shape::Control | Shape::Condition(ConditionalRender::OnOne) => {
+                    dot(&mut s, x, y, "black");
+                }
+                Shape::Condition(ConditionalRender::OnZero) => dot(&mut s, x, y, "white"),
+            }
+        }
+
+        s.push_str("</svg>\n");
+        s
+    }
+}
+
+fn label(operation: &Operation) -> String {
+    let mut label = operation.gate.clone();
+    if operation.is_adjoint {
+        label.push('\'');
+    }
+    if let Some(args) = &operation.display_args {
+        let _ = write!(label, "({args})");
+    }
+    label
+}
+
+fn gate_width(label: &str) -> usize {
+    (label.chars().count() * CHAR_WIDTH + COLUMN_PADDING).max(MIN_GATE_WIDTH)
+}
+
+fn gate(s: &mut String, x: usize, y: usize, label: &str) {
+    let width = gate_width(label);
+    let _ = writeln!(
+        s,
+        r#"<rect x="{}" y="{}" width="{width}" height="{GATE_HEIGHT}" fill="white" stroke="black"/>"#,
+        x - width / 2,
+        y - GATE_HEIGHT / 2
+    );
+    let _ = writeln!(
+        s,
+        r#"<text x="{x}" y="{y}" text-anchor="middle" dominant-baseline="middle">{}</text>"#,
+        escape(label)
+    );
+}
+
+fn dot(s: &mut String, x: usize, y: usize, fill: &str) {
+    let _ = writeln!(
+        s,
+        r#"<circle cx="{x}" cy="{y}" r="{DOT_RADIUS}" fill="{fill}" stroke="black"/>"#
+    );
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,91 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use crate::circuit::{Circuit, Operation, Qubit, Register};
+use expect_test::expect;
+
+fn gate(name: &str, controls: &[usize], targets: &[usize]) -> Operation {
+    Operation {
+        gate: name.to_string(),
+        display_args: None,
+        params: vec![],
+        is_controlled: !controls.is_empty(),
+        is_adjoint: false,
+        is_measurement: false,
+        controls: controls.iter().copied().map(Register::quantum).collect(),
+        targets: targets.iter().copied().map(Register::quantum).collect(),
+        children: vec![],
+        is_conditional: false,
+        conditional_render: None,
+        source: None,
+    }
+}
+
+#[test]
+fn controlled_gate_and_measurement() {
+    let c = Circuit {
+        operations: vec![
+            gate("H", &[], &[0]),
+            gate("X", &[0], &[1]),
+            Operation {
+                is_measurement: true,
+                controls: vec![Register::quantum(1)],
+                targets: vec![Register::classical(1, 0)],
+                ..gate("Measure", &[], &[])
+            },
+        ],
+        qubits: vec![
+            Qubit {
+                id: 0,
+                num_children: 0,
+            },
+            Qubit {
+                id: 1,
+                num_children: 1,
+            },
+        ],
+        ..Default::default()
+    };
+
+    expect![[r#"
+        <svg xmlns="http://www.w3.org/2000/svg" width="180" height="120" viewBox="0 0 180 120" font-family="sans-serif" font-size="14">
+        <text x="5" y="20" dominant-baseline="middle">q_0</text>
+        <line x1="50" y1="20" x2="180" y2="20" stroke="black"/>
+        <text x="5" y="60" dominant-baseline="middle">q_1</text>
+        <line x1="50" y1="60" x2="180" y2="60" stroke="black"/>
+        <line x1="150" y1="98" x2="180" y2="98" stroke="black"/>
+        <line x1="150" y1="102" x2="180" y2="102" stroke="black"/>
+        <line x1="110" y1="20" x2="110" y2="60" stroke="black"/>
+        <line x1="150" y1="60" x2="150" y2="100" stroke="black"/>
+        <rect x="55" y="5" width="30" height="30" fill="white" stroke="black"/>
+        <text x="70" y="20" text-anchor="middle" dominant-baseline="middle">H</text>
+        <rect x="95" y="45" width="30" height="30" fill="white" stroke="black"/>
+        <text x="110" y="60" text-anchor="middle" dominant-baseline="middle">X</text>
+        <circle cx="110" cy="20" r="5" fill="black" stroke="black"/>
+        <rect x="135" y="45" width="30" height="30" fill="white" stroke="black"/>
+        <text x="150" y="60" text-anchor="middle" dominant-baseline="middle">M</text>
+        </svg>
+    "#]]
+    .assert_eq(&c.to_svg());
+}
+
+#[test]
+fn labels_are_escaped() {
+    let c = Circuit {
+        operations: vec![Operation {
+            display_args: Some("\"a<b\"".to_string()),
+            ..gate("Foo", &[], &[0])
+        }],
+        qubits: vec![Qubit {
+            id: 0,
+            num_children: 0,
+        }],
+        ..Default::default()
+    };
+
+    let svg = c.to_svg();
+    assert!(
+        svg.contains(">Foo(&quot;a&lt;b&quot;)</text>"),
+        "label should be escaped: {svg}"
+    );
+}
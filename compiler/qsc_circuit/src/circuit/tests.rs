@@ -9,6 +9,7 @@ fn empty() {
     let c = Circuit {
         operations: vec![],
         qubits: vec![],
+        ..Default::default()
     };
 
     expect![[""]].assert_eq(&c.to_string());
@@ -28,6 +29,7 @@ fn no_gates() {
                 num_children: 0,
             },
         ],
+        ..Default::default()
     };
 
     expect![[r"
@@ -44,42 +46,58 @@ fn bell() {
             Operation {
                 gate: "H".to_string(),
                 display_args: None,
+                params: vec![],
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: false,
                 controls: vec![],
                 targets: vec![Register::quantum(0)],
                 children: vec![],
+                is_conditional: false,
+                conditional_render: None,
+                source: None,
             },
             Operation {
                 gate: "X".to_string(),
                 display_args: None,
+                params: vec![],
                 is_controlled: true,
                 is_adjoint: false,
                 is_measurement: false,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::quantum(1)],
                 children: vec![],
+                is_conditional: false,
+                conditional_render: None,
+                source: None,
             },
             Operation {
                 gate: "Measure".to_string(),
                 display_args: None,
+                params: vec![],
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: true,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::classical(0, 0)],
                 children: vec![],
+                is_conditional: false,
+                conditional_render: None,
+                source: None,
             },
             Operation {
                 gate: "Measure".to_string(),
                 display_args: None,
+                params: vec![],
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: true,
                 controls: vec![Register::quantum(1)],
                 targets: vec![Register::classical(1, 0)],
                 children: vec![],
+                is_conditional: false,
+                conditional_render: None,
+                source: None,
             },
         ],
         qubits: vec![
@@ -92,6 +110,7 @@ fn bell() {
                 num_children: 1,
             },
         ],
+        ..Default::default()
     };
 
     expect![[r"
@@ -110,32 +129,44 @@ fn control_classical() {
             Operation {
                 gate: "Measure".to_string(),
                 display_args: None,
+                params: vec![],
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: true,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::classical(0, 0)],
                 children: vec![],
+                is_conditional: false,
+                conditional_render: None,
+                source: None,
             },
             Operation {
                 gate: "X".to_string(),
                 display_args: None,
+                params: vec![],
                 is_controlled: true,
                 is_adjoint: false,
                 is_measurement: false,
                 controls: vec![Register::classical(0, 0)],
                 targets: vec![Register::quantum(2)],
                 children: vec![],
+                is_conditional: false,
+                conditional_render: None,
+                source: None,
             },
             Operation {
                 gate: "X".to_string(),
                 display_args: None,
+                params: vec![],
                 is_controlled: true,
                 is_adjoint: false,
                 is_measurement: false,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::quantum(2)],
                 children: vec![],
+                is_conditional: false,
+                conditional_render: None,
+                source: None,
             },
         ],
         qubits: vec![
@@ -152,6 +183,7 @@ fn control_classical() {
                 num_children: 0,
             },
         ],
+        ..Default::default()
     };
 
     expect![[r"
@@ -170,28 +202,37 @@ fn two_measurements() {
             Operation {
                 gate: "Measure".to_string(),
                 display_args: None,
+                params: vec![],
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: true,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::classical(0, 0)],
                 children: vec![],
+                is_conditional: false,
+                conditional_render: None,
+                source: None,
             },
             Operation {
                 gate: "Measure".to_string(),
                 display_args: None,
+                params: vec![],
                 is_controlled: false,
                 is_adjoint: false,
                 is_measurement: true,
                 controls: vec![Register::quantum(0)],
                 targets: vec![Register::classical(0, 1)],
                 children: vec![],
+                is_conditional: false,
+                conditional_render: None,
+                source: None,
             },
         ],
         qubits: vec![Qubit {
             id: 0,
             num_children: 2,
         }],
+        ..Default::default()
     };
 
     expect![[r"
@@ -208,17 +249,22 @@ fn with_args() {
         operations: vec![Operation {
             gate: "rx".to_string(),
             display_args: Some("1.5708".to_string()),
+            params: vec![],
             is_controlled: false,
             is_adjoint: false,
             is_measurement: false,
             controls: vec![],
             targets: vec![Register::quantum(0)],
             children: vec![],
+            is_conditional: false,
+            conditional_render: None,
+            source: None,
         }],
         qubits: vec![Qubit {
             id: 0,
             num_children: 0,
         }],
+        ..Default::default()
     };
 
     // This looks wonky because the gate label is longer
@@ -235,12 +281,16 @@ fn two_targets() {
         operations: vec![Operation {
             gate: "rzz".to_string(),
             display_args: Some("1.0000".to_string()),
+            params: vec![],
             is_controlled: false,
             is_adjoint: false,
             is_measurement: false,
             controls: vec![],
             targets: vec![Register::quantum(0), Register::quantum(2)],
             children: vec![],
+            is_conditional: false,
+            conditional_render: None,
+            source: None,
         }],
         qubits: vec![
             Qubit {
@@ -256,6 +306,7 @@ fn two_targets() {
                 num_children: 0,
             },
         ],
+        ..Default::default()
     };
 
     // This looks wonky because the gate label is longer
@@ -267,3 +318,376 @@ fn two_targets() {
     "]]
     .assert_eq(&c.to_string());
 }
+
+#[test]
+fn conditional_group() {
+    let gate = |name: &str, render| Operation {
+        gate: name.to_string(),
+        display_args: None,
+        params: vec![],
+        is_controlled: false,
+        is_adjoint: false,
+        is_measurement: false,
+        controls: vec![],
+        targets: vec![Register::quantum(1)],
+        children: vec![],
+        is_conditional: false,
+        conditional_render: Some(render),
+        source: None,
+    };
+    let c = Circuit {
+        operations: vec![
+            Operation {
+                gate: "Measure".to_string(),
+                display_args: None,
+                params: vec![],
+                is_controlled: false,
+                is_adjoint: false,
+                is_measurement: true,
+                controls: vec![Register::quantum(0)],
+                targets: vec![Register::classical(0, 0)],
+                children: vec![],
+                is_conditional: false,
+                conditional_render: None,
+                source: None,
+            },
+            Operation {
+                gate: String::new(),
+                display_args: None,
+                params: vec![],
+                is_controlled: false,
+                is_adjoint: false,
+                is_measurement: false,
+                controls: vec![Register::classical(0, 0)],
+                targets: vec![Register::quantum(1)],
+                children: vec![
+                    gate("X", ConditionalRender::OnOne),
+                    gate("Z", ConditionalRender::OnZero),
+                ],
+                is_conditional: true,
+                conditional_render: None,
+                source: None,
+            },
+        ],
+        qubits: vec![
+            Qubit {
+                id: 0,
+                num_children: 1,
+            },
+            Qubit {
+                id: 1,
+                num_children: 0,
+            },
+        ],
+        ..Default::default()
+    };
+
+    expect![[r"
+        q_0    ── M ────────────────
+                  ╘═════ ● ════ ○ ══
+        q_1    ───────── X ──── Z ──
+    "]]
+    .assert_eq(&c.to_string());
+
+    expect![[r#"{"gate":"","controls":[{"qId":0,"type":1,"cId":0}],"targets":[{"qId":1,"type":0}],"children":[{"gate":"X","targets":[{"qId":1,"type":0}],"conditionalRender":2},{"gate":"Z","targets":[{"qId":1,"type":0}],"conditionalRender":1}],"isConditional":true}"#]]
+    .assert_eq(&serde_json::to_string(&c.operations[1]).expect("operation should serialize"));
+}
+
+fn single_qubit_gate(name: &str, display_args: Option<&str>, q_id: usize) -> Operation {
+    Operation {
+        gate: name.to_string(),
+        display_args: display_args.map(ToString::to_string),
+        params: vec![],
+        is_controlled: false,
+        is_adjoint: false,
+        is_measurement: false,
+        controls: vec![],
+        targets: vec![Register::quantum(q_id)],
+        children: vec![],
+        is_conditional: false,
+        conditional_render: None,
+        source: None,
+    }
+}
+
+fn qubits(count: usize) -> Vec<Qubit> {
+    (0..count)
+        .map(|id| Qubit {
+            id,
+            num_children: 0,
+        })
+        .collect()
+}
+
+#[test]
+fn compact_columns_fit_labels() {
+    let c = Circuit {
+        operations: vec![
+            single_qubit_gate("rx", Some("1.5708"), 0),
+            single_qubit_gate("H", None, 0),
+            single_qubit_gate("H", None, 1),
+            single_qubit_gate("rx", Some("1.5708"), 1),
+        ],
+        qubits: qubits(2),
+        ..Default::default()
+    };
+
+    expect![[r"
+        q_0 ─ rx(1.5708) ────── H ──────
+        q_1 ───── H ─────── rx(1.5708) ─
+    "]]
+    .assert_eq(
+        &c.display(DisplayOptions {
+            compact: true,
+            max_width: None,
+        })
+        .to_string(),
+    );
+}
+
+#[test]
+fn compact_elides_idle_qubits() {
+    let c = Circuit {
+        operations: vec![
+            single_qubit_gate("H", None, 0),
+            single_qubit_gate("X", None, 2),
+            single_qubit_gate("X", None, 6),
+        ],
+        qubits: qubits(7),
+        ..Default::default()
+    };
+
+    expect![[r"
+        q_0 ─ H ─
+        q_1 ─────
+        q_2 ─ X ─
+        q_3 … q_5
+        q_6 ─ X ─
+    "]]
+    .assert_eq(
+        &c.display(DisplayOptions {
+            compact: true,
+            max_width: None,
+        })
+        .to_string(),
+    );
+}
+
+#[test]
+fn max_width_wraps_columns() {
+    let c = Circuit {
+        operations: vec![
+            single_qubit_gate("H", None, 0),
+            single_qubit_gate("X", None, 0),
+            single_qubit_gate("Y", None, 0),
+            single_qubit_gate("Z", None, 0),
+            single_qubit_gate("S", None, 1),
+        ],
+        qubits: qubits(2),
+        ..Default::default()
+    };
+
+    expect![[r"
+        q_0    ── H ──── X ──── Y ──
+        q_1    ── S ────────────────
+
+        q_0    ── Z ──
+        q_1    ───────
+    "]]
+    .assert_eq(
+        &c.display(DisplayOptions {
+            compact: false,
+            max_width: Some(30),
+        })
+        .to_string(),
+    );
+}
+
+fn slicing_circuit() -> Circuit {
+    Circuit {
+        operations: vec![
+            single_qubit_gate("H", None, 0),
+            single_qubit_gate("X", None, 1),
+            Operation {
+                is_controlled: true,
+                controls: vec![Register::quantum(0)],
+                ..single_qubit_gate("X", None, 1)
+            },
+            single_qubit_gate("Z", None, 2),
+            single_qubit_gate("Y", None, 0),
+            single_qubit_gate("S", None, 2),
+        ],
+        qubits: qubits(3),
+        ..Default::default()
+    }
+}
+
+fn gates(c: &Circuit) -> Vec<&str> {
+    c.operations.iter().map(|op| op.gate.as_str()).collect()
+}
+
+#[test]
+fn slice_keeps_operations_in_time_steps() {
+    let c = slicing_circuit().slice(1..3);
+
+    assert_eq!(gates(&c), ["X", "Y", "S"]);
+    assert!(c.operations[0].is_controlled);
+    assert_eq!(c.qubits, qubits(3));
+}
+
+#[test]
+fn slice_past_end_is_empty() {
+    let c = slicing_circuit().slice(3..10);
+
+    assert!(c.operations.is_empty());
+    assert_eq!(c.qubits, qubits(3));
+}
+
+#[test]
+fn filter_qubits_drops_operations_on_other_qubits() {
+    let c = slicing_circuit().filter_qubits(&[0, 2, 5]);
+
+    assert_eq!(gates(&c), ["H", "Z", "Y", "S"]);
+    assert_eq!(c.qubits.iter().map(|q| q.id).collect::<Vec<_>>(), [0, 2]);
+    expect![[r"
+        q_0    ── H ──── Y ──
+        q_2    ── Z ──── S ──
+    "]]
+    .assert_eq(&c.to_string());
+}
+
+fn measurement(q_id: usize, c_id: usize) -> Operation {
+    Operation {
+        is_measurement: true,
+        controls: vec![Register::quantum(q_id)],
+        targets: vec![Register::classical(q_id, c_id)],
+        ..single_qubit_gate("Measure", None, q_id)
+    }
+}
+
+#[test]
+fn reuse_qubits_moves_operations_after_reset() {
+    let c = Circuit {
+        operations: vec![
+            single_qubit_gate("H", None, 0),
+            measurement(0, 0),
+            single_qubit_gate(KET_ZERO, None, 0),
+            single_qubit_gate("X", None, 1),
+            measurement(1, 0),
+            Operation {
+                is_conditional: true,
+                controls: vec![Register::classical(1, 0)],
+                children: vec![Operation {
+                    conditional_render: Some(ConditionalRender::OnOne),
+                    ..single_qubit_gate("Z", None, 2)
+                }],
+                ..single_qubit_gate("", None, 2)
+            },
+        ],
+        qubits: vec![
+            Qubit {
+                id: 0,
+                num_children: 1,
+            },
+            Qubit {
+                id: 1,
+                num_children: 1,
+            },
+            Qubit {
+                id: 2,
+                num_children: 0,
+            },
+        ],
+        ..Default::default()
+    }
+    .reuse_qubits();
+
+    assert_eq!(
+        c.qubits,
+        [
+            Qubit {
+                id: 0,
+                num_children: 2,
+            },
+            Qubit {
+                id: 1,
+                num_children: 0,
+            },
+        ]
+    );
+    assert_eq!(c.operations[3].targets, [Register::quantum(0)]);
+    assert_eq!(c.operations[4].targets, [Register::classical(0, 1)]);
+    assert_eq!(c.operations[5].controls, [Register::classical(0, 1)]);
+    assert_eq!(c.operations[5].children[0].targets, [Register::quantum(1)]);
+}
+
+#[test]
+fn reuse_qubits_keeps_qubits_without_reset() {
+    let c = Circuit {
+        operations: vec![
+            single_qubit_gate("H", None, 0),
+            measurement(0, 0),
+            single_qubit_gate("X", None, 1),
+        ],
+        qubits: vec![
+            Qubit {
+                id: 0,
+                num_children: 1,
+            },
+            Qubit {
+                id: 1,
+                num_children: 0,
+            },
+        ],
+        ..Default::default()
+    };
+
+    assert_eq!(c.reuse_qubits(), c);
+}
+
+#[test]
+#[allow(clippy::single_range_in_vec_init)]
+fn qubit_lifetimes_track_idle_steps_and_partners() {
+    let c = Circuit {
+        operations: vec![
+            single_qubit_gate("X", None, 1),
+            single_qubit_gate("H", None, 0),
+            single_qubit_gate("S", None, 0),
+            single_qubit_gate("T", None, 0),
+            Operation {
+                is_controlled: true,
+                controls: vec![Register::quantum(0)],
+                ..single_qubit_gate("X", None, 1)
+            },
+        ],
+        qubits: qubits(3),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        c.qubit_lifetimes(),
+        [
+            QubitLifetime {
+                qubit: 0,
+                allocated: 0,
+                released: Some(4),
+                idle: vec![],
+                partners: vec![(3, 1)],
+            },
+            QubitLifetime {
+                qubit: 1,
+                allocated: 0,
+                released: Some(4),
+                idle: vec![1..3],
+                partners: vec![(3, 0)],
+            },
+            QubitLifetime {
+                qubit: 2,
+                allocated: 0,
+                released: Some(0),
+                idle: vec![],
+                partners: vec![],
+            },
+        ]
+    );
+}
@@ -3,7 +3,12 @@
 
 mod builder;
 mod circuit;
+mod lifetimes;
 pub mod operations;
 
 pub use builder::Builder;
-pub use circuit::{Circuit, Config, Operation};
+pub use circuit::{
+    Circuit, CircuitDisplay, ConditionalRender, Config, DisplayOptions, Operation, QasmError,
+    Register, SourceLocation,
+};
+pub use lifetimes::{LifetimeTracker, QubitLifetime};
@@ -0,0 +1,85 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use rustc_hash::FxHashMap;
+use std::ops::Range;
+
+/// The lifetime of one allocation of a qubit, for visualizations of when qubits are in use.
+/// Times count operations in a run and time steps in a circuit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QubitLifetime {
+    /// The id of the qubit. Ids are reused once a qubit is released.
+    pub qubit: usize,
+    /// When the qubit was allocated.
+    pub allocated: u64,
+    /// When the qubit was released, or `None` if it was still allocated at the end.
+    pub released: Option<u64>,
+    /// The windows of time between allocation and release in which no operation acted on the
+    /// qubit, in order.
+    pub idle: Vec<Range<u64>>,
+    /// The qubits that a multi-qubit operation acted on together with this one, along with the
+    /// time of the operation, in order.
+    pub partners: Vec<(u64, usize)>,
+}
+
+/// Builds the lifetimes of qubits from the allocations, operations and releases of a trace.
+#[derive(Debug, Default)]
+pub struct LifetimeTracker {
+    lifetimes: Vec<QubitLifetime>,
+    /// The index of the lifetime of each allocated qubit, and the time it was last used.
+    live: FxHashMap<usize, (usize, u64)>,
+}
+
+impl LifetimeTracker {
+    /// Starts a lifetime of the qubit, unless it is already allocated.
+    pub fn allocate(&mut self, qubit: usize, time: u64) {
+        if self.live.contains_key(&qubit) {
+            return;
+        }
+        self.live.insert(qubit, (self.lifetimes.len(), time));
+        self.lifetimes.push(QubitLifetime {
+            qubit,
+            allocated: time,
+            ..QubitLifetime::default()
+        });
+    }
+
+    /// Records an operation that acts on the given qubits. Qubits that aren't allocated are
+    /// ignored.
+    pub fn operate(&mut self, qubits: &[usize], time: u64) {
+        for &qubit in qubits {
+            let Some((index, last_used)) = self.live.get_mut(&qubit) else {
+                continue;
+            };
+            let lifetime = &mut self.lifetimes[*index];
+            if time > *last_used + 1 {
+                lifetime.idle.push(*last_used + 1..time);
+            }
+            *last_used = time;
+            lifetime.partners.extend(
+                qubits
+                    .iter()
+                    .filter(|partner| **partner != qubit)
+                    .map(|partner| (time, *partner)),
+            );
+        }
+    }
+
+    /// Ends the lifetime of the qubit.
+    pub fn release(&mut self, qubit: usize, time: u64) {
+        let Some((index, last_used)) = self.live.remove(&qubit) else {
+            return;
+        };
+        let lifetime = &mut self.lifetimes[index];
+        if time > last_used + 1 {
+            lifetime.idle.push(last_used + 1..time);
+        }
+        lifetime.released = Some(time);
+    }
+
+    /// The lifetimes of the qubits, in the order they were allocated.
+    #[must_use]
+    pub fn finish(self) -> Vec<QubitLifetime> {
+        self.lifetimes
+    }
+}
@@ -13,13 +13,18 @@ use qsc_eval::{
     eval,
     output::GenericReceiver,
     val::Value,
-    Env, Error,
+    Env, Error, Limits, PackageSpan,
 };
 use qsc_fir::fir;
 use qsc_frontend::compile::PackageStore;
 use qsc_hir::hir::{self};
-use rustc_hash::FxHashSet;
-use std::fmt::{Display, Write};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Write},
+    rc::Rc,
+};
+use tracing::info_span;
 
 /// # Errors
 ///
@@ -31,6 +36,7 @@ pub fn generate_qir(
     store: &PackageStore,
     package: hir::PackageId,
 ) -> std::result::Result<String, (Error, Vec<Frame>)> {
+    let _span = info_span!("codegen").entered();
     let mut fir_lowerer = qsc_eval::lower::Lowerer::new();
     let mut fir_store = fir::PackageStore::new();
     for (id, unit) in store {
@@ -49,6 +55,7 @@ pub fn generate_qir(
     let result = eval(
         package,
         None,
+        Limits::default(),
         unit.entry_exec_graph.clone(),
         &fir_store,
         &mut Env::default(),
@@ -66,6 +73,20 @@ pub struct BaseProfSim {
     decls: String,
     decl_names: FxHashSet<String>,
     remapper: Remapper,
+    metadata: BTreeMap<String, String>,
+    debug_info: Option<DebugInfo>,
+}
+
+/// The source locations of the instructions, which are attached to them as debug metadata.
+#[derive(Default)]
+struct DebugInfo {
+    /// The distinct spans of the calls that instructions come from.
+    locations: Vec<PackageSpan>,
+    location_ids: FxHashMap<(usize, u32, u32), usize>,
+    /// The location of the instructions of the current intrinsic.
+    current: Option<usize>,
+    /// The length of the instructions that have been tagged with their location.
+    tagged: usize,
 }
 
 impl Default for BaseProfSim {
@@ -82,13 +103,47 @@ impl BaseProfSim {
             decls: String::new(),
             decl_names: FxHashSet::default(),
             remapper: Remapper::default(),
+            metadata: BTreeMap::new(),
+            debug_info: None,
         };
         sim.instrs.push_str(include_str!("./qir_base/prefix.ll"));
         sim
     }
 
+    /// Sets the key/value pairs that are added to the module flags of the generated QIR. Keys are
+    /// prefixed with `user.` so that they can't clash with the flags that QIR reserves, such as
+    /// `qir_major_version`.
+    pub fn set_metadata(&mut self, metadata: BTreeMap<String, String>) {
+        self.metadata = metadata;
+    }
+
+    /// Sets whether to attach the Q# source location of the call that each instruction comes from
+    /// as debug metadata, so that tools that consume the QIR can trace instructions back to Q#.
+    pub fn set_debug_info(&mut self, enabled: bool) {
+        self.debug_info = enabled.then(|| DebugInfo {
+            tagged: self.instrs.len(),
+            ..DebugInfo::default()
+        });
+    }
+
     #[must_use]
-    pub fn finish(mut self, val: &Value) -> String {
+    pub fn finish(self, val: &Value) -> String {
+        self.finish_with_sources(val, |_| None)
+    }
+
+    /// Finishes the module like [`BaseProfSim::finish`], using `resolve` to map the span of each
+    /// call in the debug metadata to its file and zero-based line and column. Calls that can't
+    /// be resolved get line 0, which marks them as having no source location.
+    #[must_use]
+    pub fn finish_with_sources(
+        mut self,
+        val: &Value,
+        resolve: impl Fn(PackageSpan) -> Option<(String, u32, u32)>,
+    ) -> String {
+        // Deferred measurements and output recording don't come from a single call.
+        self.tag_instrs();
+        let debug_info = self.debug_info.take();
+
         for (mapped_q, id) in self.remapper.measurements() {
             writeln!(
                 self.instrs,
@@ -101,12 +156,50 @@ impl BaseProfSim {
         self.write_output_recording(val)
             .expect("writing to string should succeed");
 
+        // The first four module flags are the fixed ones from the postfix.
+        let mut flag_refs = String::new();
+        let mut flags = String::new();
+        for (index, (key, value)) in self.metadata.iter().enumerate() {
+            let index = index + 4;
+            write!(flag_refs, ", !{index}").expect("writing to string should succeed");
+            writeln!(
+                flags,
+                "!{index} = !{{i32 1, !\"user.{}\", !\"{}\"}}",
+                MetadataString(key),
+                MetadataString(value)
+            )
+            .expect("writing to string should succeed");
+        }
+        if let Some(debug_info) = debug_info {
+            let index = self.metadata.len() + 4;
+            write!(flag_refs, ", !{index}").expect("writing to string should succeed");
+            writeln!(
+                flags,
+                "!{index} = !{{i32 2, !\"Debug Info Version\", i32 3}}"
+            )
+            .expect("writing to string should succeed");
+            let subprogram = write_debug_metadata(&mut flags, &debug_info, index + 1, resolve)
+                .expect("writing to string should succeed");
+            self.instrs = self.instrs.replacen(
+                "@ENTRYPOINT__main() #0 {",
+                &format!("@ENTRYPOINT__main() #0 !dbg !{subprogram} {{"),
+                1,
+            );
+        }
+
+        // The added flags follow the last fixed flag in the postfix, so each starts on a new line.
+        let flags = flags
+            .lines()
+            .flat_map(|line| ["\n", line])
+            .collect::<String>();
         write!(
             self.instrs,
             include_str!("./qir_base/postfix.ll"),
             self.decls,
             self.remapper.num_qubits(),
-            self.remapper.num_measurements()
+            self.remapper.num_measurements(),
+            flag_refs,
+            flags,
         )
         .expect("writing to string should succeed");
 
@@ -117,6 +210,23 @@ impl BaseProfSim {
         self.remapper.map(qubit)
     }
 
+    /// Attaches the current location to the instructions written since the last time.
+    fn tag_instrs(&mut self) {
+        let Some(debug_info) = &mut self.debug_info else {
+            return;
+        };
+        if let Some(location) = debug_info.current {
+            // Location nodes follow the module flags and the debug info version flag.
+            let id = self.metadata.len() + 5 + location;
+            let untagged = self.instrs.split_off(debug_info.tagged);
+            for line in untagged.lines() {
+                writeln!(self.instrs, "{line}, !dbg !{id}")
+                    .expect("writing to string should succeed");
+            }
+        }
+        debug_info.tagged = self.instrs.len();
+    }
+
     fn write_output_recording(&mut self, val: &Value) -> std::fmt::Result {
         match val {
             Value::Array(arr) => {
@@ -210,6 +320,85 @@ impl BaseProfSim {
     }
 }
 
+/// Writes the debug metadata nodes for the locations of the instructions, starting at the given
+/// node id, and returns the id of the node of the entry point subprogram.
+fn write_debug_metadata(
+    nodes: &mut String,
+    debug_info: &DebugInfo,
+    first_id: usize,
+    resolve: impl Fn(PackageSpan) -> Option<(String, u32, u32)>,
+) -> std::result::Result<usize, std::fmt::Error> {
+    let mut files = Vec::<String>::new();
+    let locations = debug_info
+        .locations
+        .iter()
+        .map(|span| {
+            resolve(*span).map(|(file, line, column)| {
+                let file_index = files.iter().position(|f| *f == file).unwrap_or_else(|| {
+                    files.push(file);
+                    files.len() - 1
+                });
+                (file_index, line + 1, column + 1)
+            })
+        })
+        .collect::<Vec<_>>();
+    if files.is_empty() {
+        files.push(String::new());
+    }
+
+    // The locations come first, so that their ids are known while the instructions are written.
+    let compile_unit = first_id + locations.len();
+    let subprogram = compile_unit + 1;
+    let subroutine_type = subprogram + 2;
+    let file_id = |index: usize| subroutine_type + 1 + 2 * index;
+    for (offset, location) in locations.iter().enumerate() {
+        let id = first_id + offset;
+        match location {
+            Some((file_index, line, column)) => writeln!(
+                nodes,
+                "!{id} = !DILocation(line: {line}, column: {column}, scope: !{})",
+                file_id(*file_index) + 1
+            )?,
+            None => writeln!(
+                nodes,
+                "!{id} = !DILocation(line: 0, column: 0, scope: !{subprogram})"
+            )?,
+        }
+    }
+    writeln!(nodes, "!llvm.dbg.cu = !{{!{compile_unit}}}")?;
+    // There is no DWARF language code for Q#.
+    writeln!(
+        nodes,
+        "!{compile_unit} = distinct !DICompileUnit(language: DW_LANG_C99, file: !{}, producer: \"qsc\", isOptimized: false, runtimeVersion: 0, emissionKind: LineTablesOnly)",
+        file_id(0)
+    )?;
+    writeln!(
+        nodes,
+        "!{subprogram} = distinct !DISubprogram(name: \"ENTRYPOINT__main\", scope: !{file}, file: !{file}, type: !{subroutine_type}, spFlags: DISPFlagDefinition, unit: !{compile_unit})",
+        file = file_id(0)
+    )?;
+    writeln!(nodes, "!{} = !{{null}}", subprogram + 1)?;
+    writeln!(
+        nodes,
+        "!{subroutine_type} = !DISubroutineType(types: !{})",
+        subprogram + 1
+    )?;
+    for (index, file) in files.iter().enumerate() {
+        let id = file_id(index);
+        writeln!(
+            nodes,
+            "!{id} = !DIFile(filename: \"{}\", directory: \"\")",
+            MetadataString(file)
+        )?;
+        writeln!(
+            nodes,
+            "!{} = distinct !DILexicalBlockFile(scope: !{subprogram}, file: !{id}, discriminator: 0)",
+            id + 1
+        )?;
+    }
+    Ok(subprogram)
+}
+
 impl Backend for BaseProfSim {
     type ResultType = usize;
 
@@ -382,6 +571,19 @@ impl Backend for BaseProfSim {
         .expect("writing to string should succeed");
     }
 
+    fn sx(&mut self, q: usize) {
+        // Only targets with the square root of X in their gate set use it, so it is declared
+        // on first use rather than in the postfix.
+        let name = "__quantum__qis__sx__body";
+        if self.decl_names.insert(name.to_string()) {
+            writeln!(self.decls, "declare void @{name}(%Qubit*)")
+                .expect("writing to string should succeed");
+        }
+        let q = self.map(q);
+        writeln!(self.instrs, "  call void @{name}({})", Qubit(q))
+            .expect("writing to string should succeed");
+    }
+
     fn swap(&mut self, q0: usize, q1: usize) {
         let q0 = self.map(q0);
         let q1 = self.map(q1);
@@ -497,6 +699,23 @@ impl Backend for BaseProfSim {
         writeln!(self.instrs, ")").expect("writing to string should succeed");
         Some(Ok(Value::unit()))
     }
+
+    fn records_call_sites(&self) -> bool {
+        self.debug_info.is_some()
+    }
+
+    fn set_call_site(&mut self, span: PackageSpan, _callable: Rc<str>) {
+        self.tag_instrs();
+        if let Some(debug_info) = &mut self.debug_info {
+            let key = (usize::from(span.package), span.span.lo, span.span.hi);
+            let locations = &mut debug_info.locations;
+            let location = *debug_info.location_ids.entry(key).or_insert_with(|| {
+                locations.push(span);
+                locations.len() - 1
+            });
+            debug_info.current = Some(location);
+        }
+    }
 }
 
 struct Qubit(HardwareId);
@@ -549,3 +768,20 @@ impl Display for Int {
         write!(f, "i64 {}", self.0)
     }
 }
+
+/// The contents of an LLVM metadata string, with quotes, backslashes and
+/// non-printable bytes escaped as `\XX` hex sequences.
+struct MetadataString<'a>(&'a str);
+
+impl Display for MetadataString<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for byte in self.0.bytes() {
+            if byte == b'"' || byte == b'\\' || !(b' '..=b'~').contains(&byte) {
+                write!(f, "\\{byte:02X}")?;
+            } else {
+                write!(f, "{}", char::from(byte))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -9,10 +9,11 @@ use std::sync::Arc;
 use expect_test::{expect, Expect};
 use indoc::indoc;
 use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_eval::val::Value;
 use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
 use qsc_passes::{run_core_passes, run_default_passes, PackageType};
 
-use crate::qir_base::generate_qir;
+use crate::qir_base::{generate_qir, BaseProfSim};
 
 fn check(program: &str, expr: Option<&str>, expect: &Expect) {
     let mut core = compile::core();
@@ -1592,3 +1593,33 @@ fn custom_intrinsic_fail_on_non_unit_return() {
         "#]],
     );
 }
+
+#[test]
+fn metadata_keys_do_not_clash_with_reserved_module_flags() {
+    let mut sim = BaseProfSim::new();
+    sim.set_metadata(
+        [
+            ("qir_major_version".to_string(), "2".to_string()),
+            ("run".to_string(), "7".to_string()),
+        ]
+        .into(),
+    );
+    let qir = sim.finish(&Value::unit());
+    let flags = qir
+        .split("; module flags")
+        .nth(1)
+        .expect("QIR should have module flags");
+    expect![[r#"
+
+
+        !llvm.module.flags = !{!0, !1, !2, !3, !4, !5}
+
+        !0 = !{i32 1, !"qir_major_version", i32 1}
+        !1 = !{i32 7, !"qir_minor_version", i32 0}
+        !2 = !{i32 1, !"dynamic_qubit_management", i1 false}
+        !3 = !{i32 1, !"dynamic_result_management", i1 false}
+        !4 = !{i32 1, !"user.qir_major_version", !"2"}
+        !5 = !{i32 1, !"user.run", !"7"}
+    "#]]
+    .assert_eq(flags);
+}
@@ -1,8 +1,15 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+#[cfg(test)]
+mod tests;
+
 use bitflags::bitflags;
 use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
 
 #[derive(Deserialize, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Copy)]
 pub struct LanguageFeatures(u8);
@@ -25,16 +32,94 @@ impl Default for LanguageFeatures {
     }
 }
 
+/// Unknown feature names are ignored. Use [`LanguageFeatures::try_from_names`] to reject them.
 impl<I> FromIterator<I> for LanguageFeatures
 where
     I: AsRef<str>,
 {
     fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
         iter.into_iter().fold(LanguageFeatures::empty(), |acc, x| {
-            acc | match x.as_ref() {
-                "v2-preview-syntax" => LanguageFeatures::V2PreviewSyntax,
-                _ => LanguageFeatures::empty(),
+            acc | LanguageFeatures::from_known_name(x.as_ref()).unwrap_or_default()
+        })
+    }
+}
+
+/// How settled a language feature is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stability {
+    /// The feature may still change or be removed in a later release.
+    Preview,
+    /// The feature is complete and will be kept.
+    Stable,
+}
+
+impl Display for Stability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Preview => write!(f, "preview"),
+            Self::Stable => write!(f, "stable"),
+        }
+    }
+}
+
+/// A language feature that can be enabled by name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LanguageFeatureInfo {
+    /// The name the feature is enabled by, in projects and APIs.
+    pub name: &'static str,
+    pub description: &'static str,
+    pub stability: Stability,
+    pub feature: LanguageFeatures,
+}
+
+/// The language features known to the compiler.
+pub const KNOWN_LANGUAGE_FEATURES: &[LanguageFeatureInfo] = &[LanguageFeatureInfo {
+    name: "v2-preview-syntax",
+    description: "Syntax planned for the next version of Q#, which removes deprecated forms such as `set` statements and `()` in callable calls without arguments.",
+    stability: Stability::Preview,
+    feature: LanguageFeatures::V2PreviewSyntax,
+}];
+
+/// A language feature name that the compiler doesn't know.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownLanguageFeature(pub String);
+
+impl Display for UnknownLanguageFeature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown language feature `{}`, expected one of ", self.0)?;
+        for (i, info) in KNOWN_LANGUAGE_FEATURES.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
             }
+            write!(f, "`{}`", info.name)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for UnknownLanguageFeature {}
+
+impl LanguageFeatures {
+    /// The feature with the given name, if it is known.
+    #[must_use]
+    pub fn from_known_name(name: &str) -> Option<Self> {
+        KNOWN_LANGUAGE_FEATURES
+            .iter()
+            .find(|info| info.name == name)
+            .map(|info| info.feature)
+    }
+
+    /// Collects the features with the given names.
+    /// # Errors
+    /// Returns the first name that is not a known feature.
+    pub fn try_from_names<I: AsRef<str>>(
+        names: impl IntoIterator<Item = I>,
+    ) -> Result<Self, UnknownLanguageFeature> {
+        names.into_iter().try_fold(Self::empty(), |acc, name| {
+            let name = name.as_ref();
+            Self::from_known_name(name)
+                .map(|feature| acc | feature)
+                .ok_or_else(|| UnknownLanguageFeature(name.to_string()))
         })
     }
 }
@@ -0,0 +1,34 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{LanguageFeatures, UnknownLanguageFeature, KNOWN_LANGUAGE_FEATURES};
+
+#[test]
+fn known_names_are_parsed() {
+    for info in KNOWN_LANGUAGE_FEATURES {
+        assert_eq!(
+            LanguageFeatures::from_known_name(info.name),
+            Some(info.feature)
+        );
+        assert_eq!(
+            LanguageFeatures::try_from_names([info.name]),
+            Ok(info.feature)
+        );
+    }
+}
+
+#[test]
+fn unknown_name_is_an_error() {
+    let result = LanguageFeatures::try_from_names(["v2-preview-syntax", "v3-syntax"]);
+    assert_eq!(result, Err(UnknownLanguageFeature("v3-syntax".to_string())));
+    assert_eq!(
+        result.expect_err("feature should be unknown").to_string(),
+        "unknown language feature `v3-syntax`, expected one of `v2-preview-syntax`"
+    );
+}
+
+#[test]
+fn from_iter_ignores_unknown_names() {
+    let features = LanguageFeatures::from_iter(["v3-syntax", "v2-preview-syntax"]);
+    assert_eq!(features, LanguageFeatures::V2PreviewSyntax);
+}
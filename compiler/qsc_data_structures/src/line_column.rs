@@ -5,6 +5,8 @@
 mod tests;
 
 use crate::span::Span;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// A line and column pair that describes a position in a string.
 #[derive(Clone, Copy, PartialEq, Debug, Eq, Hash)]
@@ -14,6 +16,7 @@ pub struct Position {
     /// Column offset.
     /// When created using [`Encoding::Utf8`], this is the byte offset.
     /// When created using [`Encoding::Utf16`], this is the code unit (word) offset.
+    /// When created using [`Encoding::Display`], this is the display column.
     pub column: u32,
 }
 
@@ -30,11 +33,16 @@ pub struct Position {
 ///
 /// Therefore it's important to be aware of exactly how this column offset will be treated
 /// by the code that's using it. e.g. in LSP (language server protocol) and
-/// DAP (debug adapter protocol) it's explicitly defined to use UTF-16 code units.
+/// DAP (debug adapter protocol) it's explicitly defined to use UTF-16 code units,
+/// while a person reading a terminal or notebook counts the columns they see.
 #[derive(Clone, Copy, PartialEq, Debug, Eq, Hash)]
 pub enum Encoding {
     Utf8,
     Utf16,
+    /// Columns count the width of grapheme clusters as they are displayed: a letter
+    /// with combining marks takes one column, and a wide character such as a CJK
+    /// ideograph takes two.
+    Display,
 }
 
 impl Position {
@@ -42,15 +50,16 @@ impl Position {
     /// that corresponds to that offset.
     ///
     /// The column information is expressed in code units, depending on the passed in `encoding`.
-    /// [`Encoding::Utf8`] will use byte offsets, and [`Encoding::Utf16`] will use
-    /// utf-16 code unit (word) offsets.
+    /// [`Encoding::Utf8`] will use byte offsets, [`Encoding::Utf16`] will use
+    /// utf-16 code unit (word) offsets, and [`Encoding::Display`] will use display columns.
     ///
     /// If the given offset is past the end of the string, returns the position of
     /// the end of the string (e.g. for "hello" the end of the string is line=0, column=5).
     ///
     /// Note that this function does not validate whether the passed in offset
     /// is a valid utf8 char boundary. If an invalid offset is passed in,
-    /// the next char boundary will be returned.
+    /// the next char boundary will be returned. With [`Encoding::Display`],
+    /// an offset within a grapheme cluster maps to the next grapheme cluster.
     #[must_use]
     pub fn from_utf8_byte_offset(
         encoding: Encoding,
@@ -65,7 +74,8 @@ impl Position {
     ///
     /// The column information in the position is interpreted based on the
     /// passed in `encoding`. [`Encoding::Utf8`] will treat the column information
-    /// as byte offsets, and [`Encoding::Utf16`] will use utf-16 code unit (word) offsets.
+    /// as byte offsets, [`Encoding::Utf16`] will use utf-16 code unit (word) offsets,
+    /// and [`Encoding::Display`] will use display columns.
     ///
     /// If the position is past the end of the string, returns the offset of
     /// the end of the string (e.g. for "hello" returns 5).
@@ -74,12 +84,12 @@ impl Position {
         let mut column: u32 = 0;
         let mut line: u32 = 0;
 
-        for (byte_offset, c) in contents.char_indices() {
-            if c == '\n' {
+        for (byte_offset, segment) in segments(encoding, contents) {
+            if segment.ends_with('\n') {
                 line += 1;
                 column = 0;
             } else {
-                column += num_code_units(encoding, c);
+                column += num_code_units(encoding, segment);
             }
 
             if line > self.line || (line == self.line && column > self.column) {
@@ -158,7 +168,7 @@ fn positions_from_utf8_byte_offsets<const N: usize>(
     let mut column: u32 = 0;
     let mut line: u32 = 0;
 
-    for (char_index, c) in contents.char_indices() {
+    for (char_index, segment) in segments(encoding, contents) {
         if i == N {
             // We've run out of offsets to look for
             break;
@@ -177,12 +187,13 @@ fn positions_from_utf8_byte_offsets<const N: usize>(
 
         // Windows (\r\n) line endings will be handled
         // fine here, with the \r counting as an extra char
-        // at the end of the line.
-        if c == '\n' {
+        // at the end of the line, or being part of the same
+        // grapheme cluster as the \n.
+        if segment.ends_with('\n') {
             line += 1;
             column = 0;
         } else {
-            column += num_code_units(encoding, c);
+            column += num_code_units(encoding, segment);
         }
     }
 
@@ -195,10 +206,27 @@ fn positions_from_utf8_byte_offsets<const N: usize>(
     positions
 }
 
-fn num_code_units(encoding: Encoding, c: char) -> u32 {
+/// Splits the string into the segments that columns are counted over, along with
+/// their utf-8 byte offsets: grapheme clusters for [`Encoding::Display`], and chars
+/// for the other encodings.
+fn segments(encoding: Encoding, contents: &str) -> Box<dyn Iterator<Item = (usize, &str)> + '_> {
+    match encoding {
+        Encoding::Utf8 | Encoding::Utf16 => Box::new(
+            contents
+                .char_indices()
+                .map(|(i, c)| (i, &contents[i..i + c.len_utf8()])),
+        ),
+        Encoding::Display => Box::new(contents.grapheme_indices(true)),
+    }
+}
+
+fn num_code_units(encoding: Encoding, segment: &str) -> u32 {
     match encoding {
-        Encoding::Utf8 => u32(c.len_utf8()),
-        Encoding::Utf16 => u32(c.len_utf16()),
+        Encoding::Utf8 => u32(segment.len()),
+        Encoding::Utf16 => u32(segment.encode_utf16().count()),
+        // Control characters such as tabs have no width of their own,
+        // but still take up a column where they appear.
+        Encoding::Display => u32(segment.width().max(1)),
     }
 }
 
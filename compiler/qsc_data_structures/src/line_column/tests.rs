@@ -279,6 +279,38 @@ fn grapheme_clusters() {
     );
 }
 
+#[test]
+fn display_columns() {
+    // string         | // é中!\nx
+    // grapheme bytes | 0  1  2  3-5 (e + combining acute)  6-8  9  10  11
+    // display width  | 1  1  1  1                          2    1      1
+    let contents = "// e\u{301}中!\nx";
+    let columns = [0, 3, 4, 6, 9, 10, 11]
+        .map(|offset| {
+            let pos = Position::from_utf8_byte_offset(Encoding::Display, contents, offset);
+            (pos.line, pos.column)
+        })
+        .to_vec();
+    assert_eq!(
+        columns,
+        vec![(0, 0), (0, 3), (0, 4), (0, 4), (0, 6), (0, 7), (1, 0)]
+    );
+
+    // A position within a wide character maps to the start of that character
+    for (column, offset) in [(3, 3), (4, 6), (5, 6), (6, 9)] {
+        let pos = Position { line: 0, column };
+        assert_eq!(pos.to_utf8_byte_offset(Encoding::Display, contents), offset);
+    }
+}
+
+#[test]
+fn display_columns_windows_crlf_line_breaks() {
+    let contents = "a\r\nb";
+    let pos = Position::from_utf8_byte_offset(Encoding::Display, contents, 3);
+    assert_eq!((pos.line, pos.column), (1, 0));
+    assert_eq!(pos.to_utf8_byte_offset(Encoding::Display, contents), 3);
+}
+
 #[test]
 fn empty_range() {
     let contents = "hello";
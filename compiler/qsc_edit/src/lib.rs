@@ -0,0 +1,255 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! This module rewrites Q# sources by replacing the text at spans. The edits of a rewrite are
+//! collected into a [`Patch`], which checks that they don't overlap before any of them is applied,
+//! and the spans of the original sources can be mapped into the rewritten ones with a [`SpanMap`].
+//! Tools that rewrite sources, like lint fixes and the formatter, go through this module so that
+//! their edits are validated and applied the same way.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_data_structures::span::Span;
+use qsc_frontend::compile::{Source, SourceMap};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// An edit that replaces the text at a span. An edit with an empty span inserts its text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    /// The span of the replaced text.
+    pub span: Span,
+    /// The replacement text.
+    pub new_text: String,
+}
+
+impl Edit {
+    /// An edit that replaces the text at the span with the new text.
+    #[must_use]
+    pub fn replace(span: Span, new_text: impl Into<String>) -> Self {
+        Self {
+            span,
+            new_text: new_text.into(),
+        }
+    }
+
+    /// An edit that inserts the new text at the offset.
+    #[must_use]
+    pub fn insert(offset: u32, new_text: impl Into<String>) -> Self {
+        Self::replace(
+            Span {
+                lo: offset,
+                hi: offset,
+            },
+            new_text,
+        )
+    }
+
+    /// An edit that deletes the text at the span.
+    #[must_use]
+    pub fn delete(span: Span) -> Self {
+        Self::replace(span, "")
+    }
+
+    fn is_insertion(&self) -> bool {
+        self.span.lo == self.span.hi
+    }
+
+    fn len_delta(&self) -> i64 {
+        i64::try_from(self.new_text.len()).expect("edit length should fit into i64")
+            - i64::from(self.span.hi - self.span.lo)
+    }
+}
+
+#[derive(Clone, Debug, Diagnostic, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("edit has a span that ends before it starts")]
+    #[diagnostic(code("Qsc.Edit.InvalidSpan"))]
+    InvalidSpan(#[label] Span),
+
+    #[error("edits overlap")]
+    #[diagnostic(help("each part of a source can only be replaced by one edit"))]
+    #[diagnostic(code("Qsc.Edit.Overlap"))]
+    Overlap(
+        #[label("this edit")] Span,
+        #[label("overlaps this edit")] Span,
+    ),
+
+    #[error("edit is outside of the sources")]
+    #[diagnostic(code("Qsc.Edit.OutOfBounds"))]
+    OutOfBounds(#[label] Span),
+
+    #[error("edit spans more than one source")]
+    #[diagnostic(code("Qsc.Edit.MultipleSources"))]
+    MultipleSources(#[label] Span),
+
+    #[error("edit starts or ends inside of a character")]
+    #[diagnostic(code("Qsc.Edit.CharBoundary"))]
+    CharBoundary(#[label] Span),
+}
+
+/// A set of edits that don't overlap, in the order of their spans.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Patch {
+    edits: Vec<Edit>,
+}
+
+impl Patch {
+    /// Orders the edits by their spans and checks that no two of them replace the same text.
+    /// Edits that only touch, and insertions at the same offset, don't overlap. Insertions at
+    /// the same offset are applied in the order they are given.
+    pub fn new(edits: impl IntoIterator<Item = Edit>) -> Result<Self, Error> {
+        let mut edits: Vec<_> = edits.into_iter().collect();
+        if let Some(edit) = edits.iter().find(|edit| edit.span.lo > edit.span.hi) {
+            return Err(Error::InvalidSpan(edit.span));
+        }
+
+        edits.sort_by_key(|edit| (edit.span.lo, edit.span.hi));
+        for pair in edits.windows(2) {
+            if pair[1].span.lo < pair[0].span.hi {
+                return Err(Error::Overlap(pair[1].span, pair[0].span));
+            }
+        }
+
+        Ok(Self { edits })
+    }
+
+    /// The edits, in the order of their spans.
+    #[must_use]
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Applies the edits to a single source. The spans of the edits are offsets into the source.
+    pub fn apply_to_str(&self, source: &str) -> Result<String, Error> {
+        apply_edits(source, 0, &self.edits)
+    }
+
+    /// Applies the edits to the sources of the source map. The spans of the edits are offsets
+    /// into the source map, like the spans of a compilation, and each edit must stay within one
+    /// source. Returns the rewritten sources, which keep the entry expression and configuration,
+    /// along with the mapping from spans of the original sources to spans of the rewritten ones.
+    pub fn apply(&self, sources: &SourceMap) -> Result<(SourceMap, SpanMap), Error> {
+        let sources_list: Vec<&Source> = sources.iter().collect();
+        let mut grouped = vec![Vec::new(); sources_list.len()];
+        for edit in &self.edits {
+            let index = sources_list
+                .iter()
+                .rposition(|source| source.offset <= edit.span.lo)
+                .ok_or(Error::OutOfBounds(edit.span))?;
+            let source = sources_list[index];
+            let end = source.offset + source_len(source);
+            if edit.span.lo > end {
+                return Err(Error::OutOfBounds(edit.span));
+            }
+            if edit.span.hi > end {
+                return Err(Error::MultipleSources(edit.span));
+            }
+            grouped[index].push(edit.clone());
+        }
+
+        let mut new_sources = Vec::with_capacity(sources_list.len());
+        for (source, edits) in sources_list.iter().zip(&grouped) {
+            let contents: Arc<str> = if edits.is_empty() {
+                source.contents.clone()
+            } else {
+                apply_edits(&source.contents, source.offset, edits)?.into()
+            };
+            new_sources.push((source.name.clone(), contents));
+        }
+
+        let new_source_map = SourceMap::new(
+            new_sources,
+            sources.entry().map(|entry| entry.contents.clone()),
+        )
+        .with_config(sources.config().iter().cloned());
+        Ok((new_source_map, self.span_map()))
+    }
+
+    /// The mapping from spans of the original sources to spans of the sources that the edits
+    /// are applied to.
+    #[must_use]
+    pub fn span_map(&self) -> SpanMap {
+        SpanMap {
+            edits: self.edits.clone(),
+        }
+    }
+}
+
+/// Maps offsets and spans of sources to the offsets and spans of the same text after a
+/// [`Patch`] is applied to them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpanMap {
+    edits: Vec<Edit>,
+}
+
+impl SpanMap {
+    /// The offset of the text at the offset after the edits, or `None` if the text at the offset
+    /// was replaced. The text inserted at an offset comes before the text that was at the offset.
+    #[must_use]
+    pub fn map_offset(&self, offset: u32) -> Option<u32> {
+        self.map(offset, false)
+    }
+
+    /// The span of the text at the span after the edits, or `None` if the span starts or ends in
+    /// replaced text. A span grows and shrinks with the edits within it, while the text inserted
+    /// at the start or end of a span stays outside of it.
+    #[must_use]
+    pub fn map_span(&self, span: Span) -> Option<Span> {
+        let lo = self.map(span.lo, false)?;
+        let hi = if span.lo == span.hi {
+            lo
+        } else {
+            self.map(span.hi, true)?
+        };
+        Some(Span { lo, hi })
+    }
+
+    fn map(&self, offset: u32, is_end: bool) -> Option<u32> {
+        let mut delta = 0;
+        for edit in &self.edits {
+            if edit.span.hi < offset || (edit.span.hi == offset && !(is_end && edit.is_insertion()))
+            {
+                delta += edit.len_delta();
+            } else if edit.span.lo < offset {
+                return None;
+            } else {
+                break;
+            }
+        }
+
+        Some(u32::try_from(i64::from(offset) + delta).expect("mapped offset should fit into u32"))
+    }
+}
+
+fn source_len(source: &Source) -> u32 {
+    u32::try_from(source.contents.len()).expect("contents length should fit into u32")
+}
+
+/// Applies edits, in the order of their spans, to a source that starts at the offset.
+fn apply_edits(source: &str, offset: u32, edits: &[Edit]) -> Result<String, Error> {
+    let mut new_source = String::with_capacity(source.len());
+    let mut last = 0;
+    for edit in edits {
+        let lo = (edit.span.lo - offset) as usize;
+        let hi = (edit.span.hi - offset) as usize;
+        if hi > source.len() {
+            return Err(Error::OutOfBounds(edit.span));
+        }
+        if !source.is_char_boundary(lo) || !source.is_char_boundary(hi) {
+            return Err(Error::CharBoundary(edit.span));
+        }
+        new_source.push_str(&source[last..lo]);
+        new_source.push_str(&edit.new_text);
+        last = hi;
+    }
+    new_source.push_str(&source[last..]);
+    Ok(new_source)
+}
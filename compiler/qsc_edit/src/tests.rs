@@ -0,0 +1,171 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Edit, Error, Patch};
+use qsc_data_structures::span::Span;
+use qsc_frontend::compile::SourceMap;
+use std::sync::Arc;
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span { lo, hi }
+}
+
+fn patch(edits: impl IntoIterator<Item = Edit>) -> Patch {
+    Patch::new(edits).expect("edits should not overlap")
+}
+
+#[test]
+fn edits_are_applied_in_span_order() {
+    let patch = patch([
+        Edit::delete(span(5, 6)),
+        Edit::replace(span(1, 3), "X"),
+        Edit::insert(4, "_"),
+    ]);
+    assert_eq!(
+        patch.apply_to_str("abcdef").expect("edits should apply"),
+        "aXd_e"
+    );
+}
+
+#[test]
+fn touching_edits_and_insertions_at_same_offset_are_applied() {
+    let patch = patch([
+        Edit::replace(span(3, 4), "Y"),
+        Edit::insert(3, "1"),
+        Edit::replace(span(1, 3), "X"),
+        Edit::insert(3, "2"),
+    ]);
+    assert_eq!(
+        patch.apply_to_str("abcdef").expect("edits should apply"),
+        "aX12Yef"
+    );
+}
+
+#[test]
+fn overlapping_edits_are_rejected() {
+    assert_eq!(
+        Patch::new([
+            Edit::replace(span(1, 3), "x"),
+            Edit::replace(span(2, 4), "y")
+        ]),
+        Err(Error::Overlap(span(2, 4), span(1, 3)))
+    );
+}
+
+#[test]
+fn insertion_inside_replacement_is_rejected() {
+    assert_eq!(
+        Patch::new([Edit::insert(2, "x"), Edit::delete(span(1, 4))]),
+        Err(Error::Overlap(span(2, 2), span(1, 4)))
+    );
+}
+
+#[test]
+fn reversed_span_is_rejected() {
+    assert_eq!(
+        Patch::new([Edit::delete(span(3, 1))]),
+        Err(Error::InvalidSpan(span(3, 1)))
+    );
+}
+
+#[test]
+fn edit_past_end_of_source_is_rejected() {
+    assert_eq!(
+        patch([Edit::delete(span(2, 5))]).apply_to_str("abc"),
+        Err(Error::OutOfBounds(span(2, 5)))
+    );
+}
+
+#[test]
+fn edit_inside_character_is_rejected() {
+    assert_eq!(
+        patch([Edit::delete(span(2, 3))]).apply_to_str("aé"),
+        Err(Error::CharBoundary(span(2, 3)))
+    );
+}
+
+fn source_map() -> SourceMap {
+    // The entry is at offsets 0-6, "a.qs" at 7-10 and "b.qs" at 11-15.
+    SourceMap::new(
+        [
+            ("a.qs".into(), "abc".into()),
+            ("b.qs".into(), "defg".into()),
+        ],
+        Some("Main()".into()),
+    )
+    .with_config([Arc::from("Flag")])
+}
+
+#[test]
+fn edits_are_applied_to_source_map() {
+    let (sources, _) = patch([
+        Edit::replace(span(8, 9), "XYZ"),
+        Edit::insert(11, "_"),
+        Edit::delete(span(13, 15)),
+    ])
+    .apply(&source_map())
+    .expect("edits should apply");
+
+    let actual: Vec<_> = sources
+        .iter()
+        .map(|source| {
+            (
+                source.name.as_ref(),
+                source.contents.as_ref(),
+                source.offset,
+            )
+        })
+        .collect();
+    assert_eq!(actual, vec![("a.qs", "aXYZc", 7), ("b.qs", "_de", 13)]);
+    assert_eq!(
+        sources.entry().map(|entry| entry.contents.as_ref()),
+        Some("Main()")
+    );
+    assert_eq!(sources.config(), &[Arc::<str>::from("Flag")]);
+}
+
+#[test]
+fn spans_are_mapped_to_rewritten_source_map() {
+    let (_, span_map) = patch([
+        Edit::replace(span(8, 9), "XYZ"),
+        Edit::insert(11, "_"),
+        Edit::delete(span(13, 15)),
+    ])
+    .apply(&source_map())
+    .expect("edits should apply");
+
+    // "abc" and "defg" are now "aXYZc" at 7-12 and "_de" at 13-16.
+    assert_eq!(span_map.map_span(span(7, 10)), Some(span(7, 12)));
+    assert_eq!(span_map.map_span(span(11, 15)), Some(span(14, 16)));
+    assert_eq!(span_map.map_offset(12), Some(15));
+    assert_eq!(span_map.map_offset(14), None);
+    assert_eq!(span_map.map_span(span(8, 9)), Some(span(8, 11)));
+}
+
+#[test]
+fn text_inserted_at_span_boundaries_stays_outside_of_span() {
+    let span_map = patch([Edit::insert(2, "xx")]).span_map();
+    assert_eq!(span_map.map_span(span(0, 2)), Some(span(0, 2)));
+    assert_eq!(span_map.map_span(span(2, 5)), Some(span(4, 7)));
+    assert_eq!(span_map.map_span(span(2, 2)), Some(span(4, 4)));
+}
+
+#[test]
+fn edit_across_sources_is_rejected() {
+    assert_eq!(
+        patch([Edit::delete(span(9, 12))])
+            .apply(&source_map())
+            .map(|_| ()),
+        Err(Error::MultipleSources(span(9, 12)))
+    );
+}
+
+#[test]
+fn edit_of_entry_is_rejected() {
+    assert_eq!(
+        patch([Edit::delete(span(0, 4))])
+            .apply(&source_map())
+            .map(|_| ()),
+        Err(Error::OutOfBounds(span(0, 4)))
+    );
+}
@@ -5,8 +5,22 @@ use num_bigint::BigUint;
 use num_complex::Complex;
 use quantum_sparse_sim::QuantumSim;
 use rand::RngCore;
+use rustc_hash::FxHashMap;
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display, Formatter},
+    rc::Rc,
+    str::FromStr,
+};
 
-use crate::val::Value;
+use crate::{val::Value, PackageSpan};
+use qsc_fir::fir::CustomIntrinsic;
+
+mod decompose;
+mod dense;
+
+pub use decompose::{Decomposer, GateSet};
+pub use dense::DenseSim;
 
 /// The trait that must be implemented by a quantum backend, whose functions will be invoked when
 /// quantum intrinsics are called.
@@ -29,6 +43,12 @@ pub trait Backend {
     fn rzz(&mut self, theta: f64, q0: usize, q1: usize);
     fn sadj(&mut self, q: usize);
     fn s(&mut self, q: usize);
+    /// The square root of X, which is only applied by targets that execute it natively.
+    /// Backends that don't emit it as a gate of its own apply it as an X rotation of a quarter
+    /// turn, which is equal up to a global phase.
+    fn sx(&mut self, q: usize) {
+        self.rx(std::f64::consts::FRAC_PI_2, q);
+    }
     fn swap(&mut self, q0: usize, q1: usize);
     fn tadj(&mut self, q: usize);
     fn t(&mut self, q: usize);
@@ -40,16 +60,111 @@ pub trait Backend {
     fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize);
     fn qubit_is_zero(&mut self, q: usize) -> bool;
 
+    /// Borrows a qubit that may be in any state, which the caller must restore before returning
+    /// it. By default, borrowed qubits are allocated in the zero state.
+    fn qubit_borrow(&mut self) -> usize {
+        self.qubit_allocate()
+    }
+
+    /// Returns a borrowed qubit, or returns `false` and keeps the qubit if it isn't in the state
+    /// it was borrowed in.
+    fn qubit_return(&mut self, q: usize) -> bool {
+        if self.qubit_is_zero(q) {
+            self.qubit_release(q);
+            true
+        } else {
+            false
+        }
+    }
+
     fn custom_intrinsic(&mut self, _name: &str, _arg: Value) -> Option<Result<Value, String>> {
         None
     }
 
+    /// Called for intrinsics with the `@CustomIntrinsic` attribute, which are implemented by the
+    /// target. By default they are handled like other custom intrinsics, by the name they are
+    /// declared with in QIR.
+    fn target_intrinsic(
+        &mut self,
+        intrinsic: &CustomIntrinsic,
+        arg: Value,
+    ) -> Option<Result<Value, String>> {
+        self.custom_intrinsic(&intrinsic.qir_name, arg)
+    }
+
     fn set_seed(&mut self, _seed: Option<u64>) {}
+
+    /// Whether the backend can trace both branches of an `if` whose condition compares a
+    /// measurement result that is not known to the evaluator, such as one returned as an id.
+    /// When this is `false`, such comparisons fail with an error.
+    fn supports_conditionals(&self) -> bool {
+        false
+    }
+
+    /// Marks the start of operations that only run when the measurement with the given
+    /// result id has the given value.
+    fn begin_conditional(&mut self, _result: usize, _value: bool) {}
+
+    /// Marks the end of the operations started by the last call to `begin_conditional`.
+    fn end_conditional(&mut self) {}
+
+    /// Whether the backend records where in the source its quantum operations come from. When
+    /// this is `true`, the evaluator calls `set_call_site` before each call to an intrinsic.
+    fn records_call_sites(&self) -> bool {
+        false
+    }
+
+    /// Sets the call that the quantum operations of the next intrinsic come from: the span of
+    /// the call, and the name of the callable it calls. This is the call that led into the
+    /// package that defines the intrinsic, such as a call to `H` from user code, rather than the
+    /// call to the intrinsic itself.
+    fn set_call_site(&mut self, _span: PackageSpan, _callable: Rc<str>) {}
+}
+
+/// The Y and Z rotations that put a borrowed qubit into the state the simulators lend it in, so
+/// that algorithms that borrow qubits are exercised with states other than zero. The state
+/// depends on the id of the qubit so that borrowed qubits aren't all alike.
+const DIRTY_ROTATIONS: [(f64, f64); 4] = [(0.7, 1.3), (1.9, 0.4), (2.6, 2.2), (1.1, 2.9)];
+
+fn dirty_rotations(q: usize) -> (f64, f64) {
+    DIRTY_ROTATIONS[q % DIRTY_ROTATIONS.len()]
+}
+
+/// Measurement outcomes that the sparse simulator reports instead of the simulated ones, so that
+/// tests can exercise measurement-conditioned logic deterministically. When a forced outcome
+/// differs from the simulated one, the measured qubit is flipped to match it. Qubits entangled
+/// with the measured qubit are left as the simulated outcome collapsed them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ForcedOutcomes {
+    /// The outcomes of the next measurements, in the order they are made, where `true` is `One`.
+    /// Each entry is consumed by a measurement, and `None` leaves that measurement unforced.
+    pub sequence: VecDeque<Option<bool>>,
+    /// The outcome of every measurement of the given qubits that isn't forced by the sequence,
+    /// keyed by qubit id.
+    pub qubits: FxHashMap<usize, bool>,
 }
 
+impl ForcedOutcomes {
+    fn next(&mut self, q: usize) -> Option<bool> {
+        self.sequence
+            .pop_front()
+            .flatten()
+            .or_else(|| self.qubits.get(&q).copied())
+    }
+}
+
+/// A function that simulates a custom intrinsic, given its arguments.
+pub type IntrinsicCallback = Rc<dyn Fn(Value) -> Result<Value, String>>;
+
+/// The callbacks that simulate custom intrinsics, keyed by the name the intrinsic is called by,
+/// which is the QIR name given by its `@CustomIntrinsic` attribute if it has one.
+pub type IntrinsicCallbacks = FxHashMap<Rc<str>, IntrinsicCallback>;
+
 /// Default backend used when targeting sparse simulation.
 pub struct SparseSim {
     pub sim: QuantumSim,
+    pub forced: ForcedOutcomes,
+    pub intrinsics: IntrinsicCallbacks,
 }
 
 impl Default for SparseSim {
@@ -63,6 +178,19 @@ impl SparseSim {
     pub fn new() -> Self {
         Self {
             sim: QuantumSim::new(),
+            forced: ForcedOutcomes::default(),
+            intrinsics: IntrinsicCallbacks::default(),
+        }
+    }
+
+    fn measure(&mut self, q: usize) -> bool {
+        let res = self.sim.measure(q);
+        match self.forced.next(q) {
+            Some(forced) if forced != res => {
+                self.sim.x(q);
+                forced
+            }
+            _ => res,
         }
     }
 }
@@ -91,11 +219,11 @@ impl Backend for SparseSim {
     }
 
     fn m(&mut self, q: usize) -> Self::ResultType {
-        self.sim.measure(q)
+        self.measure(q)
     }
 
     fn mresetz(&mut self, q: usize) -> Self::ResultType {
-        let res = self.sim.measure(q);
+        let res = self.measure(q);
         if res {
             self.sim.x(q);
         }
@@ -103,7 +231,9 @@ impl Backend for SparseSim {
     }
 
     fn reset(&mut self, q: usize) {
-        self.mresetz(q);
+        if self.sim.measure(q) {
+            self.sim.x(q);
+        }
     }
 
     fn rx(&mut self, theta: f64, q: usize) {
@@ -212,7 +342,32 @@ impl Backend for SparseSim {
         self.sim.qubit_is_zero(q)
     }
 
-    fn custom_intrinsic(&mut self, name: &str, _arg: Value) -> Option<Result<Value, String>> {
+    fn qubit_borrow(&mut self) -> usize {
+        let q = self.sim.allocate();
+        let (theta, phi) = dirty_rotations(q);
+        self.sim.ry(theta, q);
+        self.sim.rz(phi, q);
+        q
+    }
+
+    fn qubit_return(&mut self, q: usize) -> bool {
+        let (theta, phi) = dirty_rotations(q);
+        self.sim.rz(-phi, q);
+        self.sim.ry(-theta, q);
+        if self.sim.qubit_is_zero(q) {
+            self.sim.release(q);
+            true
+        } else {
+            self.sim.ry(theta, q);
+            self.sim.rz(phi, q);
+            false
+        }
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        if let Some(callback) = self.intrinsics.get(name) {
+            return Some(callback(arg));
+        }
         match name {
             "BeginEstimateCaching" => Some(Ok(Value::Bool(true))),
             "EndEstimateCaching"
@@ -231,6 +386,223 @@ impl Backend for SparseSim {
     }
 }
 
+/// The simulators that programs can run on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SimulatorKind {
+    /// Stores only the nonzero amplitudes, see [`SparseSim`].
+    #[default]
+    Sparse,
+    /// Stores every amplitude, see [`DenseSim`].
+    Dense,
+}
+
+impl SimulatorKind {
+    /// The estimated memory in bytes that the state of the simulator takes up with the given
+    /// number of qubits allocated and nonzero amplitudes. The dense simulator stores every
+    /// amplitude regardless of their values, while the sparse simulator stores each nonzero
+    /// amplitude in a hash map keyed by its basis state.
+    #[must_use]
+    pub fn estimated_memory(self, qubits: usize, amplitudes: usize) -> usize {
+        let amplitude = std::mem::size_of::<Complex<f64>>();
+        match self {
+            Self::Sparse => {
+                // The basis state is a big integer with a heap-allocated digit per 64 qubits,
+                // and the hash map keeps its load factor below 7/8 with a control byte per entry.
+                let key = std::mem::size_of::<BigUint>() + 8 * qubits.div_ceil(64).max(1);
+                amplitudes.saturating_mul((key + amplitude) * 8 / 7 + 1)
+            }
+            Self::Dense => u32::try_from(qubits)
+                .ok()
+                .and_then(|qubits| 1usize.checked_shl(qubits))
+                .map_or(usize::MAX, |states| states.saturating_mul(amplitude)),
+        }
+    }
+}
+
+impl FromStr for SimulatorKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sparse" => Ok(Self::Sparse),
+            "dense" => Ok(Self::Dense),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for SimulatorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sparse => write!(f, "sparse"),
+            Self::Dense => write!(f, "dense"),
+        }
+    }
+}
+
+/// A simulator backend of the kind chosen at runtime.
+pub enum Simulator {
+    Sparse(SparseSim),
+    Dense(DenseSim),
+}
+
+impl Simulator {
+    #[must_use]
+    pub fn new(kind: SimulatorKind) -> Self {
+        match kind {
+            SimulatorKind::Sparse => Self::Sparse(SparseSim::new()),
+            SimulatorKind::Dense => Self::Dense(DenseSim::new()),
+        }
+    }
+
+    pub fn set_forced_outcomes(&mut self, forced: ForcedOutcomes) {
+        match self {
+            Self::Sparse(sim) => sim.forced = forced,
+            Self::Dense(sim) => sim.forced = forced,
+        }
+    }
+
+    pub fn set_intrinsics(&mut self, intrinsics: IntrinsicCallbacks) {
+        match self {
+            Self::Sparse(sim) => sim.intrinsics = intrinsics,
+            Self::Dense(sim) => sim.intrinsics = intrinsics,
+        }
+    }
+}
+
+macro_rules! dispatch {
+    ($self:ident, $sim:ident => $call:expr) => {
+        match $self {
+            Simulator::Sparse($sim) => $call,
+            Simulator::Dense($sim) => $call,
+        }
+    };
+}
+
+impl Backend for Simulator {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        dispatch!(self, sim => sim.ccx(ctl0, ctl1, q));
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        dispatch!(self, sim => sim.cx(ctl, q));
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        dispatch!(self, sim => sim.cy(ctl, q));
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        dispatch!(self, sim => sim.cz(ctl, q));
+    }
+
+    fn h(&mut self, q: usize) {
+        dispatch!(self, sim => sim.h(q));
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        dispatch!(self, sim => sim.m(q))
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        dispatch!(self, sim => sim.mresetz(q))
+    }
+
+    fn reset(&mut self, q: usize) {
+        dispatch!(self, sim => sim.reset(q));
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        dispatch!(self, sim => sim.rx(theta, q));
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        dispatch!(self, sim => sim.rxx(theta, q0, q1));
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        dispatch!(self, sim => sim.ry(theta, q));
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        dispatch!(self, sim => sim.ryy(theta, q0, q1));
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        dispatch!(self, sim => sim.rz(theta, q));
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        dispatch!(self, sim => sim.rzz(theta, q0, q1));
+    }
+
+    fn sadj(&mut self, q: usize) {
+        dispatch!(self, sim => sim.sadj(q));
+    }
+
+    fn s(&mut self, q: usize) {
+        dispatch!(self, sim => sim.s(q));
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        dispatch!(self, sim => sim.swap(q0, q1));
+    }
+
+    fn tadj(&mut self, q: usize) {
+        dispatch!(self, sim => sim.tadj(q));
+    }
+
+    fn t(&mut self, q: usize) {
+        dispatch!(self, sim => sim.t(q));
+    }
+
+    fn x(&mut self, q: usize) {
+        dispatch!(self, sim => sim.x(q));
+    }
+
+    fn y(&mut self, q: usize) {
+        dispatch!(self, sim => sim.y(q));
+    }
+
+    fn z(&mut self, q: usize) {
+        dispatch!(self, sim => sim.z(q));
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        dispatch!(self, sim => sim.qubit_allocate())
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        dispatch!(self, sim => sim.qubit_release(q));
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        dispatch!(self, sim => sim.capture_quantum_state())
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        dispatch!(self, sim => sim.qubit_is_zero(q))
+    }
+
+    fn qubit_borrow(&mut self) -> usize {
+        dispatch!(self, sim => sim.qubit_borrow())
+    }
+
+    fn qubit_return(&mut self, q: usize) -> bool {
+        dispatch!(self, sim => sim.qubit_return(q))
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        dispatch!(self, sim => sim.custom_intrinsic(name, arg))
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        dispatch!(self, sim => sim.set_seed(seed));
+    }
+}
+
 /// Simple struct that chains two backends together so that the chained
 /// backend is called before the main backend.
 /// For any intrinsics that return a value,
@@ -341,6 +713,11 @@ where
         self.main.s(q);
     }
 
+    fn sx(&mut self, q: usize) {
+        self.chained.sx(q);
+        self.main.sx(q);
+    }
+
     fn swap(&mut self, q0: usize, q1: usize) {
         self.chained.swap(q0, q1);
         self.main.swap(q0, q1);
@@ -398,13 +775,55 @@ where
         self.main.qubit_is_zero(q)
     }
 
+    fn qubit_borrow(&mut self) -> usize {
+        // As with allocation, the qubit id borrowed by the main backend is used for both.
+        let _ = self.chained.qubit_borrow();
+        self.main.qubit_borrow()
+    }
+
+    fn qubit_return(&mut self, q: usize) -> bool {
+        let _ = self.chained.qubit_return(q);
+        self.main.qubit_return(q)
+    }
+
     fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
         let _ = self.chained.custom_intrinsic(name, arg.clone());
         self.main.custom_intrinsic(name, arg)
     }
 
+    fn target_intrinsic(
+        &mut self,
+        intrinsic: &CustomIntrinsic,
+        arg: Value,
+    ) -> Option<Result<Value, String>> {
+        let _ = self.chained.target_intrinsic(intrinsic, arg.clone());
+        self.main.target_intrinsic(intrinsic, arg)
+    }
+
     fn set_seed(&mut self, seed: Option<u64>) {
         self.chained.set_seed(seed);
         self.main.set_seed(seed);
     }
+
+    // Result ids come from the main backend, so conditionals are only forwarded to it.
+    fn supports_conditionals(&self) -> bool {
+        self.main.supports_conditionals()
+    }
+
+    fn begin_conditional(&mut self, result: usize, value: bool) {
+        self.main.begin_conditional(result, value);
+    }
+
+    fn end_conditional(&mut self) {
+        self.main.end_conditional();
+    }
+
+    fn records_call_sites(&self) -> bool {
+        self.main.records_call_sites() || self.chained.records_call_sites()
+    }
+
+    fn set_call_site(&mut self, span: PackageSpan, callable: Rc<str>) {
+        self.chained.set_call_site(span, callable.clone());
+        self.main.set_call_site(span, callable);
+    }
 }
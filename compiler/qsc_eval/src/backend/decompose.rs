@@ -0,0 +1,385 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::Backend;
+use crate::{val::Value, PackageSpan};
+use num_bigint::BigUint;
+use num_complex::Complex;
+use qsc_fir::fir::CustomIntrinsic;
+use std::{
+    f64::consts::{FRAC_PI_2, FRAC_PI_4, PI},
+    fmt::{self, Display, Formatter},
+    rc::Rc,
+    str::FromStr,
+};
+
+/// The gates that a target executes natively. Intrinsics outside of the gate set are rewritten
+/// into gates of the set, up to a global phase, before they reach the backend that emits them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GateSet {
+    /// Every intrinsic of the standard library, which is emitted as is.
+    #[default]
+    Full,
+    /// Z rotations, square roots of X and controlled NOTs.
+    RzSxCx,
+    /// X and Y rotations, and ZZ rotations.
+    RxRyRzz,
+}
+
+impl GateSet {
+    /// The names of the gates in the set, or `None` for the full set of intrinsics.
+    #[must_use]
+    pub fn gates(self) -> Option<&'static [&'static str]> {
+        match self {
+            Self::Full => None,
+            Self::RzSxCx => Some(&["rz", "sx", "cx"]),
+            Self::RxRyRzz => Some(&["rx", "ry", "rzz"]),
+        }
+    }
+}
+
+/// Parses `full`, or the comma-separated names of the gates in a set, in any order.
+impl FromStr for GateSet {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "full" {
+            return Ok(Self::Full);
+        }
+        let mut names = s.split(',').map(str::trim).collect::<Vec<_>>();
+        names.sort_unstable();
+        [Self::RzSxCx, Self::RxRyRzz]
+            .into_iter()
+            .find(|gate_set| {
+                let mut gates = gate_set.gates().unwrap_or_default().to_vec();
+                gates.sort_unstable();
+                gates == names
+            })
+            .ok_or(())
+    }
+}
+
+impl Display for GateSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.gates() {
+            None => write!(f, "full"),
+            Some(gates) => write!(f, "{}", gates.join(",")),
+        }
+    }
+}
+
+/// A backend that rewrites the intrinsics outside of the target gate set into gates of the set
+/// before passing them on to the wrapped backend, so that the QIR or circuits it emits only use
+/// gates the target executes natively. Measurements, resets and qubit management are passed on
+/// unchanged.
+pub struct Decomposer<B> {
+    pub backend: B,
+    gate_set: GateSet,
+}
+
+impl<B: Backend> Decomposer<B> {
+    pub fn new(backend: B, gate_set: GateSet) -> Self {
+        Self { backend, gate_set }
+    }
+}
+
+impl<B: Backend> Backend for Decomposer<B> {
+    type ResultType = B::ResultType;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        if self.gate_set == GateSet::Full {
+            self.backend.ccx(ctl0, ctl1, q);
+        } else {
+            self.h(q);
+            self.cx(ctl1, q);
+            self.tadj(q);
+            self.cx(ctl0, q);
+            self.t(q);
+            self.cx(ctl1, q);
+            self.tadj(q);
+            self.cx(ctl0, q);
+            self.t(ctl1);
+            self.t(q);
+            self.h(q);
+            self.cx(ctl0, ctl1);
+            self.t(ctl0);
+            self.tadj(ctl1);
+            self.cx(ctl0, ctl1);
+        }
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        match self.gate_set {
+            GateSet::Full | GateSet::RzSxCx => self.backend.cx(ctl, q),
+            GateSet::RxRyRzz => {
+                self.h(q);
+                self.cz(ctl, q);
+                self.h(q);
+            }
+        }
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        if self.gate_set == GateSet::Full {
+            self.backend.cy(ctl, q);
+        } else {
+            self.sadj(q);
+            self.cx(ctl, q);
+            self.s(q);
+        }
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        match self.gate_set {
+            GateSet::Full => self.backend.cz(ctl, q),
+            GateSet::RzSxCx => {
+                self.h(q);
+                self.cx(ctl, q);
+                self.h(q);
+            }
+            GateSet::RxRyRzz => {
+                self.rzz(-FRAC_PI_2, ctl, q);
+                self.rz(FRAC_PI_2, ctl);
+                self.rz(FRAC_PI_2, q);
+            }
+        }
+    }
+
+    fn h(&mut self, q: usize) {
+        match self.gate_set {
+            GateSet::Full => self.backend.h(q),
+            GateSet::RzSxCx => {
+                self.rz(FRAC_PI_2, q);
+                self.sx(q);
+                self.rz(FRAC_PI_2, q);
+            }
+            GateSet::RxRyRzz => {
+                self.ry(FRAC_PI_2, q);
+                self.rx(PI, q);
+            }
+        }
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.backend.m(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        self.backend.mresetz(q)
+    }
+
+    fn reset(&mut self, q: usize) {
+        self.backend.reset(q);
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        match self.gate_set {
+            GateSet::Full | GateSet::RxRyRzz => self.backend.rx(theta, q),
+            GateSet::RzSxCx => {
+                self.h(q);
+                self.rz(theta, q);
+                self.h(q);
+            }
+        }
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        if self.gate_set == GateSet::Full {
+            self.backend.rxx(theta, q0, q1);
+        } else {
+            self.h(q0);
+            self.h(q1);
+            self.rzz(theta, q0, q1);
+            self.h(q1);
+            self.h(q0);
+        }
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        match self.gate_set {
+            GateSet::Full | GateSet::RxRyRzz => self.backend.ry(theta, q),
+            GateSet::RzSxCx => {
+                self.sadj(q);
+                self.rx(theta, q);
+                self.s(q);
+            }
+        }
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        if self.gate_set == GateSet::Full {
+            self.backend.ryy(theta, q0, q1);
+        } else {
+            self.sadj(q0);
+            self.sadj(q1);
+            self.rxx(theta, q0, q1);
+            self.s(q1);
+            self.s(q0);
+        }
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        match self.gate_set {
+            GateSet::Full | GateSet::RzSxCx => self.backend.rz(theta, q),
+            GateSet::RxRyRzz => {
+                self.rx(-FRAC_PI_2, q);
+                self.ry(theta, q);
+                self.rx(FRAC_PI_2, q);
+            }
+        }
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        match self.gate_set {
+            GateSet::Full | GateSet::RxRyRzz => self.backend.rzz(theta, q0, q1),
+            GateSet::RzSxCx => {
+                self.cx(q0, q1);
+                self.rz(theta, q1);
+                self.cx(q0, q1);
+            }
+        }
+    }
+
+    fn sadj(&mut self, q: usize) {
+        if self.gate_set == GateSet::Full {
+            self.backend.sadj(q);
+        } else {
+            self.rz(-FRAC_PI_2, q);
+        }
+    }
+
+    fn s(&mut self, q: usize) {
+        if self.gate_set == GateSet::Full {
+            self.backend.s(q);
+        } else {
+            self.rz(FRAC_PI_2, q);
+        }
+    }
+
+    fn sx(&mut self, q: usize) {
+        match self.gate_set {
+            GateSet::Full | GateSet::RzSxCx => self.backend.sx(q),
+            GateSet::RxRyRzz => self.rx(FRAC_PI_2, q),
+        }
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        if self.gate_set == GateSet::Full {
+            self.backend.swap(q0, q1);
+        } else {
+            self.cx(q0, q1);
+            self.cx(q1, q0);
+            self.cx(q0, q1);
+        }
+    }
+
+    fn tadj(&mut self, q: usize) {
+        if self.gate_set == GateSet::Full {
+            self.backend.tadj(q);
+        } else {
+            self.rz(-FRAC_PI_4, q);
+        }
+    }
+
+    fn t(&mut self, q: usize) {
+        if self.gate_set == GateSet::Full {
+            self.backend.t(q);
+        } else {
+            self.rz(FRAC_PI_4, q);
+        }
+    }
+
+    fn x(&mut self, q: usize) {
+        match self.gate_set {
+            GateSet::Full => self.backend.x(q),
+            GateSet::RzSxCx => {
+                self.sx(q);
+                self.sx(q);
+            }
+            GateSet::RxRyRzz => self.rx(PI, q),
+        }
+    }
+
+    fn y(&mut self, q: usize) {
+        match self.gate_set {
+            GateSet::Full => self.backend.y(q),
+            GateSet::RzSxCx => {
+                self.z(q);
+                self.x(q);
+            }
+            GateSet::RxRyRzz => self.ry(PI, q),
+        }
+    }
+
+    fn z(&mut self, q: usize) {
+        if self.gate_set == GateSet::Full {
+            self.backend.z(q);
+        } else {
+            self.rz(PI, q);
+        }
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        self.backend.qubit_allocate()
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.backend.qubit_release(q);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        self.backend.capture_quantum_state()
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.backend.qubit_is_zero(q)
+    }
+
+    fn qubit_borrow(&mut self) -> usize {
+        self.backend.qubit_borrow()
+    }
+
+    fn qubit_return(&mut self, q: usize) -> bool {
+        self.backend.qubit_return(q)
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        self.backend.custom_intrinsic(name, arg)
+    }
+
+    fn target_intrinsic(
+        &mut self,
+        intrinsic: &CustomIntrinsic,
+        arg: Value,
+    ) -> Option<Result<Value, String>> {
+        // Intrinsics of the target are already native to it.
+        self.backend.target_intrinsic(intrinsic, arg)
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.backend.set_seed(seed);
+    }
+
+    fn supports_conditionals(&self) -> bool {
+        self.backend.supports_conditionals()
+    }
+
+    fn begin_conditional(&mut self, result: usize, value: bool) {
+        self.backend.begin_conditional(result, value);
+    }
+
+    fn end_conditional(&mut self) {
+        self.backend.end_conditional();
+    }
+
+    fn records_call_sites(&self) -> bool {
+        self.backend.records_call_sites()
+    }
+
+    fn set_call_site(&mut self, span: PackageSpan, callable: Rc<str>) {
+        self.backend.set_call_site(span, callable);
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{Decomposer, GateSet};
+use crate::backend::{Backend, DenseSim};
+use num_bigint::BigUint;
+use num_complex::Complex;
+
+/// Applies every intrinsic to three qubits in a superposition, so that the decompositions are
+/// checked on states where their phases matter.
+fn apply_intrinsics(sim: &mut impl Backend) {
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.ry(0.3, q1);
+    sim.rx(0.7, q2);
+    sim.cx(q0, q1);
+    sim.cy(q1, q2);
+    sim.cz(q2, q0);
+    sim.ccx(q0, q1, q2);
+    sim.swap(q0, q2);
+    sim.rxx(0.2, q0, q1);
+    sim.ryy(0.4, q1, q2);
+    sim.rzz(0.6, q2, q0);
+    sim.rz(0.8, q0);
+    sim.s(q1);
+    sim.sadj(q2);
+    sim.t(q0);
+    sim.tadj(q1);
+    sim.x(q2);
+    sim.y(q0);
+    sim.z(q1);
+    sim.sx(q2);
+    sim.h(q1);
+}
+
+fn amplitude_of(state: &[(BigUint, Complex<f64>)], id: &BigUint) -> Complex<f64> {
+    state
+        .iter()
+        .find(|(state_id, _)| state_id == id)
+        .map_or(Complex::new(0.0, 0.0), |(_, amplitude)| *amplitude)
+}
+
+fn assert_equal_up_to_global_phase(
+    expected: &[(BigUint, Complex<f64>)],
+    actual: &[(BigUint, Complex<f64>)],
+) {
+    let (reference_id, reference) = expected
+        .iter()
+        .max_by(|(_, a), (_, b)| a.norm().total_cmp(&b.norm()))
+        .expect("state should have a nonzero amplitude");
+    let phase = amplitude_of(actual, reference_id) / reference;
+    assert!((phase.norm() - 1.0).abs() < 1e-6, "phase should be unit");
+    for (id, _) in expected.iter().chain(actual) {
+        let expected = amplitude_of(expected, id) * phase;
+        let actual = amplitude_of(actual, id);
+        assert!(
+            (expected - actual).norm() < 1e-6,
+            "amplitude of {id} should be {expected} up to global phase, but was {actual}"
+        );
+    }
+}
+
+fn check_decomposition(gate_set: GateSet) {
+    let mut sim = DenseSim::new();
+    apply_intrinsics(&mut sim);
+    let (expected, _) = sim.capture_quantum_state();
+
+    let mut decomposer = Decomposer::new(DenseSim::new(), gate_set);
+    apply_intrinsics(&mut decomposer);
+    let (actual, _) = decomposer.capture_quantum_state();
+
+    assert_equal_up_to_global_phase(&expected, &actual);
+}
+
+#[test]
+fn rz_sx_cx_decomposition_preserves_state() {
+    check_decomposition(GateSet::RzSxCx);
+}
+
+#[test]
+fn rx_ry_rzz_decomposition_preserves_state() {
+    check_decomposition(GateSet::RxRyRzz);
+}
+
+#[test]
+fn gate_set_parses_gates_in_any_order() {
+    assert_eq!("full".parse(), Ok(GateSet::Full));
+    assert_eq!("cx, rz, sx".parse(), Ok(GateSet::RzSxCx));
+    assert_eq!("rzz,rx,ry".parse(), Ok(GateSet::RxRyRzz));
+    assert_eq!("rz,cx".parse::<GateSet>(), Err(()));
+    assert_eq!(GateSet::RzSxCx.to_string(), "rz,sx,cx");
+}
@@ -0,0 +1,406 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use super::{dirty_rotations, Backend, ForcedOutcomes, IntrinsicCallbacks};
+use crate::val::Value;
+use num_bigint::BigUint;
+use num_complex::{Complex, Complex64};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::f64::consts::{FRAC_1_SQRT_2, FRAC_PI_4};
+
+/// Amplitudes with a smaller magnitude are left out of state dumps.
+const DUMP_THRESHOLD: f64 = 1e-12;
+
+/// Backend that simulates the full state vector, with an amplitude for every basis state of the
+/// allocated qubits. It uses memory exponential in the number of qubits regardless of the state,
+/// but outperforms the sparse simulator on highly entangled states, where most amplitudes are
+/// nonzero.
+///
+/// Gates are applied by splitting the state into the halves where the target qubit is zero and
+/// one, and updating the halves pairwise in contiguous runs, which the compiler vectorizes.
+pub struct DenseSim {
+    /// The amplitudes of the basis states, indexed by the bits of the qubits at their positions.
+    state: Vec<Complex64>,
+    /// The bit position of each qubit in the state indices, indexed by qubit id. Released ids
+    /// are `None` until they are reused.
+    positions: Vec<Option<usize>>,
+    rng: StdRng,
+    pub forced: ForcedOutcomes,
+    pub intrinsics: IntrinsicCallbacks,
+}
+
+impl Default for DenseSim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DenseSim {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            state: vec![Complex64::new(1.0, 0.0)],
+            positions: Vec::new(),
+            rng: StdRng::from_entropy(),
+            forced: ForcedOutcomes::default(),
+            intrinsics: IntrinsicCallbacks::default(),
+        }
+    }
+
+    fn qubit_count(&self) -> usize {
+        self.state.len().trailing_zeros() as usize
+    }
+
+    fn mask(&self, q: usize) -> usize {
+        1 << self
+            .positions
+            .get(q)
+            .copied()
+            .flatten()
+            .expect("qubit should be allocated")
+    }
+
+    /// Applies the given matrix to the target qubit in the basis states where all the controls
+    /// are one.
+    fn apply(&mut self, ctls: &[usize], q: usize, matrix: [[Complex64; 2]; 2]) {
+        let ctls = ctls.iter().fold(0, |mask, &ctl| mask | self.mask(ctl));
+        let target = self.mask(q);
+        let [[m00, m01], [m10, m11]] = matrix;
+        for (block, chunk) in self.state.chunks_mut(target * 2).enumerate() {
+            let base = block * target * 2;
+            let (zeros, ones) = chunk.split_at_mut(target);
+            for (i, (a0, a1)) in zeros.iter_mut().zip(ones.iter_mut()).enumerate() {
+                if (base + i) & ctls == ctls {
+                    let (b0, b1) = (*a0, *a1);
+                    *a0 = m00 * b0 + m01 * b1;
+                    *a1 = m10 * b0 + m11 * b1;
+                }
+            }
+        }
+    }
+
+    /// Multiplies the amplitudes of the basis states where the target qubit and all the controls
+    /// are one by the given phase.
+    fn phase(&mut self, ctls: &[usize], q: usize, phase: Complex64) {
+        let mask = ctls
+            .iter()
+            .fold(self.mask(q), |mask, &ctl| mask | self.mask(ctl));
+        for (i, amplitude) in self.state.iter_mut().enumerate() {
+            if i & mask == mask {
+                *amplitude *= phase;
+            }
+        }
+    }
+
+    fn flip(&mut self, ctls: &[usize], q: usize) {
+        let ctls = ctls.iter().fold(0, |mask, &ctl| mask | self.mask(ctl));
+        let target = self.mask(q);
+        for (block, chunk) in self.state.chunks_mut(target * 2).enumerate() {
+            let base = block * target * 2;
+            let (zeros, ones) = chunk.split_at_mut(target);
+            if ctls == 0 {
+                zeros.swap_with_slice(ones);
+            } else {
+                for (i, (a0, a1)) in zeros.iter_mut().zip(ones.iter_mut()).enumerate() {
+                    if (base + i) & ctls == ctls {
+                        std::mem::swap(a0, a1);
+                    }
+                }
+            }
+        }
+    }
+
+    fn probability_of_one(&self, q: usize) -> f64 {
+        let target = self.mask(q);
+        self.state
+            .chunks(target * 2)
+            .flat_map(|chunk| &chunk[target..])
+            .map(Complex64::norm_sqr)
+            .sum()
+    }
+
+    /// Measures the qubit without forcing the outcome, collapsing the state to the basis states
+    /// that agree with the outcome.
+    fn collapse(&mut self, q: usize) -> bool {
+        let one = self.probability_of_one(q);
+        let res = self.rng.gen::<f64>() < one;
+        let norm = (if res { one } else { 1.0 - one }).sqrt().recip();
+        let target = self.mask(q);
+        for (i, amplitude) in self.state.iter_mut().enumerate() {
+            if (i & target != 0) == res {
+                *amplitude *= norm;
+            } else {
+                *amplitude = Complex64::default();
+            }
+        }
+        res
+    }
+
+    fn measure(&mut self, q: usize) -> bool {
+        let res = self.collapse(q);
+        match self.forced.next(q) {
+            Some(forced) if forced != res => {
+                self.flip(&[], q);
+                forced
+            }
+            _ => res,
+        }
+    }
+
+    /// Moves the state of the qubit at the given position to the other position, and the state of
+    /// the qubit at the other position to the given one.
+    fn swap_positions(&mut self, p0: usize, p1: usize) {
+        let (m0, m1) = (1 << p0, 1 << p1);
+        for i in 0..self.state.len() {
+            if i & m0 != 0 && i & m1 == 0 {
+                self.state.swap(i, i ^ m0 ^ m1);
+            }
+        }
+        for position in self.positions.iter_mut().flatten() {
+            if *position == p0 {
+                *position = p1;
+            } else if *position == p1 {
+                *position = p0;
+            }
+        }
+    }
+}
+
+impl Backend for DenseSim {
+    type ResultType = bool;
+
+    fn ccx(&mut self, ctl0: usize, ctl1: usize, q: usize) {
+        self.flip(&[ctl0, ctl1], q);
+    }
+
+    fn cx(&mut self, ctl: usize, q: usize) {
+        self.flip(&[ctl], q);
+    }
+
+    fn cy(&mut self, ctl: usize, q: usize) {
+        let i = Complex64::i();
+        let zero = Complex64::default();
+        self.apply(&[ctl], q, [[zero, -i], [i, zero]]);
+    }
+
+    fn cz(&mut self, ctl: usize, q: usize) {
+        self.phase(&[ctl], q, Complex64::new(-1.0, 0.0));
+    }
+
+    fn h(&mut self, q: usize) {
+        let h = Complex64::new(FRAC_1_SQRT_2, 0.0);
+        self.apply(&[], q, [[h, h], [h, -h]]);
+    }
+
+    fn m(&mut self, q: usize) -> Self::ResultType {
+        self.measure(q)
+    }
+
+    fn mresetz(&mut self, q: usize) -> Self::ResultType {
+        let res = self.measure(q);
+        if res {
+            self.flip(&[], q);
+        }
+        res
+    }
+
+    fn reset(&mut self, q: usize) {
+        if self.collapse(q) {
+            self.flip(&[], q);
+        }
+    }
+
+    fn rx(&mut self, theta: f64, q: usize) {
+        let c = Complex64::new((theta / 2.0).cos(), 0.0);
+        let s = Complex64::new(0.0, -(theta / 2.0).sin());
+        self.apply(&[], q, [[c, s], [s, c]]);
+    }
+
+    fn rxx(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.h(q0);
+        self.h(q1);
+        self.rzz(theta, q0, q1);
+        self.h(q1);
+        self.h(q0);
+    }
+
+    fn ry(&mut self, theta: f64, q: usize) {
+        let c = Complex64::new((theta / 2.0).cos(), 0.0);
+        let s = Complex64::new((theta / 2.0).sin(), 0.0);
+        self.apply(&[], q, [[c, -s], [s, c]]);
+    }
+
+    fn ryy(&mut self, theta: f64, q0: usize, q1: usize) {
+        for q in [q0, q1] {
+            self.h(q);
+            self.s(q);
+            self.h(q);
+        }
+        self.rzz(theta, q0, q1);
+        for q in [q1, q0] {
+            self.h(q);
+            self.sadj(q);
+            self.h(q);
+        }
+    }
+
+    fn rz(&mut self, theta: f64, q: usize) {
+        let zero = Complex64::default();
+        self.apply(
+            &[],
+            q,
+            [
+                [Complex64::from_polar(1.0, -theta / 2.0), zero],
+                [zero, Complex64::from_polar(1.0, theta / 2.0)],
+            ],
+        );
+    }
+
+    fn rzz(&mut self, theta: f64, q0: usize, q1: usize) {
+        self.cx(q1, q0);
+        self.rz(theta, q0);
+        self.cx(q1, q0);
+    }
+
+    fn sadj(&mut self, q: usize) {
+        self.phase(&[], q, -Complex64::i());
+    }
+
+    fn s(&mut self, q: usize) {
+        self.phase(&[], q, Complex64::i());
+    }
+
+    fn swap(&mut self, q0: usize, q1: usize) {
+        self.positions.swap(q0, q1);
+    }
+
+    fn tadj(&mut self, q: usize) {
+        self.phase(&[], q, Complex64::from_polar(1.0, -FRAC_PI_4));
+    }
+
+    fn t(&mut self, q: usize) {
+        self.phase(&[], q, Complex64::from_polar(1.0, FRAC_PI_4));
+    }
+
+    fn x(&mut self, q: usize) {
+        self.flip(&[], q);
+    }
+
+    fn y(&mut self, q: usize) {
+        let i = Complex64::i();
+        let zero = Complex64::default();
+        self.apply(&[], q, [[zero, -i], [i, zero]]);
+    }
+
+    fn z(&mut self, q: usize) {
+        self.phase(&[], q, Complex64::new(-1.0, 0.0));
+    }
+
+    fn qubit_allocate(&mut self) -> usize {
+        let position = self.qubit_count();
+        // The new qubit is the most significant bit of the state indices, so the states where it
+        // is one are appended to the state, all with zero amplitude.
+        self.state
+            .resize(self.state.len() * 2, Complex64::default());
+        if let Some(id) = self.positions.iter().position(Option::is_none) {
+            self.positions[id] = Some(position);
+            id
+        } else {
+            self.positions.push(Some(position));
+            self.positions.len() - 1
+        }
+    }
+
+    fn qubit_release(&mut self, q: usize) {
+        self.reset(q);
+        // Once the qubit is moved to the most significant bit, the states where it is one are at
+        // the end of the state, and they all have zero amplitude.
+        let top = self.qubit_count() - 1;
+        let position = self.mask(q).trailing_zeros() as usize;
+        if position != top {
+            self.swap_positions(position, top);
+        }
+        self.positions[q] = None;
+        self.state.truncate(self.state.len() / 2);
+    }
+
+    fn capture_quantum_state(&mut self) -> (Vec<(BigUint, Complex<f64>)>, usize) {
+        // The qubits are ordered by id, with the first one as the most significant bit of the
+        // reported state indices, to match the sparse simulator.
+        let mut ids = self
+            .positions
+            .iter()
+            .enumerate()
+            .filter_map(|(id, position)| position.map(|position| (id, position)))
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        let count = ids.len();
+        let mut state = self
+            .state
+            .iter()
+            .enumerate()
+            .filter(|(_, amplitude)| amplitude.norm() > DUMP_THRESHOLD)
+            .map(|(i, amplitude)| {
+                let mut index = BigUint::default();
+                for (order, &(_, position)) in ids.iter().enumerate() {
+                    if i & (1 << position) != 0 {
+                        index.set_bit((count - 1 - order) as u64, true);
+                    }
+                }
+                (index, *amplitude)
+            })
+            .collect::<Vec<_>>();
+        state.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        (state, count)
+    }
+
+    fn qubit_is_zero(&mut self, q: usize) -> bool {
+        self.probability_of_one(q) <= DUMP_THRESHOLD
+    }
+
+    fn qubit_borrow(&mut self) -> usize {
+        let q = self.qubit_allocate();
+        let (theta, phi) = dirty_rotations(q);
+        self.ry(theta, q);
+        self.rz(phi, q);
+        q
+    }
+
+    fn qubit_return(&mut self, q: usize) -> bool {
+        let (theta, phi) = dirty_rotations(q);
+        self.rz(-phi, q);
+        self.ry(-theta, q);
+        if self.qubit_is_zero(q) {
+            self.qubit_release(q);
+            true
+        } else {
+            self.ry(theta, q);
+            self.rz(phi, q);
+            false
+        }
+    }
+
+    fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
+        if let Some(callback) = self.intrinsics.get(name) {
+            return Some(callback(arg));
+        }
+        match name {
+            "BeginEstimateCaching" => Some(Ok(Value::Bool(true))),
+            "EndEstimateCaching"
+            | "AccountForEstimatesInternal"
+            | "BeginRepeatEstimatesInternal"
+            | "EndRepeatEstimatesInternal" => Some(Ok(Value::unit())),
+            _ => None,
+        }
+    }
+
+    fn set_seed(&mut self, seed: Option<u64>) {
+        self.rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::DenseSim;
+use crate::{
+    backend::{Backend, ForcedOutcomes},
+    state::{fmt_complex, format_state_id},
+};
+use expect_test::{expect, Expect};
+use std::fmt::Write;
+
+fn check_state(sim: &mut DenseSim, expect: &Expect) {
+    let (state, count) = sim.capture_quantum_state();
+    let mut actual = String::new();
+    for (id, amplitude) in state {
+        let _ = writeln!(
+            actual,
+            "{}: {}",
+            format_state_id(&id, count),
+            fmt_complex(&amplitude)
+        );
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn bell_state() {
+    let mut sim = DenseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+    check_state(
+        &mut sim,
+        &expect![[r#"
+            |00⟩: 0.7071+0.0000𝑖
+            |11⟩: 0.7071+0.0000𝑖
+        "#]],
+    );
+}
+
+#[test]
+fn first_qubit_is_leftmost() {
+    let mut sim = DenseSim::new();
+    let q0 = sim.qubit_allocate();
+    let _ = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.ry(std::f64::consts::PI, q2);
+    check_state(
+        &mut sim,
+        &expect![[r#"
+            |101⟩: 1.0000+0.0000𝑖
+        "#]],
+    );
+}
+
+#[test]
+fn swap_exchanges_qubit_states() {
+    let mut sim = DenseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.s(q0);
+    sim.swap(q0, q1);
+    check_state(
+        &mut sim,
+        &expect![[r#"
+            |01⟩: 0.0000+1.0000𝑖
+        "#]],
+    );
+}
+
+#[test]
+fn release_compacts_state_and_reuses_id() {
+    let mut sim = DenseSim::new();
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    let q2 = sim.qubit_allocate();
+    sim.x(q0);
+    sim.h(q1);
+    sim.x(q2);
+    sim.qubit_release(q1);
+    check_state(
+        &mut sim,
+        &expect![[r#"
+            |11⟩: 1.0000+0.0000𝑖
+        "#]],
+    );
+
+    assert_eq!(sim.qubit_allocate(), q1);
+    sim.rx(std::f64::consts::PI, q1);
+    check_state(
+        &mut sim,
+        &expect![[r#"
+            |111⟩: 0.0000−1.0000𝑖
+        "#]],
+    );
+}
+
+#[test]
+fn measurement_collapses_entangled_qubits() {
+    let mut sim = DenseSim::new();
+    sim.set_seed(Some(42));
+    let q0 = sim.qubit_allocate();
+    let q1 = sim.qubit_allocate();
+    sim.h(q0);
+    sim.cx(q0, q1);
+    let res = sim.m(q0);
+    assert_eq!(sim.qubit_is_zero(q1), !res);
+    assert_eq!(sim.mresetz(q1), res);
+    assert!(sim.qubit_is_zero(q1));
+}
+
+#[test]
+fn forced_outcome_flips_measured_qubit() {
+    let mut sim = DenseSim::new();
+    let q = sim.qubit_allocate();
+    sim.forced = ForcedOutcomes {
+        sequence: [Some(true)].into(),
+        ..ForcedOutcomes::default()
+    };
+    assert!(sim.m(q));
+    assert!(!sim.qubit_is_zero(q));
+    assert!(sim.m(q));
+}
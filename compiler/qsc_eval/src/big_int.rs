@@ -0,0 +1,74 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Arithmetic on `BigInt` values. Programs such as the classical post-processing of Shor's algorithm
+//! spend most of their time on `BigInt` values that would fit in an `Int`, so the operations that
+//! are expensive for arbitrary-precision values are computed with machine integers when both
+//! operands fit and the result doesn't overflow. Their results, like those of the other
+//! operations, are stored in the left operand, reusing its digits instead of allocating new ones.
+
+#[cfg(test)]
+mod tests;
+
+use num_bigint::{BigInt, Sign};
+use num_traits::ToPrimitive;
+use std::cmp::Ordering;
+
+fn small(lhs: &BigInt, rhs: &BigInt) -> Option<(i64, i64)> {
+    Some((lhs.to_i64()?, rhs.to_i64()?))
+}
+
+/// Stores a machine integer in `target`, reusing its digits instead of allocating new ones.
+fn assign(mut target: BigInt, value: i64) -> BigInt {
+    let sign = match value.cmp(&0) {
+        Ordering::Less => Sign::Minus,
+        Ordering::Equal => Sign::NoSign,
+        Ordering::Greater => Sign::Plus,
+    };
+    let magnitude = value.unsigned_abs();
+    // The magnitude is split into its low and high halves.
+    #[allow(clippy::cast_possible_truncation)]
+    target.assign_from_slice(sign, &[magnitude as u32, (magnitude >> 32) as u32]);
+    target
+}
+
+pub(crate) fn add(mut lhs: BigInt, rhs: &BigInt) -> BigInt {
+    lhs += rhs;
+    lhs
+}
+
+pub(crate) fn sub(mut lhs: BigInt, rhs: &BigInt) -> BigInt {
+    lhs -= rhs;
+    lhs
+}
+
+pub(crate) fn mul(lhs: BigInt, rhs: &BigInt) -> BigInt {
+    match small(&lhs, rhs).and_then(|(lhs, rhs)| lhs.checked_mul(rhs)) {
+        Some(product) => assign(lhs, product),
+        None => lhs * rhs,
+    }
+}
+
+/// Divides, truncating towards zero. The divisor must not be zero.
+pub(crate) fn div(lhs: BigInt, rhs: &BigInt) -> BigInt {
+    match small(&lhs, rhs).and_then(|(lhs, rhs)| lhs.checked_div(rhs)) {
+        Some(quotient) => assign(lhs, quotient),
+        None => lhs / rhs,
+    }
+}
+
+/// The remainder of truncating division, which has the sign of the dividend. The divisor must not
+/// be zero.
+pub(crate) fn rem(lhs: BigInt, rhs: &BigInt) -> BigInt {
+    match small(&lhs, rhs).and_then(|(lhs, rhs)| lhs.checked_rem(rhs)) {
+        Some(remainder) => assign(lhs, remainder),
+        None => lhs % rhs,
+    }
+}
+
+pub(crate) fn pow(base: BigInt, exp: u32) -> BigInt {
+    match base.to_i64().and_then(|base| base.checked_pow(exp)) {
+        Some(power) => assign(base, power),
+        None => num_traits::Pow::pow(base, exp),
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{add, div, mul, pow, rem, sub};
+use num_bigint::BigInt;
+
+fn big(value: &str) -> BigInt {
+    value.parse().expect("value should be an integer")
+}
+
+#[test]
+fn small_values_match_arbitrary_precision() {
+    let values = [-7, -2, -1, 0, 1, 3, 10, i64::MAX, i64::MIN];
+    for lhs in values {
+        for rhs in values {
+            let (l, r) = (BigInt::from(lhs), BigInt::from(rhs));
+            assert_eq!(add(l.clone(), &r), &l + &r);
+            assert_eq!(sub(l.clone(), &r), &l - &r);
+            assert_eq!(mul(l.clone(), &r), &l * &r);
+            if rhs != 0 {
+                assert_eq!(div(l.clone(), &r), &l / &r, "{lhs} / {rhs}");
+                assert_eq!(rem(l.clone(), &r), &l % &r, "{lhs} % {rhs}");
+            }
+        }
+    }
+}
+
+#[test]
+fn overflowing_results_fall_back_to_arbitrary_precision() {
+    let max = BigInt::from(i64::MAX);
+    assert_eq!(
+        mul(max.clone(), &max),
+        big("85070591730234615847396907784232501249")
+    );
+    assert_eq!(
+        div(BigInt::from(i64::MIN), &BigInt::from(-1)),
+        big("9223372036854775808")
+    );
+    assert_eq!(
+        rem(BigInt::from(i64::MIN), &BigInt::from(-1)),
+        BigInt::from(0)
+    );
+    assert_eq!(pow(BigInt::from(2), 64), big("18446744073709551616"));
+    assert_eq!(pow(BigInt::from(-3), 3), BigInt::from(-27));
+}
+
+#[test]
+fn large_values_use_arbitrary_precision() {
+    let large = big("123456789012345678901234567890");
+    assert_eq!(rem(large.clone(), &BigInt::from(97)), BigInt::from(52));
+    assert_eq!(
+        div(large.clone(), &big("1234567890123456789")),
+        big("100000000000")
+    );
+    assert_eq!(
+        add(large.clone(), &large),
+        big("246913578024691357802469135780")
+    );
+}
@@ -32,6 +32,12 @@ impl CallStack {
         self.frames.len()
     }
 
+    /// The frames of the calls, outermost first.
+    #[must_use]
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
     #[must_use]
     pub fn into_frames(self) -> Vec<Frame> {
         self.frames
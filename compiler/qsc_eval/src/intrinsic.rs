@@ -14,6 +14,7 @@ use crate::{
     Error,
 };
 use num_bigint::BigInt;
+use qsc_fir::fir::CustomIntrinsic;
 use rand::{rngs::StdRng, Rng};
 use rustc_hash::FxHashSet;
 use std::array;
@@ -55,16 +56,30 @@ pub(crate) fn call(
             let (state, qubit_count) = sim.capture_quantum_state();
             let state = utils::split_state(&qubits, state, qubit_count)
                 .map_err(|()| Error::QubitsNotSeparable(arg_span))?;
-            match out.state(state, qubits.len()) {
+            match out.register_state(&qubits, state) {
                 Ok(()) => Ok(Value::unit()),
                 Err(_) => Err(Error::OutputFail(name_span)),
             }
         }
+        "Message" if out.drops_messages() => Ok(Value::unit()),
         "Message" => match out.message(&arg.unwrap_string()) {
             Ok(()) => Ok(Value::unit()),
             Err(_) => Err(Error::OutputFail(name_span)),
         },
         "CheckZero" => Ok(Value::Bool(sim.qubit_is_zero(arg.unwrap_qubit().0))),
+        "EqualityFact" => {
+            let [actual, expected, message] = unwrap_tuple(arg);
+            if actual == expected {
+                Ok(Value::unit())
+            } else {
+                Err(Error::FactFailed(
+                    message.unwrap_string().to_string(),
+                    actual.to_string(),
+                    expected.to_string(),
+                    arg_span,
+                ))
+            }
+        }
         "ArcCos" => Ok(Value::Double(arg.unwrap_double().acos())),
         "ArcSin" => Ok(Value::Double(arg.unwrap_double().asin())),
         "ArcTan" => Ok(Value::Double(arg.unwrap_double().atan())),
@@ -112,6 +127,15 @@ pub(crate) fn call(
                 Err(Error::ReleasedQubitNotZero(qubit, arg_span))
             }
         }
+        "__quantum__rt__qubit_borrow" => Ok(Value::Qubit(Qubit(sim.qubit_borrow()))),
+        "__quantum__rt__qubit_return" => {
+            let qubit = arg.unwrap_qubit().0;
+            if sim.qubit_return(qubit) {
+                Ok(Value::unit())
+            } else {
+                Err(Error::BorrowedQubitNotRestored(qubit, arg_span))
+            }
+        }
         "__quantum__qis__ccx__body" => {
             three_qubit_gate(|ctl0, ctl1, q| sim.ccx(ctl0, ctl1, q), arg, arg_span)
         }
@@ -163,6 +187,28 @@ pub(crate) fn call(
     }
 }
 
+/// Calls an intrinsic with the `@CustomIntrinsic` attribute, which is implemented by the target
+/// and so can only be handled by the backend.
+pub(crate) fn call_custom(
+    intrinsic: &CustomIntrinsic,
+    name_span: PackageSpan,
+    arg: Value,
+    sim: &mut dyn Backend<ResultType = impl Into<val::Result>>,
+) -> Result<Value, Error> {
+    match sim.target_intrinsic(intrinsic, arg) {
+        Some(Ok(value)) => Ok(value),
+        Some(Err(message)) => Err(Error::IntrinsicFail(
+            intrinsic.qir_name.to_string(),
+            message,
+            name_span,
+        )),
+        None => Err(Error::UnknownIntrinsic(
+            intrinsic.qir_name.to_string(),
+            name_span,
+        )),
+    }
+}
+
 fn one_qubit_gate(mut gate: impl FnMut(usize), arg: Value) -> Value {
     gate(arg.unwrap_qubit().0);
     Value::unit()
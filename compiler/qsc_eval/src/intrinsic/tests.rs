@@ -136,6 +136,14 @@ impl Backend for CustomSim {
         self.sim.qubit_is_zero(q)
     }
 
+    fn qubit_borrow(&mut self) -> usize {
+        self.sim.qubit_borrow()
+    }
+
+    fn qubit_return(&mut self, q: usize) -> bool {
+        self.sim.qubit_return(q)
+    }
+
     fn custom_intrinsic(&mut self, name: &str, arg: Value) -> Option<Result<Value, String>> {
         match name {
             "Add1" => Some(Ok(Value::Int(arg.unwrap_int() + 1))),
@@ -500,6 +508,24 @@ fn check_zero_false() {
     );
 }
 
+#[test]
+fn equality_fact() {
+    check_intrinsic_value(
+        "",
+        r#"Microsoft.Quantum.Diagnostics.EqualityFact(2 + 2, 4, "wrong sum")"#,
+        &Value::unit(),
+    );
+}
+
+#[test]
+fn equality_fact_fails_with_values() {
+    check_intrinsic_result(
+        "",
+        r#"Microsoft.Quantum.Diagnostics.EqualityFact([1, 2], [1, 3], "wrong array")"#,
+        &expect!["fact failed: wrong array: expected [1, 3], found [1, 2]"],
+    );
+}
+
 #[test]
 fn length() {
     check_intrinsic_value("", "Length([1, 2, 3])", &Value::Int(3));
@@ -1241,6 +1267,42 @@ fn qubit_release_non_zero_failure() {
     );
 }
 
+#[test]
+fn borrowed_qubit_is_not_zero() {
+    check_intrinsic_result(
+        "",
+        "{borrow q = Qubit(); Microsoft.Quantum.Diagnostics.CheckZero(q)}",
+        &expect!["false"],
+    );
+}
+
+#[test]
+fn borrowed_qubits_restored() {
+    check_intrinsic_output(
+        "",
+        indoc! {"{
+            borrow (q, qs) = (Qubit(), Qubit[2]);
+            CNOT(q, qs[1]);
+            H(qs[0]);
+            H(qs[0]);
+            CNOT(q, qs[1]);
+        }"},
+        &expect![""],
+    );
+}
+
+#[test]
+fn borrowed_qubit_not_restored_failure() {
+    check_intrinsic_output(
+        "",
+        indoc! {"{
+            borrow q = Qubit();
+            X(q);
+        }"},
+        &expect!["Qubit0 returned while not in the state it was borrowed in"],
+    );
+}
+
 #[test]
 fn qubit_not_unique_two_qubit_error() {
     check_intrinsic_output(
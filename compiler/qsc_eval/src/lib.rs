@@ -18,6 +18,7 @@
 mod tests;
 
 pub mod backend;
+mod big_int;
 pub mod debug;
 mod error;
 mod intrinsic;
@@ -29,9 +30,9 @@ pub mod val;
 use crate::val::Value;
 use backend::Backend;
 use debug::{map_fir_package_to_hir, CallStack, Frame};
-use error::PackageSpan;
+pub use error::PackageSpan;
 use miette::Diagnostic;
-use num_bigint::BigInt;
+use num_traits::Zero;
 use output::Receiver;
 use qsc_data_structures::{functors::FunctorApp, index_map::IndexMap, span::Span};
 use qsc_fir::fir::{
@@ -44,12 +45,15 @@ use rand::{rngs::StdRng, SeedableRng};
 use std::ops;
 use std::{
     cell::RefCell,
-    fmt::{self, Display, Formatter},
+    fmt::{self, Display, Formatter, Write},
     iter,
     ops::Neg,
     rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 use thiserror::Error;
+use tracing::info_span;
 
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub enum Error {
@@ -115,6 +119,11 @@ pub enum Error {
     #[diagnostic(code("Qsc.Eval.ReleasedQubitNotZero"))]
     ReleasedQubitNotZero(usize, #[label("Qubit{0}")] PackageSpan),
 
+    #[error("Qubit{0} returned while not in the state it was borrowed in")]
+    #[diagnostic(help("borrowed qubits should be restored to the state they were borrowed in before being returned, since other parts of the program may depend on it"))]
+    #[diagnostic(code("Qsc.Eval.BorrowedQubitNotRestored"))]
+    BorrowedQubitNotRestored(usize, #[label("Qubit{0}")] PackageSpan),
+
     #[error("Result comparison is unsupported for this backend")]
     #[diagnostic(code("Qsc.Eval.ResultComparisonUnsupported"))]
     ResultComparisonUnsupported(#[label("cannot compare to result")] PackageSpan),
@@ -138,6 +147,49 @@ pub enum Error {
     #[error("program failed: {0}")]
     #[diagnostic(code("Qsc.Eval.UserFail"))]
     UserFail(String, #[label("explicit fail")] PackageSpan),
+
+    #[error("fact failed: {0}: expected {2}, found {1}")]
+    #[diagnostic(code("Qsc.Eval.FactFailed"))]
+    FactFailed(
+        String,
+        String,
+        String,
+        #[label("the actual value differs from the expected one")] PackageSpan,
+    ),
+
+    #[error("qubit limit exceeded: cannot allocate more than {0} qubits")]
+    #[diagnostic(help("raise `max_qubits` with `set_limits` to allocate more qubits at once"))]
+    #[diagnostic(code("Qsc.Eval.QubitLimitExceeded"))]
+    QubitLimitExceeded(
+        usize,
+        #[label("allocating this qubit exceeds the limit")] PackageSpan,
+    ),
+
+    #[error("call depth limit exceeded: cannot nest more than {0} calls")]
+    #[diagnostic(help("raise `max_call_depth` with `set_limits` to nest calls more deeply"))]
+    #[diagnostic(code("Qsc.Eval.CallDepthLimitExceeded"))]
+    CallDepthLimitExceeded(usize, #[label("this call exceeds the limit")] PackageSpan),
+
+    #[error("statement limit exceeded: cannot execute more than {0} statements")]
+    #[diagnostic(help("raise `max_statements` with `set_limits` to execute more statements"))]
+    #[diagnostic(code("Qsc.Eval.StatementLimitExceeded"))]
+    StatementLimitExceeded(
+        u64,
+        #[label("executing this statement exceeds the limit")] PackageSpan,
+    ),
+
+    #[error("output limit exceeded: cannot print more than {0} messages and dumps")]
+    #[diagnostic(help("raise `max_outputs` with `set_limits` to print more messages and dumps"))]
+    #[diagnostic(code("Qsc.Eval.OutputLimitExceeded"))]
+    OutputLimitExceeded(u64, #[label("this output exceeds the limit")] PackageSpan),
+
+    #[error("time limit exceeded: cannot run for longer than {0:?}")]
+    #[diagnostic(help("raise `max_seconds` with `set_limits` to run for longer"))]
+    #[diagnostic(code("Qsc.Eval.TimeLimitExceeded"))]
+    TimeLimitExceeded(
+        Duration,
+        #[label("the limit was exceeded while executing this statement")] PackageSpan,
+    ),
 }
 
 impl Error {
@@ -158,12 +210,19 @@ impl Error {
             | Error::QubitsNotSeparable(span)
             | Error::RangeStepZero(span)
             | Error::ReleasedQubitNotZero(_, span)
+            | Error::BorrowedQubitNotRestored(_, span)
             | Error::ResultComparisonUnsupported(span)
             | Error::UnboundName(span)
             | Error::UnknownIntrinsic(_, span)
             | Error::UnsupportedIntrinsicType(_, span)
             | Error::UserFail(_, span)
-            | Error::InvalidArrayLength(_, span) => span,
+            | Error::FactFailed(_, _, _, span)
+            | Error::InvalidArrayLength(_, span)
+            | Error::QubitLimitExceeded(_, span)
+            | Error::CallDepthLimitExceeded(_, span)
+            | Error::StatementLimitExceeded(_, span)
+            | Error::OutputLimitExceeded(_, span)
+            | Error::TimeLimitExceeded(_, span) => span,
         }
     }
 }
@@ -195,9 +254,9 @@ impl Display for Spec {
 /// range.
 #[must_use]
 pub fn exec_graph_section(
-    graph: &Rc<[ExecGraphNode]>,
+    graph: &Arc<[ExecGraphNode]>,
     range: ops::Range<usize>,
-) -> Rc<[ExecGraphNode]> {
+) -> Arc<[ExecGraphNode]> {
     let start: u32 = range
         .start
         .try_into()
@@ -214,21 +273,83 @@ pub fn exec_graph_section(
         .into()
 }
 
+/// The intrinsics that print output, which count towards [`Limits::max_outputs`].
+const OUTPUT_INTRINSICS: [&str; 3] = ["Message", "DumpMachine", "DumpRegister"];
+
+/// The number of statements between reads of the clock for [`Limits::max_duration`].
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+/// The maximum number of nested calls of the default [`Limits`], which is well beyond the depth
+/// that recursive algorithms need.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+
+/// Limits on the resources a program can use, so that runaway programs fail fast instead of
+/// exhausting memory or running forever. A limit that is `None` isn't enforced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum number of qubits allocated at the same time.
+    pub max_qubits: Option<usize>,
+    /// The maximum number of nested calls, which is [`DEFAULT_MAX_CALL_DEPTH`] by default so
+    /// that runaway recursion fails with an error instead of exhausting memory.
+    pub max_call_depth: Option<usize>,
+    /// The maximum number of statements executed in one evaluation.
+    pub max_statements: Option<u64>,
+    /// The maximum number of messages and state dumps printed in one evaluation.
+    pub max_outputs: Option<u64>,
+    /// The maximum time one evaluation runs for. Time can't be measured on
+    /// `wasm32-unknown-unknown`, so this limit must not be set there; the worker that runs the
+    /// evaluation is stopped instead.
+    pub max_duration: Option<Duration>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_qubits: None,
+            max_call_depth: Some(DEFAULT_MAX_CALL_DEPTH),
+            max_statements: None,
+            max_outputs: None,
+            max_duration: None,
+        }
+    }
+}
+
+impl Limits {
+    /// Limits for running code from untrusted sources, such as the submissions to a grading or
+    /// playground service. They are well beyond what exercises and samples need, but stop
+    /// programs that would exhaust memory, recurse or loop forever, or flood the output. They
+    /// don't limit the time, which the host sets where it can be measured.
+    #[must_use]
+    pub fn untrusted() -> Self {
+        Self {
+            max_qubits: Some(24),
+            max_call_depth: Some(1_000),
+            max_statements: Some(10_000_000),
+            max_outputs: Some(1_000),
+            max_duration: None,
+        }
+    }
+}
+
 /// Evaluates the given code with the given context.
 /// # Errors
 /// Returns the first error encountered during execution.
 /// # Panics
 /// On internal error where no result is returned.
+#[allow(clippy::too_many_arguments)]
 pub fn eval(
     package: PackageId,
     seed: Option<u64>,
-    exec_graph: Rc<[ExecGraphNode]>,
+    limits: Limits,
+    exec_graph: Arc<[ExecGraphNode]>,
     globals: &impl PackageStoreLookup,
     env: &mut Env,
     sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
     receiver: &mut impl Receiver,
 ) -> Result<Value, (Error, Vec<Frame>)> {
+    let _span = info_span!("eval").entered();
     let mut state = State::new(package, exec_graph, seed);
+    state.set_limits(limits);
     let res = state.eval(globals, env, sim, receiver, &[], StepAction::Continue)?;
     let StepResult::Return(value) = res else {
         panic!("eval should always return a value");
@@ -274,7 +395,7 @@ impl AsIndex for i64 {
 
 #[derive(Debug, Clone)]
 struct Variable {
-    name: Rc<str>,
+    name: Arc<str>,
     value: Value,
     span: Span,
 }
@@ -282,7 +403,7 @@ struct Variable {
 #[derive(Debug, Clone)]
 pub struct VariableInfo {
     pub value: Value,
-    pub name: Rc<str>,
+    pub name: Arc<str>,
     pub type_name: String,
     pub span: Span,
 }
@@ -317,24 +438,35 @@ impl Range {
     }
 }
 
-pub struct Env(Vec<Scope>);
+#[derive(Clone)]
+pub struct Env {
+    scopes: Vec<Scope>,
+    /// The number of qubits the program currently has allocated.
+    qubits: usize,
+}
 
 impl Default for Env {
     #[must_use]
     fn default() -> Self {
-        // Always create a global scope for top-level statements.
-        Self(vec![Scope::default()])
+        Self {
+            // Always create a global scope for top-level statements.
+            scopes: vec![Scope::default()],
+            qubits: 0,
+        }
     }
 }
 
 impl Env {
     #[must_use]
     fn get(&self, id: LocalVarId) -> Option<&Variable> {
-        self.0.iter().rev().find_map(|scope| scope.bindings.get(id))
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.bindings.get(id))
     }
 
     fn get_mut(&mut self, id: LocalVarId) -> Option<&mut Variable> {
-        self.0
+        self.scopes
             .iter_mut()
             .rev()
             .find_map(|scope| scope.bindings.get_mut(id))
@@ -345,22 +477,39 @@ impl Env {
             frame_id,
             ..Default::default()
         };
-        self.0.push(scope);
+        self.scopes.push(scope);
     }
 
     fn leave_scope(&mut self) {
         // Only pop the scope if there is more than one scope in the stack,
         // because the global/top-level scope cannot be exited.
-        if self.0.len() > 1 {
-            self.0
+        if self.scopes.len() > 1 {
+            self.scopes
                 .pop()
                 .expect("scope should have more than one entry.");
         }
     }
 
+    /// Whether any variable bound in `earlier`, a copy of this environment, has since been
+    /// given a different value.
+    fn updates_variables_of(&self, earlier: &Env) -> bool {
+        earlier
+            .scopes
+            .iter()
+            .zip(&self.scopes)
+            .any(|(earlier, scope)| {
+                earlier.bindings.iter().any(|(id, var)| {
+                    scope
+                        .bindings
+                        .get(id)
+                        .is_some_and(|current| current.value != var.value)
+                })
+            })
+    }
+
     #[must_use]
     pub fn get_variables_in_top_frame(&self) -> Vec<VariableInfo> {
-        if let Some(scope) = self.0.last() {
+        if let Some(scope) = self.scopes.last() {
             self.get_variables_in_frame(scope.frame_id)
         } else {
             vec![]
@@ -370,7 +519,7 @@ impl Env {
     #[must_use]
     pub fn get_variables_in_frame(&self, frame_id: usize) -> Vec<VariableInfo> {
         let candidate_scopes: Vec<_> = self
-            .0
+            .scopes
             .iter()
             .filter(|scope| scope.frame_id == frame_id)
             .map(|scope| scope.bindings.iter())
@@ -393,14 +542,14 @@ impl Env {
     }
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 struct Scope {
     bindings: IndexMap<LocalVarId, Variable>,
     frame_id: usize,
 }
 
 pub struct State {
-    exec_graph_stack: Vec<Rc<[ExecGraphNode]>>,
+    exec_graph_stack: Vec<Arc<[ExecGraphNode]>>,
     idx: u32,
     idx_stack: Vec<u32>,
     val_register: Option<Value>,
@@ -409,13 +558,49 @@ pub struct State {
     call_stack: CallStack,
     current_span: Span,
     rng: RefCell<StdRng>,
+    pending_condition: Option<Condition>,
+    branches: Vec<Branch>,
+    limits: Limits,
+    /// The number of statements executed so far.
+    statements: u64,
+    /// The number of messages and state dumps printed so far.
+    outputs: u64,
+    /// The time by which the evaluation has to finish, when its duration is limited.
+    deadline: Option<Instant>,
+}
+
+/// A comparison of a measurement result against a value, deferred until the `if` that uses it.
+struct Condition {
+    result: usize,
+    value: bool,
+    span: PackageSpan,
+}
+
+/// An `if` on a measurement result whose branches are traced one after the other by a
+/// backend that supports conditionals. Branches can't update variables from outside the
+/// `if`, so the classical state after it is the same on either path.
+struct Branch {
+    condition: Condition,
+    /// The depth of the execution graph stack that the branch runs in.
+    depth: usize,
+    /// The index at which the branch being traced ends.
+    end: u32,
+    /// The start and end of the else-branch, if it still needs to be traced.
+    else_range: Option<(u32, u32)>,
+    /// Whether the true branch is being traced.
+    tracing_true: bool,
+    /// The index to continue from when there is no else-branch.
+    resume: u32,
+    /// The value stack and environment from before the `if`.
+    val_stack: Vec<Vec<Value>>,
+    env: Env,
 }
 
 impl State {
     #[must_use]
     pub fn new(
         package: PackageId,
-        exec_graph: Rc<[ExecGraphNode]>,
+        exec_graph: Arc<[ExecGraphNode]>,
         classical_seed: Option<u64>,
     ) -> Self {
         let rng = match classical_seed {
@@ -432,12 +617,25 @@ impl State {
             call_stack: CallStack::default(),
             current_span: Span::default(),
             rng,
+            pending_condition: None,
+            branches: Vec::new(),
+            limits: Limits::default(),
+            statements: 0,
+            outputs: 0,
+            deadline: None,
         }
     }
 
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+        self.deadline = limits
+            .max_duration
+            .map(|duration| Instant::now() + duration);
+    }
+
     fn push_frame(
         &mut self,
-        exec_graph: Rc<[ExecGraphNode]>,
+        exec_graph: Arc<[ExecGraphNode]>,
         id: StoreItemId,
         functor: FunctorApp,
     ) {
@@ -471,6 +669,58 @@ impl State {
         self.val_register.take().expect("value should be present")
     }
 
+    fn check_statement_limit(&self) -> Result<(), Error> {
+        match self.limits.max_statements {
+            Some(max) if self.statements > max => Err(Error::StatementLimitExceeded(
+                max,
+                self.to_global_span(self.current_span),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_time_limit(&self) -> Result<(), Error> {
+        // Reading the clock is slow compared to a statement, so it is only read once in a while.
+        if self.statements % TIME_CHECK_INTERVAL != 0 {
+            return Ok(());
+        }
+        match (self.deadline, self.limits.max_duration) {
+            (Some(deadline), Some(max)) if Instant::now() > deadline => Err(
+                Error::TimeLimitExceeded(max, self.to_global_span(self.current_span)),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_output_limit(&self) -> Result<(), Error> {
+        match self.limits.max_outputs {
+            Some(max) if self.outputs > max => Err(Error::OutputLimitExceeded(
+                max,
+                self.to_global_span(self.current_span),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_qubit_limit(&self, env: &Env, span: Span) -> Result<(), Error> {
+        match self.limits.max_qubits {
+            Some(max) if env.qubits >= max => {
+                Err(Error::QubitLimitExceeded(max, self.to_global_span(span)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_call_depth_limit(&self, span: Span) -> Result<(), Error> {
+        match self.limits.max_call_depth {
+            Some(max) if self.call_stack.len() >= max => Err(Error::CallDepthLimitExceeded(
+                max,
+                self.to_global_span(span),
+            )),
+            _ => Ok(()),
+        }
+    }
+
     fn set_val_register(&mut self, val: Value) {
         self.val_register = Some(val);
     }
@@ -510,8 +760,15 @@ impl State {
         frames
     }
 
+    /// The frames of the call stack, each with the span of the call that made it rather than the
+    /// span that execution is at in it like [`State::get_stack_frames`].
+    fn get_call_sites(&self) -> Vec<Frame> {
+        self.call_stack.clone().into_frames()
+    }
+
     /// # Errors
-    /// Returns the first error encountered during execution.
+    /// Returns the first error encountered during execution, along with the call stack at the
+    /// point of the error.
     /// # Panics
     /// When returning a value in the middle of execution.
     pub fn eval(
@@ -526,6 +783,13 @@ impl State {
         let current_frame = self.call_stack.len();
 
         while !self.exec_graph_stack.is_empty() {
+            if self
+                .update_branches(env, sim)
+                .map_err(|e| (e, self.get_call_sites()))?
+            {
+                continue;
+            }
+
             let exec_graph = self
                 .exec_graph_stack
                 .last()
@@ -539,12 +803,16 @@ impl State {
                 Some(ExecGraphNode::Expr(expr)) => {
                     self.idx += 1;
                     self.eval_expr(env, sim, globals, out, *expr)
-                        .map_err(|e| (e, self.get_stack_frames()))?;
+                        .map_err(|e| (e, self.get_call_sites()))?;
                     continue;
                 }
                 Some(ExecGraphNode::Stmt(stmt)) => {
                     self.idx += 1;
                     self.current_span = globals.get_stmt((self.package, *stmt).into()).span;
+                    self.statements += 1;
+                    self.check_statement_limit()
+                        .and_then(|()| self.check_time_limit())
+                        .map_err(|e| (e, self.get_call_sites()))?;
 
                     if let Some(bp) = breakpoints.iter().find(|&bp| *bp == *stmt) {
                         StepResult::BreakpointHit(*bp)
@@ -624,6 +892,106 @@ impl State {
         Ok(StepResult::Return(self.get_result()))
     }
 
+    /// Starts or ends tracing a branch on a measurement result if execution has reached
+    /// one of its boundaries. Returns `true` if it did.
+    fn update_branches(&mut self, env: &mut Env, sim: &mut impl Backend) -> Result<bool, Error> {
+        if let Some(condition) = self.pending_condition.take() {
+            self.begin_branch(env, sim, condition)?;
+            return Ok(true);
+        }
+        if let Some(branch) = self.branches.last() {
+            let depth = self.exec_graph_stack.len();
+            if depth < branch.depth || (depth == branch.depth && self.idx == branch.end) {
+                self.end_branch(env, sim)?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Starts tracing the true branch of the `if` that uses the given condition.
+    fn begin_branch(
+        &mut self,
+        env: &Env,
+        sim: &mut impl Backend,
+        condition: Condition,
+    ) -> Result<(), Error> {
+        let exec_graph = self
+            .exec_graph_stack
+            .last()
+            .expect("should have at least one stack frame");
+        let Some(ExecGraphNode::JumpIfNot(else_idx)) = exec_graph.get(self.idx as usize) else {
+            // Only the condition of an if-expression can be traced without knowing the result.
+            return Err(Error::ResultComparisonUnsupported(condition.span));
+        };
+        let else_idx = *else_idx;
+        let (end, else_range) = match (
+            exec_graph.get(else_idx as usize - 1),
+            exec_graph.get(else_idx as usize),
+        ) {
+            // The true branch of an if-else ends with a jump past the else-branch.
+            (Some(ExecGraphNode::Jump(end)), _) if *end >= else_idx => {
+                (else_idx - 1, Some((else_idx, *end)))
+            }
+            // A jump back to the condition belongs to a loop.
+            (Some(ExecGraphNode::Jump(_)), _) => {
+                return Err(Error::ResultComparisonUnsupported(condition.span));
+            }
+            (_, Some(ExecGraphNode::Unit)) => (else_idx, None),
+            _ => return Err(Error::ResultComparisonUnsupported(condition.span)),
+        };
+
+        sim.begin_conditional(condition.result, condition.value);
+        self.branches.push(Branch {
+            condition,
+            depth: self.exec_graph_stack.len(),
+            end,
+            else_range,
+            tracing_true: true,
+            resume: else_idx,
+            val_stack: self.val_stack.clone(),
+            env: env.clone(),
+        });
+        self.val_register = None;
+        self.idx += 1;
+        Ok(())
+    }
+
+    /// Finishes tracing the current branch, moving on to the else-branch if there is one.
+    fn end_branch(&mut self, env: &mut Env, sim: &mut impl Backend) -> Result<(), Error> {
+        let branch = self.branches.last_mut().expect("branch should be present");
+        let returns_unit = !matches!(&self.val_register, Some(val) if *val != Value::unit());
+        if self.exec_graph_stack.len() < branch.depth
+            || !returns_unit
+            || env.updates_variables_of(&branch.env)
+        {
+            // Branches that return from the callable, produce a value or update variables
+            // can't be traced, since what follows depends on which one runs.
+            return Err(Error::ResultComparisonUnsupported(branch.condition.span));
+        }
+        sim.end_conditional();
+        self.val_register = None;
+
+        if branch.tracing_true {
+            if let Some((start, end)) = branch.else_range.take() {
+                self.val_stack = branch.val_stack.clone();
+                *env = branch.env.clone();
+                sim.begin_conditional(branch.condition.result, !branch.condition.value);
+                branch.tracing_true = false;
+                branch.end = end;
+                self.idx = start;
+                return Ok(());
+            }
+            let branch = self.branches.pop().expect("branch should be present");
+            self.val_stack = branch.val_stack;
+            *env = branch.env;
+            self.idx = branch.resume;
+        } else {
+            self.branches.pop();
+        }
+        Ok(())
+    }
+
     pub fn get_result(&mut self) -> Value {
         // Some executions don't have any statements to execute,
         // such as a fragment that has only item definitions.
@@ -670,21 +1038,19 @@ impl State {
                 self.eval_assign(env, globals, *record)?;
             }
             ExprKind::AssignIndex(lhs, mid, _) => {
+                // The array of the variable is copied only if it is shared with another value, so
+                // repeated updates of the same variable only copy it once.
                 let mid_span = globals.get_expr((self.package, *mid).into()).span;
-                let (_, is_unique) =
-                    is_updatable_in_place(env, globals.get_expr((self.package, *lhs).into()));
-                if is_unique {
-                    self.eval_update_index_in_place(env, globals, *lhs, mid_span)?;
-                    return Ok(());
-                }
-                self.push_val();
-                self.eval_expr(env, sim, globals, out, *lhs)?;
-                self.eval_update_index(mid_span)?;
-                self.eval_assign(env, globals, *lhs)?;
+                self.eval_update_index_in_place(env, globals, *lhs, mid_span)?;
             }
             ExprKind::BinOp(op, _, rhs) => {
                 let rhs_span = globals.get_expr((self.package, *rhs).into()).span;
-                self.eval_binop(*op, rhs_span)?;
+                if !(matches!(op, BinOp::Eq | BinOp::Neq)
+                    && sim.supports_conditionals()
+                    && self.defer_result_comparison(*op, rhs_span))
+                {
+                    self.eval_binop(*op, rhs_span)?;
+                }
             }
             ExprKind::Block(..) => panic!("block expr should be handled by control flow"),
             ExprKind::Call(callee_expr, args_expr) => {
@@ -741,22 +1107,32 @@ impl State {
 
     fn collect_string(&mut self, components: &[StringComponent]) {
         if let [StringComponent::Lit(str)] = components {
-            self.set_val_register(Value::String(Rc::clone(str)));
+            self.set_val_register(Value::String(str.as_ref().into()));
             return;
         }
 
+        // The values of the interpolated expressions are on the stack in order, so the string is
+        // written front to back into a single buffer instead of being prepended to.
+        let expr_count = components
+            .iter()
+            .filter(|component| matches!(component, StringComponent::Expr(..)))
+            .count();
+        let frame = self
+            .val_stack
+            .last_mut()
+            .expect("should have at least one value frame");
+        let mut vals = frame.drain(frame.len() - expr_count..);
         let mut string = String::new();
-        for component in components.iter().rev() {
+        for component in components {
             match component {
                 StringComponent::Expr(..) => {
-                    let expr_str = format!("{}", self.pop_val());
-                    string.insert_str(0, &expr_str);
-                }
-                StringComponent::Lit(lit) => {
-                    string.insert_str(0, lit);
+                    let val = vals.next().expect("value should be present");
+                    write!(string, "{val}").expect("writing to a string should succeed");
                 }
+                StringComponent::Lit(lit) => string.push_str(lit),
             }
         }
+        drop(vals);
         self.set_val_register(Value::String(Rc::from(string)));
     }
 
@@ -853,6 +1229,32 @@ impl State {
         Ok(())
     }
 
+    /// Defers a comparison of a result id against a result value to the `if` that uses it, so
+    /// that the backend can trace both of its branches. Returns `false` if the operands are
+    /// anything else.
+    fn defer_result_comparison(&mut self, op: BinOp, span: Span) -> bool {
+        let lhs = self.val_stack.last().and_then(|vals| vals.last());
+        let (result, value) = match (lhs, &self.val_register) {
+            (
+                Some(Value::Result(val::Result::Id(result))),
+                Some(Value::Result(val::Result::Val(value))),
+            )
+            | (
+                Some(Value::Result(val::Result::Val(value))),
+                Some(Value::Result(val::Result::Id(result))),
+            ) => (*result, *value),
+            _ => return false,
+        };
+        self.pop_val();
+        self.set_val_register(Value::Bool(true));
+        self.pending_condition = Some(Condition {
+            result,
+            value: value == (op == BinOp::Eq),
+            span: self.to_global_span(span),
+        });
+        true
+    }
+
     fn eval_binop_simple(&mut self, binop_func: impl FnOnce(Value, Value) -> Value) {
         let rhs_val = self.take_val_register();
         let lhs_val = self.pop_val();
@@ -903,29 +1305,55 @@ impl State {
         let spec = spec_from_functor_app(functor);
         match &callee.implementation {
             CallableImpl::Intrinsic => {
+                let name = &callee.name.name;
+                let is_allocation = matches!(
+                    name.as_ref(),
+                    "__quantum__rt__qubit_allocate" | "__quantum__rt__qubit_borrow"
+                );
+                if is_allocation {
+                    self.check_qubit_limit(env, callable_span)?;
+                }
+                if OUTPUT_INTRINSICS.contains(&name.as_ref()) {
+                    self.outputs += 1;
+                    self.check_output_limit()?;
+                }
                 self.push_frame(Vec::new().into(), callee_id, functor);
+                if sim.records_call_sites() {
+                    self.set_call_site(sim, globals);
+                }
 
-                let name = &callee.name.name;
-                let val = intrinsic::call(
-                    name,
-                    callee_span,
-                    arg,
-                    arg_span,
-                    sim,
-                    &mut self.rng.borrow_mut(),
-                    out,
-                )?;
+                let val = match globals.get_item(callee_id).custom_intrinsic() {
+                    Some(intrinsic) => intrinsic::call_custom(intrinsic, callee_span, arg, sim)?,
+                    None => intrinsic::call(
+                        name,
+                        callee_span,
+                        arg,
+                        arg_span,
+                        sim,
+                        &mut self.rng.borrow_mut(),
+                        out,
+                    )?,
+                };
                 if val == Value::unit() && callee.output != Ty::UNIT {
                     return Err(Error::UnsupportedIntrinsicType(
                         callee.name.name.to_string(),
                         callee_span,
                     ));
                 }
+                if is_allocation {
+                    env.qubits += 1;
+                } else if matches!(
+                    name.as_ref(),
+                    "__quantum__rt__qubit_release" | "__quantum__rt__qubit_return"
+                ) {
+                    env.qubits = env.qubits.saturating_sub(1);
+                }
                 self.set_val_register(val);
                 self.leave_frame();
                 Ok(())
             }
             CallableImpl::Spec(specialized_implementation) => {
+                self.check_call_depth_limit(callable_span)?;
                 let spec_decl = match spec {
                     Spec::Body => Some(&specialized_implementation.body),
                     Spec::Adj => specialized_implementation.adj.as_ref(),
@@ -1018,9 +1446,9 @@ impl State {
         let index = self.pop_val();
         let span = self.to_global_span(span);
         match index {
-            Value::Int(index) => self.eval_update_index_single(&values, index, update, span),
+            Value::Int(index) => self.eval_update_index_single(values, index, update, span),
             Value::Range(inner) => self.eval_update_index_range(
-                &values,
+                values,
                 inner.start,
                 inner.step,
                 inner.end,
@@ -1031,9 +1459,11 @@ impl State {
         }
     }
 
+    // The updates copy the array only if it is shared with another value, so that a chain of
+    // updates to a temporary array, such as `a w/ 0 <- x w/ 1 <- y`, copies it at most once.
     fn eval_update_index_single(
         &mut self,
-        values: &[Value],
+        mut values: Rc<Vec<Value>>,
         index: i64,
         update: Value,
         span: PackageSpan,
@@ -1042,39 +1472,38 @@ impl State {
             return Err(Error::InvalidNegativeInt(index, span));
         }
         let i = index.as_index(span)?;
-        let mut values = values.to_vec();
-        match values.get_mut(i) {
+        match Rc::make_mut(&mut values).get_mut(i) {
             Some(value) => {
                 *value = update;
             }
             None => return Err(Error::IndexOutOfRange(index, span)),
         }
-        self.set_val_register(Value::Array(values.into()));
+        self.set_val_register(Value::Array(values));
         Ok(())
     }
 
     fn eval_update_index_range(
         &mut self,
-        values: &[Value],
+        mut values: Rc<Vec<Value>>,
         start: Option<i64>,
         step: i64,
         end: Option<i64>,
         update: Value,
         span: PackageSpan,
     ) -> Result<(), Error> {
-        let range = make_range(values, start, step, end, span)?;
-        let mut values = values.to_vec();
+        let range = make_range(&values, start, step, end, span)?;
         let update = update.unwrap_array();
+        let array = Rc::make_mut(&mut values);
         for (idx, update) in range.into_iter().zip(update.iter()) {
             let i = idx.as_index(span)?;
-            match values.get_mut(i) {
+            match array.get_mut(i) {
                 Some(value) => {
                     *value = update.clone();
                 }
                 None => return Err(Error::IndexOutOfRange(idx, span)),
             }
         }
-        self.set_val_register(Value::Array(values.into()));
+        self.set_val_register(Value::Array(values));
         Ok(())
     }
 
@@ -1176,7 +1605,7 @@ impl State {
         let pat = globals.get_pat((self.package, pat).into());
         match &pat.kind {
             PatKind::Bind(variable) => {
-                let scope = env.0.last_mut().expect("binding should have a scope");
+                let scope = env.scopes.last_mut().expect("binding should have a scope");
                 scope.bindings.insert(
                     variable.id,
                     Variable {
@@ -1329,6 +1758,27 @@ impl State {
         }
     }
 
+    /// Tells the backend which call the intrinsic in the innermost frame comes from: the innermost
+    /// call made from outside the package of the intrinsic, or the outermost call if there is none.
+    fn set_call_site(&self, sim: &mut impl Backend, globals: &impl PackageStoreLookup) {
+        let frames = self.call_stack.frames();
+        let Some(intrinsic) = frames.last() else {
+            return;
+        };
+        let frame = frames
+            .iter()
+            .rev()
+            .find(|frame| frame.caller != intrinsic.id.package)
+            .unwrap_or(&frames[0]);
+        if let Some(Global::Callable(callable)) = globals.get_global(frame.id) {
+            let span = PackageSpan {
+                package: map_fir_package_to_hir(frame.caller),
+                span: frame.span,
+            };
+            sim.set_call_site(span, callable.name.name.as_ref().into());
+        }
+    }
+
     fn to_global_span(&self, span: Span) -> PackageSpan {
         PackageSpan {
             package: map_fir_package_to_hir(self.package),
@@ -1498,7 +1948,7 @@ fn eval_binop_add(lhs_val: Value, rhs_val: Value) -> Value {
         }
         Value::BigInt(val) => {
             let rhs = rhs_val.unwrap_big_int();
-            Value::BigInt(val + rhs)
+            Value::BigInt(big_int::add(val, &rhs))
         }
         Value::Double(val) => {
             let rhs = rhs_val.unwrap_double();
@@ -1534,10 +1984,10 @@ fn eval_binop_div(lhs_val: Value, rhs_val: Value, rhs_span: PackageSpan) -> Resu
     match lhs_val {
         Value::BigInt(val) => {
             let rhs = rhs_val.unwrap_big_int();
-            if rhs == BigInt::from(0) {
+            if rhs.is_zero() {
                 Err(Error::DivZero(rhs_span))
             } else {
-                Ok(Value::BigInt(val / rhs))
+                Ok(Value::BigInt(big_int::div(val, &rhs)))
             }
         }
         Value::Int(val) => {
@@ -1567,7 +2017,7 @@ fn eval_binop_exp(lhs_val: Value, rhs_val: Value, rhs_span: PackageSpan) -> Resu
                     Ok(v) => Ok(v),
                     Err(_) => Err(Error::IntTooLarge(rhs_val, rhs_span)),
                 }?;
-                Ok(Value::BigInt(val.pow(rhs_val)))
+                Ok(Value::BigInt(big_int::pow(val, rhs_val)))
             }
         }
         Value::Double(val) => Ok(Value::Double(val.powf(rhs_val.unwrap_double()))),
@@ -1665,10 +2115,10 @@ fn eval_binop_mod(lhs_val: Value, rhs_val: Value, rhs_span: PackageSpan) -> Resu
     match lhs_val {
         Value::BigInt(val) => {
             let rhs = rhs_val.unwrap_big_int();
-            if rhs == BigInt::from(0) {
+            if rhs.is_zero() {
                 Err(Error::DivZero(rhs_span))
             } else {
-                Ok(Value::BigInt(val % rhs))
+                Ok(Value::BigInt(big_int::rem(val, &rhs)))
             }
         }
         Value::Int(val) => {
@@ -1695,7 +2145,7 @@ fn eval_binop_mul(lhs_val: Value, rhs_val: Value) -> Value {
     match lhs_val {
         Value::BigInt(val) => {
             let rhs = rhs_val.unwrap_big_int();
-            Value::BigInt(val * rhs)
+            Value::BigInt(big_int::mul(val, &rhs))
         }
         Value::Int(val) => {
             let rhs = rhs_val.unwrap_int();
@@ -1787,7 +2237,7 @@ fn eval_binop_sub(lhs_val: Value, rhs_val: Value) -> Value {
     match lhs_val {
         Value::BigInt(val) => {
             let rhs = rhs_val.unwrap_big_int();
-            Value::BigInt(val - rhs)
+            Value::BigInt(big_int::sub(val, &rhs))
         }
         Value::Double(val) => {
             let rhs = rhs_val.unwrap_double();
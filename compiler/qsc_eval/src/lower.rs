@@ -10,7 +10,7 @@ use qsc_fir::{
 };
 use qsc_hir::hir::{self, SpecBody, SpecGen};
 use std::iter::once;
-use std::{clone::Clone, rc::Rc};
+use std::{clone::Clone, sync::Arc};
 
 pub struct Lowerer {
     nodes: IndexMap<hir::NodeId, fir::NodeId>,
@@ -163,7 +163,7 @@ impl Lowerer {
             id: lower_local_item_id(item.id),
             span: item.span,
             parent: item.parent.map(lower_local_item_id),
-            doc: Rc::clone(&item.doc),
+            doc: item.doc.as_ref().into(),
             attrs,
             visibility: lower_visibility(item.visibility),
             kind,
@@ -620,7 +620,7 @@ impl Lowerer {
                 self.exec_graph.push(ExecGraphNode::Store);
                 fir::StringComponent::Expr(expr)
             }
-            hir::StringComponent::Lit(str) => fir::StringComponent::Lit(Rc::clone(str)),
+            hir::StringComponent::Lit(str) => fir::StringComponent::Lit(str.as_ref().into()),
         }
     }
 
@@ -677,13 +677,13 @@ impl Lowerer {
         fir::Ident {
             id: self.lower_local_id(ident.id),
             span: ident.span,
-            name: ident.name.clone(),
+            name: ident.name.as_ref().into(),
         }
     }
 
     fn lower_udt(&mut self, udt: &qsc_hir::ty::Udt) -> qsc_fir::ty::Udt {
         let span = udt.span;
-        let name = udt.name.clone();
+        let name = udt.name.as_ref().into();
         let definition = self.lower_udt_defn(&udt.definition);
         qsc_fir::ty::Udt {
             span,
@@ -747,7 +747,7 @@ impl Lowerer {
     fn lower_udt_field(&mut self, field: &qsc_hir::ty::UdtField) -> qsc_fir::ty::UdtField {
         qsc_fir::ty::UdtField {
             ty: self.lower_ty(&field.ty),
-            name: field.name.clone(),
+            name: field.name.as_deref().map(Arc::from),
             name_span: field.name_span,
         }
     }
@@ -758,7 +758,23 @@ fn lower_generics(generics: &[qsc_hir::ty::GenericParam]) -> Vec<qsc_fir::ty::Ge
 }
 
 fn lower_attrs(attrs: &[hir::Attr]) -> Vec<fir::Attr> {
-    attrs.iter().map(|_| fir::Attr::EntryPoint).collect()
+    attrs
+        .iter()
+        .map(|attr| match attr {
+            hir::Attr::CustomIntrinsic(intrinsic) => {
+                fir::Attr::CustomIntrinsic(fir::CustomIntrinsic {
+                    qir_name: intrinsic.qir_name.as_ref().into(),
+                    glyph: intrinsic.glyph.as_deref().map(Arc::from),
+                    unrestricted: intrinsic.unrestricted,
+                })
+            }
+            hir::Attr::TargetProfile(profile) => fir::Attr::TargetProfile(match profile {
+                hir::TargetProfile::Base => fir::TargetProfile::Base,
+                hir::TargetProfile::Unrestricted => fir::TargetProfile::Unrestricted,
+            }),
+            _ => fir::Attr::EntryPoint,
+        })
+        .collect()
 }
 
 fn lower_functors(functors: qsc_hir::ty::FunctorSetValue) -> qsc_fir::ty::FunctorSetValue {
@@ -4,7 +4,7 @@
 #![allow(clippy::needless_raw_string_hashes)]
 
 use core::panic;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::{
     backend::{Backend, SparseSim},
@@ -26,7 +26,7 @@ use qsc_passes::{run_core_passes, run_default_passes, PackageType};
 /// # Errors
 /// Returns the first error encountered during execution.
 pub(super) fn eval_graph(
-    graph: Rc<[ExecGraphNode]>,
+    graph: Arc<[ExecGraphNode]>,
     sim: &mut impl Backend<ResultType = impl Into<val::Result>>,
     globals: &impl PackageStoreLookup,
     package: PackageId,
@@ -448,8 +448,8 @@ fn block_qubit_use_array_invalid_count_expr() {
                 [
                     Frame {
                         span: Span {
-                            lo: 1568,
-                            hi: 1625,
+                            lo: 6,
+                            hi: 24,
                         },
                         id: StoreItemId {
                             package: PackageId(
@@ -2146,6 +2146,28 @@ fn update_array_index_expr() {
     );
 }
 
+#[test]
+fn update_array_shared_with_var() {
+    check_expr(
+        "",
+        indoc! {"{
+            let xs = [1, 2, 3];
+            let ys = xs w/ 0 <- 10;
+            (xs, ys)
+        }"},
+        &expect!["([1, 2, 3], [10, 2, 3])"],
+    );
+}
+
+#[test]
+fn update_array_chained() {
+    check_expr(
+        "",
+        "[1, 2, 3] w/ 0 <- 10 w/ 2 <- 30",
+        &expect!["[10, 2, 30]"],
+    );
+}
+
 #[test]
 fn update_udt_known_field_name() {
     check_expr(
@@ -2412,6 +2434,35 @@ fn assignupdate_on_copy_should_work() {
     );
 }
 
+#[test]
+fn assignupdate_range_on_copy_should_work() {
+    check_expr(
+        "",
+        indoc! {"{
+            let x = [1, 2, 3];
+            mutable y = x;
+            set y w/= 0..1 <- [10, 11];
+            (x, y)
+        }"},
+        &expect!["([1, 2, 3], [10, 11, 3])"],
+    );
+}
+
+#[test]
+fn assignupdate_from_own_elements() {
+    check_expr(
+        "",
+        indoc! {"{
+            mutable x = [1, 2, 3];
+            let y = x;
+            set x w/= 0 <- x[2];
+            set x w/= 2 <- y[0];
+            (x, y)
+        }"},
+        &expect!["([3, 2, 1], [1, 2, 3])"],
+    );
+}
+
 #[test]
 fn assignupdate_out_of_range_err() {
     check_expr(
@@ -3065,8 +3116,8 @@ fn call_adjoint_expr() {
                 [
                     Frame {
                         span: Span {
-                            lo: 185,
-                            hi: 214,
+                            lo: 0,
+                            hi: 18,
                         },
                         id: StoreItemId {
                             package: PackageId(
@@ -3129,8 +3180,8 @@ fn call_adjoint_adjoint_expr() {
                 [
                     Frame {
                         span: Span {
-                            lo: 119,
-                            hi: 145,
+                            lo: 0,
+                            hi: 26,
                         },
                         id: StoreItemId {
                             package: PackageId(
@@ -3188,8 +3239,8 @@ fn call_adjoint_self_expr() {
                 [
                     Frame {
                         span: Span {
-                            lo: 111,
-                            hi: 137,
+                            lo: 0,
+                            hi: 18,
                         },
                         id: StoreItemId {
                             package: PackageId(
@@ -3685,29 +3736,29 @@ fn partial_eval_simple_stmt() {
     check_partial_eval_stmt(
         "",
         "{3; {4} 5;}",
-        &[5011_u32.into()],
+        &[5034_u32.into()],
         &expect![[r#"
             Package:
-                Entry Expression: 25960
+                Entry Expression: 26031
                 Items:
                 Blocks:
-                    Block 2097 [0-11] [Type Unit]:
-                        5009
-                        5010
-                        5012
-                    Block 2098 [4-7] [Type Int]:
-                        5011
+                    Block 2106 [0-11] [Type Unit]:
+                        5032
+                        5033
+                        5035
+                    Block 2107 [4-7] [Type Int]:
+                        5034
                 Stmts:
-                    Stmt 5009 [1-3]: Semi: 25961
-                    Stmt 5010 [4-7]: Expr: 25962
-                    Stmt 5011 [5-6]: Expr: 25963
-                    Stmt 5012 [8-10]: Semi: 25964
+                    Stmt 5032 [1-3]: Semi: 26032
+                    Stmt 5033 [4-7]: Expr: 26033
+                    Stmt 5034 [5-6]: Expr: 26034
+                    Stmt 5035 [8-10]: Semi: 26035
                 Exprs:
-                    Expr 25960 [0-11] [Type Unit]: Expr Block: 2097
-                    Expr 25961 [1-2] [Type Int]: Lit: Int(3)
-                    Expr 25962 [4-7] [Type Int]: Expr Block: 2098
-                    Expr 25963 [5-6] [Type Int]: Lit: Int(4)
-                    Expr 25964 [8-9] [Type Int]: Lit: Int(5)
+                    Expr 26031 [0-11] [Type Unit]: Expr Block: 2106
+                    Expr 26032 [1-2] [Type Int]: Lit: Int(3)
+                    Expr 26033 [4-7] [Type Int]: Expr Block: 2107
+                    Expr 26034 [5-6] [Type Int]: Lit: Int(4)
+                    Expr 26035 [8-9] [Type Int]: Lit: Int(5)
                 Pats:"#]],
         &expect!["4"],
     );
@@ -3718,33 +3769,33 @@ fn partial_eval_stmt_with_bound_variable() {
     check_partial_eval_stmt(
         "",
         "{let x = 3; {x} ()}",
-        &[5009_u32.into(), 5011_u32.into()],
+        &[5032_u32.into(), 5034_u32.into()],
         &expect![[r#"
             Package:
-                Entry Expression: 25960
+                Entry Expression: 26031
                 Items:
                 Blocks:
-                    Block 2097 [0-19] [Type Unit]:
-                        5009
-                        5010
-                        5012
-                    Block 2098 [12-15] [Type Int]:
-                        5011
+                    Block 2106 [0-19] [Type Unit]:
+                        5032
+                        5033
+                        5035
+                    Block 2107 [12-15] [Type Int]:
+                        5034
                 Stmts:
-                    Stmt 5009 [1-11]: Local (Immutable):
-                        2896
-                        25961
-                    Stmt 5010 [12-15]: Expr: 25962
-                    Stmt 5011 [13-14]: Expr: 25963
-                    Stmt 5012 [16-18]: Expr: 25964
+                    Stmt 5032 [1-11]: Local (Immutable):
+                        2917
+                        26032
+                    Stmt 5033 [12-15]: Expr: 26033
+                    Stmt 5034 [13-14]: Expr: 26034
+                    Stmt 5035 [16-18]: Expr: 26035
                 Exprs:
-                    Expr 25960 [0-19] [Type Unit]: Expr Block: 2097
-                    Expr 25961 [9-10] [Type Int]: Lit: Int(3)
-                    Expr 25962 [12-15] [Type Int]: Expr Block: 2098
-                    Expr 25963 [13-14] [Type Int]: Var: Local 22
-                    Expr 25964 [16-18] [Type Unit]: Unit
+                    Expr 26031 [0-19] [Type Unit]: Expr Block: 2106
+                    Expr 26032 [9-10] [Type Int]: Lit: Int(3)
+                    Expr 26033 [12-15] [Type Int]: Expr Block: 2107
+                    Expr 26034 [13-14] [Type Int]: Var: Local 22
+                    Expr 26035 [16-18] [Type Unit]: Unit
                 Pats:
-                    Pat 2896 [5-6] [Type Int]: Bind: Ident 22 [5-6] "x""#]],
+                    Pat 2917 [5-6] [Type Int]: Bind: Ident 22 [5-6] "x""#]],
         &expect!["3"],
     );
 }
@@ -3754,46 +3805,46 @@ fn partial_eval_stmt_with_mutable_variable_update() {
     check_partial_eval_stmt(
         "",
         "{mutable x = 0; set x += 1; {x} set x = -1;}",
-        &[5009_u32.into(), 5010_u32.into(), 5012_u32.into()],
+        &[5032_u32.into(), 5033_u32.into(), 5035_u32.into()],
         &expect![[r#"
             Package:
-                Entry Expression: 25960
+                Entry Expression: 26031
                 Items:
                 Blocks:
-                    Block 2097 [0-44] [Type Unit]:
-                        5009
-                        5010
-                        5011
-                        5013
-                    Block 2098 [28-31] [Type Int]:
-                        5012
+                    Block 2106 [0-44] [Type Unit]:
+                        5032
+                        5033
+                        5034
+                        5036
+                    Block 2107 [28-31] [Type Int]:
+                        5035
                 Stmts:
-                    Stmt 5009 [1-15]: Local (Mutable):
-                        2896
-                        25961
-                    Stmt 5010 [16-27]: Semi: 25962
-                    Stmt 5011 [28-31]: Expr: 25965
-                    Stmt 5012 [29-30]: Expr: 25966
-                    Stmt 5013 [32-43]: Semi: 25967
+                    Stmt 5032 [1-15]: Local (Mutable):
+                        2917
+                        26032
+                    Stmt 5033 [16-27]: Semi: 26033
+                    Stmt 5034 [28-31]: Expr: 26036
+                    Stmt 5035 [29-30]: Expr: 26037
+                    Stmt 5036 [32-43]: Semi: 26038
                 Exprs:
-                    Expr 25960 [0-44] [Type Unit]: Expr Block: 2097
-                    Expr 25961 [13-14] [Type Int]: Lit: Int(0)
-                    Expr 25962 [16-26] [Type Unit]: AssignOp (Add):
-                        25963
-                        25964
-                    Expr 25963 [20-21] [Type Int]: Var: Local 22
-                    Expr 25964 [25-26] [Type Int]: Lit: Int(1)
-                    Expr 25965 [28-31] [Type Int]: Expr Block: 2098
-                    Expr 25966 [29-30] [Type Int]: Var: Local 22
-                    Expr 25967 [32-42] [Type Unit]: Assign:
-                        25968
-                        25969
-                    Expr 25968 [36-37] [Type Int]: Var: Local 22
-                    Expr 25969 [40-42] [Type Int]: UnOp (Neg):
-                        25970
-                    Expr 25970 [41-42] [Type Int]: Lit: Int(1)
+                    Expr 26031 [0-44] [Type Unit]: Expr Block: 2106
+                    Expr 26032 [13-14] [Type Int]: Lit: Int(0)
+                    Expr 26033 [16-26] [Type Unit]: AssignOp (Add):
+                        26034
+                        26035
+                    Expr 26034 [20-21] [Type Int]: Var: Local 22
+                    Expr 26035 [25-26] [Type Int]: Lit: Int(1)
+                    Expr 26036 [28-31] [Type Int]: Expr Block: 2107
+                    Expr 26037 [29-30] [Type Int]: Var: Local 22
+                    Expr 26038 [32-42] [Type Unit]: Assign:
+                        26039
+                        26040
+                    Expr 26039 [36-37] [Type Int]: Var: Local 22
+                    Expr 26040 [40-42] [Type Int]: UnOp (Neg):
+                        26041
+                    Expr 26041 [41-42] [Type Int]: Lit: Int(1)
                 Pats:
-                    Pat 2896 [9-10] [Type Int]: Bind: Ident 22 [9-10] "x""#]],
+                    Pat 2917 [9-10] [Type Int]: Bind: Ident 22 [9-10] "x""#]],
         &expect!["1"],
     );
 }
@@ -3803,46 +3854,46 @@ fn partial_eval_stmt_with_mutable_variable_update_out_of_order_works() {
     check_partial_eval_stmt(
         "",
         "{mutable x = 0; set x += 1; {x} set x = -1;}",
-        &[5009_u32.into(), 5013_u32.into(), 5012_u32.into()],
+        &[5032_u32.into(), 5036_u32.into(), 5035_u32.into()],
         &expect![[r#"
             Package:
-                Entry Expression: 25960
+                Entry Expression: 26031
                 Items:
                 Blocks:
-                    Block 2097 [0-44] [Type Unit]:
-                        5009
-                        5010
-                        5011
-                        5013
-                    Block 2098 [28-31] [Type Int]:
-                        5012
+                    Block 2106 [0-44] [Type Unit]:
+                        5032
+                        5033
+                        5034
+                        5036
+                    Block 2107 [28-31] [Type Int]:
+                        5035
                 Stmts:
-                    Stmt 5009 [1-15]: Local (Mutable):
-                        2896
-                        25961
-                    Stmt 5010 [16-27]: Semi: 25962
-                    Stmt 5011 [28-31]: Expr: 25965
-                    Stmt 5012 [29-30]: Expr: 25966
-                    Stmt 5013 [32-43]: Semi: 25967
+                    Stmt 5032 [1-15]: Local (Mutable):
+                        2917
+                        26032
+                    Stmt 5033 [16-27]: Semi: 26033
+                    Stmt 5034 [28-31]: Expr: 26036
+                    Stmt 5035 [29-30]: Expr: 26037
+                    Stmt 5036 [32-43]: Semi: 26038
                 Exprs:
-                    Expr 25960 [0-44] [Type Unit]: Expr Block: 2097
-                    Expr 25961 [13-14] [Type Int]: Lit: Int(0)
-                    Expr 25962 [16-26] [Type Unit]: AssignOp (Add):
-                        25963
-                        25964
-                    Expr 25963 [20-21] [Type Int]: Var: Local 22
-                    Expr 25964 [25-26] [Type Int]: Lit: Int(1)
-                    Expr 25965 [28-31] [Type Int]: Expr Block: 2098
-                    Expr 25966 [29-30] [Type Int]: Var: Local 22
-                    Expr 25967 [32-42] [Type Unit]: Assign:
-                        25968
-                        25969
-                    Expr 25968 [36-37] [Type Int]: Var: Local 22
-                    Expr 25969 [40-42] [Type Int]: UnOp (Neg):
-                        25970
-                    Expr 25970 [41-42] [Type Int]: Lit: Int(1)
+                    Expr 26031 [0-44] [Type Unit]: Expr Block: 2106
+                    Expr 26032 [13-14] [Type Int]: Lit: Int(0)
+                    Expr 26033 [16-26] [Type Unit]: AssignOp (Add):
+                        26034
+                        26035
+                    Expr 26034 [20-21] [Type Int]: Var: Local 22
+                    Expr 26035 [25-26] [Type Int]: Lit: Int(1)
+                    Expr 26036 [28-31] [Type Int]: Expr Block: 2107
+                    Expr 26037 [29-30] [Type Int]: Var: Local 22
+                    Expr 26038 [32-42] [Type Unit]: Assign:
+                        26039
+                        26040
+                    Expr 26039 [36-37] [Type Int]: Var: Local 22
+                    Expr 26040 [40-42] [Type Int]: UnOp (Neg):
+                        26041
+                    Expr 26041 [41-42] [Type Int]: Lit: Int(1)
                 Pats:
-                    Pat 2896 [9-10] [Type Int]: Bind: Ident 22 [9-10] "x""#]],
+                    Pat 2917 [9-10] [Type Int]: Bind: Ident 22 [9-10] "x""#]],
         &expect!["-1"],
     );
 }
@@ -3853,50 +3904,50 @@ fn partial_eval_stmt_with_mutable_variable_update_repeat_stmts_works() {
         "",
         "{mutable x = 0; set x += 1; {x} set x = -1;}",
         &[
-            5009_u32.into(),
-            5010_u32.into(),
-            5010_u32.into(),
-            5012_u32.into(),
+            5032_u32.into(),
+            5033_u32.into(),
+            5033_u32.into(),
+            5035_u32.into(),
         ],
         &expect![[r#"
             Package:
-                Entry Expression: 25960
+                Entry Expression: 26031
                 Items:
                 Blocks:
-                    Block 2097 [0-44] [Type Unit]:
-                        5009
-                        5010
-                        5011
-                        5013
-                    Block 2098 [28-31] [Type Int]:
-                        5012
+                    Block 2106 [0-44] [Type Unit]:
+                        5032
+                        5033
+                        5034
+                        5036
+                    Block 2107 [28-31] [Type Int]:
+                        5035
                 Stmts:
-                    Stmt 5009 [1-15]: Local (Mutable):
-                        2896
-                        25961
-                    Stmt 5010 [16-27]: Semi: 25962
-                    Stmt 5011 [28-31]: Expr: 25965
-                    Stmt 5012 [29-30]: Expr: 25966
-                    Stmt 5013 [32-43]: Semi: 25967
+                    Stmt 5032 [1-15]: Local (Mutable):
+                        2917
+                        26032
+                    Stmt 5033 [16-27]: Semi: 26033
+                    Stmt 5034 [28-31]: Expr: 26036
+                    Stmt 5035 [29-30]: Expr: 26037
+                    Stmt 5036 [32-43]: Semi: 26038
                 Exprs:
-                    Expr 25960 [0-44] [Type Unit]: Expr Block: 2097
-                    Expr 25961 [13-14] [Type Int]: Lit: Int(0)
-                    Expr 25962 [16-26] [Type Unit]: AssignOp (Add):
-                        25963
-                        25964
-                    Expr 25963 [20-21] [Type Int]: Var: Local 22
-                    Expr 25964 [25-26] [Type Int]: Lit: Int(1)
-                    Expr 25965 [28-31] [Type Int]: Expr Block: 2098
-                    Expr 25966 [29-30] [Type Int]: Var: Local 22
-                    Expr 25967 [32-42] [Type Unit]: Assign:
-                        25968
-                        25969
-                    Expr 25968 [36-37] [Type Int]: Var: Local 22
-                    Expr 25969 [40-42] [Type Int]: UnOp (Neg):
-                        25970
-                    Expr 25970 [41-42] [Type Int]: Lit: Int(1)
+                    Expr 26031 [0-44] [Type Unit]: Expr Block: 2106
+                    Expr 26032 [13-14] [Type Int]: Lit: Int(0)
+                    Expr 26033 [16-26] [Type Unit]: AssignOp (Add):
+                        26034
+                        26035
+                    Expr 26034 [20-21] [Type Int]: Var: Local 22
+                    Expr 26035 [25-26] [Type Int]: Lit: Int(1)
+                    Expr 26036 [28-31] [Type Int]: Expr Block: 2107
+                    Expr 26037 [29-30] [Type Int]: Var: Local 22
+                    Expr 26038 [32-42] [Type Unit]: Assign:
+                        26039
+                        26040
+                    Expr 26039 [36-37] [Type Int]: Var: Local 22
+                    Expr 26040 [40-42] [Type Int]: UnOp (Neg):
+                        26041
+                    Expr 26041 [41-42] [Type Int]: Lit: Int(1)
                 Pats:
-                    Pat 2896 [9-10] [Type Int]: Bind: Ident 22 [9-10] "x""#]],
+                    Pat 2917 [9-10] [Type Int]: Bind: Ident 22 [9-10] "x""#]],
         &expect!["2"],
     );
 }
@@ -3906,37 +3957,37 @@ fn partial_eval_stmt_with_bool_short_circuit() {
     check_partial_eval_stmt(
         "",
         "{let x = true; { x or false } ();}",
-        &[5009_u32.into(), 5011_u32.into()],
+        &[5032_u32.into(), 5034_u32.into()],
         &expect![[r#"
             Package:
-                Entry Expression: 25960
+                Entry Expression: 26031
                 Items:
                 Blocks:
-                    Block 2097 [0-34] [Type Unit]:
-                        5009
-                        5010
-                        5012
-                    Block 2098 [15-29] [Type Bool]:
-                        5011
+                    Block 2106 [0-34] [Type Unit]:
+                        5032
+                        5033
+                        5035
+                    Block 2107 [15-29] [Type Bool]:
+                        5034
                 Stmts:
-                    Stmt 5009 [1-14]: Local (Immutable):
-                        2896
-                        25961
-                    Stmt 5010 [15-29]: Expr: 25962
-                    Stmt 5011 [17-27]: Expr: 25963
-                    Stmt 5012 [30-33]: Semi: 25966
+                    Stmt 5032 [1-14]: Local (Immutable):
+                        2917
+                        26032
+                    Stmt 5033 [15-29]: Expr: 26033
+                    Stmt 5034 [17-27]: Expr: 26034
+                    Stmt 5035 [30-33]: Semi: 26037
                 Exprs:
-                    Expr 25960 [0-34] [Type Unit]: Expr Block: 2097
-                    Expr 25961 [9-13] [Type Bool]: Lit: Bool(true)
-                    Expr 25962 [15-29] [Type Bool]: Expr Block: 2098
-                    Expr 25963 [17-27] [Type Bool]: BinOp (OrL):
-                        25964
-                        25965
-                    Expr 25964 [17-18] [Type Bool]: Var: Local 22
-                    Expr 25965 [22-27] [Type Bool]: Lit: Bool(false)
-                    Expr 25966 [30-32] [Type Unit]: Unit
+                    Expr 26031 [0-34] [Type Unit]: Expr Block: 2106
+                    Expr 26032 [9-13] [Type Bool]: Lit: Bool(true)
+                    Expr 26033 [15-29] [Type Bool]: Expr Block: 2107
+                    Expr 26034 [17-27] [Type Bool]: BinOp (OrL):
+                        26035
+                        26036
+                    Expr 26035 [17-18] [Type Bool]: Var: Local 22
+                    Expr 26036 [22-27] [Type Bool]: Lit: Bool(false)
+                    Expr 26037 [30-32] [Type Unit]: Unit
                 Pats:
-                    Pat 2896 [5-6] [Type Bool]: Bind: Ident 22 [5-6] "x""#]],
+                    Pat 2917 [5-6] [Type Bool]: Bind: Ident 22 [5-6] "x""#]],
         &expect!["true"],
     );
 }
@@ -3946,37 +3997,37 @@ fn partial_eval_stmt_with_bool_no_short_circuit() {
     check_partial_eval_stmt(
         "",
         "{let x = false; { x or true } ();}",
-        &[5009_u32.into(), 5011_u32.into()],
+        &[5032_u32.into(), 5034_u32.into()],
         &expect![[r#"
             Package:
-                Entry Expression: 25960
+                Entry Expression: 26031
                 Items:
                 Blocks:
-                    Block 2097 [0-34] [Type Unit]:
-                        5009
-                        5010
-                        5012
-                    Block 2098 [16-29] [Type Bool]:
-                        5011
+                    Block 2106 [0-34] [Type Unit]:
+                        5032
+                        5033
+                        5035
+                    Block 2107 [16-29] [Type Bool]:
+                        5034
                 Stmts:
-                    Stmt 5009 [1-15]: Local (Immutable):
-                        2896
-                        25961
-                    Stmt 5010 [16-29]: Expr: 25962
-                    Stmt 5011 [18-27]: Expr: 25963
-                    Stmt 5012 [30-33]: Semi: 25966
+                    Stmt 5032 [1-15]: Local (Immutable):
+                        2917
+                        26032
+                    Stmt 5033 [16-29]: Expr: 26033
+                    Stmt 5034 [18-27]: Expr: 26034
+                    Stmt 5035 [30-33]: Semi: 26037
                 Exprs:
-                    Expr 25960 [0-34] [Type Unit]: Expr Block: 2097
-                    Expr 25961 [9-14] [Type Bool]: Lit: Bool(false)
-                    Expr 25962 [16-29] [Type Bool]: Expr Block: 2098
-                    Expr 25963 [18-27] [Type Bool]: BinOp (OrL):
-                        25964
-                        25965
-                    Expr 25964 [18-19] [Type Bool]: Var: Local 22
-                    Expr 25965 [23-27] [Type Bool]: Lit: Bool(true)
-                    Expr 25966 [30-32] [Type Unit]: Unit
+                    Expr 26031 [0-34] [Type Unit]: Expr Block: 2106
+                    Expr 26032 [9-14] [Type Bool]: Lit: Bool(false)
+                    Expr 26033 [16-29] [Type Bool]: Expr Block: 2107
+                    Expr 26034 [18-27] [Type Bool]: BinOp (OrL):
+                        26035
+                        26036
+                    Expr 26035 [18-19] [Type Bool]: Var: Local 22
+                    Expr 26036 [23-27] [Type Bool]: Lit: Bool(true)
+                    Expr 26037 [30-32] [Type Unit]: Unit
                 Pats:
-                    Pat 2896 [5-6] [Type Bool]: Bind: Ident 22 [5-6] "x""#]],
+                    Pat 2917 [5-6] [Type Bool]: Bind: Ident 22 [5-6] "x""#]],
         &expect!["true"],
     );
 }
@@ -3986,51 +4037,51 @@ fn partial_eval_stmt_with_loop() {
     check_partial_eval_stmt(
         "",
         "{mutable x = 0; while x < 3 { set x += 1; } {x} ();}",
-        &[5009_u32.into(), 5010_u32.into(), 5013_u32.into()],
+        &[5032_u32.into(), 5033_u32.into(), 5036_u32.into()],
         &expect![[r#"
             Package:
-                Entry Expression: 25960
+                Entry Expression: 26031
                 Items:
                 Blocks:
-                    Block 2097 [0-52] [Type Unit]:
-                        5009
-                        5010
-                        5012
-                        5014
-                    Block 2098 [28-43] [Type Unit]:
-                        5011
-                    Block 2099 [44-47] [Type Int]:
-                        5013
+                    Block 2106 [0-52] [Type Unit]:
+                        5032
+                        5033
+                        5035
+                        5037
+                    Block 2107 [28-43] [Type Unit]:
+                        5034
+                    Block 2108 [44-47] [Type Int]:
+                        5036
                 Stmts:
-                    Stmt 5009 [1-15]: Local (Mutable):
-                        2896
-                        25961
-                    Stmt 5010 [16-43]: Expr: 25962
-                    Stmt 5011 [30-41]: Semi: 25966
-                    Stmt 5012 [44-47]: Expr: 25969
-                    Stmt 5013 [45-46]: Expr: 25970
-                    Stmt 5014 [48-51]: Semi: 25971
+                    Stmt 5032 [1-15]: Local (Mutable):
+                        2917
+                        26032
+                    Stmt 5033 [16-43]: Expr: 26033
+                    Stmt 5034 [30-41]: Semi: 26037
+                    Stmt 5035 [44-47]: Expr: 26040
+                    Stmt 5036 [45-46]: Expr: 26041
+                    Stmt 5037 [48-51]: Semi: 26042
                 Exprs:
-                    Expr 25960 [0-52] [Type Unit]: Expr Block: 2097
-                    Expr 25961 [13-14] [Type Int]: Lit: Int(0)
-                    Expr 25962 [16-43] [Type Unit]: While:
-                        25963
-                        2098
-                    Expr 25963 [22-27] [Type Bool]: BinOp (Lt):
-                        25964
-                        25965
-                    Expr 25964 [22-23] [Type Int]: Var: Local 22
-                    Expr 25965 [26-27] [Type Int]: Lit: Int(3)
-                    Expr 25966 [30-40] [Type Unit]: AssignOp (Add):
-                        25967
-                        25968
-                    Expr 25967 [34-35] [Type Int]: Var: Local 22
-                    Expr 25968 [39-40] [Type Int]: Lit: Int(1)
-                    Expr 25969 [44-47] [Type Int]: Expr Block: 2099
-                    Expr 25970 [45-46] [Type Int]: Var: Local 22
-                    Expr 25971 [48-50] [Type Unit]: Unit
+                    Expr 26031 [0-52] [Type Unit]: Expr Block: 2106
+                    Expr 26032 [13-14] [Type Int]: Lit: Int(0)
+                    Expr 26033 [16-43] [Type Unit]: While:
+                        26034
+                        2107
+                    Expr 26034 [22-27] [Type Bool]: BinOp (Lt):
+                        26035
+                        26036
+                    Expr 26035 [22-23] [Type Int]: Var: Local 22
+                    Expr 26036 [26-27] [Type Int]: Lit: Int(3)
+                    Expr 26037 [30-40] [Type Unit]: AssignOp (Add):
+                        26038
+                        26039
+                    Expr 26038 [34-35] [Type Int]: Var: Local 22
+                    Expr 26039 [39-40] [Type Int]: Lit: Int(1)
+                    Expr 26040 [44-47] [Type Int]: Expr Block: 2108
+                    Expr 26041 [45-46] [Type Int]: Var: Local 22
+                    Expr 26042 [48-50] [Type Unit]: Unit
                 Pats:
-                    Pat 2896 [9-10] [Type Int]: Bind: Ident 22 [9-10] "x""#]],
+                    Pat 2917 [9-10] [Type Int]: Bind: Ident 22 [9-10] "x""#]],
         &expect!["3"],
     );
 }
@@ -4044,10 +4095,10 @@ fn partial_eval_stmt_function_calls() {
             }
         "},
         "{let x = Test.Add1(4); {x} Test.Add1(3)}",
-        &[5009_u32.into(), 5011_u32.into()],
+        &[5032_u32.into(), 5034_u32.into()],
         &expect![[r#"
             Package:
-                Entry Expression: 25960
+                Entry Expression: 26031
                 Items:
                     Item 0 [41-102] (Public):
                         Namespace (Ident 23 [51-55] "Test"): Item 1
@@ -4055,54 +4106,54 @@ fn partial_eval_stmt_function_calls() {
                         Parent: 0
                         Callable 0 [62-100] (function):
                             name: Ident 0 [71-75] "Add1"
-                            input: 2897
+                            input: 2918
                             output: Int
                             functors: empty set
                             implementation: Spec:
                                 SpecImpl:
-                                    body: SpecDecl 942 [62-100]: None 2099
+                                    body: SpecDecl 951 [62-100]: None 2108
                                     adj: <none>
                                     ctl: <none>
                                     ctl-adj: <none>
                 Blocks:
-                    Block 2097 [0-40] [Type Int]:
-                        5009
-                        5010
-                        5012
-                    Block 2098 [23-26] [Type Int]:
-                        5011
-                    Block 2099 [91-100] [Type Int]:
-                        5013
+                    Block 2106 [0-40] [Type Int]:
+                        5032
+                        5033
+                        5035
+                    Block 2107 [23-26] [Type Int]:
+                        5034
+                    Block 2108 [91-100] [Type Int]:
+                        5036
                 Stmts:
-                    Stmt 5009 [1-22]: Local (Immutable):
-                        2896
-                        25961
-                    Stmt 5010 [23-26]: Expr: 25964
-                    Stmt 5011 [24-25]: Expr: 25965
-                    Stmt 5012 [27-39]: Expr: 25966
-                    Stmt 5013 [93-98]: Expr: 25969
+                    Stmt 5032 [1-22]: Local (Immutable):
+                        2917
+                        26032
+                    Stmt 5033 [23-26]: Expr: 26035
+                    Stmt 5034 [24-25]: Expr: 26036
+                    Stmt 5035 [27-39]: Expr: 26037
+                    Stmt 5036 [93-98]: Expr: 26040
                 Exprs:
-                    Expr 25960 [0-40] [Type Int]: Expr Block: 2097
-                    Expr 25961 [9-21] [Type Int]: Call:
-                        25962
-                        25963
-                    Expr 25962 [9-18] [Type (Int -> Int)]: Var: Item 1
-                    Expr 25963 [19-20] [Type Int]: Lit: Int(4)
-                    Expr 25964 [23-26] [Type Int]: Expr Block: 2098
-                    Expr 25965 [24-25] [Type Int]: Var: Local 22
-                    Expr 25966 [27-39] [Type Int]: Call:
-                        25967
-                        25968
-                    Expr 25967 [27-36] [Type (Int -> Int)]: Var: Item 1
-                    Expr 25968 [37-38] [Type Int]: Lit: Int(3)
-                    Expr 25969 [93-98] [Type Int]: BinOp (Add):
-                        25970
-                        25971
-                    Expr 25970 [93-94] [Type Int]: Var: Local 1
-                    Expr 25971 [97-98] [Type Int]: Lit: Int(1)
+                    Expr 26031 [0-40] [Type Int]: Expr Block: 2106
+                    Expr 26032 [9-21] [Type Int]: Call:
+                        26033
+                        26034
+                    Expr 26033 [9-18] [Type (Int -> Int)]: Var: Item 1
+                    Expr 26034 [19-20] [Type Int]: Lit: Int(4)
+                    Expr 26035 [23-26] [Type Int]: Expr Block: 2107
+                    Expr 26036 [24-25] [Type Int]: Var: Local 22
+                    Expr 26037 [27-39] [Type Int]: Call:
+                        26038
+                        26039
+                    Expr 26038 [27-36] [Type (Int -> Int)]: Var: Item 1
+                    Expr 26039 [37-38] [Type Int]: Lit: Int(3)
+                    Expr 26040 [93-98] [Type Int]: BinOp (Add):
+                        26041
+                        26042
+                    Expr 26041 [93-94] [Type Int]: Var: Local 1
+                    Expr 26042 [97-98] [Type Int]: Lit: Int(1)
                 Pats:
-                    Pat 2896 [5-6] [Type Int]: Bind: Ident 22 [5-6] "x"
-                    Pat 2897 [76-83] [Type Int]: Bind: Ident 1 [76-77] "x""#]],
+                    Pat 2917 [5-6] [Type Int]: Bind: Ident 22 [5-6] "x"
+                    Pat 2918 [76-83] [Type Int]: Bind: Ident 1 [76-77] "x""#]],
         &expect!["5"],
     );
 }
@@ -4112,46 +4163,46 @@ fn partial_eval_stmt_function_calls_from_library() {
     check_partial_eval_stmt(
         "",
         "{let x = [1, 2, 3]; {Length(x)} 3}",
-        &[5009_u32.into(), 5011_u32.into()],
+        &[5032_u32.into(), 5034_u32.into()],
         &expect![[r#"
             Package:
-                Entry Expression: 25960
+                Entry Expression: 26031
                 Items:
                 Blocks:
-                    Block 2097 [0-34] [Type Int]:
-                        5009
-                        5010
-                        5012
-                    Block 2098 [20-31] [Type Int]:
-                        5011
+                    Block 2106 [0-34] [Type Int]:
+                        5032
+                        5033
+                        5035
+                    Block 2107 [20-31] [Type Int]:
+                        5034
                 Stmts:
-                    Stmt 5009 [1-19]: Local (Immutable):
-                        2896
-                        25961
-                    Stmt 5010 [20-31]: Expr: 25965
-                    Stmt 5011 [21-30]: Expr: 25966
-                    Stmt 5012 [32-33]: Expr: 25969
+                    Stmt 5032 [1-19]: Local (Immutable):
+                        2917
+                        26032
+                    Stmt 5033 [20-31]: Expr: 26036
+                    Stmt 5034 [21-30]: Expr: 26037
+                    Stmt 5035 [32-33]: Expr: 26040
                 Exprs:
-                    Expr 25960 [0-34] [Type Int]: Expr Block: 2097
-                    Expr 25961 [9-18] [Type (Int)[]]: Array:
-                        25962
-                        25963
-                        25964
-                    Expr 25962 [10-11] [Type Int]: Lit: Int(1)
-                    Expr 25963 [13-14] [Type Int]: Lit: Int(2)
-                    Expr 25964 [16-17] [Type Int]: Lit: Int(3)
-                    Expr 25965 [20-31] [Type Int]: Expr Block: 2098
-                    Expr 25966 [21-30] [Type Int]: Call:
-                        25967
-                        25968
-                    Expr 25967 [21-27] [Type ((Int)[] -> Int)]: Var:
+                    Expr 26031 [0-34] [Type Int]: Expr Block: 2106
+                    Expr 26032 [9-18] [Type (Int)[]]: Array:
+                        26033
+                        26034
+                        26035
+                    Expr 26033 [10-11] [Type Int]: Lit: Int(1)
+                    Expr 26034 [13-14] [Type Int]: Lit: Int(2)
+                    Expr 26035 [16-17] [Type Int]: Lit: Int(3)
+                    Expr 26036 [20-31] [Type Int]: Expr Block: 2107
+                    Expr 26037 [21-30] [Type Int]: Call:
+                        26038
+                        26039
+                    Expr 26038 [21-27] [Type ((Int)[] -> Int)]: Var:
                         res: Item 1 (Package 0)
                         generics:
                             Int
-                    Expr 25968 [28-29] [Type (Int)[]]: Var: Local 22
-                    Expr 25969 [32-33] [Type Int]: Lit: Int(3)
+                    Expr 26039 [28-29] [Type (Int)[]]: Var: Local 22
+                    Expr 26040 [32-33] [Type Int]: Lit: Int(3)
                 Pats:
-                    Pat 2896 [5-6] [Type (Int)[]]: Bind: Ident 22 [5-6] "x""#]],
+                    Pat 2917 [5-6] [Type (Int)[]]: Bind: Ident 22 [5-6] "x""#]],
         &expect!["3"],
     );
 }
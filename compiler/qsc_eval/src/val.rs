@@ -171,14 +171,15 @@ impl Value {
         v
     }
 
-    /// Updates a value in an array in-place.
+    /// Updates a value in an array in-place, copying the array first if it is shared with another
+    /// value.
     /// # Panics
     /// This will panic if the [Value] is not a [`Value::Array`].
     pub fn update_array(&mut self, index: usize, value: Self) -> core::result::Result<(), usize> {
         let Value::Array(arr) = self else {
             panic!("value should be Array, got {}", self.type_name());
         };
-        let arr = Rc::get_mut(arr).expect("array should be uniquely referenced");
+        let arr = Rc::make_mut(arr);
         match arr.get_mut(index) {
             Some(v) => {
                 *v = value;
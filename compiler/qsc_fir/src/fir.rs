@@ -19,10 +19,9 @@ use std::{
     cmp::Ordering,
     fmt::{self, Debug, Display, Formatter, Write},
     hash::{Hash, Hasher},
-    ops,
-    rc::Rc,
-    result,
+    ops, result,
     str::FromStr,
+    sync::Arc,
 };
 
 fn set_indentation<'a, 'b>(
@@ -324,7 +323,7 @@ pub enum Global<'a> {
 }
 
 /// A unique identifier for an item within a package store.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct StoreItemId {
     /// The package ID.
     pub package: PackageId,
@@ -451,6 +450,8 @@ pub trait PackageStoreLookup {
     fn get_expr(&self, id: StoreExprId) -> &Expr;
     /// Gets a global.
     fn get_global(&self, id: StoreItemId) -> Option<Global>;
+    /// Gets an item.
+    fn get_item(&self, id: StoreItemId) -> &Item;
     /// Gets a pat.
     fn get_pat(&self, id: StorePatId) -> &Pat;
     /// Gets a statement.
@@ -474,6 +475,10 @@ impl PackageStoreLookup for PackageStore {
         self.get(id.package).get_global(id.item)
     }
 
+    fn get_item(&self, id: StoreItemId) -> &Item {
+        self.get(id.package).get_item(id.item)
+    }
+
     fn get_pat(&self, id: StorePatId) -> &Pat {
         self.get(id.package).get_pat(id.pat)
     }
@@ -556,7 +561,7 @@ pub struct Package {
     /// The entry expression for an executable package.
     pub entry: Option<ExprId>,
     /// The control flow graph for the entry expression in the package.
-    pub entry_exec_graph: Rc<[ExecGraphNode]>,
+    pub entry_exec_graph: Arc<[ExecGraphNode]>,
     /// The blocks in the package.
     pub blocks: IndexMap<BlockId, Block>,
     /// The expressions in the package.
@@ -653,7 +658,7 @@ pub struct Item {
     /// The parent item.
     pub parent: Option<LocalItemId>,
     /// The documentation.
-    pub doc: Rc<str>,
+    pub doc: Arc<str>,
     /// The attributes.
     pub attrs: Vec<Attr>,
     /// The visibility.
@@ -864,7 +869,7 @@ pub struct SpecDecl {
     /// The input of the specialization.
     pub input: Option<PatId>,
     /// The flattened control flow graph for the execution of the specialization.
-    pub exec_graph: Rc<[ExecGraphNode]>,
+    pub exec_graph: Arc<[ExecGraphNode]>,
 }
 
 impl Display for SpecDecl {
@@ -1362,7 +1367,7 @@ pub enum StringComponent {
     /// An expression.
     Expr(ExprId),
     /// A string literal.
-    Lit(Rc<str>),
+    Lit(Arc<str>),
 }
 
 /// A pattern.
@@ -1431,7 +1436,7 @@ pub struct Ident {
     /// The span.
     pub span: Span,
     /// The identifier name.
-    pub name: Rc<str>,
+    pub name: Arc<str>,
 }
 
 impl Display for Ident {
@@ -1445,6 +1450,51 @@ impl Display for Ident {
 pub enum Attr {
     /// Indicates that a callable is an entry point to a program.
     EntryPoint,
+    /// Indicates that an intrinsic callable is implemented by the target, with the details of how
+    /// it is emitted.
+    CustomIntrinsic(CustomIntrinsic),
+    /// Indicates that a callable must be able to run on targets with the given profile.
+    TargetProfile(TargetProfile),
+}
+
+/// The target profile that a callable with the `@TargetProfile` attribute declares it runs on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetProfile {
+    /// Targets that can only execute quantum operations on statically allocated qubits.
+    Base,
+    /// Targets with every runtime capability.
+    Unrestricted,
+}
+
+/// How an intrinsic callable with the `@CustomIntrinsic` attribute is emitted for the target.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CustomIntrinsic {
+    /// The name of the function that the intrinsic is declared and called as in QIR.
+    pub qir_name: Arc<str>,
+    /// The label of the gate for the intrinsic in circuits, if it isn't the QIR name.
+    pub glyph: Option<Arc<str>>,
+    /// Whether only targets with unrestricted capabilities can run the intrinsic.
+    pub unrestricted: bool,
+}
+
+impl Item {
+    /// The details of the custom intrinsic, if the item has the `@CustomIntrinsic` attribute.
+    #[must_use]
+    pub fn custom_intrinsic(&self) -> Option<&CustomIntrinsic> {
+        self.attrs.iter().find_map(|attr| match attr {
+            Attr::CustomIntrinsic(intrinsic) => Some(intrinsic),
+            Attr::EntryPoint | Attr::TargetProfile(_) => None,
+        })
+    }
+
+    /// The profile the item declares it runs on, if it has the `@TargetProfile` attribute.
+    #[must_use]
+    pub fn target_profile(&self) -> Option<TargetProfile> {
+        self.attrs.iter().find_map(|attr| match attr {
+            Attr::TargetProfile(profile) => Some(*profile),
+            Attr::EntryPoint | Attr::CustomIntrinsic(_) => None,
+        })
+    }
 }
 
 /// A field.
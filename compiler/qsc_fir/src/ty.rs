@@ -8,7 +8,7 @@ use rustc_hash::FxHashMap;
 use crate::fir::{CallableKind, FieldPath, Functor, ItemId, Res};
 use std::{
     fmt::{self, Debug, Display, Formatter, Write},
-    rc::Rc,
+    sync::Arc,
 };
 
 fn set_indentation<'a, 'b>(
@@ -407,7 +407,7 @@ pub struct Udt {
     /// The span.
     pub span: Span,
     /// The name.
-    pub name: Rc<str>,
+    pub name: Arc<str>,
     // The definition.
     pub definition: UdtDef,
 }
@@ -562,7 +562,7 @@ pub struct UdtField {
     /// The span of the field name.
     pub name_span: Option<Span>,
     /// The field name.
-    pub name: Option<Rc<str>>,
+    pub name: Option<Arc<str>>,
     // The field type.
     pub ty: Ty,
 }
@@ -2,6 +2,7 @@
 // Licensed under the MIT License.
 
 use qsc_data_structures::span::Span;
+use qsc_edit::{Edit, Patch};
 use qsc_frontend::{
     keyword::Keyword,
     lex::{
@@ -19,17 +20,12 @@ mod tests;
 /// Applies formatting rules to the give code str and returns
 /// the formatted string.
 pub fn format_str(code: &str) -> String {
-    let mut edits = calculate_format_edits(code);
-    edits.sort_by_key(|edit| edit.span.hi); // sort edits by their span's hi value from lowest to highest
-    edits.reverse(); // sort from highest to lowest so that that as edits are applied they don't invalidate later applications of edits
-    let mut new_code = String::from(code);
-
-    for edit in edits {
-        let range = (edit.span.lo as usize)..(edit.span.hi as usize);
-        new_code.replace_range(range, &edit.new_text);
-    }
-
-    new_code
+    let edits = calculate_format_edits(code)
+        .into_iter()
+        .map(|edit| Edit::replace(edit.span, edit.new_text));
+    Patch::new(edits)
+        .and_then(|patch| patch.apply_to_str(code))
+        .expect("formatting edits should be disjoint and within the code")
 }
 
 /// Applies formatting rules to the given code str, generating edits where
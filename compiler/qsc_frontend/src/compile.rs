@@ -4,6 +4,7 @@
 #[cfg(test)]
 mod tests;
 
+mod parse_cache;
 pub mod preprocess;
 
 use crate::{
@@ -14,6 +15,7 @@ use crate::{
 };
 use bitflags::bitflags;
 use miette::{Diagnostic, Report};
+pub use parse_cache::ParseCache;
 use preprocess::TrackedName;
 use qsc_ast::{
     assigner::Assigner as AstAssigner,
@@ -34,8 +36,9 @@ use qsc_hir::{
     validate::Validator as HirValidator,
     visit::Visitor as _,
 };
-use std::{fmt::Debug, str::FromStr, sync::Arc};
+use std::{fmt::Debug, rc::Rc, str::FromStr, sync::Arc};
 use thiserror::Error;
+use tracing::info_span;
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -108,10 +111,27 @@ pub struct AstPackage {
     pub locals: Locals,
 }
 
-#[derive(Debug, Default)]
+impl AstPackage {
+    /// Lowers a callable declared in the package to HIR, as it is before any passes run. The
+    /// lowered callable is separate from the compiled package: its node IDs, and the IDs of the
+    /// items declared in it, come from the returned assigner.
+    #[must_use]
+    pub fn lower_callable(&self, decl: &ast::CallableDecl) -> (hir::CallableDecl, HirAssigner) {
+        let mut assigner = HirAssigner::new();
+        let mut lowerer = Lowerer::new();
+        let decl = lowerer
+            .with(&mut assigner, &self.names, &self.tys)
+            .lower_callable_decl(decl);
+        (decl, assigner)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct SourceMap {
     sources: Vec<Source>,
     entry: Option<Source>,
+    /// The user-defined flags that `@Config` attributes in the sources can test for.
+    config: Vec<Arc<str>>,
 }
 
 impl SourceMap {
@@ -141,9 +161,23 @@ impl SourceMap {
         Self {
             sources: offset_sources,
             entry: entry_source,
+            config: Vec::new(),
         }
     }
 
+    /// Sets the user-defined flags that `@Config` attributes in the sources can test for, such as
+    /// the ones declared in the project manifest.
+    #[must_use]
+    pub fn with_config(mut self, config: impl IntoIterator<Item = Arc<str>>) -> Self {
+        self.config = config.into_iter().collect();
+        self
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &[Arc<str>] {
+        &self.config
+    }
+
     pub fn push(&mut self, name: SourceName, contents: SourceContents) -> u32 {
         let offset = next_offset(self.sources.last());
 
@@ -173,6 +207,12 @@ impl SourceMap {
     pub fn iter(&self) -> impl Iterator<Item = &Source> {
         self.sources.iter()
     }
+
+    /// The source of the entry expression, if there is one.
+    #[must_use]
+    pub fn entry(&self) -> Option<&Source> {
+        self.entry.as_ref()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -206,7 +246,8 @@ pub(super) enum ErrorKind {
 
 pub struct PackageStore {
     core: global::Table,
-    units: IndexMap<PackageId, CompileUnit>,
+    /// The units are shared so that compiled libraries can be inserted into several stores without being copied.
+    units: IndexMap<PackageId, Rc<CompileUnit>>,
     next_id: PackageId,
 }
 
@@ -218,7 +259,8 @@ impl Debug for PackageStore {
 
 impl PackageStore {
     #[must_use]
-    pub fn new(core: CompileUnit) -> Self {
+    pub fn new(core: impl Into<Rc<CompileUnit>>) -> Self {
+        let core = core.into();
         let table = global::iter_package(Some(PackageId::CORE), &core.package).collect();
         let mut units = IndexMap::new();
         units.insert(PackageId::CORE, core);
@@ -234,16 +276,23 @@ impl PackageStore {
         &self.core
     }
 
-    pub fn insert(&mut self, unit: CompileUnit) -> PackageId {
+    pub fn insert(&mut self, unit: impl Into<Rc<CompileUnit>>) -> PackageId {
         let id = self.next_id;
         self.next_id = id.successor();
-        self.units.insert(id, unit);
+        self.units.insert(id, unit.into());
         id
     }
 
     #[must_use]
     pub fn get(&self, id: PackageId) -> Option<&CompileUnit> {
-        self.units.get(id)
+        self.units.get(id).map(|unit| &**unit)
+    }
+
+    /// Returns the unit with the given ID in a form that can be inserted into another store
+    /// without copying it.
+    #[must_use]
+    pub fn get_shared(&self, id: PackageId) -> Option<Rc<CompileUnit>> {
+        self.units.get(id).cloned()
     }
 
     #[must_use]
@@ -258,7 +307,7 @@ impl PackageStore {
     pub fn open(mut self) -> OpenPackageStore {
         let id = self.next_id;
         self.next_id = id.successor();
-        self.units.insert(id, CompileUnit::default());
+        self.units.insert(id, Rc::default());
 
         OpenPackageStore {
             store: self,
@@ -303,13 +352,35 @@ impl OpenPackageStore {
 
         (
             &self.store.core,
-            self.store
-                .units
-                .get_mut(id)
-                .expect("open package id should exist in store"),
+            Rc::get_mut(
+                self.store
+                    .units
+                    .get_mut(id)
+                    .expect("open package id should exist in store"),
+            )
+            .expect("open package should not be shared"),
         )
     }
 
+    /// Returns a new package store with every package of this store except the open one. The
+    /// packages are shared rather than copied and keep their IDs, so that another package can be
+    /// compiled against them without compiling them again.
+    #[must_use]
+    pub fn share_dependencies(&self) -> PackageStore {
+        let mut store = PackageStore::new(
+            self.store
+                .get_shared(PackageId::CORE)
+                .expect("store should contain the core library"),
+        );
+        for (id, unit) in self.store.units.iter() {
+            if id != PackageId::CORE && id != self.open {
+                let shared_id = store.insert(unit.clone());
+                assert_eq!(shared_id, id, "shared package should keep its ID");
+            }
+        }
+        store
+    }
+
     /// Consumes the `OpenPackageStore` and returns a `PackageStore`
     /// along with the id of the formerly open package.
     #[must_use]
@@ -318,13 +389,13 @@ impl OpenPackageStore {
     }
 }
 
-pub struct Iter<'a>(index_map::Iter<'a, PackageId, CompileUnit>);
+pub struct Iter<'a>(index_map::Iter<'a, PackageId, Rc<CompileUnit>>);
 
 impl<'a> Iterator for Iter<'a> {
     type Item = (PackageId, &'a CompileUnit);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        self.0.next().map(|(id, unit)| (id, &**unit))
     }
 }
 
@@ -337,6 +408,7 @@ impl MutVisitor for Offsetter {
     }
 }
 
+#[must_use]
 pub fn compile(
     store: &PackageStore,
     dependencies: &[PackageId],
@@ -344,9 +416,57 @@ pub fn compile(
     capabilities: RuntimeCapabilityFlags,
     language_features: LanguageFeatures,
 ) -> CompileUnit {
-    let (mut ast_package, parse_errors) = parse_all(&sources, language_features);
+    compile_sources(
+        store,
+        dependencies,
+        sources,
+        None,
+        false,
+        capabilities,
+        language_features,
+    )
+}
+
+/// Compiles the sources like [`compile`], but parses them starting from the ASTs in the cache, so
+/// that a source edited since the cache last saw it only has the item around the edit parsed again.
+/// The cache is updated with the ASTs of the sources.
+///
+/// When `top_level_stmts` is set, statements outside of namespaces are parsed and resolved instead
+/// of being parse errors, so that the linter can point them out. Otherwise they are rejected like
+/// they are by [`compile`].
+pub fn compile_with_parse_cache(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    sources: SourceMap,
+    parse_cache: &mut ParseCache,
+    top_level_stmts: bool,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+) -> CompileUnit {
+    compile_sources(
+        store,
+        dependencies,
+        sources,
+        Some(parse_cache),
+        top_level_stmts,
+        capabilities,
+        language_features,
+    )
+}
 
-    let mut cond_compile = preprocess::Conditional::new(capabilities);
+fn compile_sources(
+    store: &PackageStore,
+    dependencies: &[PackageId],
+    sources: SourceMap,
+    parse_cache: Option<&mut ParseCache>,
+    top_level_stmts: bool,
+    capabilities: RuntimeCapabilityFlags,
+    language_features: LanguageFeatures,
+) -> CompileUnit {
+    let (mut ast_package, parse_errors) = info_span!("parse")
+        .in_scope(|| parse_all(&sources, language_features, parse_cache, top_level_stmts));
+
+    let mut cond_compile = preprocess::Conditional::new(capabilities).with_config(sources.config());
     cond_compile.visit_package(&mut ast_package);
     let dropped_names = cond_compile.into_names();
 
@@ -354,18 +474,23 @@ pub fn compile(
     ast_assigner.visit_package(&mut ast_package);
     AstValidator::default().visit_package(&ast_package);
     let mut hir_assigner = HirAssigner::new();
-    let (names, locals, name_errors) = resolve_all(
-        store,
-        dependencies,
-        &mut hir_assigner,
-        &ast_package,
-        dropped_names.clone(),
-    );
-    let (tys, ty_errors) = typeck_all(store, dependencies, &ast_package, &names);
+    let (names, locals, name_errors) = info_span!("resolve").in_scope(|| {
+        resolve_all(
+            store,
+            dependencies,
+            &mut hir_assigner,
+            &ast_package,
+            dropped_names.clone(),
+        )
+    });
+    let (tys, ty_errors) =
+        info_span!("typeck").in_scope(|| typeck_all(store, dependencies, &ast_package, &names));
     let mut lowerer = Lowerer::new();
-    let package = lowerer
-        .with(&mut hir_assigner, &names, &tys)
-        .lower_package(&ast_package);
+    let package = info_span!("lower").in_scope(|| {
+        lowerer
+            .with(&mut hir_assigner, &names, &tys)
+            .lower_package(&ast_package)
+    });
     HirValidator::default().visit_package(&package);
     let lower_errors = lowerer.drain_errors();
 
@@ -447,21 +572,55 @@ pub fn std(store: &PackageStore, capabilities: RuntimeCapabilityFlags) -> Compil
     unit
 }
 
+/// Parses the sources into an AST package with node IDs assigned, without resolving names or
+/// checking types, so that the syntax of sources can be inspected on its own. Top-level
+/// statements are allowed alongside namespaces, as they are in interactive fragments.
+#[must_use]
+pub fn parse(
+    sources: &SourceMap,
+    language_features: LanguageFeatures,
+) -> (ast::Package, Vec<Error>) {
+    let (mut package, errors) = parse_all(sources, language_features, None, true);
+    AstAssigner::new().visit_package(&mut package);
+    (
+        package,
+        errors
+            .into_iter()
+            .map(|error| Error(error.into()))
+            .collect(),
+    )
+}
+
 fn parse_all(
     sources: &SourceMap,
     features: LanguageFeatures,
+    mut parse_cache: Option<&mut ParseCache>,
+    top_level_stmts: bool,
 ) -> (ast::Package, Vec<qsc_parse::Error>) {
-    let mut namespaces = Vec::new();
+    let mut nodes = Vec::new();
     let mut errors = Vec::new();
     for source in &sources.sources {
-        let (source_namespaces, source_errors) = qsc_parse::namespaces(&source.contents, features);
-        for mut namespace in source_namespaces {
-            Offsetter(source.offset).visit_namespace(&mut namespace);
-            namespaces.push(TopLevelNode::Namespace(namespace));
+        let (source_nodes, source_errors) = match parse_cache.as_deref_mut() {
+            Some(parse_cache) => {
+                parse_cache.parse(&source.name, &source.contents, features, top_level_stmts)
+            }
+            None => parse_source(&source.contents, features, top_level_stmts),
+        };
+        for mut node in source_nodes {
+            match &mut node {
+                TopLevelNode::Namespace(namespace) => {
+                    Offsetter(source.offset).visit_namespace(namespace);
+                }
+                TopLevelNode::Stmt(stmt) => Offsetter(source.offset).visit_stmt(stmt),
+            }
+            nodes.push(node);
         }
 
         append_parse_errors(&mut errors, source.offset, source_errors);
     }
+    if let Some(parse_cache) = parse_cache {
+        parse_cache.retain(sources);
+    }
 
     let entry = sources
         .entry
@@ -476,13 +635,35 @@ fn parse_all(
 
     let package = ast::Package {
         id: ast::NodeId::default(),
-        nodes: namespaces.into_boxed_slice(),
+        nodes: nodes.into_boxed_slice(),
         entry,
     };
 
     (package, errors)
 }
 
+/// Parses the contents of a source into its top-level nodes. Top-level statements are only parsed
+/// when they are allowed, so that the rest of the source is still compiled and the linter can point
+/// out where they are. Otherwise they are a parse error.
+fn parse_source(
+    contents: &str,
+    features: LanguageFeatures,
+    top_level_stmts: bool,
+) -> (Vec<TopLevelNode>, Vec<qsc_parse::Error>) {
+    if top_level_stmts {
+        qsc_parse::top_level_nodes(contents, features)
+    } else {
+        let (namespaces, errors) = qsc_parse::namespaces(contents, features);
+        (
+            namespaces
+                .into_iter()
+                .map(TopLevelNode::Namespace)
+                .collect(),
+            errors,
+        )
+    }
+}
+
 fn resolve_all(
     store: &PackageStore,
     dependencies: &[PackageId],
@@ -505,7 +686,17 @@ fn resolve_all(
     }
 
     let mut errors = globals.add_local_package(assigner, package);
-    let mut resolver = Resolver::new(globals, dropped_names);
+    let mut resolver = if package
+        .nodes
+        .iter()
+        .any(|node| matches!(node, TopLevelNode::Stmt(_)))
+    {
+        let mut resolver = Resolver::with_persistent_local_scope(globals, dropped_names);
+        resolver.bind_top_level_items(package, assigner);
+        resolver
+    } else {
+        Resolver::new(globals, dropped_names)
+    };
     resolver.with(assigner).visit_package(package);
     let (names, locals, mut resolver_errors) = resolver.into_result();
     errors.append(&mut resolver_errors);
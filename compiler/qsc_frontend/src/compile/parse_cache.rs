@@ -0,0 +1,211 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Keeps the ASTs that sources were parsed into, so that after an edit to a source only the item of
+//! a namespace that the edit is in has to be parsed again. This keeps the language service
+//! responsive on large files, where most edits are inside of the body of a single callable.
+
+#[cfg(test)]
+mod tests;
+
+use super::{parse_source, Offsetter, SourceContents, SourceMap, SourceName};
+use qsc_ast::{
+    ast::{Namespace, TopLevelNode},
+    mut_visit::MutVisitor,
+};
+use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
+use rustc_hash::FxHashMap;
+
+/// The ASTs of the sources of a package as they were last parsed, before their spans were offset
+/// into the package.
+#[derive(Debug, Default)]
+pub struct ParseCache {
+    sources: FxHashMap<SourceName, ParsedSource>,
+}
+
+#[derive(Debug)]
+struct ParsedSource {
+    contents: SourceContents,
+    language_features: LanguageFeatures,
+    top_level_stmts: bool,
+    nodes: Vec<TopLevelNode>,
+    errors: Vec<qsc_parse::Error>,
+}
+
+impl ParseCache {
+    /// Parses the contents of a source, starting from the AST of its previous contents if the cache
+    /// has it. The returned nodes and errors are relative to the start of the source.
+    pub(super) fn parse(
+        &mut self,
+        name: &SourceName,
+        contents: &SourceContents,
+        language_features: LanguageFeatures,
+        top_level_stmts: bool,
+    ) -> (Vec<TopLevelNode>, Vec<qsc_parse::Error>) {
+        let parsed = match self.sources.remove(name) {
+            Some(previous)
+                if previous.language_features == language_features
+                    && previous.top_level_stmts == top_level_stmts =>
+            {
+                reparse(previous, contents)
+            }
+            _ => parse(contents, language_features, top_level_stmts),
+        };
+        let nodes = parsed.nodes.clone();
+        let errors = parsed.errors.clone();
+        self.sources.insert(name.clone(), parsed);
+        (nodes, errors)
+    }
+
+    /// Drops the ASTs of the sources that are no longer in the source map.
+    pub(super) fn retain(&mut self, sources: &SourceMap) {
+        self.sources
+            .retain(|name, _| sources.sources.iter().any(|source| source.name == *name));
+    }
+}
+
+fn parse(
+    contents: &SourceContents,
+    language_features: LanguageFeatures,
+    top_level_stmts: bool,
+) -> ParsedSource {
+    let (nodes, errors) = parse_source(contents, language_features, top_level_stmts);
+    ParsedSource {
+        contents: contents.clone(),
+        language_features,
+        top_level_stmts,
+        nodes,
+        errors,
+    }
+}
+
+fn reparse(previous: ParsedSource, contents: &SourceContents) -> ParsedSource {
+    if previous.contents == *contents {
+        return previous;
+    }
+
+    match reparse_item(&previous, contents) {
+        Some(nodes) => ParsedSource {
+            contents: contents.clone(),
+            language_features: previous.language_features,
+            top_level_stmts: previous.top_level_stmts,
+            nodes,
+            errors: Vec::new(),
+        },
+        None => parse(
+            contents,
+            previous.language_features,
+            previous.top_level_stmts,
+        ),
+    }
+}
+
+/// Parses the namespace item that the edit between the previous and the new contents is in, and
+/// puts it in place of the previous item. Returns `None` when the edit isn't inside of a single
+/// item, or when the source or the item don't parse without errors, since then the edit can change
+/// how the rest of the source is parsed.
+fn reparse_item(previous: &ParsedSource, contents: &str) -> Option<Vec<TopLevelNode>> {
+    if !previous.errors.is_empty() {
+        return None;
+    }
+
+    let edit = Edit::between(&previous.contents, contents)?;
+    let (namespace_index, item_index) =
+        previous
+            .nodes
+            .iter()
+            .enumerate()
+            .find_map(|(namespace_index, node)| match node {
+                TopLevelNode::Namespace(namespace) => namespace
+                    .items
+                    .iter()
+                    .position(|item| item.span.lo < edit.lo && edit.hi < item.span.hi)
+                    .map(|item_index| (namespace_index, item_index)),
+                TopLevelNode::Stmt(_) => None,
+            })?;
+
+    let TopLevelNode::Namespace(namespace) = &previous.nodes[namespace_index] else {
+        unreachable!("edited item should be in a namespace");
+    };
+    let span = namespace.items[item_index].span;
+    let hi = u32::try_from(i64::from(span.hi) + edit.delta).ok()?;
+    let (mut item, errors) = qsc_parse::item(
+        contents.get(span.lo as usize..hi as usize)?,
+        previous.language_features,
+    );
+    if !errors.is_empty() {
+        return None;
+    }
+    Offsetter(span.lo).visit_item(&mut item);
+
+    let mut nodes = previous.nodes.clone();
+    let mut shifter = Shifter {
+        from: span.hi,
+        delta: edit.delta,
+    };
+    for node in &mut nodes[namespace_index..] {
+        match node {
+            TopLevelNode::Namespace(namespace) => shifter.visit_namespace(namespace),
+            TopLevelNode::Stmt(stmt) => shifter.visit_stmt(stmt),
+        }
+    }
+    let TopLevelNode::Namespace(Namespace { items, .. }) = &mut nodes[namespace_index] else {
+        unreachable!("edited item should be in a namespace");
+    };
+    items[item_index] = item;
+    Some(nodes)
+}
+
+/// The range of the previous contents that an edit replaced, found from the text that the previous
+/// and the new contents have in common at their start and at their end.
+struct Edit {
+    lo: u32,
+    hi: u32,
+    /// The difference in length between the new and the previous contents.
+    delta: i64,
+}
+
+impl Edit {
+    fn between(previous: &str, contents: &str) -> Option<Self> {
+        let prefix = previous
+            .bytes()
+            .zip(contents.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = previous.as_bytes()[prefix..]
+            .iter()
+            .rev()
+            .zip(contents.as_bytes()[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        Some(Self {
+            lo: u32::try_from(prefix).ok()?,
+            hi: u32::try_from(previous.len() - suffix).ok()?,
+            delta: i64::try_from(contents.len()).ok()? - i64::try_from(previous.len()).ok()?,
+        })
+    }
+}
+
+/// Moves the offsets after an edit by the difference in length that the edit made.
+struct Shifter {
+    from: u32,
+    delta: i64,
+}
+
+impl Shifter {
+    fn shift(&self, offset: u32) -> u32 {
+        if offset < self.from {
+            offset
+        } else {
+            u32::try_from(i64::from(offset) + self.delta)
+                .expect("shifted offset should fit into u32")
+        }
+    }
+}
+
+impl MutVisitor for Shifter {
+    fn visit_span(&mut self, span: &mut Span) {
+        span.lo = self.shift(span.lo);
+        span.hi = self.shift(span.hi);
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use super::{parse, reparse, reparse_item, ParseCache};
+use crate::compile::SourceMap;
+use qsc_ast::ast::TopLevelNode;
+use qsc_data_structures::language_features::LanguageFeatures;
+
+const SOURCE: &str = "namespace A {
+    function Foo() : Int {
+        let x = 1;
+        x + 1
+    }
+    function Bar() : Int {
+        Foo()
+    }
+    export Foo;
+}
+namespace B {
+    function Baz() : Unit {}
+}";
+
+/// Parses the edited source from the AST of the original one, checks whether only the item around
+/// the edit was parsed again, and checks that the result is the same as parsing the edited source
+/// from scratch.
+fn check(edited: &str, only_item_parsed: bool) {
+    let previous = parse(&SOURCE.into(), LanguageFeatures::default(), true);
+    assert_eq!(
+        reparse_item(&previous, edited).is_some(),
+        only_item_parsed,
+        "whether only the item should be parsed again"
+    );
+
+    let parsed = reparse(previous, &edited.into());
+    let (nodes, errors) = qsc_parse::top_level_nodes(edited, LanguageFeatures::default());
+    assert_eq!(display(&parsed.nodes), display(&nodes));
+    assert_eq!(parsed.errors, errors);
+}
+
+/// The parsed nodes have no IDs yet, so they are compared by how they are displayed.
+fn display(nodes: &[TopLevelNode]) -> Vec<String> {
+    nodes.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn insertion_in_callable_body_parses_item() {
+    check(&SOURCE.replace("x + 1", "x + 10 * x"), true);
+}
+
+#[test]
+fn deletion_in_callable_body_parses_item() {
+    check(&SOURCE.replace("let x = 1;\n        x + 1", "1"), true);
+}
+
+#[test]
+fn edit_in_last_item_parses_item() {
+    check(
+        &SOURCE.replace("Baz() : Unit {}", "Baz() : Unit { Bar(); }"),
+        true,
+    );
+}
+
+#[test]
+fn edit_in_export_parses_item() {
+    check(&SOURCE.replace("export Foo;", "export Foo, Bar;"), true);
+}
+
+#[test]
+fn edit_between_items_parses_source() {
+    check(
+        &SOURCE.replace("    function Bar()", "    open C;\n    function Bar()"),
+        false,
+    );
+}
+
+#[test]
+fn edit_that_closes_item_early_parses_source() {
+    check(&SOURCE.replace("let x = 1;", "let x = 1; }"), false);
+}
+
+#[test]
+fn edit_with_syntax_error_parses_source() {
+    check(&SOURCE.replace("x + 1", "x +"), false);
+}
+
+#[test]
+fn unchanged_source_is_not_parsed_again() {
+    let previous = parse(&SOURCE.into(), LanguageFeatures::default(), true);
+    let nodes = display(&previous.nodes);
+    let parsed = reparse(previous, &SOURCE.into());
+    assert_eq!(display(&parsed.nodes), nodes);
+}
+
+#[test]
+fn removed_sources_are_dropped() {
+    let mut cache = ParseCache::default();
+    let features = LanguageFeatures::default();
+    cache.parse(&"a.qs".into(), &SOURCE.into(), features, true);
+    cache.parse(&"b.qs".into(), &SOURCE.into(), features, true);
+    cache.retain(&SourceMap::new([("b.qs".into(), SOURCE.into())], None));
+    assert!(!cache.sources.contains_key("a.qs"));
+    assert!(cache.sources.contains_key("b.qs"));
+}
@@ -3,11 +3,11 @@
 
 use core::str::FromStr;
 use qsc_ast::{
-    ast::{Attr, ExprKind, ItemKind, Namespace, Stmt, StmtKind},
+    ast::{Attr, Expr, ExprKind, ItemKind, Namespace, Stmt, StmtKind, UnOp},
     mut_visit::MutVisitor,
 };
 use qsc_hir::hir;
-use std::rc::Rc;
+use std::{rc::Rc, sync::Arc};
 
 use super::{ConfigAttr, RuntimeCapabilityFlags};
 
@@ -19,6 +19,8 @@ pub struct TrackedName {
 
 pub(crate) struct Conditional {
     capabilities: RuntimeCapabilityFlags,
+    /// The user-defined flags that are set.
+    config: Vec<Arc<str>>,
     dropped_names: Vec<TrackedName>,
     included_names: Vec<TrackedName>,
 }
@@ -27,11 +29,17 @@ impl Conditional {
     pub(crate) fn new(capabilities: RuntimeCapabilityFlags) -> Self {
         Self {
             capabilities,
+            config: Vec::new(),
             dropped_names: Vec::new(),
             included_names: Vec::new(),
         }
     }
 
+    pub(crate) fn with_config(mut self, config: &[Arc<str>]) -> Self {
+        self.config = config.to_vec();
+        self
+    }
+
     pub(crate) fn into_names(self) -> Vec<TrackedName> {
         self.dropped_names
             .into_iter()
@@ -46,7 +54,7 @@ impl MutVisitor for Conditional {
             .items
             .iter()
             .filter_map(|item| {
-                if matches_config(&item.attrs, self.capabilities) {
+                if matches_config(&item.attrs, self.capabilities, &self.config) {
                     match item.kind.as_ref() {
                         ItemKind::Callable(callable) => {
                             self.included_names.push(TrackedName {
@@ -84,7 +92,7 @@ impl MutVisitor for Conditional {
 
     fn visit_stmt(&mut self, stmt: &mut Stmt) {
         if let StmtKind::Item(item) = stmt.kind.as_mut() {
-            if matches_config(&item.attrs, self.capabilities) {
+            if matches_config(&item.attrs, self.capabilities, &self.config) {
                 match item.kind.as_ref() {
                     ItemKind::Callable(callable) => {
                         self.included_names.push(TrackedName {
@@ -118,21 +126,15 @@ impl MutVisitor for Conditional {
     }
 }
 
-fn matches_config(attrs: &[Box<Attr>], capabilities: RuntimeCapabilityFlags) -> bool {
+fn matches_config(
+    attrs: &[Box<Attr>],
+    capabilities: RuntimeCapabilityFlags,
+    config: &[Arc<str>],
+) -> bool {
     attrs.iter().all(|attr| {
         if hir::Attr::from_str(attr.name.name.as_ref()) == Ok(hir::Attr::Config) {
             if let ExprKind::Paren(inner) = attr.arg.kind.as_ref() {
-                match inner.kind.as_ref() {
-                    // If there is no config attribute, then we assume that the item matches
-                    // the target. We can't do membership tests on the capabilities because
-                    // Base is not a subset of any capabilities, it is a lack of capabilities.
-                    ExprKind::Path(path) => match ConfigAttr::from_str(path.name.name.as_ref()) {
-                        Ok(ConfigAttr::Unrestricted) => capabilities.is_all(),
-                        Ok(ConfigAttr::Base) => capabilities.is_empty(),
-                        _ => true,
-                    },
-                    _ => true, // Unknown config attribute, so we assume it matches
-                }
+                matches_config_expr(inner, capabilities, config)
             } else {
                 // Something other than a parenthesized expression, so we assume it matches
                 true
@@ -143,3 +145,29 @@ fn matches_config(attrs: &[Box<Attr>], capabilities: RuntimeCapabilityFlags) ->
         }
     })
 }
+
+/// Evaluates the argument of a config attribute, which names a profile, a runtime capability or a
+/// user-defined flag, optionally negated with `not`.
+fn matches_config_expr(
+    expr: &Expr,
+    capabilities: RuntimeCapabilityFlags,
+    config: &[Arc<str>],
+) -> bool {
+    match expr.kind.as_ref() {
+        ExprKind::Paren(inner) => matches_config_expr(inner, capabilities, config),
+        ExprKind::UnOp(UnOp::NotL, inner) => !matches_config_expr(inner, capabilities, config),
+        // We can't do membership tests on the capabilities for the profiles because Base is not a
+        // subset of any capabilities, it is a lack of capabilities.
+        ExprKind::Path(path) => match ConfigAttr::from_str(path.name.name.as_ref()) {
+            Ok(ConfigAttr::Unrestricted) => capabilities.is_all(),
+            Ok(ConfigAttr::Base) => capabilities.is_empty(),
+            Err(()) => match RuntimeCapabilityFlags::from_name(path.name.name.as_ref()) {
+                Some(capability) => capabilities.contains(capability),
+                None => config
+                    .iter()
+                    .any(|flag| flag.as_ref() == path.name.name.as_ref()),
+            },
+        },
+        _ => true, // Unknown config attribute, so we assume it matches
+    }
+}
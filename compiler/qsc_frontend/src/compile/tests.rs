@@ -5,7 +5,10 @@
 
 use crate::compile::RuntimeCapabilityFlags;
 
-use super::{compile, CompileUnit, Error, PackageStore, SourceMap};
+use super::{
+    compile, compile_with_parse_cache, CompileUnit, Error, ErrorKind, PackageStore, ParseCache,
+    SourceMap,
+};
 use expect_test::expect;
 use indoc::indoc;
 use miette::Diagnostic;
@@ -19,6 +22,7 @@ use qsc_hir::{
     mut_visit::MutVisitor,
     ty::{Prim, Ty},
 };
+use std::sync::Arc;
 
 fn error_span(error: &Error) -> Span {
     let label = error
@@ -112,6 +116,36 @@ fn one_file_error() {
     assert_eq!(vec![("test", Span { lo: 50, hi: 51 })], errors);
 }
 
+#[test]
+fn top_level_stmts_are_parse_errors_unless_allowed() {
+    let source = indoc! {"
+        namespace Foo {
+            function A() : Unit {}
+        }
+        let x = 1;
+    "};
+    let sources = || SourceMap::new([("test".into(), source.into())], None);
+
+    let unit = default_compile(sources());
+    assert!(
+        matches!(&unit.errors[..], [Error(ErrorKind::Parse(_))]),
+        "top-level statement should be a parse error: {:?}",
+        unit.errors
+    );
+
+    let unit = compile_with_parse_cache(
+        &PackageStore::new(super::core()),
+        &[],
+        sources(),
+        &mut ParseCache::default(),
+        true,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    assert_eq!(unit.package.stmts.len(), 1);
+}
+
 #[test]
 fn two_files_dependency() {
     let sources = SourceMap::new(
@@ -256,6 +290,67 @@ fn entry_call_operation() {
     );
 }
 
+#[test]
+fn config_attr_selects_items_by_flags_and_capabilities() {
+    let source = indoc! {"
+        namespace Foo {
+            @Config(FastPath)
+            function Answer() : Int { 1 }
+            @Config(not FastPath)
+            function Answer() : Int { 2 }
+            @Config(IntegerComputations)
+            function Dynamic() : Unit {}
+        }
+    "};
+    let compile_with = |config: &[&str], capabilities| {
+        let sources = SourceMap::new([("test".into(), source.into())], None)
+            .with_config(config.iter().map(|flag| Arc::from(*flag)));
+        let unit = compile(
+            &PackageStore::new(super::core()),
+            &[],
+            sources,
+            capabilities,
+            LanguageFeatures::default(),
+        );
+        assert!(unit.errors.is_empty(), "{:#?}", unit.errors);
+        let mut included = Vec::new();
+        for item in unit.package.items.values() {
+            let ItemKind::Callable(decl) = &item.kind else {
+                continue;
+            };
+            let SpecBody::Impl(_, block) = &decl.body.body else {
+                continue;
+            };
+            match block.stmts.as_slice() {
+                [Stmt {
+                    kind: StmtKind::Expr(expr),
+                    ..
+                }] => {
+                    let ExprKind::Lit(Lit::Int(value)) = &expr.kind else {
+                        panic!("callable should return a literal");
+                    };
+                    included.push(format!("{} {value}", decl.name.name));
+                }
+                _ => included.push(decl.name.name.to_string()),
+            }
+        }
+        included
+    };
+
+    assert_eq!(
+        compile_with(&["FastPath"], RuntimeCapabilityFlags::all()),
+        ["Answer 1", "Dynamic"]
+    );
+    assert_eq!(
+        compile_with(&[], RuntimeCapabilityFlags::all()),
+        ["Answer 2", "Dynamic"]
+    );
+    assert_eq!(
+        compile_with(&["FastPath"], RuntimeCapabilityFlags::empty()),
+        ["Answer 1"]
+    );
+}
+
 #[test]
 fn entry_error() {
     let sources = SourceMap::new(
@@ -491,6 +586,221 @@ fn package_dependency() {
     .assert_eq(&unit2.package.to_string());
 }
 
+#[test]
+fn package_dependency_export_list() {
+    let mut store = PackageStore::new(super::core());
+
+    let sources1 = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Package1 {
+                    export Foo;
+
+                    function Foo() : Int {
+                        Helper()
+                    }
+
+                    function Helper() : Int {
+                        1
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit1 = compile(
+        &store,
+        &[],
+        sources1,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit1.errors.is_empty(), "{:#?}", unit1.errors);
+    let package1 = store.insert(unit1);
+
+    let sources2 = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Package2 {
+                    function Bar() : Int {
+                        Package1.Foo() + Package1.Helper()
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit2 = compile(
+        &store,
+        &[package1],
+        sources2,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+
+    let errors: Vec<_> = unit2
+        .errors
+        .iter()
+        .map(|error| source_span(&unit2.sources, error))
+        .collect();
+    assert_eq!(vec![("test", Span { lo: 82, hi: 88 }),], errors);
+    assert_eq!(
+        unit2.errors[0]
+            .code()
+            .expect("expected error code")
+            .to_string(),
+        "Qsc.Resolve.NotExported"
+    );
+    assert_eq!(
+        unit2.errors[0]
+            .help()
+            .expect("expected help")
+            .to_string(),
+        "found a matching item `Package1.Helper` that is not exported from the package that declares it"
+    );
+}
+
+#[test]
+fn package_dependency_unexported_item_from_unopened_namespace_is_not_found() {
+    let mut store = PackageStore::new(super::core());
+
+    let sources1 = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Package1 {
+                    export Foo;
+
+                    function Foo() : Int {
+                        Helper()
+                    }
+
+                    function Helper() : Int {
+                        1
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit1 = compile(
+        &store,
+        &[],
+        sources1,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit1.errors.is_empty(), "{:#?}", unit1.errors);
+    let package1 = store.insert(unit1);
+
+    let sources2 = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Package2 {
+                    function Bar() : Int {
+                        Helper()
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit2 = compile(
+        &store,
+        &[package1],
+        sources2,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+
+    let errors: Vec<_> = unit2
+        .errors
+        .iter()
+        .map(|error| source_span(&unit2.sources, error))
+        .collect();
+    assert_eq!(vec![("test", Span { lo: 56, hi: 62 }),], errors);
+    assert_eq!(
+        unit2.errors[0]
+            .code()
+            .expect("expected error code")
+            .to_string(),
+        "Qsc.Resolve.NotFound"
+    );
+}
+
+#[test]
+fn package_dependency_export_list_across_namespace_blocks() {
+    let mut store = PackageStore::new(super::core());
+
+    let sources1 = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Package1 {
+                    export Foo;
+                }
+
+                namespace Package1 {
+                    function Foo() : Int {
+                        Helper()
+                    }
+
+                    function Helper() : Int {
+                        1
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit1 = compile(
+        &store,
+        &[],
+        sources1,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit1.errors.is_empty(), "{:#?}", unit1.errors);
+    let package1 = store.insert(unit1);
+
+    let sources2 = SourceMap::new(
+        [(
+            "test".into(),
+            indoc! {"
+                namespace Package2 {
+                    function Bar() : Int {
+                        Package1.Foo() + Package1.Helper()
+                    }
+                }
+            "}
+            .into(),
+        )],
+        None,
+    );
+    let unit2 = compile(
+        &store,
+        &[package1],
+        sources2,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+
+    let errors: Vec<_> = unit2
+        .errors
+        .iter()
+        .map(|error| source_span(&unit2.sources, error))
+        .collect();
+    assert_eq!(vec![("test", Span { lo: 82, hi: 88 }),], errors);
+}
+
 #[test]
 fn package_dependency_internal_error() {
     let mut store = PackageStore::new(super::core());
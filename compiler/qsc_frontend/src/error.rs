@@ -6,6 +6,7 @@ mod tests;
 
 use crate::compile::{Source, SourceMap};
 use miette::{Diagnostic, MietteError, MietteSpanContents, SourceCode, SourceSpan, SpanContents};
+use qsc_data_structures::line_column::{Encoding, Position};
 use std::{
     error::Error,
     fmt::{self, Debug, Display, Formatter},
@@ -15,6 +16,7 @@ use std::{
 pub struct WithSource<E> {
     sources: Vec<Source>,
     error: E,
+    position_encoding: Encoding,
 }
 
 impl<E: Diagnostic + Send + Sync> WithSource<E> {
@@ -26,6 +28,14 @@ impl<E: Diagnostic + Send + Sync> WithSource<E> {
         self.error
     }
 
+    /// Sets how the columns of the rendered diagnostic are counted, which are utf-16
+    /// code units by default, to match editors.
+    #[must_use]
+    pub fn with_position_encoding(mut self, position_encoding: Encoding) -> Self {
+        self.position_encoding = position_encoding;
+        self
+    }
+
     /// Construct a diagnostic with source information from a source map.
     /// Since errors may contain labeled spans from any source file in the
     /// compilation, the entire source map is needed to resolve offsets.
@@ -54,6 +64,7 @@ impl<E: Diagnostic + Send + Sync> WithSource<E> {
         Self {
             sources: filtered,
             error,
+            position_encoding: Encoding::Utf16,
         }
     }
 
@@ -64,6 +75,7 @@ impl<E: Diagnostic + Send + Sync> WithSource<E> {
         WithSource {
             sources: self.sources,
             error: self.error.into(),
+            position_encoding: self.position_encoding,
         }
     }
 
@@ -143,12 +155,21 @@ impl<E: Diagnostic + Sync + Send> SourceCode for WithSource<E> {
             context_lines_after,
         )?;
 
+        // The column from miette counts bytes, which doesn't match what editors
+        // and notebooks show for lines with non-ASCII characters.
+        let column = Position::from_utf8_byte_offset(
+            self.position_encoding,
+            &source.contents,
+            u32::try_from(contents.span().offset()).expect("offset should fit into u32"),
+        )
+        .column;
+
         Ok(Box::new(MietteSpanContents::new_named(
             source.name.to_string(),
             contents.data(),
             with_offset(contents.span(), |o| o + (source.offset as usize)),
             contents.line(),
-            contents.column(),
+            column as usize,
             contents.line_count(),
         )))
     }
@@ -7,7 +7,7 @@ use super::WithSource;
 use crate::compile::SourceMap;
 use expect_test::expect;
 use miette::Diagnostic;
-use qsc_data_structures::span::Span;
+use qsc_data_structures::{line_column::Encoding, span::Span};
 use std::{error::Error, fmt::Write, iter, str::from_utf8};
 use thiserror::Error;
 
@@ -164,6 +164,38 @@ fn resolve_spans() {
     .assert_debug_eq(&resolved_spans);
 }
 
+#[test]
+fn label_columns_follow_position_encoding() {
+    let contents = "\"中é\" + y";
+    let mut sources = SourceMap::default();
+    let offset = sources.push("test.qs".into(), contents.into());
+    let span = span_with_offset(offset, 10, 11);
+    let error = WithSource::from_map(&sources, TestError::TwoSpans("value".into(), span, span));
+
+    assert_eq!(label_column(&error), 7);
+    assert_eq!(
+        label_column(&error.clone().with_position_encoding(Encoding::Display)),
+        8
+    );
+    assert_eq!(
+        label_column(&error.with_position_encoding(Encoding::Utf8)),
+        10
+    );
+}
+
+fn label_column(error: &WithSource<TestError>) -> usize {
+    let label = error
+        .labels()
+        .and_then(|mut labels| labels.next())
+        .expect("expected labels to exist");
+    error
+        .source_code()
+        .expect("expected valid source code")
+        .read_span(label.inner(), 0, 0)
+        .expect("expected to be able to read span")
+        .column()
+}
+
 fn span_with_offset(offset: u32, lo: u32, hi: u32) -> Span {
     Span {
         lo: lo + offset,
@@ -28,7 +28,7 @@ use qsc_hir::{
     validate::Validator as HirValidator,
     visit::Visitor as HirVisitor,
 };
-use std::mem::take;
+use std::{mem::take, sync::Arc};
 
 /// The frontend for an incremental compiler.
 /// It is used to update a single `CompileUnit`
@@ -40,6 +40,8 @@ pub struct Compiler {
     lowerer: Lowerer,
     capabilities: RuntimeCapabilityFlags,
     language_features: LanguageFeatures,
+    /// The user-defined flags that `@Config` attributes in the fragments can test for.
+    config: Vec<Arc<str>>,
 }
 
 pub type Error = WithSource<compile::Error>;
@@ -86,9 +88,18 @@ impl Compiler {
             lowerer: Lowerer::new(),
             capabilities,
             language_features,
+            config: Vec::new(),
         }
     }
 
+    /// Sets the user-defined flags that `@Config` attributes in the fragments can test for,
+    /// which are usually the ones the sources of the package were compiled with.
+    #[must_use]
+    pub fn with_config(mut self, config: impl IntoIterator<Item = Arc<str>>) -> Self {
+        self.config = config.into_iter().collect();
+        self
+    }
+
     /// Compiles Q# fragments.
     ///
     /// Uses the assigners and other mutable state from the passed in
@@ -198,7 +209,8 @@ impl Compiler {
         unit: &mut CompileUnit,
         ast: &mut ast::Package,
     ) -> (hir::Package, Vec<Error>) {
-        let mut cond_compile = preprocess::Conditional::new(self.capabilities);
+        let mut cond_compile =
+            preprocess::Conditional::new(self.capabilities).with_config(&self.config);
         cond_compile.visit_package(ast);
 
         self.ast_assigner.visit_package(ast);
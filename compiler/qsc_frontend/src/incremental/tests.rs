@@ -100,6 +100,8 @@ fn one_callable() {
                         ty_vars: {},
                     },
                 ],
+                used_opens: {},
+                shadowing_aliases: {},
             }
             hir:
             Package:
@@ -174,6 +176,8 @@ fn one_statement() {
                         ty_vars: {},
                     },
                 ],
+                used_opens: {},
+                shadowing_aliases: {},
             }
             hir:
             Package:
@@ -223,6 +227,7 @@ fn parse_error() {
                         ),
                     ),
                 ),
+                position_encoding: Utf16,
             },
         ]
     "#]]
@@ -378,6 +383,7 @@ fn continue_after_parse_error() {
                         ),
                     ),
                 ),
+                position_encoding: Utf16,
             },
             WithSource {
                 sources: [
@@ -398,6 +404,7 @@ fn continue_after_parse_error() {
                         ),
                     ),
                 ),
+                position_encoding: Utf16,
             },
         ]
     "#]]
@@ -451,6 +458,7 @@ fn continue_after_lower_error() {
                         ),
                     ),
                 ),
+                position_encoding: Utf16,
             },
         ]
     "#]].assert_debug_eq(&errors);
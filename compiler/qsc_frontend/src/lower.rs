@@ -19,13 +19,14 @@ use qsc_hir::{
     mut_visit::MutVisitor,
     ty::{Arrow, FunctorSetValue, Ty},
 };
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::{clone::Clone, rc::Rc, str::FromStr, vec};
 use thiserror::Error;
 
 #[derive(Clone, Debug, Diagnostic, Error)]
 pub(super) enum Error {
     #[error("unknown attribute {0}")]
-    #[diagnostic(help("supported attributes are: EntryPoint, Config"))]
+    #[diagnostic(help("supported attributes are: EntryPoint, Config, Test, CustomIntrinsic"))]
     #[diagnostic(code("Qsc.LowerAst.UnknownAttr"))]
     UnknownAttr(String, #[label] Span),
     #[error("invalid attribute arguments: expected {0}")]
@@ -55,6 +56,8 @@ pub(super) struct Lowerer {
     nodes: IndexMap<ast::NodeId, hir::NodeId>,
     locals: IndexMap<hir::NodeId, (hir::Ident, Ty)>,
     parent: Option<LocalItemId>,
+    /// The items exported by the namespace being lowered, if any of its blocks have export declarations.
+    exports: Option<FxHashSet<LocalItemId>>,
     items: Vec<hir::Item>,
     errors: Vec<Error>,
 }
@@ -65,6 +68,7 @@ impl Lowerer {
             nodes: IndexMap::new(),
             locals: IndexMap::new(),
             parent: None,
+            exports: None,
             items: Vec::new(),
             errors: Vec::new(),
         }
@@ -102,10 +106,14 @@ pub(super) struct With<'a> {
 
 impl With<'_> {
     pub(super) fn lower_package(&mut self, package: &ast::Package) -> hir::Package {
+        let exports = self.exports(&package.nodes);
         let mut stmts = Vec::new();
         for node in &*package.nodes {
             match node {
-                ast::TopLevelNode::Namespace(namespace) => self.lower_namespace(namespace),
+                ast::TopLevelNode::Namespace(namespace) => {
+                    let exports = exports.get(&namespace.name.name).cloned();
+                    self.lower_namespace(namespace, exports);
+                }
                 ast::TopLevelNode::Stmt(stmt) => {
                     if let Some(stmt) = self.lower_stmt(stmt) {
                         stmts.push(stmt);
@@ -123,7 +131,11 @@ impl With<'_> {
         }
     }
 
-    pub(super) fn lower_namespace(&mut self, namespace: &ast::Namespace) {
+    pub(super) fn lower_namespace(
+        &mut self,
+        namespace: &ast::Namespace,
+        exports: Option<FxHashSet<LocalItemId>>,
+    ) {
         let Some(&resolve::Res::Item(hir::ItemId { item: id, .. }, _)) =
             self.names.get(namespace.name.id)
         else {
@@ -131,6 +143,7 @@ impl With<'_> {
         };
 
         self.lowerer.parent = Some(id);
+        self.lowerer.exports = exports;
         let items = namespace
             .items
             .iter()
@@ -149,6 +162,30 @@ impl With<'_> {
         });
 
         self.lowerer.parent = None;
+        self.lowerer.exports = None;
+    }
+
+    /// The items named by the export declarations of each namespace, across all of the blocks that declare a
+    /// namespace with the same name. A namespace without export declarations isn't in the map, and exports all of its
+    /// public items.
+    fn exports(&self, nodes: &[ast::TopLevelNode]) -> FxHashMap<Rc<str>, FxHashSet<LocalItemId>> {
+        let mut exports = FxHashMap::<_, FxHashSet<_>>::default();
+        for node in nodes {
+            let ast::TopLevelNode::Namespace(namespace) = node else {
+                continue;
+            };
+            for item in &*namespace.items {
+                if let ast::ItemKind::Export(names) = &*item.kind {
+                    let exports = exports.entry(Rc::clone(&namespace.name.name)).or_default();
+                    for name in &**names {
+                        if let Some(&resolve::Res::Item(id, _)) = self.names.get(name.id) {
+                            exports.insert(id.item);
+                        }
+                    }
+                }
+            }
+        }
+        exports
     }
 
     fn lower_item(&mut self, scope: ItemScope, item: &ast::Item) -> Option<LocalItemId> {
@@ -158,7 +195,7 @@ impl With<'_> {
             .filter_map(|a| self.lower_attr(a))
             .collect();
 
-        let visibility = match scope {
+        let mut visibility = match scope {
             ItemScope::Global => item
                 .visibility
                 .as_ref()
@@ -172,7 +209,9 @@ impl With<'_> {
         };
 
         let (id, kind) = match &*item.kind {
-            ast::ItemKind::Err | ast::ItemKind::Open(..) => return None,
+            ast::ItemKind::Err | ast::ItemKind::Open(..) | ast::ItemKind::Export(..) => {
+                return None
+            }
             ast::ItemKind::Callable(callable) => {
                 let id = resolve_id(callable.name.id);
                 let grandparent = self.lowerer.parent;
@@ -193,6 +232,12 @@ impl With<'_> {
             }
         };
 
+        if let (ItemScope::Global, Some(exports)) = (scope, &self.lowerer.exports) {
+            if !exports.contains(&id.item) {
+                visibility = hir::Visibility::Internal;
+            }
+        }
+
         self.lowerer.items.push(hir::Item {
             id: id.item,
             span: item.span,
@@ -226,13 +271,51 @@ impl With<'_> {
                     None
                 }
             },
+            Ok(hir::Attr::Test) => match &*attr.arg.kind {
+                ast::ExprKind::Tuple(args) if args.is_empty() => Some(hir::Attr::Test),
+                _ => {
+                    self.lowerer
+                        .errors
+                        .push(Error::InvalidAttrArgs("()", attr.arg.span));
+                    None
+                }
+            },
+            Ok(hir::Attr::CustomIntrinsic(_)) => {
+                let intrinsic = lower_custom_intrinsic_args(&attr.arg);
+                if intrinsic.is_none() {
+                    self.lowerer.errors.push(Error::InvalidAttrArgs(
+                        "a QIR name, optionally followed by a circuit glyph and \"Base\" or \"Unrestricted\"",
+                        attr.arg.span,
+                    ));
+                }
+                intrinsic.map(hir::Attr::CustomIntrinsic)
+            }
+            Ok(hir::Attr::Deprecated(_)) => {
+                let note = lower_deprecated_args(&attr.arg);
+                if note.is_none() {
+                    self.lowerer.errors.push(Error::InvalidAttrArgs(
+                        "a string that suggests what to use instead",
+                        attr.arg.span,
+                    ));
+                }
+                note.map(hir::Attr::Deprecated)
+            }
+            Ok(hir::Attr::TargetProfile(_)) => {
+                let profile = lower_target_profile_args(&attr.arg);
+                if profile.is_none() {
+                    self.lowerer.errors.push(Error::InvalidAttrArgs(
+                        "\"Base\" or \"Unrestricted\"",
+                        attr.arg.span,
+                    ));
+                }
+                profile.map(hir::Attr::TargetProfile)
+            }
             Ok(hir::Attr::Config) => {
                 if !matches!(attr.arg.kind.as_ref(), ast::ExprKind::Paren(inner)
-                    if matches!(inner.kind.as_ref(), ast::ExprKind::Path(path)
-                        if ConfigAttr::from_str(path.name.name.as_ref()).is_ok()))
+                    if is_config_expr(inner))
                 {
                     self.lowerer.errors.push(Error::InvalidAttrArgs(
-                        "Unrestricted or Base",
+                        "a profile, a runtime capability or a configuration flag, optionally negated with `not`",
                         attr.arg.span,
                     ));
                 }
@@ -746,6 +829,81 @@ fn lower_visibility(visibility: &ast::Visibility) -> hir::Visibility {
     }
 }
 
+/// Lowers the arguments of a `@CustomIntrinsic` attribute, which are string literals for the QIR
+/// name, the circuit glyph and the capability the intrinsic needs, of which only the first is
+/// required.
+fn lower_custom_intrinsic_args(arg: &ast::Expr) -> Option<hir::CustomIntrinsic> {
+    let args = match &*arg.kind {
+        ast::ExprKind::Paren(inner) => vec![&**inner],
+        ast::ExprKind::Tuple(items) => items.iter().map(|item| &**item).collect(),
+        _ => return None,
+    };
+    let strings = args
+        .into_iter()
+        .map(|arg| match &*arg.kind {
+            ast::ExprKind::Lit(lit) => match &**lit {
+                ast::Lit::String(value) => Some(Rc::clone(value)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let (qir_name, rest) = strings.split_first()?;
+    if qir_name.is_empty() || rest.len() > 2 {
+        return None;
+    }
+    let unrestricted = match rest.get(1) {
+        None => false,
+        Some(capability) => ConfigAttr::from_str(capability).ok()? == ConfigAttr::Unrestricted,
+    };
+    Some(hir::CustomIntrinsic {
+        qir_name: Rc::clone(qir_name),
+        glyph: rest.first().cloned(),
+        unrestricted,
+    })
+}
+
+fn lower_deprecated_args(arg: &ast::Expr) -> Option<Rc<str>> {
+    match &*arg.kind {
+        ast::ExprKind::Paren(inner) => match &*inner.kind {
+            ast::ExprKind::Lit(lit) => match &**lit {
+                ast::Lit::String(note) if !note.is_empty() => Some(Rc::clone(note)),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether an expression is a valid argument of the `@Config` attribute: a name without a namespace,
+/// optionally negated with `not`.
+fn is_config_expr(expr: &ast::Expr) -> bool {
+    match &*expr.kind {
+        ast::ExprKind::Paren(inner) | ast::ExprKind::UnOp(ast::UnOp::NotL, inner) => {
+            is_config_expr(inner)
+        }
+        ast::ExprKind::Path(path) => path.namespace.is_none(),
+        _ => false,
+    }
+}
+
+fn lower_target_profile_args(arg: &ast::Expr) -> Option<hir::TargetProfile> {
+    match &*arg.kind {
+        ast::ExprKind::Paren(inner) => match &*inner.kind {
+            ast::ExprKind::Lit(lit) => match &**lit {
+                ast::Lit::String(profile) => match ConfigAttr::from_str(profile).ok()? {
+                    ConfigAttr::Base => Some(hir::TargetProfile::Base),
+                    ConfigAttr::Unrestricted => Some(hir::TargetProfile::Unrestricted),
+                },
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 fn lower_callable_kind(kind: ast::CallableKind) -> hir::CallableKind {
     match kind {
         ast::CallableKind::Function => hir::CallableKind::Function,
@@ -89,6 +89,124 @@ fn test_entrypoint_attr_wrong_args() {
     );
 }
 
+#[test]
+fn test_custom_intrinsic_attr_allowed() {
+    check_errors(
+        indoc! {r#"
+            namespace input {
+                @CustomIntrinsic("__quantum__qis__pulse__body", "Pulse", "Unrestricted")
+                operation Foo(q : Qubit) : Unit {
+                    body intrinsic;
+                }
+            }
+        "#},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn test_custom_intrinsic_attr_wrong_args() {
+    check_errors(
+        indoc! {"
+            namespace input {
+                @CustomIntrinsic(1)
+                operation Foo(q : Qubit) : Unit {
+                    body intrinsic;
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                InvalidAttrArgs(
+                    "a QIR name, optionally followed by a circuit glyph and \"Base\" or \"Unrestricted\"",
+                    Span {
+                        lo: 38,
+                        hi: 41,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn test_deprecated_attr_allowed() {
+    check_errors(
+        indoc! {r#"
+            namespace input {
+                @Deprecated("use Bar instead")
+                operation Foo() : Unit {}
+            }
+        "#},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn test_deprecated_attr_wrong_args() {
+    check_errors(
+        indoc! {"
+            namespace input {
+                @Deprecated(1)
+                operation Foo() : Unit {}
+            }
+        "},
+        &expect![[r#"
+            [
+                InvalidAttrArgs(
+                    "a string that suggests what to use instead",
+                    Span {
+                        lo: 33,
+                        hi: 36,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn test_declared_target_profile_attr_allowed() {
+    check_errors(
+        indoc! {r#"
+            namespace input {
+                @TargetProfile("Base")
+                operation Foo() : Unit {}
+            }
+        "#},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn test_declared_target_profile_attr_unknown_profile() {
+    check_errors(
+        indoc! {r#"
+            namespace input {
+                @TargetProfile("Adaptive")
+                operation Foo() : Unit {}
+            }
+        "#},
+        &expect![[r#"
+            [
+                InvalidAttrArgs(
+                    "\"Base\" or \"Unrestricted\"",
+                    Span {
+                        lo: 36,
+                        hi: 48,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
 #[test]
 fn test_target_profile_base_attr_allowed() {
     check_errors(
@@ -128,7 +246,7 @@ fn test_target_profile_attr_wrong_args() {
     check_errors(
         indoc! {"
             namespace input {
-                @Config(Bar)
+                @Config(1)
                 operation Foo() : Unit {
                     body ... {}
                 }
@@ -137,10 +255,10 @@ fn test_target_profile_attr_wrong_args() {
         &expect![[r#"
             [
                 InvalidAttrArgs(
-                    "Unrestricted or Base",
+                    "a profile, a runtime capability or a configuration flag, optionally negated with `not`",
                     Span {
                         lo: 29,
-                        hi: 34,
+                        hi: 32,
                     },
                 ),
             ]
@@ -148,6 +266,25 @@ fn test_target_profile_attr_wrong_args() {
     );
 }
 
+#[test]
+fn test_config_attr_with_flags_allowed() {
+    check_errors(
+        indoc! {"
+            namespace input {
+                @Config(not Base)
+                operation Foo() : Unit {}
+                @Config(IntegerComputations)
+                operation Bar() : Unit {}
+                @Config(FastMath)
+                operation Baz() : Unit {}
+            }
+        "},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
 #[test]
 fn test_unknown_attr() {
     check_errors(
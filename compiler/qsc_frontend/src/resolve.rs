@@ -92,6 +92,11 @@ pub(super) enum Error {
     #[diagnostic(code("Qsc.Resolve.DuplicateIntrinsic"))]
     DuplicateIntrinsic(String, #[label] Span),
 
+    #[error("exported item `{0}` is not declared in namespace `{1}`")]
+    #[diagnostic(help("only the items declared in a namespace can be exported from it"))]
+    #[diagnostic(code("Qsc.Resolve.ExportNotFound"))]
+    ExportNotFound(String, String, #[label] Span),
+
     #[error("`{0}` not found")]
     #[diagnostic(code("Qsc.Resolve.NotFound"))]
     NotFound(String, #[label] Span),
@@ -103,6 +108,13 @@ pub(super) enum Error {
     #[diagnostic(code("Qsc.Resolve.NotFound"))]
     NotAvailable(String, String, #[label] Span),
 
+    #[error("`{0}` not found")]
+    #[diagnostic(help(
+        "found a matching item `{1}` that is not exported from the package that declares it"
+    ))]
+    #[diagnostic(code("Qsc.Resolve.NotExported"))]
+    NotExported(String, String, #[label] Span),
+
     #[error("use of unimplemented item `{0}`")]
     #[diagnostic(help("this item is not implemented and cannot be used"))]
     #[diagnostic(code("Qsc.Resolve.Unimplemented"))]
@@ -161,9 +173,27 @@ type ScopeId = usize;
 pub struct Locals {
     // order is ascending by span (outermost -> innermost)
     scopes: Vec<Scope>,
+    /// The spans of the namespace names of the open statements that names were resolved through.
+    used_opens: FxHashSet<Span>,
+    /// The spans of the aliases of open statements that have the name of another namespace.
+    shadowing_aliases: FxHashSet<Span>,
 }
 
 impl Locals {
+    /// Whether any name was resolved through the open statement whose namespace name has the
+    /// given span. Opens that are shadowed by a later open of the same namespace are unused.
+    #[must_use]
+    pub fn is_open_used(&self, namespace_span: Span) -> bool {
+        self.used_opens.contains(&namespace_span)
+    }
+
+    /// Whether the open statement alias with the given span has the name of a namespace other
+    /// than the one it opens, so that paths qualified with it no longer refer to that namespace.
+    #[must_use]
+    pub fn is_alias_shadowing(&self, alias_span: Span) -> bool {
+        self.shadowing_aliases.contains(&alias_span)
+    }
+
     fn get_scopes<'a>(&'a self, scope_chain: &'a [ScopeId]) -> impl Iterator<Item = &Scope> + 'a {
         // reverse to go from innermost -> outermost
         scope_chain.iter().rev().map(|id| {
@@ -242,6 +272,9 @@ pub struct GlobalScope {
     terms: FxHashMap<Rc<str>, FxHashMap<Rc<str>, Res>>,
     namespaces: FxHashSet<Rc<str>>,
     intrinsics: FxHashSet<Rc<str>>,
+    /// Items of dependency packages that are internal to their package, kept only to report
+    /// uses of them.
+    unexported: Vec<TrackedName>,
 }
 
 impl GlobalScope {
@@ -358,15 +391,27 @@ impl Resolver {
                         &mut self.errors,
                     );
                 }
-                ast::TopLevelNode::Stmt(stmt) => {
-                    if let ast::StmtKind::Item(item) = stmt.kind.as_ref() {
-                        self.bind_local_item(assigner, item);
-                    }
-                }
+                ast::TopLevelNode::Stmt(stmt) => self.bind_top_level_item(assigner, stmt),
             }
         }
     }
 
+    /// Binds the items declared by top-level statements, for a package whose namespaces
+    /// have already been bound by the [`GlobalTable`].
+    pub(super) fn bind_top_level_items(&mut self, ast: &ast::Package, assigner: &mut Assigner) {
+        for node in &*ast.nodes {
+            if let ast::TopLevelNode::Stmt(stmt) = node {
+                self.bind_top_level_item(assigner, stmt);
+            }
+        }
+    }
+
+    fn bind_top_level_item(&mut self, assigner: &mut Assigner, stmt: &ast::Stmt) {
+        if let ast::StmtKind::Item(item) = stmt.kind.as_ref() {
+            self.bind_local_item(assigner, item);
+        }
+    }
+
     fn check_item_status(&mut self, res: Res, name: String, span: Span) {
         if let Res::Item(_, ItemStatus::Unimplemented) = res {
             self.errors.push(Error::Unimplemented(name, span));
@@ -376,14 +421,15 @@ impl Resolver {
     fn resolve_ident(&mut self, kind: NameKind, name: &Ident) {
         let namespace = None;
 
-        match resolve(
+        match resolve_with_open(
             kind,
             &self.globals,
             self.locals.get_scopes(&self.curr_scope_chain),
             name,
             &namespace,
         ) {
-            Ok(res) => {
+            Ok((res, open)) => {
+                self.locals.used_opens.extend(open);
                 self.check_item_status(res, name.name.to_string(), name.span);
                 self.names.insert(name.id, res);
             }
@@ -395,14 +441,15 @@ impl Resolver {
         let name = &path.name;
         let namespace = &path.namespace;
 
-        match resolve(
+        match resolve_with_open(
             kind,
             &self.globals,
             self.locals.get_scopes(&self.curr_scope_chain),
             name,
             namespace,
         ) {
-            Ok(res) => {
+            Ok((res, open)) => {
+                self.locals.used_opens.extend(open);
                 self.check_item_status(res, path.name.name.to_string(), path.span);
                 self.names.insert(path.id, res);
             }
@@ -416,6 +463,11 @@ impl Resolver {
                             format!("{}.{}", dropped_name.namespace, dropped_name.name),
                             span,
                         ));
+                    } else if let Some(unexported) = self
+                        .find_unexported(&name, namespace.as_deref())
+                        .map(|n| format!("{}.{}", n.namespace, n.name))
+                    {
+                        self.errors.push(Error::NotExported(name, unexported, span));
                     } else {
                         self.errors.push(Error::NotFound(name, span));
                     }
@@ -426,6 +478,24 @@ impl Resolver {
         }
     }
 
+    /// Finds the internal item of a dependency that a name that wasn't found refers to, if the
+    /// name is qualified with the namespace of the item or the namespace is opened. Internal items
+    /// of other namespaces, such as the helpers of the standard library, aren't considered, so that
+    /// a typo isn't reported as a use of one of them.
+    fn find_unexported(&self, name: &str, namespace: Option<&ast::Ident>) -> Option<&TrackedName> {
+        let key = namespace.map_or("", |ns| ns.name.as_ref());
+        self.globals.unexported.iter().find(|n| {
+            n.name.as_ref() == name
+                && (namespace.is_some_and(|ns| ns.name == n.namespace)
+                    || self
+                        .locals
+                        .get_scopes(&self.curr_scope_chain)
+                        .filter_map(|scope| scope.opens.get(key))
+                        .flatten()
+                        .any(|open| open.namespace == n.namespace))
+        })
+    }
+
     /// # Arguments
     ///
     /// * `pat` - The pattern to bind.
@@ -464,6 +534,11 @@ impl Resolver {
     }
 
     fn bind_open(&mut self, name: &ast::Ident, alias: &Option<Box<ast::Ident>>) {
+        if let Some(alias) = alias {
+            if alias.name != name.name && self.globals.namespaces.contains(&alias.name) {
+                self.locals.shadowing_aliases.insert(alias.span);
+            }
+        }
         let alias = alias.as_ref().map_or("".into(), |a| Rc::clone(&a.name));
         if self.globals.namespaces.contains(&name.name) {
             self.current_scope_mut()
@@ -480,6 +555,26 @@ impl Resolver {
         }
     }
 
+    /// Binds the names of an export declaration to the items of the namespace they refer to.
+    fn bind_export(&mut self, namespace: &Rc<str>, names: &[Box<ast::Ident>]) {
+        for name in names {
+            let res = [&self.globals.terms, &self.globals.tys]
+                .into_iter()
+                .find_map(|globals| globals.get(namespace)?.get(&name.name))
+                .filter(|res| matches!(res, Res::Item(ItemId { package: None, .. }, _)));
+            match res {
+                Some(&res) => {
+                    self.names.insert(name.id, res);
+                }
+                None => self.errors.push(Error::ExportNotFound(
+                    name.name.to_string(),
+                    namespace.to_string(),
+                    name.span,
+                )),
+            }
+        }
+    }
+
     pub(super) fn bind_local_item(&mut self, assigner: &mut Assigner, item: &ast::Item) {
         match &*item.kind {
             ast::ItemKind::Open(name, alias) => self.bind_open(name, alias),
@@ -509,7 +604,8 @@ impl Resolver {
                 scope.tys.insert(Rc::clone(&name.name), id);
                 scope.terms.insert(Rc::clone(&name.name), id);
             }
-            ast::ItemKind::Err => {}
+            // Export declarations are only parsed as namespace items.
+            ast::ItemKind::Err | ast::ItemKind::Export(_) => {}
         }
     }
 
@@ -596,8 +692,12 @@ impl AstVisitor<'_> for With<'_> {
         let kind = ScopeKind::Namespace(Rc::clone(&namespace.name.name));
         self.with_scope(namespace.span, kind, |visitor| {
             for item in &*namespace.items {
-                if let ast::ItemKind::Open(name, alias) = &*item.kind {
-                    visitor.resolver.bind_open(name, alias);
+                match &*item.kind {
+                    ast::ItemKind::Open(name, alias) => visitor.resolver.bind_open(name, alias),
+                    ast::ItemKind::Export(names) => {
+                        visitor.resolver.bind_export(&namespace.name.name, names);
+                    }
+                    _ => {}
                 }
             }
 
@@ -766,6 +866,7 @@ impl GlobalTable {
                 terms: FxHashMap::default(),
                 namespaces: FxHashSet::default(),
                 intrinsics: FxHashSet::default(),
+                unexported: Vec::new(),
             },
         }
     }
@@ -787,19 +888,16 @@ impl GlobalTable {
                         &mut errors,
                     );
                 }
-                TopLevelNode::Stmt(_) => {
-                    unimplemented!("did not expect top-level statements in the ast")
-                }
+                // Top-level statements are resolved along with the package, so there are no
+                // global items to bind for them.
+                TopLevelNode::Stmt(_) => {}
             }
         }
         errors
     }
 
     pub(super) fn add_external_package(&mut self, id: PackageId, package: &hir::Package) {
-        for global in global::iter_package(Some(id), package).filter(|global| {
-            global.visibility == hir::Visibility::Public
-                || matches!(&global.kind, global::Kind::Term(t) if t.intrinsic)
-        }) {
+        for global in global::iter_package(Some(id), package) {
             match (global.kind, global.visibility) {
                 (global::Kind::Ty(ty), hir::Visibility::Public) => {
                     self.scope
@@ -815,6 +913,11 @@ impl GlobalTable {
                             .entry(global.namespace)
                             .or_default()
                             .insert(global.name.clone(), Res::Item(term.id, global.status));
+                    } else {
+                        self.scope.unexported.push(TrackedName {
+                            name: global.name.clone(),
+                            namespace: global.namespace,
+                        });
                     }
                     if term.intrinsic {
                         self.scope.intrinsics.insert(global.name);
@@ -823,7 +926,13 @@ impl GlobalTable {
                 (global::Kind::Namespace, hir::Visibility::Public) => {
                     self.scope.namespaces.insert(global.name);
                 }
-                (_, hir::Visibility::Internal) => {}
+                (global::Kind::Ty(_), hir::Visibility::Internal) => {
+                    self.scope.unexported.push(TrackedName {
+                        name: global.name,
+                        namespace: global.namespace,
+                    });
+                }
+                (global::Kind::Namespace, hir::Visibility::Internal) => {}
             }
         }
     }
@@ -970,7 +1079,7 @@ fn bind_global_item(
                 }
             }
         }
-        ast::ItemKind::Err | ast::ItemKind::Open(..) => Ok(()),
+        ast::ItemKind::Err | ast::ItemKind::Open(..) | ast::ItemKind::Export(..) => Ok(()),
     }
 }
 
@@ -991,6 +1100,18 @@ fn resolve<'a>(
     name: &Ident,
     namespace: &Option<Box<Ident>>,
 ) -> Result<Res, Error> {
+    resolve_with_open(kind, globals, scopes, name, namespace).map(|(res, _)| res)
+}
+
+/// Like [`resolve`], but also returns the span of the namespace name of the open statement that the
+/// name was resolved through, if any.
+fn resolve_with_open<'a>(
+    kind: NameKind,
+    globals: &GlobalScope,
+    scopes: impl Iterator<Item = &'a Scope>,
+    name: &Ident,
+    namespace: &Option<Box<Ident>>,
+) -> Result<(Res, Option<Span>), Error> {
     let scopes = scopes.collect::<Vec<_>>();
     let mut candidates = FxHashMap::default();
     let mut vars = true;
@@ -1000,7 +1121,7 @@ fn resolve<'a>(
         if namespace.is_empty() {
             if let Some(res) = resolve_scope_locals(kind, globals, scope, vars, name_str) {
                 // Local declarations shadow everything.
-                return Ok(res);
+                return Ok((res, None));
             }
         }
 
@@ -1041,14 +1162,14 @@ fn resolve<'a>(
             });
         }
         if let Some((res, _)) = single(candidates) {
-            return Ok(res);
+            return Ok((res, None));
         }
     }
 
     if candidates.is_empty() {
         if let Some(&res) = globals.get(kind, namespace, name_str) {
             // An unopened global is the last resort.
-            return Ok(res);
+            return Ok((res, None));
         }
     }
 
@@ -1079,7 +1200,8 @@ fn resolve<'a>(
             second_open_span: opens[1].span,
         })
     } else {
-        single(candidates.into_keys())
+        single(candidates)
+            .map(|(res, open)| (res, Some(open.span)))
             .ok_or_else(|| Error::NotFound(name_str.to_string(), name.span))
     }
 }
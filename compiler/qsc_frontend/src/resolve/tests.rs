@@ -13,7 +13,7 @@ use expect_test::{expect, Expect};
 use indoc::indoc;
 use qsc_ast::{
     assigner::Assigner as AstAssigner,
-    ast::{Ident, NodeId, Package, Path, TopLevelNode},
+    ast::{Ident, ItemKind, NodeId, Package, Path, TopLevelNode},
     mut_visit::MutVisitor,
     visit::{self, Visitor},
 };
@@ -186,6 +186,82 @@ fn global_callable_internal() {
     );
 }
 
+#[test]
+fn export_list() {
+    check(
+        indoc! {"
+            namespace Foo {
+                export A, B;
+
+                function A() : Unit {}
+                newtype B = Int;
+                function C() : Unit {}
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                export item1, item2;
+
+                function item1() : Unit {}
+                newtype item2 = Int;
+                function item3() : Unit {}
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn export_not_found_error() {
+    check(
+        indoc! {"
+            namespace Foo {
+                export A, B;
+
+                function A() : Unit {}
+            }
+
+            namespace Bar {
+                function B() : Unit {}
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                export item1, B;
+
+                function item1() : Unit {}
+            }
+
+            namespace item2 {
+                function item3() : Unit {}
+            }
+
+            // ExportNotFound("B", "Foo", Span { lo: 30, hi: 31 })
+        "#]],
+    );
+}
+
+#[test]
+fn export_as_local_name() {
+    check(
+        indoc! {"
+            namespace Foo {
+                function A() : Int {
+                    let export = 0;
+                    export
+                }
+            }
+        "},
+        &expect![[r#"
+            namespace item0 {
+                function item1() : Int {
+                    let local13 = 0;
+                    local13
+                }
+            }
+        "#]],
+    );
+}
+
 #[test]
 fn global_callable_duplicate_error() {
     check(
@@ -2654,3 +2730,52 @@ fn newtype_with_tuple_destructuring() {
         "#]],
     );
 }
+
+#[test]
+fn opens_used_by_resolved_names() {
+    let input = indoc! {"
+        namespace Foo {
+            function A() : Unit {}
+        }
+
+        namespace Bar {
+            function B() : Unit {}
+        }
+
+        namespace Baz {
+            open Foo;
+            open Bar;
+            open Foo as F;
+
+            function C() : Unit {
+                A();
+                F.A();
+            }
+        }
+    "};
+    let (package, _, locals, errors) = compile(input, LanguageFeatures::default());
+    assert!(errors.is_empty(), "{errors:?}");
+    let TopLevelNode::Namespace(namespace) = &package.nodes[2] else {
+        panic!("third node should be a namespace");
+    };
+    let used: Vec<_> = namespace
+        .items
+        .iter()
+        .filter_map(|item| match &*item.kind {
+            ItemKind::Open(name, alias) => Some((
+                name.name.to_string(),
+                alias.as_ref().map(|alias| alias.name.to_string()),
+                locals.is_open_used(name.span),
+            )),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        used,
+        [
+            ("Foo".to_string(), None, true),
+            ("Bar".to_string(), None, false),
+            ("Foo".to_string(), Some("F".to_string()), true),
+        ]
+    );
+}
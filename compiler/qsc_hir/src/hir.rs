@@ -1161,6 +1161,39 @@ pub enum Attr {
     EntryPoint,
     /// Indicates that an item does not have an implementation available for use.
     Unimplemented,
+    /// Indicates that a callable is a test, which is run by the test runner and passes if it
+    /// returns without failing.
+    Test,
+    /// Indicates that an intrinsic callable is implemented by the target, with the details of how
+    /// it is emitted.
+    CustomIntrinsic(CustomIntrinsic),
+    /// Indicates that a callable is deprecated, with a note that suggests what to use instead.
+    Deprecated(Rc<str>),
+    /// Indicates that a callable must be able to run on targets with the given profile, which the
+    /// compiler checks with the runtime capabilities analysis.
+    TargetProfile(TargetProfile),
+}
+
+/// The target profile that a callable with the `@TargetProfile` attribute declares it runs on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TargetProfile {
+    /// Targets that can only execute quantum operations on statically allocated qubits.
+    Base,
+    /// Targets with every runtime capability.
+    #[default]
+    Unrestricted,
+}
+
+/// How an intrinsic callable with the `@CustomIntrinsic` attribute is emitted for the target,
+/// such as a pulse-level operation that the standard library has no equivalent of.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CustomIntrinsic {
+    /// The name of the function that the intrinsic is declared and called as in QIR.
+    pub qir_name: Rc<str>,
+    /// The label of the gate for the intrinsic in circuits, if it isn't the QIR name.
+    pub glyph: Option<Rc<str>>,
+    /// Whether only targets with unrestricted capabilities can run the intrinsic.
+    pub unrestricted: bool,
 }
 
 impl FromStr for Attr {
@@ -1171,6 +1204,11 @@ impl FromStr for Attr {
             "Config" => Ok(Self::Config),
             "EntryPoint" => Ok(Self::EntryPoint),
             "Unimplemented" => Ok(Self::Unimplemented),
+            "Test" => Ok(Self::Test),
+            // The details are given by the arguments of these attributes, which are lowered separately.
+            "CustomIntrinsic" => Ok(Self::CustomIntrinsic(CustomIntrinsic::default())),
+            "Deprecated" => Ok(Self::Deprecated(Rc::from(""))),
+            "TargetProfile" => Ok(Self::TargetProfile(TargetProfile::default())),
             _ => Err(()),
         }
     }
@@ -10,7 +10,8 @@
 //! # Usage
 //!
 //! The entry points to the linter is the `run_lints` function, which takes
-//! a [`qsc_frontend::compile::CompileUnit`] as input and outputs a [`Vec<Lint>`](Lint).
+//! a [`qsc_frontend::compile::CompileUnit`] and the store of the packages it depends on
+//! as input and outputs a [`Vec<Lint>`](Lint).
 //!
 //! ## Example
 //!
@@ -20,10 +21,13 @@
 //!
 //! let unit: CompileUnit = compile(...);
 //!
-//! // The second argument is an optional user configuration.
-//! let lints: Vec<Lint> = run_ast_lints(&package, None);
+//! // The last argument is an optional user configuration.
+//! let lints: Vec<Lint> = run_lints(&store, &unit, None);
 //! ```
 //!
+//! Some lints carry the [`TextEdit`]s that fix them, which `apply_fixes` applies to the
+//! source they were reported for.
+//!
 //! # How to add a new Lint
 //!
 //! We can add a new lint in two steps:
@@ -66,4 +70,6 @@ mod lints;
 #[cfg(test)]
 mod tests;
 
-pub use linter::{run_lints, Lint, LintConfig, LintKind, LintLevel};
+pub use linter::{
+    apply_fixes, fixable_lints, run_lints, Lint, LintConfig, LintKind, LintLevel, TextEdit,
+};
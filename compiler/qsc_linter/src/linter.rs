@@ -8,16 +8,21 @@ use self::{ast::run_ast_lints, hir::run_hir_lints};
 use crate::lints::{ast::AstLint, hir::HirLint};
 use miette::{Diagnostic, LabeledSpan};
 use qsc_data_structures::span::Span;
-use qsc_frontend::compile::CompileUnit;
+use qsc_edit::{Edit, Patch};
+use qsc_frontend::compile::{CompileUnit, PackageStore};
 use serde::Deserialize;
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display};
 
-/// The entry point to the linter. It takes a [`qsc_frontend::compile::CompileUnit`]
-/// as input and outputs a [`Vec<Lint>`](Lint).
+/// The entry point to the linter. It takes a [`qsc_frontend::compile::CompileUnit`], along with
+/// the store of the packages it depends on, as input and outputs a [`Vec<Lint>`](Lint).
 #[must_use]
-pub fn run_lints(compile_unit: &CompileUnit, config: Option<&[LintConfig]>) -> Vec<Lint> {
-    let mut ast_lints = run_ast_lints(&compile_unit.ast.package, config);
-    let mut hir_lints = run_hir_lints(&compile_unit.package, config);
+pub fn run_lints(
+    package_store: &PackageStore,
+    compile_unit: &CompileUnit,
+    config: Option<&[LintConfig]>,
+) -> Vec<Lint> {
+    let mut ast_lints = run_ast_lints(&compile_unit.ast, config);
+    let mut hir_lints = run_hir_lints(&compile_unit.package, package_store, config);
 
     let mut lints = Vec::new();
     lints.append(&mut ast_lints);
@@ -36,9 +41,76 @@ pub struct Lint {
     /// The lint level: allow, warning, error.
     pub level: LintLevel,
     /// The message the user will see in the code editor.
-    pub message: &'static str,
+    pub message: Cow<'static, str>,
     /// The help text the user will see in the code editor.
     pub help: &'static str,
+    /// The edits that fix the lint without changing the meaning of the program.
+    /// Empty if the lint can't be fixed automatically.
+    pub edits: Vec<TextEdit>,
+}
+
+/// An edit that replaces the source code at a span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The replacement text.
+    pub new_text: String,
+    /// The span of the replaced source code.
+    pub span: Span,
+}
+
+impl TextEdit {
+    /// An edit that replaces the source code at a span with the given text.
+    pub(crate) fn replace(span: Span, new_text: &str) -> Self {
+        Self {
+            new_text: new_text.to_string(),
+            span,
+        }
+    }
+
+    /// An edit that deletes the source code at a span.
+    pub(crate) fn delete(span: Span) -> Self {
+        Self::replace(span, "")
+    }
+}
+
+/// The configuration that enables every lint with edits that fix it, including the ones
+/// that are allowed by default, for callers that apply the fixes on request.
+#[must_use]
+pub fn fixable_lints() -> Vec<LintConfig> {
+    [
+        AstLint::NeedlessParens,
+        AstLint::RedundantSemicolons,
+        AstLint::ConstantCondition,
+        AstLint::EmptyConditionalBlock,
+        AstLint::UnusedOpen,
+    ]
+    .into_iter()
+    .map(|lint| LintConfig {
+        kind: LintKind::Ast(lint),
+        level: LintLevel::Warn,
+    })
+    .collect()
+}
+
+/// Applies the edits of the lints to the source and returns the rewritten source.
+/// The spans of the lints must be relative to the source. A lint whose edits overlap
+/// the edits of an earlier lint is left unfixed, so that every fix is applied whole.
+#[must_use]
+pub fn apply_fixes(source: &str, lints: &[Lint]) -> String {
+    let mut patch = Patch::default();
+    for lint in lints {
+        let edits = lint
+            .edits
+            .iter()
+            .map(|edit| Edit::replace(edit.span, edit.new_text.clone()));
+        if let Ok(extended) = Patch::new(patch.edits().iter().cloned().chain(edits)) {
+            patch = extended;
+        }
+    }
+
+    patch
+        .apply_to_str(source)
+        .expect("fixes should be within the source")
 }
 
 impl std::fmt::Display for Lint {
@@ -73,7 +145,7 @@ impl Diagnostic for Lint {
 
 /// A lint level. This defines if a lint will be treated as a warning or an error,
 /// and if the lint level can be overriden by the user.
-#[derive(Debug, Clone, Copy, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum LintLevel {
     /// The lint is effectively disabled.
@@ -101,7 +173,7 @@ impl Display for LintLevel {
 }
 
 /// End-user configuration for each lint level.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct LintConfig {
     #[serde(rename = "lint")]
     /// Represents the lint name.
@@ -111,7 +183,7 @@ pub struct LintConfig {
 }
 
 /// Represents a lint name.
-#[derive(Debug, Clone, Copy, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[serde(untagged)]
 pub enum LintKind {
     /// AST lint name.
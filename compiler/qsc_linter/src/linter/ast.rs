@@ -8,15 +8,17 @@ use crate::{
 use qsc_ast::{
     ast::{
         Attr, Block, CallableDecl, Expr, FunctorExpr, Ident, Item, Namespace, Package, Pat, Path,
-        QubitInit, SpecDecl, Stmt, TopLevelNode, Ty, TyDef, Visibility,
+        QubitInit, SpecDecl, Stmt, Ty, TyDef, Visibility,
     },
     visit::Visitor,
 };
+use qsc_frontend::{compile::AstPackage, resolve::Locals, typeck::Table};
 
-/// The entry point to the AST linter. It takes a [`qsc_ast::ast::Package`]
-/// as input and outputs a [`Vec<Lint>`](Lint).
+/// The entry point to the AST linter. It takes a [`qsc_frontend::compile::AstPackage`],
+/// with the types inferred for its nodes and its resolved names, as input and outputs
+/// a [`Vec<Lint>`](Lint).
 #[must_use]
-pub fn run_ast_lints(package: &qsc_ast::ast::Package, config: Option<&[LintConfig]>) -> Vec<Lint> {
+pub fn run_ast_lints(ast: &AstPackage, config: Option<&[LintConfig]>) -> Vec<Lint> {
     let config: Vec<(AstLint, LintLevel)> = config
         .unwrap_or(&[])
         .iter()
@@ -29,15 +31,8 @@ pub fn run_ast_lints(package: &qsc_ast::ast::Package, config: Option<&[LintConfi
         })
         .collect();
 
-    let mut lints = CombinedAstLints::from_config(config);
-
-    for node in package.nodes.iter() {
-        match node {
-            TopLevelNode::Namespace(namespace) => lints.visit_namespace(namespace),
-            TopLevelNode::Stmt(stmt) => lints.visit_stmt(stmt),
-        }
-    }
-
+    let mut lints = CombinedAstLints::from_config(config, ast);
+    lints.visit_package(&ast.package);
     lints.buffer
 }
 
@@ -49,10 +44,23 @@ pub(crate) trait AstLintPass {
     fn check_attr(&self, _attr: &Attr, _buffer: &mut Vec<Lint>) {}
     fn check_block(&self, _block: &Block, _buffer: &mut Vec<Lint>) {}
     fn check_callable_decl(&self, _callable_decl: &CallableDecl, _buffer: &mut Vec<Lint>) {}
+    /// Like `check_callable_decl`, for lints that need the names and types resolved for the
+    /// package, such as to lower the callable to HIR.
+    fn check_resolved_callable_decl(
+        &self,
+        _callable_decl: &CallableDecl,
+        _ast: &AstPackage,
+        _buffer: &mut Vec<Lint>,
+    ) {
+    }
     fn check_expr(&self, _expr: &Expr, _buffer: &mut Vec<Lint>) {}
+    /// Like `check_expr`, for lints that need the types of the expressions.
+    fn check_typed_expr(&self, _expr: &Expr, _tys: &Table, _buffer: &mut Vec<Lint>) {}
     fn check_functor_expr(&self, _functor_expr: &FunctorExpr, _buffer: &mut Vec<Lint>) {}
     fn check_ident(&self, _ident: &Ident, _buffer: &mut Vec<Lint>) {}
     fn check_item(&self, _item: &Item, _buffer: &mut Vec<Lint>) {}
+    /// Like `check_item`, for lints that need to know how the names of the package were resolved.
+    fn check_resolved_item(&self, _item: &Item, _locals: &Locals, _buffer: &mut Vec<Lint>) {}
     fn check_namespace(&self, _namespace: &Namespace, _buffer: &mut Vec<Lint>) {}
     fn check_package(&self, _package: &Package, _buffer: &mut Vec<Lint>) {}
     fn check_pat(&self, _pat: &Pat, _buffer: &mut Vec<Lint>) {}
@@ -90,6 +98,7 @@ macro_rules! declare_ast_lints {
                 },
                 visit::{self, Visitor},
             };
+            use qsc_frontend::compile::AstPackage;
             use super::{$($lint_name),*};
 
             // Declare & implement the `AstLintsConfig` and CombinedAstLints structs.
@@ -133,7 +142,7 @@ macro_rules! declare_ast_lints {
     (@CONFIG_ENUM $($lint_name:ident),*) => {
         use serde::Deserialize;
 
-        #[derive(Debug, Clone, Copy, Deserialize)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
         #[serde(rename_all = "camelCase")]
         pub enum AstLint {
             $($lint_name),*
@@ -150,24 +159,20 @@ macro_rules! declare_ast_lints {
         /// Combined AST lints for speed. This combined lint allow us to
         /// evaluate all the lints in a single AST pass, instead of doing
         /// an individual pass for each lint in the linter.
-        pub(crate) struct CombinedAstLints {
+        pub(crate) struct CombinedAstLints<'t> {
             pub buffer: Vec<Lint>,
+            ast: &'t AstPackage,
             $($lint_name: $lint_name),*
         }
 
-        impl Default for CombinedAstLints {
-            fn default() -> Self {
-                Self {
+        // Most of the calls here are empty methods and they get optimized at compile time to a no-op.
+        impl<'t> CombinedAstLints<'t> {
+            pub fn from_config(config: Vec<(AstLint, LintLevel)>, ast: &'t AstPackage) -> Self {
+                let mut combined_ast_lints = Self {
                     buffer: Vec::default(),
+                    ast,
                     $($lint_name: <$lint_name>::default()),*
-                }
-            }
-        }
-
-        // Most of the calls here are empty methods and they get optimized at compile time to a no-op.
-        impl CombinedAstLints {
-            pub fn from_config(config: Vec<(AstLint, LintLevel)>) -> Self {
-                let mut combined_ast_lints = Self::default();
+                };
                 for (lint, level) in config {
                     match lint {
                         $(AstLint::$lint_name => combined_ast_lints.$lint_name.level = level),*
@@ -178,24 +183,33 @@ macro_rules! declare_ast_lints {
 
             fn check_package(&mut self, package: &Package) { $(self.$lint_name.check_package(package, &mut self.buffer));*; }
             fn check_namespace(&mut self, namespace: &Namespace) { $(self.$lint_name.check_namespace(namespace, &mut self.buffer));*; }
-            fn check_item(&mut self, item: &Item) { $(self.$lint_name.check_item(item, &mut self.buffer));*; }
+            fn check_item(&mut self, item: &Item) {
+                $(self.$lint_name.check_item(item, &mut self.buffer));*;
+                $(self.$lint_name.check_resolved_item(item, &self.ast.locals, &mut self.buffer));*;
+            }
             fn check_attr(&mut self, attr: &Attr) { $(self.$lint_name.check_attr(attr, &mut self.buffer));*; }
             fn check_visibility(&mut self, visibility: &Visibility) { $(self.$lint_name.check_visibility(visibility, &mut self.buffer));*; }
             fn check_ty_def(&mut self, def: &TyDef) { $(self.$lint_name.check_ty_def(def, &mut self.buffer));*; }
-            fn check_callable_decl(&mut self, decl: &CallableDecl) { $(self.$lint_name.check_callable_decl(decl, &mut self.buffer));*; }
+            fn check_callable_decl(&mut self, decl: &CallableDecl) {
+                $(self.$lint_name.check_callable_decl(decl, &mut self.buffer));*;
+                $(self.$lint_name.check_resolved_callable_decl(decl, self.ast, &mut self.buffer));*;
+            }
             fn check_spec_decl(&mut self, decl: &SpecDecl) { $(self.$lint_name.check_spec_decl(decl, &mut self.buffer));*; }
             fn check_functor_expr(&mut self, expr: &FunctorExpr) { $(self.$lint_name.check_functor_expr(expr, &mut self.buffer));*; }
             fn check_ty(&mut self, ty: &Ty) { $(self.$lint_name.check_ty(ty, &mut self.buffer));*; }
             fn check_block(&mut self, block: &Block) { $(self.$lint_name.check_block(block, &mut self.buffer));*; }
             fn check_stmt(&mut self, stmt: &Stmt) { $(self.$lint_name.check_stmt(stmt, &mut self.buffer));*; }
-            fn check_expr(&mut self, expr: &Expr) { $(self.$lint_name.check_expr(expr, &mut self.buffer));*; }
+            fn check_expr(&mut self, expr: &Expr) {
+                $(self.$lint_name.check_expr(expr, &mut self.buffer));*;
+                $(self.$lint_name.check_typed_expr(expr, &self.ast.tys, &mut self.buffer));*;
+            }
             fn check_pat(&mut self, pat: &Pat) { $(self.$lint_name.check_pat(pat, &mut self.buffer));*; }
             fn check_qubit_init(&mut self, init: &QubitInit) { $(self.$lint_name.check_qubit_init(init, &mut self.buffer));*; }
             fn check_path(&mut self, path: &Path) { $(self.$lint_name.check_path(path, &mut self.buffer));*; }
             fn check_ident(&mut self, ident: &Ident) { $(self.$lint_name.check_ident(ident, &mut self.buffer));*; }
         }
 
-        impl<'a> Visitor<'a> for CombinedAstLints {
+        impl<'a> Visitor<'a> for CombinedAstLints<'_> {
             fn visit_package(&mut self, package: &'a Package) {
                 self.check_package(package);
                 visit::walk_package(self, package);
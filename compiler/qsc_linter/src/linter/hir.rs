@@ -5,15 +5,22 @@ use crate::{
     lints::hir::{CombinedHirLints, HirLint},
     Lint, LintConfig, LintLevel,
 };
+use qsc_frontend::compile::PackageStore;
 use qsc_hir::{
-    hir::{Block, CallableDecl, Expr, Ident, Item, Package, Pat, QubitInit, SpecDecl, Stmt},
+    hir::{
+        Block, CallableDecl, Expr, Ident, Item, ItemId, Package, Pat, QubitInit, SpecDecl, Stmt,
+    },
     visit::Visitor,
 };
 
-/// The entry point to the HIR linter. It takes a [`qsc_hir::hir::Package`]
-/// as input and outputs a [`Vec<Lint>`](Lint).
+/// The entry point to the HIR linter. It takes a [`qsc_hir::hir::Package`], along with the
+/// store of the packages it depends on, as input and outputs a [`Vec<Lint>`](Lint).
 #[must_use]
-pub fn run_hir_lints(package: &Package, config: Option<&[LintConfig]>) -> Vec<Lint> {
+pub fn run_hir_lints(
+    package: &Package,
+    package_store: &PackageStore,
+    config: Option<&[LintConfig]>,
+) -> Vec<Lint> {
     let config: Vec<(HirLint, LintLevel)> = config
         .unwrap_or(&[])
         .iter()
@@ -26,7 +33,11 @@ pub fn run_hir_lints(package: &Package, config: Option<&[LintConfig]>) -> Vec<Li
         })
         .collect();
 
-    let mut lints = CombinedHirLints::from_config(config);
+    let items = Items {
+        package,
+        package_store,
+    };
+    let mut lints = CombinedHirLints::from_config(config, items);
 
     for (_, item) in &package.items {
         lints.visit_item(item);
@@ -39,6 +50,29 @@ pub fn run_hir_lints(package: &Package, config: Option<&[LintConfig]>) -> Vec<Li
     lints.buffer
 }
 
+/// The items that the package being linted can refer to, either its own or the ones declared in
+/// the packages it depends on.
+#[derive(Clone, Copy)]
+pub(crate) struct Items<'a> {
+    package: &'a Package,
+    package_store: &'a PackageStore,
+}
+
+impl<'a> Items<'a> {
+    /// Looks up an item relative to the package being linted.
+    pub(crate) fn get(&self, id: ItemId) -> Option<&'a Item> {
+        match id.package {
+            None => self.package.items.get(id.item),
+            Some(package_id) => self
+                .package_store
+                .get(package_id)?
+                .package
+                .items
+                .get(id.item),
+        }
+    }
+}
+
 /// Represents a lint pass in the HIR.
 /// You only need to implement the `check_*` function relevant to your lint.
 /// The trait provides default empty implementations for the rest of the methods,
@@ -47,6 +81,8 @@ pub(crate) trait HirLintPass {
     fn check_block(&self, _block: &Block, _buffer: &mut Vec<Lint>) {}
     fn check_callable_decl(&self, _callable_decl: &CallableDecl, _buffer: &mut Vec<Lint>) {}
     fn check_expr(&self, _expr: &Expr, _buffer: &mut Vec<Lint>) {}
+    /// Like `check_expr`, for lints that need the items the expression refers to.
+    fn check_resolved_expr(&self, _expr: &Expr, _items: Items, _buffer: &mut Vec<Lint>) {}
     fn check_ident(&self, _ident: &Ident, _buffer: &mut Vec<Lint>) {}
     fn check_item(&self, _item: &Item, _buffer: &mut Vec<Lint>) {}
     fn check_package(&self, _package: &Package, _buffer: &mut Vec<Lint>) {}
@@ -73,7 +109,7 @@ macro_rules! declare_hir_lints {
         // This is a silly wrapper module to avoid contaminating the environment
         // calling the macro with unwanted imports.
         mod _hir_macro_expansion {
-            use crate::{linter::hir::{declare_hir_lints, HirLintPass}, Lint, LintLevel};
+            use crate::{linter::hir::{declare_hir_lints, HirLintPass, Items}, Lint, LintLevel};
             use qsc_hir::{
                 hir::{Block, CallableDecl, Expr, Ident, Item, Package, Pat, QubitInit, SpecDecl, Stmt},
                 visit::{self, Visitor},
@@ -121,7 +157,7 @@ macro_rules! declare_hir_lints {
     (@CONFIG_ENUM $($lint_name:ident),*) => {
         use serde::Deserialize;
 
-        #[derive(Debug, Clone, Copy, Deserialize)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
         #[serde(rename_all = "camelCase")]
         pub enum HirLint {
             $($lint_name),*
@@ -138,24 +174,20 @@ macro_rules! declare_hir_lints {
         /// Combined HIR lints for speed. This combined lint allow us to
         /// evaluate all the lints in a single HIR pass, instead of doing
         /// an individual pass for each lint in the linter.
-        pub(crate) struct CombinedHirLints {
+        pub(crate) struct CombinedHirLints<'a> {
             pub buffer: Vec<Lint>,
+            items: Items<'a>,
             $($lint_name: $lint_name),*
         }
 
-        impl Default for CombinedHirLints {
-            fn default() -> Self {
-                Self {
+        // Most of the calls here are empty methods and they get optimized at compile time to a no-op.
+        impl<'a> CombinedHirLints<'a> {
+            pub fn from_config(config: Vec<(HirLint, LintLevel)>, items: Items<'a>) -> Self {
+                let mut combined_hir_lints = Self {
                     buffer: Vec::default(),
+                    items,
                     $($lint_name: <$lint_name>::default()),*
-                }
-            }
-        }
-
-        // Most of the calls here are empty methods and they get optimized at compile time to a no-op.
-        impl CombinedHirLints {
-            pub fn from_config(config: Vec<(HirLint, LintLevel)>) -> Self {
-                let mut combined_hir_lints = Self::default();
+                };
                 for (lint, level) in config {
                     match lint {
                         $(HirLint::$lint_name => combined_hir_lints.$lint_name.level = level),*
@@ -166,7 +198,10 @@ macro_rules! declare_hir_lints {
 
             fn check_block(&mut self, block: &Block) { $(self.$lint_name.check_block(block, &mut self.buffer));* }
             fn check_callable_decl(&mut self, decl: &CallableDecl) { $(self.$lint_name.check_callable_decl(decl, &mut self.buffer));* }
-            fn check_expr(&mut self, expr: &Expr) { $(self.$lint_name.check_expr(expr, &mut self.buffer));* }
+            fn check_expr(&mut self, expr: &Expr) {
+                $(self.$lint_name.check_expr(expr, &mut self.buffer));*;
+                $(self.$lint_name.check_resolved_expr(expr, self.items, &mut self.buffer));*;
+            }
             fn check_ident(&mut self, ident: &Ident) { $(self.$lint_name.check_ident(ident, &mut self.buffer));* }
             fn check_item(&mut self, item: &Item) { $(self.$lint_name.check_item(item, &mut self.buffer));* }
             fn check_package(&mut self, package: &Package) { $(self.$lint_name.check_package(package, &mut self.buffer));* }
@@ -176,7 +211,7 @@ macro_rules! declare_hir_lints {
             fn check_stmt(&mut self, stmt: &Stmt) { $(self.$lint_name.check_stmt(stmt, &mut self.buffer));* }
         }
 
-        impl<'a> Visitor<'a> for CombinedHirLints {
+        impl<'a> Visitor<'a> for CombinedHirLints<'_> {
             fn visit_block(&mut self, block: &'a Block) {
                 self.check_block(block);
                 visit::walk_block(self, block);
@@ -6,11 +6,15 @@ pub(super) mod hir;
 
 macro_rules! lint {
     ($lint:expr, $span:expr) => {
+        lint!($lint, $span, Vec::new())
+    };
+    ($lint:expr, $span:expr, $edits:expr) => {
         Lint {
             span: $span,
             level: $lint.level,
-            message: $lint.message,
+            message: $lint.message.into(),
             help: $lint.help,
+            edits: $edits,
         }
     };
 }
@@ -2,14 +2,36 @@
 // Licensed under the MIT License.
 
 use super::lint;
-use crate::linter::ast::declare_ast_lints;
-use qsc_ast::ast::{BinOp, ExprKind, Lit, StmtKind};
+use crate::{linter::ast::declare_ast_lints, TextEdit};
+use qsc_ast::{
+    ast::{
+        BinOp, Block, CallableBody, CallableDecl, Expr, ExprKind, Item, ItemKind, Lit, Pat,
+        PatKind, Spec, SpecBody, Stmt, StmtKind, TopLevelNode,
+    },
+    visit::{self, Visitor},
+};
 use qsc_data_structures::span::Span;
+use qsc_frontend::{compile::AstPackage, resolve::Locals, typeck::Table};
+use qsc_hir::{
+    hir::CallableKind,
+    ty::{Prim, Ty as HirTy},
+};
+use qsc_passes::is_generated_ctl;
+use std::rc::Rc;
 
 declare_ast_lints! {
     (DivisionByZero, LintLevel::Warn, "attempt to divide by zero", "division by zero is not allowed"),
     (NeedlessParens, LintLevel::Allow, "unnecessary parentheses", "remove the extra parentheses for clarity"),
     (RedundantSemicolons, LintLevel::Warn, "redundant semicolons", "remove the redundant semicolons"),
+    (TopLevelStatements, LintLevel::Error, "statements are not allowed outside of callables in a project", "move the statements into an operation marked with `@EntryPoint()`"),
+    (RedundantControlledSpec, LintLevel::Warn, "controlled specialization is identical to the generated one", "replace the implementation with `controlled auto;`"),
+    (ShadowedBinding, LintLevel::Allow, "binding shadows a binding of an enclosing scope", "rename the binding so that it can't be confused with the one it shadows"),
+    (ResultComparisonInLoop, LintLevel::Allow, "loop condition compares a measurement result, which requires the BackwardsBranching capability", "the runtime capabilities analysis reports this as `LoopWithDynamicCondition`; to run on targets without backwards branching, loop a static number of times and check the result inside the loop"),
+    (ConstantCondition, LintLevel::Warn, "condition of `if` is a constant", "remove the condition, keeping only the branch that runs"),
+    (EmptyConditionalBlock, LintLevel::Warn, "empty block in `if` expression", "remove the empty block"),
+    (UnusedOpen, LintLevel::Warn, "unused open statement", "remove the open statement"),
+    (AliasShadowsNamespace, LintLevel::Warn, "alias of open statement shadows a namespace", "rename the alias so that paths qualified with it can't be confused with the namespace"),
+    (LoopInvariantArray, LintLevel::Allow, "array is constructed again on every iteration of the loop", "the array doesn't depend on the loop, so bind it to a variable before the loop"),
 }
 
 impl AstLintPass for DivisionByZero {
@@ -38,12 +60,26 @@ impl NeedlessParens {
     ) {
         if let ExprKind::Paren(expr) = &*child.kind {
             if precedence(parent) < precedence(expr) {
-                buffer.push(lint!(self, child.span));
+                buffer.push(lint!(self, child.span, remove_parens(child, expr)));
             }
         }
     }
 }
 
+/// The edits that remove the parentheses around an expression.
+fn remove_parens(paren: &Expr, inner: &Expr) -> Vec<TextEdit> {
+    vec![
+        TextEdit::delete(Span {
+            lo: paren.span.lo,
+            hi: inner.span.lo,
+        }),
+        TextEdit::delete(Span {
+            lo: inner.span.hi,
+            hi: paren.span.hi,
+        }),
+    ]
+}
+
 impl AstLintPass for NeedlessParens {
     fn check_expr(&self, expr: &qsc_ast::ast::Expr, buffer: &mut Vec<Lint>) {
         match &*expr.kind {
@@ -61,8 +97,8 @@ impl AstLintPass for NeedlessParens {
     /// Checks the assignment statements.
     fn check_stmt(&self, stmt: &qsc_ast::ast::Stmt, buffer: &mut Vec<Lint>) {
         if let StmtKind::Local(_, _, right) = &*stmt.kind {
-            if let ExprKind::Paren(_) = &*right.kind {
-                buffer.push(lint!(self, right.span));
+            if let ExprKind::Paren(expr) = &*right.kind {
+                buffer.push(lint!(self, right.span, remove_parens(right, expr)));
             }
         }
     }
@@ -70,10 +106,16 @@ impl AstLintPass for NeedlessParens {
 
 impl RedundantSemicolons {
     /// Helper function that pushes a lint to the buffer if we have
-    /// found two or more semicolons.
-    fn maybe_push(&self, seq: &mut Option<Span>, buffer: &mut Vec<Lint>) {
+    /// found two or more semicolons. The fix deletes each redundant
+    /// semicolon on its own, keeping any comments between them.
+    fn maybe_push(
+        &self,
+        seq: &mut Option<Span>,
+        edits: &mut Vec<TextEdit>,
+        buffer: &mut Vec<Lint>,
+    ) {
         if let Some(span) = seq.take() {
-            buffer.push(lint!(self, span));
+            buffer.push(lint!(self, span, std::mem::take(edits)));
         }
     }
 }
@@ -88,16 +130,536 @@ impl AstLintPass for RedundantSemicolons {
         // None: no redundant semicolons
         // Some(_): one or more redundant semicolons
         let mut seq: Option<Span> = None;
+        let mut edits = Vec::new();
 
         for stmt in block.stmts.iter() {
             match (&*stmt.kind, &mut seq) {
                 (StmtKind::Empty, None) => seq = Some(stmt.span),
                 (StmtKind::Empty, Some(span)) => span.hi = stmt.span.hi,
-                (_, seq) => self.maybe_push(seq, buffer),
+                (_, seq) => self.maybe_push(seq, &mut edits, buffer),
+            }
+            if let StmtKind::Empty = &*stmt.kind {
+                edits.push(TextEdit::delete(stmt.span));
+            }
+        }
+
+        self.maybe_push(&mut seq, &mut edits, buffer);
+    }
+}
+
+impl AstLintPass for TopLevelStatements {
+    /// Checks for executable statements outside of namespaces. These are valid in notebook
+    /// cells, which are not linted, but in a project they are usually code copied from a
+    /// notebook that never runs.
+    fn check_package(&self, package: &qsc_ast::ast::Package, buffer: &mut Vec<Lint>) {
+        for node in &package.nodes {
+            if let TopLevelNode::Stmt(stmt) = node {
+                if !matches!(&*stmt.kind, StmtKind::Item(_) | StmtKind::Empty) {
+                    buffer.push(lint!(self, stmt.span));
+                }
+            }
+        }
+    }
+}
+
+impl AstLintPass for RedundantControlledSpec {
+    /// Checks for explicit controlled specializations that are what the specialization
+    /// generator would produce from the body, by running the generator in comparison mode
+    /// on the callable as it is lowered to HIR.
+    fn check_resolved_callable_decl(
+        &self,
+        decl: &CallableDecl,
+        ast: &AstPackage,
+        buffer: &mut Vec<Lint>,
+    ) {
+        let CallableBody::Specs(specs) = &*decl.body else {
+            return;
+        };
+        let Some(ctl) = specs
+            .iter()
+            .find(|spec| spec.spec == Spec::Ctl && matches!(spec.body, SpecBody::Impl(..)))
+        else {
+            return;
+        };
+
+        let (lowered, mut assigner) = ast.lower_callable(decl);
+        if is_generated_ctl(&lowered, &mut assigner) {
+            buffer.push(lint!(self, ctl.span));
+        }
+    }
+}
+
+impl AstLintPass for ResultComparisonInLoop {
+    /// Checks the conditions of `while` and `repeat` loops for comparisons of `Result` values,
+    /// which make the number of iterations depend on measurements.
+    fn check_typed_expr(&self, expr: &Expr, tys: &Table, buffer: &mut Vec<Lint>) {
+        let (ExprKind::While(cond, _) | ExprKind::Repeat(_, cond, _)) = &*expr.kind else {
+            return;
+        };
+        let mut finder = ResultComparisonFinder { tys, found: false };
+        finder.visit_expr(cond);
+        if finder.found {
+            buffer.push(lint!(self, cond.span));
+        }
+    }
+}
+
+struct ResultComparisonFinder<'a> {
+    tys: &'a Table,
+    found: bool,
+}
+
+impl<'a> Visitor<'a> for ResultComparisonFinder<'_> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::BinOp(BinOp::Eq | BinOp::Neq, lhs, _) = &*expr.kind {
+            if matches!(self.tys.terms.get(lhs.id), Some(HirTy::Prim(Prim::Result))) {
+                self.found = true;
+                return;
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+impl AstLintPass for ConstantCondition {
+    fn check_callable_decl(&self, decl: &CallableDecl, buffer: &mut Vec<Lint>) {
+        for branch in Branch::find(decl) {
+            if let ExprKind::Lit(lit) = &*branch.cond.kind {
+                if let Lit::Bool(value) = **lit {
+                    let edits = if value {
+                        branch.keep_body()
+                    } else {
+                        branch.skip_body()
+                    };
+                    buffer.push(lint!(self, branch.cond.span, edits));
+                }
+            }
+        }
+    }
+}
+
+impl AstLintPass for EmptyConditionalBlock {
+    fn check_callable_decl(&self, decl: &CallableDecl, buffer: &mut Vec<Lint>) {
+        for branch in Branch::find(decl) {
+            if branch.body.stmts.is_empty() {
+                let edits = match (branch.is_elif, branch.otherwise.map(|o| &*o.kind)) {
+                    // Removing the branch would skip the evaluation of a condition with side effects.
+                    (_, None) if has_call(branch.cond) => Vec::new(),
+                    (_, None) => branch.skip_body(),
+                    (false, Some(ExprKind::Block(block))) => vec![
+                        TextEdit::replace(span(branch.cond.span.lo, branch.cond.span.lo), "not ("),
+                        TextEdit::replace(span(branch.cond.span.hi, block.span.lo), ") "),
+                    ],
+                    _ => Vec::new(),
+                };
+                buffer.push(lint!(self, branch.body.span, edits));
+            }
+            if let Some(otherwise) = branch.otherwise {
+                if let ExprKind::Block(block) = &*otherwise.kind {
+                    if block.stmts.is_empty() {
+                        let edits = vec![TextEdit::delete(span(
+                            branch.body.span.hi,
+                            otherwise.span.hi,
+                        ))];
+                        buffer.push(lint!(self, otherwise.span, edits));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A branch of an `if` expression. The span of an `elif` branch starts at its keyword and
+/// ends with the rest of the chain, like the span of the `if` that starts the chain does.
+struct Branch<'a> {
+    expr: &'a Expr,
+    cond: &'a Expr,
+    body: &'a Block,
+    otherwise: Option<&'a Expr>,
+    is_elif: bool,
+}
+
+impl<'a> Branch<'a> {
+    /// The branches of the `if` expressions in a callable, outside of nested items.
+    fn find(decl: &'a CallableDecl) -> Vec<Self> {
+        let mut finder = BranchFinder::default();
+        visit::walk_callable_decl(&mut finder, decl);
+        finder.branches
+    }
+
+    /// The edits that remove the condition of the branch and everything but its body.
+    fn keep_body(&self) -> Vec<TextEdit> {
+        let keyword = if self.is_elif { "else " } else { "" };
+        let mut edits = vec![TextEdit::replace(
+            span(self.expr.span.lo, self.body.span.lo),
+            keyword,
+        )];
+        if self.otherwise.is_some() {
+            edits.push(TextEdit::delete(span(self.body.span.hi, self.expr.span.hi)));
+        }
+        edits
+    }
+
+    /// The edits that remove the branch, keeping the rest of the chain.
+    fn skip_body(&self) -> Vec<TextEdit> {
+        let hi = match (self.is_elif, self.otherwise) {
+            (_, None) => self.expr.span.hi,
+            (true, Some(otherwise)) => otherwise.span.lo,
+            (false, Some(otherwise)) => match &*otherwise.kind {
+                ExprKind::Block(block) => block.span.lo,
+                // Turns the `elif` that follows into an `if`.
+                _ => otherwise.span.lo + 2,
+            },
+        };
+        vec![TextEdit::delete(span(self.expr.span.lo, hi))]
+    }
+}
+
+#[derive(Default)]
+struct BranchFinder<'a> {
+    branches: Vec<Branch<'a>>,
+}
+
+impl<'a> BranchFinder<'a> {
+    fn visit_branch(&mut self, expr: &'a Expr, is_elif: bool) {
+        let ExprKind::If(cond, body, otherwise) = &*expr.kind else {
+            self.visit_expr(expr);
+            return;
+        };
+        self.branches.push(Branch {
+            expr,
+            cond,
+            body,
+            otherwise: otherwise.as_deref(),
+            is_elif,
+        });
+        self.visit_expr(cond);
+        self.visit_block(body);
+        if let Some(otherwise) = otherwise {
+            self.visit_branch(otherwise, true);
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for BranchFinder<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        // Nested callables are checked on their own.
+        if !matches!(&*stmt.kind, StmtKind::Item(_)) {
+            visit::walk_stmt(self, stmt);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprKind::If(..) = &*expr.kind {
+            self.visit_branch(expr, false);
+        } else {
+            visit::walk_expr(self, expr);
+        }
+    }
+}
+
+fn has_call(expr: &Expr) -> bool {
+    struct CallFinder(bool);
+    impl<'a> Visitor<'a> for CallFinder {
+        fn visit_expr(&mut self, expr: &'a Expr) {
+            self.0 |= matches!(&*expr.kind, ExprKind::Call(..));
+            visit::walk_expr(self, expr);
+        }
+    }
+    let mut finder = CallFinder(false);
+    finder.visit_expr(expr);
+    finder.0
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span { lo, hi }
+}
+
+impl AstLintPass for UnusedOpen {
+    fn check_resolved_item(&self, item: &Item, locals: &Locals, buffer: &mut Vec<Lint>) {
+        if let ItemKind::Open(name, _) = &*item.kind {
+            if !locals.is_open_used(name.span) {
+                buffer.push(lint!(self, item.span, vec![TextEdit::delete(item.span)]));
+            }
+        }
+    }
+}
+
+impl AstLintPass for AliasShadowsNamespace {
+    fn check_resolved_item(&self, item: &Item, locals: &Locals, buffer: &mut Vec<Lint>) {
+        if let ItemKind::Open(_, Some(alias)) = &*item.kind {
+            if locals.is_alias_shadowing(alias.span) {
+                buffer.push(lint!(self, alias.span));
             }
         }
+    }
+}
+
+impl AstLintPass for LoopInvariantArray {
+    /// Checks the arrays constructed in the body of each loop, leaving the arrays of nested loops
+    /// to the check of those loops.
+    fn check_typed_expr(&self, expr: &Expr, tys: &Table, buffer: &mut Vec<Lint>) {
+        let mut names = LoopNames::default();
+        let mut finder = InvariantArrayFinder {
+            tys,
+            variant: Vec::new(),
+            spans: Vec::new(),
+        };
+        match &*expr.kind {
+            ExprKind::For(pat, _, block) => {
+                names.bind(pat);
+                names.visit_block(block);
+                finder.variant = names.names;
+                visit::walk_block(&mut finder, block);
+            }
+            ExprKind::While(cond, block) => {
+                names.visit_expr(cond);
+                names.visit_block(block);
+                finder.variant = names.names;
+                finder.visit_expr(cond);
+                visit::walk_block(&mut finder, block);
+            }
+            ExprKind::Repeat(block, cond, fixup) => {
+                names.visit_expr(expr);
+                finder.variant = names.names;
+                visit::walk_block(&mut finder, block);
+                finder.visit_expr(cond);
+                if let Some(fixup) = fixup {
+                    visit::walk_block(&mut finder, fixup);
+                }
+            }
+            _ => return,
+        }
+        for span in finder.spans {
+            buffer.push(lint!(self, span));
+        }
+    }
+}
+
+/// Collects the names that are bound or assigned inside of a loop, whose values can change from
+/// one iteration to the next.
+#[derive(Default)]
+struct LoopNames {
+    names: Vec<Rc<str>>,
+}
 
-        self.maybe_push(&mut seq, buffer);
+impl LoopNames {
+    fn bind(&mut self, pat: &Pat) {
+        match &*pat.kind {
+            PatKind::Bind(ident, _) => self.names.push(ident.name.clone()),
+            PatKind::Paren(pat) => self.bind(pat),
+            PatKind::Tuple(pats) => pats.iter().for_each(|pat| self.bind(pat)),
+            PatKind::Discard(_) | PatKind::Elided | PatKind::Err => {}
+        }
+    }
+
+    fn assign(&mut self, lhs: &Expr) {
+        match &*lhs.kind {
+            ExprKind::Path(path) if path.namespace.is_none() => {
+                self.names.push(path.name.name.clone());
+            }
+            ExprKind::Paren(expr) => self.assign(expr),
+            ExprKind::Tuple(exprs) => exprs.iter().for_each(|expr| self.assign(expr)),
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for LoopNames {
+    fn visit_pat(&mut self, pat: &'a Pat) {
+        self.bind(pat);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &*expr.kind {
+            ExprKind::Assign(lhs, _)
+            | ExprKind::AssignOp(_, lhs, _)
+            | ExprKind::AssignUpdate(lhs, _, _) => self.assign(lhs),
+            _ => {}
+        }
+        visit::walk_expr(self, expr);
+    }
+}
+
+/// Collects the spans of the outermost array constructions that only depend on values that stay
+/// the same across the iterations of a loop.
+struct InvariantArrayFinder<'a> {
+    tys: &'a Table,
+    variant: Vec<Rc<str>>,
+    spans: Vec<Span>,
+}
+
+impl InvariantArrayFinder<'_> {
+    fn is_array_construction(expr: &Expr) -> bool {
+        match &*expr.kind {
+            ExprKind::Array(items) => !items.is_empty(),
+            ExprKind::ArrayRepeat(..) => true,
+            ExprKind::Call(callee, _) => {
+                matches!(&*callee.kind, ExprKind::Path(path) if &*path.name.name == "Repeated")
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the expression evaluates to the same value on every iteration. Calls are only
+    /// invariant when they are calls to functions, which can't have side effects.
+    fn is_invariant(&self, expr: &Expr) -> bool {
+        match &*expr.kind {
+            ExprKind::Lit(_) => true,
+            ExprKind::Path(path) => {
+                path.namespace.is_some() || !self.variant.contains(&path.name.name)
+            }
+            ExprKind::Array(exprs) | ExprKind::Tuple(exprs) => {
+                exprs.iter().all(|expr| self.is_invariant(expr))
+            }
+            ExprKind::ArrayRepeat(lhs, rhs) | ExprKind::BinOp(_, lhs, rhs) => {
+                self.is_invariant(lhs) && self.is_invariant(rhs)
+            }
+            ExprKind::Paren(expr) | ExprKind::UnOp(_, expr) => self.is_invariant(expr),
+            ExprKind::Range(start, step, end) => [start, step, end]
+                .into_iter()
+                .flatten()
+                .all(|expr| self.is_invariant(expr)),
+            ExprKind::Call(callee, arg) => {
+                matches!(
+                    self.tys.terms.get(callee.id),
+                    Some(HirTy::Arrow(arrow)) if arrow.kind == CallableKind::Function
+                ) && self.is_invariant(callee)
+                    && self.is_invariant(arg)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for InvariantArrayFinder<'_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        // Nested callables aren't part of the loop.
+        if !matches!(&*stmt.kind, StmtKind::Item(_)) {
+            visit::walk_stmt(self, stmt);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &*expr.kind {
+            // Nested loops are checked on their own, and lambda bodies don't run in the loop.
+            ExprKind::For(..)
+            | ExprKind::While(..)
+            | ExprKind::Repeat(..)
+            | ExprKind::Lambda(..) => {}
+            _ if Self::is_array_construction(expr) && self.is_invariant(expr) => {
+                self.spans.push(expr.span);
+            }
+            _ => visit::walk_expr(self, expr),
+        }
+    }
+}
+
+impl AstLintPass for ShadowedBinding {
+    fn check_callable_decl(&self, decl: &CallableDecl, buffer: &mut Vec<Lint>) {
+        let mut finder = ShadowFinder {
+            scopes: vec![Vec::new()],
+            spans: Vec::new(),
+        };
+        finder.bind(&decl.input, false);
+        match &*decl.body {
+            CallableBody::Block(block) => finder.visit_block(block),
+            CallableBody::Specs(specs) => {
+                for spec in specs {
+                    if let SpecBody::Impl(input, block) = &spec.body {
+                        finder.scopes.push(Vec::new());
+                        finder.bind(input, false);
+                        finder.visit_block(block);
+                        finder.scopes.pop();
+                    }
+                }
+            }
+        }
+        for span in finder.spans {
+            buffer.push(lint!(self, span));
+        }
+    }
+}
+
+/// Tracks the names bound in each scope of a callable, innermost last, and collects the spans
+/// of the `let` and `mutable` bindings that reuse a name bound in an enclosing scope.
+struct ShadowFinder {
+    scopes: Vec<Vec<Rc<str>>>,
+    spans: Vec<Span>,
+}
+
+impl ShadowFinder {
+    fn bind(&mut self, pat: &Pat, check: bool) {
+        match &*pat.kind {
+            PatKind::Bind(ident, _) => {
+                let (scope, enclosing) = self
+                    .scopes
+                    .split_last_mut()
+                    .expect("there should be a current scope");
+                if check && enclosing.iter().flatten().any(|name| *name == ident.name) {
+                    self.spans.push(ident.span);
+                }
+                scope.push(ident.name.clone());
+            }
+            PatKind::Paren(pat) => self.bind(pat, check),
+            PatKind::Tuple(pats) => {
+                for pat in pats {
+                    self.bind(pat, check);
+                }
+            }
+            PatKind::Discard(_) | PatKind::Elided | PatKind::Err => {}
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for ShadowFinder {
+    fn visit_block(&mut self, block: &'a Block) {
+        self.scopes.push(Vec::new());
+        visit::walk_block(self, block);
+        self.scopes.pop();
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match &*stmt.kind {
+            StmtKind::Local(_, pat, value) => {
+                self.visit_expr(value);
+                self.bind(pat, true);
+            }
+            StmtKind::Qubit(_, pat, init, block) => {
+                self.visit_qubit_init(init);
+                match block {
+                    Some(block) => {
+                        self.scopes.push(Vec::new());
+                        self.bind(pat, false);
+                        self.visit_block(block);
+                        self.scopes.pop();
+                    }
+                    None => self.bind(pat, false),
+                }
+            }
+            // Nested callables are checked on their own.
+            StmtKind::Item(_) => {}
+            _ => visit::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &*expr.kind {
+            ExprKind::For(pat, iterable, block) => {
+                self.visit_expr(iterable);
+                self.scopes.push(Vec::new());
+                self.bind(pat, false);
+                self.visit_block(block);
+                self.scopes.pop();
+            }
+            ExprKind::Lambda(_, pat, body) => {
+                self.scopes.push(Vec::new());
+                self.bind(pat, false);
+                self.visit_expr(body);
+                self.scopes.pop();
+            }
+            _ => visit::walk_expr(self, expr),
+        }
     }
 }
 
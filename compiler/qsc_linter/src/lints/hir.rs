@@ -1,17 +1,107 @@
-use qsc_hir::hir::Lit;
-
-use crate::linter::hir::declare_hir_lints;
+use qsc_data_structures::span::Span;
+use qsc_hir::{
+    hir::{
+        Attr, CallableDecl, Expr, ExprKind, ItemKind, Mutability, NodeId, Pat, PatKind, Res, Stmt,
+        StmtKind,
+    },
+    visit::{self, Visitor},
+};
 
 use super::lint;
+use crate::linter::hir::{declare_hir_lints, Items};
 
 declare_hir_lints! {
-    (Placeholder, LintLevel::Allow, "this a placeholder", "remove after addding the first HIR lint"),
+    (DeprecatedCallable, LintLevel::Warn, "use of deprecated callable", "use the replacement suggested in the message"),
+    (NeedlessMutable, LintLevel::Allow, "mutable variable is never reassigned", "declare it with `let` instead, so that arrays can share their storage instead of being copied"),
+}
+
+impl HirLintPass for DeprecatedCallable {
+    fn check_resolved_expr(&self, expr: &Expr, items: Items, buffer: &mut Vec<Lint>) {
+        let ExprKind::Var(Res::Item(item_id), _) = &expr.kind else {
+            return;
+        };
+        let Some(item) = items.get(*item_id) else {
+            return;
+        };
+        let ItemKind::Callable(decl) = &item.kind else {
+            return;
+        };
+        if let Some(note) = item.attrs.iter().find_map(|attr| match attr {
+            Attr::Deprecated(note) => Some(note),
+            _ => None,
+        }) {
+            buffer.push(Lint {
+                span: expr.span,
+                level: self.level,
+                message: format!("{} `{}`: {note}", self.message, decl.name.name).into(),
+                help: self.help,
+                edits: Vec::new(),
+            });
+        }
+    }
+}
+
+impl HirLintPass for NeedlessMutable {
+    fn check_callable_decl(&self, decl: &CallableDecl, buffer: &mut Vec<Lint>) {
+        let mut finder = MutableFinder::default();
+        finder.visit_callable_decl(decl);
+        let mut spans = Vec::new();
+        for (id, span) in finder.bindings {
+            // Generated specializations repeat the bindings of the body with the same spans.
+            if !finder.assigned.contains(&id) && !spans.contains(&span) {
+                spans.push(span);
+            }
+        }
+        for span in spans {
+            buffer.push(lint!(self, span));
+        }
+    }
+}
+
+/// Collects the `mutable` bindings of a callable along with the locals that are assigned to.
+#[derive(Default)]
+struct MutableFinder {
+    bindings: Vec<(NodeId, Span)>,
+    assigned: Vec<NodeId>,
 }
 
-impl HirLintPass for Placeholder {
-    fn check_expr(&self, expr: &qsc_hir::hir::Expr, buffer: &mut Vec<Lint>) {
-        if let qsc_hir::hir::ExprKind::Lit(Lit::Int(42)) = &expr.kind {
-            buffer.push(lint!(self, expr.span));
+impl MutableFinder {
+    fn bind(&mut self, pat: &Pat) {
+        match &pat.kind {
+            // Names starting with `@` are generated by the passes, not written by the user.
+            PatKind::Bind(ident) if !ident.name.starts_with('@') => {
+                self.bindings.push((ident.id, ident.span));
+            }
+            PatKind::Tuple(pats) => pats.iter().for_each(|pat| self.bind(pat)),
+            PatKind::Bind(_) | PatKind::Discard | PatKind::Err => {}
+        }
+    }
+
+    fn assign(&mut self, lhs: &Expr) {
+        match &lhs.kind {
+            ExprKind::Var(Res::Local(id), _) => self.assigned.push(*id),
+            ExprKind::Tuple(exprs) => exprs.iter().for_each(|expr| self.assign(expr)),
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for MutableFinder {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let StmtKind::Local(Mutability::Mutable, pat, _) = &stmt.kind {
+            self.bind(pat);
+        }
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Assign(lhs, _)
+            | ExprKind::AssignOp(_, lhs, _)
+            | ExprKind::AssignField(lhs, _, _)
+            | ExprKind::AssignIndex(lhs, _, _) => self.assign(lhs),
+            _ => {}
         }
+        visit::walk_expr(self, expr);
     }
 }
@@ -2,12 +2,16 @@
 // Licensed under the MIT License.
 
 use crate::{
+    apply_fixes,
     linter::{ast::run_ast_lints, hir::run_hir_lints},
-    Lint, LintConfig, LintLevel,
+    Lint, LintConfig, LintLevel, TextEdit,
 };
 use expect_test::{expect, Expect};
-use qsc_data_structures::language_features::LanguageFeatures;
-use qsc_frontend::compile::{self, CompileUnit, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use indoc::indoc;
+use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
+use qsc_frontend::compile::{
+    self, CompileUnit, PackageStore, ParseCache, RuntimeCapabilityFlags, SourceMap,
+};
 use qsc_passes::PackageType;
 
 #[test]
@@ -85,12 +89,6 @@ fn needless_parens_in_assignment() {
                     message: "unnecessary parentheses",
                     help: "remove the extra parentheses for clarity",
                 },
-                SrcLint {
-                    source: "42",
-                    level: Allow,
-                    message: "this a placeholder",
-                    help: "remove after addding the first HIR lint",
-                },
             ]
         "#]],
     );
@@ -143,42 +141,524 @@ fn redundant_semicolons() {
 }
 
 #[test]
-fn hir_placeholder() {
+fn fix_needless_parens_and_redundant_semicolons() {
+    check_fixes(
+        "let x = (1);; let y = 2 + (3 * 4);; // kept\n ;",
+        "let x = 1; let y = 2 + 3 * 4; // kept\n ",
+    );
+}
+
+#[test]
+fn overlapping_fixes_are_skipped() {
+    let lint = |lo, hi| Lint {
+        span: Span { lo, hi },
+        level: LintLevel::Warn,
+        message: "".into(),
+        help: "",
+        edits: vec![TextEdit {
+            new_text: "x".into(),
+            span: Span { lo, hi },
+        }],
+    };
+    assert_eq!(
+        apply_fixes("abcdef", &[lint(1, 3), lint(2, 4), lint(4, 5)]),
+        "axdxf"
+    );
+}
+
+#[test]
+fn constant_condition() {
+    check(
+        r#"if true { Message("a"); } elif false { Message("b"); }"#,
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "true",
+                    level: Warn,
+                    message: "condition of `if` is a constant",
+                    help: "remove the condition, keeping only the branch that runs",
+                },
+                SrcLint {
+                    source: "false",
+                    level: Warn,
+                    message: "condition of `if` is a constant",
+                    help: "remove the condition, keeping only the branch that runs",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn fix_constant_condition() {
+    check_fixes(
+        r#"if true { Message("a"); } if false { Message("b"); } else { Message("c"); } if vector[0] > 0.0 { Message("d"); } elif false { Message("e"); } else { Message("f"); }"#,
+        r#"{ Message("a"); } { Message("c"); } if vector[0] > 0.0 { Message("d"); } else { Message("f"); }"#,
+    );
+}
+
+#[test]
+fn empty_conditional_block() {
+    check(
+        r#"if vector[0] > 0.0 {} else { Message("a"); } if vector[0] > 1.0 { Message("b"); } else {}"#,
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "{}",
+                    level: Warn,
+                    message: "empty block in `if` expression",
+                    help: "remove the empty block",
+                },
+                SrcLint {
+                    source: "else {}",
+                    level: Warn,
+                    message: "empty block in `if` expression",
+                    help: "remove the empty block",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn fix_empty_conditional_block() {
+    check_fixes(
+        r#"if vector[0] > 0.0 {} else { Message("a"); } if vector[0] > 1.0 { Message("b"); } else {} if vector[0] > 2.0 {}"#,
+        r#"if not (vector[0] > 0.0) { Message("a"); } if vector[0] > 1.0 { Message("b"); } "#,
+    );
+}
+
+#[test]
+fn empty_conditional_block_with_side_effects_is_not_fixed() {
+    check_fixes(
+        "use q = Qubit(); if M(q) == One {}",
+        "use q = Qubit(); if M(q) == One {}",
+    );
+}
+
+#[test]
+fn result_comparison_in_while_condition() {
     check(
-        "let placeholder = 42;",
+        "use q = Qubit(); while M(q) == Zero {}",
         &expect![[r#"
             [
                 SrcLint {
-                    source: "42",
+                    source: "M(q) == Zero",
                     level: Allow,
-                    message: "this a placeholder",
-                    help: "remove after addding the first HIR lint",
+                    message: "loop condition compares a measurement result, which requires the BackwardsBranching capability",
+                    help: "the runtime capabilities analysis reports this as `LoopWithDynamicCondition`; to run on targets without backwards branching, loop a static number of times and check the result inside the loop",
                 },
             ]
         "#]],
     );
 }
 
+#[test]
+fn result_comparison_in_repeat_condition() {
+    check(
+        "use q = Qubit(); repeat {} until M(q) != One or vector[0] > 0.0;",
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "M(q) != One or vector[0] > 0.0",
+                    level: Allow,
+                    message: "loop condition compares a measurement result, which requires the BackwardsBranching capability",
+                    help: "the runtime capabilities analysis reports this as `LoopWithDynamicCondition`; to run on targets without backwards branching, loop a static number of times and check the result inside the loop",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn static_loop_condition() {
+    check(
+        "mutable i = 0; while i < 3 { set i += 1; }",
+        &expect![[r"
+            []
+        "]],
+    );
+}
+
+#[test]
+fn shadowed_binding_in_nested_block() {
+    check(
+        "let x = 1; if x > 0 { mutable x = 2; set x += 1; }",
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "x",
+                    level: Allow,
+                    message: "binding shadows a binding of an enclosing scope",
+                    help: "rename the binding so that it can't be confused with the one it shadows",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn shadowed_parameter() {
+    check(
+        "for i in 0..1 { let vector = [1.0]; let i = 2; }",
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "vector",
+                    level: Allow,
+                    message: "binding shadows a binding of an enclosing scope",
+                    help: "rename the binding so that it can't be confused with the one it shadows",
+                },
+                SrcLint {
+                    source: "i",
+                    level: Allow,
+                    message: "binding shadows a binding of an enclosing scope",
+                    help: "rename the binding so that it can't be confused with the one it shadows",
+                },
+                SrcLint {
+                    source: "[1.0]",
+                    level: Allow,
+                    message: "array is constructed again on every iteration of the loop",
+                    help: "the array doesn't depend on the loop, so bind it to a variable before the loop",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn rebinding_in_same_scope_is_not_shadowing() {
+    check(
+        "let x = 1; let x = 2; for i in 0..1 {} let i = 3;",
+        &expect![[r"
+            []
+        "]],
+    );
+}
+
+#[test]
+fn loop_invariant_array() {
+    check(
+        "mutable x = 0; while x < 3 { let a = [1, 2, 3]; let b = [x, 1]; let c = Repeated(0.0, 4); set x += 1; }",
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "[1, 2, 3]",
+                    level: Allow,
+                    message: "array is constructed again on every iteration of the loop",
+                    help: "the array doesn't depend on the loop, so bind it to a variable before the loop",
+                },
+                SrcLint {
+                    source: "Repeated(0.0, 4)",
+                    level: Allow,
+                    message: "array is constructed again on every iteration of the loop",
+                    help: "the array doesn't depend on the loop, so bind it to a variable before the loop",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn loop_invariant_array_in_nested_loop() {
+    check(
+        "for i in 0..1 { for j in 0..1 { let a = [i, 0]; let b = [j, 0]; } }",
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "[i, 0]",
+                    level: Allow,
+                    message: "array is constructed again on every iteration of the loop",
+                    help: "the array doesn't depend on the loop, so bind it to a variable before the loop",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn needless_mutable() {
+    check(
+        "mutable x = 1; mutable (y, z) = (2, 3); set y += x;",
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "x",
+                    level: Allow,
+                    message: "mutable variable is never reassigned",
+                    help: "declare it with `let` instead, so that arrays can share their storage instead of being copied",
+                },
+                SrcLint {
+                    source: "z",
+                    level: Allow,
+                    message: "mutable variable is never reassigned",
+                    help: "declare it with `let` instead, so that arrays can share their storage instead of being copied",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn mutable_updated_in_place_is_needed() {
+    check(
+        "mutable arr = [0, 0]; set arr w/= 0 <- 1; mutable (a, b) = (1, 2); set (a, b) = (b, a);",
+        &expect![[r"
+            []
+        "]],
+    );
+}
+
+#[test]
+fn unused_open() {
+    check_source(
+        indoc! {"
+            namespace foo {
+                open Microsoft.Quantum.Math;
+                open Microsoft.Quantum.Arrays;
+                open Microsoft.Quantum.Math;
+                function Main() : Double {
+                    PI()
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "open Microsoft.Quantum.Math;",
+                    level: Warn,
+                    message: "unused open statement",
+                    help: "remove the open statement",
+                },
+                SrcLint {
+                    source: "open Microsoft.Quantum.Arrays;",
+                    level: Warn,
+                    message: "unused open statement",
+                    help: "remove the open statement",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn alias_shadows_namespace() {
+    check_source(
+        indoc! {"
+            namespace foo {
+                open Microsoft.Quantum.Arrays as Microsoft.Quantum.Math;
+                open Microsoft.Quantum.Math as Math;
+                function Main() : Double {
+                    Microsoft.Quantum.Math.Head([Math.PI()])
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "Microsoft.Quantum.Math",
+                    level: Warn,
+                    message: "alias of open statement shadows a namespace",
+                    help: "rename the alias so that paths qualified with it can't be confused with the namespace",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn deprecated_callable() {
+    check_source(
+        indoc! {r#"
+            namespace foo {
+                @Deprecated("use Bar instead")
+                function Foo() : Unit {}
+                function Bar() : Unit {}
+                function Main() : Unit {
+                    Foo();
+                    Bar();
+                }
+            }
+        "#},
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "Foo",
+                    level: Warn,
+                    message: "use of deprecated callable `Foo`: use Bar instead",
+                    help: "use the replacement suggested in the message",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn top_level_statements() {
+    check_source(
+        indoc! {"
+            namespace foo {
+                operation Main() : Unit {}
+            }
+            open Microsoft.Quantum.Math;
+            use q = Qubit();
+            H(q);;
+            function Helper() : Unit {}
+        "},
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "use q = Qubit();",
+                    level: Error,
+                    message: "statements are not allowed outside of callables in a project",
+                    help: "move the statements into an operation marked with `@EntryPoint()`",
+                },
+                SrcLint {
+                    source: "H(q);",
+                    level: Error,
+                    message: "statements are not allowed outside of callables in a project",
+                    help: "move the statements into an operation marked with `@EntryPoint()`",
+                },
+                SrcLint {
+                    source: "open Microsoft.Quantum.Math;",
+                    level: Warn,
+                    message: "unused open statement",
+                    help: "remove the open statement",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn redundant_controlled_spec() {
+    check_source(
+        indoc! {"
+            namespace foo {
+                operation Foo(qs : Qubit[]) : Unit is Ctl {
+                    body ... {
+                        for i in 0..Length(qs) - 1 {
+                            H(qs[i]);
+                        }
+                    }
+                    controlled (cs, ...) {
+                        for i in 0..Length(qs) - 1 {
+                            Controlled H(cs, (qs[i]));
+                        }
+                    }
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "controlled (cs, ...) {\n            for i in 0..Length(qs) - 1 {\n                Controlled H(cs, (qs[i]));\n            }\n        }",
+                    level: Warn,
+                    message: "controlled specialization is identical to the generated one",
+                    help: "replace the implementation with `controlled auto;`",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn redundant_controlled_spec_within_apply() {
+    check_source(
+        indoc! {"
+            namespace foo {
+                operation Foo(q : Qubit) : Unit is Ctl {
+                    body ... {
+                        within {
+                            H(q);
+                        } apply {
+                            Z(q);
+                        }
+                    }
+                    controlled (ctls, ...) {
+                        within {
+                            H(q);
+                        } apply {
+                            Controlled Z(ctls, q);
+                        }
+                    }
+                }
+            }
+        "},
+        &expect![[r#"
+            [
+                SrcLint {
+                    source: "controlled (ctls, ...) {\n            within {\n                H(q);\n            } apply {\n                Controlled Z(ctls, q);\n            }\n        }",
+                    level: Warn,
+                    message: "controlled specialization is identical to the generated one",
+                    help: "replace the implementation with `controlled auto;`",
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn controlled_spec_different_from_generated() {
+    check_source(
+        indoc! {"
+            namespace foo {
+                operation Foo(q : Qubit) : Unit is Ctl {
+                    body ... {
+                        H(q);
+                        Z(q);
+                    }
+                    controlled (cs, ...) {
+                        Controlled H(cs, q);
+                        Z(q);
+                    }
+                }
+            }
+        "},
+        &expect![[r"
+            []
+        "]],
+    );
+}
+
 fn check(source: &str, expected: &Expect) {
+    check_source(&wrap_in_namespace(source), expected);
+}
+
+fn check_source(source: &str, expected: &Expect) {
+    let (store, unit) = compile_source(source);
+    let actual: Vec<SrcLint> = run_lints(&store, &unit, None)
+        .into_iter()
+        .map(|lint| SrcLint::from(&lint, source))
+        .collect();
+
+    expected.assert_debug_eq(&actual);
+}
+
+/// Checks the source after applying the fixes of the lints, including the ones that are allowed by default.
+fn check_fixes(source: &str, expected: &str) {
     let source = wrap_in_namespace(source);
+    let (store, unit) = compile_source(&source);
+    let lints = run_lints(&store, &unit, None);
+    assert_eq!(apply_fixes(&source, &lints), wrap_in_namespace(expected));
+}
+
+fn compile_source(source: &str) -> (PackageStore, CompileUnit) {
     let mut store = PackageStore::new(compile::core());
     let std = store.insert(compile::std(&store, RuntimeCapabilityFlags::all()));
-    let sources = SourceMap::new([("source.qs".into(), source.clone().into())], None);
-    let (package, _) = qsc::compile::compile(
+    let sources = SourceMap::new([("source.qs".into(), source.into())], None);
+    // Compile like the language service does, so that top-level statements reach the linter.
+    let (unit, _) = qsc::compile::compile_with_parse_cache(
         &store,
         &[std],
         sources,
+        &mut ParseCache::default(),
+        true,
         PackageType::Exe,
         RuntimeCapabilityFlags::all(),
         LanguageFeatures::default(),
     );
-
-    let actual: Vec<SrcLint> = run_lints(&package, None)
-        .into_iter()
-        .map(|lint| SrcLint::from(&lint, &source))
-        .collect();
-
-    expected.assert_debug_eq(&actual);
+    (store, unit)
 }
 
 /// Wraps some source code into a namespace, to make testing easier.
@@ -198,7 +678,7 @@ fn wrap_in_namespace(source: &str) -> String {
 struct SrcLint {
     source: String,
     level: LintLevel,
-    message: &'static str,
+    message: String,
     help: &'static str,
 }
 
@@ -207,7 +687,7 @@ impl SrcLint {
         Self {
             source: source[lint.span].into(),
             level: lint.level,
-            message: lint.message,
+            message: lint.message.to_string(),
             help: lint.help,
         }
     }
@@ -228,9 +708,13 @@ impl std::fmt::Display for SrcLint {
     }
 }
 
-fn run_lints(compile_unit: &CompileUnit, config: Option<&[LintConfig]>) -> Vec<Lint> {
-    let mut ast_lints = run_ast_lints(&compile_unit.ast.package, config);
-    let mut hir_lints = run_hir_lints(&compile_unit.package, config);
+fn run_lints(
+    package_store: &PackageStore,
+    compile_unit: &CompileUnit,
+    config: Option<&[LintConfig]>,
+) -> Vec<Lint> {
+    let mut ast_lints = run_ast_lints(&compile_unit.ast, config);
+    let mut hir_lints = run_hir_lints(&compile_unit.package, package_store, config);
     let mut lints = Vec::new();
     lints.append(&mut ast_lints);
     lints.append(&mut hir_lints);
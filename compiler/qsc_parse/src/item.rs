@@ -18,7 +18,7 @@ use super::{
 };
 use crate::{
     lex::{Delim, TokenKind},
-    prim::{barrier, recovering, recovering_token, shorten},
+    prim::{barrier, recovering, recovering_many, recovering_token, shorten},
     stmt::check_semis,
     ty::array_or_arrow,
     ErrorKind,
@@ -68,6 +68,12 @@ pub(super) fn parse(s: &mut ParserContext) -> Result<Box<Item>> {
     }))
 }
 
+pub(super) fn parse_item_eof(s: &mut ParserContext) -> Result<Box<Item>> {
+    let item = parse_namespace_item(s)?;
+    token(s, TokenKind::Eof)?;
+    Ok(item)
+}
+
 #[allow(clippy::vec_box)]
 fn parse_many(s: &mut ParserContext) -> Result<Vec<Box<Item>>> {
     const BARRIER_TOKENS: &[TokenKind] = &[
@@ -82,12 +88,17 @@ fn parse_many(s: &mut ParserContext) -> Result<Vec<Box<Item>>> {
     const RECOVERY_TOKENS: &[TokenKind] = &[TokenKind::Semi, TokenKind::Close(Delim::Brace)];
 
     barrier(s, BARRIER_TOKENS, |s| {
-        many(s, |s| recovering(s, default, RECOVERY_TOKENS, parse))
+        Ok(recovering_many(
+            s,
+            default,
+            TokenKind::Close(Delim::Brace),
+            |s| recovering(s, default, RECOVERY_TOKENS, parse_namespace_item),
+        ))
     })
 }
 
 #[allow(clippy::unnecessary_box_returns)]
-fn default(span: Span) -> Box<Item> {
+pub(super) fn default(span: Span) -> Box<Item> {
     Box::new(Item {
         id: NodeId::default(),
         span,
@@ -212,6 +223,27 @@ fn parse_open(s: &mut ParserContext) -> Result<Box<ItemKind>> {
     Ok(Box::new(ItemKind::Open(name, alias)))
 }
 
+/// Parses an item of a namespace. `export` is a contextual keyword that is only recognized here, where an identifier
+/// can't start an item, so that it can still be used as a name everywhere else.
+fn parse_namespace_item(s: &mut ParserContext) -> Result<Box<Item>> {
+    if s.peek().kind == TokenKind::Ident && s.read() == "export" {
+        let lo = s.peek().span.lo;
+        s.advance();
+        let (names, _) = seq(s, ident)?;
+        token(s, TokenKind::Semi)?;
+        Ok(Box::new(Item {
+            id: NodeId::default(),
+            span: s.span(lo),
+            doc: "".into(),
+            attrs: Vec::new().into_boxed_slice(),
+            visibility: None,
+            kind: Box::new(ItemKind::Export(names.into_boxed_slice())),
+        }))
+    } else {
+        parse(s)
+    }
+}
+
 fn parse_newtype(s: &mut ParserContext) -> Result<Box<ItemKind>> {
     token(s, TokenKind::Keyword(Keyword::Newtype))?;
     let name = ident(s)?;
@@ -348,7 +380,7 @@ fn parse_callable_body(s: &mut ParserContext) -> Result<CallableBody> {
     barrier(s, &[TokenKind::Close(Delim::Brace)], |s| {
         let specs = many(s, parse_spec_decl)?;
         if specs.is_empty() {
-            let stmts = stmt::parse_many(s)?;
+            let stmts = stmt::parse_many(s);
             check_semis(s, &stmts);
             recovering_token(s, TokenKind::Close(Delim::Brace));
             Ok(CallableBody::Block(Box::new(Block {
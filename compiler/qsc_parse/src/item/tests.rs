@@ -134,6 +134,53 @@ fn open_alias_dot() {
     );
 }
 
+#[test]
+fn export_decl() {
+    check_vec(
+        parse_namespaces,
+        "namespace A { export Foo, Bar; }",
+        &expect![[r#"
+            Namespace _id_ [0-32] (Ident _id_ [10-11] "A"):
+                Item _id_ [14-30]:
+                    Export (Ident _id_ [21-24] "Foo") (Ident _id_ [26-29] "Bar")"#]],
+    );
+}
+
+#[test]
+fn export_is_not_an_item_keyword_outside_namespaces() {
+    check(
+        parse,
+        "export Foo;",
+        &expect![[r#"
+            Error(
+                Rule(
+                    "item",
+                    Ident,
+                    Span {
+                        lo: 0,
+                        hi: 6,
+                    },
+                ),
+            )
+        "#]],
+    );
+}
+
+#[test]
+fn callable_named_export() {
+    check(
+        parse,
+        "function export() : Unit {}",
+        &expect![[r#"
+            Item _id_ [0-27]:
+                Callable _id_ [0-27] (Function):
+                    name: Ident _id_ [9-15] "export"
+                    input: Pat _id_ [15-17]: Unit
+                    output: Type _id_ [20-24]: Path: Path _id_ [20-24] (Ident _id_ [20-24] "Unit")
+                    body: Block: Block _id_ [25-27]: <empty>"#]],
+    );
+}
+
 #[test]
 fn ty_decl() {
     check(
@@ -1378,6 +1425,45 @@ fn recover_callable_item() {
     );
 }
 
+#[test]
+fn recover_stray_tokens_between_items() {
+    check_vec(
+        parse_namespaces,
+        "namespace A { function Foo() : Unit {} let x = 1; function Bar() : Unit {} }",
+        &expect![[r#"
+            Namespace _id_ [0-76] (Ident _id_ [10-11] "A"):
+                Item _id_ [14-38]:
+                    Callable _id_ [14-38] (Function):
+                        name: Ident _id_ [23-26] "Foo"
+                        input: Pat _id_ [26-28]: Unit
+                        output: Type _id_ [31-35]: Path: Path _id_ [31-35] (Ident _id_ [31-35] "Unit")
+                        body: Block: Block _id_ [36-38]: <empty>
+                Item _id_ [39-49]:
+                    Err
+                Item _id_ [50-74]:
+                    Callable _id_ [50-74] (Function):
+                        name: Ident _id_ [59-62] "Bar"
+                        input: Pat _id_ [62-64]: Unit
+                        output: Type _id_ [67-71]: Path: Path _id_ [67-71] (Ident _id_ [67-71] "Unit")
+                        body: Block: Block _id_ [72-74]: <empty>
+
+            [
+                Error(
+                    Rule(
+                        "item",
+                        Keyword(
+                            Let,
+                        ),
+                        Span {
+                            lo: 39,
+                            hi: 42,
+                        },
+                    ),
+                ),
+            ]"#]],
+    );
+}
+
 #[test]
 fn recover_unclosed_callable_item() {
     check_vec(
@@ -1569,14 +1655,23 @@ fn reject_nested_namespace_with_items() {
             }
         }",
         &expect![[r#"
-            Namespace _id_ [0-99] (Ident _id_ [10-15] "Outer"):
+            Namespace _id_ [0-155] (Ident _id_ [10-15] "Outer"):
+                Item _id_ [30-47]:
+                    Err
+                Item _id_ [64-99]:
+                    Callable _id_ [64-99] (Function):
+                        name: Ident _id_ [73-87] "NestedFunction"
+                        input: Pat _id_ [87-89]: Unit
+                        output: Type _id_ [92-96]: Path: Path _id_ [92-96] (Ident _id_ [92-96] "Unit")
+                        body: Block: Block _id_ [97-99]: <empty>
+                Item _id_ [116-141]:
+                    New Type (Ident _id_ [124-134] "NestedType"): TyDef _id_ [137-140]: Field:
+                        Type _id_ [137-140]: Path: Path _id_ [137-140] (Ident _id_ [137-140] "Int")
 
             [
                 Error(
-                    Token(
-                        Close(
-                            Brace,
-                        ),
+                    Rule(
+                        "item",
                         Keyword(
                             Namespace,
                         ),
@@ -1589,12 +1684,12 @@ fn reject_nested_namespace_with_items() {
                 Error(
                     Token(
                         Eof,
-                        Keyword(
-                            Newtype,
+                        Close(
+                            Brace,
                         ),
                         Span {
-                            lo: 116,
-                            hi: 123,
+                            lo: 164,
+                            hi: 165,
                         },
                     ),
                 ),
@@ -1614,14 +1709,20 @@ fn reject_namespace_with_multiple_nested_levels() {
             }
         }",
         &expect![[r#"
-            Namespace _id_ [0-146] (Ident _id_ [10-18] "LevelOne"):
+            Namespace _id_ [0-164] (Ident _id_ [10-18] "LevelOne"):
+                Item _id_ [33-92]:
+                    Err
+                Item _id_ [113-146]:
+                    Callable _id_ [113-146] (Function):
+                        name: Ident _id_ [122-134] "DeepFunction"
+                        input: Pat _id_ [134-136]: Unit
+                        output: Type _id_ [139-143]: Path: Path _id_ [139-143] (Ident _id_ [139-143] "Unit")
+                        body: Block: Block _id_ [144-146]: <empty>
 
             [
                 Error(
-                    Token(
-                        Close(
-                            Brace,
-                        ),
+                    Rule(
+                        "item",
                         Keyword(
                             Namespace,
                         ),
@@ -1638,8 +1739,8 @@ fn reject_namespace_with_multiple_nested_levels() {
                             Brace,
                         ),
                         Span {
-                            lo: 163,
-                            hi: 164,
+                            lo: 177,
+                            hi: 178,
                         },
                     ),
                 ),
@@ -18,7 +18,7 @@ mod ty;
 
 use lex::TokenKind;
 use miette::Diagnostic;
-use qsc_ast::ast::{Expr, Namespace, TopLevelNode};
+use qsc_ast::ast::{Expr, Item, Namespace, TopLevelNode};
 use qsc_data_structures::{language_features::LanguageFeatures, span::Span};
 use scan::ParserContext;
 use std::result;
@@ -133,6 +133,21 @@ pub fn top_level_nodes(
     }
 }
 
+/// Parses a single item of a namespace, such as a callable declaration or an export, that spans the
+/// whole input.
+#[must_use]
+pub fn item(input: &str, language_features: LanguageFeatures) -> (Box<Item>, Vec<Error>) {
+    let mut scanner = ParserContext::new(input, language_features);
+    match item::parse_item_eof(&mut scanner) {
+        Ok(item) => (item, scanner.into_errors()),
+        Err(error) => {
+            let mut errors = scanner.into_errors();
+            errors.push(error);
+            (item::default(Span::default()), errors)
+        }
+    }
+}
+
 #[must_use]
 pub fn expr(input: &str, language_features: LanguageFeatures) -> (Box<Expr>, Vec<Error>) {
     let mut scanner = ParserContext::new(input, language_features);
@@ -227,6 +227,31 @@ pub(super) fn recovering<T>(
     }
 }
 
+/// Parses a sequence of `p` up to the `end` token. Unlike [`many`], a token that `p` can't start at
+/// doesn't end the sequence: the error is reported and the tokens up to the next semicolon or
+/// barrier token are skipped, so that the rest of the sequence is still parsed.
+pub(super) fn recovering_many<T>(
+    s: &mut ParserContext,
+    default: impl Fn(Span) -> T,
+    end: TokenKind,
+    mut p: impl Parser<T>,
+) -> Vec<T> {
+    let mut xs = Vec::new();
+    while s.peek().kind != end && s.peek().kind != TokenKind::Eof {
+        let offset = s.peek().span.lo;
+        match p(s) {
+            Ok(x) => xs.push(x),
+            Err(error) => {
+                s.push_error(error);
+                s.advance();
+                s.recover(&[TokenKind::Semi]);
+                xs.push(default(s.span(offset)));
+            }
+        }
+    }
+    xs
+}
+
 pub(super) fn recovering_semi(s: &mut ParserContext) {
     if let Err(error) = token(s, TokenKind::Semi) {
         // no recovery, just move on to the next token
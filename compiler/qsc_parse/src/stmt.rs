@@ -8,13 +8,13 @@ use super::{
     expr::{self, expr, expr_stmt},
     item,
     keyword::Keyword,
-    prim::{ident, many, opt, pat, seq, token},
+    prim::{ident, opt, pat, seq, token},
     scan::ParserContext,
     Error, Result,
 };
 use crate::{
     lex::{Delim, TokenKind},
-    prim::{barrier, recovering, recovering_semi, recovering_token},
+    prim::{barrier, recovering, recovering_many, recovering_semi, recovering_token},
     ErrorKind,
 };
 use qsc_ast::ast::{
@@ -49,14 +49,16 @@ pub(super) fn parse(s: &mut ParserContext) -> Result<Box<Stmt>> {
 }
 
 #[allow(clippy::vec_box)]
-pub(super) fn parse_many(s: &mut ParserContext) -> Result<Vec<Box<Stmt>>> {
-    many(s, |s| recovering(s, default, &[TokenKind::Semi], parse))
+pub(super) fn parse_many(s: &mut ParserContext) -> Vec<Box<Stmt>> {
+    recovering_many(s, default, TokenKind::Close(Delim::Brace), |s| {
+        recovering(s, default, &[TokenKind::Semi], parse)
+    })
 }
 
 pub(super) fn parse_block(s: &mut ParserContext) -> Result<Box<Block>> {
     let lo = s.peek().span.lo;
     token(s, TokenKind::Open(Delim::Brace))?;
-    let stmts = barrier(s, &[TokenKind::Close(Delim::Brace)], parse_many)?;
+    let stmts = barrier(s, &[TokenKind::Close(Delim::Brace)], |s| Ok(parse_many(s)))?;
     check_semis(s, &stmts);
     recovering_token(s, TokenKind::Close(Delim::Brace));
     Ok(Box::new(Block {
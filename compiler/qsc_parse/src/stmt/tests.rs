@@ -765,6 +765,41 @@ fn recover_in_nested_block() {
     );
 }
 
+#[test]
+fn recover_stray_token_between_statements() {
+    check(
+        parse_block,
+        "{ let x = 1; ); let y = x; y }",
+        &expect![[r#"
+            Block _id_ [0-30]:
+                Stmt _id_ [2-12]: Local (Immutable):
+                    Pat _id_ [6-7]: Bind:
+                        Ident _id_ [6-7] "x"
+                    Expr _id_ [10-11]: Lit: Int(1)
+                Stmt _id_ [13-15]: Err
+                Stmt _id_ [16-26]: Local (Immutable):
+                    Pat _id_ [20-21]: Bind:
+                        Ident _id_ [20-21] "y"
+                    Expr _id_ [24-25]: Path: Path _id_ [24-25] (Ident _id_ [24-25] "x")
+                Stmt _id_ [27-28]: Expr: Expr _id_ [27-28]: Path: Path _id_ [27-28] (Ident _id_ [27-28] "y")
+
+            [
+                Error(
+                    Rule(
+                        "expression",
+                        Close(
+                            Paren,
+                        ),
+                        Span {
+                            lo: 13,
+                            hi: 14,
+                        },
+                    ),
+                ),
+            ]"#]],
+    );
+}
+
 #[test]
 fn recover_statements_before_and_after() {
     check(
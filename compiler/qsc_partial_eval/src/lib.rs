@@ -0,0 +1,533 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Partial evaluation is the process of executing the classical fragments of a program at compile time so that only
+//! quantum operations and computations that depend on dynamic values remain. It uses the results of runtime
+//! capabilities analysis (RCA) to identify expressions that are purely classical, evaluates them using the Q# evaluator
+//! and replaces them in the FIR with the literals they evaluate to. The simplified FIR can then be fed to code
+//! generation for targets with constrained runtime capabilities.
+
+#[cfg(test)]
+mod tests;
+
+use num_bigint::BigUint;
+use num_complex::Complex64;
+use qsc_eval::{
+    backend::SparseSim,
+    output::{self, Receiver},
+    val::{self, Value},
+    Env, State, StepAction, StepResult,
+};
+use qsc_fir::{
+    fir::{
+        Block, BlockId, CallableImpl, CallableKind, ExecGraphNode, Expr, ExprId, ExprKind, Global,
+        ItemId, ItemKind, Lit, LocalItemId, LocalVarId, Package, PackageId, PackageLookup,
+        PackageStore, PackageStoreLookup, Pat, PatId, PatKind, Res, SpecDecl, Stmt, StmtId,
+        StoreExprId, StoreItemId, StringComponent,
+    },
+    ty::{Prim, Ty},
+    visit::{self, Visitor},
+};
+use qsc_rca::{ComputeKind, ComputePropertiesLookup, PackageStoreComputeProperties};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::{ops::Range, sync::Arc};
+
+/// The maximum number of statements the evaluator can step through when evaluating a single expression at compile
+/// time. Expressions that take longer to evaluate are left untouched so they are computed at runtime.
+const STEP_LIMIT: usize = 10_000;
+
+/// Intrinsic callables that are classical but whose results cannot be determined at compile time.
+const NON_DETERMINISTIC_INTRINSICS: [&str; 2] = ["DrawRandomInt", "DrawRandomDouble"];
+
+/// Partially evaluates the package with the specified ID, replacing all the classical expressions that can be
+/// evaluated at compile time with the literals they evaluate to.
+/// Returns the IDs of the expressions that were replaced.
+pub fn partially_evaluate(
+    package_id: PackageId,
+    package_store: &mut PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+) -> Vec<ExprId> {
+    let package = package_store.get(package_id);
+    let mut graph_owners = collect_graph_owners(package, |_| true);
+    if let Some(entry) = package.entry {
+        graph_owners.push(GraphOwner::Entry(entry));
+    }
+    fold_graph_owners(package_id, package_store, compute_properties, graph_owners)
+}
+
+/// Partially evaluates the callables of the package with the specified ID that `include` accepts, leaving the rest of
+/// the package and its entry expression untouched. This lets a package that grows incrementally, like the fragments of
+/// an interpreter, have only the callables added since it was last partially evaluated folded.
+/// Returns the IDs of the expressions that were replaced.
+pub fn partially_evaluate_callables(
+    package_id: PackageId,
+    include: impl Fn(LocalItemId) -> bool,
+    package_store: &mut PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+) -> Vec<ExprId> {
+    let graph_owners = collect_graph_owners(package_store.get(package_id), include);
+    fold_graph_owners(package_id, package_store, compute_properties, graph_owners)
+}
+
+fn fold_graph_owners(
+    package_id: PackageId,
+    package_store: &mut PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    graph_owners: Vec<GraphOwner>,
+) -> Vec<ExprId> {
+    let mut folded_exprs = Vec::new();
+    for graph_owner in graph_owners {
+        let folds = {
+            let mut folder = Folder::new(package_id, package_store, compute_properties);
+            folder.fold_graph_owner(graph_owner);
+            folder.folds
+        };
+
+        if folds.is_empty() {
+            continue;
+        }
+
+        let package = package_store.get_mut(package_id);
+        let mut exec_graph = graph_owner.exec_graph(package).to_vec();
+        for fold in folds {
+            patch_exec_graph(&mut exec_graph, fold.expr, &fold.exec_graph_range);
+            package
+                .exprs
+                .get_mut(fold.expr)
+                .expect("expression should exist")
+                .kind = fold.kind;
+            folded_exprs.push(fold.expr);
+        }
+        graph_owner.set_exec_graph(package, exec_graph.into());
+    }
+
+    folded_exprs
+}
+
+/// A program element that owns an execution graph.
+#[derive(Clone, Copy, Debug)]
+enum GraphOwner {
+    Entry(ExprId),
+    Spec(LocalItemId, SpecKind),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum SpecKind {
+    Body,
+    Adj,
+    Ctl,
+    CtlAdj,
+}
+
+impl GraphOwner {
+    fn exec_graph(self, package: &Package) -> &Arc<[ExecGraphNode]> {
+        match self {
+            Self::Entry(_) => &package.entry_exec_graph,
+            Self::Spec(item_id, spec_kind) => &spec_decl(package, item_id, spec_kind).exec_graph,
+        }
+    }
+
+    fn set_exec_graph(self, package: &mut Package, exec_graph: Arc<[ExecGraphNode]>) {
+        match self {
+            Self::Entry(_) => package.entry_exec_graph = exec_graph,
+            Self::Spec(item_id, spec_kind) => {
+                let item = package.items.get_mut(item_id).expect("item should exist");
+                let ItemKind::Callable(decl) = &mut item.kind else {
+                    panic!("item should be a callable");
+                };
+                let CallableImpl::Spec(spec_impl) = &mut decl.implementation else {
+                    panic!("callable should have specializations");
+                };
+                let spec_decl = match spec_kind {
+                    SpecKind::Body => Some(&mut spec_impl.body),
+                    SpecKind::Adj => spec_impl.adj.as_mut(),
+                    SpecKind::Ctl => spec_impl.ctl.as_mut(),
+                    SpecKind::CtlAdj => spec_impl.ctl_adj.as_mut(),
+                }
+                .expect("specialization should exist");
+                spec_decl.exec_graph = exec_graph;
+            }
+        }
+    }
+}
+
+fn spec_decl(package: &Package, item_id: LocalItemId, spec_kind: SpecKind) -> &SpecDecl {
+    let ItemKind::Callable(decl) = &package.get_item(item_id).kind else {
+        panic!("item should be a callable");
+    };
+    let CallableImpl::Spec(spec_impl) = &decl.implementation else {
+        panic!("callable should have specializations");
+    };
+    match spec_kind {
+        SpecKind::Body => Some(&spec_impl.body),
+        SpecKind::Adj => spec_impl.adj.as_ref(),
+        SpecKind::Ctl => spec_impl.ctl.as_ref(),
+        SpecKind::CtlAdj => spec_impl.ctl_adj.as_ref(),
+    }
+    .expect("specialization should exist")
+}
+
+fn collect_graph_owners(
+    package: &Package,
+    include: impl Fn(LocalItemId) -> bool,
+) -> Vec<GraphOwner> {
+    let mut graph_owners = Vec::new();
+    for (item_id, item) in &package.items {
+        let ItemKind::Callable(decl) = &item.kind else {
+            continue;
+        };
+        if !include(item_id) {
+            continue;
+        }
+        let CallableImpl::Spec(spec_impl) = &decl.implementation else {
+            continue;
+        };
+        graph_owners.push(GraphOwner::Spec(item_id, SpecKind::Body));
+        if spec_impl.adj.is_some() {
+            graph_owners.push(GraphOwner::Spec(item_id, SpecKind::Adj));
+        }
+        if spec_impl.ctl.is_some() {
+            graph_owners.push(GraphOwner::Spec(item_id, SpecKind::Ctl));
+        }
+        if spec_impl.ctl_adj.is_some() {
+            graph_owners.push(GraphOwner::Spec(item_id, SpecKind::CtlAdj));
+        }
+    }
+
+    graph_owners
+}
+
+/// Replaces the nodes of an expression in an execution graph so that the expression itself is the only node executed.
+/// The remaining nodes in the range become unreachable, which keeps the indices of all other nodes stable.
+fn patch_exec_graph(exec_graph: &mut [ExecGraphNode], expr: ExprId, range: &Range<usize>) {
+    exec_graph[range.start] = ExecGraphNode::Expr(expr);
+    if range.len() > 1 {
+        exec_graph[range.start + 1] =
+            ExecGraphNode::Jump(range.end.try_into().expect("nodes should fit into u32"));
+    }
+}
+
+struct Fold {
+    expr: ExprId,
+    kind: ExprKind,
+    exec_graph_range: Range<usize>,
+}
+
+struct Folder<'a> {
+    package_id: PackageId,
+    package_store: &'a PackageStore,
+    compute_properties: &'a PackageStoreComputeProperties,
+    exec_graph: Arc<[ExecGraphNode]>,
+    deterministic_callables: FxHashMap<(PackageId, LocalItemId), bool>,
+    folds: Vec<Fold>,
+}
+
+impl<'a> Folder<'a> {
+    fn new(
+        package_id: PackageId,
+        package_store: &'a PackageStore,
+        compute_properties: &'a PackageStoreComputeProperties,
+    ) -> Self {
+        Self {
+            package_id,
+            package_store,
+            compute_properties,
+            exec_graph: Arc::from([]),
+            deterministic_callables: FxHashMap::default(),
+            folds: Vec::new(),
+        }
+    }
+
+    fn package(&self) -> &'a Package {
+        self.package_store.get(self.package_id)
+    }
+
+    fn fold_graph_owner(&mut self, graph_owner: GraphOwner) {
+        let package = self.package();
+        self.exec_graph = graph_owner.exec_graph(package).clone();
+        match graph_owner {
+            GraphOwner::Entry(expr_id) => self.visit_expr(expr_id),
+            GraphOwner::Spec(item_id, spec_kind) => {
+                self.visit_block(spec_decl(package, item_id, spec_kind).block);
+            }
+        }
+    }
+
+    fn try_fold(&mut self, expr_id: ExprId) -> bool {
+        let expr = self.get_expr(expr_id);
+        if !is_foldable_kind(expr) || !self.is_classical(expr_id) {
+            return false;
+        }
+
+        let mut references = References::new(self.package());
+        references.visit_expr(expr_id);
+        if !references.is_closed() || !self.are_deterministic(references.items, self.package_id) {
+            return false;
+        }
+
+        let Some(kind) = self
+            .evaluate(&expr.exec_graph_range)
+            .and_then(value_to_expr_kind)
+        else {
+            return false;
+        };
+
+        self.folds.push(Fold {
+            expr: expr_id,
+            kind,
+            exec_graph_range: expr.exec_graph_range.clone(),
+        });
+        true
+    }
+
+    fn is_classical(&self, expr_id: ExprId) -> bool {
+        matches!(
+            self.compute_properties
+                .find_expr(StoreExprId::from((self.package_id, expr_id))),
+            Some(generator_set) if matches!(generator_set.inherent, ComputeKind::Classical)
+        )
+    }
+
+    /// Whether all the callables referenced, directly or indirectly, produce the same results every time they are
+    /// called.
+    fn are_deterministic(&mut self, items: FxHashSet<ItemId>, package_id: PackageId) -> bool {
+        items.into_iter().all(|item_id| {
+            let store_item_id = StoreItemId {
+                package: item_id.package.unwrap_or(package_id),
+                item: item_id.item,
+            };
+            self.is_deterministic(store_item_id)
+        })
+    }
+
+    fn is_deterministic(&mut self, id: StoreItemId) -> bool {
+        if let Some(is_deterministic) = self.deterministic_callables.get(&(id.package, id.item)) {
+            return *is_deterministic;
+        }
+
+        // Assume the callable is deterministic while it is being analyzed so cycles terminate.
+        self.deterministic_callables
+            .insert((id.package, id.item), true);
+        let is_deterministic = match self.package_store.get_global(id) {
+            Some(Global::Callable(decl)) if decl.kind == CallableKind::Operation => false,
+            Some(Global::Callable(decl)) => match &decl.implementation {
+                CallableImpl::Intrinsic => {
+                    !NON_DETERMINISTIC_INTRINSICS.contains(&decl.name.name.as_ref())
+                }
+                CallableImpl::Spec(spec_impl) => {
+                    let mut references = References::new(self.package_store.get(id.package));
+                    references.visit_spec_impl(spec_impl);
+                    self.are_deterministic(references.items, id.package)
+                }
+            },
+            Some(Global::Udt) => true,
+            None => false,
+        };
+        self.deterministic_callables
+            .insert((id.package, id.item), is_deterministic);
+        is_deterministic
+    }
+
+    /// Evaluates the portion of the current execution graph that corresponds to an expression.
+    /// Returns `None` if the evaluation fails, produces output or exceeds the step limit.
+    fn evaluate(&self, range: &Range<usize>) -> Option<Value> {
+        let offset = u32::try_from(range.start).expect("nodes should fit into u32");
+        let exec_graph = self.exec_graph[range.clone()]
+            .iter()
+            .map(|node| match node {
+                ExecGraphNode::Jump(idx) => idx.checked_sub(offset).map(ExecGraphNode::Jump),
+                ExecGraphNode::JumpIf(idx) => idx.checked_sub(offset).map(ExecGraphNode::JumpIf),
+                ExecGraphNode::JumpIfNot(idx) => {
+                    idx.checked_sub(offset).map(ExecGraphNode::JumpIfNot)
+                }
+                _ => Some(*node),
+            })
+            .collect::<Option<Arc<[ExecGraphNode]>>>()?;
+
+        let mut state = State::new(self.package_id, exec_graph, None);
+        let mut env = Env::default();
+        let mut sim = SparseSim::new();
+        let mut out = OutputDetector::default();
+        for _ in 0..STEP_LIMIT {
+            match state.eval(
+                self.package_store,
+                &mut env,
+                &mut sim,
+                &mut out,
+                &[],
+                StepAction::In,
+            ) {
+                Ok(StepResult::Return(value)) => return (!out.has_output).then_some(value),
+                Ok(_) => {}
+                Err(_) => return None,
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> Visitor<'a> for Folder<'a> {
+    fn visit_expr(&mut self, expr: ExprId) {
+        if !self.try_fold(expr) {
+            visit::walk_expr(self, expr);
+        }
+    }
+
+    fn get_block(&self, id: BlockId) -> &'a Block {
+        self.package().get_block(id)
+    }
+
+    fn get_expr(&self, id: ExprId) -> &'a Expr {
+        self.package().get_expr(id)
+    }
+
+    fn get_pat(&self, id: PatId) -> &'a Pat {
+        self.package().get_pat(id)
+    }
+
+    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
+        self.package().get_stmt(id)
+    }
+}
+
+/// Whether an expression is of a kind and type that can be replaced by a literal.
+fn is_foldable_kind(expr: &Expr) -> bool {
+    let is_literal = match &expr.kind {
+        ExprKind::Lit(_) => true,
+        ExprKind::String(components) => components
+            .iter()
+            .all(|component| matches!(component, StringComponent::Lit(_))),
+        _ => false,
+    };
+
+    !is_literal
+        && matches!(
+            expr.ty,
+            Ty::Prim(
+                Prim::BigInt
+                    | Prim::Bool
+                    | Prim::Double
+                    | Prim::Int
+                    | Prim::Pauli
+                    | Prim::Result
+                    | Prim::String
+            )
+        )
+}
+
+fn value_to_expr_kind(value: Value) -> Option<ExprKind> {
+    let lit = match value {
+        Value::BigInt(value) => Lit::BigInt(value),
+        Value::Bool(value) => Lit::Bool(value),
+        Value::Double(value) => Lit::Double(value),
+        Value::Int(value) => Lit::Int(value),
+        Value::Pauli(value) => Lit::Pauli(value),
+        Value::Result(val::Result::Val(value)) => Lit::Result(if value {
+            qsc_fir::fir::Result::One
+        } else {
+            qsc_fir::fir::Result::Zero
+        }),
+        Value::String(value) => {
+            return Some(ExprKind::String(vec![StringComponent::Lit(
+                value.as_ref().into(),
+            )]))
+        }
+        _ => return None,
+    };
+    Some(ExprKind::Lit(lit))
+}
+
+/// Collects the locals and items referenced by a program element.
+struct References<'a> {
+    package: &'a Package,
+    bound_locals: FxHashSet<LocalVarId>,
+    used_locals: FxHashSet<LocalVarId>,
+    items: FxHashSet<ItemId>,
+    has_escaping_control_flow: bool,
+}
+
+impl<'a> References<'a> {
+    fn new(package: &'a Package) -> Self {
+        Self {
+            package,
+            bound_locals: FxHashSet::default(),
+            used_locals: FxHashSet::default(),
+            items: FxHashSet::default(),
+            has_escaping_control_flow: false,
+        }
+    }
+
+    /// Whether the element only uses locals it binds itself and has no control flow that escapes it.
+    fn is_closed(&self) -> bool {
+        !self.has_escaping_control_flow && self.used_locals.is_subset(&self.bound_locals)
+    }
+}
+
+impl<'a> Visitor<'a> for References<'a> {
+    fn visit_expr(&mut self, expr: ExprId) {
+        match &self.get_expr(expr).kind {
+            ExprKind::Var(Res::Local(local_var_id), _) => {
+                self.used_locals.insert(*local_var_id);
+            }
+            ExprKind::Var(Res::Item(item_id), _) => {
+                self.items.insert(*item_id);
+            }
+            ExprKind::Closure(..) | ExprKind::Hole | ExprKind::Return(_) | ExprKind::Var(..) => {
+                self.has_escaping_control_flow = true;
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, expr);
+    }
+
+    fn visit_pat(&mut self, pat: PatId) {
+        if let PatKind::Bind(ident) = &self.get_pat(pat).kind {
+            self.bound_locals.insert(ident.id);
+        }
+        visit::walk_pat(self, pat);
+    }
+
+    fn get_block(&self, id: BlockId) -> &'a Block {
+        self.package.get_block(id)
+    }
+
+    fn get_expr(&self, id: ExprId) -> &'a Expr {
+        self.package.get_expr(id)
+    }
+
+    fn get_pat(&self, id: PatId) -> &'a Pat {
+        self.package.get_pat(id)
+    }
+
+    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
+        self.package.get_stmt(id)
+    }
+}
+
+/// A receiver that records whether any output was produced.
+#[derive(Default)]
+struct OutputDetector {
+    has_output: bool,
+}
+
+impl Receiver for OutputDetector {
+    fn state(
+        &mut self,
+        _state: Vec<(BigUint, Complex64)>,
+        _qubit_count: usize,
+    ) -> Result<(), output::Error> {
+        self.has_output = true;
+        Ok(())
+    }
+
+    fn matrix(&mut self, _matrix: Vec<Vec<Complex64>>) -> Result<(), output::Error> {
+        self.has_output = true;
+        Ok(())
+    }
+
+    fn message(&mut self, _msg: &str) -> Result<(), output::Error> {
+        self.has_output = true;
+        Ok(())
+    }
+}
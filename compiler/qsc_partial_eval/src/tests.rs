@@ -0,0 +1,340 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::{partially_evaluate, partially_evaluate_callables};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc::incremental::Compiler;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_eval::{
+    backend::SparseSim, debug::map_hir_package_to_fir, lower::Lowerer, output::GenericReceiver, Env,
+};
+use qsc_fir::fir::{ItemKind, PackageLookup, PackageStore};
+use qsc_frontend::compile::{RuntimeCapabilityFlags, SourceMap};
+use qsc_passes::PackageType;
+use qsc_rca::Analyzer;
+use std::fmt::Write;
+
+struct PartiallyEvaluated {
+    fir_store: PackageStore,
+    package_id: qsc_fir::fir::PackageId,
+    folded: String,
+}
+
+fn lower_source(source: &str) -> (PackageStore, qsc_fir::fir::PackageId) {
+    let sources = SourceMap::new([("test".into(), source.into())], None);
+    let compiler = Compiler::new(
+        true,
+        sources,
+        PackageType::Exe,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    )
+    .expect("code should compile");
+    let mut lowerer = Lowerer::new();
+    let mut fir_store = PackageStore::new();
+    for (id, unit) in compiler.package_store() {
+        fir_store.insert(
+            map_hir_package_to_fir(id),
+            lowerer.lower_package(&unit.package),
+        );
+    }
+
+    (
+        fir_store,
+        map_hir_package_to_fir(compiler.source_package_id()),
+    )
+}
+
+fn partially_evaluate_source(source: &str) -> PartiallyEvaluated {
+    let (mut fir_store, package_id) = lower_source(source);
+    let compute_properties = Analyzer::init(&fir_store).analyze_all();
+    let folded_exprs = partially_evaluate(package_id, &mut fir_store, &compute_properties);
+    let package = fir_store.get(package_id);
+    let mut folded = String::new();
+    for expr_id in folded_exprs {
+        let expr = package.get_expr(expr_id);
+        writeln!(folded, "{}: {}", expr.ty, expr.kind).expect("writing to string should succeed");
+    }
+
+    PartiallyEvaluated {
+        fir_store,
+        package_id,
+        folded,
+    }
+}
+
+fn check(source: &str, expect: &Expect) {
+    let partially_evaluated = partially_evaluate_source(source);
+    expect.assert_eq(&partially_evaluated.folded);
+}
+
+fn check_result(source: &str, expect: &Expect) {
+    let partially_evaluated = partially_evaluate_source(source);
+    let package = partially_evaluated
+        .fir_store
+        .get(partially_evaluated.package_id);
+    let mut stdout = Vec::new();
+    let mut out = GenericReceiver::new(&mut stdout);
+    let value = qsc_eval::eval(
+        partially_evaluated.package_id,
+        None,
+        qsc_eval::Limits::default(),
+        package.entry_exec_graph.clone(),
+        &partially_evaluated.fir_store,
+        &mut Env::default(),
+        &mut SparseSim::new(),
+        &mut out,
+    )
+    .expect("evaluation should succeed");
+    expect.assert_eq(&format!(
+        "{value}\n{}",
+        String::from_utf8(stdout).expect("output should be valid utf-8")
+    ));
+}
+
+#[test]
+fn classical_arithmetic_is_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                let x = 2 + 3 * 4;
+                x
+            }
+        }
+        "#},
+        &expect![[r#"
+            Int: Lit: Int(14)
+        "#]],
+    );
+}
+
+#[test]
+fn classical_function_calls_are_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Square(n : Int) : Int {
+                n * n
+            }
+            @EntryPoint()
+            operation Main() : Double {
+                use q = Qubit();
+                Rx(Microsoft.Quantum.Convert.IntAsDouble(Square(3)), q);
+                let angle = Microsoft.Quantum.Math.PI() / 2.0;
+                angle
+            }
+        }
+        "#},
+        &expect![[r#"
+            Double: Lit: Double(9)
+            Double: Lit: Double(1.5707963267948966)
+        "#]],
+    );
+}
+
+#[test]
+fn dynamic_expressions_are_not_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Bool {
+                use q = Qubit();
+                let r = M(q);
+                r == One
+            }
+        }
+        "#},
+        &expect![""],
+    );
+}
+
+#[test]
+fn calls_with_literal_arguments_are_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Add(a : Int, b : Int) : Int {
+                a + b
+            }
+            @EntryPoint()
+            operation Main() : Int {
+                Add(1, 2)
+            }
+        }
+        "#},
+        &expect![[r#"
+            Int: Lit: Int(3)
+        "#]],
+    );
+}
+
+#[test]
+fn blocks_with_inner_bindings_are_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                let sum = {
+                    mutable acc = 0;
+                    for i in 1..4 {
+                        set acc += i;
+                    }
+                    acc
+                };
+                sum
+            }
+        }
+        "#},
+        &expect![[r#"
+            Int: Lit: Int(10)
+        "#]],
+    );
+}
+
+#[test]
+fn expressions_with_output_are_not_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Log(n : Int) : Int {
+                Message($"{n}");
+                n
+            }
+            @EntryPoint()
+            operation Main() : Int {
+                Log(1)
+            }
+        }
+        "#},
+        &expect![""],
+    );
+}
+
+#[test]
+fn random_numbers_are_not_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                Microsoft.Quantum.Random.DrawRandomInt(0, 10)
+            }
+        }
+        "#},
+        &expect![""],
+    );
+}
+
+#[test]
+fn failing_expressions_are_not_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Int {
+                let x = 1 / 0;
+                x
+            }
+        }
+        "#},
+        &expect![""],
+    );
+}
+
+#[test]
+fn non_terminating_expressions_are_not_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Forever() : Int {
+                mutable i = 0;
+                while true {
+                    set i += 1;
+                }
+                i
+            }
+            @EntryPoint()
+            operation Main() : Int {
+                Forever()
+            }
+        }
+        "#},
+        &expect![""],
+    );
+}
+
+#[test]
+fn program_result_is_preserved() {
+    check_result(
+        indoc! {r#"
+        namespace Test {
+            function Fib(n : Int) : Int {
+                n < 2 ? n | Fib(n - 1) + Fib(n - 2)
+            }
+            @EntryPoint()
+            operation Main() : (Int, Result) {
+                Message($"fib: {Fib(10)}");
+                use q = Qubit();
+                if Fib(5) > 4 {
+                    X(q);
+                }
+                (Fib(10) + 1, MResetZ(q))
+            }
+        }
+        "#},
+        &expect![[r#"
+            (56, One)
+            fib: 55
+        "#]],
+    );
+}
+
+#[test]
+fn only_given_callables_are_folded() {
+    let (mut fir_store, package_id) = lower_source(indoc! {r#"
+        namespace Test {
+            function Folded() : Int {
+                let x = 2 + 3;
+                x
+            }
+            function Unfolded() : Int {
+                let x = 4 + 5;
+                x
+            }
+            @EntryPoint()
+            operation Main() : Int {
+                Folded() + Unfolded()
+            }
+        }
+        "#});
+    let folded_callable = fir_store
+        .get(package_id)
+        .items
+        .iter()
+        .find_map(|(item_id, item)| match &item.kind {
+            ItemKind::Callable(decl) if &*decl.name.name == "Folded" => Some(item_id),
+            _ => None,
+        })
+        .expect("callable should exist");
+
+    let compute_properties = Analyzer::init(&fir_store).analyze_all();
+    let folded_exprs = partially_evaluate_callables(
+        package_id,
+        |item_id| item_id == folded_callable,
+        &mut fir_store,
+        &compute_properties,
+    );
+    let package = fir_store.get(package_id);
+    let folded: Vec<_> = folded_exprs
+        .into_iter()
+        .map(|expr_id| package.get_expr(expr_id).kind.to_string())
+        .collect();
+    assert_eq!(folded, ["Lit: Int(5)"]);
+}
@@ -0,0 +1,198 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_hir::{
+    hir::{
+        BinOp, Expr, ExprKind, Lit, Mutability, NodeId, PatKind, Res, Stmt, StmtKind,
+        StringComponent, UnOp,
+    },
+    mut_visit::{self, MutVisitor},
+};
+use rustc_hash::FxHashMap;
+use std::rc::Rc;
+
+/// Folds operators whose operands are literals into a single literal, and replaces references to
+/// immutable bindings of literals with the literal itself. Folding follows the evaluator's
+/// semantics, so expressions that would fail at runtime, such as a division by zero, are kept.
+#[derive(Default)]
+pub(super) struct ConstFold {
+    constants: FxHashMap<NodeId, Constant>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Constant {
+    Lit(Lit),
+    String(Rc<str>),
+}
+
+impl Constant {
+    fn of(expr: &Expr) -> Option<Self> {
+        match &expr.kind {
+            ExprKind::Lit(lit) => Some(Self::Lit(lit.clone())),
+            ExprKind::String(components) => {
+                let mut string = String::new();
+                for component in components {
+                    match component {
+                        StringComponent::Lit(lit) => string.push_str(lit),
+                        StringComponent::Expr(_) => return None,
+                    }
+                }
+                Some(Self::String(string.into()))
+            }
+            _ => None,
+        }
+    }
+
+    fn into_expr_kind(self) -> ExprKind {
+        match self {
+            Self::Lit(lit) => ExprKind::Lit(lit),
+            Self::String(string) => ExprKind::String(vec![StringComponent::Lit(string)]),
+        }
+    }
+}
+
+impl MutVisitor for ConstFold {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        mut_visit::walk_stmt(self, stmt);
+        if let StmtKind::Local(Mutability::Immutable, pat, expr) = &stmt.kind {
+            if let (PatKind::Bind(name), Some(constant)) = (&pat.kind, Constant::of(expr)) {
+                self.constants.insert(name.id, constant);
+            }
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        mut_visit::walk_expr(self, expr);
+        let folded = match &mut expr.kind {
+            ExprKind::Var(Res::Local(id), _) => self.constants.get(id).cloned(),
+            ExprKind::BinOp(op, lhs, rhs) => Constant::of(lhs)
+                .zip(Constant::of(rhs))
+                .and_then(|(lhs, rhs)| fold_bin_op(*op, lhs, rhs)),
+            ExprKind::UnOp(op, operand) => {
+                Constant::of(operand).and_then(|operand| fold_un_op(*op, operand))
+            }
+            ExprKind::String(components) => {
+                inline_string_constants(components);
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(constant) = folded {
+            expr.kind = constant.into_expr_kind();
+        }
+    }
+}
+
+/// Replaces interpolated string constants with their contents and merges adjacent literal parts.
+fn inline_string_constants(components: &mut Vec<StringComponent>) {
+    let mut inlined: Vec<StringComponent> = Vec::with_capacity(components.len());
+    for component in components.drain(..) {
+        let component = match component {
+            StringComponent::Expr(expr) => match Constant::of(&expr) {
+                Some(Constant::String(string)) => StringComponent::Lit(string),
+                _ => StringComponent::Expr(expr),
+            },
+            StringComponent::Lit(lit) => StringComponent::Lit(lit),
+        };
+        match (inlined.last_mut(), component) {
+            (Some(StringComponent::Lit(last)), StringComponent::Lit(lit)) => {
+                *last = format!("{last}{lit}").into();
+            }
+            (_, component) => inlined.push(component),
+        }
+    }
+    *components = inlined;
+}
+
+fn fold_bin_op(op: BinOp, lhs: Constant, rhs: Constant) -> Option<Constant> {
+    match (lhs, rhs) {
+        (Constant::Lit(Lit::Int(lhs)), Constant::Lit(Lit::Int(rhs))) => {
+            fold_int_bin_op(op, lhs, rhs)
+        }
+        (Constant::Lit(Lit::Double(lhs)), Constant::Lit(Lit::Double(rhs))) => {
+            fold_double_bin_op(op, lhs, rhs)
+        }
+        (Constant::Lit(Lit::Bool(lhs)), Constant::Lit(Lit::Bool(rhs))) => {
+            let value = match op {
+                BinOp::AndL => lhs && rhs,
+                BinOp::OrL => lhs || rhs,
+                BinOp::Eq => lhs == rhs,
+                BinOp::Neq => lhs != rhs,
+                _ => return None,
+            };
+            Some(Constant::Lit(Lit::Bool(value)))
+        }
+        (Constant::String(lhs), Constant::String(rhs)) => match op {
+            BinOp::Add => Some(Constant::String(format!("{lhs}{rhs}").into())),
+            BinOp::Eq => Some(Constant::Lit(Lit::Bool(lhs == rhs))),
+            BinOp::Neq => Some(Constant::Lit(Lit::Bool(lhs != rhs))),
+            _ => None,
+        },
+        (lhs @ Constant::Lit(Lit::Pauli(_) | Lit::Result(_)), rhs) => match op {
+            BinOp::Eq => Some(Constant::Lit(Lit::Bool(lhs == rhs))),
+            BinOp::Neq => Some(Constant::Lit(Lit::Bool(lhs != rhs))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn fold_int_bin_op(op: BinOp, lhs: i64, rhs: i64) -> Option<Constant> {
+    let value = match op {
+        BinOp::Add => lhs.wrapping_add(rhs),
+        BinOp::Sub => lhs.wrapping_sub(rhs),
+        BinOp::Mul => lhs.wrapping_mul(rhs),
+        BinOp::Div if rhs != 0 => lhs.wrapping_div(rhs),
+        BinOp::Mod if rhs != 0 => lhs.wrapping_rem(rhs),
+        BinOp::Exp => lhs.checked_pow(u32::try_from(rhs).ok()?)?,
+        BinOp::AndB => lhs & rhs,
+        BinOp::OrB => lhs | rhs,
+        BinOp::XorB => lhs ^ rhs,
+        BinOp::Shl => lhs.checked_shl(u32::try_from(rhs).ok()?)?,
+        BinOp::Shr => lhs.checked_shr(u32::try_from(rhs).ok()?)?,
+        BinOp::Eq => return Some(Constant::Lit(Lit::Bool(lhs == rhs))),
+        BinOp::Neq => return Some(Constant::Lit(Lit::Bool(lhs != rhs))),
+        BinOp::Gt => return Some(Constant::Lit(Lit::Bool(lhs > rhs))),
+        BinOp::Gte => return Some(Constant::Lit(Lit::Bool(lhs >= rhs))),
+        BinOp::Lt => return Some(Constant::Lit(Lit::Bool(lhs < rhs))),
+        BinOp::Lte => return Some(Constant::Lit(Lit::Bool(lhs <= rhs))),
+        _ => return None,
+    };
+    Some(Constant::Lit(Lit::Int(value)))
+}
+
+#[allow(clippy::float_cmp)]
+fn fold_double_bin_op(op: BinOp, lhs: f64, rhs: f64) -> Option<Constant> {
+    let value = match op {
+        BinOp::Add => lhs + rhs,
+        BinOp::Sub => lhs - rhs,
+        BinOp::Mul => lhs * rhs,
+        BinOp::Div => lhs / rhs,
+        BinOp::Mod if rhs != 0.0 => lhs % rhs,
+        BinOp::Exp => lhs.powf(rhs),
+        BinOp::Eq => return Some(Constant::Lit(Lit::Bool(lhs == rhs))),
+        BinOp::Neq => return Some(Constant::Lit(Lit::Bool(lhs != rhs))),
+        BinOp::Gt => return Some(Constant::Lit(Lit::Bool(lhs > rhs))),
+        BinOp::Gte => return Some(Constant::Lit(Lit::Bool(lhs >= rhs))),
+        BinOp::Lt => return Some(Constant::Lit(Lit::Bool(lhs < rhs))),
+        BinOp::Lte => return Some(Constant::Lit(Lit::Bool(lhs <= rhs))),
+        _ => return None,
+    };
+    Some(Constant::Lit(Lit::Double(value)))
+}
+
+fn fold_un_op(op: UnOp, operand: Constant) -> Option<Constant> {
+    let lit = match (op, operand) {
+        (UnOp::Neg, Constant::Lit(Lit::Int(value))) => Lit::Int(value.wrapping_neg()),
+        (UnOp::Neg, Constant::Lit(Lit::Double(value))) => Lit::Double(-value),
+        (UnOp::NotB, Constant::Lit(Lit::Int(value))) => Lit::Int(!value),
+        (UnOp::NotL, Constant::Lit(Lit::Bool(value))) => Lit::Bool(!value),
+        (UnOp::Pos, Constant::Lit(lit @ (Lit::Int(_) | Lit::Double(_)))) => lit,
+        _ => return None,
+    };
+    Some(Constant::Lit(lit))
+}
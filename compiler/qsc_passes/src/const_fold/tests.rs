@@ -0,0 +1,199 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use crate::const_fold::ConstFold;
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::{hir::ItemKind, mut_visit::MutVisitor, validate::Validator, visit::Visitor};
+
+fn check(file: &str, expect: &Expect) {
+    let sources = SourceMap::new([("test".into(), file.into())], None);
+    let mut unit = compile(
+        &PackageStore::new(compile::core()),
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+    ConstFold::default().visit_package(&mut unit.package);
+    Validator::default().visit_package(&unit.package);
+
+    let main = unit
+        .package
+        .items
+        .values()
+        .find_map(|item| match &item.kind {
+            ItemKind::Callable(decl) if decl.name.name.as_ref() == "Main" => Some(decl),
+            _ => None,
+        })
+        .expect("package should have a callable named `Main`");
+    expect.assert_eq(&main.body.to_string());
+}
+
+#[test]
+fn int_arithmetic_is_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Main() : Int {
+                (2 + 3) * 4 - 2 ^ 3 / 2 % 3
+            }
+        }
+        "#},
+        &expect![[r#"
+            SpecDecl 3 [21-86]: Impl:
+                Block 4 [43-86] [Type Int]:
+                    Stmt 5 [53-80]: Expr: Expr 6 [53-80] [Type Int]: Lit: Int(19)"#]],
+    );
+}
+
+#[test]
+fn double_arithmetic_and_comparisons_are_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Main() : Bool {
+                -1.5 * 2.0 < 0.0 and 3 >= 3 and not (PauliX == PauliZ)
+            }
+        }
+        "#},
+        &expect![[r#"
+            SpecDecl 3 [21-114]: Impl:
+                Block 4 [44-114] [Type Bool]:
+                    Stmt 5 [54-108]: Expr: Expr 6 [54-108] [Type Bool]: Lit: Bool(true)"#]],
+    );
+}
+
+#[test]
+fn string_concatenation_is_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Main() : String {
+                let name = "world";
+                $"hello, {name}" + "!"
+            }
+        }
+        "#},
+        &expect![[r#"
+            SpecDecl 3 [21-112]: Impl:
+                Block 4 [46-112] [Type String]:
+                    Stmt 5 [56-75]: Local (Immutable):
+                        Pat 6 [60-64] [Type String]: Bind: Ident 7 [60-64] "name"
+                        Expr 8 [67-74] [Type String]: String:
+                            Lit: "world"
+                    Stmt 9 [84-106]: Expr: Expr 10 [84-106] [Type String]: String:
+                        Lit: "hello, world!""#]],
+    );
+}
+
+#[test]
+fn immutable_bindings_are_propagated() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Main() : Int[] {
+                let n = 4;
+                let size = n * 2;
+                mutable arr = [0, size = size];
+                set arr w/= n - 1 <- 1;
+                arr
+            }
+        }
+        "#},
+        &expect![[r#"
+            SpecDecl 3 [21-181]: Impl:
+                Block 4 [45-181] [Type Int[]]:
+                    Stmt 5 [55-65]: Local (Immutable):
+                        Pat 6 [59-60] [Type Int]: Bind: Ident 7 [59-60] "n"
+                        Expr 8 [63-64] [Type Int]: Lit: Int(4)
+                    Stmt 9 [74-91]: Local (Immutable):
+                        Pat 10 [78-82] [Type Int]: Bind: Ident 11 [78-82] "size"
+                        Expr 12 [85-90] [Type Int]: Lit: Int(8)
+                    Stmt 15 [100-131]: Local (Mutable):
+                        Pat 16 [108-111] [Type Int[]]: Bind: Ident 17 [108-111] "arr"
+                        Expr 18 [114-130] [Type Int[]]: ArrayRepeat:
+                            Expr 19 [115-116] [Type Int]: Lit: Int(0)
+                            Expr 20 [125-129] [Type Int]: Lit: Int(8)
+                    Stmt 21 [140-163]: Semi: Expr 22 [140-162] [Type Unit]: AssignIndex:
+                        Expr 23 [144-147] [Type Int[]]: Var: Local 17
+                        Expr 24 [152-157] [Type Int]: Lit: Int(3)
+                        Expr 27 [161-162] [Type Int]: Lit: Int(1)
+                    Stmt 28 [172-175]: Expr: Expr 29 [172-175] [Type Int[]]: Var: Local 17"#]],
+    );
+}
+
+#[test]
+fn mutable_bindings_are_not_propagated() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Main() : Int {
+                mutable x = 1;
+                set x += 1;
+                x + 1
+            }
+        }
+        "#},
+        &expect![[r#"
+            SpecDecl 3 [21-107]: Impl:
+                Block 4 [43-107] [Type Int]:
+                    Stmt 5 [53-67]: Local (Mutable):
+                        Pat 6 [61-62] [Type Int]: Bind: Ident 7 [61-62] "x"
+                        Expr 8 [65-66] [Type Int]: Lit: Int(1)
+                    Stmt 9 [76-87]: Semi: Expr 10 [76-86] [Type Unit]: AssignOp (Add):
+                        Expr 11 [80-81] [Type Int]: Var: Local 7
+                        Expr 12 [85-86] [Type Int]: Lit: Int(1)
+                    Stmt 13 [96-101]: Expr: Expr 14 [96-101] [Type Int]: BinOp (Add):
+                        Expr 15 [96-97] [Type Int]: Var: Local 7
+                        Expr 16 [100-101] [Type Int]: Lit: Int(1)"#]],
+    );
+}
+
+#[test]
+fn expressions_that_fail_at_runtime_are_not_folded() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Main() : (Int, Int, Int) {
+                (1 / 0, 2 ^ -1, 1 <<< -1)
+            }
+        }
+        "#},
+        &expect![[r#"
+            SpecDecl 3 [21-96]: Impl:
+                Block 4 [55-96] [Type (Int, Int, Int)]:
+                    Stmt 5 [65-90]: Expr: Expr 6 [65-90] [Type (Int, Int, Int)]: Tuple:
+                        Expr 7 [66-71] [Type Int]: BinOp (Div):
+                            Expr 8 [66-67] [Type Int]: Lit: Int(1)
+                            Expr 9 [70-71] [Type Int]: Lit: Int(0)
+                        Expr 10 [73-79] [Type Int]: BinOp (Exp):
+                            Expr 11 [73-74] [Type Int]: Lit: Int(2)
+                            Expr 12 [77-79] [Type Int]: Lit: Int(-1)
+                        Expr 14 [81-89] [Type Int]: BinOp (Shl):
+                            Expr 15 [81-82] [Type Int]: Lit: Int(1)
+                            Expr 16 [87-89] [Type Int]: Lit: Int(-1)"#]],
+    );
+}
+
+#[test]
+fn int_overflow_wraps_like_the_evaluator() {
+    check(
+        indoc! {r#"
+        namespace Test {
+            function Main() : Int {
+                9223372036854775807 + 1
+            }
+        }
+        "#},
+        &expect![[r#"
+            SpecDecl 3 [21-82]: Impl:
+                Block 4 [43-82] [Type Int]:
+                    Stmt 5 [53-76]: Expr: Expr 6 [53-76] [Type Int]: Lit: Int(-9223372036854775808)"#]],
+    );
+}
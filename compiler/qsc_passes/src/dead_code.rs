@@ -0,0 +1,135 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use qsc_hir::{
+    hir::{
+        Attr, Expr, ExprKind, Item, ItemId, ItemKind, LocalItemId, Package, Res, Stmt, StmtKind,
+        Visibility,
+    },
+    visit::{self, Visitor},
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::PackageType;
+
+/// Removes the callables in the package that cannot be reached from its roots. The roots are the
+/// entry expression for executables or the public callables for libraries, along with any
+/// top-level statements and tests. Namespaces and types are always kept.
+pub(super) fn eliminate_dead_code(package: &mut Package, package_type: PackageType) {
+    let call_graph = CallGraph::build(package);
+    let reachable = call_graph.reachable_from(roots(package, package_type));
+    let dead = package
+        .items
+        .iter()
+        .filter_map(|(id, item)| {
+            (matches!(item.kind, ItemKind::Callable(_)) && !reachable.contains(&id)).then_some(id)
+        })
+        .collect::<FxHashSet<_>>();
+
+    for id in &dead {
+        package.items.remove(*id);
+    }
+
+    for item in package.items.values_mut() {
+        if let ItemKind::Namespace(_, items) = &mut item.kind {
+            items.retain(|id| !dead.contains(id));
+        }
+    }
+}
+
+fn roots(package: &Package, package_type: PackageType) -> Vec<LocalItemId> {
+    let mut roots = References::default();
+    match package_type {
+        PackageType::Exe => package.entry.iter().for_each(|e| roots.visit_expr(e)),
+        PackageType::Lib => roots.items.extend(
+            package
+                .items
+                .iter()
+                .filter_map(|(id, item)| is_exported(package, item).then_some(id)),
+        ),
+    }
+    roots.items.extend(
+        package
+            .items
+            .iter()
+            .filter_map(|(id, item)| item.attrs.contains(&Attr::Test).then_some(id)),
+    );
+    package.stmts.iter().for_each(|s| roots.visit_stmt(s));
+    roots.items
+}
+
+/// Whether the item is visible to packages that depend on this one.
+fn is_exported(package: &Package, item: &Item) -> bool {
+    item.visibility == Visibility::Public
+        && !item.parent.is_some_and(|parent| {
+            matches!(
+                package.items.get(parent).map(|parent| &parent.kind),
+                Some(ItemKind::Callable(_))
+            )
+        })
+}
+
+/// The local items referenced by each callable in a package.
+#[derive(Default)]
+struct CallGraph {
+    edges: FxHashMap<LocalItemId, Vec<LocalItemId>>,
+}
+
+impl CallGraph {
+    fn build(package: &Package) -> Self {
+        let mut graph = Self::default();
+        for (id, item) in package.items.iter() {
+            if let ItemKind::Callable(decl) = &item.kind {
+                let mut references = References::default();
+                references.visit_callable_decl(decl);
+                graph.edges.insert(id, references.items);
+            }
+        }
+        graph
+    }
+
+    fn reachable_from(&self, roots: Vec<LocalItemId>) -> FxHashSet<LocalItemId> {
+        let mut reachable = FxHashSet::default();
+        let mut stack = roots;
+        while let Some(id) = stack.pop() {
+            if reachable.insert(id) {
+                if let Some(callees) = self.edges.get(&id) {
+                    stack.extend(callees.iter().copied());
+                }
+            }
+        }
+        reachable
+    }
+}
+
+#[derive(Default)]
+struct References {
+    items: Vec<LocalItemId>,
+}
+
+impl Visitor<'_> for References {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if let StmtKind::Item(item) = &stmt.kind {
+            self.items.push(*item);
+        }
+        visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Var(
+                Res::Item(ItemId {
+                    package: None,
+                    item,
+                }),
+                _,
+            )
+            | ExprKind::Closure(_, item) => self.items.push(*item),
+            _ => {}
+        }
+        visit::walk_expr(self, expr);
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use crate::{dead_code::eliminate_dead_code, entry_point::generate_entry_expr, PackageType};
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+use qsc_hir::hir::ItemKind;
+
+fn check(file: &str, package_type: PackageType, expect: &Expect) {
+    let sources = SourceMap::new([("test".into(), file.into())], None);
+    let mut unit = compile(
+        &PackageStore::new(compile::core()),
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+
+    if package_type == PackageType::Exe {
+        let errors = generate_entry_expr(&mut unit.package, &mut unit.assigner);
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    eliminate_dead_code(&mut unit.package, package_type);
+    let items = unit
+        .package
+        .items
+        .values()
+        .map(|item| match &item.kind {
+            ItemKind::Callable(decl) => format!("callable {}\n", decl.name.name),
+            ItemKind::Namespace(name, items) => {
+                format!("namespace {} ({} items)\n", name.name, items.len())
+            }
+            ItemKind::Ty(name, _) => format!("type {}\n", name.name),
+        })
+        .collect::<String>();
+    expect.assert_eq(&items);
+}
+
+#[test]
+fn exe_keeps_callables_reachable_from_entry_point() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Used() : Int { Helper() }
+                function Helper() : Int { 1 }
+                function Unused() : Int { Helper() }
+                @EntryPoint()
+                operation Main() : Int { Used() }
+            }
+        "},
+        PackageType::Exe,
+        &expect![[r#"
+            namespace Test (3 items)
+            callable Used
+            callable Helper
+            callable Main
+        "#]],
+    );
+}
+
+#[test]
+fn exe_removes_unreachable_recursive_callables() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Even(n : Int) : Bool { n == 0 ? true | Odd(n - 1) }
+                function Odd(n : Int) : Bool { n == 0 ? false | Even(n - 1) }
+                @EntryPoint()
+                operation Main() : Unit {}
+            }
+        "},
+        PackageType::Exe,
+        &expect![[r#"
+            namespace Test (1 items)
+            callable Main
+        "#]],
+    );
+}
+
+#[test]
+fn exe_keeps_callables_used_as_values() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Apply(f : Int -> Int, x : Int) : Int { f(x) }
+                function Double(x : Int) : Int { 2 * x }
+                @EntryPoint()
+                operation Main() : Int { Apply(Double, 1) + Apply(x -> x + 1, 2) }
+            }
+        "},
+        PackageType::Exe,
+        &expect![[r#"
+            namespace Test (3 items)
+            callable Apply
+            callable Double
+            callable Main
+            callable lambda
+        "#]],
+    );
+}
+
+#[test]
+fn exe_removes_nested_callables_of_unreachable_callables() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Unused() : Int {
+                    function Inner() : Int { 1 }
+                    Inner()
+                }
+                @EntryPoint()
+                operation Main() : Unit {}
+            }
+        "},
+        PackageType::Exe,
+        &expect![[r#"
+            namespace Test (1 items)
+            callable Main
+        "#]],
+    );
+}
+
+#[test]
+fn exe_keeps_types() {
+    check(
+        indoc! {"
+            namespace Test {
+                newtype Pair = (Int, Int);
+                function Unused() : Pair { Pair(1, 2) }
+                @EntryPoint()
+                operation Main() : Unit {}
+            }
+        "},
+        PackageType::Exe,
+        &expect![[r#"
+            namespace Test (2 items)
+            type Pair
+            callable Main
+        "#]],
+    );
+}
+
+#[test]
+fn lib_keeps_callables_reachable_from_public_callables() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Public() : Int { Used() }
+                internal function Used() : Int { 1 }
+                internal function Unused() : Int { 2 }
+            }
+        "},
+        PackageType::Lib,
+        &expect![[r#"
+            namespace Test (2 items)
+            callable Public
+            callable Used
+        "#]],
+    );
+}
+
+#[test]
+fn exe_keeps_tests_and_their_callees() {
+    check(
+        indoc! {"
+            namespace Test {
+                function Helper() : Int { 1 }
+                function Unused() : Int { 2 }
+                @Test()
+                function HelperReturnsOne() : Unit {
+                    if Helper() != 1 { fail \"expected one\"; }
+                }
+                @EntryPoint()
+                operation Main() : Unit {}
+            }
+        "},
+        PackageType::Exe,
+        &expect![[r#"
+            namespace Test (3 items)
+            callable Helper
+            callable HelperReturnsOne
+            callable Main
+        "#]],
+    );
+}
@@ -6,6 +6,8 @@ mod borrowck;
 mod callable_limits;
 mod common;
 mod conjugate_invert;
+mod const_fold;
+mod dead_code;
 mod entry_point;
 mod id_update;
 mod invert_block;
@@ -13,8 +15,10 @@ mod logic_sep;
 mod loop_unification;
 mod replace_qubit_allocation;
 mod spec_gen;
+mod test_attr;
 
 use callable_limits::CallableLimits;
+use const_fold::ConstFold;
 use entry_point::generate_entry_expr;
 use loop_unification::LoopUni;
 use miette::Diagnostic;
@@ -28,7 +32,9 @@ use qsc_hir::{
     visit::Visitor,
 };
 use replace_qubit_allocation::ReplaceQubitAllocation;
+pub use spec_gen::is_generated_ctl;
 use thiserror::Error;
+use tracing::info_span;
 
 #[derive(Clone, Debug, Diagnostic, Error)]
 #[diagnostic(transparent)]
@@ -40,6 +46,7 @@ pub enum Error {
     ConjInvert(conjugate_invert::Error),
     EntryPoint(entry_point::Error),
     SpecGen(spec_gen::Error),
+    Test(test_attr::Error),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -51,17 +58,73 @@ pub enum PackageType {
 pub struct PassContext {
     capabilities: RuntimeCapabilityFlags,
     borrow_check: borrowck::Checker,
+    config: PassConfig,
+}
+
+/// The optional passes that run on top of the passes required for evaluation. All of them are
+/// disabled by default, which leaves the package as close to the source as evaluation allows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PassConfig {
+    /// Whether operators on literals are folded and immutable bindings to literals are
+    /// propagated. The folded package no longer refers to those bindings, which tools working on
+    /// the source, like the language service, depend on.
+    pub constant_folding: bool,
+    /// Whether callables that are unreachable from the entry expression are removed from
+    /// executables.
+    pub dead_code_elimination_exe: bool,
+    /// Whether callables that are unreachable from the public items are removed from libraries.
+    /// Interactive sessions may call any callable after the package is compiled.
+    pub dead_code_elimination_lib: bool,
+}
+
+impl PassConfig {
+    fn dead_code_elimination(self, package_type: PackageType) -> bool {
+        match package_type {
+            PackageType::Exe => self.dead_code_elimination_exe,
+            PackageType::Lib => self.dead_code_elimination_lib,
+        }
+    }
 }
 
 impl PassContext {
     #[must_use]
     pub fn new(capabilities: RuntimeCapabilityFlags) -> Self {
+        Self::with_config(capabilities, PassConfig::default())
+    }
+
+    /// Creates a pass context that runs the optional passes enabled in the given configuration.
+    #[must_use]
+    pub fn with_config(capabilities: RuntimeCapabilityFlags, config: PassConfig) -> Self {
         Self {
             capabilities,
             borrow_check: borrowck::Checker::default(),
+            config,
+        }
+    }
+
+    /// The optional passes that are run.
+    #[must_use]
+    pub fn config(&self) -> PassConfig {
+        self.config
+    }
+
+    /// Enables or disables removal of callables that are unreachable from the entry expression
+    /// (for executables) or from the public items (for libraries). Disabled by default, since
+    /// interactive sessions may call any callable after the package is compiled.
+    pub fn set_dead_code_elimination(&mut self, package_type: PackageType, enabled: bool) {
+        match package_type {
+            PackageType::Exe => self.config.dead_code_elimination_exe = enabled,
+            PackageType::Lib => self.config.dead_code_elimination_lib = enabled,
         }
     }
 
+    /// Enables or disables folding of operators on literals and propagation of immutable bindings
+    /// to literals. Disabled by default, since the folded package no longer refers to those
+    /// bindings, which tools working on the source, like the language service, depend on.
+    pub fn set_constant_folding(&mut self, enabled: bool) {
+        self.config.constant_folding = enabled;
+    }
+
     /// Run the default set of passes required for evaluation.
     pub fn run_default_passes(
         &mut self,
@@ -70,10 +133,13 @@ impl PassContext {
         core: &Table,
         package_type: PackageType,
     ) -> Vec<Error> {
+        let _span = info_span!("passes").entered();
         let mut call_limits = CallableLimits::default();
         call_limits.visit_package(package);
         let callable_errors = call_limits.errors;
 
+        let test_errors = test_attr::validate_tests(package);
+
         self.borrow_check.visit_package(package);
         let borrow_errors = &mut self.borrow_check.errors;
 
@@ -94,9 +160,19 @@ impl PassContext {
         LoopUni { core, assigner }.visit_package(package);
         Validator::default().visit_package(package);
 
-        ReplaceQubitAllocation::new(core, assigner).visit_package(package);
+        ReplaceQubitAllocation::new(core, assigner, self.capabilities).visit_package(package);
         Validator::default().visit_package(package);
 
+        if self.config.constant_folding {
+            ConstFold::default().visit_package(package);
+            Validator::default().visit_package(package);
+        }
+
+        if self.config.dead_code_elimination(package_type) {
+            dead_code::eliminate_dead_code(package, package_type);
+            Validator::default().visit_package(package);
+        }
+
         let base_prof_errors = if self.capabilities == RuntimeCapabilityFlags::empty() {
             baseprofck::check_base_profile_compliance(package)
         } else {
@@ -110,6 +186,7 @@ impl PassContext {
             .chain(spec_errors.into_iter().map(Error::SpecGen))
             .chain(conjugate_errors.into_iter().map(Error::ConjInvert))
             .chain(entry_point_errors)
+            .chain(test_errors.into_iter().map(Error::Test))
             .chain(base_prof_errors.into_iter().map(Error::BaseProfCk))
             .collect()
     }
@@ -143,7 +220,8 @@ pub fn run_core_passes(core: &mut CompileUnit) -> Vec<Error> {
     .visit_package(&mut core.package);
     Validator::default().visit_package(&core.package);
 
-    ReplaceQubitAllocation::new(&table, &mut core.assigner).visit_package(&mut core.package);
+    ReplaceQubitAllocation::new(&table, &mut core.assigner, RuntimeCapabilityFlags::all())
+        .visit_package(&mut core.package);
     Validator::default().visit_package(&core.package);
 
     let base_prof_errors = baseprofck::check_base_profile_compliance(&core.package);
@@ -5,11 +5,13 @@
 mod tests;
 
 use qsc_data_structures::span::Span;
+use qsc_frontend::compile::RuntimeCapabilityFlags;
 use qsc_hir::{
     assigner::Assigner,
     global::Table,
     hir::{
-        Block, Expr, ExprKind, Mutability, Pat, PatKind, QubitInit, QubitInitKind, Stmt, StmtKind,
+        Block, Expr, ExprKind, Mutability, Pat, PatKind, QubitInit, QubitInitKind, QubitSource,
+        Stmt, StmtKind,
     },
     mut_visit::{walk_expr, walk_stmt, MutVisitor},
     ty::{Prim, Ty},
@@ -22,30 +24,67 @@ use crate::common::{create_gen_core_ref, generated_name, IdentTemplate};
 struct QubitIdent {
     id: IdentTemplate,
     is_array: bool,
+    source: QubitSource,
+}
+
+/// The names of the callables that acquire a qubit and an array of qubits from the given source.
+fn alloc_callables(source: QubitSource) -> (&'static str, &'static str) {
+    match source {
+        QubitSource::Fresh => ("__quantum__rt__qubit_allocate", "AllocateQubitArray"),
+        QubitSource::Dirty => ("__quantum__rt__qubit_borrow", "BorrowQubitArray"),
+    }
+}
+
+/// The names of the callables that give back a qubit and an array of qubits to the given source.
+fn dealloc_callables(source: QubitSource) -> (&'static str, &'static str) {
+    match source {
+        QubitSource::Fresh => ("__quantum__rt__qubit_release", "ReleaseQubitArray"),
+        QubitSource::Dirty => ("__quantum__rt__qubit_return", "ReturnQubitArray"),
+    }
 }
 
 pub(crate) struct ReplaceQubitAllocation<'a> {
     assigner: &'a mut Assigner,
     core: &'a Table,
+    capabilities: RuntimeCapabilityFlags,
     qubits_curr_callable: Vec<Vec<QubitIdent>>,
     qubits_curr_block: Vec<QubitIdent>,
     prefix_qubits: Vec<QubitIdent>,
 }
 
 impl<'a> ReplaceQubitAllocation<'a> {
-    pub(crate) fn new(core: &'a Table, assigner: &'a mut Assigner) -> Self {
+    pub(crate) fn new(
+        core: &'a Table,
+        assigner: &'a mut Assigner,
+        capabilities: RuntimeCapabilityFlags,
+    ) -> Self {
         Self {
             assigner,
             core,
+            capabilities,
             qubits_curr_callable: Vec::new(),
             qubits_curr_block: Vec::new(),
             prefix_qubits: Vec::new(),
         }
     }
 
+    /// The source that qubits are acquired from. Targets without higher-level constructs can't borrow qubits, so
+    /// borrowed qubits are allocated fresh for them instead, since a borrowed qubit may be in any state.
+    fn qubit_source(&self, source: QubitSource) -> QubitSource {
+        if self
+            .capabilities
+            .contains(RuntimeCapabilityFlags::HigherLevelConstructs)
+        {
+            source
+        } else {
+            QubitSource::Fresh
+        }
+    }
+
     fn generate_qubit_alloc_stmts(
         &mut self,
         stmt_span: Span,
+        source: QubitSource,
         pat: Pat,
         mut init: QubitInit,
     ) -> (Vec<QubitIdent>, Vec<Stmt>) {
@@ -73,11 +112,15 @@ impl<'a> ReplaceQubitAllocation<'a> {
                 new_stmts.push(match opt {
                     Some(mut size) => {
                         self.visit_expr(&mut size);
-                        self.create_array_alloc_stmt(&id, size)
+                        self.create_array_alloc_stmt(&id, source, size)
                     }
-                    None => self.create_alloc_stmt(&id),
+                    None => self.create_alloc_stmt(&id, source),
+                });
+                new_ids.push(QubitIdent {
+                    id,
+                    is_array,
+                    source,
                 });
-                new_ids.push(QubitIdent { id, is_array });
             } else {
                 panic!("Shape of identifier pattern doesn't match shape of initializer");
             }
@@ -88,9 +131,9 @@ impl<'a> ReplaceQubitAllocation<'a> {
                 .map(|(id, size)| match size {
                     Some(size) => {
                         self.visit_expr(size);
-                        self.create_array_alloc_stmt(id, size.clone())
+                        self.create_array_alloc_stmt(id, source, size.clone())
                     }
-                    None => self.create_alloc_stmt(id),
+                    None => self.create_alloc_stmt(id, source),
                 })
                 .collect();
             new_ids = ids
@@ -98,6 +141,7 @@ impl<'a> ReplaceQubitAllocation<'a> {
                 .map(|(id, expr)| QubitIdent {
                     id,
                     is_array: expr.is_some(),
+                    source,
                 })
                 .collect();
             new_stmts.push(Stmt {
@@ -113,11 +157,12 @@ impl<'a> ReplaceQubitAllocation<'a> {
     fn process_qubit_stmt(
         &mut self,
         stmt_span: Span,
+        source: QubitSource,
         pat: Pat,
         init: QubitInit,
         block: Option<Block>,
     ) -> Vec<Stmt> {
-        let (new_ids, new_stmts) = self.generate_qubit_alloc_stmts(stmt_span, pat, init);
+        let (new_ids, new_stmts) = self.generate_qubit_alloc_stmts(stmt_span, source, pat, init);
         if let Some(block) = block {
             vec![self.generate_block_stmt(stmt_span, new_ids, block, new_stmts)]
         } else {
@@ -211,9 +256,9 @@ impl<'a> ReplaceQubitAllocation<'a> {
 
     fn get_dealloc_stmt(&mut self, qubit: &QubitIdent) -> Stmt {
         if qubit.is_array {
-            self.create_array_dealloc_stmt(&qubit.id)
+            self.create_array_dealloc_stmt(&qubit.id, qubit.source)
         } else {
-            self.create_dealloc_stmt(&qubit.id)
+            self.create_dealloc_stmt(&qubit.id, qubit.source)
         }
     }
 
@@ -237,11 +282,11 @@ impl<'a> ReplaceQubitAllocation<'a> {
         stmts
     }
 
-    fn create_alloc_stmt(&mut self, ident: &IdentTemplate) -> Stmt {
+    fn create_alloc_stmt(&mut self, ident: &IdentTemplate, source: QubitSource) -> Stmt {
         let mut call_expr = create_gen_core_ref(
             self.core,
             "QIR.Runtime",
-            "__quantum__rt__qubit_allocate",
+            alloc_callables(source).0,
             Vec::new(),
             ident.span,
         );
@@ -249,11 +294,16 @@ impl<'a> ReplaceQubitAllocation<'a> {
         create_general_alloc_stmt(self.assigner, ident, call_expr, None)
     }
 
-    fn create_array_alloc_stmt(&mut self, ident: &IdentTemplate, array_size: Expr) -> Stmt {
+    fn create_array_alloc_stmt(
+        &mut self,
+        ident: &IdentTemplate,
+        source: QubitSource,
+        array_size: Expr,
+    ) -> Stmt {
         let mut call_expr = create_gen_core_ref(
             self.core,
             "QIR.Runtime",
-            "AllocateQubitArray",
+            alloc_callables(source).1,
             Vec::new(),
             ident.span,
         );
@@ -261,11 +311,11 @@ impl<'a> ReplaceQubitAllocation<'a> {
         create_general_alloc_stmt(self.assigner, ident, call_expr, Some(array_size))
     }
 
-    fn create_dealloc_stmt(&mut self, ident: &IdentTemplate) -> Stmt {
+    fn create_dealloc_stmt(&mut self, ident: &IdentTemplate, source: QubitSource) -> Stmt {
         let mut call_expr = create_gen_core_ref(
             self.core,
             "QIR.Runtime",
-            "__quantum__rt__qubit_release",
+            dealloc_callables(source).0,
             Vec::new(),
             ident.span,
         );
@@ -273,11 +323,11 @@ impl<'a> ReplaceQubitAllocation<'a> {
         create_general_dealloc_stmt(self.assigner, call_expr, ident)
     }
 
-    fn create_array_dealloc_stmt(&mut self, ident: &IdentTemplate) -> Stmt {
+    fn create_array_dealloc_stmt(&mut self, ident: &IdentTemplate, source: QubitSource) -> Stmt {
         let mut call_expr = create_gen_core_ref(
             self.core,
             "QIR.Runtime",
-            "ReleaseQubitArray",
+            dealloc_callables(source).1,
             Vec::new(),
             ident.span,
         );
@@ -295,10 +345,14 @@ impl MutVisitor for ReplaceQubitAllocation<'_> {
         // walk block
         let old_stmts = take(&mut block.stmts);
         for mut stmt in old_stmts {
-            if let StmtKind::Qubit(_, pat, init, qubit_scope) = stmt.kind {
-                block
-                    .stmts
-                    .extend(self.process_qubit_stmt(stmt.span, pat, init, qubit_scope));
+            if let StmtKind::Qubit(source, pat, init, qubit_scope) = stmt.kind {
+                block.stmts.extend(self.process_qubit_stmt(
+                    stmt.span,
+                    self.qubit_source(source),
+                    pat,
+                    init,
+                    qubit_scope,
+                ));
             } else {
                 walk_stmt(self, &mut stmt);
                 block.stmts.push(stmt);
@@ -389,12 +443,15 @@ impl MutVisitor for ReplaceQubitAllocation<'_> {
         // top-level statements. Given that, the qubits allocated will always be live for
         // the entirety of a global scope, so only qubit allocations need to be generated.
         match stmt.kind.clone() {
-            StmtKind::Qubit(_, pat, qubit_init, None) => {
-                stmt.kind = create_qubit_global_alloc(self.assigner, self.core, pat, qubit_init);
+            StmtKind::Qubit(source, pat, qubit_init, None) => {
+                let source = self.qubit_source(source);
+                stmt.kind =
+                    create_qubit_global_alloc(self.assigner, self.core, source, pat, qubit_init);
             }
-            StmtKind::Qubit(_, pat, qubit_init, Some(block)) => {
+            StmtKind::Qubit(source, pat, qubit_init, Some(block)) => {
+                let source = self.qubit_source(source);
                 let (new_ids, new_stmts) =
-                    self.generate_qubit_alloc_stmts(stmt.span, pat, qubit_init);
+                    self.generate_qubit_alloc_stmts(stmt.span, source, pat, qubit_init);
                 *stmt = self.generate_block_stmt(stmt.span, new_ids, block, new_stmts);
             }
             kind => {
@@ -408,16 +465,22 @@ impl MutVisitor for ReplaceQubitAllocation<'_> {
 fn create_qubit_global_alloc(
     assigner: &mut Assigner,
     core: &Table,
+    source: QubitSource,
     pat: Pat,
     qubit_init: QubitInit,
 ) -> StmtKind {
-    fn qubit_alloc_expr(assigner: &mut Assigner, core: &Table, qubit_init: QubitInit) -> Expr {
+    fn qubit_alloc_expr(
+        assigner: &mut Assigner,
+        core: &Table,
+        source: QubitSource,
+        qubit_init: QubitInit,
+    ) -> Expr {
         match qubit_init.kind {
             QubitInitKind::Array(mut expr) => {
                 let mut call_expr = create_gen_core_ref(
                     core,
                     "QIR.Runtime",
-                    "AllocateQubitArray",
+                    alloc_callables(source).1,
                     Vec::new(),
                     qubit_init.span,
                 );
@@ -433,7 +496,7 @@ fn create_qubit_global_alloc(
                 let mut call_expr = create_gen_core_ref(
                     core,
                     "QIR.Runtime",
-                    "__quantum__rt__qubit_allocate",
+                    alloc_callables(source).0,
                     Vec::new(),
                     qubit_init.span,
                 );
@@ -446,7 +509,7 @@ fn create_qubit_global_alloc(
                 ty: qubit_init.ty,
                 kind: ExprKind::Tuple(
                     tup.into_iter()
-                        .map(|init| qubit_alloc_expr(assigner, core, init))
+                        .map(|init| qubit_alloc_expr(assigner, core, source, init))
                         .collect(),
                 ),
             },
@@ -457,7 +520,7 @@ fn create_qubit_global_alloc(
     StmtKind::Local(
         Mutability::Immutable,
         pat,
-        qubit_alloc_expr(assigner, core, qubit_init),
+        qubit_alloc_expr(assigner, core, source, qubit_init),
     )
 }
 
@@ -9,6 +9,10 @@ use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags,
 use qsc_hir::{mut_visit::MutVisitor, validate::Validator, visit::Visitor};
 
 fn check(file: &str, expect: &Expect) {
+    check_with_capabilities(file, RuntimeCapabilityFlags::all(), expect);
+}
+
+fn check_with_capabilities(file: &str, capabilities: RuntimeCapabilityFlags, expect: &Expect) {
     let store = PackageStore::new(compile::core());
     let sources = SourceMap::new([("test".into(), file.into())], None);
     let mut unit = compile(
@@ -19,7 +23,8 @@ fn check(file: &str, expect: &Expect) {
         LanguageFeatures::default(),
     );
     assert!(unit.errors.is_empty(), "{:?}", unit.errors);
-    ReplaceQubitAllocation::new(store.core(), &mut unit.assigner).visit_package(&mut unit.package);
+    ReplaceQubitAllocation::new(store.core(), &mut unit.assigner, capabilities)
+        .visit_package(&mut unit.package);
     Validator::default().visit_package(&unit.package);
     expect.assert_eq(&unit.package.to_string());
 }
@@ -828,3 +833,82 @@ fn test_unrelated_unaffected() {
                         ctl-adj: <none>"#]],
     );
 }
+
+#[test]
+fn test_borrowed_qubit() {
+    check(
+        indoc! { "namespace input {
+            operation Foo() : Unit {
+                borrow q = Qubit();
+                let x = 3;
+            }
+        }" },
+        &expect![[r#"
+            Package:
+                Item 0 [0-101] (Public):
+                    Namespace (Ident 13 [10-15] "input"): Item 1
+                Item 1 [22-99] (Public):
+                    Parent: 0
+                    Callable 0 [22-99] (operation):
+                        name: Ident 1 [32-35] "Foo"
+                        input: Pat 2 [35-37] [Type Unit]: Unit
+                        output: Unit
+                        functors: empty set
+                        body: SpecDecl 3 [22-99]: Impl:
+                            Block 4 [45-99] [Type Unit]:
+                                Stmt 17 [55-74]: Local (Immutable):
+                                    Pat 18 [55-74] [Type Qubit]: Bind: Ident 7 [55-74] "q"
+                                    Expr 15 [55-74] [Type Qubit]: Call:
+                                        Expr 14 [55-74] [Type (Unit => Qubit)]: Var: Item 8 (Package 0)
+                                        Expr 16 [55-74] [Type Unit]: Unit
+                                Stmt 9 [83-93]: Local (Immutable):
+                                    Pat 10 [87-88] [Type Int]: Bind: Ident 11 [87-88] "x"
+                                    Expr 12 [91-92] [Type Int]: Lit: Int(3)
+                                Stmt 20 [0-0]: Semi: Expr 21 [0-0] [Type Unit]: Call:
+                                    Expr 19 [55-74] [Type (Qubit => Unit)]: Var: Item 9 (Package 0)
+                                    Expr 22 [55-74] [Type Qubit]: Var: Local 7
+                        adj: <none>
+                        ctl: <none>
+                        ctl-adj: <none>"#]],
+    );
+}
+
+#[test]
+fn test_borrowed_qubit_without_higher_level_constructs_is_allocated() {
+    check_with_capabilities(
+        indoc! { "namespace input {
+            operation Foo() : Unit {
+                borrow q = Qubit();
+                let x = 3;
+            }
+        }" },
+        RuntimeCapabilityFlags::empty(),
+        &expect![[r#"
+            Package:
+                Item 0 [0-101] (Public):
+                    Namespace (Ident 13 [10-15] "input"): Item 1
+                Item 1 [22-99] (Public):
+                    Parent: 0
+                    Callable 0 [22-99] (operation):
+                        name: Ident 1 [32-35] "Foo"
+                        input: Pat 2 [35-37] [Type Unit]: Unit
+                        output: Unit
+                        functors: empty set
+                        body: SpecDecl 3 [22-99]: Impl:
+                            Block 4 [45-99] [Type Unit]:
+                                Stmt 17 [55-74]: Local (Immutable):
+                                    Pat 18 [55-74] [Type Qubit]: Bind: Ident 7 [55-74] "q"
+                                    Expr 15 [55-74] [Type Qubit]: Call:
+                                        Expr 14 [55-74] [Type (Unit => Qubit)]: Var: Item 4 (Package 0)
+                                        Expr 16 [55-74] [Type Unit]: Unit
+                                Stmt 9 [83-93]: Local (Immutable):
+                                    Pat 10 [87-88] [Type Int]: Bind: Ident 11 [87-88] "x"
+                                    Expr 12 [91-92] [Type Int]: Lit: Int(3)
+                                Stmt 20 [0-0]: Semi: Expr 21 [0-0] [Type Unit]: Call:
+                                    Expr 19 [55-74] [Type (Qubit => Unit)]: Var: Item 5 (Package 0)
+                                    Expr 22 [55-74] [Type Qubit]: Var: Local 7
+                        adj: <none>
+                        ctl: <none>
+                        ctl-adj: <none>"#]],
+    );
+}
@@ -16,12 +16,13 @@ use qsc_hir::{
     assigner::Assigner,
     global::Table,
     hir::{
-        Block, CallableDecl, CallableKind, Functor, Ident, NodeId, Package, Pat, PatKind, Res,
-        SpecBody, SpecDecl, SpecGen,
+        Block, CallableDecl, CallableKind, Expr, ExprKind, Functor, Ident, NodeId, Package, Pat,
+        PatKind, QubitInit, Res, SpecBody, SpecDecl, SpecGen, Stmt,
     },
-    mut_visit::MutVisitor,
+    mut_visit::{self, MutVisitor},
     ty::{Prim, Ty},
 };
+use rustc_hash::FxHashMap;
 use std::option::Option;
 use thiserror::Error;
 
@@ -74,6 +75,114 @@ pub(super) fn generate_specs(
     generate_spec_impls(core, package, assigner)
 }
 
+/// Runs the controlled specialization generator in comparison mode: instead of replacing the
+/// controlled specialization of the callable, compares its explicit implementation with the one
+/// the generator would produce from the body. The callable is expected as it is lowered, before
+/// any passes run. The implementations are the same if they only differ in node IDs, spans, types,
+/// and the IDs of the locals they bind.
+#[must_use]
+pub fn is_generated_ctl(decl: &CallableDecl, assigner: &mut Assigner) -> bool {
+    if decl.kind == CallableKind::Function {
+        return false;
+    }
+    let (SpecBody::Impl(_, body), Some(ctl)) = (&decl.body.body, &decl.ctl) else {
+        return false;
+    };
+    let SpecBody::Impl(Some(ctls_pat), ctl_block) = &ctl.body else {
+        return false;
+    };
+    let PatKind::Bind(ctls) = &ctls_pat.kind else {
+        return false;
+    };
+
+    let mut generated = body.clone();
+    let mut distrib = CtlDistrib {
+        ctls: Res::Local(ctls.id),
+        errors: Vec::new(),
+    };
+    distrib.visit_block(&mut generated);
+    if !distrib.errors.is_empty() {
+        return false;
+    }
+
+    // Locals bound in either block are numbered in the order they are bound, starting past the
+    // IDs of the callable, so that they can't be confused with the locals bound outside of them.
+    let first_local = assigner.next_node();
+    let mut explicit = ctl_block.clone();
+    Normalizer::new(first_local).visit_block(&mut generated);
+    Normalizer::new(first_local).visit_block(&mut explicit);
+    generated == explicit
+}
+
+/// Resets the node IDs, spans and types of a block, and renumbers the locals bound in it, so that
+/// blocks can be compared structurally.
+struct Normalizer {
+    next_local: NodeId,
+    locals: FxHashMap<NodeId, NodeId>,
+}
+
+impl Normalizer {
+    fn new(first_local: NodeId) -> Self {
+        Self {
+            next_local: first_local,
+            locals: FxHashMap::default(),
+        }
+    }
+
+    fn local(&self, id: NodeId) -> NodeId {
+        self.locals.get(&id).copied().unwrap_or(id)
+    }
+}
+
+impl MutVisitor for Normalizer {
+    fn visit_block(&mut self, block: &mut Block) {
+        block.id = NodeId::FIRST;
+        block.ty = Ty::Err;
+        mut_visit::walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.id = NodeId::FIRST;
+        mut_visit::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        expr.id = NodeId::FIRST;
+        expr.ty = Ty::Err;
+        match &mut expr.kind {
+            ExprKind::Var(Res::Local(id), _) => *id = self.local(*id),
+            ExprKind::Closure(captures, _) => {
+                for id in captures {
+                    *id = self.local(*id);
+                }
+            }
+            _ => {}
+        }
+        mut_visit::walk_expr(self, expr);
+    }
+
+    fn visit_pat(&mut self, pat: &mut Pat) {
+        pat.id = NodeId::FIRST;
+        pat.ty = Ty::Err;
+        if let PatKind::Bind(name) = &mut pat.kind {
+            self.locals.insert(name.id, self.next_local);
+            name.id = self.next_local;
+            self.next_local = self.next_local.successor();
+        }
+        mut_visit::walk_pat(self, pat);
+    }
+
+    fn visit_qubit_init(&mut self, init: &mut QubitInit) {
+        init.id = NodeId::FIRST;
+        init.ty = Ty::Err;
+        mut_visit::walk_qubit_init(self, init);
+    }
+
+    fn visit_span(&mut self, span: &mut Span) {
+        *span = Span::default();
+    }
+}
+
 fn generate_placeholders(package: &mut Package, assigner: &mut Assigner) {
     SpecPlacePass { assigner }.visit_package(package);
 }
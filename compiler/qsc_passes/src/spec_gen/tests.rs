@@ -8,9 +8,9 @@ use expect_test::{expect, Expect};
 use indoc::indoc;
 use qsc_data_structures::language_features::LanguageFeatures;
 use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
-use qsc_hir::{validate::Validator, visit::Visitor};
+use qsc_hir::{hir::ItemKind, validate::Validator, visit::Visitor};
 
-use crate::spec_gen::generate_specs;
+use crate::spec_gen::{generate_specs, is_generated_ctl};
 
 fn check(file: &str, expect: &Expect) {
     let store = PackageStore::new(compile::core());
@@ -2124,3 +2124,81 @@ fn op_array_unsupported_functors_with_lambdas() {
         "#]],
     );
 }
+
+fn check_is_generated_ctl(file: &str, expected: bool) {
+    let store = PackageStore::new(compile::core());
+    let sources = SourceMap::new([("test".into(), file.into())], None);
+    let mut unit = compile(
+        &store,
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+
+    let decl = unit
+        .package
+        .items
+        .values()
+        .find_map(|item| match &item.kind {
+            ItemKind::Callable(decl) if decl.name.name.as_ref() == "A" => Some(decl),
+            _ => None,
+        })
+        .expect("package should have a callable named `A`");
+    assert_eq!(is_generated_ctl(decl, &mut unit.assigner), expected);
+}
+
+#[test]
+fn is_generated_ctl_with_renamed_locals() {
+    check_is_generated_ctl(
+        indoc! {"
+        namespace test {
+            operation B(q : Qubit) : Unit is Ctl {}
+            operation A(q : Qubit) : Unit is Ctl {
+                body ... {
+                    let x = 1;
+                    within {
+                        B(q);
+                    } apply {
+                        for i in 0..x {
+                            B(q);
+                        }
+                    }
+                }
+                controlled (cs, ...) {
+                    let x = 1;
+                    within {
+                        B(q);
+                    } apply {
+                        for i in 0..x {
+                            Controlled B(cs, q);
+                        }
+                    }
+                }
+            }
+        }
+        "},
+        true,
+    );
+}
+
+#[test]
+fn is_generated_ctl_with_uncontrolled_call() {
+    check_is_generated_ctl(
+        indoc! {"
+        namespace test {
+            operation B(q : Qubit) : Unit is Ctl {}
+            operation A(q : Qubit) : Unit is Ctl {
+                body ... {
+                    B(q);
+                }
+                controlled (cs, ...) {
+                    B(q);
+                }
+            }
+        }
+        "},
+        false,
+    );
+}
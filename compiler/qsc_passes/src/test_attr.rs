@@ -0,0 +1,50 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use miette::Diagnostic;
+use qsc_data_structures::span::Span;
+use qsc_hir::hir::{Attr, ItemKind, Package, PatKind};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Error {
+    #[error("test cannot have parameters")]
+    #[diagnostic(code("Qsc.Test.Args"))]
+    Args(#[label] Span),
+
+    #[error("test cannot have type parameters")]
+    #[diagnostic(code("Qsc.Test.TypeParams"))]
+    TypeParams(#[label] Span),
+
+    #[error("test attribute is only allowed on callables")]
+    #[diagnostic(code("Qsc.Test.NotCallable"))]
+    NotCallable(#[label] Span),
+}
+
+/// Checks that the items with the test attribute are callables that the test runner can call
+/// without any arguments.
+pub(super) fn validate_tests(package: &Package) -> Vec<Error> {
+    let mut errors = Vec::new();
+    for item in package.items.values() {
+        if !item.attrs.contains(&Attr::Test) {
+            continue;
+        }
+        match &item.kind {
+            ItemKind::Callable(decl) => {
+                if !matches!(&decl.input.kind, PatKind::Tuple(params) if params.is_empty()) {
+                    errors.push(Error::Args(decl.input.span));
+                }
+                if !decl.generics.is_empty() {
+                    errors.push(Error::TypeParams(decl.name.span));
+                }
+            }
+            ItemKind::Namespace(..) | ItemKind::Ty(..) => {
+                errors.push(Error::NotCallable(item.span));
+            }
+        }
+    }
+    errors
+}
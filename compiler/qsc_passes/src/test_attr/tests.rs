@@ -0,0 +1,108 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use crate::test_attr::validate_tests;
+use expect_test::{expect, Expect};
+use indoc::indoc;
+use qsc_data_structures::language_features::LanguageFeatures;
+use qsc_frontend::compile::{self, compile, PackageStore, RuntimeCapabilityFlags, SourceMap};
+
+fn check(file: &str, expect: &Expect) {
+    let sources = SourceMap::new([("test".into(), file.into())], None);
+    let unit = compile(
+        &PackageStore::new(compile::core()),
+        &[],
+        sources,
+        RuntimeCapabilityFlags::all(),
+        LanguageFeatures::default(),
+    );
+    assert!(unit.errors.is_empty(), "{:?}", unit.errors);
+
+    let errors = validate_tests(&unit.package);
+    expect.assert_debug_eq(&errors);
+}
+
+#[test]
+fn valid_tests() {
+    check(
+        indoc! {"
+            namespace Test {
+                @Test()
+                operation AllocatesQubit() : Unit {
+                    use q = Qubit();
+                }
+                @Test()
+                function AddsNumbers() : Unit {
+                    if 1 + 1 != 2 { fail \"wrong sum\"; }
+                }
+            }"},
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn test_with_params() {
+    check(
+        indoc! {"
+            namespace Test {
+                @Test()
+                operation WithParams(q : Qubit, n : Int) : Unit {}
+            }"},
+        &expect![[r#"
+            [
+                Args(
+                    Span {
+                        lo: 53,
+                        hi: 73,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn test_with_type_params() {
+    check(
+        indoc! {"
+            namespace Test {
+                @Test()
+                function Generic<'T>() : Unit {}
+            }"},
+        &expect![[r#"
+            [
+                TypeParams(
+                    Span {
+                        lo: 42,
+                        hi: 49,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn test_on_type() {
+    check(
+        indoc! {"
+            namespace Test {
+                @Test()
+                newtype Pair = (Int, Int);
+            }"},
+        &expect![[r#"
+            [
+                NotCallable(
+                    Span {
+                        lo: 21,
+                        hi: 59,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
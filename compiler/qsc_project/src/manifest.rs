@@ -25,10 +25,13 @@ pub struct Manifest {
     pub language_features: Vec<String>,
     #[serde(default)]
     pub lints: Vec<LintConfig>,
+    /// The flags that are set for the `@Config` attributes in the sources of the project.
+    #[serde(default)]
+    pub config: Vec<String>,
 }
 
 /// Describes the contents and location of a Q# manifest file.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ManifestDescriptor {
     pub manifest: Manifest,
     pub manifest_dir: PathBuf,
@@ -0,0 +1,408 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The advisor looks for common program constructs that raise the runtime capabilities a program needs and proposes
+//! concrete rewrites that avoid them. It relies on the results of the runtime capabilities analysis to determine which
+//! expressions are dynamic, so it only makes suggestions for constructs that actually depend on measurement results.
+
+use crate::{ComputePropertiesLookup, PackageStoreComputeProperties};
+use miette::Diagnostic;
+use qsc_data_structures::{index_map::IndexMap, span::Span};
+use qsc_fir::{
+    fir::{
+        BinOp, Block, BlockId, CallableImpl, Expr, ExprId, ExprKind, Global, ItemId, Lit,
+        LocalVarId, Mutability, Package, PackageId, PackageLookup, PackageStore,
+        PackageStoreLookup, Pat, PatId, PatKind, Res, Result, SpecImpl, Stmt, StmtId, StmtKind,
+        StoreItemId,
+    },
+    visit::{self, Visitor},
+};
+use qsc_frontend::compile::SourceMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use thiserror::Error;
+
+/// A suggested rewrite for a construct that raises the runtime capabilities a program needs.
+#[derive(Clone, Debug, Diagnostic, Error)]
+pub enum Advice {
+    #[error("branching on a stored measurement result requires forward branching")]
+    #[diagnostic(severity(Advice))]
+    #[diagnostic(help(
+        "apply the conditional operations as controlled operations before the measurement instead:\n{after}"
+    ))]
+    #[diagnostic(code("Qsc.Advisor.DeferMeasurement"))]
+    DeferMeasurement {
+        before: String,
+        after: String,
+        #[label]
+        span: Span,
+    },
+
+    #[error("a loop with a dynamic condition requires backwards branching")]
+    #[diagnostic(severity(Advice))]
+    #[diagnostic(help(
+        "use a loop with a static maximum number of iterations and check the condition inside of it:\n{after}"
+    ))]
+    #[diagnostic(code("Qsc.Advisor.BoundedLoop"))]
+    BoundedLoop {
+        before: String,
+        after: String,
+        #[label]
+        span: Span,
+    },
+
+    #[error("an array sized by a dynamic value requires higher-level constructs")]
+    #[diagnostic(severity(Advice))]
+    #[diagnostic(help(
+        "allocate the array with a static maximum size and keep track of the number of elements in use separately:\n{after}"
+    ))]
+    #[diagnostic(code("Qsc.Advisor.StaticArraySize"))]
+    StaticArraySize {
+        before: String,
+        after: String,
+        #[label]
+        span: Span,
+    },
+}
+
+impl Advice {
+    /// The source code of the construct the advice applies to.
+    #[must_use]
+    pub fn before(&self) -> &str {
+        match self {
+            Advice::DeferMeasurement { before, .. }
+            | Advice::BoundedLoop { before, .. }
+            | Advice::StaticArraySize { before, .. } => before,
+        }
+    }
+
+    /// The suggested replacement for the construct.
+    #[must_use]
+    pub fn after(&self) -> &str {
+        match self {
+            Advice::DeferMeasurement { after, .. }
+            | Advice::BoundedLoop { after, .. }
+            | Advice::StaticArraySize { after, .. } => after,
+        }
+    }
+
+    /// The span of the construct the advice applies to.
+    #[must_use]
+    pub fn span(&self) -> Span {
+        match self {
+            Advice::DeferMeasurement { span, .. }
+            | Advice::BoundedLoop { span, .. }
+            | Advice::StaticArraySize { span, .. } => *span,
+        }
+    }
+}
+
+/// Proposes rewrites for the constructs reachable from the entry expression of the given package that raise the
+/// runtime capabilities the program needs. Only the packages that have an entry in `sources` are advised on, since the
+/// suggestions are built from the source code of the constructs. The advice for each package is returned separately so
+/// that its spans can be resolved against the corresponding source map.
+#[must_use]
+pub fn advise(
+    package_store: &PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    entry_package_id: PackageId,
+    sources: &IndexMap<PackageId, &SourceMap>,
+) -> Vec<(PackageId, Advice)> {
+    let mut advice = Vec::new();
+    let mut visited = FxHashSet::default();
+    let mut callables = Vec::new();
+
+    if let (Some(entry), Some(entry_sources)) = (
+        package_store.get(entry_package_id).entry,
+        sources.get(entry_package_id),
+    ) {
+        let mut advisor = Advisor::new(
+            package_store,
+            compute_properties,
+            entry_package_id,
+            entry_sources,
+        );
+        advisor.visit_expr(entry);
+        callables.extend(advisor.callees);
+        advice.extend(advisor.advice.into_iter().map(|a| (entry_package_id, a)));
+    }
+
+    while let Some(item_id) = callables.pop() {
+        if !visited.insert((item_id.package, item_id.item)) {
+            continue;
+        }
+        let Some(package_sources) = sources.get(item_id.package) else {
+            continue;
+        };
+        let Some(Global::Callable(decl)) = package_store.get_global(item_id) else {
+            continue;
+        };
+        let mut advisor = Advisor::new(
+            package_store,
+            compute_properties,
+            item_id.package,
+            package_sources,
+        );
+        advisor.visit_callable_decl(decl);
+        callables.extend(advisor.callees);
+        advice.extend(advisor.advice.into_iter().map(|a| (item_id.package, a)));
+    }
+
+    advice
+}
+
+struct Advisor<'a> {
+    package_store: &'a PackageStore,
+    compute_properties: &'a PackageStoreComputeProperties,
+    package_id: PackageId,
+    package: &'a Package,
+    sources: &'a SourceMap,
+    /// The initializers of the immutable bindings visited so far.
+    bindings: FxHashMap<LocalVarId, ExprId>,
+    /// The callables referenced by the visited code, which need to be advised on too.
+    callees: Vec<StoreItemId>,
+    advice: Vec<Advice>,
+}
+
+impl<'a> Advisor<'a> {
+    fn new(
+        package_store: &'a PackageStore,
+        compute_properties: &'a PackageStoreComputeProperties,
+        package_id: PackageId,
+        sources: &'a SourceMap,
+    ) -> Self {
+        Self {
+            package_store,
+            compute_properties,
+            package_id,
+            package: package_store.get(package_id),
+            sources,
+            bindings: FxHashMap::default(),
+            callees: Vec::new(),
+            advice: Vec::new(),
+        }
+    }
+
+    fn is_dynamic(&self, expr_id: ExprId) -> bool {
+        self.compute_properties
+            .find_expr((self.package_id, expr_id).into())
+            .is_some_and(|set| set.inherent.is_dynamic())
+    }
+
+    fn snippet(&self, span: Span) -> Option<&'a str> {
+        let source = self.sources.find_by_offset(span.lo)?;
+        source
+            .contents
+            .get((span.lo - source.offset) as usize..(span.hi - source.offset) as usize)
+    }
+
+    /// The column at which the given span starts, used to reindent multi-line snippets.
+    fn column(&self, span: Span) -> usize {
+        self.sources
+            .find_by_offset(span.lo)
+            .and_then(|source| {
+                let start = (span.lo - source.offset) as usize;
+                source.contents.get(..start).map(|prefix| {
+                    prefix.len() - prefix.rfind('\n').map_or(0, |newline| newline + 1)
+                })
+            })
+            .unwrap_or_default()
+    }
+
+    fn item_id(&self, item: &ItemId) -> StoreItemId {
+        (item.package.unwrap_or(self.package_id), item.item).into()
+    }
+
+    fn callee_name(&self, callee: ExprId) -> Option<&'a str> {
+        let ExprKind::Var(Res::Item(item), _) = &self.get_expr(callee).kind else {
+            return None;
+        };
+        match self.package_store.get_global(self.item_id(item)) {
+            Some(Global::Callable(decl)) => Some(&decl.name.name),
+            _ => None,
+        }
+    }
+
+    /// For `if r == One { X(target); }` where `r` was bound to `M(q)`, suggests applying `CNOT(q, target)` before the
+    /// measurement.
+    fn check_branch_on_result(&mut self, expr: &Expr, cond: ExprId, body: ExprId) {
+        let ExprKind::BinOp(BinOp::Eq, lhs, rhs) = &self.get_expr(cond).kind else {
+            return;
+        };
+        let ((ExprKind::Var(Res::Local(var), _), ExprKind::Lit(Lit::Result(result)))
+        | (ExprKind::Lit(Lit::Result(result)), ExprKind::Var(Res::Local(var), _))) =
+            (&self.get_expr(*lhs).kind, &self.get_expr(*rhs).kind)
+        else {
+            return;
+        };
+        let Some(ExprKind::Call(callee, qubit)) = self
+            .bindings
+            .get(var)
+            .map(|init| &self.get_expr(*init).kind)
+        else {
+            return;
+        };
+        if !matches!(self.callee_name(*callee), Some("M" | "MResetZ")) {
+            return;
+        }
+        let Some(qubit) = self.snippet(self.get_expr(*qubit).span) else {
+            return;
+        };
+        let ExprKind::Block(block) = &self.get_expr(body).kind else {
+            return;
+        };
+
+        let mut controlled = Vec::new();
+        for stmt in &self.get_block(*block).stmts {
+            let (StmtKind::Semi(call) | StmtKind::Expr(call)) = &self.get_stmt(*stmt).kind else {
+                return;
+            };
+            let ExprKind::Call(callee, target) = &self.get_expr(*call).kind else {
+                return;
+            };
+            let gate = match self.callee_name(*callee) {
+                Some("X") => "CNOT",
+                Some("Z") => "CZ",
+                _ => return,
+            };
+            let Some(target) = self.snippet(self.get_expr(*target).span) else {
+                return;
+            };
+            controlled.push(format!("{gate}({qubit}, {target});"));
+        }
+        if controlled.is_empty() {
+            return;
+        }
+
+        let after = match result {
+            Result::One => controlled.join("\n"),
+            Result::Zero => format!(
+                "within {{\n    X({qubit});\n}} apply {{\n    {}\n}}",
+                controlled.join("\n    ")
+            ),
+        };
+        if let Some(before) = self.snippet(expr.span) {
+            self.advice.push(Advice::DeferMeasurement {
+                before: reindent(before, self.column(expr.span), 0),
+                after,
+                span: expr.span,
+            });
+        }
+    }
+
+    /// For `while cond { ... }` where `cond` is dynamic, suggests a `for` loop with a static number of iterations.
+    fn check_while(&mut self, expr: &Expr, cond: ExprId, body: BlockId) {
+        let Some(before) = self.snippet(expr.span) else {
+            return;
+        };
+        // Repeat-until loops are also represented as while loops, but their condition doesn't map back to the source.
+        if !before.starts_with("while") {
+            return;
+        }
+        let (Some(cond), Some(body)) = (
+            self.snippet(self.get_expr(cond).span),
+            self.snippet(self.get_block(body).span),
+        ) else {
+            return;
+        };
+        let column = self.column(expr.span);
+        let body = reindent(body, column, 4);
+        self.advice.push(Advice::BoundedLoop {
+            before: reindent(before, column, 0),
+            after: format!("for _ in 1..MaxIterations {{\n    if {cond} {body}\n}}"),
+            span: expr.span,
+        });
+    }
+
+    /// For `[value, size = n]` where `n` is dynamic, suggests an array with a static maximum size.
+    fn check_array_repeat(&mut self, expr: &Expr, value: ExprId) {
+        let (Some(before), Some(value)) = (
+            self.snippet(expr.span),
+            self.snippet(self.get_expr(value).span),
+        ) else {
+            return;
+        };
+        self.advice.push(Advice::StaticArraySize {
+            before: before.to_string(),
+            after: format!("[{value}, size = MaxSize]"),
+            span: expr.span,
+        });
+    }
+}
+
+/// Moves the lines of a snippet after the first one from the given column to the given indentation, so that the snippet
+/// can be shown on its own or nested in a suggestion.
+fn reindent(snippet: &str, column: usize, indent: usize) -> String {
+    let mut lines = snippet.lines();
+    let mut reindented = lines.next().unwrap_or_default().to_string();
+    for line in lines {
+        let trimmed = line.trim_start_matches(' ');
+        let spaces = (line.len() - trimmed.len()).saturating_sub(column);
+        reindented.push('\n');
+        reindented.push_str(&" ".repeat(spaces + indent));
+        reindented.push_str(trimmed);
+    }
+    reindented
+}
+
+impl<'a> Visitor<'a> for Advisor<'a> {
+    fn visit_spec_impl(&mut self, spec_impl: &'a SpecImpl) {
+        // The other specializations are either generated from the body or written by hand in terms of the same
+        // constructs, so advising on the body is enough.
+        self.visit_spec_decl(&spec_impl.body);
+    }
+
+    fn visit_callable_impl(&mut self, callable_impl: &'a CallableImpl) {
+        if let CallableImpl::Spec(spec_impl) = callable_impl {
+            self.visit_spec_impl(spec_impl);
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt_id: StmtId) {
+        if let StmtKind::Local(Mutability::Immutable, pat, init) = &self.get_stmt(stmt_id).kind {
+            if let PatKind::Bind(ident) = &self.get_pat(*pat).kind {
+                self.bindings.insert(ident.id, *init);
+            }
+        }
+        visit::walk_stmt(self, stmt_id);
+    }
+
+    fn visit_expr(&mut self, expr_id: ExprId) {
+        let expr = self.get_expr(expr_id);
+        match &expr.kind {
+            ExprKind::Var(Res::Item(item), _) => {
+                let item_id = self.item_id(item);
+                self.callees.push(item_id);
+            }
+            ExprKind::Closure(_, item) => {
+                self.callees.push((self.package_id, *item).into());
+            }
+            ExprKind::If(cond, body, None) if self.is_dynamic(*cond) => {
+                self.check_branch_on_result(expr, *cond, *body);
+            }
+            ExprKind::While(cond, body) if self.is_dynamic(*cond) => {
+                self.check_while(expr, *cond, *body);
+            }
+            ExprKind::ArrayRepeat(value, size) if self.is_dynamic(*size) => {
+                self.check_array_repeat(expr, *value);
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, expr_id);
+    }
+
+    fn get_block(&self, id: BlockId) -> &'a Block {
+        self.package.get_block(id)
+    }
+
+    fn get_expr(&self, id: ExprId) -> &'a Expr {
+        self.package.get_expr(id)
+    }
+
+    fn get_pat(&self, id: PatId) -> &'a Pat {
+        self.package.get_pat(id)
+    }
+
+    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
+        self.package.get_stmt(id)
+    }
+}
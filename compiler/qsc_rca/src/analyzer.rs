@@ -3,14 +3,17 @@
 
 use crate::{
     core, cyclic_callables, overrider::Overrider,
-    scaffolding::InternalPackageStoreComputeProperties, PackageStoreComputeProperties,
+    scaffolding::InternalPackageStoreComputeProperties, EntryPointComputeProperties,
+    PackageStoreComputeProperties, UnresolvedCallees, ValueKind,
 };
-use qsc_fir::fir::{PackageId, PackageStore};
+use qsc_fir::fir::{PackageId, PackageStore, StoreItemId};
+use tracing::info_span;
 
 /// A runtime capabilities analyzer.
 pub struct Analyzer<'a> {
     package_store: &'a PackageStore,
     scaffolding: InternalPackageStoreComputeProperties,
+    unresolved_callees: UnresolvedCallees,
 }
 
 impl<'a> Analyzer<'a> {
@@ -19,6 +22,7 @@ impl<'a> Analyzer<'a> {
         Self {
             package_store,
             scaffolding: InternalPackageStoreComputeProperties::init(package_store),
+            unresolved_callees: UnresolvedCallees::default(),
         }
     }
 
@@ -30,11 +34,20 @@ impl<'a> Analyzer<'a> {
         Self {
             package_store,
             scaffolding: package_store_compute_properties.into(),
+            unresolved_callees: UnresolvedCallees::default(),
         }
     }
 
+    /// Sets how calls to callees that can't be resolved are analyzed.
+    #[must_use]
+    pub fn with_unresolved_callees(mut self, unresolved_callees: UnresolvedCallees) -> Self {
+        self.unresolved_callees = unresolved_callees;
+        self
+    }
+
     #[must_use]
     pub fn analyze_all(self) -> PackageStoreComputeProperties {
+        let _span = info_span!("rca").entered();
         // First, we populate the elements for which we override its compute properties.
         let overrider = Overrider::new(self.package_store, self.scaffolding);
         let scaffolding = overrider.populate_overrides();
@@ -46,18 +59,46 @@ impl<'a> Analyzer<'a> {
         let scaffolding = cyclic_callables_analyzer.analyze_all();
 
         // Now we can safely analyze the rest of the items.
-        let core_analyzer = core::Analyzer::new(self.package_store, scaffolding);
+        let core_analyzer =
+            core::Analyzer::new(self.package_store, scaffolding, &self.unresolved_callees);
         core_analyzer.analyze_all().into()
     }
 
+    /// Analyzes the whole program and then the body of the entry point callable with its arguments bound to the
+    /// provided value kinds. Unlike generating the application from the callable's generator set, which aggregates the
+    /// application of each dynamic parameter independently, this analyzes the specific application, so the runtime
+    /// capabilities it reports are the minimal ones that the entry point needs.
+    #[must_use]
+    pub fn analyze_entry_point(
+        self,
+        id: StoreItemId,
+        args_value_kinds: &[ValueKind],
+    ) -> EntryPointComputeProperties {
+        let _span = info_span!("rca").entered();
+        let overrider = Overrider::new(self.package_store, self.scaffolding);
+        let scaffolding = overrider.populate_overrides();
+        let cyclic_callables_analyzer =
+            cyclic_callables::Analyzer::new(self.package_store, scaffolding);
+        let scaffolding = cyclic_callables_analyzer.analyze_all();
+        let core_analyzer =
+            core::Analyzer::new(self.package_store, scaffolding, &self.unresolved_callees);
+        let (scaffolding, compute_kind) = core_analyzer.analyze_application(id, args_value_kinds);
+        EntryPointComputeProperties {
+            package_store: scaffolding.into(),
+            compute_kind,
+        }
+    }
+
     #[must_use]
     pub fn analyze_package(self, package_id: PackageId) -> PackageStoreComputeProperties {
+        let _span = info_span!("rca").entered();
         // Even when analyzing just one package we need to first analyze cyclic callables and then the rest of the items
         // to avoid an infinite analysis loop.
         let cyclic_callables_analyzer =
             cyclic_callables::Analyzer::new(self.package_store, self.scaffolding);
         let scaffolding = cyclic_callables_analyzer.analyze_package(package_id);
-        let core_analyzer = core::Analyzer::new(self.package_store, scaffolding);
+        let core_analyzer =
+            core::Analyzer::new(self.package_store, scaffolding, &self.unresolved_callees);
         core_analyzer.analyze_package(package_id).into()
     }
 }
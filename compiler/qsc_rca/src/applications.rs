@@ -40,7 +40,7 @@ impl GeneratorSetsBuilder {
             input_params,
             controls,
             return_type,
-            None,
+            &[],
         )]);
 
         // Insert the application instances representing the dynamic variant(s) of each input parameter.
@@ -55,28 +55,28 @@ impl GeneratorSetsBuilder {
                         input_params,
                         controls,
                         return_type,
-                        Some((
+                        &[(
                             input_param.index,
                             ValueKind::Array(RuntimeKind::Static, RuntimeKind::Dynamic),
-                        )),
+                        )],
                     );
                     let dynamic_content_static_size = ApplicationInstance::new(
                         input_params,
                         controls,
                         return_type,
-                        Some((
+                        &[(
                             input_param.index,
                             ValueKind::Array(RuntimeKind::Dynamic, RuntimeKind::Static),
-                        )),
+                        )],
                     );
                     let dynamic_content_dynamic_size = ApplicationInstance::new(
                         input_params,
                         controls,
                         return_type,
-                        Some((
+                        &[(
                             input_param.index,
                             ValueKind::Array(RuntimeKind::Dynamic, RuntimeKind::Dynamic),
-                        )),
+                        )],
                     );
                     vec![
                         static_content_dynamic_size,
@@ -90,7 +90,7 @@ impl GeneratorSetsBuilder {
                         input_params,
                         controls,
                         return_type,
-                        Some((input_param.index, ValueKind::Element(RuntimeKind::Dynamic))),
+                        &[(input_param.index, ValueKind::Element(RuntimeKind::Dynamic))],
                     )]
                 }
             };
@@ -104,6 +104,35 @@ impl GeneratorSetsBuilder {
         }
     }
 
+    /// Creates a new builder with a single application instance in which each input parameter is bound to the value
+    /// kind of the corresponding argument.
+    pub fn new_application(
+        input_params: &Vec<InputParam>,
+        controls: Option<&Local>,
+        return_type: &Ty,
+        args_value_kinds: &[ValueKind],
+    ) -> Self {
+        assert!(input_params.len() == args_value_kinds.len());
+        let mut dynamic_params = Vec::<(InputParamIndex, ValueKind)>::new();
+        for (input_param, arg_value_kind) in input_params.iter().zip(args_value_kinds) {
+            // Since the value kind of the argument might not match the variant of the parameter type, project it onto
+            // the parameter variant before binding it.
+            let mut value_kind = ValueKind::new_static_from_type(&input_param.ty);
+            arg_value_kind.project_onto_variant(&mut value_kind);
+            if value_kind.is_dynamic() {
+                dynamic_params.push((input_param.index, value_kind));
+            }
+        }
+
+        let application_instance =
+            ApplicationInstance::new(input_params, controls, return_type, &dynamic_params);
+        Self {
+            application_instances: vec![vec![application_instance]],
+            current_application_instance: 0,
+            input_params_count: 0,
+        }
+    }
+
     pub fn advance_current_application_instance(&mut self) -> bool {
         if self.current_application_instance < self.flattened_application_instances().count() - 1 {
             self.current_application_instance += 1;
@@ -192,6 +221,20 @@ impl GeneratorSetsBuilder {
         close_output
     }
 
+    /// Closes a builder created for a single application and returns the compute kind of the application, which is
+    /// the compute kind of the main block with the value kind of the application instance.
+    pub fn close_application(mut self, main_block: BlockId) -> ComputeKind {
+        let application_compute_properties = self.close_inherent();
+        let mut compute_kind = *application_compute_properties
+            .blocks
+            .get(&main_block)
+            .expect("main block compute kind should exist");
+        if let Some(value_kind) = application_compute_properties.value_kind {
+            compute_kind.aggregate_value_kind(value_kind);
+        }
+        compute_kind
+    }
+
     fn aggregate_param_application_value_kind(
         param_application: &mut crate::ParamApplication,
         compute_properties: &ParamApplicationComputeProperties,
@@ -451,7 +494,7 @@ impl ApplicationInstance {
         input_params: &Vec<InputParam>,
         controls: Option<&Local>,
         return_type: &Ty,
-        dynamic_param: Option<(InputParamIndex, ValueKind)>,
+        dynamic_params: &[(InputParamIndex, ValueKind)],
     ) -> Self {
         // Initialize the locals map with the specialization controls (if any).
         let mut locals_map = LocalsComputeKindMap::default();
@@ -477,15 +520,16 @@ impl ApplicationInstance {
                 panic!("only input parameters are expected");
             };
 
-            // If a dynamic application is provided, set the compute kind associated to the parameter accordingly.
+            // If the parameter is bound to a dynamic value, set the compute kind associated to it accordingly.
             let mut compute_kind = ComputeKind::Classical;
-            if let Some((dynamic_param_index, dynamic_param_value_kind)) = dynamic_param {
-                if input_param_index == dynamic_param_index {
-                    compute_kind = ComputeKind::Quantum(QuantumProperties {
-                        runtime_features: RuntimeFeatureFlags::empty(),
-                        value_kind: dynamic_param_value_kind,
-                    });
-                }
+            if let Some((_, dynamic_param_value_kind)) = dynamic_params
+                .iter()
+                .find(|(dynamic_param_index, _)| *dynamic_param_index == input_param_index)
+            {
+                compute_kind = ComputeKind::Quantum(QuantumProperties {
+                    runtime_features: RuntimeFeatureFlags::empty(),
+                    value_kind: *dynamic_param_value_kind,
+                });
             }
 
             locals_map.insert(
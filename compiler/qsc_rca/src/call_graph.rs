@@ -0,0 +1,305 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The call graph of a package annotated with the results of the runtime capabilities analysis, so that the places
+//! where capabilities such as `HigherLevelConstructs` enter a large codebase can be visualized.
+
+use crate::{
+    requirements::FlagNames, ComputeKind, ComputePropertiesLookup, ItemComputeProperties,
+    PackageStoreComputeProperties, RuntimeFeatureFlags,
+};
+use qsc_data_structures::span::Span;
+use qsc_fir::{
+    fir::{
+        Block, BlockId, CallableImpl, Expr, ExprId, ExprKind, Global, ItemKind, Package, PackageId,
+        PackageLookup, PackageStore, PackageStoreLookup, Pat, PatId, Res, Stmt, StmtId,
+        StoreItemId,
+    },
+    visit::{self, Visitor},
+};
+use qsc_frontend::compile::RuntimeCapabilityFlags;
+use rustc_hash::FxHashMap;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter, Write},
+    sync::Arc,
+};
+
+/// The callables of a package and the callables they refer to, annotated with the capabilities they need.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallGraph {
+    /// The callables declared in the sources of the packages, ordered by package and then by span, followed by the
+    /// callables of other packages they refer to, ordered by name.
+    pub nodes: Vec<CallGraphNode>,
+    /// The references from one callable to another, ordered by caller and then callee.
+    pub edges: Vec<CallGraphEdge>,
+}
+
+/// The references from one callable of the call graph to another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallGraphEdge {
+    /// The index of the referring callable in the nodes.
+    pub caller: usize,
+    /// The index of the referenced callable in the nodes.
+    pub callee: usize,
+    /// The spans of the references, which are in the package of the caller, in source order.
+    pub spans: Vec<Span>,
+}
+
+/// A callable of the call graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallGraphNode {
+    /// The namespace-qualified name of the callable.
+    pub name: Arc<str>,
+    /// The package the callable is declared in.
+    pub package: PackageId,
+    /// The span of the name of the callable in the sources of its package.
+    pub span: Span,
+    /// The capabilities needed by any of the specializations of the callable, when called with static arguments.
+    pub capabilities: RuntimeCapabilityFlags,
+    /// The capabilities the callable needs that none of the callables it refers to need, which is where they enter
+    /// the program.
+    pub introduced: RuntimeCapabilityFlags,
+    /// The runtime features of the callable, which are the sources of dynamism its capabilities come from.
+    pub runtime_features: RuntimeFeatureFlags,
+}
+
+impl CallGraph {
+    /// Renders the call graph as a Graphviz DOT digraph. Callables that introduce capabilities are filled.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph calls {\n    node [shape=box];\n".to_string();
+        for (index, node) in self.nodes.iter().enumerate() {
+            let mut label = escape(&node.name);
+            if !node.capabilities.is_empty() {
+                let _ = write!(label, "\\n{}", FlagNames(node.capabilities));
+            }
+            let style = if node.introduced.is_empty() {
+                ""
+            } else {
+                ", style=filled"
+            };
+            let _ = writeln!(dot, "    n{index} [label=\"{label}\"{style}];");
+        }
+        for edge in &self.edges {
+            let _ = writeln!(dot, "    n{} -> n{};", edge.caller, edge.callee);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl Display for CallGraph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, node) in self.nodes.iter().enumerate() {
+            write!(f, "{}: {}", node.name, FlagNames(node.capabilities))?;
+            let callees = self
+                .edges
+                .iter()
+                .filter(|edge| edge.caller == index)
+                .map(|edge| &*self.nodes[edge.callee].name)
+                .collect::<Vec<_>>();
+            if !callees.is_empty() {
+                write!(f, " -> {}", callees.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Produces the call graph of the callables declared in the source of the given package. A callable refers to every
+/// callable it names, which includes the callables it passes as arguments. Lambdas are lifted into callables without a
+/// name in source, so the callables they refer to are attributed to the callable that declares them. The callables of
+/// other packages are part of the graph, but the callables they refer to aren't.
+#[must_use]
+pub fn call_graph(
+    package_store: &PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    package_id: PackageId,
+) -> CallGraph {
+    call_graph_of_packages(package_store, compute_properties, &[package_id])
+}
+
+/// Like [`call_graph`], for the callables declared in the sources of several packages, such as the sources of a
+/// program and the fragments evaluated after them.
+#[must_use]
+pub fn call_graph_of_packages(
+    package_store: &PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    package_ids: &[PackageId],
+) -> CallGraph {
+    let mut callers = Vec::new();
+    for (order, &package_id) in package_ids.iter().enumerate() {
+        let package = package_store.get(package_id);
+        for (item_id, item) in &package.items {
+            let ItemKind::Callable(decl) = &item.kind else {
+                continue;
+            };
+            if decl.name.span == Span::default() {
+                continue;
+            }
+            let mut collector = ReferenceCollector {
+                package_store,
+                package_id,
+                package,
+                references: Vec::new(),
+            };
+            collector.visit_callable_decl(decl);
+            callers.push((
+                (order, decl.name.span.lo),
+                StoreItemId::from((package_id, item_id)),
+                collector.references,
+            ));
+        }
+    }
+    callers.sort_by_key(|(key, _, _)| *key);
+
+    let mut nodes = Vec::new();
+    let mut indices = FxHashMap::default();
+    for (_, id, _) in &callers {
+        indices.insert(*id, nodes.len());
+        nodes.push(node(package_store, compute_properties, *id));
+    }
+    let mut external = callers
+        .iter()
+        .flat_map(|(_, _, references)| references)
+        .filter(|(id, _)| !package_ids.contains(&id.package))
+        .map(|(id, _)| (*id, node(package_store, compute_properties, *id)))
+        .collect::<Vec<_>>();
+    external.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+    external.dedup_by_key(|(id, _)| *id);
+    for (id, node) in external {
+        indices.insert(id, nodes.len());
+        nodes.push(node);
+    }
+
+    let mut edges = Vec::new();
+    for (_, id, references) in &callers {
+        let caller = indices[id];
+        let mut spans_by_callee = BTreeMap::<usize, Vec<Span>>::new();
+        for (reference, span) in references {
+            if let Some(&callee) = indices.get(reference) {
+                spans_by_callee.entry(callee).or_default().push(*span);
+            }
+        }
+        let mut inherited = RuntimeCapabilityFlags::empty();
+        for (callee, mut spans) in spans_by_callee {
+            if callee != caller {
+                inherited |= nodes[callee].capabilities;
+            }
+            spans.sort_unstable_by_key(|span| span.lo);
+            spans.dedup();
+            edges.push(CallGraphEdge {
+                caller,
+                callee,
+                spans,
+            });
+        }
+        nodes[caller].introduced = nodes[caller].capabilities - inherited;
+    }
+
+    CallGraph { nodes, edges }
+}
+
+fn node(
+    package_store: &PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    id: StoreItemId,
+) -> CallGraphNode {
+    let package = package_store.get(id.package);
+    let item = package.get_item(id.item);
+    let ItemKind::Callable(decl) = &item.kind else {
+        panic!("referenced item should be a callable");
+    };
+    let name = match item.parent.map(|parent| &package.get_item(parent).kind) {
+        Some(ItemKind::Namespace(namespace, _)) => {
+            format!("{}.{}", namespace.name, decl.name.name).into()
+        }
+        _ => decl.name.name.clone(),
+    };
+
+    let mut runtime_features = RuntimeFeatureFlags::empty();
+    if let ItemComputeProperties::Callable(callable) = compute_properties.get_item(id) {
+        for set in [
+            Some(&callable.body),
+            callable.adj.as_ref(),
+            callable.ctl.as_ref(),
+            callable.ctl_adj.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if let ComputeKind::Quantum(properties) = set.inherent {
+                runtime_features |= properties.runtime_features;
+            }
+        }
+    }
+    // Intrinsics don't refer to other callables, so all the capabilities they need are their own.
+    let capabilities = runtime_features.runtime_capabilities();
+    let introduced = if matches!(decl.implementation, CallableImpl::Intrinsic) {
+        capabilities
+    } else {
+        RuntimeCapabilityFlags::empty()
+    };
+    CallGraphNode {
+        name,
+        package: id.package,
+        span: decl.name.span,
+        capabilities,
+        introduced,
+        runtime_features,
+    }
+}
+
+/// Collects the callables that the specializations of a callable, and the lambdas it declares, refer to, along with
+/// the spans of the references.
+struct ReferenceCollector<'a> {
+    package_store: &'a PackageStore,
+    package_id: PackageId,
+    package: &'a Package,
+    references: Vec<(StoreItemId, Span)>,
+}
+
+impl<'a> Visitor<'a> for ReferenceCollector<'a> {
+    fn visit_expr(&mut self, expr_id: ExprId) {
+        let expr = self.get_expr(expr_id);
+        match &expr.kind {
+            ExprKind::Var(Res::Item(item_id), _) => {
+                let id =
+                    StoreItemId::from((item_id.package.unwrap_or(self.package_id), item_id.item));
+                // Items also name the constructors of user-defined types, which aren't callables of the graph.
+                if let Some(Global::Callable(_)) = self.package_store.get_global(id) {
+                    self.references.push((id, expr.span));
+                }
+            }
+            ExprKind::Closure(_, item_id) => {
+                if let ItemKind::Callable(decl) = &self.package.get_item(*item_id).kind {
+                    self.visit_callable_decl(decl);
+                }
+            }
+            _ => {}
+        }
+        visit::walk_expr(self, expr_id);
+    }
+
+    fn get_block(&self, id: BlockId) -> &'a Block {
+        self.package.get_block(id)
+    }
+
+    fn get_expr(&self, id: ExprId) -> &'a Expr {
+        self.package.get_expr(id)
+    }
+
+    fn get_pat(&self, id: PatId) -> &'a Pat {
+        self.package.get_pat(id)
+    }
+
+    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
+        self.package.get_stmt(id)
+    }
+}
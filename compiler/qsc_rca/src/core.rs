@@ -5,11 +5,12 @@ use crate::{
     applications::{ApplicationInstance, GeneratorSetsBuilder, LocalComputeKind},
     common::{
         derive_callable_input_params, try_resolve_callee, Callee, FunctorAppExt, GlobalSpecId,
-        InputParam, Local, LocalKind, TyExt,
+        InputParam, Local, LocalKind, LocalsLookup, TyExt,
     },
     scaffolding::{InternalItemComputeProperties, InternalPackageStoreComputeProperties},
     ApplicationGeneratorSet, ArrayParamApplication, ComputeKind, ComputePropertiesLookup,
-    ParamApplication, QuantumProperties, RuntimeFeatureFlags, RuntimeKind, ValueKind,
+    ParamApplication, QuantumProperties, RuntimeFeatureFlags, RuntimeKind, UnresolvedCallees,
+    ValueKind,
 };
 use qsc_data_structures::{functors::FunctorApp, index_map::IndexMap};
 use qsc_fir::{
@@ -27,17 +28,20 @@ pub struct Analyzer<'a> {
     package_store: &'a PackageStore,
     package_store_compute_properties: InternalPackageStoreComputeProperties,
     active_contexts: Vec<AnalysisContext>,
+    unresolved_callees: &'a UnresolvedCallees,
 }
 
 impl<'a> Analyzer<'a> {
     pub fn new(
         package_store: &'a PackageStore,
         package_store_compute_properties: InternalPackageStoreComputeProperties,
+        unresolved_callees: &'a UnresolvedCallees,
     ) -> Self {
         Self {
             package_store,
             package_store_compute_properties,
             active_contexts: Vec::<AnalysisContext>::default(),
+            unresolved_callees,
         }
     }
 
@@ -57,6 +61,61 @@ impl<'a> Analyzer<'a> {
         self.package_store_compute_properties
     }
 
+    /// Analyzes all the packages and then analyzes the body of the specified callable once, with each input parameter
+    /// bound to the value kind of the corresponding argument. Returns the compute properties of the package store
+    /// along with the compute kind of the application.
+    pub fn analyze_application(
+        mut self,
+        id: StoreItemId,
+        args_value_kinds: &[ValueKind],
+    ) -> (InternalPackageStoreComputeProperties, ComputeKind) {
+        for (package_id, package) in self.package_store {
+            self.analyze_package_internal(package_id, package);
+        }
+        let compute_kind = self.analyze_callable_application(id, args_value_kinds);
+        let compute_kind = self.aggregate_args_runtime_features(id, args_value_kinds, compute_kind);
+        (self.package_store_compute_properties, compute_kind)
+    }
+
+    /// Aggregates the runtime features that the dynamic arguments of an entry point need. The arguments come from
+    /// outside of the program, so unlike the arguments of a call, no expression contributes these features.
+    fn aggregate_args_runtime_features(
+        &self,
+        id: StoreItemId,
+        args_value_kinds: &[ValueKind],
+        compute_kind: ComputeKind,
+    ) -> ComputeKind {
+        let package = self.package_store.get(id.package);
+        let Some(Global::Callable(callable_decl)) = self.package_store.get_global(id) else {
+            panic!("item should be a callable");
+        };
+        let runtime_features = derive_callable_input_params(callable_decl, &package.pats)
+            .iter()
+            .zip(args_value_kinds)
+            .fold(
+                RuntimeFeatureFlags::empty(),
+                |features, (param, value_kind)| {
+                    features
+                        | derive_runtime_features_for_value_kind_associated_to_type(
+                            *value_kind,
+                            &param.ty,
+                        )
+                },
+            );
+        if runtime_features.is_empty() {
+            return compute_kind;
+        }
+
+        let value_kind = ValueKind::new_static_from_type(&callable_decl.output);
+        compute_kind.aggregate_runtime_features(
+            ComputeKind::Quantum(QuantumProperties {
+                runtime_features,
+                value_kind,
+            }),
+            value_kind,
+        )
+    }
+
     fn analyze_expr_array(&mut self, exprs: &Vec<ExprId>) -> ComputeKind {
         // Visit each sub-expression in the array to determine their compute kind, and aggregate ONLY the runtime
         // features to the array's compute kind.
@@ -87,6 +146,50 @@ impl<'a> Analyzer<'a> {
         compute_kind
     }
 
+    fn analyze_callable_application(
+        &mut self,
+        id: StoreItemId,
+        args_value_kinds: &[ValueKind],
+    ) -> ComputeKind {
+        let package = self.package_store.get(id.package);
+        let Some(Global::Callable(callable_decl)) = self.package_store.get_global(id) else {
+            panic!("item should be a callable");
+        };
+        let CallableImpl::Spec(spec_impl) = &callable_decl.implementation else {
+            // Intrinsics do not have a body to analyze, so their application is derived from their generator set.
+            return self
+                .package_store_compute_properties
+                .get_spec((id, FunctorSetValue::Empty).into())
+                .generate_application_compute_kind(args_value_kinds);
+        };
+
+        // Visit the body block once, using a builder whose only application instance has the input parameters bound to
+        // the value kinds of the arguments.
+        self.push_item_context(id);
+        let input_params = derive_callable_input_params(callable_decl, &package.pats);
+        let builder = GeneratorSetsBuilder::new_application(
+            &input_params,
+            None,
+            &callable_decl.output,
+            args_value_kinds,
+        );
+        let current_item_context = self.get_current_item_context_mut();
+        current_item_context.set_callable_context(
+            callable_decl.kind,
+            input_params,
+            callable_decl.output.clone(),
+        );
+        current_item_context.set_current_spec_context(SpecContext {
+            functor_set_value: FunctorSetValue::Empty,
+            builder,
+        });
+        self.visit_block(spec_impl.body.block);
+        let spec_context = self.clear_current_spec_context();
+        let popped_item_id = self.pop_item_context();
+        assert!(popped_item_id == id);
+        spec_context.builder.close_application(spec_impl.body.block)
+    }
+
     fn analyze_expr_array_repeat(
         &mut self,
         value_expr_id: ExprId,
@@ -455,13 +558,17 @@ impl<'a> Analyzer<'a> {
             &application_instance.locals_map,
         );
 
-        // If the callee could not be resolved, return a compute kind with certain runtime features.
+        // If the callee could not be resolved, return a compute kind with certain runtime features, unless there is an
+        // assumption for the callee.
         let Some(callee) = maybe_callee else {
             // The value kind of a call expression with an unresolved callee is dynamic but its specific variant depends
             // on the expression's type.
             let value_kind = ValueKind::new_dynamic_from_type(expr_type);
+            let runtime_features = self
+                .assumed_callee_features(callee_expr_id)
+                .unwrap_or(RuntimeFeatureFlags::CallToUnresolvedCallee);
             return ComputeKind::Quantum(QuantumProperties {
-                runtime_features: RuntimeFeatureFlags::CallToUnresolvedCallee,
+                runtime_features,
                 value_kind,
             });
         };
@@ -482,6 +589,24 @@ impl<'a> Analyzer<'a> {
         }
     }
 
+    /// Looks up the runtime features assumed for an unresolved callee, by the name of the local it is called through.
+    fn assumed_callee_features(&self, callee_expr_id: ExprId) -> Option<RuntimeFeatureFlags> {
+        if self.unresolved_callees.assumptions.is_empty() {
+            return None;
+        }
+        let ExprKind::Var(Res::Local(local_var_id), _) = &self.get_expr(callee_expr_id).kind else {
+            return None;
+        };
+        let local = self
+            .get_current_application_instance()
+            .locals_map
+            .find(*local_var_id)?;
+        let PatKind::Bind(ident) = &self.get_pat(local.pat).kind else {
+            return None;
+        };
+        self.unresolved_callees.assumed_features(&ident.name)
+    }
+
     fn analyze_expr_call_with_udt_callee(&self, args_expr_id: ExprId) -> ComputeKind {
         let application_instance = self.get_current_application_instance();
         let args_expr_compute_kind = *application_instance.get_expr_compute_kind(args_expr_id);
@@ -948,7 +1073,7 @@ impl<'a> Analyzer<'a> {
 
         // Determine the application generator set depending on whether the callable is a function or an operation.
         let callable_context = current_item_context.get_callable_context();
-        let application_generator_set = match callable_context.kind {
+        let mut application_generator_set = match callable_context.kind {
             CallableKind::Function => {
                 derive_intrinsic_function_application_generator_set(callable_context)
             }
@@ -957,6 +1082,33 @@ impl<'a> Analyzer<'a> {
             }
         };
 
+        // Custom intrinsics declare the capabilities the target needs to run them.
+        let item = self.package_store.get_item(current_item_context.id);
+        if item
+            .custom_intrinsic()
+            .is_some_and(|intrinsic| intrinsic.unrestricted)
+        {
+            if let ComputeKind::Quantum(quantum_properties) =
+                &mut application_generator_set.inherent
+            {
+                quantum_properties.runtime_features |=
+                    RuntimeFeatureFlags::CallToUnrestrictedIntrinsic;
+            }
+        }
+
+        // Borrowed qubits may be in any state, unlike the qubits that targets allocate in the zero state.
+        let is_borrow = matches!(
+            &item.kind,
+            ItemKind::Callable(decl) if decl.name.name.as_ref() == "__quantum__rt__qubit_borrow"
+        );
+        if is_borrow {
+            if let ComputeKind::Quantum(quantum_properties) =
+                &mut application_generator_set.inherent
+            {
+                quantum_properties.runtime_features |= RuntimeFeatureFlags::UseOfBorrowedQubit;
+            }
+        }
+
         // Insert the generator set in the entry corresponding to the body specialization of the callable.
         self.package_store_compute_properties
             .insert_spec(body_specialization_id, application_generator_set);
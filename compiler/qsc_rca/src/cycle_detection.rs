@@ -5,17 +5,128 @@ use crate::common::{
     derive_callable_input_params, initialize_locals_map, try_resolve_callee, FunctorAppExt, Local,
     LocalKind, LocalSpecId,
 };
+use miette::{Diagnostic, LabeledSpan, Severity};
+use qsc_data_structures::span::Span;
 use qsc_fir::{
     fir::{
-        Block, BlockId, CallableDecl, CallableImpl, Expr, ExprId, ExprKind, Item, ItemKind,
-        LocalVarId, Mutability, Package, PackageId, PackageLookup, Pat, PatId, PatKind, SpecDecl,
-        Stmt, StmtId, StmtKind,
+        Block, BlockId, CallableDecl, CallableImpl, CallableKind, Expr, ExprId, ExprKind, Item,
+        ItemKind, LocalItemId, LocalVarId, Mutability, Package, PackageId, PackageLookup,
+        PackageStore, Pat, PatId, PatKind, SpecDecl, Stmt, StmtId, StmtKind,
     },
     ty::FunctorSetValue,
     visit::{walk_expr, Visitor},
 };
 use rustc_hash::{FxHashMap, FxHashSet};
-use std::collections::hash_map::Entry;
+use std::{
+    collections::hash_map::Entry,
+    fmt::{self, Display, Formatter},
+};
+
+/// Callables that call each other in a cycle. Cyclic operations need the `HigherLevelConstructs` capability, and so do
+/// cyclic functions when they are called with dynamic arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallCycle {
+    /// The names of the callables, along with the spans of the names, in the order they call each other starting from
+    /// the callable declared first.
+    pub callables: Vec<(String, Span)>,
+    /// Whether any of the callables is an operation, which makes the cycle need `HigherLevelConstructs` regardless of
+    /// the arguments it is called with.
+    pub has_operation: bool,
+}
+
+/// Call cycles are reported as warnings, since the code is valid Q# that restricted targets can't run.
+impl Diagnostic for CallCycle {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new("Qsc.Capabilities.CallCycle"))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(Severity::Warning)
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(
+            "cyclic operations need higher-level constructs, consider replacing the recursion with a loop",
+        ))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(
+            self.callables
+                .iter()
+                .map(|(_, span)| LabeledSpan::new_with_span(None, *span)),
+        ))
+    }
+}
+
+impl Display for CallCycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "callables call each other in a cycle: ")?;
+        for (name, _) in &self.callables {
+            write!(f, "{name} -> ")?;
+        }
+        let (first, _) = self
+            .callables
+            .first()
+            .expect("call cycle should have callables");
+        write!(f, "{first}")
+    }
+}
+
+impl std::error::Error for CallCycle {}
+
+/// Produces the call cycles among the callables declared in the given package, ordered by the span of the callable
+/// each starts from. Callees are resolved like the analysis does, so calls through callables passed as arguments
+/// aren't part of any cycle.
+#[must_use]
+pub fn call_cycles(package_store: &PackageStore, package_id: PackageId) -> Vec<CallCycle> {
+    let package = package_store.get(package_id);
+    let decl = |item_id: LocalItemId| {
+        let ItemKind::Callable(decl) = &package.get_item(item_id).kind else {
+            panic!("item must be a callable");
+        };
+        decl
+    };
+
+    let mut cycles = Vec::<Vec<LocalItemId>>::new();
+    for specializations in CycleDetector::new(package_id, package).detect_cycles() {
+        // Specializations of the same callable that call each other are part of the same step of the cycle.
+        let mut callables = specializations
+            .iter()
+            .map(|specialization| specialization.callable)
+            .collect::<Vec<_>>();
+        callables.dedup();
+        if callables.len() > 1 && callables.first() == callables.last() {
+            callables.pop();
+        }
+
+        // The same cycle is found starting from each of its callables, so start it from the one declared first.
+        let start = (0..callables.len())
+            .min_by_key(|index| decl(callables[*index]).name.span.lo)
+            .expect("cycle should have callables");
+        callables.rotate_left(start);
+        if !cycles.contains(&callables) {
+            cycles.push(callables);
+        }
+    }
+
+    cycles.sort_by_key(|callables| decl(callables[0]).name.span.lo);
+    cycles
+        .into_iter()
+        .map(|callables| CallCycle {
+            has_operation: callables
+                .iter()
+                .any(|item_id| decl(*item_id).kind == CallableKind::Operation),
+            callables: callables
+                .into_iter()
+                .map(|item_id| {
+                    let decl = decl(item_id);
+                    (decl.name.name.to_string(), decl.name.span)
+                })
+                .collect(),
+        })
+        .collect()
+}
 
 pub struct CycleDetector<'a> {
     package_id: PackageId,
@@ -23,6 +134,7 @@ pub struct CycleDetector<'a> {
     stack: CallStack,
     specializations_locals: FxHashMap<LocalSpecId, FxHashMap<LocalVarId, Local>>,
     specializations_with_cycles: FxHashSet<LocalSpecId>,
+    cycles: Vec<Vec<LocalSpecId>>,
 }
 
 impl<'a> CycleDetector<'a> {
@@ -33,6 +145,7 @@ impl<'a> CycleDetector<'a> {
             stack: CallStack::default(),
             specializations_locals: FxHashMap::default(),
             specializations_with_cycles: FxHashSet::<LocalSpecId>::default(),
+            cycles: Vec::new(),
         }
     }
 
@@ -41,6 +154,13 @@ impl<'a> CycleDetector<'a> {
         self.specializations_with_cycles.drain().collect()
     }
 
+    /// Detects the cycles of specializations that call each other, in the order they call each other. The same cycle
+    /// can be detected more than once, starting from different specializations.
+    pub fn detect_cycles(mut self) -> Vec<Vec<LocalSpecId>> {
+        self.visit_package(self.package);
+        self.cycles
+    }
+
     fn map_pat_to_expr(&mut self, mutability: Mutability, pat_id: PatId, expr_id: ExprId) {
         let pat = self.get_pat(pat_id);
         match &pat.kind {
@@ -134,6 +254,7 @@ impl<'a> CycleDetector<'a> {
         // If the specialization is already in the stack, it means the callable has a cycle.
         if self.stack.contains(&local_spec_id) {
             self.specializations_with_cycles.insert(local_spec_id);
+            self.cycles.push(self.stack.since(&local_spec_id));
             return;
         }
 
@@ -264,6 +385,16 @@ impl CallStack {
         self.set.contains(value)
     }
 
+    /// The specializations from the given one to the top of the stack.
+    fn since(&self, value: &LocalSpecId) -> Vec<LocalSpecId> {
+        let position = self
+            .stack
+            .iter()
+            .position(|spec| spec == value)
+            .expect("specialization should be in the stack");
+        self.stack[position..].to_vec()
+    }
+
     fn peak(&self) -> &LocalSpecId {
         self.stack.last().expect("stack should not be empty")
     }
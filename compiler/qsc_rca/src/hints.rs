@@ -0,0 +1,209 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Hints annotate the source code of a package with the results of the runtime capabilities analysis, so that tools
+//! such as the language service can show which bindings hold values only known at runtime and which calls need
+//! capabilities beyond the Base profile while the code is being written.
+
+use crate::{
+    ComputeKind, ComputePropertiesLookup, PackageStoreComputeProperties, RuntimeFeatureFlags,
+};
+use qsc_data_structures::span::Span;
+use qsc_fir::{
+    fir::{
+        Block, BlockId, Expr, ExprId, ExprKind, Package, PackageId, PackageLookup, PackageStore,
+        Pat, PatId, PatKind, Stmt, StmtId, StmtKind,
+    },
+    visit::{self, Visitor},
+};
+
+/// An annotation on a program element, in the style of an inlay hint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hint {
+    pub kind: HintKind,
+    /// The span of the annotated element.
+    pub span: Span,
+    pub label: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HintKind {
+    /// A binding to a value that is only known at runtime, e.g. `dynamic Int`.
+    DynamicBinding,
+    /// A call that needs runtime capabilities beyond the Base profile, e.g. `requires ForwardBranching`.
+    RequiredCapabilities,
+}
+
+/// Produces the hints for the callables of the given package, ordered by span. Only the specializations that have
+/// been analyzed get hints.
+#[must_use]
+pub fn hints(
+    package_store: &PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    package_id: PackageId,
+) -> Vec<Hint> {
+    let mut collector = HintCollector {
+        compute_properties,
+        package_id,
+        package: package_store.get(package_id),
+        hints: Vec::new(),
+    };
+    for item in collector.package.items.values() {
+        collector.visit_item(item);
+    }
+
+    // Generated specializations reuse the spans of the code they are generated from, so they produce the same hints.
+    let mut hints = collector.hints;
+    hints.sort_by_key(|hint| (hint.span.lo, hint.span.hi));
+    hints.dedup();
+    hints
+}
+
+/// The analysis results for the code at a source location, in the style of a hover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComputeInfo {
+    /// Whether the code depends on the quantum state or on quantum operations.
+    pub quantum: bool,
+    /// Whether the value of the code is only known at runtime.
+    pub dynamic: bool,
+    /// The runtime features the code uses.
+    pub runtime_features: RuntimeFeatureFlags,
+}
+
+/// Produces the analysis results for the innermost expression or statement of the given package that covers `span`.
+#[must_use]
+pub fn compute_info(
+    package_store: &PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    package_id: PackageId,
+    span: Span,
+) -> Option<ComputeInfo> {
+    let set = compute_properties.find_by_span(package_id, package_store.get(package_id), span)?;
+    Some(match set.inherent {
+        ComputeKind::Classical => ComputeInfo {
+            quantum: false,
+            dynamic: false,
+            runtime_features: RuntimeFeatureFlags::empty(),
+        },
+        ComputeKind::Quantum(properties) => ComputeInfo {
+            quantum: true,
+            dynamic: set.inherent.is_dynamic(),
+            runtime_features: properties.runtime_features,
+        },
+    })
+}
+
+struct HintCollector<'a> {
+    compute_properties: &'a PackageStoreComputeProperties,
+    package_id: PackageId,
+    package: &'a Package,
+    hints: Vec<Hint>,
+}
+
+impl HintCollector<'_> {
+    fn compute_kind(&self, expr_id: ExprId) -> Option<ComputeKind> {
+        self.compute_properties
+            .find_expr((self.package_id, expr_id).into())
+            .map(|set| set.inherent)
+    }
+
+    fn push(&mut self, kind: HintKind, span: Span, label: String) {
+        // Generated code has no source to annotate.
+        if span != Span::default() {
+            self.hints.push(Hint { kind, span, label });
+        }
+    }
+
+    fn check_binding(&mut self, pat_id: PatId, expr_id: ExprId) {
+        let pat = self.get_pat(pat_id);
+        match (&pat.kind, &self.get_expr(expr_id).kind) {
+            (PatKind::Tuple(pats), ExprKind::Tuple(exprs)) if pats.len() == exprs.len() => {
+                for (pat, expr) in pats.iter().zip(exprs.iter()) {
+                    self.check_binding(*pat, *expr);
+                }
+            }
+            (PatKind::Discard, _) => {}
+            _ => {
+                if self
+                    .compute_kind(expr_id)
+                    .is_some_and(ComputeKind::is_dynamic)
+                {
+                    self.push_dynamic_bindings(pat_id);
+                }
+            }
+        }
+    }
+
+    fn push_dynamic_bindings(&mut self, pat_id: PatId) {
+        let pat = self.get_pat(pat_id);
+        match &pat.kind {
+            // Bindings introduced by passes, such as the ones that capture the value of a qubit allocation block,
+            // have names that can't be written in source.
+            PatKind::Bind(ident) if ident.name.starts_with('@') => {}
+            PatKind::Bind(ident) => {
+                self.push(
+                    HintKind::DynamicBinding,
+                    ident.span,
+                    format!("dynamic {}", pat.ty),
+                );
+            }
+            PatKind::Tuple(pats) => {
+                for pat in pats {
+                    self.push_dynamic_bindings(*pat);
+                }
+            }
+            PatKind::Discard => {}
+        }
+    }
+
+    fn check_call(&mut self, expr: &Expr) {
+        let Some(ComputeKind::Quantum(properties)) = self.compute_kind(expr.id) else {
+            return;
+        };
+        let capabilities = properties.runtime_features.runtime_capabilities();
+        if !capabilities.is_empty() {
+            let names = capabilities
+                .iter_names()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>();
+            self.push(
+                HintKind::RequiredCapabilities,
+                expr.span,
+                format!("requires {}", names.join(", ")),
+            );
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for HintCollector<'a> {
+    fn visit_stmt(&mut self, stmt_id: StmtId) {
+        if let StmtKind::Local(_, pat, init) = &self.get_stmt(stmt_id).kind {
+            self.check_binding(*pat, *init);
+        }
+        visit::walk_stmt(self, stmt_id);
+    }
+
+    fn visit_expr(&mut self, expr_id: ExprId) {
+        let expr = self.get_expr(expr_id);
+        if let ExprKind::Call(..) = &expr.kind {
+            self.check_call(expr);
+        }
+        visit::walk_expr(self, expr_id);
+    }
+
+    fn get_block(&self, id: BlockId) -> &'a Block {
+        self.package.get_block(id)
+    }
+
+    fn get_expr(&self, id: ExprId) -> &'a Expr {
+        self.package.get_expr(id)
+    }
+
+    fn get_pat(&self, id: PatId) -> &'a Pat {
+        self.package.get_pat(id)
+    }
+
+    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
+        self.package.get_stmt(id)
+    }
+}
@@ -6,23 +6,31 @@
 //! execution on a quantum kernel and does not consider these elements when determining the capabilities. Additionally,
 //! this implementation also provides details on why the program requires each capability.
 
+mod advisor;
 mod analyzer;
 mod applications;
+mod call_graph;
 mod common;
 mod core;
 mod cycle_detection;
 mod cyclic_callables;
+mod hints;
 mod overrider;
+mod requirements;
 mod scaffolding;
+mod unresolved_callees;
 
 use crate::common::set_indentation;
 use bitflags::bitflags;
 use indenter::indented;
-use qsc_data_structures::index_map::{IndexMap, Iter};
+use qsc_data_structures::{
+    index_map::{IndexMap, Iter},
+    span::Span,
+};
 use qsc_fir::{
     fir::{
-        BlockId, ExprId, LocalItemId, PackageId, StmtId, StoreBlockId, StoreExprId, StoreItemId,
-        StoreStmtId,
+        BlockId, ExprId, LocalItemId, Package, PackageId, StmtId, StoreBlockId, StoreExprId,
+        StoreItemId, StoreStmtId,
     },
     ty::Ty,
 };
@@ -32,7 +40,21 @@ use std::{
     fmt::{self, Debug, Display, Formatter, Write},
 };
 
-pub use crate::analyzer::Analyzer;
+pub use crate::{
+    advisor::{advise, Advice},
+    analyzer::Analyzer,
+    call_graph::{call_graph, call_graph_of_packages, CallGraph, CallGraphEdge, CallGraphNode},
+    cycle_detection::{call_cycles, CallCycle},
+    hints::{compute_info, hints, ComputeInfo, Hint, HintKind},
+    requirements::{
+        library_summary, package_capabilities, profile_violations, requirements,
+        CallableRequirements, CallableSummary, LibrarySummary, ProfileViolation,
+        UnsupportedConstruct,
+    },
+    unresolved_callees::{
+        unresolved_calls, UnresolvedCall, UnresolvedCalleeLevel, UnresolvedCallees,
+    },
+};
 
 /// A trait to look for the compute properties of elements in a package store.
 pub trait ComputePropertiesLookup {
@@ -136,6 +158,64 @@ impl PackageStoreComputeProperties {
     pub fn iter(&self) -> Iter<PackageId, PackageComputeProperties> {
         self.0.iter()
     }
+
+    /// Finds the application generator set of the innermost expression or statement of the given package whose span
+    /// covers `span`, so that tools can explain the compute kind of the code at a source location. Expressions are
+    /// preferred over statements with the same span, and generated code without a span is never matched.
+    #[must_use]
+    pub fn find_by_span(
+        &self,
+        package_id: PackageId,
+        package: &Package,
+        span: Span,
+    ) -> Option<&ApplicationGeneratorSet> {
+        let package_compute_properties = self.0.get(package_id)?;
+        let covers = |element_span: Span| {
+            element_span != Span::default()
+                && element_span.lo <= span.lo
+                && span.hi <= element_span.hi
+        };
+        let exprs = package.exprs.iter().filter_map(|(id, expr)| {
+            covers(expr.span)
+                .then(|| package_compute_properties.exprs.get(id))
+                .flatten()
+                .map(|set| (expr.span, set))
+        });
+        let stmts = package.stmts.iter().filter_map(|(id, stmt)| {
+            covers(stmt.span)
+                .then(|| package_compute_properties.stmts.get(id))
+                .flatten()
+                .map(|set| (stmt.span, set))
+        });
+
+        // The minimum is the first of the narrowest spans, which is an expression if there is one.
+        exprs
+            .chain(stmts)
+            .min_by_key(|(element_span, _)| element_span.hi - element_span.lo)
+            .map(|(_, set)| set)
+    }
+}
+
+/// The compute properties of an application of an entry point, in which its arguments have specific value kinds.
+#[derive(Clone, Debug)]
+pub struct EntryPointComputeProperties {
+    /// The compute properties of the package store.
+    pub package_store: PackageStoreComputeProperties,
+    /// The compute kind of the entry point application.
+    pub compute_kind: ComputeKind,
+}
+
+impl EntryPointComputeProperties {
+    /// The minimal runtime capabilities that a target needs to run the entry point application.
+    #[must_use]
+    pub fn runtime_capabilities(&self) -> RuntimeCapabilityFlags {
+        match self.compute_kind {
+            ComputeKind::Classical => RuntimeCapabilityFlags::empty(),
+            ComputeKind::Quantum(quantum_properties) => {
+                quantum_properties.runtime_features.runtime_capabilities()
+            }
+        }
+    }
 }
 
 /// The compute properties of a package.
@@ -752,6 +832,10 @@ bitflags! {
         const LoopWithDynamicCondition = 1 << 22;
         /// Use of a closure.
         const UseOfClosure = 1 << 23;
+        /// A call to a custom intrinsic that only targets with unrestricted capabilities can run.
+        const CallToUnrestrictedIntrinsic = 1 << 24;
+        /// Borrowing of a qubit that may be in any state.
+        const UseOfBorrowedQubit = 1 << 25;
     }
 }
 
@@ -848,6 +932,12 @@ impl RuntimeFeatureFlags {
         if self.contains(RuntimeFeatureFlags::UseOfClosure) {
             runtume_capabilities |= RuntimeCapabilityFlags::HigherLevelConstructs;
         }
+        if self.contains(RuntimeFeatureFlags::CallToUnrestrictedIntrinsic) {
+            runtume_capabilities |= RuntimeCapabilityFlags::HigherLevelConstructs;
+        }
+        if self.contains(RuntimeFeatureFlags::UseOfBorrowedQubit) {
+            runtume_capabilities |= RuntimeCapabilityFlags::HigherLevelConstructs;
+        }
         runtume_capabilities
     }
 }
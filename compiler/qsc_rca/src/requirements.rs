@@ -0,0 +1,436 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Requirements summarize the results of the runtime capabilities analysis per callable, so that tools such as the
+//! playground can tell which target profiles a program is compatible with, and point at the constructs that need
+//! capabilities a target lacks.
+
+use crate::{
+    ComputeKind, ComputePropertiesLookup, PackageStoreComputeProperties, RuntimeFeatureFlags,
+};
+use miette::{Diagnostic, LabeledSpan, Severity};
+use qsc_data_structures::span::Span;
+use qsc_fir::{
+    fir::{
+        Block, BlockId, CallableImpl, Expr, ExprId, ItemKind, Package, PackageId, PackageLookup,
+        PackageStore, Pat, PatId, SpecDecl, SpecImpl, Stmt, StmtId, TargetProfile, Visibility,
+    },
+    visit::{self, Visitor},
+};
+use qsc_frontend::compile::RuntimeCapabilityFlags;
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+};
+
+/// The runtime capabilities a callable needs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallableRequirements {
+    pub name: Arc<str>,
+    /// The span of the name of the callable.
+    pub span: Span,
+    /// The capabilities needed by any of the specializations of the callable, when called with static arguments.
+    pub capabilities: RuntimeCapabilityFlags,
+    /// The constructs in the callable that need capabilities the target doesn't have, ordered by span.
+    pub unsupported: Vec<UnsupportedConstruct>,
+}
+
+/// A program element that needs capabilities the target doesn't have.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsupportedConstruct {
+    pub span: Span,
+    /// The capabilities the construct needs that the target doesn't have.
+    pub capabilities: RuntimeCapabilityFlags,
+}
+
+/// Unsupported constructs are reported as warnings, since the code is valid Q# that the target can't run.
+impl Diagnostic for UnsupportedConstruct {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new("Qsc.Capabilities.UnsupportedConstruct"))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(Severity::Warning)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let labeled_span = LabeledSpan::new_with_span(None, self.span);
+        Some(Box::new(std::iter::once(labeled_span)))
+    }
+}
+
+impl Display for UnsupportedConstruct {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "this construct needs runtime capabilities the target doesn't support: {}",
+            FlagNames(self.capabilities)
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedConstruct {}
+
+/// A construct in a callable with the `@TargetProfile` attribute that needs capabilities the declared profile doesn't
+/// have.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProfileViolation {
+    /// The name of the callable that declares the profile.
+    pub callable: String,
+    pub span: Span,
+    /// The capabilities the construct needs that the declared profile doesn't have.
+    pub capabilities: RuntimeCapabilityFlags,
+}
+
+/// Profile violations are errors, since the callable declares that it runs on targets with the profile.
+impl Diagnostic for ProfileViolation {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new("Qsc.Capabilities.TargetProfileViolation"))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!(
+            "`{}` declares the Base profile with the `@TargetProfile` attribute",
+            self.callable
+        )))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let labeled_span = LabeledSpan::new_with_span(None, self.span);
+        Some(Box::new(std::iter::once(labeled_span)))
+    }
+}
+
+impl Display for ProfileViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "this construct needs runtime capabilities the declared target profile doesn't support: {}",
+            FlagNames(self.capabilities)
+        )
+    }
+}
+
+impl std::error::Error for ProfileViolation {}
+
+/// The runtime capabilities needed by the public callables of a library package, so that library authors can document
+/// and check which targets the library supports.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LibrarySummary {
+    /// The capabilities needed by any of the public callables.
+    pub capabilities: RuntimeCapabilityFlags,
+    /// The public callables, ordered by their namespace-qualified names.
+    pub callables: Vec<CallableSummary>,
+}
+
+/// The runtime capabilities a public callable of a library needs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallableSummary {
+    /// The namespace-qualified name of the callable.
+    pub name: Arc<str>,
+    /// The capabilities needed by any of the specializations of the callable, when called with static arguments.
+    pub capabilities: RuntimeCapabilityFlags,
+}
+
+impl Display for LibrarySummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "capabilities: {}", FlagNames(self.capabilities))?;
+        for callable in &self.callables {
+            writeln!(f, "{}: {}", callable.name, FlagNames(callable.capabilities))?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct FlagNames(pub(crate) RuntimeCapabilityFlags);
+
+impl Display for FlagNames {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "none");
+        }
+        let names = self
+            .0
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        write!(f, "{}", names.join(" | "))
+    }
+}
+
+/// Produces the capability summary of the public callables declared in the source of the given package. Callables
+/// declared inside other callables aren't part of the public interface of the library, so they are skipped.
+#[must_use]
+pub fn library_summary(
+    package_store: &PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    package_id: PackageId,
+) -> LibrarySummary {
+    let package = package_store.get(package_id);
+    let mut callables = Vec::new();
+    for item in package.items.values() {
+        let ItemKind::Callable(decl) = &item.kind else {
+            continue;
+        };
+        let CallableImpl::Spec(spec_impl) = &decl.implementation else {
+            continue;
+        };
+        if item.visibility != Visibility::Public || decl.name.span == Span::default() {
+            continue;
+        }
+        let name = match item.parent.map(|parent| &package.get_item(parent).kind) {
+            None => decl.name.name.clone(),
+            Some(ItemKind::Namespace(namespace, _)) => {
+                format!("{}.{}", namespace.name, decl.name.name).into()
+            }
+            Some(_) => continue,
+        };
+        let (capabilities, _) = analyze_callable(
+            compute_properties,
+            package_id,
+            package,
+            spec_impl,
+            RuntimeCapabilityFlags::all(),
+        );
+        callables.push(CallableSummary { name, capabilities });
+    }
+
+    callables.sort_by(|a, b| a.name.cmp(&b.name));
+    LibrarySummary {
+        capabilities: callables
+            .iter()
+            .fold(RuntimeCapabilityFlags::empty(), |capabilities, callable| {
+                capabilities | callable.capabilities
+            }),
+        callables,
+    }
+}
+
+/// Produces the requirements of the callables declared in the source of the given package, ordered by span. Only the
+/// constructs that need capabilities beyond `target_capabilities` are reported as unsupported.
+#[must_use]
+pub fn requirements(
+    package_store: &PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    package_id: PackageId,
+    target_capabilities: RuntimeCapabilityFlags,
+) -> Vec<CallableRequirements> {
+    let package = package_store.get(package_id);
+    let mut requirements = Vec::new();
+    for item in package.items.values() {
+        let ItemKind::Callable(decl) = &item.kind else {
+            continue;
+        };
+        let CallableImpl::Spec(spec_impl) = &decl.implementation else {
+            continue;
+        };
+        // Lambdas are lifted into callables without a name in source, their constructs belong to the callable that
+        // declares them.
+        if decl.name.span == Span::default() {
+            continue;
+        }
+
+        let (capabilities, unsupported) = analyze_callable(
+            compute_properties,
+            package_id,
+            package,
+            spec_impl,
+            target_capabilities,
+        );
+        requirements.push(CallableRequirements {
+            name: decl.name.name.clone(),
+            span: decl.name.span,
+            capabilities,
+            unsupported,
+        });
+    }
+
+    requirements.sort_by_key(|requirements| requirements.span.lo);
+    requirements
+}
+
+/// Produces the constructs in the callables of the given package that need capabilities beyond the profile the callable
+/// declares with the `@TargetProfile` attribute, ordered by span.
+#[must_use]
+pub fn profile_violations(
+    package_store: &PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    package_id: PackageId,
+) -> Vec<ProfileViolation> {
+    let package = package_store.get(package_id);
+    let mut violations = Vec::new();
+    for item in package.items.values() {
+        let ItemKind::Callable(decl) = &item.kind else {
+            continue;
+        };
+        let CallableImpl::Spec(spec_impl) = &decl.implementation else {
+            continue;
+        };
+        // Every construct is supported by targets with all the capabilities.
+        if item.target_profile() != Some(TargetProfile::Base) {
+            continue;
+        }
+
+        let (_, unsupported) = analyze_callable(
+            compute_properties,
+            package_id,
+            package,
+            spec_impl,
+            RuntimeCapabilityFlags::empty(),
+        );
+        violations.extend(unsupported.into_iter().map(|construct| ProfileViolation {
+            callable: decl.name.name.to_string(),
+            span: construct.span,
+            capabilities: construct.capabilities,
+        }));
+    }
+
+    violations.sort_by_key(|violation| (violation.span.lo, violation.span.hi));
+    violations
+}
+
+/// Produces the capabilities needed by the specializations of a callable, and the constructs in them that need
+/// capabilities beyond `target_capabilities`, ordered by span.
+fn analyze_callable(
+    compute_properties: &PackageStoreComputeProperties,
+    package_id: PackageId,
+    package: &Package,
+    spec_impl: &SpecImpl,
+    target_capabilities: RuntimeCapabilityFlags,
+) -> (RuntimeCapabilityFlags, Vec<UnsupportedConstruct>) {
+    let mut collector = UnsupportedCollector {
+        compute_properties,
+        package_id,
+        package,
+        target_capabilities,
+        features: Vec::new(),
+        unsupported: Vec::new(),
+        capabilities: RuntimeCapabilityFlags::empty(),
+    };
+    for spec in [
+        Some(&spec_impl.body),
+        spec_impl.adj.as_ref(),
+        spec_impl.ctl.as_ref(),
+        spec_impl.ctl_adj.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        collector.visit_spec(spec);
+    }
+
+    // Generated specializations reuse the spans of the code they are generated from.
+    let mut unsupported = collector.unsupported;
+    unsupported.sort_by_key(|construct| (construct.span.lo, construct.span.hi));
+    unsupported.dedup();
+    (collector.capabilities, unsupported)
+}
+
+/// Produces the minimal runtime capabilities needed to run the given package, which are the capabilities needed by any
+/// of the callables declared in its source or by its entry expression.
+#[must_use]
+pub fn package_capabilities(
+    package_store: &PackageStore,
+    compute_properties: &PackageStoreComputeProperties,
+    package_id: PackageId,
+) -> RuntimeCapabilityFlags {
+    let mut capabilities = requirements(
+        package_store,
+        compute_properties,
+        package_id,
+        RuntimeCapabilityFlags::all(),
+    )
+    .into_iter()
+    .fold(RuntimeCapabilityFlags::empty(), |capabilities, callable| {
+        capabilities | callable.capabilities
+    });
+
+    if let Some(entry) = package_store.get(package_id).entry {
+        if let Some(ComputeKind::Quantum(properties)) = compute_properties
+            .find_expr((package_id, entry).into())
+            .map(|set| set.inherent)
+        {
+            capabilities |= properties.runtime_features.runtime_capabilities();
+        }
+    }
+    capabilities
+}
+
+struct UnsupportedCollector<'a> {
+    compute_properties: &'a PackageStoreComputeProperties,
+    package_id: PackageId,
+    package: &'a Package,
+    target_capabilities: RuntimeCapabilityFlags,
+    /// The runtime features used by the children of the expressions being visited.
+    features: Vec<RuntimeFeatureFlags>,
+    unsupported: Vec<UnsupportedConstruct>,
+    capabilities: RuntimeCapabilityFlags,
+}
+
+impl UnsupportedCollector<'_> {
+    fn visit_spec(&mut self, spec: &SpecDecl) {
+        let block_id = spec.block;
+        if let Some(ComputeKind::Quantum(properties)) = self
+            .compute_properties
+            .find_block((self.package_id, block_id).into())
+            .map(|set| set.inherent)
+        {
+            self.capabilities |= properties.runtime_features.runtime_capabilities();
+        }
+        self.visit_block(block_id);
+    }
+
+    fn runtime_features(&self, expr_id: ExprId) -> RuntimeFeatureFlags {
+        match self
+            .compute_properties
+            .find_expr((self.package_id, expr_id).into())
+            .map(|set| set.inherent)
+        {
+            Some(ComputeKind::Quantum(properties)) => properties.runtime_features,
+            _ => RuntimeFeatureFlags::empty(),
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for UnsupportedCollector<'a> {
+    fn visit_expr(&mut self, expr_id: ExprId) {
+        self.features.push(RuntimeFeatureFlags::empty());
+        visit::walk_expr(self, expr_id);
+        let children = self
+            .features
+            .pop()
+            .expect("features of the children should be on the stack");
+
+        // An expression is only reported for the features it introduces, so that the parents of an unsupported
+        // construct aren't reported too.
+        let features = self.runtime_features(expr_id);
+        let missing = (features - children).runtime_capabilities() - self.target_capabilities;
+        let span = self.get_expr(expr_id).span;
+        if !missing.is_empty() && span != Span::default() {
+            self.unsupported.push(UnsupportedConstruct {
+                span,
+                capabilities: missing,
+            });
+        }
+
+        if let Some(parent) = self.features.last_mut() {
+            *parent |= features;
+        }
+    }
+
+    fn get_block(&self, id: BlockId) -> &'a Block {
+        self.package.get_block(id)
+    }
+
+    fn get_expr(&self, id: ExprId) -> &'a Expr {
+        self.package.get_expr(id)
+    }
+
+    fn get_pat(&self, id: PatId) -> &'a Pat {
+        self.package.get_pat(id)
+    }
+
+    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
+        self.package.get_stmt(id)
+    }
+}
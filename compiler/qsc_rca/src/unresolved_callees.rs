@@ -0,0 +1,285 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! The analysis can't tell which callable a call runs when the callee is a value, such as a callable passed in as an
+//! argument, so by default such calls need the `HigherLevelConstructs` capability. Library authors that use
+//! first-class callables can instead have these calls reported, or assume the capabilities the callees need.
+
+use crate::RuntimeFeatureFlags;
+use miette::{Diagnostic, LabeledSpan, Severity};
+use qsc_data_structures::span::Span;
+use qsc_fir::{
+    fir::{
+        Block, BlockId, Expr, ExprId, ExprKind, ItemKind, LocalVarId, Mutability, Package,
+        PackageId, PackageLookup, PackageStore, Pat, PatId, PatKind, Res, Stmt, StmtId, StmtKind,
+        UnOp,
+    },
+    visit::{walk_expr, walk_pat, walk_stmt, Visitor},
+};
+use qsc_frontend::compile::RuntimeCapabilityFlags;
+use rustc_hash::FxHashMap;
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+};
+
+/// How the analysis treats calls to callees it can't resolve.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UnresolvedCallees {
+    /// How the calls to callees without an assumption are reported by [`unresolved_calls`].
+    pub level: UnresolvedCalleeLevel,
+    /// The capabilities assumed for callees, by the name of the parameter or variable they are called through, such as
+    /// `op` in `op(q)`. Calls to these callees need the assumed capabilities instead of `HigherLevelConstructs`, and
+    /// aren't reported.
+    pub assumptions: FxHashMap<Arc<str>, RuntimeCapabilityFlags>,
+}
+
+/// How calls to unresolved callees are reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnresolvedCalleeLevel {
+    /// The calls aren't reported, they only need the `HigherLevelConstructs` capability.
+    #[default]
+    Allow,
+    /// The calls are reported as warnings.
+    Warn,
+    /// The calls are reported as errors.
+    Error,
+}
+
+impl UnresolvedCallees {
+    /// The runtime features of a call through the local with the given name, when there is an assumption for it.
+    pub(crate) fn assumed_features(&self, name: &str) -> Option<RuntimeFeatureFlags> {
+        self.assumptions.get(name).map(|capabilities| {
+            // Each capability is stood in for by a feature that needs only that capability.
+            let mut features = RuntimeFeatureFlags::empty();
+            for (capability, feature) in [
+                (
+                    RuntimeCapabilityFlags::ForwardBranching,
+                    RuntimeFeatureFlags::ForwardBranchingOnDynamicValue,
+                ),
+                (
+                    RuntimeCapabilityFlags::IntegerComputations,
+                    RuntimeFeatureFlags::UseOfDynamicInt,
+                ),
+                (
+                    RuntimeCapabilityFlags::FloatingPointComputations,
+                    RuntimeFeatureFlags::UseOfDynamicDouble,
+                ),
+                (
+                    RuntimeCapabilityFlags::BackwardsBranching,
+                    RuntimeFeatureFlags::LoopWithDynamicCondition,
+                ),
+                (
+                    RuntimeCapabilityFlags::HigherLevelConstructs,
+                    RuntimeFeatureFlags::CallToDynamicCallee,
+                ),
+            ] {
+                if capabilities.contains(capability) {
+                    features |= feature;
+                }
+            }
+            features
+        })
+    }
+}
+
+/// A call to a callee that the analysis couldn't resolve and has no assumption for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnresolvedCall {
+    pub span: Span,
+    pub level: UnresolvedCalleeLevel,
+}
+
+impl Diagnostic for UnresolvedCall {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new("Qsc.Capabilities.UnresolvedCallee"))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        match self.level {
+            UnresolvedCalleeLevel::Error => Some(Severity::Error),
+            UnresolvedCalleeLevel::Allow | UnresolvedCalleeLevel::Warn => Some(Severity::Warning),
+        }
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(
+            "call a specific callable, or assume the capabilities of the callee",
+        ))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let labeled_span = LabeledSpan::new_with_span(None, self.span);
+        Some(Box::new(std::iter::once(labeled_span)))
+    }
+}
+
+impl Display for UnresolvedCall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the callee can't be resolved, so this call needs higher-level constructs"
+        )
+    }
+}
+
+impl std::error::Error for UnresolvedCall {}
+
+/// Produces the calls in the given package to callees that can't be resolved and have no assumption, ordered by span.
+/// Callees are resolved like the analysis does, through immutable bindings to global callables and functors applied
+/// to them. Nothing is reported at [`UnresolvedCalleeLevel::Allow`].
+#[must_use]
+pub fn unresolved_calls(
+    package_store: &PackageStore,
+    package_id: PackageId,
+    unresolved_callees: &UnresolvedCallees,
+) -> Vec<UnresolvedCall> {
+    if unresolved_callees.level == UnresolvedCalleeLevel::Allow {
+        return Vec::new();
+    }
+
+    // Local ids restart for each callable, so the locals of each callable and of the entry expression are looked at
+    // separately.
+    let package = package_store.get(package_id);
+    let mut calls = Vec::new();
+    for item in package.items.values() {
+        if let ItemKind::Callable(decl) = &item.kind {
+            let mut locals = Locals::new(package);
+            locals.visit_callable_decl(decl);
+            locals.report_unresolved_calls(unresolved_callees, &mut calls);
+        }
+    }
+    if let Some(entry) = package.entry {
+        let mut locals = Locals::new(package);
+        locals.visit_expr(entry);
+        locals.report_unresolved_calls(unresolved_callees, &mut calls);
+    }
+
+    // Generated specializations reuse the spans of the code they are generated from.
+    calls.sort_by_key(|call| (call.span.lo, call.span.hi));
+    calls.dedup();
+    calls
+}
+
+struct Locals<'a> {
+    package: &'a Package,
+    names: FxHashMap<LocalVarId, Arc<str>>,
+    /// The expressions that immutable locals are bound to.
+    bindings: FxHashMap<LocalVarId, ExprId>,
+    calls: Vec<ExprId>,
+}
+
+impl<'a> Locals<'a> {
+    fn new(package: &'a Package) -> Self {
+        Self {
+            package,
+            names: FxHashMap::default(),
+            bindings: FxHashMap::default(),
+            calls: Vec::new(),
+        }
+    }
+
+    fn report_unresolved_calls(
+        &self,
+        unresolved_callees: &UnresolvedCallees,
+        calls: &mut Vec<UnresolvedCall>,
+    ) {
+        for expr_id in &self.calls {
+            let expr = self.package.get_expr(*expr_id);
+            let ExprKind::Call(callee, _) = &expr.kind else {
+                continue;
+            };
+            if expr.span == Span::default() || self.resolves(*callee) {
+                continue;
+            }
+            if let ExprKind::Var(Res::Local(local_var_id), _) = &self.package.get_expr(*callee).kind
+            {
+                if self
+                    .names
+                    .get(local_var_id)
+                    .is_some_and(|name| unresolved_callees.assumptions.contains_key(name))
+                {
+                    continue;
+                }
+            }
+            calls.push(UnresolvedCall {
+                span: expr.span,
+                level: unresolved_callees.level,
+            });
+        }
+    }
+
+    fn bind(&mut self, pat_id: PatId, expr_id: ExprId) {
+        match &self.package.get_pat(pat_id).kind {
+            PatKind::Bind(ident) => {
+                self.bindings.insert(ident.id, expr_id);
+            }
+            PatKind::Tuple(pats) => match &self.package.get_expr(expr_id).kind {
+                ExprKind::Tuple(exprs) => {
+                    for (pat_id, expr_id) in pats.iter().zip(exprs) {
+                        self.bind(*pat_id, *expr_id);
+                    }
+                }
+                _ => {
+                    for pat_id in pats {
+                        self.bind(*pat_id, expr_id);
+                    }
+                }
+            },
+            PatKind::Discard => {}
+        }
+    }
+
+    fn resolves(&self, expr_id: ExprId) -> bool {
+        match &self.package.get_expr(expr_id).kind {
+            ExprKind::UnOp(UnOp::Functor(_), operand) => self.resolves(*operand),
+            ExprKind::Var(Res::Item(_), _) => true,
+            ExprKind::Var(Res::Local(local_var_id), _) => self
+                .bindings
+                .get(local_var_id)
+                .is_some_and(|expr_id| self.resolves(*expr_id)),
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Locals<'a> {
+    fn visit_pat(&mut self, pat_id: PatId) {
+        if let PatKind::Bind(ident) = &self.package.get_pat(pat_id).kind {
+            self.names.insert(ident.id, ident.name.clone());
+        }
+        walk_pat(self, pat_id);
+    }
+
+    fn visit_stmt(&mut self, stmt_id: StmtId) {
+        if let StmtKind::Local(Mutability::Immutable, pat_id, expr_id) =
+            &self.package.get_stmt(stmt_id).kind
+        {
+            self.bind(*pat_id, *expr_id);
+        }
+        walk_stmt(self, stmt_id);
+    }
+
+    fn visit_expr(&mut self, expr_id: ExprId) {
+        if matches!(self.package.get_expr(expr_id).kind, ExprKind::Call(..)) {
+            self.calls.push(expr_id);
+        }
+        walk_expr(self, expr_id);
+    }
+
+    fn get_block(&self, id: BlockId) -> &'a Block {
+        self.package.get_block(id)
+    }
+
+    fn get_expr(&self, id: ExprId) -> &'a Expr {
+        self.package.get_expr(id)
+    }
+
+    fn get_pat(&self, id: PatId) -> &'a Pat {
+        self.package.get_pat(id)
+    }
+
+    fn get_stmt(&self, id: StmtId) -> &'a Stmt {
+        self.package.get_stmt(id)
+    }
+}
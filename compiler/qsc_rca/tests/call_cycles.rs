@@ -0,0 +1,87 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! These tests check the call cycles reported to users.
+
+pub mod test_utils;
+
+use qsc_eval::debug::map_hir_package_to_fir;
+use qsc_rca::{call_cycles, CallCycle};
+use test_utils::CompilationContext;
+
+fn cycles(source: &str) -> Vec<CallCycle> {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(source);
+    call_cycles(
+        &compilation_context.fir_store,
+        map_hir_package_to_fir(compilation_context.compiler.package_id()),
+    )
+}
+
+fn names(cycle: &CallCycle) -> Vec<&str> {
+    cycle.callables.iter().map(|(name, _)| &**name).collect()
+}
+
+#[test]
+fn mutually_recursive_operations_are_one_cycle() {
+    let cycles = cycles(
+        r#"
+        operation Ping(n : Int) : Unit {
+            if n > 0 {
+                Pong(n - 1);
+            }
+        }
+        operation Pong(n : Int) : Unit {
+            Ping(n);
+        }
+        operation Main() : Unit {
+            Pong(3);
+        }"#,
+    );
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(names(&cycles[0]), ["Ping", "Pong"]);
+    assert!(cycles[0].has_operation);
+    assert_eq!(
+        cycles[0].to_string(),
+        "callables call each other in a cycle: Ping -> Pong -> Ping"
+    );
+}
+
+#[test]
+fn recursive_function_is_a_cycle_without_operations() {
+    let cycles = cycles(
+        r#"
+        function Fact(n : Int) : Int {
+            if n <= 1 { 1 } else { n * Fact(n - 1) }
+        }"#,
+    );
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(names(&cycles[0]), ["Fact"]);
+    assert!(!cycles[0].has_operation);
+}
+
+#[test]
+fn spans_point_at_the_names_of_the_callables() {
+    let source = r#"
+        operation Foo() : Unit {
+            Foo();
+        }"#;
+    let cycles = cycles(source);
+    let (_, span) = &cycles[0].callables[0];
+    let lo = u32::try_from(source.find("Foo").expect("name should be in the source"))
+        .expect("offset should fit into u32");
+    assert_eq!(span.lo, lo);
+}
+
+#[test]
+fn callables_without_cycles_are_not_reported() {
+    let cycles = cycles(
+        r#"
+        operation Foo() : Unit {}
+        operation Bar() : Unit {
+            Foo();
+            Foo();
+        }"#,
+    );
+    assert!(cycles.is_empty());
+}
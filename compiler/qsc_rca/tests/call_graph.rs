@@ -0,0 +1,113 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! These tests check the call graph annotated with the results of the analysis.
+
+pub mod test_utils;
+
+use qsc_eval::debug::map_hir_package_to_fir;
+use qsc_frontend::compile::RuntimeCapabilityFlags;
+use qsc_rca::{call_graph, CallGraph};
+use test_utils::CompilationContext;
+
+const SOURCE: &str = r#"
+    namespace Test {
+        operation Helper(q : Qubit) : Int {
+            mutable n = 0;
+            if M(q) == One {
+                set n = 1;
+            }
+            n
+        }
+
+        operation Main() : Unit {
+            use q = Qubit();
+            let n = Helper(q);
+            ApplyToEach(H, [q]);
+        }
+    }"#;
+
+fn graph() -> CallGraph {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(SOURCE);
+    call_graph(
+        &compilation_context.fir_store,
+        compilation_context.get_compute_properties(),
+        map_hir_package_to_fir(compilation_context.compiler.package_id()),
+    )
+}
+
+fn index(graph: &CallGraph, name: &str) -> usize {
+    graph
+        .nodes
+        .iter()
+        .position(|node| &*node.name == name)
+        .expect("callable should be in the graph")
+}
+
+#[test]
+fn callables_of_the_package_come_first_in_source_order() {
+    let graph = graph();
+    assert_eq!(&*graph.nodes[0].name, "Test.Helper");
+    assert_eq!(&*graph.nodes[1].name, "Test.Main");
+}
+
+#[test]
+fn callables_passed_as_arguments_are_referenced() {
+    let graph = graph();
+    let main = index(&graph, "Test.Main");
+    for callee in [
+        "Test.Helper",
+        "Microsoft.Quantum.Canon.ApplyToEach",
+        "Microsoft.Quantum.Intrinsic.H",
+    ] {
+        let callee = index(&graph, callee);
+        assert!(graph
+            .edges
+            .iter()
+            .any(|edge| edge.caller == main && edge.callee == callee));
+    }
+}
+
+#[test]
+fn edges_have_the_spans_of_the_references() {
+    let graph = graph();
+    let main = index(&graph, "Test.Main");
+    let helper = index(&graph, "Test.Helper");
+    let edge = graph
+        .edges
+        .iter()
+        .find(|edge| edge.caller == main && edge.callee == helper)
+        .expect("main should refer to helper");
+    assert_eq!(edge.spans.len(), 1);
+    assert_eq!(edge.spans[0].hi - edge.spans[0].lo, 6);
+    assert!(edge.spans[0].lo > graph.nodes[main].span.hi);
+}
+
+#[test]
+fn capabilities_are_introduced_where_they_enter() {
+    let graph = graph();
+    let helper = &graph.nodes[index(&graph, "Test.Helper")];
+    assert!(helper
+        .introduced
+        .contains(RuntimeCapabilityFlags::IntegerComputations));
+    let main = &graph.nodes[index(&graph, "Test.Main")];
+    assert!(main
+        .capabilities
+        .contains(RuntimeCapabilityFlags::IntegerComputations));
+    assert!(!main
+        .introduced
+        .contains(RuntimeCapabilityFlags::IntegerComputations));
+}
+
+#[test]
+fn dot_fills_callables_that_introduce_capabilities() {
+    let graph = graph();
+    let dot = graph.to_dot();
+    let helper = index(&graph, "Test.Helper");
+    assert!(dot.starts_with("digraph calls {\n"));
+    assert!(dot.contains(&format!(
+        "    n{helper} [label=\"Test.Helper\\n{}\", style=filled];",
+        "ForwardBranching | IntegerComputations"
+    )));
+}
@@ -0,0 +1,105 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! These tests check the analysis of entry points with arguments of specific value kinds.
+
+pub mod test_utils;
+
+use qsc_frontend::compile::RuntimeCapabilityFlags;
+use qsc_rca::{
+    Analyzer, ComputeKind, ComputePropertiesLookup, EntryPointComputeProperties,
+    ItemComputeProperties, RuntimeFeatureFlags, RuntimeKind, ValueKind,
+};
+use test_utils::{CompilationContext, PackageStoreSearch};
+
+const SOURCE: &str = r#"
+    namespace Test {
+        operation Main(n : Int, b : Bool) : Unit {
+            use q = Qubit();
+            if n > 0 {
+                X(q);
+            }
+            if b {
+                H(q);
+            }
+        }
+    }"#;
+
+fn analyze_main(args_value_kinds: &[ValueKind]) -> EntryPointComputeProperties {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(SOURCE);
+    let id = compilation_context
+        .fir_store
+        .find_callable_id_by_name("Main")
+        .expect("callable should exist");
+    Analyzer::init(&compilation_context.fir_store).analyze_entry_point(id, args_value_kinds)
+}
+
+#[test]
+fn static_arguments_need_no_capabilities() {
+    let entry_point = analyze_main(&[
+        ValueKind::Element(RuntimeKind::Static),
+        ValueKind::Element(RuntimeKind::Static),
+    ]);
+    assert_eq!(
+        entry_point.runtime_capabilities(),
+        RuntimeCapabilityFlags::empty()
+    );
+}
+
+#[test]
+fn dynamic_bool_argument_needs_forward_branching_only() {
+    let entry_point = analyze_main(&[
+        ValueKind::Element(RuntimeKind::Static),
+        ValueKind::Element(RuntimeKind::Dynamic),
+    ]);
+    assert_eq!(
+        entry_point.runtime_capabilities(),
+        RuntimeCapabilityFlags::ForwardBranching
+    );
+}
+
+#[test]
+fn dynamic_int_argument_needs_integer_computations() {
+    let entry_point = analyze_main(&[
+        ValueKind::Element(RuntimeKind::Dynamic),
+        ValueKind::Element(RuntimeKind::Static),
+    ]);
+    assert!(entry_point.runtime_capabilities().contains(
+        RuntimeCapabilityFlags::ForwardBranching | RuntimeCapabilityFlags::IntegerComputations
+    ));
+}
+
+#[test]
+fn entry_point_application_matches_generated_application() {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(SOURCE);
+    let id = compilation_context
+        .fir_store
+        .find_callable_id_by_name("Main")
+        .expect("callable should exist");
+    let args_value_kinds = [
+        ValueKind::Element(RuntimeKind::Dynamic),
+        ValueKind::Element(RuntimeKind::Dynamic),
+    ];
+    let entry_point =
+        Analyzer::init(&compilation_context.fir_store).analyze_entry_point(id, &args_value_kinds);
+    let ItemComputeProperties::Callable(callable) = entry_point.package_store.get_item(id) else {
+        panic!("item should be a callable");
+    };
+    let features = |compute_kind: ComputeKind| match compute_kind {
+        ComputeKind::Quantum(properties) => properties.runtime_features,
+        ComputeKind::Classical => RuntimeFeatureFlags::empty(),
+    };
+    // The entry point application also needs the features of its dynamic arguments, which no call expression
+    // contributes.
+    assert_eq!(
+        features(entry_point.compute_kind),
+        features(
+            callable
+                .body
+                .generate_application_compute_kind(&args_value_kinds)
+        ) | RuntimeFeatureFlags::UseOfDynamicInt
+            | RuntimeFeatureFlags::UseOfDynamicBool
+    );
+}
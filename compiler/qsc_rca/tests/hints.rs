@@ -0,0 +1,106 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! These tests check the hints produced from the RCA results for the bindings and calls in a package.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+pub mod test_utils;
+
+use expect_test::{expect, Expect};
+use qsc_eval::debug::map_hir_package_to_fir;
+use qsc_rca::hints;
+use std::fmt::Write;
+use test_utils::CompilationContext;
+
+fn check_hints(source: &str, expect: &Expect) {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(source);
+    let package_id = map_hir_package_to_fir(compilation_context.compiler.package_id());
+    let mut actual = String::new();
+    for hint in hints(
+        &compilation_context.fir_store,
+        compilation_context.get_compute_properties(),
+        package_id,
+    ) {
+        let _ = writeln!(
+            actual,
+            "{:?} [{}-{}]: {}",
+            hint.kind, hint.span.lo, hint.span.hi, hint.label
+        );
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn static_program_has_no_hints() {
+    check_hints(
+        r#"
+        namespace Test {
+            operation Foo() : Unit {
+                use q = Qubit();
+                let n = 3;
+                for _ in 1..n {
+                    H(q);
+                }
+            }
+        }"#,
+        &expect![""],
+    );
+}
+
+#[test]
+fn generated_bindings_have_no_hints() {
+    check_hints(
+        r#"
+        namespace Test {
+            operation Foo() : Result {
+                use q = Qubit();
+                MResetZ(q)
+            }
+        }"#,
+        &expect![""],
+    );
+}
+
+#[test]
+fn bindings_to_dynamic_values_have_hints() {
+    check_hints(
+        r#"
+        namespace Test {
+            operation Foo() : Unit {
+                use q = Qubit();
+                let r = M(q);
+                let (isOne, n) = (r == One, 1);
+                let count = isOne ? 1 | 0;
+            }
+        }"#,
+        &expect![[r#"
+            DynamicBinding [116-117]: dynamic Result
+            DynamicBinding [147-152]: dynamic Bool
+            DynamicBinding [194-199]: dynamic Int
+        "#]],
+    );
+}
+
+#[test]
+fn calls_that_need_capabilities_have_hints() {
+    check_hints(
+        r#"
+        namespace Test {
+            operation Foo() : Unit {
+                use q = Qubit();
+                Bar(M(q) == One);
+            }
+            operation Bar(b : Bool) : Unit {
+                use q = Qubit();
+                if b {
+                    X(q);
+                }
+            }
+        }"#,
+        &expect![[r#"
+            RequiredCapabilities [112-128]: requires ForwardBranching
+        "#]],
+    );
+}
@@ -55,6 +55,28 @@ fn check_rca_for_quantum_rt_qubit_release() {
     );
 }
 
+#[test]
+fn check_rca_for_quantum_rt_qubit_borrow() {
+    let compilation_context = CompilationContext::new();
+    check_callable_compute_properties(
+        &compilation_context.fir_store,
+        compilation_context.get_compute_properties(),
+        "__quantum__rt__qubit_borrow",
+        &expect![
+            r#"
+            Callable: CallableComputeProperties:
+                body: ApplicationsGeneratorSet:
+                    inherent: Quantum: QuantumProperties:
+                        runtime_features: RuntimeFeatureFlags(UseOfBorrowedQubit)
+                        value_kind: Element(Static)
+                    dynamic_param_applications: <empty>
+                adj: <none>
+                ctl: <none>
+                ctl-adj: <none>"#
+        ],
+    );
+}
+
 #[test]
 fn check_rca_for_quantum_qis_m_body() {
     let compilation_context = CompilationContext::new();
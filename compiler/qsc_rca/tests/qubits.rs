@@ -6,6 +6,8 @@
 pub mod test_utils;
 
 use expect_test::expect;
+use qsc_frontend::compile::RuntimeCapabilityFlags;
+use qsc_rca::RuntimeFeatureFlags;
 use test_utils::{
     check_callable_compute_properties, check_last_statement_compute_properties, CompilationContext,
 };
@@ -112,3 +114,37 @@ fn check_rca_for_dynamic_multi_qubit_allcation() {
         ],
     );
 }
+
+#[test]
+fn check_rca_for_borrowed_qubit() {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(
+        r#"
+        operation BorrowQubit() : Unit {
+            borrow q = Qubit();
+            X(q);
+            X(q);
+        }"#,
+    );
+    check_callable_compute_properties(
+        &compilation_context.fir_store,
+        compilation_context.get_compute_properties(),
+        "BorrowQubit",
+        &expect![
+            r#"
+            Callable: CallableComputeProperties:
+                body: ApplicationsGeneratorSet:
+                    inherent: Quantum: QuantumProperties:
+                        runtime_features: RuntimeFeatureFlags(UseOfBorrowedQubit)
+                        value_kind: Element(Static)
+                    dynamic_param_applications: <empty>
+                adj: <none>
+                ctl: <none>
+                ctl-adj: <none>"#
+        ],
+    );
+    assert_eq!(
+        RuntimeFeatureFlags::UseOfBorrowedQubit.runtime_capabilities(),
+        RuntimeCapabilityFlags::HigherLevelConstructs
+    );
+}
@@ -0,0 +1,287 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! These tests check the per-callable requirements produced from the RCA results for a package.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+pub mod test_utils;
+
+use expect_test::{expect, Expect};
+use qsc_eval::debug::map_hir_package_to_fir;
+use qsc_frontend::compile::RuntimeCapabilityFlags;
+use qsc_rca::{library_summary, package_capabilities, profile_violations, requirements};
+use std::fmt::Write;
+use test_utils::CompilationContext;
+
+fn check_requirements(source: &str, target: RuntimeCapabilityFlags, expect: &Expect) {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(source);
+    let package_id = map_hir_package_to_fir(compilation_context.compiler.package_id());
+    let mut actual = String::new();
+    for callable in requirements(
+        &compilation_context.fir_store,
+        compilation_context.get_compute_properties(),
+        package_id,
+        target,
+    ) {
+        let _ = writeln!(
+            actual,
+            "{} [{}-{}]: {:?}",
+            callable.name, callable.span.lo, callable.span.hi, callable.capabilities
+        );
+        for construct in callable.unsupported {
+            let _ = writeln!(
+                actual,
+                "    [{}-{}]: {:?}",
+                construct.span.lo, construct.span.hi, construct.capabilities
+            );
+        }
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn static_callables_have_no_requirements() {
+    check_requirements(
+        r#"
+        namespace Test {
+            function Square(x : Int) : Int {
+                x * x
+            }
+            operation Foo() : Result {
+                use q = Qubit();
+                H(q);
+                MResetZ(q)
+            }
+        }"#,
+        RuntimeCapabilityFlags::empty(),
+        &expect![[r#"
+            Square [47-53]: RuntimeCapabilityFlags(0x0)
+            Foo [129-132]: RuntimeCapabilityFlags(0x0)
+        "#]],
+    );
+}
+
+#[test]
+fn branching_on_measurement_is_unsupported_on_base() {
+    check_requirements(
+        r#"
+        namespace Test {
+            operation Foo() : Unit {
+                use q = Qubit();
+                if M(q) == One {
+                    X(q);
+                }
+            }
+        }"#,
+        RuntimeCapabilityFlags::empty(),
+        &expect![[r#"
+            Foo [48-51]: RuntimeCapabilityFlags(ForwardBranching)
+                [112-172]: RuntimeCapabilityFlags(ForwardBranching)
+                [115-126]: RuntimeCapabilityFlags(ForwardBranching)
+                [149-153]: RuntimeCapabilityFlags(ForwardBranching)
+        "#]],
+    );
+}
+
+#[test]
+fn supported_capabilities_are_not_reported() {
+    check_requirements(
+        r#"
+        namespace Test {
+            operation Foo() : Unit {
+                use q = Qubit();
+                if M(q) == One {
+                    X(q);
+                }
+            }
+        }"#,
+        RuntimeCapabilityFlags::all(),
+        &expect![[r#"
+            Foo [48-51]: RuntimeCapabilityFlags(ForwardBranching)
+        "#]],
+    );
+}
+
+fn check_package_capabilities(source: &str, expect: &Expect) {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(source);
+    let package_id = map_hir_package_to_fir(compilation_context.compiler.package_id());
+    let capabilities = package_capabilities(
+        &compilation_context.fir_store,
+        compilation_context.get_compute_properties(),
+        package_id,
+    );
+    expect.assert_debug_eq(&capabilities);
+}
+
+#[test]
+fn package_capabilities_combine_callables() {
+    check_package_capabilities(
+        r#"
+        namespace Test {
+            operation Foo() : Unit {
+                use q = Qubit();
+                if M(q) == One {
+                    X(q);
+                }
+            }
+            operation Bar() : Int {
+                use q = Qubit();
+                mutable i = 0;
+                if M(q) == One {
+                    set i += 1;
+                }
+                i
+            }
+        }"#,
+        &expect![[r#"
+            RuntimeCapabilityFlags(
+                ForwardBranching | IntegerComputations,
+            )
+        "#]],
+    );
+}
+
+#[test]
+fn static_package_needs_no_capabilities() {
+    check_package_capabilities(
+        r#"
+        namespace Test {
+            operation Foo() : Result {
+                use q = Qubit();
+                H(q);
+                MResetZ(q)
+            }
+        }"#,
+        &expect![[r#"
+            RuntimeCapabilityFlags(
+                0x0,
+            )
+        "#]],
+    );
+}
+
+fn check_library_summary(source: &str, expect: &Expect) {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(source);
+    let package_id = map_hir_package_to_fir(compilation_context.compiler.package_id());
+    let summary = library_summary(
+        &compilation_context.fir_store,
+        compilation_context.get_compute_properties(),
+        package_id,
+    );
+    expect.assert_eq(&summary.to_string());
+}
+
+#[test]
+fn library_summary_lists_public_callables() {
+    check_library_summary(
+        r#"
+        namespace Test {
+            function Square(x : Int) : Int {
+                x * x
+            }
+            operation Correct(q : Qubit) : Unit {
+                if M(q) == One {
+                    X(q);
+                }
+            }
+            internal operation Count() : Int {
+                use q = Qubit();
+                mutable i = 0;
+                if M(q) == One {
+                    set i += 1;
+                }
+                i
+            }
+        }"#,
+        &expect![[r#"
+            capabilities: ForwardBranching
+            Test.Correct: ForwardBranching
+            Test.Square: none
+        "#]],
+    );
+}
+
+#[test]
+fn static_library_summary_needs_no_capabilities() {
+    check_library_summary(
+        r#"
+        namespace Test {
+            operation Prepare(qs : Qubit[]) : Unit {
+                for q in qs {
+                    H(q);
+                }
+            }
+        }"#,
+        &expect![[r#"
+            capabilities: none
+            Test.Prepare: none
+        "#]],
+    );
+}
+
+fn check_profile_violations(source: &str, expect: &Expect) {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(source);
+    let package_id = map_hir_package_to_fir(compilation_context.compiler.package_id());
+    let mut actual = String::new();
+    for violation in profile_violations(
+        &compilation_context.fir_store,
+        compilation_context.get_compute_properties(),
+        package_id,
+    ) {
+        let _ = writeln!(
+            actual,
+            "{} [{}-{}]: {:?}",
+            violation.callable, violation.span.lo, violation.span.hi, violation.capabilities
+        );
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn callables_declaring_base_profile_report_violations() {
+    check_profile_violations(
+        r#"
+        namespace Test {
+            @TargetProfile("Base")
+            operation Foo() : Unit {
+                use q = Qubit();
+                if M(q) == One {
+                    X(q);
+                }
+            }
+            operation Bar() : Unit {
+                use q = Qubit();
+                if M(q) == One {
+                    X(q);
+                }
+            }
+        }"#,
+        &expect![[r#"
+            Foo [147-207]: RuntimeCapabilityFlags(ForwardBranching)
+            Foo [150-161]: RuntimeCapabilityFlags(ForwardBranching)
+            Foo [184-188]: RuntimeCapabilityFlags(ForwardBranching)
+        "#]],
+    );
+}
+
+#[test]
+fn callables_declaring_unrestricted_profile_have_no_violations() {
+    check_profile_violations(
+        r#"
+        namespace Test {
+            @TargetProfile("Unrestricted")
+            operation Foo() : Unit {
+                use q = Qubit();
+                if M(q) == One {
+                    X(q);
+                }
+            }
+        }"#,
+        &expect![[r#""#]],
+    );
+}
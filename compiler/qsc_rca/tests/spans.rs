@@ -0,0 +1,75 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! These tests check the lookup of compute properties by source span.
+
+pub mod test_utils;
+
+use qsc_data_structures::span::Span;
+use qsc_eval::debug::map_hir_package_to_fir;
+use qsc_rca::{ApplicationGeneratorSet, ComputeKind};
+use test_utils::CompilationContext;
+
+const SOURCE: &str = r#"
+    namespace Test {
+        function Double(x : Int) : Int {
+            x * 2
+        }
+
+        operation Foo() : Bool {
+            use q = Qubit();
+            let r = M(q);
+            r == One
+        }
+    }"#;
+
+fn find_by_snippet<'a>(
+    compilation_context: &'a CompilationContext,
+    snippet: &str,
+) -> Option<&'a ApplicationGeneratorSet> {
+    let lo = u32::try_from(
+        SOURCE
+            .find(snippet)
+            .expect("snippet should be in the source"),
+    )
+    .expect("offset should fit into u32");
+    let hi = lo + u32::try_from(snippet.len()).expect("length should fit into u32");
+    let package_id = map_hir_package_to_fir(compilation_context.compiler.package_id());
+    compilation_context.get_compute_properties().find_by_span(
+        package_id,
+        compilation_context.fir_store.get(package_id),
+        Span { lo, hi },
+    )
+}
+
+#[test]
+fn classical_expression_found_by_span() {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(SOURCE);
+    let set = find_by_snippet(&compilation_context, "x * 2").expect("expression should be found");
+    assert!(matches!(set.inherent, ComputeKind::Classical));
+}
+
+#[test]
+fn dynamic_comparison_found_by_span() {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(SOURCE);
+    let set =
+        find_by_snippet(&compilation_context, "r == One").expect("expression should be found");
+    assert!(matches!(set.inherent, ComputeKind::Quantum(_)));
+}
+
+#[test]
+fn innermost_expression_found_by_span() {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(SOURCE);
+    let set = find_by_snippet(&compilation_context, "One").expect("expression should be found");
+    assert!(matches!(set.inherent, ComputeKind::Classical));
+}
+
+#[test]
+fn span_outside_of_code_not_found() {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(SOURCE);
+    assert!(find_by_snippet(&compilation_context, "namespace").is_none());
+}
@@ -0,0 +1,156 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! These tests check the configurable treatment of calls to callees that the analysis can't resolve.
+
+pub mod test_utils;
+
+use miette::{Diagnostic, Severity};
+use qsc_eval::debug::map_hir_package_to_fir;
+use qsc_frontend::compile::RuntimeCapabilityFlags;
+use qsc_rca::{
+    unresolved_calls, Analyzer, ComputeKind, ComputePropertiesLookup, ItemComputeProperties,
+    RuntimeFeatureFlags, UnresolvedCalleeLevel, UnresolvedCallees,
+};
+use test_utils::{CompilationContext, PackageStoreSearch};
+
+const SOURCE: &str = r#"
+    operation ApplyOp(op : Qubit => Unit, q : Qubit) : Unit {
+        op(q);
+    }
+    operation Main() : Unit {
+        use q = Qubit();
+        ApplyOp(H, q);
+        let g = H;
+        g(q);
+    }"#;
+
+fn apply_op_features(unresolved_callees: UnresolvedCallees) -> RuntimeFeatureFlags {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(SOURCE);
+    let compute_properties = Analyzer::init(&compilation_context.fir_store)
+        .with_unresolved_callees(unresolved_callees)
+        .analyze_all();
+    let id = compilation_context
+        .fir_store
+        .find_callable_id_by_name("ApplyOp")
+        .expect("callable should exist");
+    let ItemComputeProperties::Callable(callable) = compute_properties.get_item(id) else {
+        panic!("item should be a callable");
+    };
+    match callable.body.inherent {
+        ComputeKind::Quantum(properties) => properties.runtime_features,
+        ComputeKind::Classical => RuntimeFeatureFlags::empty(),
+    }
+}
+
+fn assume(name: &str, capabilities: RuntimeCapabilityFlags) -> UnresolvedCallees {
+    UnresolvedCallees {
+        level: UnresolvedCalleeLevel::Allow,
+        assumptions: [(name.into(), capabilities)].into_iter().collect(),
+    }
+}
+
+fn unresolved_call_spans(unresolved_callees: &UnresolvedCallees) -> Vec<(u32, u32)> {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(SOURCE);
+    let package_id = map_hir_package_to_fir(compilation_context.compiler.package_id());
+    unresolved_calls(
+        &compilation_context.fir_store,
+        package_id,
+        unresolved_callees,
+    )
+    .into_iter()
+    .map(|call| (call.span.lo, call.span.hi))
+    .collect()
+}
+
+fn span_of(snippet: &str) -> (u32, u32) {
+    let lo = u32::try_from(
+        SOURCE
+            .find(snippet)
+            .expect("snippet should be in the source"),
+    )
+    .expect("offset should fit into u32");
+    (
+        lo,
+        lo + u32::try_from(snippet.len()).expect("length should fit into u32"),
+    )
+}
+
+#[test]
+fn unresolved_callee_needs_higher_level_constructs_by_default() {
+    let features = apply_op_features(UnresolvedCallees::default());
+    assert!(features.contains(RuntimeFeatureFlags::CallToUnresolvedCallee));
+    assert_eq!(
+        features.runtime_capabilities(),
+        RuntimeCapabilityFlags::HigherLevelConstructs
+    );
+}
+
+#[test]
+fn assumed_callee_without_capabilities_needs_none() {
+    let features = apply_op_features(assume("op", RuntimeCapabilityFlags::empty()));
+    assert!(!features.contains(RuntimeFeatureFlags::CallToUnresolvedCallee));
+    assert_eq!(
+        features.runtime_capabilities(),
+        RuntimeCapabilityFlags::empty()
+    );
+}
+
+#[test]
+fn assumed_callee_needs_assumed_capabilities() {
+    let capabilities =
+        RuntimeCapabilityFlags::ForwardBranching | RuntimeCapabilityFlags::IntegerComputations;
+    let features = apply_op_features(assume("op", capabilities));
+    assert_eq!(features.runtime_capabilities(), capabilities);
+}
+
+#[test]
+fn assumption_for_other_name_does_not_apply() {
+    let features = apply_op_features(assume("other", RuntimeCapabilityFlags::empty()));
+    assert!(features.contains(RuntimeFeatureFlags::CallToUnresolvedCallee));
+}
+
+#[test]
+fn unresolved_calls_not_reported_when_allowed() {
+    assert!(unresolved_call_spans(&UnresolvedCallees::default()).is_empty());
+}
+
+#[test]
+fn only_calls_to_unresolved_callees_reported() {
+    let unresolved_callees = UnresolvedCallees {
+        level: UnresolvedCalleeLevel::Warn,
+        ..UnresolvedCallees::default()
+    };
+    assert_eq!(
+        unresolved_call_spans(&unresolved_callees),
+        vec![span_of("op(q)")]
+    );
+}
+
+#[test]
+fn assumed_callees_not_reported() {
+    let unresolved_callees = UnresolvedCallees {
+        level: UnresolvedCalleeLevel::Error,
+        ..assume("op", RuntimeCapabilityFlags::empty())
+    };
+    assert!(unresolved_call_spans(&unresolved_callees).is_empty());
+}
+
+#[test]
+fn unresolved_calls_reported_as_errors() {
+    let mut compilation_context = CompilationContext::new();
+    compilation_context.update(SOURCE);
+    let package_id = map_hir_package_to_fir(compilation_context.compiler.package_id());
+    let calls = unresolved_calls(
+        &compilation_context.fir_store,
+        package_id,
+        &UnresolvedCallees {
+            level: UnresolvedCalleeLevel::Error,
+            ..UnresolvedCallees::default()
+        },
+    );
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].severity(), Some(Severity::Error));
+}
@@ -0,0 +1,305 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::compilation::Compilation;
+use crate::name_locator::{Handler, Locator, LocatorContext};
+use crate::protocol::{CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall};
+use crate::qsc_utils::{into_location, into_range};
+use qsc::ast::visit::{walk_expr, walk_item, Visitor};
+use qsc::display::Lookup;
+use qsc::line_column::{Encoding, Position};
+use qsc::{ast, hir, resolve, Span};
+
+pub(crate) fn prepare_call_hierarchy(
+    compilation: &Compilation,
+    source_name: &str,
+    position: Position,
+    position_encoding: Encoding,
+) -> Option<CallHierarchyItem> {
+    let item_id = callable_at(compilation, source_name, position, position_encoding)?;
+    Some(call_hierarchy_item(compilation, item_id, position_encoding))
+}
+
+/// The callables that call the callable at the position, along with the ranges of the calls.
+pub(crate) fn get_incoming_calls(
+    compilation: &Compilation,
+    source_name: &str,
+    position: Position,
+    position_encoding: Encoding,
+) -> Vec<CallHierarchyIncomingCall> {
+    let Some(item_id) = callable_at(compilation, source_name, position, position_encoding) else {
+        return vec![];
+    };
+
+    let calls = calls(compilation)
+        .into_iter()
+        .filter(|call| call.callee == item_id);
+    group_calls(compilation, calls, |call| call.caller, position_encoding)
+        .into_iter()
+        .map(|(from, from_ranges)| CallHierarchyIncomingCall { from, from_ranges })
+        .collect()
+}
+
+/// The callables that the callable at the position calls, along with the ranges of the calls.
+pub(crate) fn get_outgoing_calls(
+    compilation: &Compilation,
+    source_name: &str,
+    position: Position,
+    position_encoding: Encoding,
+) -> Vec<CallHierarchyOutgoingCall> {
+    let Some(item_id) = callable_at(compilation, source_name, position, position_encoding) else {
+        return vec![];
+    };
+
+    let calls = calls(compilation)
+        .into_iter()
+        .filter(|call| call.caller == item_id);
+    group_calls(compilation, calls, |call| call.callee, position_encoding)
+        .into_iter()
+        .map(|(to, from_ranges)| CallHierarchyOutgoingCall { to, from_ranges })
+        .collect()
+}
+
+/// Groups the calls by the given callable, in order of the first call.
+fn group_calls(
+    compilation: &Compilation,
+    calls: impl Iterator<Item = Call>,
+    key: impl Fn(&Call) -> hir::ItemId,
+    position_encoding: Encoding,
+) -> Vec<(CallHierarchyItem, Vec<qsc::line_column::Range>)> {
+    let mut groups: Vec<(hir::ItemId, Vec<Span>)> = Vec::new();
+    for call in calls {
+        let item_id = key(&call);
+        match groups.iter_mut().find(|(id, _)| *id == item_id) {
+            Some((_, spans)) => spans.push(call.span),
+            None => groups.push((item_id, vec![call.span])),
+        }
+    }
+
+    let sources = &compilation.user_unit().sources;
+    groups
+        .into_iter()
+        .map(|(item_id, spans)| {
+            (
+                call_hierarchy_item(compilation, item_id, position_encoding),
+                spans
+                    .into_iter()
+                    .map(|span| into_range(position_encoding, span, sources))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+fn call_hierarchy_item(
+    compilation: &Compilation,
+    item_id: hir::ItemId,
+    position_encoding: Encoding,
+) -> CallHierarchyItem {
+    let (item, package, resolved_item_id) =
+        compilation.resolve_item_relative_to_user_package(&item_id);
+    let package_id = resolved_item_id
+        .package
+        .expect("package id should be resolved");
+    let hir::ItemKind::Callable(decl) = &item.kind else {
+        panic!("call hierarchy item should be a callable");
+    };
+    let namespace = item
+        .parent
+        .and_then(|parent| package.items.get(parent))
+        .and_then(|parent| match &parent.kind {
+            hir::ItemKind::Namespace(name, _) => Some(name.name.to_string()),
+            _ => None,
+        });
+
+    CallHierarchyItem {
+        name: decl.name.name.to_string(),
+        detail: namespace.unwrap_or_default(),
+        location: into_location(position_encoding, compilation, decl.span, package_id),
+        selection_range: into_location(position_encoding, compilation, decl.name.span, package_id)
+            .range,
+    }
+}
+
+/// Finds the callable whose declaration or reference is at the position.
+fn callable_at(
+    compilation: &Compilation,
+    source_name: &str,
+    position: Position,
+    position_encoding: Encoding,
+) -> Option<hir::ItemId> {
+    let offset =
+        compilation.source_position_to_package_offset(source_name, position, position_encoding);
+    let mut callable_locator = CallableLocator {
+        compilation,
+        item_id: None,
+    };
+    let mut locator = Locator::new(&mut callable_locator, offset, compilation);
+    locator.visit_package(&compilation.user_unit().ast.package);
+    callable_locator.item_id
+}
+
+/// A call from one callable of the user package to another callable.
+struct Call {
+    caller: hir::ItemId,
+    callee: hir::ItemId,
+    /// The span of the callee in the call.
+    span: Span,
+}
+
+/// Collects the calls made by the callables of the user package. The calls made in lambdas belong
+/// to the callable that declares them.
+fn calls(compilation: &Compilation) -> Vec<Call> {
+    let mut collector = CallCollector {
+        compilation,
+        caller: None,
+        calls: Vec::new(),
+    };
+    collector.visit_package(&compilation.user_unit().ast.package);
+    collector.calls
+}
+
+struct CallCollector<'a> {
+    compilation: &'a Compilation,
+    caller: Option<hir::ItemId>,
+    calls: Vec<Call>,
+}
+
+impl CallCollector<'_> {
+    /// The callable that the callee expression of a call refers to, along with the span of its path.
+    fn callee(&self, expr: &ast::Expr) -> Option<(hir::ItemId, Span)> {
+        match &*expr.kind {
+            ast::ExprKind::Paren(expr) | ast::ExprKind::UnOp(ast::UnOp::Functor(_), expr) => {
+                self.callee(expr)
+            }
+            ast::ExprKind::Path(path) => {
+                let Some(resolve::Res::Item(item_id, _)) = self.compilation.get_res(path.id) else {
+                    return None;
+                };
+                let (item, _, resolved_item_id) = self
+                    .compilation
+                    .resolve_item_relative_to_user_package(item_id);
+                matches!(item.kind, hir::ItemKind::Callable(_))
+                    .then_some((resolved_item_id, path.span))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for CallCollector<'a> {
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        let caller = self.caller;
+        if let ast::ItemKind::Callable(decl) = &*item.kind {
+            if let Some(resolve::Res::Item(item_id, _)) = self.compilation.get_res(decl.name.id) {
+                self.caller = Some(
+                    self.compilation
+                        .resolve_item_relative_to_user_package(item_id)
+                        .2,
+                );
+            }
+        }
+        walk_item(self, item);
+        self.caller = caller;
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        if let (Some(caller), ast::ExprKind::Call(callee, _)) = (self.caller, &*expr.kind) {
+            if let Some((callee, span)) = self.callee(callee) {
+                self.calls.push(Call {
+                    caller,
+                    callee,
+                    span,
+                });
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+struct CallableLocator<'a> {
+    compilation: &'a Compilation,
+    item_id: Option<hir::ItemId>,
+}
+
+impl<'a> Handler<'a> for CallableLocator<'a> {
+    fn at_callable_def(
+        &mut self,
+        _: &LocatorContext<'a>,
+        name: &'a ast::Ident,
+        _: &'a ast::CallableDecl,
+    ) {
+        if let Some(resolve::Res::Item(item_id, _)) = self.compilation.get_res(name.id) {
+            self.item_id = Some(
+                self.compilation
+                    .resolve_item_relative_to_user_package(item_id)
+                    .2,
+            );
+        }
+    }
+
+    fn at_callable_ref(
+        &mut self,
+        _: &'a ast::Path,
+        item_id: &'_ hir::ItemId,
+        _: &'a hir::Item,
+        _: &'a hir::Package,
+        _: &'a hir::CallableDecl,
+    ) {
+        self.item_id = Some(*item_id);
+    }
+
+    fn at_type_param_def(
+        &mut self,
+        _: &LocatorContext<'a>,
+        _: &'a ast::Ident,
+        _: hir::ty::ParamId,
+    ) {
+    }
+
+    fn at_type_param_ref(
+        &mut self,
+        _: &LocatorContext<'a>,
+        _: &'a ast::Ident,
+        _: hir::ty::ParamId,
+        _: &'a ast::Ident,
+    ) {
+    }
+
+    fn at_new_type_def(&mut self, _: &'a ast::Ident, _: &'a ast::TyDef) {}
+
+    fn at_new_type_ref(
+        &mut self,
+        _: &'a ast::Path,
+        _: &'_ hir::ItemId,
+        _: &'a hir::Package,
+        _: &'a hir::Ident,
+        _: &'a hir::ty::Udt,
+    ) {
+    }
+
+    fn at_field_def(&mut self, _: &LocatorContext<'a>, _: &'a ast::Ident, _: &'a ast::Ty) {}
+
+    fn at_field_ref(
+        &mut self,
+        _: &'a ast::Ident,
+        _: &'a ast::NodeId,
+        _: &'_ hir::ItemId,
+        _: &'a hir::ty::UdtField,
+    ) {
+    }
+
+    fn at_local_def(&mut self, _: &LocatorContext<'a>, _: &'a ast::Ident, _: &'a ast::Pat) {}
+
+    fn at_local_ref(
+        &mut self,
+        _: &LocatorContext<'a>,
+        _: &'a ast::Path,
+        _: &'a ast::NodeId,
+        _: &'a ast::Ident,
+    ) {
+    }
+}
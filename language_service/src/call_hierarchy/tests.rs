@@ -0,0 +1,178 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::{get_incoming_calls, get_outgoing_calls, prepare_call_hierarchy};
+use crate::{
+    protocol::CallHierarchyItem,
+    test_utils::{
+        compile_project_with_fake_stdlib_and_markers, compile_with_fake_stdlib_and_markers,
+    },
+    Encoding,
+};
+use expect_test::{expect, Expect};
+use qsc::{line_column::Range, location::Location};
+
+/// Asserts that the callable at the cursor, given by a `↘` marker, is called from the ranges
+/// between the `◉` markers, and shows the callables that make the calls.
+fn check_incoming(sources_with_markers: &[(&str, &str)], expect: &Expect) {
+    let (compilation, cursor_uri, cursor_position, target_locations) =
+        compile_project_with_fake_stdlib_and_markers(sources_with_markers);
+    let calls = get_incoming_calls(&compilation, &cursor_uri, cursor_position, Encoding::Utf8);
+    let mut locations = Vec::new();
+    let mut actual = String::new();
+    for call in calls {
+        actual.push_str(&describe(&call.from, &call.from_ranges));
+        for range in call.from_ranges {
+            locations.push(Location {
+                source: call.from.location.source.clone(),
+                range,
+            });
+        }
+    }
+    for target in &target_locations {
+        assert!(
+            locations.contains(target),
+            "{target:?} not in {locations:?}"
+        );
+    }
+    assert_eq!(locations.len(), target_locations.len());
+    expect.assert_eq(&actual);
+}
+
+/// Asserts that the callable at the cursor, given by a `↘` marker, makes calls from the ranges
+/// between the `◉` markers, and shows the callables that receive the calls.
+fn check_outgoing(source_with_markers: &str, expect: &Expect) {
+    let (compilation, cursor_position, target_spans) =
+        compile_with_fake_stdlib_and_markers(source_with_markers);
+    let calls = get_outgoing_calls(&compilation, "<source>", cursor_position, Encoding::Utf8);
+    let mut ranges = Vec::new();
+    let mut actual = String::new();
+    for call in calls {
+        actual.push_str(&describe(&call.to, &call.from_ranges));
+        ranges.extend(call.from_ranges);
+    }
+    for target in &target_spans {
+        assert!(ranges.contains(target), "{target:?} not in {ranges:?}");
+    }
+    assert_eq!(ranges.len(), target_spans.len());
+    expect.assert_eq(&actual);
+}
+
+fn describe(item: &CallHierarchyItem, ranges: &[Range]) -> String {
+    format!(
+        "{} ({}) in {} at {}:{}, {} call(s)\n",
+        item.name,
+        item.detail,
+        item.location.source,
+        item.selection_range.start.line,
+        item.selection_range.start.column,
+        ranges.len()
+    )
+}
+
+#[test]
+fn prepare_at_reference() {
+    let (compilation, cursor_position, _) = compile_with_fake_stdlib_and_markers(
+        r#"
+        namespace Test {
+            open FakeStdLib;
+            operation Foo() : Unit {
+                Fa↘ke();
+            }
+        }
+    "#,
+    );
+    let item = prepare_call_hierarchy(&compilation, "<source>", cursor_position, Encoding::Utf8)
+        .expect("expected a call hierarchy item");
+    expect![[r#"
+        Fake (FakeStdLib) in qsharp-library-source:<std> at 1:26, 0 call(s)
+    "#]]
+    .assert_eq(&describe(&item, &[]));
+}
+
+#[test]
+fn no_prepare_at_local() {
+    let (compilation, cursor_position, _) = compile_with_fake_stdlib_and_markers(
+        r#"
+        namespace Test {
+            operation Foo() : Unit {
+                let x↘ = 1;
+            }
+        }
+    "#,
+    );
+    assert!(
+        prepare_call_hierarchy(&compilation, "<source>", cursor_position, Encoding::Utf8).is_none()
+    );
+}
+
+#[test]
+fn incoming_calls_across_sources() {
+    check_incoming(
+        &[
+            (
+                "a.qs",
+                r#"
+        namespace A {
+            operation ↘Foo() : Unit is Adj {}
+        }
+    "#,
+            ),
+            (
+                "b.qs",
+                r#"
+        namespace B {
+            open A;
+            operation Bar() : Unit {
+                ◉Foo◉();
+                let f = () => ◉A.Foo◉();
+                f();
+            }
+            operation Baz() : Unit {
+                (Adjoint ◉Foo◉)();
+            }
+            function NotACall() : Unit {
+                let f = Foo;
+            }
+        }
+    "#,
+            ),
+        ],
+        &expect![[r#"
+            Bar (B) in b.qs at 3:22, 2 call(s)
+            Baz (B) in b.qs at 8:22, 1 call(s)
+        "#]],
+    );
+}
+
+#[test]
+fn outgoing_calls() {
+    check_outgoing(
+        r#"
+        namespace Test {
+            open FakeStdLib;
+            operation Fo↘o() : Unit {
+                ◉Bar◉();
+                ◉Fake◉();
+                ◉Bar◉();
+                let f = Bar;
+                f();
+                let x = ◉Square◉(2);
+            }
+            operation Bar() : Unit {
+                Fake();
+            }
+            function Square(x : Int) : Int {
+                x * x
+            }
+        }
+    "#,
+        &expect![[r#"
+            Bar (Test) in <source> at 11:22, 2 call(s)
+            Fake (FakeStdLib) in qsharp-library-source:<std> at 1:26, 1 call(s)
+            Square (Test) in <source> at 14:21, 1 call(s)
+        "#]],
+    );
+}
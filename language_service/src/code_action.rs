@@ -0,0 +1,838 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    compilation::Compilation,
+    protocol::{CodeAction, CodeActionKind, TextEdit},
+    qsc_utils::{find_ident, into_range, is_known},
+};
+use qsc::{
+    ast::{
+        self,
+        visit::{walk_block, walk_expr, walk_pat, Visitor},
+    },
+    hir::{
+        self,
+        ty::{FunctorSetValue, Ty},
+    },
+    line_column::{Encoding, Range},
+    resolve::Res,
+    Span,
+};
+use rustc_hash::FxHashSet;
+use std::{fmt::Write, rc::Rc};
+
+pub(crate) fn get_code_actions(
+    compilation: &Compilation,
+    source_name: &str,
+    range: Range,
+    position_encoding: Encoding,
+) -> Vec<CodeAction> {
+    let unit = compilation.user_unit();
+    let source = unit
+        .sources
+        .find_by_name(source_name)
+        .expect("source should exist in the user source map");
+    let selection = Span {
+        lo: source.offset
+            + range
+                .start
+                .to_utf8_byte_offset(position_encoding, &source.contents),
+        hi: source.offset
+            + range
+                .end
+                .to_utf8_byte_offset(position_encoding, &source.contents),
+    };
+
+    extract_callable(compilation, selection, position_encoding)
+        .into_iter()
+        .chain(generate_specializations(
+            compilation,
+            selection,
+            position_encoding,
+        ))
+        .chain(organize_opens(compilation, source_name, position_encoding))
+        .collect()
+}
+
+/// Offers to move the selected statements into a new callable, replacing them with a call to it.
+/// The free variables of the statements become the parameters of the callable, and the bindings
+/// that are used after the statements become its return value.
+fn extract_callable(
+    compilation: &Compilation,
+    selection: Span,
+    position_encoding: Encoding,
+) -> Option<CodeAction> {
+    let unit = compilation.user_unit();
+    let selected = find_selected_stmts(compilation, selection)?;
+    let stmts = &selected.block.stmts[selected.range.clone()];
+    let rest = &selected.block.stmts[selected.range.end..];
+
+    let mut analyzer = Analyzer::new(compilation);
+    for stmt in stmts {
+        analyzer.visit_stmt(stmt);
+    }
+    if !analyzer.is_extractable() {
+        return None;
+    }
+    let outputs = outputs(compilation, stmts, rest)?;
+
+    // A trailing expression of the block is the value of the block, so it becomes the value of the
+    // new callable.
+    let last = stmts.last().expect("selection should not be empty");
+    let tail = match &*last.kind {
+        ast::StmtKind::Expr(expr) if rest.is_empty() => Some(unit.ast.tys.terms.get(expr.id)?),
+        _ => None,
+    };
+
+    let params = analyzer.free_variables(selected.decl)?;
+
+    let output_ty = match (tail, outputs.as_slice()) {
+        (Some(ty), _) => ty.clone(),
+        (None, []) => Ty::UNIT,
+        (None, [(_, _, ty)]) => (*ty).clone(),
+        (None, outputs) => Ty::Tuple(outputs.iter().map(|(_, _, ty)| (*ty).clone()).collect()),
+    };
+    if !params.iter().all(|(_, _, ty)| is_known(ty)) || !is_known(&output_ty) {
+        return None;
+    }
+
+    let is_operation = selected.decl.kind == ast::CallableKind::Operation
+        && (analyzer.is_operation || selection_allocates(stmts));
+    let (keyword, base_name, functors) = if is_operation {
+        (
+            "operation",
+            "ExtractedOperation",
+            required_functors(selected.decl, selected.in_within),
+        )
+    } else {
+        ("function", "ExtractedFunction", "")
+    };
+    let name = unique_name(selected.namespace, base_name);
+
+    let source = unit
+        .sources
+        .find_by_offset(selected.item.span.lo)
+        .expect("source should exist for offset");
+    let item_indent = indentation(&source.contents, selected.item.span.lo - source.offset);
+    let body_indent = format!("{item_indent}    ");
+    let stmts_span = Span {
+        lo: stmts[0].span.lo,
+        hi: last.span.hi,
+    };
+    let mut body = reindent(&source.contents, stmts_span - source.offset, &body_indent);
+    let outputs_text = match outputs.as_slice() {
+        [] => None,
+        [(ident, _, _)] => Some(ident.name.to_string()),
+        outputs => {
+            let names: Vec<_> = outputs.iter().map(|(ident, _, _)| &*ident.name).collect();
+            Some(format!("({})", names.join(", ")))
+        }
+    };
+    if tail.is_none() {
+        if let Some(outputs_text) = &outputs_text {
+            let _ = write!(body, "\n{body_indent}{outputs_text}");
+        }
+    }
+
+    let new_callable = format!(
+        "\n\n{item_indent}{} {{\n{body}\n{item_indent}}}",
+        signature(keyword, &name, &params, &output_ty, functors)
+    );
+
+    let args: Vec<_> = params.iter().map(|(_, name, _)| &**name).collect();
+    let call = format!("{name}({})", args.join(", "));
+    let call = match outputs_text {
+        _ if tail.is_some() => call,
+        Some(outputs_text) => {
+            let is_mutable = outputs
+                .iter()
+                .any(|(_, mutability, _)| *mutability == ast::Mutability::Mutable);
+            let keyword = if is_mutable { "mutable" } else { "let" };
+            format!("{keyword} {outputs_text} = {call};")
+        }
+        None => format!("{call};"),
+    };
+
+    let end_of_item = Span {
+        lo: selected.item.span.hi,
+        hi: selected.item.span.hi,
+    };
+    Some(CodeAction {
+        title: format!("Extract to {keyword} `{name}`"),
+        kind: CodeActionKind::RefactorExtract,
+        edits: vec![
+            TextEdit {
+                new_text: call,
+                range: into_range(position_encoding, stmts_span, &unit.sources),
+            },
+            TextEdit {
+                new_text: new_callable,
+                range: into_range(position_encoding, end_of_item, &unit.sources),
+            },
+        ],
+    })
+}
+
+/// Offers to write out the specializations of an operation that only declares a body, so that they
+/// can be implemented by hand. The selection has to be in the signature of the operation.
+fn generate_specializations(
+    compilation: &Compilation,
+    selection: Span,
+    position_encoding: Encoding,
+) -> Option<CodeAction> {
+    let unit = compilation.user_unit();
+    let decl = unit.ast.package.nodes.iter().find_map(|node| {
+        let ast::TopLevelNode::Namespace(namespace) = node else {
+            return None;
+        };
+        namespace.items.iter().find_map(|item| match &*item.kind {
+            ast::ItemKind::Callable(decl)
+                if decl.span.lo <= selection.lo && selection.hi <= signature_end(decl) =>
+            {
+                Some(&**decl)
+            }
+            _ => None,
+        })
+    })?;
+    let hir_decl = unit
+        .package
+        .items
+        .values()
+        .find_map(|item| match &item.kind {
+            hir::ItemKind::Callable(hir_decl) if hir_decl.span == decl.span => Some(hir_decl),
+            _ => None,
+        })?;
+    // The specializations in the HIR have already been generated by the passes, so only the AST
+    // tells whether they are declared.
+    let body_only = match &*decl.body {
+        ast::CallableBody::Block(_) => true,
+        ast::CallableBody::Specs(decls) => {
+            decls.len() == 1 && matches!(decls[0].body, ast::SpecBody::Impl(..))
+        }
+    };
+    if !body_only
+        || hir_decl.kind != hir::CallableKind::Operation
+        || hir_decl.functors == FunctorSetValue::Empty
+    {
+        return None;
+    }
+
+    let source = unit
+        .sources
+        .find_by_offset(decl.span.lo)
+        .expect("source should exist for offset");
+    let item_indent = indentation(&source.contents, decl.span.lo - source.offset);
+    let spec_indent = format!("{item_indent}    ");
+
+    let ctls = controls_name(hir_decl);
+    let mut specs = String::new();
+    let has = |functor| hir_decl.functors.contains(&functor);
+    if has(hir::Functor::Adj) {
+        let _ = write!(specs, "\n{spec_indent}adjoint (...) {{}}");
+    }
+    if has(hir::Functor::Ctl) {
+        let _ = write!(specs, "\n{spec_indent}controlled ({ctls}, ...) {{}}");
+    }
+    if hir_decl.functors == FunctorSetValue::CtlAdj {
+        let _ = write!(
+            specs,
+            "\n{spec_indent}controlled adjoint ({ctls}, ...) {{}}"
+        );
+    }
+
+    let edit = match &*decl.body {
+        ast::CallableBody::Block(block) => {
+            let body = match (block.stmts.first(), block.stmts.last()) {
+                (Some(first), Some(last)) => {
+                    let stmts_span = Span {
+                        lo: first.span.lo,
+                        hi: last.span.hi,
+                    };
+                    let stmts = reindent(
+                        &source.contents,
+                        stmts_span - source.offset,
+                        &format!("{spec_indent}    "),
+                    );
+                    format!("{{\n{stmts}\n{spec_indent}}}")
+                }
+                _ => "{}".to_string(),
+            };
+            TextEdit {
+                new_text: format!("{{\n{spec_indent}body (...) {body}{specs}\n{item_indent}}}"),
+                range: into_range(position_encoding, block.span, &unit.sources),
+            }
+        }
+        ast::CallableBody::Specs(decls) => {
+            let body = &decls[0];
+            let end_of_body = Span {
+                lo: body.span.hi,
+                hi: body.span.hi,
+            };
+            TextEdit {
+                new_text: specs,
+                range: into_range(position_encoding, end_of_body, &unit.sources),
+            }
+        }
+    };
+
+    Some(CodeAction {
+        title: format!("Add specializations to `{}`", decl.name.name),
+        kind: CodeActionKind::RefactorRewrite,
+        edits: vec![edit],
+    })
+}
+
+/// Offers to remove the open statements of the namespaces in the source that no name was resolved
+/// through, including the duplicates, and to sort the remaining ones. The remaining opens take
+/// the place of the first open statement of the namespace.
+fn organize_opens(
+    compilation: &Compilation,
+    source_name: &str,
+    position_encoding: Encoding,
+) -> Option<CodeAction> {
+    let unit = compilation.user_unit();
+    let source = unit.sources.find_by_name(source_name)?;
+    let source_end = source.offset
+        + u32::try_from(source.contents.len()).expect("source length should fit into u32");
+    let mut edits = Vec::new();
+    for node in &*unit.ast.package.nodes {
+        let ast::TopLevelNode::Namespace(namespace) = node else {
+            continue;
+        };
+        if namespace.span.lo < source.offset || namespace.span.hi > source_end {
+            continue;
+        }
+
+        let opens: Vec<_> = namespace
+            .items
+            .iter()
+            .filter_map(|item| match &*item.kind {
+                ast::ItemKind::Open(name, alias) => {
+                    let text = match alias {
+                        Some(alias) => format!("open {} as {};", name.name, alias.name),
+                        None => format!("open {};", name.name),
+                    };
+                    Some((item.span, text, unit.ast.locals.is_open_used(name.span)))
+                }
+                _ => None,
+            })
+            .collect();
+        let mut organized: Vec<&str> = opens
+            .iter()
+            .filter(|(_, _, used)| *used)
+            .map(|(_, text, _)| text.as_str())
+            .collect();
+        organized.sort_unstable();
+        organized.dedup();
+        if organized
+            .iter()
+            .copied()
+            .eq(opens.iter().map(|(_, text, _)| text.as_str()))
+        {
+            continue;
+        }
+
+        for (i, (span, _, _)) in opens.iter().enumerate() {
+            let edit = if i == 0 && !organized.is_empty() {
+                let indent = indentation(&source.contents, span.lo - source.offset);
+                TextEdit {
+                    new_text: organized.join(&format!("\n{indent}")),
+                    range: into_range(position_encoding, *span, &unit.sources),
+                }
+            } else {
+                TextEdit {
+                    new_text: String::new(),
+                    range: into_range(
+                        position_encoding,
+                        whole_lines(&source.contents, *span - source.offset) + source.offset,
+                        &unit.sources,
+                    ),
+                }
+            };
+            edits.push(edit);
+        }
+    }
+
+    if edits.is_empty() {
+        return None;
+    }
+    Some(CodeAction {
+        title: "Organize opens".to_string(),
+        kind: CodeActionKind::SourceOrganizeImports,
+        edits,
+    })
+}
+
+/// The end of the signature of the callable, before its body.
+pub(crate) fn signature_end(decl: &ast::CallableDecl) -> u32 {
+    decl.functors
+        .as_ref()
+        .map_or(decl.output.span.hi, |functors| functors.span.hi)
+}
+
+/// A name for the controls of the controlled specializations. The controls are bound next to the
+/// parameters of the operation, so the name can't shadow one of them.
+pub(crate) fn controls_name(decl: &hir::CallableDecl) -> String {
+    let mut params = Vec::new();
+    bound_names(&decl.input, &mut params);
+    let mut name = "ctls".to_string();
+    let mut suffix = 0;
+    while params.contains(&&*name) {
+        suffix += 1;
+        name = format!("ctls{suffix}");
+    }
+    name
+}
+
+fn bound_names<'a>(pat: &'a hir::Pat, names: &mut Vec<&'a str>) {
+    match &pat.kind {
+        hir::PatKind::Bind(ident) => names.push(&ident.name),
+        hir::PatKind::Tuple(items) => {
+            for item in items {
+                bound_names(item, names);
+            }
+        }
+        hir::PatKind::Discard | hir::PatKind::Err => {}
+    }
+}
+
+fn signature(
+    keyword: &str,
+    name: &str,
+    params: &[(ast::NodeId, Rc<str>, &Ty)],
+    output_ty: &Ty,
+    functors: &str,
+) -> String {
+    let mut generics = Vec::new();
+    for ty in params.iter().map(|(_, _, ty)| *ty).chain([output_ty]) {
+        collect_params(ty, &mut generics);
+    }
+    let generics = if generics.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", generics.join(", "))
+    };
+    let params = params
+        .iter()
+        .map(|(_, name, ty)| format!("{name} : {}", ty.display()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{keyword} {name}{generics}({params}) : {}{functors}",
+        output_ty.display()
+    )
+}
+
+/// A run of statements of a block, along with the declarations that enclose it.
+struct SelectedStmts<'a> {
+    namespace: &'a ast::Namespace,
+    item: &'a ast::Item,
+    decl: &'a ast::CallableDecl,
+    block: &'a ast::Block,
+    range: std::ops::Range<usize>,
+    /// Whether the block is the within block of a conjugation, or is nested in one.
+    in_within: bool,
+}
+
+/// Finds the statements covered by the selection, which has to cover whole statements of a
+/// block, and at least one of them.
+fn find_selected_stmts(compilation: &Compilation, selection: Span) -> Option<SelectedStmts<'_>> {
+    if selection.lo >= selection.hi {
+        return None;
+    }
+
+    let (namespace, item, decl) =
+        compilation
+            .user_unit()
+            .ast
+            .package
+            .nodes
+            .iter()
+            .find_map(|node| {
+                let ast::TopLevelNode::Namespace(namespace) = node else {
+                    return None;
+                };
+                namespace.items.iter().find_map(|item| match &*item.kind {
+                    ast::ItemKind::Callable(decl)
+                        if decl.span.lo <= selection.lo && selection.hi <= decl.span.hi =>
+                    {
+                        Some((namespace, &**item, &**decl))
+                    }
+                    _ => None,
+                })
+            })?;
+
+    let mut finder = BlockFinder {
+        selection,
+        in_within: false,
+        found: None,
+    };
+    finder.visit_callable_decl(decl);
+    let (block, in_within) = finder.found?;
+
+    let mut range: Option<std::ops::Range<usize>> = None;
+    for (index, stmt) in block.stmts.iter().enumerate() {
+        let inside = selection.lo <= stmt.span.lo && stmt.span.hi <= selection.hi;
+        let outside = stmt.span.hi <= selection.lo || selection.hi <= stmt.span.lo;
+        if inside {
+            range = Some(range.map_or(index, |range| range.start)..index + 1);
+        } else if !outside {
+            return None;
+        }
+    }
+
+    Some(SelectedStmts {
+        namespace,
+        item,
+        decl,
+        block,
+        range: range?,
+        in_within,
+    })
+}
+
+/// The bindings declared by the selected statements that the rest of the block still needs.
+fn outputs<'a>(
+    compilation: &'a Compilation,
+    stmts: &'a [Box<ast::Stmt>],
+    rest: &'a [Box<ast::Stmt>],
+) -> Option<Vec<(&'a ast::Ident, ast::Mutability, &'a Ty)>> {
+    let mut later_uses = Analyzer::new(compilation);
+    for stmt in rest {
+        later_uses.visit_stmt(stmt);
+    }
+
+    let mut outputs = Vec::new();
+    for stmt in stmts {
+        let (mutability, pat) = match &*stmt.kind {
+            ast::StmtKind::Local(mutability, pat, _) => (*mutability, pat),
+            ast::StmtKind::Qubit(_, pat, _, None) => (ast::Mutability::Immutable, pat),
+            _ => continue,
+        };
+        let mut bindings = Bindings(Vec::new());
+        bindings.visit_pat(pat);
+        for ident in bindings.0 {
+            if later_uses.uses.iter().any(|(id, _)| *id == ident.id) {
+                if matches!(&*stmt.kind, ast::StmtKind::Qubit(..)) {
+                    // Qubits are released when the new callable returns.
+                    return None;
+                }
+                let ty = compilation.user_unit().ast.tys.terms.get(ident.id)?;
+                outputs.push((ident, mutability, ty));
+            }
+        }
+    }
+    Some(outputs)
+}
+
+/// The functors of the enclosing operation that the new operation can support too.
+fn required_functors(decl: &ast::CallableDecl, in_within: bool) -> &'static str {
+    let (mut adj, ctl) = if generated_specs_only(decl) {
+        decl.functors
+            .as_ref()
+            .map_or((false, false), |f| functors(f))
+    } else {
+        (false, false)
+    };
+    // The statements of a within block are also run in reverse.
+    adj |= in_within;
+    match (adj, ctl) {
+        (true, true) => " is Adj + Ctl",
+        (true, false) => " is Adj",
+        (false, true) => " is Ctl",
+        (false, false) => "",
+    }
+}
+
+/// Moves the lines of the source in the span to the given indentation, keeping their
+/// indentation relative to the first line.
+fn reindent(contents: &str, span: Span, indent: &str) -> String {
+    let original_indent = indentation(contents, span.lo);
+    let mut text = String::new();
+    for (i, line) in contents[span.lo as usize..span.hi as usize]
+        .lines()
+        .enumerate()
+    {
+        let line = if i == 0 {
+            line
+        } else {
+            text.push('\n');
+            line.strip_prefix(original_indent)
+                .unwrap_or(line.trim_start())
+        };
+        if !line.is_empty() {
+            let _ = write!(text, "{indent}{line}");
+        }
+    }
+    text
+}
+
+/// Finds the innermost block that contains the selection.
+struct BlockFinder<'a> {
+    selection: Span,
+    in_within: bool,
+    found: Option<(&'a ast::Block, bool)>,
+}
+
+impl<'a> Visitor<'a> for BlockFinder<'a> {
+    fn visit_block(&mut self, block: &'a ast::Block) {
+        if block.span.lo < self.selection.lo && self.selection.hi < block.span.hi {
+            self.found = Some((block, self.in_within));
+            walk_block(self, block);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        match &*expr.kind {
+            // The body of a lambda is not a place callables can be extracted from.
+            ast::ExprKind::Lambda(..) => {}
+            ast::ExprKind::Conjugate(within, apply) => {
+                let in_within = self.in_within;
+                self.in_within = true;
+                self.visit_block(within);
+                self.in_within = in_within;
+                self.visit_block(apply);
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+/// Collects the local variables that the selected statements use, declare and assign to.
+struct Analyzer<'a> {
+    compilation: &'a Compilation,
+    /// The bindings declared by the statements.
+    declared: FxHashSet<ast::NodeId>,
+    /// The bindings used by the statements, along with the path expression that uses them.
+    uses: Vec<(ast::NodeId, ast::NodeId)>,
+    /// The bindings the statements assign to.
+    assigned: Vec<ast::NodeId>,
+    /// Whether the statements call an operation.
+    is_operation: bool,
+    /// Whether the statements have control flow that can't be moved into another callable.
+    unsupported: bool,
+}
+
+impl<'a> Analyzer<'a> {
+    fn new(compilation: &'a Compilation) -> Self {
+        Self {
+            compilation,
+            declared: FxHashSet::default(),
+            uses: Vec::new(),
+            assigned: Vec::new(),
+            is_operation: false,
+            unsupported: false,
+        }
+    }
+
+    /// Whether the statements can be moved as they are: a new callable can't return from the
+    /// enclosing one, or update its mutable variables.
+    fn is_extractable(&self) -> bool {
+        !self.unsupported && self.assigned.iter().all(|id| self.declared.contains(id))
+    }
+
+    /// The variables used by the statements that are bound outside of them, in order of first use.
+    /// Callables don't capture, so every free variable is bound in the enclosing callable.
+    fn free_variables(
+        &self,
+        decl: &'a ast::CallableDecl,
+    ) -> Option<Vec<(ast::NodeId, Rc<str>, &'a Ty)>> {
+        let tys = &self.compilation.user_unit().ast.tys;
+        let mut params: Vec<(ast::NodeId, Rc<str>, &Ty)> = Vec::new();
+        for (id, expr_id) in &self.uses {
+            if self.declared.contains(id) || params.iter().any(|(param, _, _)| param == id) {
+                continue;
+            }
+            let name = find_ident(id, decl)?.name.clone();
+            params.push((*id, name, tys.terms.get(*expr_id)?));
+        }
+        Some(params)
+    }
+
+    fn local(&self, expr: &ast::Expr) -> Option<ast::NodeId> {
+        match &*expr.kind {
+            ast::ExprKind::Path(path) => {
+                match self.compilation.user_unit().ast.names.get(path.id) {
+                    Some(Res::Local(id)) => Some(*id),
+                    _ => None,
+                }
+            }
+            ast::ExprKind::Paren(expr) => self.local(expr),
+            _ => None,
+        }
+    }
+
+    fn assigned_locals(&mut self, lhs: &ast::Expr) {
+        match &*lhs.kind {
+            ast::ExprKind::Paren(expr) => self.assigned_locals(expr),
+            ast::ExprKind::Tuple(items) => {
+                for item in &**items {
+                    self.assigned_locals(item);
+                }
+            }
+            _ => {
+                if let Some(id) = self.local(lhs) {
+                    self.assigned.push(id);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Analyzer<'a> {
+    fn visit_pat(&mut self, pat: &'a ast::Pat) {
+        if let ast::PatKind::Bind(ident, _) = &*pat.kind {
+            self.declared.insert(ident.id);
+        }
+        walk_pat(self, pat);
+    }
+
+    fn visit_expr(&mut self, expr: &'a ast::Expr) {
+        let tys = &self.compilation.user_unit().ast.tys;
+        match &*expr.kind {
+            ast::ExprKind::Path(path) => {
+                if let Some(Res::Local(id)) = self.compilation.user_unit().ast.names.get(path.id) {
+                    self.uses.push((*id, expr.id));
+                }
+            }
+            ast::ExprKind::Assign(lhs, _)
+            | ast::ExprKind::AssignOp(_, lhs, _)
+            | ast::ExprKind::AssignUpdate(lhs, _, _) => self.assigned_locals(lhs),
+            ast::ExprKind::Call(callee, _) => {
+                if let Some(Ty::Arrow(arrow)) = tys.terms.get(callee.id) {
+                    if arrow.kind == qsc::hir::CallableKind::Operation {
+                        self.is_operation = true;
+                    }
+                }
+            }
+            ast::ExprKind::Return(_) => self.unsupported = true,
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Collects the identifiers bound by a pattern.
+struct Bindings<'a>(Vec<&'a ast::Ident>);
+
+impl<'a> Visitor<'a> for Bindings<'a> {
+    fn visit_pat(&mut self, pat: &'a ast::Pat) {
+        if let ast::PatKind::Bind(ident, _) = &*pat.kind {
+            self.0.push(ident);
+        }
+        walk_pat(self, pat);
+    }
+}
+
+fn selection_allocates(stmts: &[Box<ast::Stmt>]) -> bool {
+    struct Allocates(bool);
+    impl<'a> Visitor<'a> for Allocates {
+        fn visit_stmt(&mut self, stmt: &'a ast::Stmt) {
+            if matches!(&*stmt.kind, ast::StmtKind::Qubit(..)) {
+                self.0 = true;
+            }
+            ast::visit::walk_stmt(self, stmt);
+        }
+    }
+
+    let mut allocates = Allocates(false);
+    for stmt in stmts {
+        allocates.visit_stmt(stmt);
+    }
+    allocates.0
+}
+
+/// Whether all the specializations of the callable besides the body are generated from it, so that
+/// the new callable can be given the same functors.
+fn generated_specs_only(decl: &ast::CallableDecl) -> bool {
+    match &*decl.body {
+        ast::CallableBody::Block(_) => true,
+        ast::CallableBody::Specs(specs) => specs
+            .iter()
+            .all(|spec| spec.spec == ast::Spec::Body || matches!(spec.body, ast::SpecBody::Gen(_))),
+    }
+}
+
+/// Evaluates a functor expression into whether it includes `Adj` and `Ctl`.
+fn functors(expr: &ast::FunctorExpr) -> (bool, bool) {
+    match &*expr.kind {
+        ast::FunctorExprKind::BinOp(op, lhs, rhs) => {
+            let (lhs, rhs) = (functors(lhs), functors(rhs));
+            match op {
+                ast::SetOp::Union => (lhs.0 || rhs.0, lhs.1 || rhs.1),
+                ast::SetOp::Intersect => (lhs.0 && rhs.0, lhs.1 && rhs.1),
+            }
+        }
+        ast::FunctorExprKind::Lit(ast::Functor::Adj) => (true, false),
+        ast::FunctorExprKind::Lit(ast::Functor::Ctl) => (false, true),
+        ast::FunctorExprKind::Paren(expr) => functors(expr),
+    }
+}
+
+fn collect_params(ty: &Ty, params: &mut Vec<String>) {
+    match ty {
+        Ty::Array(item) => collect_params(item, params),
+        Ty::Arrow(arrow) => {
+            collect_params(&arrow.input, params);
+            collect_params(&arrow.output, params);
+        }
+        Ty::Tuple(items) => {
+            for item in items {
+                collect_params(item, params);
+            }
+        }
+        Ty::Param(name, _) => {
+            if !params.iter().any(|param| **param == **name) {
+                params.push(name.to_string());
+            }
+        }
+        Ty::Infer(_) | Ty::Err | Ty::Prim(_) | Ty::Udt(..) => {}
+    }
+}
+
+fn unique_name(namespace: &ast::Namespace, base_name: &str) -> String {
+    let is_taken = |name: &str| {
+        namespace.items.iter().any(|item| match &*item.kind {
+            ast::ItemKind::Callable(decl) => &*decl.name.name == name,
+            ast::ItemKind::Ty(ident, _) => &*ident.name == name,
+            _ => false,
+        })
+    };
+    let mut name = base_name.to_string();
+    let mut suffix = 0;
+    while is_taken(&name) {
+        suffix += 1;
+        name = format!("{base_name}{suffix}");
+    }
+    name
+}
+
+/// The whitespace at the start of the line containing the offset.
+/// Extends the span to the whole lines it is on when there is nothing else on them, so that
+/// deleting it doesn't leave a blank line behind.
+fn whole_lines(contents: &str, span: Span) -> Span {
+    let (lo, hi) = (span.lo as usize, span.hi as usize);
+    let line_start = contents[..lo].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = contents[hi..]
+        .find('\n')
+        .map_or(contents.len(), |i| hi + i + 1);
+    if contents[line_start..lo].trim().is_empty() && contents[hi..line_end].trim().is_empty() {
+        Span {
+            lo: u32::try_from(line_start).expect("offset should fit into u32"),
+            hi: u32::try_from(line_end).expect("offset should fit into u32"),
+        }
+    } else {
+        span
+    }
+}
+
+fn indentation(contents: &str, offset: u32) -> &str {
+    let offset = offset as usize;
+    let line_start = contents[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = &contents[line_start..offset];
+    &line[..line.len() - line.trim_start().len()]
+}
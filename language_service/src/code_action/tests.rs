@@ -0,0 +1,579 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::get_code_actions;
+use crate::{test_utils::compile_with_fake_stdlib_and_markers_no_cursor, Encoding};
+use expect_test::{expect, Expect};
+use std::fmt::Write;
+
+/// Applies the edits of the code actions for the selection between the `◉` markers,
+/// and shows the titles of the actions along with the resulting source.
+fn check(source_with_markers: &str, expect: &Expect) {
+    let (compilation, target_spans) =
+        compile_with_fake_stdlib_and_markers_no_cursor(source_with_markers);
+    assert!(compilation.errors.is_empty(), "{:?}", compilation.errors);
+    let source = source_with_markers.replace('◉', "");
+    let selection = target_spans[0];
+    let actions = get_code_actions(&compilation, "<source>", selection, Encoding::Utf8);
+
+    let mut actual = String::new();
+    for action in actions {
+        let mut edits = action.edits;
+        edits.sort_by_key(|edit| {
+            std::cmp::Reverse(
+                edit.range
+                    .start
+                    .to_utf8_byte_offset(Encoding::Utf8, &source),
+            )
+        });
+        let mut new_source = source.clone();
+        for edit in edits {
+            let lo = edit
+                .range
+                .start
+                .to_utf8_byte_offset(Encoding::Utf8, &source) as usize;
+            let hi = edit.range.end.to_utf8_byte_offset(Encoding::Utf8, &source) as usize;
+            new_source.replace_range(lo..hi, &edit.new_text);
+        }
+        let _ = writeln!(
+            actual,
+            "{} ({:?}):\n{new_source}",
+            action.title, action.kind
+        );
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn extract_operation_with_parameters() {
+    check(
+        r#"
+namespace Test {
+    open FakeStdLib;
+    @EntryPoint()
+    operation Main() : Unit {
+        use q = Qubit();
+        let angle = 1.0;
+        ◉Rx(angle, q);
+        Fake();◉
+        Reset(q);
+    }
+    operation Rx(theta : Double, q : Qubit) : Unit {}
+    operation Reset(q : Qubit) : Unit {}
+}"#,
+        &expect![[r#"
+            Extract to operation `ExtractedOperation` (RefactorExtract):
+
+            namespace Test {
+                open FakeStdLib;
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    let angle = 1.0;
+                    ExtractedOperation(angle, q);
+                    Reset(q);
+                }
+
+                operation ExtractedOperation(angle : Double, q : Qubit) : Unit {
+                    Rx(angle, q);
+                    Fake();
+                }
+                operation Rx(theta : Double, q : Qubit) : Unit {}
+                operation Reset(q : Qubit) : Unit {}
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn extract_function_with_return_value() {
+    check(
+        r#"
+namespace Test {
+    @EntryPoint()
+    function Main() : Int {
+        let x = 1;
+        ◉let y = x + 1;
+        let z = y * 2;◉
+        z + x
+    }
+}"#,
+        &expect![[r#"
+            Extract to function `ExtractedFunction` (RefactorExtract):
+
+            namespace Test {
+                @EntryPoint()
+                function Main() : Int {
+                    let x = 1;
+                    let z = ExtractedFunction(x);
+                    z + x
+                }
+
+                function ExtractedFunction(x : Int) : Int {
+                    let y = x + 1;
+                    let z = y * 2;
+                    z
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn extract_multiple_outputs() {
+    check(
+        r#"
+namespace Test {
+    @EntryPoint()
+    function Main() : Int {
+        ◉let a = 1;
+        mutable b = 2;◉
+        set b += a;
+        b
+    }
+}"#,
+        &expect![[r#"
+            Extract to function `ExtractedFunction` (RefactorExtract):
+
+            namespace Test {
+                @EntryPoint()
+                function Main() : Int {
+                    mutable (a, b) = ExtractedFunction();
+                    set b += a;
+                    b
+                }
+
+                function ExtractedFunction() : (Int, Int) {
+                    let a = 1;
+                    mutable b = 2;
+                    (a, b)
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn extract_trailing_expression() {
+    check(
+        r#"
+namespace Test {
+    @EntryPoint()
+    function Main() : Int {
+        let x = 1;
+        let y = x;
+        ◉y + x◉
+    }
+}"#,
+        &expect![[r#"
+            Extract to function `ExtractedFunction` (RefactorExtract):
+
+            namespace Test {
+                @EntryPoint()
+                function Main() : Int {
+                    let x = 1;
+                    let y = x;
+                    ExtractedFunction(y, x)
+                }
+
+                function ExtractedFunction(y : Int, x : Int) : Int {
+                    y + x
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn extract_keeps_functors_and_qubit_allocation() {
+    check(
+        r#"
+namespace Test {
+    open FakeStdLib;
+    @EntryPoint()
+    operation Main() : Unit {
+        Foo();
+    }
+    operation Foo() : Unit is Adj + Ctl {
+        ◉use aux = Qubit();
+        FakeCtlAdj();◉
+    }
+}"#,
+        &expect![[r#"
+            Extract to operation `ExtractedOperation` (RefactorExtract):
+
+            namespace Test {
+                open FakeStdLib;
+                @EntryPoint()
+                operation Main() : Unit {
+                    Foo();
+                }
+                operation Foo() : Unit is Adj + Ctl {
+                    ExtractedOperation();
+                }
+
+                operation ExtractedOperation() : Unit is Adj + Ctl {
+                    use aux = Qubit();
+                    FakeCtlAdj();
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn extract_from_within_block_requires_adjoint() {
+    check(
+        r#"
+namespace Test {
+    open FakeStdLib;
+    @EntryPoint()
+    operation Main() : Unit {
+        within {
+            ◉FakeCtlAdj();◉
+        } apply {
+            Fake();
+        }
+    }
+}"#,
+        &expect![[r#"
+            Extract to operation `ExtractedOperation` (RefactorExtract):
+
+            namespace Test {
+                open FakeStdLib;
+                @EntryPoint()
+                operation Main() : Unit {
+                    within {
+                        ExtractedOperation();
+                    } apply {
+                        Fake();
+                    }
+                }
+
+                operation ExtractedOperation() : Unit is Adj {
+                    FakeCtlAdj();
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn extract_avoids_existing_names() {
+    check(
+        r#"
+namespace Test {
+    @EntryPoint()
+    function Main() : Unit {
+        ◉let x = 1;◉
+    }
+    function ExtractedFunction() : Unit {}
+}"#,
+        &expect![[r#"
+            Extract to function `ExtractedFunction1` (RefactorExtract):
+
+            namespace Test {
+                @EntryPoint()
+                function Main() : Unit {
+                    ExtractedFunction1();
+                }
+
+                function ExtractedFunction1() : Unit {
+                    let x = 1;
+                }
+                function ExtractedFunction() : Unit {}
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn no_action_for_partial_statement() {
+    check(
+        r#"
+namespace Test {
+    @EntryPoint()
+    function Main() : Int {
+        let x = ◉1;
+        let y = 2;◉
+        x + y
+    }
+}"#,
+        &expect![[r#""#]],
+    );
+}
+
+#[test]
+fn no_action_when_assigning_outer_variable() {
+    check(
+        r#"
+namespace Test {
+    @EntryPoint()
+    function Main() : Int {
+        mutable x = 1;
+        ◉set x += 1;◉
+        x
+    }
+}"#,
+        &expect![[r#""#]],
+    );
+}
+
+#[test]
+fn no_action_with_return() {
+    check(
+        r#"
+namespace Test {
+    @EntryPoint()
+    function Main() : Int {
+        let b = true;
+        ◉if b {
+            return 1;
+        }◉
+        0
+    }
+}"#,
+        &expect![[r#""#]],
+    );
+}
+
+#[test]
+fn no_action_for_qubit_used_after_selection() {
+    check(
+        r#"
+namespace Test {
+    open FakeStdLib;
+    @EntryPoint()
+    operation Main() : Unit {
+        ◉use q = Qubit();◉
+        Fake();
+        Reset(q);
+    }
+    operation Reset(q : Qubit) : Unit {}
+}"#,
+        &expect![[r#""#]],
+    );
+}
+
+#[test]
+fn generate_specializations_for_body_block() {
+    check(
+        r#"
+namespace Test {
+    open FakeStdLib;
+    @EntryPoint()
+    operation Main() : Unit {
+        use q = Qubit();
+        Foo(q);
+    }
+    operation ◉Foo◉(q : Qubit) : Unit is Adj + Ctl {
+        FakeCtlAdj();
+        within {
+            FakeCtlAdj();
+        } apply {
+            FakeCtlAdj();
+        }
+    }
+}"#,
+        &expect![[r#"
+            Add specializations to `Foo` (RefactorRewrite):
+
+            namespace Test {
+                open FakeStdLib;
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    Foo(q);
+                }
+                operation Foo(q : Qubit) : Unit is Adj + Ctl {
+                    body (...) {
+                        FakeCtlAdj();
+                        within {
+                            FakeCtlAdj();
+                        } apply {
+                            FakeCtlAdj();
+                        }
+                    }
+                    adjoint (...) {}
+                    controlled (ctls, ...) {}
+                    controlled adjoint (ctls, ...) {}
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn generate_specializations_avoids_parameter_names() {
+    check(
+        r#"
+namespace Test {
+    open FakeStdLib;
+    @EntryPoint()
+    operation Main() : Unit {
+        use q = Qubit();
+        Foo([q], 0);
+    }
+    operation Foo(ctls : Qubit[], i : Int) : Unit is Ctl◉◉ {}
+}"#,
+        &expect![[r#"
+            Add specializations to `Foo` (RefactorRewrite):
+
+            namespace Test {
+                open FakeStdLib;
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    Foo([q], 0);
+                }
+                operation Foo(ctls : Qubit[], i : Int) : Unit is Ctl {
+                    body (...) {}
+                    controlled (ctls1, ...) {}
+                }
+            }
+            Organize opens (SourceOrganizeImports):
+
+            namespace Test {
+                @EntryPoint()
+                operation Main() : Unit {
+                    use q = Qubit();
+                    Foo([q], 0);
+                }
+                operation Foo(ctls : Qubit[], i : Int) : Unit is Ctl {}
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn generate_specializations_after_body_spec() {
+    check(
+        r#"
+namespace Test {
+    open FakeStdLib;
+    @EntryPoint()
+    operation Main() : Unit {
+        Foo();
+    }
+    ◉operation Foo() : Unit is Adj◉ {
+        body (...) {
+            FakeCtlAdj();
+        }
+    }
+}"#,
+        &expect![[r#"
+            Add specializations to `Foo` (RefactorRewrite):
+
+            namespace Test {
+                open FakeStdLib;
+                @EntryPoint()
+                operation Main() : Unit {
+                    Foo();
+                }
+                operation Foo() : Unit is Adj {
+                    body (...) {
+                        FakeCtlAdj();
+                    }
+                    adjoint (...) {}
+                }
+            }
+        "#]],
+    );
+}
+
+#[test]
+fn no_specializations_action_when_declared() {
+    check(
+        r#"
+namespace Test {
+    open FakeStdLib;
+    @EntryPoint()
+    operation Main() : Unit {
+        Foo();
+    }
+    operation ◉Foo◉() : Unit is Adj {
+        body (...) {
+            FakeCtlAdj();
+        }
+        adjoint self;
+    }
+}"#,
+        &expect![[r#""#]],
+    );
+}
+
+#[test]
+fn no_specializations_action_without_functors() {
+    check(
+        r#"
+namespace Test {
+    open FakeStdLib;
+    @EntryPoint()
+    operation ◉Main◉() : Unit {
+        Fake();
+    }
+}"#,
+        &expect![[r#""#]],
+    );
+}
+
+#[test]
+fn organize_opens_removes_unused_and_duplicates_and_sorts() {
+    check(
+        r#"
+namespace A {
+    function Foo() : Unit {}
+}
+namespace B {
+    open FakeStdLib;
+    open A;
+    open Test;
+    open A;
+    @EntryPoint()
+    operation ◉Main◉() : Unit {
+        Foo();
+        Fake();
+    }
+}
+namespace Test {}"#,
+        &expect![[r#"
+            Organize opens (SourceOrganizeImports):
+
+            namespace A {
+                function Foo() : Unit {}
+            }
+            namespace B {
+                open A;
+                open FakeStdLib;
+                @EntryPoint()
+                operation Main() : Unit {
+                    Foo();
+                    Fake();
+                }
+            }
+            namespace Test {}
+        "#]],
+    );
+}
+
+#[test]
+fn no_organize_opens_action_when_organized() {
+    check(
+        r#"
+namespace A {
+    function Foo() : Unit {}
+}
+namespace B {
+    open A;
+    open FakeStdLib;
+    @EntryPoint()
+    operation ◉Main◉() : Unit {
+        Foo();
+        Fake();
+    }
+}"#,
+        &expect![[r#""#]],
+    );
+}
@@ -4,18 +4,19 @@
 use log::trace;
 use qsc::{
     ast,
-    compile::{self, Error},
+    compile::{self, Error, ErrorKind},
     display::Lookup,
     error::WithSource,
+    hints::{is_analyzable, Analysis},
     hir::{self, PackageId},
     incremental::Compiler,
     line_column::{Encoding, Position},
     resolve,
     target::Profile,
-    CompileUnit, LanguageFeatures, PackageStore, PackageType, SourceMap, Span,
+    CompileUnit, LanguageFeatures, PackageStore, PackageType, ParseCache, SourceMap, Span,
 };
 use qsc_linter::LintConfig;
-use std::sync::Arc;
+use std::{cell::OnceCell, sync::Arc};
 
 /// Represents an immutable compilation state that can be used
 /// to implement language service features.
@@ -28,6 +29,14 @@ pub(crate) struct Compilation {
     pub user_package_id: PackageId,
     pub errors: Vec<Error>,
     pub kind: CompilationKind,
+    /// The target profile the user package was compiled for.
+    pub target_profile: Profile,
+    /// The ASTs of the user sources, which the next compilation of the
+    /// same project starts from so that edits only parse the edited items again.
+    pub parse_cache: ParseCache,
+    /// The runtime capabilities analysis of the package store, run the first
+    /// time it's needed and then shared by the warnings, hover and inlay hints.
+    pub analysis: OnceCell<Option<Analysis>>,
 }
 
 #[derive(Debug)]
@@ -43,13 +52,18 @@ pub(crate) enum CompilationKind {
 }
 
 impl Compilation {
-    /// Creates a new `Compilation` by compiling sources.
+    /// Creates a new `Compilation` by compiling sources, starting from
+    /// the ASTs in the parse cache of a previous compilation.
+    /// The capability warnings aren't included, since they need the runtime
+    /// capabilities analysis, see `add_capability_warnings`.
     pub(crate) fn new(
         sources: &[(Arc<str>, Arc<str>)],
         package_type: PackageType,
         target_profile: Profile,
         language_features: LanguageFeatures,
         lints_config: &[LintConfig],
+        config: &[Arc<str>],
+        mut parse_cache: ParseCache,
     ) -> Self {
         if sources.len() == 1 {
             trace!("compiling single-file document {}", sources[0].0);
@@ -57,25 +71,30 @@ impl Compilation {
             trace!("compiling package with {} sources", sources.len());
         }
 
-        let source_map = SourceMap::new(sources.iter().map(|(x, y)| (x.clone(), y.clone())), None);
+        let source_map = SourceMap::new(sources.iter().map(|(x, y)| (x.clone(), y.clone())), None)
+            .with_config(config.iter().cloned());
 
         let mut package_store = PackageStore::new(compile::core());
         let std_package_id =
             package_store.insert(compile::std(&package_store, target_profile.into()));
 
-        let (unit, mut errors) = compile::compile(
+        let (unit, mut errors) = compile::compile_with_parse_cache(
             &package_store,
             &[std_package_id],
             source_map,
+            &mut parse_cache,
+            // Statements outside of callables are reported by the linter, which can suggest how
+            // to fix them, rather than as a parse error.
+            true,
             package_type,
             target_profile.into(),
             language_features,
         );
 
-        let lints = qsc::linter::run_lints(&unit, Some(lints_config));
+        let lints = qsc::linter::run_lints(&package_store, &unit, Some(lints_config));
         let mut lints = lints
             .into_iter()
-            .map(|lint| WithSource::from_map(&unit.sources, qsc::compile::ErrorKind::Lint(lint)))
+            .map(|lint| WithSource::from_map(&unit.sources, ErrorKind::Lint(lint)))
             .collect();
         errors.append(&mut lints);
 
@@ -86,6 +105,9 @@ impl Compilation {
             user_package_id: package_id,
             errors,
             kind: CompilationKind::OpenProject,
+            target_profile,
+            parse_cache,
+            analysis: OnceCell::new(),
         }
     }
 
@@ -123,12 +145,17 @@ impl Compilation {
 
         let (package_store, package_id) = compiler.into_package_store();
 
-        Self {
+        let mut compilation = Self {
             package_store,
             user_package_id: package_id,
             errors,
             kind: CompilationKind::Notebook,
-        }
+            target_profile,
+            parse_cache: ParseCache::default(),
+            analysis: OnceCell::new(),
+        };
+        compilation.add_capability_warnings();
+        compilation
     }
 
     /// Gets the `CompileUnit` associated with user (non-library) code.
@@ -201,15 +228,22 @@ impl Compilation {
             .sources
             .iter()
             .map(|source| (source.name.clone(), source.contents.clone()));
+        let config = self.user_unit().sources.config().to_vec();
 
         let new = match self.kind {
-            CompilationKind::OpenProject => Self::new(
-                &sources.collect::<Vec<_>>(),
-                package_type,
-                target_profile,
-                language_features,
-                lints_config,
-            ),
+            CompilationKind::OpenProject => {
+                let mut new = Self::new(
+                    &sources.collect::<Vec<_>>(),
+                    package_type,
+                    target_profile,
+                    language_features,
+                    lints_config,
+                    &config,
+                    std::mem::take(&mut self.parse_cache),
+                );
+                new.add_capability_warnings();
+                new
+            }
             CompilationKind::Notebook => {
                 Self::new_notebook(sources, target_profile, language_features)
             }
@@ -217,6 +251,51 @@ impl Compilation {
         self.package_store = new.package_store;
         self.user_package_id = new.user_package_id;
         self.errors = new.errors;
+        self.target_profile = new.target_profile;
+        self.parse_cache = new.parse_cache;
+        self.analysis = new.analysis;
+    }
+
+    /// Gets the runtime capabilities analysis of the compilation, running it the first time it's
+    /// needed. Returns `None` when the compilation has errors that prevent the analysis.
+    pub(crate) fn analysis(&self) -> Option<&Analysis> {
+        self.analysis
+            .get_or_init(|| is_analyzable(&self.errors).then(|| Analysis::new(&self.package_store)))
+            .as_ref()
+    }
+
+    /// Runs the runtime capabilities analysis and adds its warnings to the errors of the compilation.
+    pub(crate) fn add_capability_warnings(&mut self) {
+        let mut warnings = self.capability_warnings();
+        self.errors.append(&mut warnings);
+    }
+
+    /// Produces warnings for the constructs of the user package that need capabilities beyond the
+    /// target profile, along with the cycles of operations, which need higher-level constructs.
+    /// This way the mismatch shows up as the code is being written instead of when QIR is generated.
+    fn capability_warnings(&self) -> Vec<Error> {
+        if self.target_profile == Profile::Unrestricted {
+            return Vec::new();
+        }
+        let Some(analysis) = self.analysis() else {
+            return Vec::new();
+        };
+
+        let sources = &self.user_unit().sources;
+        let mut warnings = analysis
+            .requirements(self.user_package_id, self.target_profile.into())
+            .into_iter()
+            .flat_map(|callable| callable.unsupported)
+            .map(|construct| WithSource::from_map(sources, ErrorKind::Capability(construct)))
+            .collect::<Vec<_>>();
+        warnings.extend(
+            analysis
+                .call_cycles(self.user_package_id)
+                .into_iter()
+                .filter(|cycle| cycle.has_operation)
+                .map(|cycle| WithSource::from_map(sources, ErrorKind::CallCycle(cycle))),
+        );
+        warnings
     }
 }
 
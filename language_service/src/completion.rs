@@ -4,15 +4,19 @@
 #[cfg(test)]
 mod tests;
 
+use crate::code_action::{controls_name, signature_end};
 use crate::compilation::{Compilation, CompilationKind};
 use crate::protocol::{CompletionItem, CompletionItemKind, CompletionList, TextEdit};
-use crate::qsc_utils::{into_range, span_contains};
+use crate::qsc_utils::{into_range, span_contains, span_touches};
 use qsc::ast::visit::{self, Visitor};
+use qsc::ast::{self, CallableBody, CallableKind, ExprKind, StmtKind};
 use qsc::display::{CodeDisplay, Lookup};
-use qsc::hir::{ItemKind, Package, PackageId, Visibility};
+use qsc::hir::ty::{FunctorSetValue, Udt, UdtDef, UdtDefKind};
+use qsc::hir::{Functor, ItemKind, Package, PackageId, Visibility};
 use qsc::line_column::{Encoding, Position, Range};
 use qsc::resolve::{Local, LocalKind};
 use rustc_hash::FxHashSet;
+use std::fmt::Write;
 use std::rc::Rc;
 
 const PRELUDE: [&str; 3] = [
@@ -44,6 +48,7 @@ pub(crate) fn get_completions(
         opens: vec![],
         start_of_namespace: None,
         current_namespace_name: None,
+        specializations: None,
     };
     context_finder.visit_package(user_ast_package);
 
@@ -75,6 +80,11 @@ pub(crate) fn get_completions(
 
     let mut builder = CompletionListBuilder::new();
 
+    // Specializations go first, since they are all that can be declared between them
+    if let Some((decl_span, declared)) = &context_finder.specializations {
+        builder.push_specializations(compilation, *decl_span, declared);
+    }
+
     match context_finder.context {
         Context::Namespace => {
             // Include "open", "operation", etc
@@ -105,6 +115,7 @@ pub(crate) fn get_completions(
             builder.push_locals(compilation, offset, true, true);
             builder.push_stmt_keywords();
             builder.push_expr_keywords();
+            builder.push_snippets();
             builder.push_types();
             builder.push_globals(
                 compilation,
@@ -131,6 +142,7 @@ pub(crate) fn get_completions(
                 // Things that go in a block
                 builder.push_stmt_keywords();
                 builder.push_expr_keywords();
+                builder.push_snippets();
                 builder.push_types();
                 builder.push_globals(
                     compilation,
@@ -151,6 +163,45 @@ pub(crate) fn get_completions(
     }
 }
 
+/// The qualification needed to refer to an item in the namespace, or the edit that opens the
+/// namespace instead.
+fn qualify(
+    namespace: &Rc<str>,
+    opens: &[(Rc<str>, Option<Rc<str>>)],
+    insert_open_at: Option<Range>,
+    current_namespace_name: Option<&Rc<str>>,
+    indent: &str,
+) -> (Option<Rc<str>>, Option<Vec<TextEdit>>) {
+    if current_namespace_name == Some(namespace) {
+        return (None, None);
+    }
+    // open is an option of option of Rc<str>
+    // the first option tells if it found an open with the namespace name
+    // the second, nested option tells if that open has an alias
+    let open = opens.iter().find_map(
+        |(name, alias)| {
+            if name == namespace {
+                Some(alias)
+            } else {
+                None
+            }
+        },
+    );
+    match open {
+        Some(alias) => (alias.clone(), None),
+        None => match insert_open_at {
+            Some(start) => (
+                None,
+                Some(vec![TextEdit {
+                    new_text: format!("open {namespace};{indent}"),
+                    range: start,
+                }]),
+            ),
+            None => (Some(namespace.clone()), None),
+        },
+    }
+}
+
 fn get_first_non_whitespace_in_source(compilation: &Compilation, package_offset: u32) -> u32 {
     let source = compilation
         .user_unit()
@@ -210,7 +261,14 @@ impl CompletionListBuilder {
     }
 
     fn push_item_decl_keywords(&mut self) {
-        static ITEM_KEYWORDS: [&str; 5] = ["operation", "open", "internal", "function", "newtype"];
+        static ITEM_KEYWORDS: [&str; 6] = [
+            "operation",
+            "open",
+            "internal",
+            "export",
+            "function",
+            "newtype",
+        ];
 
         self.push_completions(
             ITEM_KEYWORDS
@@ -220,7 +278,7 @@ impl CompletionListBuilder {
     }
 
     fn push_attributes(&mut self) {
-        static ATTRIBUTES: [&str; 2] = ["@EntryPoint()", "@Config()"];
+        static ATTRIBUTES: [&str; 3] = ["@EntryPoint()", "@Config()", "@Test()"];
 
         self.push_completions(
             ATTRIBUTES
@@ -265,7 +323,7 @@ impl CompletionListBuilder {
         opens: &[(Rc<str>, Option<Rc<str>>)],
         insert_open_range: Option<Range>,
         current_namespace_name: &Option<Rc<str>>,
-        indent: &String,
+        indent: &str,
     ) {
         let core = &compilation
             .package_store
@@ -300,6 +358,8 @@ impl CompletionListBuilder {
         }
 
         self.push_completions(Self::get_namespaces(core));
+
+        self.push_completions(Self::get_aliases(opens));
     }
 
     fn push_locals(
@@ -309,20 +369,82 @@ impl CompletionListBuilder {
         include_terms: bool,
         include_tys: bool,
     ) {
+        let locals = compilation.user_unit().ast.locals.get_all_at_offset(offset);
+        let constructors = locals
+            .iter()
+            .filter(|_| include_terms)
+            .filter_map(|candidate| local_constructor(candidate, compilation));
         self.push_sorted_completions(
-            compilation
-                .user_unit()
-                .ast
-                .locals
-                .get_all_at_offset(offset)
+            locals
                 .iter()
                 .filter_map(|candidate| {
                     local_completion(candidate, compilation, include_terms, include_tys)
                 })
+                .chain(constructors)
                 .map(|item| (item, 0)),
         );
     }
 
+    fn push_snippets(&mut self) {
+        self.push_completions(
+            [CompletionItem {
+                insert_text: Some("within {\n\t$1\n} apply {\n\t$0\n}".to_string()),
+                ..CompletionItem::new("within … apply".to_string(), CompletionItemKind::Snippet)
+            }]
+            .into_iter(),
+        );
+    }
+
+    /// Specializations that the operation supports but doesn't declare yet.
+    fn push_specializations(
+        &mut self,
+        compilation: &Compilation,
+        decl_span: qsc::Span,
+        declared: &[ast::Spec],
+    ) {
+        let Some(decl) =
+            compilation
+                .user_unit()
+                .package
+                .items
+                .values()
+                .find_map(|item| match &item.kind {
+                    ItemKind::Callable(decl) if decl.span == decl_span => Some(decl),
+                    _ => None,
+                })
+        else {
+            return;
+        };
+        let ctls = controls_name(decl);
+        let specs = [
+            (ast::Spec::Body, true, "body (...)".to_string()),
+            (
+                ast::Spec::Adj,
+                decl.functors.contains(&Functor::Adj),
+                "adjoint (...)".to_string(),
+            ),
+            (
+                ast::Spec::Ctl,
+                decl.functors.contains(&Functor::Ctl),
+                format!("controlled ({ctls}, ...)"),
+            ),
+            (
+                ast::Spec::CtlAdj,
+                decl.functors == FunctorSetValue::CtlAdj,
+                format!("controlled adjoint ({ctls}, ...)"),
+            ),
+        ];
+        self.push_completions(
+            specs
+                .into_iter()
+                .filter(|(spec, supported, _)| *supported && !declared.contains(spec))
+                .map(|(_, _, label)| CompletionItem {
+                    insert_text: Some(format!("{label} {{\n\t$0\n}}")),
+                    ..CompletionItem::new(label, CompletionItemKind::Snippet)
+                }),
+        );
+    }
+
     fn push_stmt_keywords(&mut self) {
         static STMT_KEYWORDS: [&str; 5] = ["let", "return", "use", "mutable", "borrow"];
 
@@ -387,7 +509,7 @@ impl CompletionListBuilder {
         opens: &'a [(Rc<str>, Option<Rc<str>>)],
         insert_open_at: Option<Range>,
         current_namespace_name: Option<Rc<str>>,
-        indent: &'a String,
+        indent: &'a str,
     ) -> impl Iterator<Item = (CompletionItem, u32)> + 'a {
         let package = &compilation
             .package_store
@@ -423,72 +545,56 @@ impl CompletionListBuilder {
                                 }
                             }
                         }
-                        return match &i.kind {
+                        let (name, detail, kind, insert_text, sort_group) = match &i.kind {
                             ItemKind::Callable(callable_decl) => {
                                 let name = callable_decl.name.name.as_ref();
-                                let detail =
-                                    Some(display.hir_callable_decl(callable_decl).to_string());
-                                // Everything that starts with a __ goes last in the list
-                                let sort_group = u32::from(name.starts_with("__"));
-                                let mut additional_edits = vec![];
-                                let mut qualification: Option<Rc<str>> = None;
-                                match &current_namespace_name {
-                                    Some(curr_ns) if *curr_ns == namespace.name => {}
-                                    _ => {
-                                        // open is an option of option of Rc<str>
-                                        // the first option tells if it found an open with the namespace name
-                                        // the second, nested option tells if that open has an alias
-                                        let open = opens.iter().find_map(|(name, alias)| {
-                                            if *name == namespace.name {
-                                                Some(alias)
-                                            } else {
-                                                None
-                                            }
-                                        });
-                                        qualification = match open {
-                                            Some(alias) => alias.clone(),
-                                            None => match insert_open_at {
-                                                Some(start) => {
-                                                    additional_edits.push(TextEdit {
-                                                        new_text: format!(
-                                                            "open {};{}",
-                                                            namespace.name.clone(),
-                                                            indent,
-                                                        ),
-                                                        range: start,
-                                                    });
-                                                    None
-                                                }
-                                                None => Some(namespace.name.clone()),
-                                            },
-                                        }
-                                    }
-                                }
-
-                                let additional_text_edits = if additional_edits.is_empty() {
-                                    None
-                                } else {
-                                    Some(additional_edits)
-                                };
-
-                                let label = if let Some(qualification) = qualification {
-                                    format!("{qualification}.{name}")
-                                } else {
-                                    name.to_owned()
-                                };
-                                Some((
-                                    CompletionItem {
-                                        label,
-                                        kind: CompletionItemKind::Function,
-                                        sort_text: None, // This will get filled in during `push_sorted_completions`
-                                        detail,
-                                        additional_text_edits,
-                                    },
-                                    sort_group,
-                                ))
+                                (
+                                    name.to_string(),
+                                    display.hir_callable_decl(callable_decl).to_string(),
+                                    CompletionItemKind::Function,
+                                    None,
+                                    // Everything that starts with a __ goes last in the list
+                                    u32::from(name.starts_with("__")),
+                                )
                             }
-                            _ => None,
+                            ItemKind::Ty(_, udt) => {
+                                let (name, insert_text) = udt_constructor(udt);
+                                (
+                                    name,
+                                    display.hir_udt(udt).to_string(),
+                                    CompletionItemKind::Snippet,
+                                    Some(insert_text),
+                                    0,
+                                )
+                            }
+                            ItemKind::Namespace(..) => return None,
+                        };
+                        let (qualification, additional_text_edits) = qualify(
+                            &namespace.name,
+                            opens,
+                            insert_open_at,
+                            current_namespace_name.as_ref(),
+                            indent,
+                        );
+
+                        let (label, insert_text) = match qualification {
+                            Some(qualification) => (
+                                format!("{qualification}.{name}"),
+                                insert_text.map(|text| format!("{qualification}.{text}")),
+                            ),
+                            None => (name, insert_text),
                         };
+                        return Some((
+                            CompletionItem {
+                                label,
+                                kind,
+                                sort_text: None, // This will get filled in during `push_sorted_completions`
+                                detail: Some(detail),
+                                insert_text,
+                                additional_text_edits,
+                            },
+                            sort_group,
+                        ));
                     }
                 }
             }
@@ -514,6 +620,7 @@ impl CompletionListBuilder {
                         kind: CompletionItemKind::Function,
                         sort_text: None, // This will get filled in during `push_sorted_completions`
                         detail,
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                     sort_group,
@@ -536,6 +643,19 @@ impl CompletionListBuilder {
             _ => None,
         })
     }
+
+    /// The aliases of the open statements in scope, which qualify the items of the namespaces
+    /// they open.
+    fn get_aliases(
+        opens: &[(Rc<str>, Option<Rc<str>>)],
+    ) -> impl Iterator<Item = CompletionItem> + '_ {
+        opens.iter().filter_map(|(namespace, alias)| {
+            alias.as_ref().map(|alias| CompletionItem {
+                detail: Some(namespace.to_string()),
+                ..CompletionItem::new(alias.to_string(), CompletionItemKind::Module)
+            })
+        })
+    }
 }
 
 fn local_completion(
@@ -593,16 +713,83 @@ fn local_completion(
         kind,
         sort_text: None,
         detail,
+        insert_text: None,
+        additional_text_edits: None,
+    })
+}
+
+/// A snippet that calls the constructor of a locally declared type.
+fn local_constructor(candidate: &Local, compilation: &Compilation) -> Option<CompletionItem> {
+    let LocalKind::Item(item_id) = &candidate.kind else {
+        return None;
+    };
+    let (item, _, _) = compilation.resolve_item_relative_to_user_package(item_id);
+    let ItemKind::Ty(_, udt) = &item.kind else {
+        return None;
+    };
+    let (label, insert_text) = udt_constructor(udt);
+    Some(CompletionItem {
+        label,
+        kind: CompletionItemKind::Snippet,
+        sort_text: None,
+        detail: Some(CodeDisplay { compilation }.hir_udt(udt).to_string()),
+        insert_text: Some(insert_text),
         additional_text_edits: None,
     })
 }
 
+/// The label and snippet of a call to the constructor of the type, with a placeholder for each
+/// field of its definition, e.g. `Complex(${1:Real}, ${2:Imag})`.
+fn udt_constructor(udt: &Udt) -> (String, String) {
+    fn args(def: &UdtDef, label: &mut String, snippet: &mut String, tab_stop: &mut usize) {
+        match &def.kind {
+            UdtDefKind::Field(field) => {
+                let placeholder = match &field.name {
+                    Some(name) => name.to_string(),
+                    None => field.ty.display(),
+                };
+                *tab_stop += 1;
+                label.push_str(&placeholder);
+                let _ = write!(snippet, "${{{tab_stop}:{placeholder}}}");
+            }
+            UdtDefKind::Tuple(defs) => {
+                for (i, def) in defs.iter().enumerate() {
+                    if i > 0 {
+                        label.push_str(", ");
+                        snippet.push_str(", ");
+                    }
+                    let nested = matches!(def.kind, UdtDefKind::Tuple(_));
+                    if nested {
+                        label.push('(');
+                        snippet.push('(');
+                    }
+                    args(def, label, snippet, tab_stop);
+                    if nested {
+                        label.push(')');
+                        snippet.push(')');
+                    }
+                }
+            }
+        }
+    }
+
+    let mut label = format!("{}(", udt.name);
+    let mut snippet = label.clone();
+    args(&udt.definition, &mut label, &mut snippet, &mut 0);
+    label.push(')');
+    snippet.push(')');
+    (label, snippet)
+}
+
 struct ContextFinder {
     offset: u32,
     context: Context,
     opens: Vec<(Rc<str>, Option<Rc<str>>)>,
     start_of_namespace: Option<u32>,
     current_namespace_name: Option<Rc<str>>,
+    /// The span of the operation whose specializations can be declared at the offset, along with
+    /// the specializations it already declares.
+    specializations: Option<(qsc::Span, Vec<ast::Spec>)>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -648,6 +835,10 @@ impl Visitor<'_> for ContextFinder {
             // context will get overwritten by visit_block
             // if the offset is inside the actual body
             self.context = Context::CallableSignature;
+            if decl.kind == CallableKind::Operation {
+                self.specializations =
+                    declarable_specs(decl, self.offset).map(|declared| (decl.span, declared));
+            }
             visit::walk_callable_decl(self, decl);
         }
     }
@@ -658,3 +849,25 @@ impl Visitor<'_> for ContextFinder {
         }
     }
 }
+
+/// The specializations declared by the operation, if the offset is at a place in its body where
+/// another specialization can be declared.
+fn declarable_specs(decl: &ast::CallableDecl, offset: u32) -> Option<Vec<ast::Spec>> {
+    match &*decl.body {
+        CallableBody::Block(block) => {
+            // The body is empty, or the only statement is the word being completed
+            let empty = match &*block.stmts {
+                [] => true,
+                [stmt] => {
+                    span_touches(stmt.span, offset)
+                        && matches!(&*stmt.kind, StmtKind::Expr(expr) if matches!(&*expr.kind, ExprKind::Path(_)))
+                }
+                _ => false,
+            };
+            (empty && span_contains(block.span, offset) && offset > block.span.lo).then(Vec::new)
+        }
+        CallableBody::Specs(specs) => (offset > signature_end(decl)
+            && !specs.iter().any(|spec| span_contains(spec.span, offset)))
+        .then(|| specs.iter().map(|spec| spec.spec).collect()),
+    }
+}
@@ -117,6 +117,7 @@ fn ignore_unstable_namespace() {
                             "1101FakeStdLib",
                         ),
                         detail: None,
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -144,11 +145,12 @@ fn ignore_unstable_callable() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0700Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -196,11 +198,12 @@ fn ignore_internal_callable() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0700Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -225,11 +228,12 @@ fn ignore_internal_callable() {
                         label: "Foo",
                         kind: Function,
                         sort_text: Some(
-                            "0600Foo",
+                            "0800Foo",
                         ),
                         detail: Some(
                             "operation Foo() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -238,11 +242,12 @@ fn ignore_internal_callable() {
                         label: "Baz",
                         kind: Function,
                         sort_text: Some(
-                            "0600Baz",
+                            "0800Baz",
                         ),
                         detail: Some(
                             "operation Baz() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -270,11 +275,12 @@ fn in_block_contains_std_functions_from_open_namespace() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0700Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -283,11 +289,12 @@ fn in_block_contains_std_functions_from_open_namespace() {
                         label: "FakeWithParam",
                         kind: Function,
                         sort_text: Some(
-                            "0700FakeWithParam",
+                            "0900FakeWithParam",
                         ),
                         detail: Some(
                             "operation FakeWithParam(x : Int) : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -296,11 +303,12 @@ fn in_block_contains_std_functions_from_open_namespace() {
                         label: "FakeCtlAdj",
                         kind: Function,
                         sort_text: Some(
-                            "0700FakeCtlAdj",
+                            "0900FakeCtlAdj",
                         ),
                         detail: Some(
                             "operation FakeCtlAdj() : Unit is Adj + Ctl",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -327,11 +335,12 @@ fn in_block_contains_std_functions() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0700Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -356,11 +365,12 @@ fn in_block_contains_std_functions() {
                         label: "FakeWithParam",
                         kind: Function,
                         sort_text: Some(
-                            "0700FakeWithParam",
+                            "0900FakeWithParam",
                         ),
                         detail: Some(
                             "operation FakeWithParam(x : Int) : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -385,11 +395,12 @@ fn in_block_contains_std_functions() {
                         label: "FakeCtlAdj",
                         kind: Function,
                         sort_text: Some(
-                            "0700FakeCtlAdj",
+                            "0900FakeCtlAdj",
                         ),
                         detail: Some(
                             "operation FakeCtlAdj() : Unit is Adj + Ctl",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -495,11 +506,12 @@ fn in_block_no_auto_open() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0700Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -526,11 +538,44 @@ fn in_block_with_alias() {
                         label: "Alias.Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0700Alias.Fake",
+                            "0900Alias.Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
+                        additional_text_edits: None,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn alias_as_qualifier() {
+    check(
+        indoc! {r#"
+    namespace Test {
+        open FakeStdLib as Alias;
+        operation Foo() : Unit {
+            ↘
+        }
+    }"#},
+        &["Alias"],
+        &expect![[r#"
+            [
+                Some(
+                    CompletionItem {
+                        label: "Alias",
+                        kind: Module,
+                        sort_text: Some(
+                            "1401Alias",
+                        ),
+                        detail: Some(
+                            "FakeStdLib",
+                        ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -559,11 +604,12 @@ fn in_block_from_other_namespace() {
                         label: "Foo",
                         kind: Function,
                         sort_text: Some(
-                            "0600Foo",
+                            "0800Foo",
                         ),
                         detail: Some(
                             "operation Foo() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -611,11 +657,12 @@ fn auto_open_multiple_files() {
                         label: "FooOperation",
                         kind: Function,
                         sort_text: Some(
-                            "0600FooOperation",
+                            "0800FooOperation",
                         ),
                         detail: Some(
                             "operation FooOperation() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -663,6 +710,7 @@ fn in_block_nested_op() {
                         detail: Some(
                             "operation Foo() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -712,6 +760,7 @@ fn in_namespace_contains_open() {
                             "0102open",
                         ),
                         detail: None,
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -738,6 +787,7 @@ fn top_level_contains_namespace() {
                             "0101namespace",
                         ),
                         detail: None,
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -765,6 +815,7 @@ fn attributes() {
                             "0201@EntryPoint()",
                         ),
                         detail: None,
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -790,11 +841,12 @@ fn stdlib_udt() {
                         label: "TakesUdt",
                         kind: Function,
                         sort_text: Some(
-                            "0700TakesUdt",
+                            "0900TakesUdt",
                         ),
                         detail: Some(
                             "function TakesUdt(input : Udt) : Udt",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -839,6 +891,7 @@ fn notebook_top_level() {
                             "0201operation",
                         ),
                         detail: None,
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -847,9 +900,10 @@ fn notebook_top_level() {
                         label: "namespace",
                         kind: Keyword,
                         sort_text: Some(
-                            "1301namespace",
+                            "1501namespace",
                         ),
                         detail: None,
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -861,6 +915,7 @@ fn notebook_top_level() {
                             "0301let",
                         ),
                         detail: None,
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -869,11 +924,12 @@ fn notebook_top_level() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0800Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -915,11 +971,12 @@ fn notebook_top_level_global() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0800Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -963,11 +1020,12 @@ fn notebook_top_level_namespace_already_open_for_global() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0800Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -994,11 +1052,12 @@ fn notebook_block() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0700Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -1023,9 +1082,10 @@ fn notebook_block() {
                         label: "let",
                         kind: Keyword,
                         sort_text: Some(
-                            "0201let",
+                            "0301let",
                         ),
                         detail: None,
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -1049,11 +1109,12 @@ fn notebook_auto_open_start_of_cell_empty() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0800Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -1093,11 +1154,12 @@ fn notebook_auto_open_start_of_cell() {
                         label: "Fake",
                         kind: Function,
                         sort_text: Some(
-                            "0800Fake",
+                            "0900Fake",
                         ),
                         detail: Some(
                             "operation Fake() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: Some(
                             [
                                 TextEdit {
@@ -1147,6 +1209,7 @@ fn local_vars() {
                         detail: Some(
                             "bar : Int",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -1180,6 +1243,7 @@ fn local_items() {
                         detail: Some(
                             "operation Foo() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -1193,6 +1257,7 @@ fn local_items() {
                         detail: Some(
                             "operation Bar() : Unit",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -1206,6 +1271,7 @@ fn local_items() {
                         detail: Some(
                             "newtype Custom = String",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -1231,9 +1297,10 @@ fn type_params() {
                         label: "'T",
                         kind: TypeParameter,
                         sort_text: Some(
-                            "0100'T",
+                            "0200'T",
                         ),
                         detail: None,
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -1289,6 +1356,7 @@ fn callable_params() {
                         detail: Some(
                             "foo : Int",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -1302,6 +1370,7 @@ fn callable_params() {
                         detail: Some(
                             "bar : Custom",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -1338,6 +1407,7 @@ fn local_var_in_callable_parent_scope() {
                         detail: Some(
                             "baz : Int",
                         ),
+                        insert_text: None,
                         additional_text_edits: None,
                     },
                 ),
@@ -1389,3 +1459,292 @@ fn local_var_and_open_shadowing_rules() {
         "#]],
     );
 }
+
+#[test]
+fn within_apply_snippet() {
+    check(
+        r#"
+        namespace Test {
+            operation Foo() : Unit {
+                ↘
+            }
+        }"#,
+        &["within … apply"],
+        &expect![[r#"
+            [
+                Some(
+                    CompletionItem {
+                        label: "within … apply",
+                        kind: Snippet,
+                        sort_text: Some(
+                            "0501within … apply",
+                        ),
+                        detail: None,
+                        insert_text: Some(
+                            "within {\n\t$1\n} apply {\n\t$0\n}",
+                        ),
+                        additional_text_edits: None,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn specialization_snippets_in_empty_body() {
+    check(
+        r#"
+        namespace Test {
+            operation Foo(ctls : Qubit[]) : Unit is Adj + Ctl {
+                con↘
+            }
+        }"#,
+        &[
+            "body (...)",
+            "adjoint (...)",
+            "controlled (ctls1, ...)",
+            "controlled adjoint (ctls1, ...)",
+        ],
+        &expect![[r#"
+            [
+                Some(
+                    CompletionItem {
+                        label: "body (...)",
+                        kind: Snippet,
+                        sort_text: Some(
+                            "0101body (...)",
+                        ),
+                        detail: None,
+                        insert_text: Some(
+                            "body (...) {\n\t$0\n}",
+                        ),
+                        additional_text_edits: None,
+                    },
+                ),
+                Some(
+                    CompletionItem {
+                        label: "adjoint (...)",
+                        kind: Snippet,
+                        sort_text: Some(
+                            "0102adjoint (...)",
+                        ),
+                        detail: None,
+                        insert_text: Some(
+                            "adjoint (...) {\n\t$0\n}",
+                        ),
+                        additional_text_edits: None,
+                    },
+                ),
+                Some(
+                    CompletionItem {
+                        label: "controlled (ctls1, ...)",
+                        kind: Snippet,
+                        sort_text: Some(
+                            "0103controlled (ctls1, ...)",
+                        ),
+                        detail: None,
+                        insert_text: Some(
+                            "controlled (ctls1, ...) {\n\t$0\n}",
+                        ),
+                        additional_text_edits: None,
+                    },
+                ),
+                Some(
+                    CompletionItem {
+                        label: "controlled adjoint (ctls1, ...)",
+                        kind: Snippet,
+                        sort_text: Some(
+                            "0104controlled adjoint (ctls1, ...)",
+                        ),
+                        detail: None,
+                        insert_text: Some(
+                            "controlled adjoint (ctls1, ...) {\n\t$0\n}",
+                        ),
+                        additional_text_edits: None,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn specialization_snippets_between_specs() {
+    check(
+        r#"
+        namespace Test {
+            operation Foo() : Unit is Adj {
+                body (...) {}
+                ↘
+            }
+        }"#,
+        &["body (...)", "adjoint (...)", "controlled (ctls, ...)"],
+        &expect![[r#"
+            [
+                None,
+                Some(
+                    CompletionItem {
+                        label: "adjoint (...)",
+                        kind: Snippet,
+                        sort_text: Some(
+                            "0101adjoint (...)",
+                        ),
+                        detail: None,
+                        insert_text: Some(
+                            "adjoint (...) {\n\t$0\n}",
+                        ),
+                        additional_text_edits: None,
+                    },
+                ),
+                None,
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn no_specialization_snippets_in_function() {
+    check(
+        r#"
+        namespace Test {
+            function Foo() : Unit {
+                ↘
+            }
+        }"#,
+        &["body (...)"],
+        &expect![[r#"
+            [
+                None,
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn no_specialization_snippets_after_statements() {
+    check(
+        r#"
+        namespace Test {
+            operation Foo() : Unit is Adj {
+                let x = 1;
+                ↘
+            }
+        }"#,
+        &["adjoint (...)"],
+        &expect![[r#"
+            [
+                None,
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn udt_constructor_snippet() {
+    check_project(
+        &[
+            (
+                "udt.qs",
+                r#"
+        namespace Udts {
+            newtype Pair = (First : Int, (Double, Last : Bool));
+        }"#,
+            ),
+            (
+                "test.qs",
+                r#"
+        namespace Test {
+            operation Foo() : Unit {
+                ↘
+            }
+        }"#,
+            ),
+        ],
+        &["Pair(First, (Double, Last))"],
+        &expect![[r#"
+            [
+                Some(
+                    CompletionItem {
+                        label: "Pair(First, (Double, Last))",
+                        kind: Snippet,
+                        sort_text: Some(
+                            "0800Pair(First, (Double, Last))",
+                        ),
+                        detail: Some(
+                            "newtype Pair = (First: Int, (Double, Last: Bool))",
+                        ),
+                        insert_text: Some(
+                            "Pair(${1:First}, (${2:Double}, ${3:Last}))",
+                        ),
+                        additional_text_edits: Some(
+                            [
+                                TextEdit {
+                                    new_text: "open Udts;\n            ",
+                                    range: Range {
+                                        start: Position {
+                                            line: 2,
+                                            column: 12,
+                                        },
+                                        end: Position {
+                                            line: 2,
+                                            column: 12,
+                                        },
+                                    },
+                                },
+                            ],
+                        ),
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn local_udt_constructor_snippet() {
+    check(
+        r#"
+        namespace Test {
+            operation Foo() : Unit {
+                newtype Complex = (Real : Double, Imag : Double);
+                ↘
+            }
+        }"#,
+        &["Complex", "Complex(Real, Imag)"],
+        &expect![[r#"
+            [
+                Some(
+                    CompletionItem {
+                        label: "Complex",
+                        kind: Interface,
+                        sort_text: Some(
+                            "0100Complex",
+                        ),
+                        detail: Some(
+                            "newtype Complex = (Real: Double, Imag: Double)",
+                        ),
+                        insert_text: None,
+                        additional_text_edits: None,
+                    },
+                ),
+                Some(
+                    CompletionItem {
+                        label: "Complex(Real, Imag)",
+                        kind: Snippet,
+                        sort_text: Some(
+                            "0100Complex(Real, Imag)",
+                        ),
+                        detail: Some(
+                            "newtype Complex = (Real: Double, Imag: Double)",
+                        ),
+                        insert_text: Some(
+                            "Complex(${1:Real}, ${2:Imag})",
+                        ),
+                        additional_text_edits: None,
+                    },
+                ),
+            ]
+        "#]],
+    );
+}
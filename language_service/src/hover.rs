@@ -10,8 +10,10 @@ use crate::protocol::Hover;
 use crate::qsc_utils::into_range;
 use qsc::ast::visit::Visitor;
 use qsc::display::{parse_doc_for_param, parse_doc_for_summary, CodeDisplay};
+use qsc::hints::ComputeInfo;
 use qsc::line_column::{Encoding, Position, Range};
-use qsc::{ast, hir, Span};
+use qsc::target::Profile;
+use qsc::{ast, hir, RuntimeCapabilityFlags, Span};
 use std::fmt::Display;
 use std::rc::Rc;
 
@@ -34,7 +36,67 @@ pub(crate) fn get_hover(
 
     let mut locator = Locator::new(&mut hover_visitor, offset, compilation);
     locator.visit_package(user_ast_package);
-    hover_visitor.hover
+    hover_visitor.hover.map(|hover| Hover {
+        contents: with_compute_info(compilation, offset, hover.contents),
+        span: hover.span,
+    })
+}
+
+/// Appends the runtime capabilities analysis results for the code at `offset`, so that the hover explains why the code
+/// can or can't run on the target. Nothing is added when targeting unrestricted capabilities, since all code can run
+/// there, or when the compilation has errors that prevent the analysis.
+fn with_compute_info(compilation: &Compilation, offset: u32, contents: String) -> String {
+    if compilation.target_profile == Profile::Unrestricted {
+        return contents;
+    }
+    let Some(analysis) = compilation.analysis() else {
+        return contents;
+    };
+
+    let span = Span {
+        lo: offset,
+        hi: offset,
+    };
+    match analysis.compute_info(compilation.user_package_id, span) {
+        Some(info) => format!(
+            "{contents}---\n{}\n",
+            display_compute_info(info, compilation.target_profile)
+        ),
+        None => contents,
+    }
+}
+
+fn display_compute_info(info: ComputeInfo, target_profile: Profile) -> String {
+    let kind = if info.dynamic {
+        "dynamic"
+    } else if info.quantum {
+        "quantum"
+    } else {
+        "classical"
+    };
+    let mut lines = vec![format!("compute kind: {kind}")];
+    if !info.runtime_features.is_empty() {
+        let names = info
+            .runtime_features
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        lines.push(format!("runtime features: {}", names.join(", ")));
+    }
+    let unsupported =
+        info.runtime_features.runtime_capabilities() - RuntimeCapabilityFlags::from(target_profile);
+    if !unsupported.is_empty() {
+        let names = unsupported
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        lines.push(format!(
+            "unsupported by the {} profile: {}",
+            target_profile.to_str(),
+            names.join(", ")
+        ));
+    }
+    lines.join("\n\n")
 }
 
 enum LocalKind {
@@ -9,7 +9,7 @@ use crate::test_utils::{
 };
 use expect_test::{expect, Expect};
 use indoc::indoc;
-use qsc::line_column::Encoding;
+use qsc::{line_column::Encoding, target::Profile};
 
 /// Asserts that the hover text at the given cursor position matches the expected hover text.
 /// The cursor position is indicated by a `↘` marker in the source text.
@@ -23,6 +23,18 @@ fn check(source_with_markers: &str, expect: &Expect) {
     expect.assert_eq(&actual.contents);
 }
 
+/// Asserts that the hover text at the given cursor position matches the expected hover text when the
+/// compilation targets the Base profile.
+fn check_with_base_profile(source_with_markers: &str, expect: &Expect) {
+    let (mut compilation, cursor_position, target_spans) =
+        compile_with_fake_stdlib_and_markers(source_with_markers);
+    compilation.target_profile = Profile::Base;
+    let actual = get_hover(&compilation, "<source>", cursor_position, Encoding::Utf8)
+        .expect("Expected a hover.");
+    assert_eq!(&actual.span, &target_spans[0]);
+    expect.assert_eq(&actual.contents);
+}
+
 /// Asserts that there is no hover for the given test case.
 fn check_none(source_with_markers: &str) {
     let (compilation, cursor_position, _) =
@@ -1252,3 +1264,80 @@ fn notebook_callable_defined_in_later_cell() {
         ("cell2", "operation Callee() : Unit {}"),
     ]);
 }
+
+#[test]
+fn dynamic_local_ref_with_base_profile() {
+    check_with_base_profile(
+        indoc! {r#"
+        namespace Test {
+            operation Meas(q : Qubit) : Result {
+                body intrinsic;
+            }
+            operation Foo() : Unit {
+                use q = Qubit();
+                let r = Meas(q);
+                let b = ◉↘r◉ == One;
+            }
+        }
+    "#},
+        &expect![[r#"
+            local
+            ```qsharp
+            r : Result
+            ```
+            ---
+            compute kind: dynamic
+        "#]],
+    );
+}
+
+#[test]
+fn local_needing_capabilities_with_base_profile() {
+    check_with_base_profile(
+        indoc! {r#"
+        namespace Test {
+            operation Meas(q : Qubit) : Result {
+                body intrinsic;
+            }
+            operation Foo() : Unit {
+                use q = Qubit();
+                let r = Meas(q);
+                let ◉↘b◉ = r == One;
+            }
+        }
+    "#},
+        &expect![[r#"
+            local
+            ```qsharp
+            b : Bool
+            ```
+            ---
+            compute kind: quantum
+
+            runtime features: UseOfDynamicBool
+
+            unsupported by the Base profile: ForwardBranching
+        "#]],
+    );
+}
+
+#[test]
+fn classical_local_with_base_profile() {
+    check_with_base_profile(
+        indoc! {r#"
+        namespace Test {
+            function Foo() : Unit {
+                let ◉↘a◉ = 3;
+            }
+        }
+    "#},
+        &expect![[r#"
+            local
+            ```qsharp
+            a : Int
+            ```
+            ---
+            compute kind: classical
+        "#]],
+    );
+}
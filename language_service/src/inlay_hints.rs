@@ -0,0 +1,182 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    compilation::Compilation,
+    protocol::{InlayHint, InlayHintKind, InlayHintsConfig},
+    qsc_utils::{into_range, is_known, span_contains},
+};
+use qsc::{
+    ast::{
+        self,
+        visit::{walk_pat, walk_qubit_init, walk_stmt, Visitor},
+    },
+    hints::HintKind,
+    line_column::Encoding,
+    resolve::Res,
+    Span,
+};
+use rustc_hash::FxHashMap;
+
+pub(crate) fn get_inlay_hints(
+    compilation: &Compilation,
+    source_name: &str,
+    config: InlayHintsConfig,
+    position_encoding: Encoding,
+) -> Vec<InlayHint> {
+    let user_unit = compilation.user_unit();
+    let source_span = compilation.package_span_of_source(source_name);
+
+    // The analysis needs a package that can be lowered, so there are no analysis
+    // hints until the errors that prevent it are fixed.
+    let analysis_hints = compilation
+        .analysis()
+        .map(|analysis| analysis.hints(compilation.user_package_id))
+        .unwrap_or_default();
+
+    let mut hints = Vec::new();
+    for hint in analysis_hints {
+        let kind = match hint.kind {
+            HintKind::DynamicBinding if config.dynamic_bindings => InlayHintKind::DynamicBinding,
+            HintKind::RequiredCapabilities if config.required_capabilities => {
+                InlayHintKind::RequiredCapabilities
+            }
+            _ => continue,
+        };
+        hints.push((hint.span, hint.label, kind));
+    }
+
+    let mut collector = HintCollector {
+        compilation,
+        config,
+        constants: FxHashMap::default(),
+        hints: Vec::new(),
+    };
+    collector.visit_package(&user_unit.ast.package);
+    for (span, label, kind) in collector.hints {
+        // A dynamic binding hint already shows the type of the binding.
+        let is_dynamic = hints.iter().any(|(hint_span, _, hint_kind)| {
+            *hint_span == span && *hint_kind == InlayHintKind::DynamicBinding
+        });
+        if !(kind == InlayHintKind::InferredType && is_dynamic) {
+            hints.push((span, label, kind));
+        }
+    }
+
+    hints.sort_by_key(|(span, _, _)| span.hi);
+    hints
+        .into_iter()
+        .filter(|(span, _, _)| span_contains(source_span, span.lo))
+        .map(|(span, label, kind)| InlayHint {
+            // Hints are shown right after the binding, call or allocation they annotate.
+            position: into_range(position_encoding, span, &user_unit.sources).end,
+            label,
+            kind,
+        })
+        .collect()
+}
+
+/// Collects the hints that come from the type checker, along with the lengths of
+/// qubit array allocations.
+struct HintCollector<'a> {
+    compilation: &'a Compilation,
+    config: InlayHintsConfig,
+    /// The values of the immutable integer bindings that are known statically.
+    constants: FxHashMap<ast::NodeId, i64>,
+    hints: Vec<(Span, String, InlayHintKind)>,
+}
+
+impl HintCollector<'_> {
+    /// Evaluates an integer expression made of literals, known bindings and arithmetic.
+    fn eval(&self, expr: &ast::Expr) -> Option<i64> {
+        match &*expr.kind {
+            ast::ExprKind::Lit(lit) => match **lit {
+                ast::Lit::Int(value) => Some(value),
+                _ => None,
+            },
+            ast::ExprKind::Paren(expr) | ast::ExprKind::UnOp(ast::UnOp::Pos, expr) => {
+                self.eval(expr)
+            }
+            ast::ExprKind::Path(path) => {
+                match self.compilation.user_unit().ast.names.get(path.id) {
+                    Some(Res::Local(id)) => self.constants.get(id).copied(),
+                    _ => None,
+                }
+            }
+            ast::ExprKind::UnOp(ast::UnOp::Neg, expr) => self.eval(expr)?.checked_neg(),
+            ast::ExprKind::BinOp(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.eval(lhs)?, self.eval(rhs)?);
+                match op {
+                    ast::BinOp::Add => lhs.checked_add(rhs),
+                    ast::BinOp::Sub => lhs.checked_sub(rhs),
+                    ast::BinOp::Mul => lhs.checked_mul(rhs),
+                    ast::BinOp::Div => lhs.checked_div(rhs),
+                    ast::BinOp::Mod => lhs.checked_rem(rhs),
+                    ast::BinOp::Exp => lhs.checked_pow(u32::try_from(rhs).ok()?),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for HintCollector<'a> {
+    fn visit_stmt(&mut self, stmt: &'a ast::Stmt) {
+        walk_stmt(self, stmt);
+        if let ast::StmtKind::Local(mutability, pat, expr) = &*stmt.kind {
+            if self.config.inferred_types {
+                InferredTypes(self).visit_pat(pat);
+            }
+            if let (ast::Mutability::Immutable, ast::PatKind::Bind(ident, _)) =
+                (mutability, &*pat.kind)
+            {
+                if let Some(value) = self.eval(expr) {
+                    self.constants.insert(ident.id, value);
+                }
+            }
+        }
+    }
+
+    fn visit_qubit_init(&mut self, init: &'a ast::QubitInit) {
+        match &*init.kind {
+            // The length is already written out when it is a literal.
+            ast::QubitInitKind::Array(size)
+                if self.config.qubit_counts && !matches!(&*size.kind, ast::ExprKind::Lit(_)) =>
+            {
+                if let Some(len) = self.eval(size) {
+                    let label = if len == 1 {
+                        "1 qubit".to_string()
+                    } else {
+                        format!("{len} qubits")
+                    };
+                    self.hints
+                        .push((init.span, label, InlayHintKind::QubitCount));
+                }
+            }
+            _ => {}
+        }
+        walk_qubit_init(self, init);
+    }
+}
+
+/// Collects the hints for the bindings of a pattern that have no type annotation.
+struct InferredTypes<'a, 'b>(&'b mut HintCollector<'a>);
+
+impl<'a> Visitor<'a> for InferredTypes<'a, '_> {
+    fn visit_pat(&mut self, pat: &'a ast::Pat) {
+        if let ast::PatKind::Bind(ident, None) = &*pat.kind {
+            if let Some(ty) = self.0.compilation.user_unit().ast.tys.terms.get(ident.id) {
+                if is_known(ty) {
+                    self.0
+                        .hints
+                        .push((ident.span, ty.display(), InlayHintKind::InferredType));
+                }
+            }
+        }
+        walk_pat(self, pat);
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+#![allow(clippy::needless_raw_string_hashes)]
+
+use super::get_inlay_hints;
+use crate::{
+    protocol::InlayHintsConfig, test_utils::compile_with_fake_stdlib_and_markers_no_cursor,
+    Encoding,
+};
+use expect_test::{expect, Expect};
+
+fn check(source: &str, expect: &Expect) {
+    check_with_config(source, InlayHintsConfig::default(), expect);
+}
+
+fn check_with_config(source: &str, config: InlayHintsConfig, expect: &Expect) {
+    let (compilation, _) = compile_with_fake_stdlib_and_markers_no_cursor(source);
+    let actual = get_inlay_hints(&compilation, "<source>", config, Encoding::Utf8);
+    expect.assert_debug_eq(&actual);
+}
+
+#[test]
+fn dynamic_binding_and_call() {
+    check(
+        r#"
+        namespace Test {
+            operation Measure(q : Qubit) : Result {
+                body intrinsic;
+            }
+            operation Flip(q : Qubit) : Unit {
+                body intrinsic;
+            }
+            operation FlipIf(b : Bool, q : Qubit) : Unit {
+                if b {
+                    Flip(q);
+                }
+            }
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                let r = Measure(q);
+                FlipIf(r == One, q);
+            }
+        }"#,
+        &expect![[r#"
+            [
+                InlayHint {
+                    position: Position {
+                        line: 16,
+                        column: 21,
+                    },
+                    label: "dynamic Result",
+                    kind: DynamicBinding,
+                },
+                InlayHint {
+                    position: Position {
+                        line: 17,
+                        column: 35,
+                    },
+                    label: "requires ForwardBranching",
+                    kind: RequiredCapabilities,
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn no_hints_with_errors() {
+    check_with_config(
+        r#"
+        namespace Test {
+            operation Measure(q : Qubit) : Result {
+                body intrinsic;
+            }
+            @EntryPoint()
+            operation Main() : Unit {
+                use q = Qubit();
+                let r = Measure(q);
+                Undefined();
+            }
+        }"#,
+        InlayHintsConfig {
+            inferred_types: false,
+            ..InlayHintsConfig::default()
+        },
+        &expect![[r#"
+            []
+        "#]],
+    );
+}
+
+#[test]
+fn inferred_types() {
+    check(
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Unit {
+                let (a, b) = (1, [true]);
+                mutable c = 1.0;
+                let d : Int = a;
+                let f = x -> x + a;
+            }
+        }"#,
+        &expect![[r#"
+            [
+                InlayHint {
+                    position: Position {
+                        line: 4,
+                        column: 22,
+                    },
+                    label: "Int",
+                    kind: InferredType,
+                },
+                InlayHint {
+                    position: Position {
+                        line: 4,
+                        column: 25,
+                    },
+                    label: "Bool[]",
+                    kind: InferredType,
+                },
+                InlayHint {
+                    position: Position {
+                        line: 5,
+                        column: 25,
+                    },
+                    label: "Double",
+                    kind: InferredType,
+                },
+                InlayHint {
+                    position: Position {
+                        line: 7,
+                        column: 21,
+                    },
+                    label: "dynamic (Int -> Int)",
+                    kind: DynamicBinding,
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn qubit_counts() {
+    check(
+        r#"
+        namespace Test {
+            @EntryPoint()
+            operation Main() : Unit {
+                let n = 3;
+                use qs = Qubit[2 * n + 1];
+                use (a, b) = (Qubit[n], Qubit[4]);
+                mutable m = 1;
+                use c = Qubit[m];
+            }
+        }"#,
+        &expect![[r#"
+            [
+                InlayHint {
+                    position: Position {
+                        line: 4,
+                        column: 21,
+                    },
+                    label: "Int",
+                    kind: InferredType,
+                },
+                InlayHint {
+                    position: Position {
+                        line: 5,
+                        column: 41,
+                    },
+                    label: "7 qubits",
+                    kind: QubitCount,
+                },
+                InlayHint {
+                    position: Position {
+                        line: 6,
+                        column: 38,
+                    },
+                    label: "3 qubits",
+                    kind: QubitCount,
+                },
+                InlayHint {
+                    position: Position {
+                        line: 7,
+                        column: 25,
+                    },
+                    label: "Int",
+                    kind: InferredType,
+                },
+            ]
+        "#]],
+    );
+}
+
+#[test]
+fn hints_disabled_by_config() {
+    check_with_config(
+        r#"
+        namespace Test {
+            operation Measure(q : Qubit) : Result {
+                body intrinsic;
+            }
+            @EntryPoint()
+            operation Main() : Unit {
+                let n = 1;
+                use qs = Qubit[n];
+                let r = Measure(qs[0]);
+            }
+        }"#,
+        InlayHintsConfig {
+            dynamic_bindings: false,
+            inferred_types: false,
+            ..InlayHintsConfig::default()
+        },
+        &expect![[r#"
+            [
+                InlayHint {
+                    position: Position {
+                        line: 8,
+                        column: 33,
+                    },
+                    label: "1 qubit",
+                    kind: QubitCount,
+                },
+            ]
+        "#]],
+    );
+}
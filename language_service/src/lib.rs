@@ -1,12 +1,15 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+pub mod call_hierarchy;
+pub mod code_action;
 pub mod code_lens;
 mod compilation;
 pub mod completion;
 pub mod definition;
 pub mod format;
 pub mod hover;
+pub mod inlay_hints;
 mod name_locator;
 mod project_system;
 pub mod protocol;
@@ -25,7 +28,8 @@ use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
 use futures_util::StreamExt;
 use log::{trace, warn};
 use protocol::{
-    CodeLens, CompletionList, DiagnosticUpdate, Hover, NotebookMetadata, SignatureHelp, TextEdit,
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, CodeAction, CodeLens,
+    CompletionList, DiagnosticUpdate, Hover, InlayHint, NotebookMetadata, SignatureHelp, TextEdit,
     WorkspaceConfigurationUpdate,
 };
 use qsc::{
@@ -119,6 +123,9 @@ impl LanguageService {
     /// LSP: textDocument/didOpen, textDocument/didChange
     pub fn update_document(&mut self, uri: &str, version: u32, text: &str) {
         trace!("update_document: {uri} {version}");
+        // Let the update worker know that any earlier updates to this
+        // document, including one it may be in the middle of, are stale.
+        self.state.borrow_mut().queue_document_version(uri, version);
         self.send_update(Update::Document {
             uri: uri.into(),
             version,
@@ -132,6 +139,7 @@ impl LanguageService {
     /// LSP: textDocument/didClose
     pub fn close_document(&mut self, uri: &str) {
         trace!("close_document: {uri}");
+        self.state.borrow_mut().forget_document_version(uri);
         self.send_update(Update::CloseDocument { uri: uri.into() });
     }
 
@@ -274,6 +282,73 @@ impl LanguageService {
         )
     }
 
+    /// LSP: textDocument/inlayHint
+    #[must_use]
+    pub fn get_inlay_hints(&self, uri: &str) -> Vec<InlayHint> {
+        let config = self.state.borrow().inlay_hints_config();
+        self.document_op(inlay_hints::get_inlay_hints, "get_inlay_hints", uri, config)
+    }
+
+    /// LSP: textDocument/prepareCallHierarchy
+    #[must_use]
+    pub fn prepare_call_hierarchy(
+        &self,
+        uri: &str,
+        position: Position,
+    ) -> Option<CallHierarchyItem> {
+        self.document_op(
+            call_hierarchy::prepare_call_hierarchy,
+            "prepare_call_hierarchy",
+            uri,
+            position,
+        )
+    }
+
+    /// The callable is identified by the position of its name, or of a reference to it.
+    ///
+    /// LSP: callHierarchy/incomingCalls
+    #[must_use]
+    pub fn get_incoming_calls(
+        &self,
+        uri: &str,
+        position: Position,
+    ) -> Vec<CallHierarchyIncomingCall> {
+        self.document_op(
+            call_hierarchy::get_incoming_calls,
+            "get_incoming_calls",
+            uri,
+            position,
+        )
+    }
+
+    /// The callable is identified by the position of its name, or of a reference to it.
+    ///
+    /// LSP: callHierarchy/outgoingCalls
+    #[must_use]
+    pub fn get_outgoing_calls(
+        &self,
+        uri: &str,
+        position: Position,
+    ) -> Vec<CallHierarchyOutgoingCall> {
+        self.document_op(
+            call_hierarchy::get_outgoing_calls,
+            "get_outgoing_calls",
+            uri,
+            position,
+        )
+    }
+
+    /// LSP: textDocument/codeAction
+    #[must_use]
+    pub fn get_code_actions(&self, uri: &str, range: Range) -> Vec<CodeAction> {
+        self.document_op(
+            code_action::get_code_actions,
+            "get_code_actions",
+            uri,
+            range,
+        )
+    }
+
     /// Executes an operation that takes a document uri, using the current compilation for that document.
     /// All "read" operations should go through this method. This method will borrow the current
     /// compilation state to perform the request.
@@ -1,7 +1,8 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use qsc::line_column::Range;
+use qsc::line_column::{Position, Range};
+use qsc::location::Location;
 use qsc::{compile::Error, target::Profile, LanguageFeatures, PackageType};
 
 /// A change to the workspace configuration
@@ -9,6 +10,7 @@ use qsc::{compile::Error, target::Profile, LanguageFeatures, PackageType};
 pub struct WorkspaceConfigurationUpdate {
     pub target_profile: Option<Profile>,
     pub package_type: Option<PackageType>,
+    pub inlay_hints: Option<InlayHintsConfig>,
 }
 
 #[derive(Debug)]
@@ -30,6 +32,7 @@ pub enum CompletionItemKind {
     Property,
     Variable,
     TypeParameter,
+    Snippet,
 }
 
 #[derive(Debug, Default)]
@@ -43,6 +46,9 @@ pub struct CompletionItem {
     pub kind: CompletionItemKind,
     pub sort_text: Option<String>,
     pub detail: Option<String>,
+    /// The text to insert in place of the label, in snippet syntax with `$1`, `${2:placeholder}`
+    /// and `$0` tab stops.
+    pub insert_text: Option<String>,
     pub additional_text_edits: Option<Vec<TextEdit>>,
 }
 
@@ -54,6 +60,7 @@ impl CompletionItem {
             kind,
             sort_text: None,
             detail: None,
+            insert_text: None,
             additional_text_edits: None,
         }
     }
@@ -65,6 +72,7 @@ impl PartialEq for CompletionItem {
         self.label == other.label
             && self.kind == other.kind
             && self.detail == other.detail
+            && self.insert_text == other.insert_text
             && self.additional_text_edits == other.additional_text_edits
     }
 }
@@ -139,6 +147,92 @@ pub enum CodeLensCommand {
     Circuit(Option<OperationInfo>),
 }
 
+#[derive(Debug, PartialEq)]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: String,
+    pub kind: InlayHintKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InlayHintKind {
+    /// Follows a binding to a value that is only known at runtime.
+    DynamicBinding,
+    /// Follows a call that needs runtime capabilities beyond the Base profile.
+    RequiredCapabilities,
+    /// Follows a binding declared without a type annotation.
+    InferredType,
+    /// Follows a qubit array allocation whose length is known statically.
+    QubitCount,
+}
+
+/// The categories of inlay hints that are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct InlayHintsConfig {
+    pub dynamic_bindings: bool,
+    pub required_capabilities: bool,
+    pub inferred_types: bool,
+    pub qubit_counts: bool,
+}
+
+impl Default for InlayHintsConfig {
+    fn default() -> Self {
+        Self {
+            dynamic_bindings: true,
+            required_capabilities: true,
+            inferred_types: true,
+            qubit_counts: true,
+        }
+    }
+}
+
+/// A callable in a call hierarchy.
+#[derive(Debug, PartialEq)]
+pub struct CallHierarchyItem {
+    pub name: String,
+    /// The namespace the callable is declared in.
+    pub detail: String,
+    /// The location of the whole declaration.
+    pub location: Location,
+    /// The range of the name of the callable, in the same source as `location`.
+    pub selection_range: Range,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CallHierarchyIncomingCall {
+    /// The callable that makes the calls.
+    pub from: CallHierarchyItem,
+    /// The ranges of the calls, in the source of the calling callable.
+    pub from_ranges: Vec<Range>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CallHierarchyOutgoingCall {
+    /// The callable that is called.
+    pub to: CallHierarchyItem,
+    /// The ranges of the calls, in the source of the callable the calls were requested for.
+    pub from_ranges: Vec<Range>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CodeAction {
+    pub title: String,
+    pub kind: CodeActionKind,
+    /// The edits to the document the action was requested for.
+    pub edits: Vec<TextEdit>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CodeActionKind {
+    /// Moves code into a new declaration.
+    RefactorExtract,
+    /// Rewrites a declaration without moving it.
+    RefactorRewrite,
+    /// Removes the unused and duplicate opens of a source and sorts the rest.
+    SourceOrganizeImports,
+}
+
 #[derive(Debug)]
 pub struct OperationInfo {
     pub operation: String,
@@ -4,7 +4,14 @@
 use crate::compilation::Compilation;
 use qsc::line_column::{Encoding, Range};
 use qsc::location::Location;
-use qsc::{ast, hir::PackageId, SourceMap, Span};
+use qsc::{
+    ast,
+    hir::{
+        ty::{Arrow, Ty},
+        PackageId,
+    },
+    SourceMap, Span,
+};
 
 pub(crate) fn span_contains(span: Span, offset: u32) -> bool {
     offset >= span.lo && offset < span.hi
@@ -85,3 +92,17 @@ impl<'a> ast::visit::Visitor<'a> for AstIdentFinder<'a> {
         }
     }
 }
+
+/// Whether the type is fully inferred, so that it can be written in source.
+pub(crate) fn is_known(ty: &Ty) -> bool {
+    match ty {
+        Ty::Array(item) => is_known(item),
+        Ty::Arrow(arrow) => {
+            let Arrow { input, output, .. } = &**arrow;
+            is_known(input) && is_known(output)
+        }
+        Ty::Tuple(items) => items.iter().all(is_known),
+        Ty::Infer(_) | Ty::Err => false,
+        Ty::Param(..) | Ty::Prim(_) | Ty::Udt(..) => true,
+    }
+}
@@ -6,13 +6,14 @@ mod tests;
 
 use crate::compilation::Compilation;
 use crate::name_locator::{Handler, Locator, LocatorContext};
-use crate::qsc_utils::into_range;
+use crate::qsc_utils::{into_location, into_range, span_touches};
 use crate::references::ReferenceFinder;
-use qsc::ast::visit::Visitor;
+use qsc::ast::visit::{walk_item, walk_namespace, walk_path, Visitor};
 use qsc::display::Lookup;
 use qsc::line_column::{Encoding, Position, Range};
 use qsc::location::Location;
 use qsc::{ast, hir, resolve, Span};
+use std::rc::Rc;
 
 pub(crate) fn prepare_rename(
     compilation: &Compilation,
@@ -24,6 +25,22 @@ pub(crate) fn prepare_rename(
         compilation.source_position_to_package_offset(source_name, position, position_encoding);
     let user_ast_package = &compilation.user_unit().ast.package;
 
+    if let Some((span, name)) = namespace_at(compilation, offset) {
+        return is_user_namespace(compilation, &name).then(|| {
+            (
+                into_range(position_encoding, span, &compilation.user_unit().sources),
+                name.to_string(),
+            )
+        });
+    }
+
+    if let Some((span, name, _)) = alias_at(compilation, offset) {
+        return Some((
+            into_range(position_encoding, span, &compilation.user_unit().sources),
+            name.to_string(),
+        ));
+    }
+
     let mut prepare_rename = Rename::new(position_encoding, compilation, true);
     let mut locator = Locator::new(&mut prepare_rename, offset, compilation);
     locator.visit_package(user_ast_package);
@@ -45,6 +62,53 @@ pub(crate) fn get_rename(
         compilation.source_position_to_package_offset(source_name, position, position_encoding);
     let user_ast_package = &compilation.user_unit().ast.package;
 
+    if let Some((_, name)) = namespace_at(compilation, offset) {
+        if !is_user_namespace(compilation, &name) {
+            return vec![];
+        }
+        let mut finder = NamespaceReferences {
+            compilation,
+            name,
+            spans: vec![],
+        };
+        finder.visit_package(user_ast_package);
+        return finder
+            .spans
+            .into_iter()
+            .map(|span| {
+                into_location(
+                    position_encoding,
+                    compilation,
+                    span,
+                    compilation.user_package_id,
+                )
+            })
+            .collect();
+    }
+
+    if let Some((_, name, scope)) = alias_at(compilation, offset) {
+        let mut finder = AliasReferences {
+            compilation,
+            name,
+            scope,
+            current_namespace: None,
+            spans: vec![],
+        };
+        finder.visit_package(user_ast_package);
+        return finder
+            .spans
+            .into_iter()
+            .map(|span| {
+                into_location(
+                    position_encoding,
+                    compilation,
+                    span,
+                    compilation.user_package_id,
+                )
+            })
+            .collect();
+    }
+
     let mut rename = Rename::new(position_encoding, compilation, false);
     let mut locator = Locator::new(&mut rename, offset, compilation);
     locator.visit_package(user_ast_package);
@@ -280,3 +344,210 @@ pub(crate) fn type_param_ident_range(mut range: Range) -> Range {
     range.start.column += 1;
     range
 }
+
+/// Finds the name of the namespace at the offset, which can be in a namespace declaration, an
+/// `open` statement, or the namespace of a qualified path.
+fn namespace_at(compilation: &Compilation, offset: u32) -> Option<(Span, Rc<str>)> {
+    struct NamespaceLocator<'a> {
+        compilation: &'a Compilation,
+        offset: u32,
+        found: Option<(Span, Rc<str>)>,
+    }
+
+    impl<'a> Visitor<'a> for NamespaceLocator<'a> {
+        fn visit_namespace(&mut self, namespace: &'a ast::Namespace) {
+            if span_touches(namespace.name.span, self.offset) {
+                self.found = Some((namespace.name.span, namespace.name.name.clone()));
+            } else {
+                walk_namespace(self, namespace);
+            }
+        }
+
+        fn visit_item(&mut self, item: &'a ast::Item) {
+            match &*item.kind {
+                ast::ItemKind::Open(name, _) if span_touches(name.span, self.offset) => {
+                    self.found = Some((name.span, name.name.clone()));
+                }
+                _ => walk_item(self, item),
+            }
+        }
+
+        fn visit_path(&mut self, path: &'a ast::Path) {
+            match &path.namespace {
+                Some(namespace)
+                    if span_touches(namespace.span, self.offset)
+                        && is_namespace_of(self.compilation, path, &namespace.name) =>
+                {
+                    self.found = Some((namespace.span, namespace.name.clone()));
+                }
+                _ => walk_path(self, path),
+            }
+        }
+    }
+
+    let mut locator = NamespaceLocator {
+        compilation,
+        offset,
+        found: None,
+    };
+    locator.visit_package(&compilation.user_unit().ast.package);
+    locator.found
+}
+
+/// Whether the namespace is only declared in the user package. A namespace that other packages
+/// declare too can't be renamed, since their items would no longer be found under the new name.
+fn is_user_namespace(compilation: &Compilation, name: &str) -> bool {
+    let declares = |package: &hir::Package| {
+        package.items.values().any(
+            |item| matches!(&item.kind, hir::ItemKind::Namespace(ident, _) if &*ident.name == name),
+        )
+    };
+    compilation
+        .package_store
+        .iter()
+        .all(|(id, unit)| (id == compilation.user_package_id) == declares(&unit.package))
+}
+
+/// Whether the path refers to an item that is declared in the namespace, rather than qualifying
+/// the item with an alias.
+fn is_namespace_of(compilation: &Compilation, path: &ast::Path, namespace: &str) -> bool {
+    let Some(resolve::Res::Item(item_id, _)) = compilation.get_res(path.id) else {
+        return false;
+    };
+    let (item, package, _) = compilation.resolve_item_relative_to_user_package(item_id);
+    item.parent
+        .and_then(|parent| package.items.get(parent))
+        .is_some_and(|parent| {
+            matches!(&parent.kind, hir::ItemKind::Namespace(ident, _) if &*ident.name == namespace)
+        })
+}
+
+/// Collects the spans of the references to a namespace: its declarations, the `open` statements
+/// for it, and the paths qualified with it.
+struct NamespaceReferences<'a> {
+    compilation: &'a Compilation,
+    name: Rc<str>,
+    spans: Vec<Span>,
+}
+
+impl<'a> Visitor<'a> for NamespaceReferences<'a> {
+    fn visit_namespace(&mut self, namespace: &'a ast::Namespace) {
+        if namespace.name.name == self.name {
+            self.spans.push(namespace.name.span);
+        }
+        walk_namespace(self, namespace);
+    }
+
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        if let ast::ItemKind::Open(name, _) = &*item.kind {
+            if name.name == self.name {
+                self.spans.push(name.span);
+            }
+        }
+        walk_item(self, item);
+    }
+
+    fn visit_path(&mut self, path: &'a ast::Path) {
+        if let Some(namespace) = &path.namespace {
+            if namespace.name == self.name && is_namespace_of(self.compilation, path, &self.name) {
+                self.spans.push(namespace.span);
+            }
+        }
+        walk_path(self, path);
+    }
+}
+
+/// Finds the alias at the offset, which can be in the `open` statement that declares it or the
+/// qualifier of a path, along with the span of the namespace that the alias is declared in.
+fn alias_at(compilation: &Compilation, offset: u32) -> Option<(Span, Rc<str>, Option<Span>)> {
+    struct AliasLocator<'a> {
+        compilation: &'a Compilation,
+        offset: u32,
+        current_namespace: Option<Span>,
+        found: Option<(Span, Rc<str>, Option<Span>)>,
+    }
+
+    impl<'a> Visitor<'a> for AliasLocator<'a> {
+        fn visit_namespace(&mut self, namespace: &'a ast::Namespace) {
+            let outer = self.current_namespace.replace(namespace.span);
+            walk_namespace(self, namespace);
+            self.current_namespace = outer;
+        }
+
+        fn visit_item(&mut self, item: &'a ast::Item) {
+            match &*item.kind {
+                ast::ItemKind::Open(_, Some(alias)) if span_touches(alias.span, self.offset) => {
+                    self.found = Some((alias.span, alias.name.clone(), self.current_namespace));
+                }
+                _ => walk_item(self, item),
+            }
+        }
+
+        fn visit_path(&mut self, path: &'a ast::Path) {
+            match &path.namespace {
+                Some(alias)
+                    if span_touches(alias.span, self.offset)
+                        && is_alias_of(self.compilation, path, &alias.name) =>
+                {
+                    self.found = Some((alias.span, alias.name.clone(), self.current_namespace));
+                }
+                _ => walk_path(self, path),
+            }
+        }
+    }
+
+    let mut locator = AliasLocator {
+        compilation,
+        offset,
+        current_namespace: None,
+        found: None,
+    };
+    locator.visit_package(&compilation.user_unit().ast.package);
+    locator.found
+}
+
+/// Whether the path is qualified with an alias, rather than the namespace that declares the item
+/// it refers to.
+fn is_alias_of(compilation: &Compilation, path: &ast::Path, alias: &str) -> bool {
+    matches!(compilation.get_res(path.id), Some(resolve::Res::Item(..)))
+        && !is_namespace_of(compilation, path, alias)
+}
+
+/// Collects the spans of the references to an alias: the `open` statement that declares it, and
+/// the paths qualified with it in the same namespace.
+struct AliasReferences<'a> {
+    compilation: &'a Compilation,
+    name: Rc<str>,
+    scope: Option<Span>,
+    current_namespace: Option<Span>,
+    spans: Vec<Span>,
+}
+
+impl<'a> Visitor<'a> for AliasReferences<'a> {
+    fn visit_namespace(&mut self, namespace: &'a ast::Namespace) {
+        let outer = self.current_namespace.replace(namespace.span);
+        walk_namespace(self, namespace);
+        self.current_namespace = outer;
+    }
+
+    fn visit_item(&mut self, item: &'a ast::Item) {
+        if let ast::ItemKind::Open(_, Some(alias)) = &*item.kind {
+            if alias.name == self.name && self.current_namespace == self.scope {
+                self.spans.push(alias.span);
+            }
+        }
+        walk_item(self, item);
+    }
+
+    fn visit_path(&mut self, path: &'a ast::Path) {
+        if let Some(alias) = &path.namespace {
+            if alias.name == self.name
+                && self.current_namespace == self.scope
+                && is_alias_of(self.compilation, path, &self.name)
+            {
+                self.spans.push(alias.span);
+            }
+        }
+        walk_path(self, path);
+    }
+}
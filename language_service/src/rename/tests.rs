@@ -6,7 +6,8 @@
 use super::{get_rename, prepare_rename};
 use crate::{
     test_utils::{
-        compile_notebook_with_fake_stdlib_and_markers, compile_with_fake_stdlib_and_markers,
+        compile_notebook_with_fake_stdlib_and_markers,
+        compile_project_with_fake_stdlib_and_markers, compile_with_fake_stdlib_and_markers,
     },
     Encoding,
 };
@@ -28,6 +29,18 @@ fn check(source_with_markers: &str) {
     assert!(target_spans.len() == actual.len());
 }
 
+/// Asserts that the rename locations given at the cursor position, in any of the sources of the
+/// project, match the expected rename locations.
+fn check_project(sources_with_markers: &[(&str, &str)]) {
+    let (compilation, cursor_uri, cursor_position, target_locations) =
+        compile_project_with_fake_stdlib_and_markers(sources_with_markers);
+    let actual = get_rename(&compilation, &cursor_uri, cursor_position, Encoding::Utf8);
+    for target in &target_locations {
+        assert!(actual.contains(target), "{target:?} not in {actual:?}");
+    }
+    assert_eq!(target_locations.len(), actual.len());
+}
+
 /// Asserts that the prepare rename given at the cursor position returns None.
 /// The cursor position is indicated by a `↘` marker in the source text.
 fn assert_no_rename(source_with_markers: &str) {
@@ -242,10 +255,47 @@ fn udt_field_complex_ref() {
 }
 
 #[test]
-fn no_rename_namespace() {
+fn namespace_def() {
+    check(
+        r#"
+        namespace ◉Te↘st◉ {
+            operation Foo() : Unit {}
+        }
+        namespace Other {
+            open ◉Test◉;
+            open ◉Test◉ as T;
+            operation Bar() : Unit {
+                Foo();
+                ◉Test◉.Foo();
+                T.Foo();
+            }
+        }
+    "#,
+    );
+}
+
+#[test]
+fn namespace_qualified_ref() {
+    check(
+        r#"
+        namespace ◉Test.Inner◉ {
+            operation Foo() : Unit {}
+        }
+        namespace Other {
+            operation Bar() : Unit {
+                ◉Test.In↘ner◉.Foo();
+            }
+        }
+    "#,
+    );
+}
+
+#[test]
+fn no_rename_std_namespace() {
     assert_no_rename(
         r#"
-        namespace Te↘st {
+        namespace Test {
+            open FakeSt↘dLib;
             operation Foo() : Unit {}
 
         }
@@ -253,6 +303,97 @@ fn no_rename_namespace() {
     );
 }
 
+#[test]
+fn alias_def() {
+    check(
+        r#"
+        namespace Test {
+            open FakeStdLib as ◉F↘S◉;
+            operation Foo() : Unit {
+                ◉FS◉.Fake();
+                FakeStdLib.Fake();
+            }
+        }
+        namespace Other {
+            open FakeStdLib as FS;
+            operation Bar() : Unit {
+                FS.Fake();
+            }
+        }
+    "#,
+    );
+}
+
+#[test]
+fn alias_ref() {
+    check(
+        r#"
+        namespace Test {
+            open FakeStdLib as ◉FS◉;
+            operation Foo() : Unit {
+                ◉F↘S◉.Fake();
+                ◉FS◉.FakeWithParam(4);
+            }
+        }
+    "#,
+    );
+}
+
+#[test]
+fn project_callable_rename() {
+    check_project(&[
+        (
+            "a.qs",
+            r#"
+        namespace A {
+            operation ◉Fo↘o◉() : Unit {}
+        }
+    "#,
+        ),
+        (
+            "b.qs",
+            r#"
+        namespace B {
+            open A;
+            operation Bar() : Unit {
+                ◉Foo◉();
+                A.◉Foo◉();
+            }
+        }
+    "#,
+        ),
+    ]);
+}
+
+#[test]
+fn project_namespace_rename() {
+    check_project(&[
+        (
+            "a.qs",
+            r#"
+        namespace ◉A◉ {
+            operation Foo() : Unit {}
+        }
+    "#,
+        ),
+        (
+            "b.qs",
+            r#"
+        namespace ◉A◉ {
+            operation Bar() : Unit {}
+        }
+        namespace B {
+            open ◉↘A◉;
+            operation Baz() : Unit {
+                Foo();
+                ◉A◉.Bar();
+            }
+        }
+    "#,
+        ),
+    ]);
+}
+
 #[test]
 fn no_rename_keyword() {
     assert_no_rename(
@@ -6,14 +6,23 @@ mod tests;
 
 use super::compilation::Compilation;
 use super::protocol::{DiagnosticUpdate, NotebookMetadata};
-use crate::protocol::WorkspaceConfigurationUpdate;
+use crate::protocol::{InlayHintsConfig, WorkspaceConfigurationUpdate};
 use log::{error, trace};
 use miette::Diagnostic;
 use qsc::{compile::Error, target::Profile, LanguageFeatures, PackageType};
 use qsc_linter::LintConfig;
 use qsc_project::{FileSystemAsync, JSFileEntry};
 use rustc_hash::{FxHashMap, FxHashSet};
-use std::{cell::RefCell, fmt::Debug, future::Future, mem::take, pin::Pin, rc::Rc, sync::Arc};
+use std::{
+    cell::RefCell,
+    fmt::Debug,
+    future::{poll_fn, Future},
+    mem::take,
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+    task::Poll,
+};
 
 /// the desugared return type of an "async fn"
 type PinnedFuture<T> = Pin<Box<dyn Future<Output = T>>>;
@@ -46,6 +55,14 @@ pub(super) struct CompilationState {
     /// This map doesn't necessarily contain ALL the documents that
     /// make up a compilation - only the ones that are currently open.
     open_documents: FxHashMap<DocumentUri, OpenDocument>,
+    /// The categories of inlay hints the client wants. Unlike the other
+    /// configuration settings, these don't affect compilation.
+    inlay_hints_config: InlayHintsConfig,
+    /// The latest version of each document that the client has sent, which
+    /// may still be waiting in the update queue. An update for an older
+    /// version is superseded, and is abandoned at its next `await` point
+    /// rather than spending time compiling and analyzing a stale document.
+    latest_versions: FxHashMap<DocumentUri, u32>,
 }
 
 type CompilationUri = Arc<str>;
@@ -130,6 +147,7 @@ struct LoadManifestResult {
     sources: Vec<(Arc<str>, Arc<str>)>,
     language_features: LanguageFeatures,
     lints: Vec<LintConfig>,
+    config: Vec<Arc<str>>,
 }
 
 impl<'a> CompilationStateUpdater<'a> {
@@ -165,16 +183,27 @@ impl<'a> CompilationStateUpdater<'a> {
     }
 
     pub(super) async fn update_document(&mut self, uri: &str, version: u32, text: &str) {
+        if self.is_superseded(uri, version) {
+            return;
+        }
+
         let doc_uri: Arc<str> = Arc::from(uri);
         let text: Arc<str> = Arc::from(text);
 
         let project = self.load_manifest(&doc_uri).await;
 
+        // Loading the manifest can read the whole project from the file system,
+        // and newer edits may have arrived in the meantime.
+        if self.is_superseded(uri, version) {
+            return;
+        }
+
         let LoadManifestResult {
             compilation_uri,
             sources,
             language_features,
             lints: lints_config,
+            config,
         } = project.unwrap_or_else(|| {
             // If we are in single file mode, use the file's path as the compilation identifier.
             LoadManifestResult {
@@ -182,6 +211,7 @@ impl<'a> CompilationStateUpdater<'a> {
                 sources: vec![(doc_uri.clone(), text.clone())],
                 language_features: LanguageFeatures::default(),
                 lints: Vec::default(),
+                config: Vec::default(),
             }
         });
 
@@ -212,11 +242,39 @@ impl<'a> CompilationStateUpdater<'a> {
             &compilation_uri,
             language_features,
             &lints_config,
+            &config,
         );
 
+        // The runtime capabilities analysis can take longer than the compilation,
+        // so give newer edits a chance to arrive before running it. The pending
+        // update will compile and analyze the newer version and publish its diagnostics.
+        yield_now().await;
+        if self.is_superseded(uri, version) {
+            return;
+        }
+
+        self.add_capability_warnings(&compilation_uri);
         self.publish_diagnostics();
     }
 
+    /// Checks whether the client has sent a newer version of the document than
+    /// the one being applied. The pending update will compile the newer version,
+    /// so the current one can be skipped. Documents that aren't open yet are
+    /// never skipped, so that a queued `close_document` always has a document to close.
+    fn is_superseded(&self, uri: &str, version: u32) -> bool {
+        let superseded = self.with_state(|state| {
+            state.open_documents.contains_key(uri)
+                && state
+                    .latest_versions
+                    .get(uri)
+                    .is_some_and(|latest| *latest != version)
+        });
+        if superseded {
+            trace!("skipping update for {uri} {version}, a newer version is pending");
+        }
+        superseded
+    }
+
     /// Attempts to resolve a manifest for the given document uri.
     /// If a manifest is found, returns the manifest uri along
     /// with the sources for the project
@@ -234,6 +292,12 @@ impl<'a> CompilationStateUpdater<'a> {
                         .iter()
                         .collect::<LanguageFeatures>(),
                     lints: manifest.manifest.lints.clone(),
+                    config: manifest
+                        .manifest
+                        .config
+                        .iter()
+                        .map(|flag| flag.as_str().into())
+                        .collect(),
                 }),
                 Err(e) => {
                     error!("failed to load manifest: {e:?}, defaulting to single-file mode");
@@ -256,6 +320,7 @@ impl<'a> CompilationStateUpdater<'a> {
         compilation_uri: &Arc<str>,
         language_features: LanguageFeatures,
         lints_config: &[LintConfig],
+        config: &[Arc<str>],
     ) {
         self.with_state_mut(|state| {
             // replace source with one from memory if it exists
@@ -268,12 +333,22 @@ impl<'a> CompilationStateUpdater<'a> {
                 }
             }
 
+            // Start from the ASTs of the previous compilation of the project,
+            // so that an edit to a document only parses the edited item again.
+            let parse_cache = state
+                .compilations
+                .get_mut(compilation_uri)
+                .map(|(compilation, _)| std::mem::take(&mut compilation.parse_cache))
+                .unwrap_or_default();
+
             let compilation = Compilation::new(
                 &sources,
                 self.configuration.package_type,
                 self.configuration.target_profile,
                 language_features,
                 lints_config,
+                config,
+                parse_cache,
             );
 
             state.compilations.insert(
@@ -286,6 +361,15 @@ impl<'a> CompilationStateUpdater<'a> {
         });
     }
 
+    /// Adds the capability warnings to a compilation created by `insert_buffer_aware_compilation`.
+    fn add_capability_warnings(&mut self, compilation_uri: &Arc<str>) {
+        self.with_state_mut(|state| {
+            if let Some((compilation, _)) = state.compilations.get_mut(compilation_uri) {
+                compilation.add_capability_warnings();
+            }
+        });
+    }
+
     pub(super) async fn close_document(&mut self, uri: &str) {
         let project = self.load_manifest(&uri.into()).await;
 
@@ -300,6 +384,7 @@ impl<'a> CompilationStateUpdater<'a> {
                 compilation_uri,
                 language_features,
                 lints: lints_config,
+                config,
             }) = project
             {
                 self.insert_buffer_aware_compilation(
@@ -307,7 +392,9 @@ impl<'a> CompilationStateUpdater<'a> {
                     &compilation_uri,
                     language_features,
                     &lints_config,
+                    &config,
                 );
+                self.add_capability_warnings(&compilation_uri);
             }
         }
 
@@ -474,6 +561,10 @@ impl<'a> CompilationStateUpdater<'a> {
             self.configuration.target_profile = target_profile;
         }
 
+        if let Some(inlay_hints_config) = configuration.inlay_hints {
+            self.with_state_mut(|state| state.inlay_hints_config = inlay_hints_config);
+        }
+
         // Possible optimization: some projects will have overrides for these configurations,
         // so workspace updates won't impact them. We could exclude those projects
         // from recompilation, but we don't right now.
@@ -538,6 +629,21 @@ impl<'a> CompilationStateUpdater<'a> {
 }
 
 impl CompilationState {
+    pub(crate) fn inlay_hints_config(&self) -> InlayHintsConfig {
+        self.inlay_hints_config
+    }
+
+    /// Records the version of a document update that was just queued.
+    pub(crate) fn queue_document_version(&mut self, uri: &str, version: u32) {
+        self.latest_versions.insert(uri.into(), version);
+    }
+
+    /// Forgets the queued version of a document when it's closed, since
+    /// the client may start counting versions again when it's reopened.
+    pub(crate) fn forget_document_version(&mut self, uri: &str) {
+        self.latest_versions.remove(uri);
+    }
+
     pub(crate) fn get_compilation(&self, uri: &str) -> Option<&Compilation> {
         let compilation_uri = &self
             .open_documents
@@ -600,3 +706,19 @@ fn merge_configurations(
             .unwrap_or(workspace_scope.lints_config.clone()),
     }
 }
+
+/// Yields to the executor once, so that updates queued in the meantime are
+/// recorded before the worker continues.
+async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await;
+}
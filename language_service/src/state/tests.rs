@@ -7,7 +7,7 @@
 use super::{CompilationState, CompilationStateUpdater};
 use crate::protocol::{DiagnosticUpdate, NotebookMetadata, WorkspaceConfigurationUpdate};
 use expect_test::{expect, Expect};
-use qsc::{compile::ErrorKind, target::Profile, PackageType};
+use qsc::{compile::ErrorKind, target::Profile, PackageType, RuntimeCapabilityFlags};
 use qsc_project::{EntryType, JSFileEntry, Manifest, ManifestDescriptor};
 use rustc_hash::FxHashMap;
 use std::{cell::RefCell, fmt::Write, future::ready, rc::Rc, sync::Arc};
@@ -150,6 +150,7 @@ async fn close_last_doc_in_project() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
         &expect![[r#"
@@ -284,20 +285,29 @@ async fn compile_error() {
                     [
                         Frontend(
                             Error(
-                                Parse(
-                                    Error(
-                                        Token(
-                                            Eof,
-                                            Ident,
-                                            Span {
-                                                lo: 0,
-                                                hi: 9,
-                                            },
-                                        ),
+                                Resolve(
+                                    NotFound(
+                                        "badsyntax",
+                                        Span {
+                                            lo: 0,
+                                            hi: 9,
+                                        },
                                     ),
                                 ),
                             ),
                         ),
+                        Lint(
+                            Lint {
+                                span: Span {
+                                    lo: 0,
+                                    hi: 9,
+                                },
+                                level: Error,
+                                message: "statements are not allowed outside of callables in a project",
+                                help: "move the statements into an operation marked with `@EntryPoint()`",
+                                edits: [],
+                            },
+                        ),
                     ],
                 ),
             ]
@@ -313,6 +323,7 @@ async fn package_type_update_causes_error() {
     updater.update_configuration(WorkspaceConfigurationUpdate {
         target_profile: None,
         package_type: Some(PackageType::Lib),
+        inlay_hints: None,
     });
 
     updater
@@ -333,6 +344,7 @@ async fn package_type_update_causes_error() {
     updater.update_configuration(WorkspaceConfigurationUpdate {
         target_profile: None,
         package_type: Some(PackageType::Exe),
+        inlay_hints: None,
     });
 
     expect_errors(
@@ -365,6 +377,7 @@ async fn target_profile_update_fixes_error() {
     updater.update_configuration(WorkspaceConfigurationUpdate {
         target_profile: Some(Profile::Base),
         package_type: Some(PackageType::Lib),
+        inlay_hints: None,
     });
 
     updater
@@ -424,6 +437,7 @@ async fn target_profile_update_fixes_error() {
     updater.update_configuration(WorkspaceConfigurationUpdate {
         target_profile: Some(Profile::Unrestricted),
         package_type: None,
+        inlay_hints: None,
     });
 
     expect_errors(
@@ -463,6 +477,7 @@ async fn target_profile_update_causes_error_in_stdlib() {
     updater.update_configuration(WorkspaceConfigurationUpdate {
         target_profile: Some(Profile::Base),
         package_type: None,
+        inlay_hints: None,
     });
 
     expect_errors(
@@ -510,6 +525,62 @@ async fn target_profile_update_causes_error_in_stdlib() {
     );
 }
 
+#[tokio::test]
+async fn base_profile_warns_about_unsupported_capabilities() {
+    let errors = RefCell::new(Vec::new());
+    let mut updater = new_updater(&errors);
+
+    updater.update_configuration(WorkspaceConfigurationUpdate {
+        target_profile: Some(Profile::Base),
+        package_type: Some(PackageType::Lib),
+        inlay_hints: None,
+    });
+
+    updater
+        .update_document(
+            "single/foo.qs",
+            1,
+            r#"namespace Foo { operation Main() : Unit { use q = Qubit(); if M(q) == One { X(q); } } }"#,
+        )
+        .await;
+
+    let received = errors.borrow();
+    let (uri, _, doc_errors) = received.last().expect("diagnostics should be published");
+    assert_eq!(uri, "single/foo.qs");
+    assert!(doc_errors.iter().any(|error| matches!(
+        error,
+        ErrorKind::Capability(construct)
+            if construct.capabilities.contains(RuntimeCapabilityFlags::ForwardBranching)
+    )));
+}
+
+#[tokio::test]
+async fn base_profile_warns_about_cyclic_operations() {
+    let errors = RefCell::new(Vec::new());
+    let mut updater = new_updater(&errors);
+
+    updater.update_configuration(WorkspaceConfigurationUpdate {
+        target_profile: Some(Profile::Base),
+        package_type: Some(PackageType::Lib),
+        inlay_hints: None,
+    });
+
+    updater
+        .update_document(
+            "single/foo.qs",
+            1,
+            r#"namespace Foo { operation Ping() : Unit { Pong(); } operation Pong() : Unit { Ping(); } }"#,
+        )
+        .await;
+
+    let received = errors.borrow();
+    let (_, _, doc_errors) = received.last().expect("diagnostics should be published");
+    assert!(doc_errors.iter().any(|error| matches!(
+        error,
+        ErrorKind::CallCycle(cycle) if cycle.callables.len() == 2
+    )));
+}
+
 #[test]
 fn notebook_document_no_errors() {
     let errors = RefCell::new(Vec::new());
@@ -796,6 +867,7 @@ async fn update_doc_updates_project() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
         &expect![[r#"
@@ -810,10 +882,8 @@ async fn update_doc_updates_project() {
                             Error(
                                 Parse(
                                     Error(
-                                        Token(
-                                            Close(
-                                                Brace,
-                                            ),
+                                        Rule(
+                                            "item",
                                             Ident,
                                             Span {
                                                 lo: 75,
@@ -886,6 +956,7 @@ async fn close_doc_prioritizes_fs() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
         &expect![[r#"
@@ -965,6 +1036,7 @@ async fn delete_manifest() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
     );
@@ -1000,6 +1072,7 @@ async fn delete_manifest() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
     );
@@ -1044,6 +1117,7 @@ async fn delete_manifest_then_close() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
     );
@@ -1105,6 +1179,7 @@ async fn doc_switches_project() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
     );
@@ -1155,6 +1230,7 @@ async fn doc_switches_project() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
     );
@@ -1204,6 +1280,7 @@ async fn doc_switches_project_on_close() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
     );
@@ -1247,6 +1324,7 @@ async fn doc_switches_project_on_close() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
     );
@@ -1303,6 +1381,7 @@ async fn loading_lints_config_from_manifest() {
 }
 
 #[tokio::test]
+#[allow(clippy::too_many_lines)]
 async fn lints_update_after_manifest_change() {
     let this_file_qs = "namespace Foo { operation Main() : Unit { let x = 5 / 0 + (2 ^ 4); } }";
     let fs = FsNode::Dir(
@@ -1350,6 +1429,22 @@ async fn lints_update_after_manifest_change() {
                     level: Error,
                     message: "unnecessary parentheses",
                     help: "remove the extra parentheses for clarity",
+                    edits: [
+                        TextEdit {
+                            new_text: "",
+                            span: Span {
+                                lo: 58,
+                                hi: 59,
+                            },
+                        },
+                        TextEdit {
+                            new_text: "",
+                            span: Span {
+                                lo: 64,
+                                hi: 65,
+                            },
+                        },
+                    ],
                 },
             ),
             Lint(
@@ -1361,6 +1456,7 @@ async fn lints_update_after_manifest_change() {
                     level: Error,
                     message: "attempt to divide by zero",
                     help: "division by zero is not allowed",
+                    edits: [],
                 },
             ),
         ]"#]],
@@ -1392,6 +1488,22 @@ async fn lints_update_after_manifest_change() {
                     level: Warn,
                     message: "unnecessary parentheses",
                     help: "remove the extra parentheses for clarity",
+                    edits: [
+                        TextEdit {
+                            new_text: "",
+                            span: Span {
+                                lo: 58,
+                                hi: 59,
+                            },
+                        },
+                        TextEdit {
+                            new_text: "",
+                            span: Span {
+                                lo: 64,
+                                hi: 65,
+                            },
+                        },
+                    ],
                 },
             ),
             Lint(
@@ -1403,6 +1515,7 @@ async fn lints_update_after_manifest_change() {
                     level: Warn,
                     message: "attempt to divide by zero",
                     help: "division by zero is not allowed",
+                    edits: [],
                 },
             ),
         ]"#]],
@@ -1,7 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::sync::Arc;
+use std::{cell::OnceCell, sync::Arc};
 
 use crate::compilation::{Compilation, CompilationKind};
 use qsc::{
@@ -11,7 +11,7 @@ use qsc::{
     line_column::{Encoding, Position, Range},
     location::Location,
     target::Profile,
-    LanguageFeatures, PackageStore, PackageType, SourceMap, Span,
+    LanguageFeatures, PackageStore, PackageType, ParseCache, SourceMap, Span,
 };
 
 pub(crate) fn compile_with_fake_stdlib_and_markers(
@@ -86,6 +86,9 @@ fn compile_project_with_fake_stdlib_and_markers_cursor_optional(
             user_package_id: package_id,
             kind: CompilationKind::OpenProject,
             errors,
+            target_profile: Profile::Unrestricted,
+            parse_cache: ParseCache::default(),
+            analysis: OnceCell::new(),
         },
         cursor_location,
         target_spans,
@@ -150,6 +153,9 @@ where
         user_package_id: package_id,
         errors,
         kind: CompilationKind::Notebook,
+        target_profile: Profile::Unrestricted,
+        parse_cache: ParseCache::default(),
+        analysis: OnceCell::new(),
     }
 }
 
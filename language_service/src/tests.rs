@@ -5,12 +5,13 @@
 
 use crate::{protocol::DiagnosticUpdate, Encoding, JSFileEntry, LanguageService, UpdateWorker};
 use expect_test::{expect, Expect};
+use futures::poll;
 use qsc::{
     compile::{self, ErrorKind},
     line_column::Position,
 };
 use qsc_project::{EntryType, Manifest, ManifestDescriptor};
-use std::{cell::RefCell, future::ready, sync::Arc};
+use std::{cell::RefCell, future::ready, pin::pin, sync::Arc};
 
 #[tokio::test]
 async fn single_document() {
@@ -53,6 +54,7 @@ async fn single_document() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]]),
     );
@@ -100,6 +102,7 @@ async fn single_document_update() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]]),
     );
@@ -138,6 +141,7 @@ async fn single_document_update() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]]),
     );
@@ -198,11 +202,155 @@ async fn document_in_project() {
                     },
                 ],
                 entry: None,
+                config: [],
             }
         "#]],
     );
 }
 
+#[tokio::test]
+#[allow(clippy::too_many_lines)]
+async fn superseded_document_update_is_skipped() {
+    let received_errors = RefCell::new(Vec::new());
+    let mut ls = LanguageService::new(Encoding::Utf8);
+    let mut worker = create_update_worker(&mut ls, &received_errors);
+
+    ls.update_document("foo.qs", 1, "namespace Foo { }");
+
+    worker.apply_pending().await;
+    received_errors.borrow_mut().clear();
+
+    // The update to another document keeps the two updates to foo.qs from
+    // being deduped in the queue, but version 2 is still stale by the time
+    // it's applied, so its parse error should never be reported.
+    ls.update_document("foo.qs", 2, "namespace Foo {");
+    ls.update_document(
+        "bar.qs",
+        1,
+        "namespace Bar { @EntryPoint() operation Main() : Unit {} }",
+    );
+    ls.update_document(
+        "foo.qs",
+        3,
+        "namespace Foo { @EntryPoint() operation Main() : Unit {} }",
+    );
+
+    worker.apply_pending().await;
+
+    check_errors_and_compilation(
+        &ls,
+        &mut received_errors.borrow_mut(),
+        "foo.qs",
+        &(expect![[r#"
+            [
+                (
+                    "foo.qs",
+                    Some(
+                        1,
+                    ),
+                    [
+                        Pass(
+                            EntryPoint(
+                                NotFound,
+                            ),
+                        ),
+                    ],
+                ),
+                (
+                    "foo.qs",
+                    Some(
+                        3,
+                    ),
+                    [],
+                ),
+            ]
+        "#]]),
+        &(expect![[r#"
+            SourceMap {
+                sources: [
+                    Source {
+                        name: "foo.qs",
+                        contents: "namespace Foo { @EntryPoint() operation Main() : Unit {} }",
+                        offset: 0,
+                    },
+                ],
+                entry: None,
+                config: [],
+            }
+        "#]]),
+    );
+}
+
+#[tokio::test]
+async fn document_update_superseded_during_compilation_is_not_analyzed() {
+    let received_errors = RefCell::new(Vec::new());
+    let mut ls = LanguageService::new(Encoding::Utf8);
+    let mut worker = create_update_worker(&mut ls, &received_errors);
+
+    ls.update_document("foo.qs", 1, "namespace Foo { }");
+
+    worker.apply_pending().await;
+    received_errors.borrow_mut().clear();
+
+    // Version 3 arrives while the worker is compiling version 2, so version 2
+    // should be neither analyzed nor have its parse error reported.
+    ls.update_document("foo.qs", 2, "namespace Foo {");
+    let compiled_version_2 = || {
+        ls.state
+            .borrow()
+            .get_compilation("foo.qs")
+            .is_some_and(|compilation| {
+                let source = compilation.user_unit().sources.find_by_name("foo.qs");
+                source.is_some_and(|source| &*source.contents == "namespace Foo {")
+            })
+    };
+    {
+        let mut apply_pending = pin!(worker.apply_pending());
+        // Step the worker until it has compiled version 2, after which it yields before the analysis.
+        while !compiled_version_2() {
+            assert!(poll!(&mut apply_pending).is_pending());
+            tokio::task::yield_now().await;
+        }
+        ls.update_document(
+            "foo.qs",
+            3,
+            "namespace Foo { @EntryPoint() operation Main() : Unit {} }",
+        );
+        apply_pending.await;
+    }
+    worker.apply_pending().await;
+
+    check_errors_and_compilation(
+        &ls,
+        &mut received_errors.borrow_mut(),
+        "foo.qs",
+        &(expect![[r#"
+            [
+                (
+                    "foo.qs",
+                    Some(
+                        3,
+                    ),
+                    [],
+                ),
+            ]
+        "#]]),
+        &(expect![[r#"
+            SourceMap {
+                sources: [
+                    Source {
+                        name: "foo.qs",
+                        contents: "namespace Foo { @EntryPoint() operation Main() : Unit {} }",
+                        offset: 0,
+                    },
+                ],
+                entry: None,
+                config: [],
+            }
+        "#]]),
+    );
+}
+
 // the below tests test the asynchronous behavior of the language service.
 // we use `get_completions` as a rough analog for all document operations, as
 // they all go through the same `document_op` infrastructure.
@@ -363,7 +511,7 @@ fn create_update_worker<'a>(
                         manifest: Manifest::default(),
                         manifest_dir: ".".into(),
                     }),
-                    "foo.qs" => None,
+                    "foo.qs" | "bar.qs" => None,
                     _ => panic!("unknown file"),
                 }))
                 .await
@@ -40,3 +40,16 @@ fn check_operations_are_equal() {
         ),
     );
 }
+
+#[test]
+fn check_facts_hold() {
+    test_expression(
+        r#"{
+            open Microsoft.Quantum.Diagnostics;
+            Fact(true, "fact should hold");
+            Contradiction(false, "contradiction should hold");
+            EqualityFact((1, "a"), (1, "a"), "tuples should be equal");
+        }"#,
+        &Value::unit(),
+    );
+}
@@ -158,7 +158,11 @@ export function getLanguageServiceWorker(
   return createProxy(worker, wasmModule, languageServiceProtocol);
 }
 
-export { type Dump, type ShotResult } from "./compiler/common.js";
+export {
+  type Dump,
+  type Matrix,
+  type ShotResult,
+} from "./compiler/common.js";
 export { type CompilerState } from "./compiler/compiler.js";
 export { QscEventTarget } from "./compiler/events.js";
 export {
@@ -187,6 +191,8 @@ export type {
   IPosition,
   IRange,
   ILocation,
+  ICallableCapabilities,
+  IUnsupportedConstruct,
   VSDiagnostic,
 } from "../lib/web/qsc_wasm.js";
 export { type IStructStepResult, StepResultId } from "../lib/web/qsc_wasm.js";
@@ -19,6 +19,15 @@ interface DumpMsg {
   stateLatex: string;
 }
 
+// Each matrix output is represented as a list of rows, where each entry is the
+// [real, imag] parts of the complex entry.
+export type Matrix = [number, number][][];
+
+interface MatrixMsg {
+  type: "Matrix";
+  matrix: Matrix;
+}
+
 interface MessageMsg {
   type: "Message";
   message: string;
@@ -29,7 +38,7 @@ interface ResultMsg {
   result: Result;
 }
 
-type EventMsg = ResultMsg | DumpMsg | MessageMsg;
+type EventMsg = ResultMsg | DumpMsg | MatrixMsg | MessageMsg;
 
 function outputAsResult(msg: string): ResultMsg | null {
   try {
@@ -73,12 +82,29 @@ function outputAsDump(msg: string): DumpMsg | null {
   return null;
 }
 
+function outputAsMatrix(msg: string): MatrixMsg | null {
+  try {
+    const obj = JSON.parse(msg);
+    if (obj?.type == "Matrix" && Array.isArray(obj.matrix)) {
+      return obj as MatrixMsg;
+    }
+  } catch {
+    return null;
+  }
+  return null;
+}
+
 export function eventStringToMsg(msg: string): EventMsg | null {
-  return outputAsResult(msg) || outputAsMessage(msg) || outputAsDump(msg);
+  return (
+    outputAsResult(msg) ||
+    outputAsMessage(msg) ||
+    outputAsDump(msg) ||
+    outputAsMatrix(msg)
+  );
 }
 
 export type ShotResult = {
   success: boolean;
   result: string | VSDiagnostic;
-  events: Array<MessageMsg | DumpMsg>;
+  events: Array<MessageMsg | DumpMsg | MatrixMsg>;
 };
@@ -1,7 +1,11 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-import { type VSDiagnostic } from "../../lib/web/qsc_wasm.js";
+import {
+  type ICallableCapabilities,
+  type TargetProfile,
+  type VSDiagnostic,
+} from "../../lib/web/qsc_wasm.js";
 import { log } from "../log.js";
 import {
   IServiceProxy,
@@ -71,6 +75,16 @@ export interface ICompiler {
   ): Promise<string>;
   getEstimates(config: ProgramConfig, params: string): Promise<string>;
 
+  /**
+   * Runs the runtime capabilities analysis on the program, returning the capabilities
+   * each callable needs and the constructs that need capabilities the given target
+   * profile doesn't support.
+   */
+  getRuntimeCapabilities(
+    config: ProgramConfig,
+    profile: TargetProfile,
+  ): Promise<ICallableCapabilities[]>;
+
   checkExerciseSolution(
     userCode: string,
     exerciseSources: string[],
@@ -84,6 +98,12 @@ export type ProgramConfig = {
   sources: [string, string][];
   /** An array of language features to be opted in to in this compilation. */
   languageFeatures?: string[];
+  /**
+   * Whether the program comes from an untrusted source, such as the submissions to a
+   * grading or playground service. Running it is then limited in its qubits, call depth,
+   * statements and output. Terminate the worker to limit the time it runs for.
+   */
+  untrusted?: boolean;
 };
 
 // WebWorker also support being explicitly terminated to tear down the worker thread
@@ -177,6 +197,17 @@ export class Compiler implements ICompiler {
     return this.wasm.get_estimates(sources, params, languageFeatures);
   }
 
+  async getRuntimeCapabilities(
+    { sources, languageFeatures = [] }: ProgramConfig,
+    profile: TargetProfile,
+  ): Promise<ICallableCapabilities[]> {
+    return this.wasm.get_runtime_capabilities(
+      sources,
+      profile,
+      languageFeatures,
+    );
+  }
+
   async getHir(code: string, languageFeatures: string[]): Promise<string> {
     return this.wasm.get_hir(code, languageFeatures);
   }
@@ -189,6 +220,7 @@ export class Compiler implements ICompiler {
   ): Promise<void> {
     let sources;
     let languageFeatures: string[] = [];
+    let untrusted = false;
 
     if (Array.isArray(sourcesOrConfig)) {
       // this is the deprecated API
@@ -197,6 +229,7 @@ export class Compiler implements ICompiler {
       // this is the new API
       sources = sourcesOrConfig.sources;
       languageFeatures = sourcesOrConfig.languageFeatures || [];
+      untrusted = sourcesOrConfig.untrusted || false;
     }
     // All results are communicated as events, but if there is a compiler error (e.g. an invalid
     // entry expression or similar), it may throw on run. The caller should expect this promise
@@ -207,6 +240,7 @@ export class Compiler implements ICompiler {
       (msg: string) => onCompilerEvent(msg, eventHandler!),
       shots!,
       languageFeatures,
+      untrusted,
     );
   }
 
@@ -245,6 +279,9 @@ export function onCompilerEvent(msg: string, eventTarget: IQscEventTarget) {
         stateLatex: qscMsg.stateLatex,
       });
       break;
+    case "Matrix":
+      qscEvent = makeEvent("Matrix", qscMsg.matrix);
+      break;
     case "Result":
       qscEvent = makeEvent("Result", qscMsg.result);
       break;
@@ -264,8 +301,9 @@ export const compilerProtocol: ServiceProtocol<ICompiler, QscEventData> = {
     getHir: "request",
     getQir: "request",
     getEstimates: "request",
+    getRuntimeCapabilities: "request",
     run: "requestWithProgress",
     checkExerciseSolution: "requestWithProgress",
   },
-  eventNames: ["DumpMachine", "Message", "Result"],
+  eventNames: ["DumpMachine", "Matrix", "Message", "Result"],
 };
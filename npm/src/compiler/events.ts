@@ -1,7 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-import { ShotResult, Dump, Result } from "./common.js";
+import { ShotResult, Dump, Matrix, Result } from "./common.js";
 import { log } from "../log.js";
 import { IServiceEventTarget } from "../workers/common.js";
 
@@ -9,6 +9,7 @@ import { IServiceEventTarget } from "../workers/common.js";
 export type QscEventData =
   | { type: "Message"; detail: string }
   | { type: "DumpMachine"; detail: { state: Dump; stateLatex: string } }
+  | { type: "Matrix"; detail: Matrix }
   | { type: "Result"; detail: Result };
 
 export type QscEvents = Event & QscEventData;
@@ -80,6 +81,7 @@ export class QscEventTarget implements IQscEventTarget {
       this.addEventListener("DumpMachine", (ev) =>
         this.onDumpMachine(ev.detail),
       );
+      this.addEventListener("Matrix", (ev) => this.onMatrix(ev.detail));
       this.addEventListener("Result", (ev) => this.onResult(ev.detail));
     }
   }
@@ -106,6 +108,15 @@ export class QscEventTarget implements IQscEventTarget {
     this.queueUiRefresh();
   }
 
+  private onMatrix(matrix: Matrix) {
+    this.ensureActiveShot();
+
+    const shotIdx = this.results.length - 1;
+    this.results[shotIdx].events.push({ type: "Matrix", matrix });
+
+    this.queueUiRefresh();
+  }
+
   private onResult(result: Result) {
     this.ensureActiveShot();
 
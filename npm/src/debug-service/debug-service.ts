@@ -158,6 +158,9 @@ export function onCompilerEvent(msg: string, eventTarget: IQscEventTarget) {
         stateLatex: qscMsg.stateLatex,
       });
       break;
+    case "Matrix":
+      qscEvent = makeEvent("Matrix", qscMsg.matrix);
+      break;
     case "Result":
       qscEvent = makeEvent("Result", qscMsg.result);
       break;
@@ -187,5 +190,5 @@ export const debugServiceProtocol: ServiceProtocol<
     evalStepOut: "requestWithProgress",
     dispose: "request",
   },
-  eventNames: ["DumpMachine", "Message", "Result"],
+  eventNames: ["DumpMachine", "Matrix", "Message", "Result"],
 };
@@ -2,12 +2,18 @@
 // Licensed under the MIT License.
 
 import type {
+  ICallHierarchyIncomingCall,
+  ICallHierarchyItem,
+  ICallHierarchyOutgoingCall,
+  ICodeAction,
   ICodeLens,
   ICompletionList,
   IHover,
+  IInlayHint,
   ILocation,
   INotebookMetadata,
   IPosition,
+  IRange,
   ISignatureHelp,
   ITextEdit,
   IWorkspaceConfiguration,
@@ -82,6 +88,20 @@ export interface ILanguageService {
     position: IPosition,
   ): Promise<ITextEdit | undefined>;
   getCodeLenses(documentUri: string): Promise<ICodeLens[]>;
+  getInlayHints(documentUri: string): Promise<IInlayHint[]>;
+  getCodeActions(documentUri: string, range: IRange): Promise<ICodeAction[]>;
+  prepareCallHierarchy(
+    documentUri: string,
+    position: IPosition,
+  ): Promise<ICallHierarchyItem | undefined>;
+  getIncomingCalls(
+    documentUri: string,
+    position: IPosition,
+  ): Promise<ICallHierarchyIncomingCall[]>;
+  getOutgoingCalls(
+    documentUri: string,
+    position: IPosition,
+  ): Promise<ICallHierarchyOutgoingCall[]>;
 
   dispose(): Promise<void>;
 
@@ -224,6 +244,38 @@ export class QSharpLanguageService implements ILanguageService {
     return this.languageService.get_code_lenses(documentUri);
   }
 
+  async getInlayHints(documentUri: string): Promise<IInlayHint[]> {
+    return this.languageService.get_inlay_hints(documentUri);
+  }
+
+  async getCodeActions(
+    documentUri: string,
+    range: IRange,
+  ): Promise<ICodeAction[]> {
+    return this.languageService.get_code_actions(documentUri, range);
+  }
+
+  async prepareCallHierarchy(
+    documentUri: string,
+    position: IPosition,
+  ): Promise<ICallHierarchyItem | undefined> {
+    return this.languageService.prepare_call_hierarchy(documentUri, position);
+  }
+
+  async getIncomingCalls(
+    documentUri: string,
+    position: IPosition,
+  ): Promise<ICallHierarchyIncomingCall[]> {
+    return this.languageService.get_incoming_calls(documentUri, position);
+  }
+
+  async getOutgoingCalls(
+    documentUri: string,
+    position: IPosition,
+  ): Promise<ICallHierarchyOutgoingCall[]> {
+    return this.languageService.get_outgoing_calls(documentUri, position);
+  }
+
   async dispose() {
     this.languageService.stop_background_work();
     await this.backgroundWork;
@@ -288,6 +340,11 @@ export const languageServiceProtocol: ServiceProtocol<
     getRename: "request",
     prepareRename: "request",
     getCodeLenses: "request",
+    getInlayHints: "request",
+    getCodeActions: "request",
+    prepareCallHierarchy: "request",
+    getIncomingCalls: "request",
+    getOutgoingCalls: "request",
     dispose: "request",
     addEventListener: "addEventListener",
     removeEventListener: "removeEventListener",
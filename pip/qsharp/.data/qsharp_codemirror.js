@@ -22,7 +22,7 @@ if (window.require && window.CodeMirror && window.Jupyter) {
       },
       {
         token: "keyword",
-        regex: String.raw`(namespace|open|as|operation|function|body|adjoint|newtype|controlled|internal)\b`,
+        regex: String.raw`(namespace|open|as|operation|function|body|adjoint|newtype|controlled|internal|export)\b`,
         beginWord: true,
       },
       {
@@ -5,18 +5,43 @@ from ._qsharp import (
     init,
     eval,
     run,
+    run_shots,
+    last_recording,
+    last_run_statistics,
+    compilation_stats,
+    call_graph,
     compile,
     circuit,
     estimate,
+    advise,
+    benchmark,
+    run_tests,
+    hints,
+    fix,
+    supported_language_features,
+    set_trace_callback,
     set_quantum_seed,
     set_classical_seed,
+    set_limits,
+    set_forced_outcomes,
+    set_metadata,
+    set_gate_set,
     dump_machine,
+    check_equivalence,
+    set_state_filter,
     dump_circuit,
     StateDump,
     ShotResult,
 )
 
-from ._native import Result, Pauli, QSharpError, TargetProfile
+from ._native import (
+    Result,
+    Pauli,
+    QSharpError,
+    TargetProfile,
+    RuntimeCapability,
+    RuntimeFeature,
+)
 
 # IPython notebook specific features
 try:
@@ -33,17 +58,37 @@ __all__ = [
     "init",
     "eval",
     "run",
+    "run_shots",
+    "last_recording",
+    "last_run_statistics",
+    "compilation_stats",
+    "call_graph",
     "set_quantum_seed",
     "set_classical_seed",
+    "set_limits",
+    "set_forced_outcomes",
+    "set_metadata",
+    "set_gate_set",
     "dump_machine",
+    "check_equivalence",
+    "set_state_filter",
     "dump_circuit",
     "compile",
     "circuit",
     "estimate",
+    "advise",
+    "benchmark",
+    "run_tests",
+    "hints",
+    "fix",
+    "supported_language_features",
+    "set_trace_callback",
     "Result",
     "Pauli",
     "QSharpError",
     "TargetProfile",
+    "RuntimeCapability",
+    "RuntimeFeature",
     "StateDump",
     "ShotResult",
 ]
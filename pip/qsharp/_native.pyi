@@ -2,7 +2,17 @@
 # Licensed under the MIT License.
 
 from enum import Enum
-from typing import Any, Callable, ClassVar, Tuple, Optional, Dict, List
+from typing import (
+    Any,
+    Callable,
+    ClassVar,
+    Tuple,
+    Optional,
+    Dict,
+    Iterator,
+    List,
+    Sequence,
+)
 
 class TargetProfile:
     """
@@ -35,6 +45,8 @@ class Interpreter:
         manifest_descriptor: Optional[Dict[str, str]],
         read_file: Callable[[str], str],
         list_directory: Callable[[str], str],
+        simulator: Optional[str] = None,
+        untrusted: Optional[bool] = None,
     ) -> None:
         """
         Initializes the Q# interpreter.
@@ -43,6 +55,10 @@ class Interpreter:
         :param manifest_descriptor: A dictionary that represents the manifest descriptor
         :param read_file: A function that reads a file from the file system.
         :param list_directory: A function that lists the contents of a directory.
+        :param simulator: The simulator programs run on, either `"sparse"` (the default)
+            or `"dense"`.
+        :param untrusted: Whether to limit the resources of each run for running code
+            from untrusted sources.
         """
         ...
 
@@ -59,33 +75,176 @@ class Interpreter:
         """
         ...
 
-    def run(self, entry_expr: str, output_fn: Callable[[Output], None]) -> Any:
+    def run(
+        self,
+        entry_expr: str,
+        output_fn: Callable[[Output], None],
+        replay: Optional[Dict[str, Any]] = None,
+        quiet: bool = False,
+    ) -> Any:
         """
         Runs the given Q# expression with an independent instance of the simulator.
+        The measurement outcomes of the run are recorded, see `last_recording`.
 
         :param entry_expr: The entry expression.
         :param output_fn: A callback function that will be called with each output.
+        :param replay: A recording of a previous run to replay.
+        :param quiet: If true, messages are dropped without being formatted or
+            passed to `output_fn`. Other output is still passed to it.
 
         :returns values: A result or runtime errors.
 
-        :raises QSharpError: If there is an error interpreting the input.
+        :raises QSharpError: If there is an error interpreting the input, or if
+            a measurement outcome differs from the replayed recording.
+        """
+        ...
+
+    def run_entry(
+        self,
+        args: Optional[List[Any]] = None,
+        output_fn: Optional[Callable[[Output], None]] = None,
+    ) -> Any:
+        """
+        Runs the entry point of the compiled sources, the callable with the
+        `@EntryPoint()` attribute, with the given arguments.
+
+        :param args: The arguments to pass to the entry point, which are checked
+            against the types of its parameters.
+        :param output_fn: A callback function that will be called with each output.
+
+        :returns values: The value returned by the entry point.
+
+        :raises QSharpError: If there is no single entry point, the arguments
+            don't match its parameters, or there is an error running it.
         """
         ...
 
-    def qir(self, entry_expr: str) -> str:
+    def last_run_statistics(self) -> Optional[Dict[str, Any]]:
+        """
+        The resources the simulator used in the most recent run, including a
+        run that failed.
+
+        :returns statistics: A dictionary with the keys `qubit_high_water_mark`,
+            `reused_qubit_high_water_mark`, `peak_amplitudes`,
+            `peak_memory_bytes`, `samples` and `qubit_lifetimes`, or `None` if
+            nothing has been run. `reused_qubit_high_water_mark` is the largest number of qubits
+            that held state at once, which is how many qubits the run needs if
+            qubits that are reset, such as after a measurement, are reused.
+        """
+        ...
+
+    def compilation_stats(self) -> Dict[str, Any]:
+        """
+        Statistics about the compilation of the sources the interpreter was
+        created with.
+
+        :returns stats: A dictionary with the keys `total_ms`, `phases`,
+            `memory_bytes`, `peak_memory_bytes`, `packages` and `rca`.
+        """
+        ...
+
+    def call_graph(self) -> Dict[str, Any]:
+        """
+        The call graph of the callables declared in the sources and the
+        fragments evaluated so far, along with the library callables they refer
+        to.
+
+        :returns graph: A dictionary with the keys `nodes` and `edges`.
+        """
+        ...
+
+    def run_shots(
+        self,
+        entry_expr: str,
+        shots: int,
+        workers: Optional[int] = None,
+        measurement_statistics: bool = False,
+    ) -> Dict[str, Any]:
+        """
+        Runs the given entry expression for the given number of shots on a
+        pool of threads, releasing the GIL while the shots run.
+
+        :param entry_expr: The entry expression.
+        :param shots: The number of shots to run.
+        :param workers: The number of threads to run the shots on.
+        :param measurement_statistics: Whether to compute statistics of the
+            outcome of each measured qubit.
+
+        :returns results: A dictionary with the keys `seed`, `counts`,
+            `failures` and `measurement_statistics`.
+
+        :raises QSharpError: If there is an error compiling the entry expression.
+        """
+        ...
+
+    def last_recording(self) -> Optional[Dict[str, Any]]:
+        """
+        The measurement outcomes and seeds of the most recent run, including
+        a run that failed.
+
+        :returns recording: A dictionary with the keys `quantum_seed`,
+            `classical_seed` and `measurements`, or `None` if nothing has been run.
+        """
+        ...
+
+    def qir(self, entry_expr: str, debug_info: bool = False) -> str:
         """
         Generates QIR from Q# source code.
 
         :param entry_expr: The entry expression.
+        :param debug_info: Whether to attach the Q# source location of each
+            instruction as debug metadata.
 
         :returns qir: The QIR string.
         """
         ...
 
+    def benchmark(self, entry_expr: str, repetitions: int) -> Dict[str, Any]:
+        """
+        Compiles and runs the given entry expression repeatedly and collects
+        timing statistics and gate counts.
+
+        :param entry_expr: The entry expression.
+        :param repetitions: The number of times to compile and run the entry expression.
+
+        :returns benchmark: A dictionary with the keys `repetitions`, `compile`,
+            `classical`, `simulator`, `total` and `gate_counts`.
+
+        :raises QSharpError: If there is an error compiling or running the entry expression.
+        """
+        ...
+
+    def run_tests(self) -> List[Dict[str, Any]]:
+        """
+        Runs the callables with the `@Test()` attribute in the sources the
+        interpreter was initialized with, each with an independent instance
+        of the simulator.
+
+        :returns results: A list of dictionaries with the keys `name`, `passed`,
+            `seed`, `output` and `errors`.
+        """
+        ...
+
+    def advise(self, entry_expr: str) -> List[Dict[str, Any]]:
+        """
+        Suggests rewrites for the constructs reachable from the entry expression
+        that require more runtime capabilities than the Base profile provides.
+
+        :param entry_expr: The entry expression.
+
+        :returns advice: A list of dictionaries with the keys `code`, `message`,
+            `before`, `after` and `diagnostic`.
+
+        :raises QSharpError: If there is an error compiling the entry expression.
+        """
+        ...
+
     def circuit(
         self,
         entry_expr: Optional[str],
         operation: Optional[str],
+        optimize: bool = True,
+        simulate: bool = False,
     ) -> Circuit:
         """
         Synthesizes a circuit for a Q# program. Either an entry
@@ -97,6 +256,12 @@ class Interpreter:
         an operation of a lambda expression. The operation must take only
         qubits or arrays of qubits as parameters.
 
+        :param optimize: Whether to cancel adjacent inverse gates and merge
+        consecutive rotations on the same qubits.
+
+        :param simulate: Whether to run the program on the simulator when it
+        compares measurement results in a way that can't be traced without it.
+
         :raises QSharpError: If there is an error synthesizing the circuit.
         """
         ...
@@ -112,6 +277,22 @@ class Interpreter:
         """
         ...
 
+    def estimate_call(
+        self, operation: str, args: List[Any], repetitions: int, params: str
+    ) -> str:
+        """
+        Estimates resources for calling an operation with the given arguments
+        the given number of times in a row.
+
+        :param operation: The name of the operation.
+        :param args: The arguments of the operation.
+        :param repetitions: The number of times the operation is called.
+        :param params: The parameters to configure estimation.
+
+        :returns resources: The estimated resources.
+        """
+        ...
+
     def set_quantum_seed(self, seed: Optional[int]) -> None:
         """
         Sets the seed for the quantum random number generator.
@@ -130,14 +311,226 @@ class Interpreter:
         """
         ...
 
-    def dump_machine(self) -> StateDumpData:
+    def set_limits(
+        self,
+        max_qubits: Optional[int] = None,
+        max_call_depth: Optional[int] = None,
+        max_statements: Optional[int] = None,
+        max_outputs: Optional[int] = None,
+        max_seconds: Optional[float] = None,
+    ) -> None:
+        """
+        Sets the limits on the resources used by the programs the interpreter runs.
+        A program that exceeds a limit fails with a runtime error.
+
+        :param max_qubits: The maximum number of qubits allocated at the same time.
+            If None, the number of qubits is not limited.
+        :param max_call_depth: The maximum number of nested calls. If None, the call
+            depth is limited to 10,000 nested calls.
+        :param max_statements: The maximum number of statements executed per run.
+            If None, the number of statements is not limited.
+        :param max_outputs: The maximum number of messages and state dumps printed
+            per run. If None, the output is not limited.
+        :param max_seconds: The maximum time in seconds that each run takes. If None,
+            the time is not limited.
+        """
+        ...
+
+    def set_forced_outcomes(
+        self,
+        sequence: Optional[List[Optional[Result]]] = None,
+        qubits: Optional[Dict[int, Result]] = None,
+    ) -> None:
+        """
+        Forces the outcomes of measurements. Calling this again replaces the
+        forced outcomes.
+
+        :param sequence: The outcomes of the next measurements, in the order
+            they are made. `None` leaves a measurement unforced.
+        :param qubits: The outcome of every other measurement of the given
+            qubits, keyed by qubit id.
+        """
+        ...
+
+    def config(self) -> Dict[str, Any]:
+        """
+        Returns the effective configuration of the interpreter.
+
+        :returns config: A dictionary with the keys `target_profile`, `language_features`,
+            `manifest`, `stdlib`, `quantum_seed`, `classical_seed`, `simulator` and `metadata`.
+            `stdlib` is whether the builtin standard library is included, or the sources
+            of the custom standard library keyed by name.
+        """
+        ...
+
+    def clone_with(self, **overrides: Any) -> Interpreter:
+        """
+        Creates a new interpreter with the same configuration as this one, except
+        for the given overrides. The project sources already loaded by this
+        interpreter are reused, but fragments evaluated in this session are not
+        carried over to the new one.
+
+        :param overrides: Any of `target_profile`, `language_features`, `stdlib`,
+            `quantum_seed`, `classical_seed`, `simulator` and `metadata`. `stdlib` is
+            either whether to include the builtin standard library, or the sources of
+            a custom standard library to use in its place, keyed by name.
+
+        :returns interpreter: The new interpreter.
+
+        :raises QSharpError: If the sources fail to compile with the new configuration.
+        """
+        ...
+
+    def set_metadata(self, metadata: Dict[str, str]) -> None:
+        """
+        Sets the key/value metadata embedded into the QIR and circuits generated
+        by the interpreter.
+
+        :param metadata: The metadata, which replaces any previously set metadata.
+        """
+        ...
+
+    def register_intrinsic(self, name: str, callback: Callable[[Any], Any]) -> None:
+        """
+        Registers a Python function that simulates a custom intrinsic, which is
+        called with the arguments of the intrinsic and returns its result.
+
+        :param name: The QIR name given by the `@CustomIntrinsic` attribute of the
+            intrinsic, or the name of the intrinsic callable if it has none.
+        :param callback: The function that simulates the intrinsic.
+        """
+        ...
+
+    def register_function(
+        self,
+        name: str,
+        params: List[Tuple[str, str]],
+        output: str,
+        callback: Callable[..., Any],
+    ) -> None:
+        """
+        Registers a Python function as the implementation of a new Q# function,
+        which Q# code can call to run classical logic, such as an optimizer,
+        while it is simulated.
+
+        :param name: The name of the Q# function.
+        :param params: The names and Q# types of the parameters of the function.
+        :param output: The Q# return type of the function.
+        :param callback: The Python function, which is called with an argument for
+            each parameter and returns the result of the Q# function.
+        :raises QSharpError: If the declaration of the function fails to compile.
+        """
+        ...
+
+    def set_gate_set(self, gate_set: str) -> None:
+        """
+        Sets the native gates of the target, which the intrinsics in the QIR and
+        circuits generated by the interpreter are decomposed into.
+
+        :param gate_set: `full` for every intrinsic, or the comma-separated names
+            of a supported gate set, `rz,sx,cx` or `rx,ry,rzz`.
+        """
+        ...
+
+    def set_state_filter(
+        self, min_probability: Optional[float] = None, top: Optional[int] = None
+    ) -> None:
+        """
+        Sets the filter applied to the states reported by `DumpMachine` and
+        `DumpRegister` calls in Q# code, so that only significant amplitudes are
+        passed to the output callback.
+
+        :param min_probability: If set, basis states with a smaller probability are dropped.
+        :param top: If set, only this many of the most probable basis states are kept.
+        """
+        ...
+
+    def dump_machine(
+        self, min_probability: Optional[float] = None, top: Optional[int] = None
+    ) -> StateDumpData:
         """
         Returns the sparse state vector of the simulator as a StateDump object.
 
+        :param min_probability: If set, basis states with a smaller probability are dropped.
+        :param top: If set, only this many of the most probable basis states are kept.
+
         :returns: The state of the simulator.
         """
         ...
 
+    def dump_operation(self, operation: str, num_qubits: int) -> List[List[complex]]:
+        """
+        Computes the unitary matrix of an operation that takes an array of qubits
+        by simulating it on a new instance of the simulator.
+
+        :param operation: The operation, which must take an array of qubits and
+            must not measure them.
+        :param num_qubits: The number of qubits the operation is applied to.
+
+        :returns: The rows of the matrix as lists of complex entries.
+
+        :raises QSharpError: If the operation fails to compile or run, or if it
+            measures qubits.
+        """
+        ...
+
+    def check_equivalence(
+        self,
+        operation_a: str,
+        operation_b: str,
+        num_qubits: int,
+        samples: Optional[int] = None,
+    ) -> bool:
+        """
+        Checks whether two operations that take an array of qubits implement the
+        same unitary up to a global phase.
+
+        :param operation_a: The first operation.
+        :param operation_b: The second operation.
+        :param num_qubits: The number of qubits the operations are applied to.
+        :param samples: If set, the operations are applied to this many random
+            states instead of comparing their full matrices.
+
+        :returns: Whether the operations are equivalent.
+
+        :raises QSharpError: If either operation fails to compile or run, or if
+            it measures qubits.
+        """
+        ...
+
+    def dump_ast(self, source: str, json: bool = False) -> str:
+        """
+        Parses the given source and dumps its AST without compiling it.
+
+        :param source: The source, which may contain namespaces and statements.
+        :param json: Whether to dump the tree as JSON instead of text.
+
+        :returns: The dump of the AST.
+
+        :raises QSharpError: If the source has syntax errors.
+        """
+        ...
+
+    def dump_hir(self, json: bool = False) -> str:
+        """
+        Dumps the HIR of the sources and the code evaluated so far.
+
+        :param json: Whether to dump the tree as JSON instead of text.
+
+        :returns: The dump of the HIR.
+        """
+        ...
+
+    def dump_fir(self, json: bool = False) -> str:
+        """
+        Dumps the FIR of the sources and the code evaluated so far.
+
+        :param json: Whether to dump the tree as JSON instead of text.
+
+        :returns: The dump of the FIR.
+        """
+        ...
+
     def dump_circuit(self) -> Circuit:
         """
         Dumps the current circuit state of the interpreter.
@@ -168,13 +561,15 @@ class Pauli(Enum):
 class Output:
     """
     An output returned from the Q# interpreter.
-    Outputs can be a state dumps or messages. These are normally printed to the console.
+    Outputs can be state dumps, matrix dumps or messages. These are normally printed to the console.
     """
 
     def __repr__(self) -> str: ...
     def __str__(self) -> str: ...
     def _repr_html_(self) -> str: ...
     def state_dump(self) -> Optional[StateDumpData]: ...
+    def matrix_dump(self) -> Optional[MatrixDumpData]: ...
+    def message(self) -> Optional[str]: ...
 
 class StateDumpData:
     """
@@ -186,17 +581,155 @@ class StateDumpData:
     """
     qubit_count: int
 
+    """
+    The ids of the qubits of the dumped register, or `None` if the whole state
+    of the simulator was dumped.
+    """
+    qubits: Optional[List[int]]
+
     """
     Get the amplitudes of the state vector as a dictionary from state integer to
     complex amplitudes.
     """
     def get_dict(self) -> dict: ...
+
+    """
+    Get the dense state vector as a NumPy array of complex amplitudes indexed by
+    basis state. Basis states that are absent from the dump have an amplitude of
+    zero. Requires NumPy to be installed.
+    """
+    def as_numpy(self) -> Any: ...
+
+    """
+    Get a state dump with only the `k` most probable basis states.
+    """
+    def top(self, k: int) -> StateDumpData: ...
+
+    """
+    Get a state dump without the basis states whose probability is smaller than
+    `min_probability`.
+    """
+    def filter(self, min_probability: float) -> StateDumpData: ...
+    def __repr__(self) -> str: ...
+    def __str__(self) -> str: ...
+    def _repr_html_(self) -> str: ...
+
+class MatrixDumpData:
+    """
+    A matrix dump returned from the Q# interpreter.
+    """
+
+    """
+    Get the rows of the matrix as lists of complex entries.
+    """
+    def get_matrix(self) -> List[List[complex]]: ...
+
+    """
+    Get the matrix as a two-dimensional NumPy array of complex entries. Requires
+    NumPy to be installed.
+    """
+    def as_numpy(self) -> Any: ...
+    def __len__(self) -> int: ...
     def __repr__(self) -> str: ...
     def __str__(self) -> str: ...
     def _repr_html_(self) -> str: ...
 
 class Circuit:
     def json(self) -> str: ...
+    def to_dict(self) -> Dict[str, Any]:
+        """
+        Returns the circuit as a dictionary that can be converted into the
+        circuit objects of other frameworks, such as Qiskit or Cirq, without
+        parsing its JSON.
+
+        The dictionary has the following keys:
+        - `qubits`: the ids of the qubits.
+        - `num_results`: the number of measurement results of each qubit.
+        - `operations`: the operations, in order.
+        - `metadata`: the key/value metadata of the circuit.
+
+        Measurement results are identified by `(qubit, index)` pairs. Each
+        operation has a `kind`, and depending on it the following keys:
+        - `"gate"`: `gate`, the name of the gate, `adjoint`, `controls` and
+          `targets`, the ids of its qubits, `params`, the exact values of its
+          parameters such as rotation angles, and `args`, the formatted classical
+          arguments of a custom gate or `None`.
+        - `"measurement"`: `qubit`, the measured qubit, and `result`, the
+          result it is stored in.
+        - `"conditional"`: `result`, the result the operations depend on, and
+          `on_one` and `on_zero`, the operations that run for each of its values.
+
+        Gates and measurements also have a `source`: the call in the Q# source
+        they come from, as a dictionary with the `file`, the zero-based `line`
+        and `column` of the call, and the name of the `callable` it calls, or
+        `None` if it isn't known.
+
+        :returns dict: The circuit.
+        """
+        ...
+    def render(self, compact: bool = False, max_width: Optional[int] = None) -> str:
+        """
+        Renders the circuit as text.
+
+        :param compact: Size each column to fit its widest gate and collapse
+            runs of idle qubit wires into a single line, which keeps wide
+            circuits readable.
+        :param max_width: If set, wraps the diagram into blocks of columns so
+            that lines are no wider than this many characters.
+
+        :returns text: The rendered circuit.
+        """
+        ...
+    def slice(self, start: int, stop: int) -> Circuit:
+        """
+        Returns the part of the circuit in a window of time steps, on all of
+        its qubits. Like the columns of the rendered circuit, an operation's
+        time step is the first one after every earlier operation on the wires
+        it spans, and each operation, including a conditional group as a
+        whole, takes one time step.
+
+        :param start: The first time step to include.
+        :param stop: The time step to stop before.
+
+        :returns circuit: The operations in the window.
+        """
+        ...
+    def filter_qubits(self, ids: Sequence[int]) -> Circuit:
+        """
+        Returns the part of the circuit on the given qubits. Operations that
+        also act on other qubits, such as a CNOT from a selected qubit to
+        another one, are left out.
+
+        :param ids: The ids of the qubits to keep. Unknown ids are ignored.
+
+        :returns circuit: The operations on the qubits.
+        """
+        ...
+    def reuse_qubits(self) -> Circuit:
+        """
+        Returns an equivalent circuit on fewer qubits, where a qubit that has
+        been reset, such as after a measurement, is reused for a qubit whose
+        first operation comes after the reset. Measurement results move with
+        the operations that produce them, and qubits without any operations
+        are left out.
+
+        :returns circuit: The circuit with reused qubits.
+        """
+        ...
+    def qubit_lifetimes(self) -> List[Dict[str, Any]]:
+        """
+        Returns the lifetime of each qubit of the circuit, in the order of the
+        qubits, with times that are the time steps used by `slice`. A qubit
+        counts as allocated from its first operation until the time step after
+        its last one.
+
+        :returns lifetimes: A list of dictionaries with the keys `qubit`,
+            `allocated`, `released`, `idle`, the `(start, end)` windows of time
+            in which no operation acts on the qubit, and `partners`, the
+            `(time, qubit)` pairs of the other qubits that multi-qubit
+            operations act on together with it.
+        """
+        ...
     def __repr__(self) -> str: ...
     def __str__(self) -> str: ...
 
@@ -205,7 +738,229 @@ class QSharpError(BaseException):
     An error returned from the Q# interpreter.
     """
 
-    ...
+    stack_frames: Sequence[Dict[str, Any]]
+    """
+    The frames of the Q# call stack at the point where a runtime error
+    occurred, innermost first. Each frame is a dictionary with the `name` and
+    `functor` of the called callable, and the `source`, `line` and `column` of
+    the call, which are None if the call has no source. Errors that don't
+    occur at runtime have no frames.
+    """
+
+class LogicalCounts:
+    """
+    Pre-calculated logical resource counts of an algorithm, which physical
+    resources can be estimated from. The counts are validated when they are
+    created, rather than when they are estimated.
+    """
+
+    num_qubits: int
+    t_count: int
+    rotation_count: int
+    rotation_depth: int
+    ccz_count: int
+    ccix_count: int
+    measurement_count: int
+
+    def __init__(
+        self,
+        num_qubits: int,
+        *,
+        t_count: int = 0,
+        rotation_count: int = 0,
+        rotation_depth: int = 0,
+        ccz_count: int = 0,
+        ccix_count: int = 0,
+        measurement_count: int = 0,
+    ) -> None:
+        """
+        :raises ValueError: If there are no logical qubits, or the rotation
+            depth is inconsistent with the rotation count.
+        :raises OverflowError: If a count is negative.
+        """
+        ...
+
+    def json(self) -> str:
+        """
+        Returns the counts in the JSON schema that `physical_estimates` accepts.
+        """
+        ...
+
+    def estimate(self, params: str) -> str:
+        """
+        Estimates physical resources from the counts.
+
+        :param params: The parameters to configure physical estimation.
+
+        :returns resources: The estimated resources.
+        """
+        ...
+
+    def __repr__(self) -> str: ...
+
+class RuntimeCapability:
+    """
+    A set of the capabilities a target needs to run a Q# program, beyond
+    executing quantum operations on statically allocated qubits.
+
+    Flags are combined with `|`, and `flag in flags` checks whether all the
+    bits of `flag` are set.
+    """
+
+    ForwardBranching: ClassVar["RuntimeCapability"]
+    IntegerComputations: ClassVar["RuntimeCapability"]
+    FloatingPointComputations: ClassVar["RuntimeCapability"]
+    BackwardsBranching: ClassVar["RuntimeCapability"]
+    HigherLevelConstructs: ClassVar["RuntimeCapability"]
+
+    def __init__(self, value: int) -> None:
+        """
+        Creates the flags whose bits are set in `value`. Unknown bits are dropped.
+        """
+        ...
+
+    @staticmethod
+    def empty() -> "RuntimeCapability":
+        """
+        The flags that have no bits set.
+        """
+        ...
+
+    @staticmethod
+    def all() -> "RuntimeCapability":
+        """
+        The combination of every flag.
+        """
+        ...
+
+    value: int
+    """
+    The bits of the flags.
+    """
+
+    names: List[str]
+    """
+    The names of the single flags that are set, in bit order.
+    """
+
+    def __or__(self, other: "RuntimeCapability") -> "RuntimeCapability": ...
+    def __and__(self, other: "RuntimeCapability") -> "RuntimeCapability": ...
+    def __xor__(self, other: "RuntimeCapability") -> "RuntimeCapability": ...
+    def __sub__(self, other: "RuntimeCapability") -> "RuntimeCapability": ...
+    def __invert__(self) -> "RuntimeCapability": ...
+    def __contains__(self, other: "RuntimeCapability") -> bool:
+        """
+        Whether every flag of `other` is also set in these flags.
+        """
+        ...
+
+    def __bool__(self) -> bool: ...
+    def __int__(self) -> int: ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator["RuntimeCapability"]:
+        """
+        Iterates over the single flags that are set, in bit order.
+        """
+        ...
+
+    def __hash__(self) -> int: ...
+    def __repr__(self) -> str: ...
+
+class RuntimeFeature:
+    """
+    A set of the constructs a Q# program uses that are more complex than
+    executing quantum operations on statically allocated qubits with constant
+    arguments. Each feature needs one or more runtime capabilities.
+
+    Flags are combined with `|`, and `flag in flags` checks whether all the
+    bits of `flag` are set.
+    """
+
+    UseOfDynamicBool: ClassVar["RuntimeFeature"]
+    UseOfDynamicInt: ClassVar["RuntimeFeature"]
+    UseOfDynamicPauli: ClassVar["RuntimeFeature"]
+    UseOfDynamicRange: ClassVar["RuntimeFeature"]
+    UseOfDynamicDouble: ClassVar["RuntimeFeature"]
+    UseOfDynamicQubit: ClassVar["RuntimeFeature"]
+    UseOfDynamicBigInt: ClassVar["RuntimeFeature"]
+    UseOfDynamicString: ClassVar["RuntimeFeature"]
+    UseOfDynamicallySizedArray: ClassVar["RuntimeFeature"]
+    UseOfDynamicUdt: ClassVar["RuntimeFeature"]
+    UseOfDynamicArrowFunction: ClassVar["RuntimeFeature"]
+    UseOfDynamicArrowOperation: ClassVar["RuntimeFeature"]
+    CallToCyclicFunctionWithDynamicArg: ClassVar["RuntimeFeature"]
+    CyclicOperationSpec: ClassVar["RuntimeFeature"]
+    CallToCyclicOperation: ClassVar["RuntimeFeature"]
+    CallToDynamicCallee: ClassVar["RuntimeFeature"]
+    CallToUnresolvedCallee: ClassVar["RuntimeFeature"]
+    ForwardBranchingOnDynamicValue: ClassVar["RuntimeFeature"]
+    DynamicQubitAllocation: ClassVar["RuntimeFeature"]
+    DynamicResultAllocation: ClassVar["RuntimeFeature"]
+    UseOfDynamicIndex: ClassVar["RuntimeFeature"]
+    ReturnWithinDynamicScope: ClassVar["RuntimeFeature"]
+    LoopWithDynamicCondition: ClassVar["RuntimeFeature"]
+    UseOfClosure: ClassVar["RuntimeFeature"]
+    CallToUnrestrictedIntrinsic: ClassVar["RuntimeFeature"]
+    UseOfBorrowedQubit: ClassVar["RuntimeFeature"]
+
+    def __init__(self, value: int) -> None:
+        """
+        Creates the flags whose bits are set in `value`. Unknown bits are dropped.
+        """
+        ...
+
+    @staticmethod
+    def empty() -> "RuntimeFeature":
+        """
+        The flags that have no bits set.
+        """
+        ...
+
+    @staticmethod
+    def all() -> "RuntimeFeature":
+        """
+        The combination of every flag.
+        """
+        ...
+
+    value: int
+    """
+    The bits of the flags.
+    """
+
+    names: List[str]
+    """
+    The names of the single flags that are set, in bit order.
+    """
+
+    def __or__(self, other: "RuntimeFeature") -> "RuntimeFeature": ...
+    def __and__(self, other: "RuntimeFeature") -> "RuntimeFeature": ...
+    def __xor__(self, other: "RuntimeFeature") -> "RuntimeFeature": ...
+    def __sub__(self, other: "RuntimeFeature") -> "RuntimeFeature": ...
+    def __invert__(self) -> "RuntimeFeature": ...
+    def __contains__(self, other: "RuntimeFeature") -> bool:
+        """
+        Whether every flag of `other` is also set in these flags.
+        """
+        ...
+
+    def __bool__(self) -> bool: ...
+    def __int__(self) -> int: ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator["RuntimeFeature"]:
+        """
+        Iterates over the single flags that are set, in bit order.
+        """
+        ...
+
+    def __hash__(self) -> int: ...
+    def __repr__(self) -> str: ...
+
+    def runtime_capabilities(self) -> RuntimeCapability:
+        """
+        The minimal runtime capabilities a target needs to support these features.
+        """
+        ...
 
 def physical_estimates(logical_resources: str, params: str) -> str:
     """
@@ -217,3 +972,46 @@ def physical_estimates(logical_resources: str, params: str) -> str:
     :returns resources: The estimated resources.
     """
     ...
+
+def hints(source: str) -> List[Dict[str, Any]]:
+    """
+    Annotates a standalone Q# source with the results of the runtime
+    capabilities analysis.
+
+    :param source: The Q# source code.
+
+    :returns hints: A list of hints, each a dictionary with the keys `kind`,
+        `label`, `start` and `end`.
+
+    :raises QSharpError: If there is an error compiling the source.
+    """
+    ...
+
+def fix(source: str) -> str:
+    """
+    Applies the fixes suggested by the lints to a standalone Q# source.
+
+    :param source: The Q# source code.
+
+    :returns source: The source code with the fixes applied.
+
+    :raises QSharpError: If there is an error compiling the source.
+    """
+    ...
+
+def supported_language_features() -> List[Dict[str, str]]:
+    """
+    Lists the language features that can be enabled.
+
+    :returns features: A list of dictionaries with the keys `name`,
+        `description` and `stability` (`"preview"` or `"stable"`).
+    """
+    ...
+
+def set_trace_callback(callback: Optional[Callable[[str, float], None]]) -> None:
+    """
+    Sets the function that the time spent in each phase of compilation and
+    evaluation is reported to, with the name of the phase and the elapsed
+    milliseconds. Passing `None` stops the reports.
+    """
+    ...
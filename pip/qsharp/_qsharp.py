@@ -8,13 +8,24 @@ from ._native import (
     QSharpError,
     Output,
     Circuit,
+    Result,
+    hints as _hints,
+    fix as _fix,
+    supported_language_features as _supported_language_features,
+    set_trace_callback as _set_trace_callback,
+)
+from typing import Any, Callable, Dict, Optional, TypedDict, TypeVar, Union, List
+from .estimator._estimator import (
+    EstimatorResult,
+    EstimatorParams,
+    ErrorBudgetPartition,
 )
-from typing import Any, Callable, Dict, Optional, TypedDict, Union, List
-from .estimator._estimator import EstimatorResult, EstimatorParams
 import json
 
 _interpreter = None
 
+T = TypeVar("T")
+
 
 class Config:
     _config: Dict[str, str]
@@ -50,6 +61,8 @@ def init(
     target_profile: TargetProfile = TargetProfile.Unrestricted,
     project_root: Optional[str] = None,
     language_features: List[str] = [],
+    simulator: str = "sparse",
+    untrusted: bool = False,
 ) -> Config:
     """
     Initializes the Q# interpreter.
@@ -60,6 +73,17 @@ def init(
 
     :param project_root: An optional path to a root directory with a Q# project to include.
         It must contain a qsharp.json project manifest.
+
+    :param simulator: The simulator that Q# programs run on. The default `"sparse"`
+        simulator only stores the nonzero amplitudes of the state. The `"dense"`
+        simulator stores every amplitude, which is faster for highly entangled
+        states but takes memory exponential in the number of qubits.
+
+    :param untrusted: Whether the interpreter runs code from untrusted sources, such as
+        the submissions to a grading or playground service. Each run is then limited
+        in its qubits, call depth, statements, printed messages and dumps, and time,
+        so that a program can't exhaust memory, run forever or flood the output.
+        `set_limits` replaces these limits.
     """
     from ._fs import read_file, list_directory, exists, join
 
@@ -103,6 +127,8 @@ def init(
         manifest_descriptor,
         read_file,
         list_directory,
+        simulator,
+        untrusted,
     )
 
     # Return the configuration information to provide a hint to the
@@ -155,6 +181,8 @@ def run(
     *,
     on_result: Optional[Callable[[ShotResult], None]] = None,
     save_events: bool = False,
+    replay: Optional[Dict[str, Any]] = None,
+    quiet: bool = False,
 ) -> List[Any]:
     """
     Runs the given Q# expression for the given number of shots.
@@ -164,6 +192,10 @@ def run(
     :param shots: The number of shots to run.
     :param on_result: A callback function that will be called with each result.
     :param save_events: If true, the output of each shot will be saved. If false, they will be printed.
+    :param replay: A recording returned by `last_recording`. Each shot replays the recorded
+        run, and fails if a measurement outcome differs from the recording.
+    :param quiet: If true, messages are dropped instead of being printed or saved, which
+        avoids the cost of formatting them when running many shots.
 
     :returns values: A list of results or runtime errors. If `save_events` is true,
     a List of ShotResults is returned.
@@ -183,7 +215,10 @@ def run(
     for _ in range(shots):
         results.append({"result": None, "events": []})
         run_results = get_interpreter().run(
-            entry_expr, on_save_events if save_events else print_output
+            entry_expr,
+            on_save_events if save_events else print_output,
+            replay,
+            quiet,
         )
         results[-1]["result"] = run_results
         if on_result:
@@ -195,6 +230,146 @@ def run(
         return [shot["result"] for shot in results]
 
 
+def last_recording() -> Optional[Dict[str, Any]]:
+    """
+    Returns the recording of the most recent shot run with `run`, including a
+    shot that failed. Passing it to `run` as `replay` reproduces the shot, so
+    that a probabilistic failure can be investigated.
+
+    :returns recording: A dictionary with the keys `quantum_seed`,
+        `classical_seed` and `measurements`, the outcome of every measurement
+        in the order they were made, or `None` if nothing has been run.
+    """
+    return get_interpreter().last_recording()
+
+
+def last_run_statistics() -> Optional[Dict[str, Any]]:
+    """
+    Returns the resources the simulator used in the most recent shot run with
+    `run`, including a shot that failed, to anticipate whether a larger instance
+    of the program will fit in memory.
+
+    :returns statistics: A dictionary with the keys:
+        - `qubit_high_water_mark`: the largest number of qubits allocated at once.
+        - `reused_qubit_high_water_mark`: the largest number of qubits that held
+          state at once, from their first operation until they were reset or
+          released, which is how many qubits the run needs if reset qubits are
+          reused for later allocations.
+        - `peak_amplitudes`: the largest number of nonzero amplitudes sampled.
+        - `peak_memory_bytes`: the estimated peak memory taken up by the state
+          of the simulator.
+        - `samples`: the size of the state over the run, sampled before each
+          measurement, reset and qubit release, as a list of dictionaries with
+          the keys `operation`, `qubits` and `amplitudes`.
+        - `qubit_lifetimes`: the lifetime of each allocation of a qubit, in the
+          order of allocation, for visualizing when qubits are in use. Times
+          count the operations applied before. Each lifetime is a dictionary
+          with the keys `qubit`, `allocated`, `released`, which is `None` if the
+          qubit was never released, `idle`, the `(start, end)` windows of time
+          in which no operation acted on the qubit, and `partners`, the
+          `(time, qubit)` pairs of the other qubits that multi-qubit operations
+          acted on together with it.
+        Returns `None` if nothing has been run.
+    """
+    return get_interpreter().last_run_statistics()
+
+
+def compilation_stats() -> Dict[str, Any]:
+    """
+    Returns statistics about the compilation of the sources the interpreter was
+    initialized with, to diagnose why a project takes long to load.
+
+    :returns stats: A dictionary with the keys:
+        - `total_ms`: the wall time of the compilation in milliseconds.
+        - `phases`: the milliseconds spent in each phase of the compiler, by
+          name of the phase.
+        - `memory_bytes`: the growth of the memory committed by the process over
+          the compilation, or `None` where it can't be measured.
+        - `peak_memory_bytes`: the most memory the process has committed at
+          once, or `None` where it can't be measured.
+        - `packages`: the size of each compiled package, as a list of
+          dictionaries with the keys `name`, `items`, `callables`, `blocks`,
+          `stmts` and `exprs`.
+        - `rca`: the result of running the runtime capabilities analysis, as a
+          dictionary with the keys `ms`, `callables`, `quantum_callables` and
+          `runtime_feature_callables`.
+    """
+    return get_interpreter().compilation_stats()
+
+
+def call_graph() -> Dict[str, Any]:
+    """
+    Returns the call graph of the callables declared in the sources and the
+    fragments evaluated so far, along with the library callables they refer to,
+    to compute metrics like fan-in and fan-out or find unused callables.
+
+    :returns graph: A dictionary with the keys:
+        - `nodes`: the callables, as a list of dictionaries with the keys
+          `name`, `capabilities` and `introduced`, the runtime capabilities the
+          callable needs and the ones its callees don't, `runtime_features`,
+          and `location`.
+        - `edges`: the callables that refer to other callables, as a list of
+          dictionaries with the keys `caller` and `callee`, indices into the
+          nodes, and `calls`, the locations of the references in source order.
+
+        Locations are dictionaries with the keys `file`, and the zero-based
+        `line` and `column`, or `None` where they can't be resolved.
+    """
+    return get_interpreter().call_graph()
+
+
+def run_shots(
+    entry_expr: str,
+    shots: int,
+    *,
+    workers: Optional[int] = None,
+    measurement_statistics: bool = False,
+) -> Dict[str, Any]:
+    """
+    Runs the given Q# expression for the given number of shots on a pool of
+    threads, and aggregates the results. Each shot uses an independent instance
+    of the simulator, and the output of the shots is discarded.
+
+    Each shot is seeded with a seed derived from the quantum seed if one is set
+    with `set_quantum_seed`, or from a random seed otherwise, so the results
+    don't depend on the number of threads.
+
+    :param entry_expr: The entry expression.
+    :param shots: The number of shots to run.
+    :param workers: The number of threads to run the shots on. Defaults to the
+        number of available processors.
+    :param measurement_statistics: Whether to record the outcome of each
+        measured qubit in each shot, and compute statistics of the outcomes,
+        so that they don't have to be derived from the returned values.
+
+    :returns results: A dictionary with the keys:
+        - `seed`: the seed the seeds of the shots were derived from.
+        - `counts`: the number of shots that returned each value, keyed by the
+          value formatted as a string.
+        - `failures`: a list of the shots that failed, each a dictionary with
+          the keys `shot`, `seed` and `errors`. Setting the seed of a failed
+          shot reproduces it.
+        - `measurement_statistics`: `None` unless requested, otherwise the
+          statistics of the outcomes of the shots that succeeded, where the
+          outcome of a qubit in a shot is that of its last measurement, as a
+          dictionary with the keys:
+          - `qubits`: the ids of the measured qubits, in increasing order.
+            Ids are reused once a qubit is released.
+          - `shots`: the number of shots that measured each qubit.
+          - `marginals`: the probability that each qubit is measured as `One`.
+          - `joint`: a matrix with the probability that both qubits of each
+            pair are measured as `One`, over the shots that measure both.
+          - `correlations`: a matrix with the Pearson correlation of the
+            outcomes of each pair of qubits, or NaN where a qubit always has
+            the same outcome.
+
+    :raises QSharpError: If there is an error compiling the entry expression.
+    """
+    return get_interpreter().run_shots(
+        entry_expr, shots, workers, measurement_statistics
+    )
+
+
 # Class that wraps generated QIR, which can be used by
 # azure-quantum as input data.
 #
@@ -219,13 +394,73 @@ class QirInputData:
         return self._ll_str
 
 
-def compile(entry_expr: str) -> QirInputData:
+def set_metadata(metadata: Dict[str, str]) -> None:
+    """
+    Sets key/value metadata for the session, such as an experiment name or run ID.
+    The metadata is embedded into the QIR module flags, circuit JSON and resource
+    estimation results produced from now on, so that they can be correlated with
+    the experiment that produced them. In QIR, the keys are prefixed with `user.`
+    so that they don't clash with the module flags that QIR reserves.
+
+    :param metadata: The metadata, which replaces any previously set metadata.
+        Keys and values must be strings.
+    """
+    get_interpreter().set_metadata(metadata)
+
+
+def set_gate_set(gate_set: str) -> None:
+    """
+    Sets the native gates of the target for the session. The intrinsics in the
+    QIR and circuits produced from now on are decomposed into these gates, so
+    that the output matches the gates the hardware executes.
+
+    :param gate_set: `"full"` to keep every intrinsic, or the comma-separated
+        names of a supported gate set, `"rz,sx,cx"` or `"rx,ry,rzz"`.
+    """
+    get_interpreter().set_gate_set(gate_set)
+
+
+def _with_metadata(
+    metadata: Optional[Dict[str, str]],
+    f: Callable[[Interpreter, Dict[str, str]], T],
+) -> T:
+    """
+    Calls `f` with the interpreter and the session metadata extended with
+    the metadata of a single run, which is applied to the interpreter for
+    the duration of the call.
+    """
+    interpreter = get_interpreter()
+    session_metadata = interpreter.config()["metadata"]
+    if not metadata:
+        return f(interpreter, session_metadata)
+
+    run_metadata = {**session_metadata, **metadata}
+    interpreter.set_metadata(run_metadata)
+    try:
+        return f(interpreter, run_metadata)
+    finally:
+        interpreter.set_metadata(session_metadata)
+
+
+def compile(
+    entry_expr: str,
+    *,
+    metadata: Optional[Dict[str, str]] = None,
+    debug_info: bool = False,
+) -> QirInputData:
     """
     Compiles the Q# source code into a program that can be submitted to a target.
 
     :param entry_expr: The Q# expression that will be used as the entrypoint
         for the program.
 
+    :param metadata: Metadata for this program, added to the session metadata
+        set with `qsharp.set_metadata`. It is embedded into the QIR module flags.
+
+    :param debug_info: Whether to attach the Q# source location of the call
+        that each instruction comes from as debug metadata, so that tools that
+        consume the QIR can trace instructions back to Q# lines.
+
     :returns QirInputData: The compiled program.
 
     To get the QIR string from the compiled program, use `str()`.
@@ -237,39 +472,95 @@ def compile(entry_expr: str) -> QirInputData:
         with open('myfile.ll', 'w') as file:
             file.write(str(program))
     """
-    ll_str = get_interpreter().qir(entry_expr)
+    ll_str = _with_metadata(
+        metadata, lambda interpreter, _: interpreter.qir(entry_expr, debug_info)
+    )
     return QirInputData("main", ll_str)
 
 
 def circuit(
-    entry_expr: Optional[str] = None, *, operation: Optional[str] = None
+    entry_expr: Optional[str] = None,
+    *,
+    operation: Optional[str] = None,
+    optimize: bool = True,
+    simulate: bool = False,
+    metadata: Optional[Dict[str, str]] = None,
 ) -> Circuit:
     """
     Synthesizes a circuit for a Q# program. Either an entry
     expression or an operation must be provided.
 
+    Programs are traced without running the simulator, so the circuit is the
+    same on every call. Branches on measurement results are shown as groups
+    of gates controlled by the result.
+
     :param entry_expr: An entry expression.
 
     :param operation: The operation to synthesize. This can be a name of
     an operation of a lambda expression. The operation must take only
     qubits or arrays of qubits as parameters.
 
+    :param optimize: Whether to cancel adjacent inverse gates, such as two
+    consecutive `H` gates on the same qubit, and merge consecutive rotations
+    on the same qubits. Pass `False` to get the literal gate sequence.
+
+    :param simulate: Whether to run the program on the simulator when it
+    compares measurement results in a way that can't be traced without it.
+    The circuit then shows the path that was taken.
+
+    :param metadata: Metadata for this circuit, added to the session metadata
+    set with `qsharp.set_metadata`. It is included in the circuit JSON.
+
     :raises QSharpError: If there is an error synthesizing the circuit.
     """
-    return get_interpreter().circuit(entry_expr, operation)
+    return _with_metadata(
+        metadata,
+        lambda interpreter, _: interpreter.circuit(
+            entry_expr, operation, optimize, simulate
+        ),
+    )
 
 
 def estimate(
-    entry_expr, params: Optional[Union[Dict[str, Any], List, EstimatorParams]] = None
+    entry_expr,
+    params: Optional[Union[Dict[str, Any], List, EstimatorParams]] = None,
+    *,
+    metadata: Optional[Dict[str, str]] = None,
+    args: Optional[List[Any]] = None,
+    repetitions: int = 1,
+    mode: Optional[str] = None,
+    error_budget: Optional[Union[float, ErrorBudgetPartition]] = None,
 ) -> EstimatorResult:
     """
     Estimates resources for Q# source code.
 
-    :param entry_expr: The entry expression.
+    :param entry_expr: The entry expression, or the name of an operation if
+        `args` or `repetitions` are given.
     :param params: The parameters to configure physical estimation.
+    :param metadata: Metadata for this estimate, added to the session metadata
+        set with `qsharp.set_metadata`. It is included in each result.
+    :param args: The arguments the operation is called with.
+    :param repetitions: The number of times the operation is called in a row.
+        The calls run in a loop, so large counts don't produce a large entry
+        expression.
+    :param mode: `"frontier"` to estimate the Pareto frontier of trade-offs
+        between runtime and physical qubits, whose points are listed by the
+        `frontier` property of the result, or `"singlePoint"` for a single
+        estimate. Overrides the estimate type of the parameters.
+    :param error_budget: The total error budget, which is partitioned evenly,
+        or its partition into logical errors, T state distillation and rotation
+        synthesis. Overrides the error budget of the parameters. The partition
+        that was used is reported by the `error_budget` property of the result.
 
     :returns resources: The estimated resources.
+
+    :raises ValueError: If the number of repetitions is not positive, or the
+        mode is not supported.
     """
+    if repetitions < 1:
+        raise ValueError("The number of repetitions must be positive.")
+    if mode is not None and mode not in ["frontier", "singlePoint"]:
+        raise ValueError("The mode must be either 'frontier' or 'singlePoint'.")
     if params is None:
         params = [{}]
     elif isinstance(params, EstimatorParams):
@@ -279,9 +570,160 @@ def estimate(
             params = [params.as_dict()]
     elif isinstance(params, dict):
         params = [params]
-    return EstimatorResult(
-        json.loads(get_interpreter().estimate(entry_expr, json.dumps(params)))
-    )
+    if mode is not None:
+        params = [{**item, "estimateType": mode} for item in params]
+    if error_budget is not None:
+        if isinstance(error_budget, ErrorBudgetPartition):
+            budget = error_budget.as_dict()
+        elif 0 < error_budget < 1:
+            budget = error_budget
+        else:
+            raise ValueError("error_budget must be value between 0 and 1")
+        params = [{**item, "errorBudget": budget} for item in params]
+
+    def estimate_with_metadata(
+        interpreter: Interpreter, metadata: Dict[str, str]
+    ) -> List[Dict[str, Any]]:
+        if args is None and repetitions == 1:
+            results = interpreter.estimate(entry_expr, json.dumps(params))
+        else:
+            results = interpreter.estimate_call(
+                entry_expr, args or [], repetitions, json.dumps(params)
+            )
+        results = json.loads(results)
+        if metadata:
+            for result in results:
+                result["metadata"] = metadata
+        return results
+
+    return EstimatorResult(_with_metadata(metadata, estimate_with_metadata))
+
+
+def benchmark(entry_expr: str, repetitions: int = 10) -> Dict[str, Any]:
+    """
+    Compiles and runs the given entry expression repeatedly, each time with a
+    fresh simulator, and reports how long each phase took. Output from the
+    program is discarded.
+
+    :param entry_expr: The entry expression.
+    :param repetitions: The number of times to compile and run the entry expression.
+
+    :returns benchmark: A dictionary with the keys:
+        - `repetitions`: the number of repetitions.
+        - `compile`, `classical`, `simulator` and `total`: wall-time statistics
+          in seconds for compiling the entry expression, evaluating the program
+          outside of the simulator, running the simulator, and the whole
+          repetition. Each is a dictionary with the keys `mean`, `min`, `max`
+          and `std_dev`.
+        - `gate_counts`: the average number of times each gate was applied per
+          repetition, keyed by gate name such as `h`, `cx` or `mresetz`.
+
+    :raises QSharpError: If there is an error compiling or running the entry expression.
+    :raises ValueError: If the number of repetitions is not positive.
+    """
+    if repetitions < 1:
+        raise ValueError("The number of repetitions must be positive.")
+    return get_interpreter().benchmark(entry_expr, repetitions)
+
+
+def run_tests() -> List[Dict[str, Any]]:
+    """
+    Runs the operations and functions marked with the `@Test()` attribute in
+    the project the interpreter was initialized with. Each test runs with a
+    fresh simulator and passes if it returns without failing.
+
+    Each test is seeded with the quantum seed if one is set with
+    `set_quantum_seed`, or with a random seed otherwise. Setting the reported
+    seed reproduces the run of a test.
+
+    :returns results: A list of results in source order, each a dictionary with the keys:
+        - `name`: the namespace-qualified name of the test.
+        - `passed`: whether the test passed.
+        - `seed`: the seed the test ran with.
+        - `output`: the messages and state dumps the test printed.
+        - `errors`: the formatted errors that failed the test, or `None`.
+    """
+    return get_interpreter().run_tests()
+
+
+def advise(entry_expr: str) -> List[Dict[str, Any]]:
+    """
+    Suggests rewrites for the constructs reachable from the entry expression
+    that need more runtime capabilities than the Base profile provides, such as
+    branching on measurement results or loops with dynamic conditions.
+
+    :param entry_expr: The entry expression.
+
+    :returns advice: A list of suggestions, each a dictionary with the keys
+        `code`, `message`, `before`, `after` and `diagnostic`.
+
+    :raises QSharpError: If there is an error compiling the entry expression.
+    """
+    return get_interpreter().advise(entry_expr)
+
+
+def hints(source: str) -> List[Dict[str, Any]]:
+    """
+    Annotates Q# source code with the results of the runtime capabilities
+    analysis: bindings to values that are only known at runtime get a hint
+    like `dynamic Int`, and calls that need capabilities beyond the Base
+    profile get a hint like `requires ForwardBranching`.
+
+    The source is compiled on its own against the standard library, and is
+    independent of the interpreter's state.
+
+    :param source: The Q# source code.
+
+    :returns hints: A list of hints ordered by position, each a dictionary
+        with the keys `kind` (`"dynamicBinding"` or `"requiredCapabilities"`),
+        `label`, and the zero-based `(line, column)` tuples `start` and `end`
+        of the annotated code.
+
+    :raises QSharpError: If there is an error compiling the source.
+    """
+    return _hints(source)
+
+
+def fix(source: str) -> str:
+    """
+    Applies the fixes suggested by the Q# linter to Q# source code, such as
+    removing redundant semicolons and needless parentheses, and returns the
+    rewritten source. The fixes are applied even for lints that are allowed
+    by default.
+
+    The source is compiled on its own against the standard library, and is
+    independent of the interpreter's state.
+
+    :param source: The Q# source code.
+
+    :returns source: The source code with the fixes applied.
+
+    :raises QSharpError: If there is an error compiling the source.
+    """
+    return _fix(source)
+
+
+def supported_language_features() -> List[Dict[str, str]]:
+    """
+    Lists the language features that can be passed to `init`.
+
+    :returns features: A list of dictionaries with the keys `name`,
+        `description` and `stability` (`"preview"` or `"stable"`).
+    """
+    return _supported_language_features()
+
+
+def set_trace_callback(callback: Optional[Callable[[str, float], None]]) -> None:
+    """
+    Sets a function to call with the time spent in each phase of compilation
+    (`"parse"`, `"resolve"`, `"typeck"`, `"lower"`, `"passes"`, `"rca"` and
+    `"codegen"`) and of evaluation (`"eval"`), to profile where the time of
+    large project builds goes.
+
+    :param callback: A function taking the name of the phase and the elapsed
+        milliseconds, or `None` to stop reporting.
+    """
+    _set_trace_callback(callback)
 
 
 def set_quantum_seed(seed: Optional[int]) -> None:
@@ -307,6 +749,64 @@ def set_classical_seed(seed: Optional[int]) -> None:
     get_interpreter().set_classical_seed(seed)
 
 
+def set_limits(
+    *,
+    max_qubits: Optional[int] = None,
+    max_call_depth: Optional[int] = None,
+    max_statements: Optional[int] = None,
+    max_outputs: Optional[int] = None,
+    max_seconds: Optional[float] = None,
+) -> None:
+    """
+    Sets limits on the resources Q# programs can use, so that runaway programs
+    fail fast with a `QSharpError` instead of exhausting memory or running
+    forever. This applies to all Q# code executed from now on. Calling this
+    function again replaces all the limits.
+
+    :param max_qubits: The maximum number of qubits allocated at the same time.
+        If None, the number of qubits is not limited.
+    :param max_call_depth: The maximum number of nested calls, which stops
+        runaway recursion. If None, the call depth is limited to 10,000 nested
+        calls.
+    :param max_statements: The maximum number of statements executed by each
+        evaluation, which stops infinite loops. If None, the number of
+        statements is not limited.
+    :param max_outputs: The maximum number of messages and state dumps printed by
+        each evaluation, which stops output floods. If None, the output is not
+        limited.
+    :param max_seconds: The maximum time in seconds that each evaluation runs for.
+        If None, the time is not limited.
+    """
+    get_interpreter().set_limits(
+        max_qubits, max_call_depth, max_statements, max_outputs, max_seconds
+    )
+
+
+def set_forced_outcomes(
+    *,
+    sequence: Optional[List[Optional[Result]]] = None,
+    qubits: Optional[Dict[int, Result]] = None,
+) -> None:
+    """
+    Forces the outcomes of measurements, so that unit tests can exercise both
+    branches of measurement-conditioned logic deterministically. This applies to
+    all Q# code executed from now on. Calling this function again replaces the
+    forced outcomes, and calling it without arguments stops forcing outcomes.
+
+    When a forced outcome differs from the simulated one, the measured qubit is
+    flipped to match it. Qubits entangled with the measured qubit are left as
+    the simulated outcome collapsed them.
+
+    :param sequence: The outcomes of the next measurements, in the order they
+        are made. Each `run` shot starts from the beginning of the sequence.
+        A `None` entry leaves that measurement unforced.
+    :param qubits: The outcome of every measurement of the given qubits that
+        isn't forced by the sequence, keyed by qubit id. Qubit ids are assigned
+        in allocation order starting from 0, and are reused once released.
+    """
+    get_interpreter().set_forced_outcomes(sequence, qubits)
+
+
 class StateDump:
     """
     A state dump returned from the Q# interpreter.
@@ -317,6 +817,12 @@ class StateDump:
     """
     qubit_count: int
 
+    """
+    The ids of the qubits of the dumped register, or `None` if the whole state
+    of the simulator was dumped.
+    """
+    qubits: Optional[List[int]]
+
     __inner: dict
     __data: StateDumpData
 
@@ -324,6 +830,7 @@ class StateDump:
         self.__data = data
         self.__inner = data.get_dict()
         self.qubit_count = data.qubit_count
+        self.qubits = data.qubits
 
     def __getitem__(self, index: int) -> complex:
         return self.__inner.__getitem__(index)
@@ -334,6 +841,27 @@ class StateDump:
     def __len__(self) -> int:
         return len(self.__inner)
 
+    def as_numpy(self):
+        """
+        Returns the dense state vector as a NumPy array of complex amplitudes
+        indexed by basis state. Basis states that are absent from the dump have
+        an amplitude of zero. Requires NumPy to be installed.
+        """
+        return self.__data.as_numpy()
+
+    def top(self, k: int) -> "StateDump":
+        """
+        Returns a state dump with only the `k` most probable basis states.
+        """
+        return StateDump(self.__data.top(k))
+
+    def filter(self, min_probability: float) -> "StateDump":
+        """
+        Returns a state dump without the basis states whose probability is
+        smaller than `min_probability`.
+        """
+        return StateDump(self.__data.filter(min_probability))
+
     def __repr__(self) -> str:
         return self.__data.__repr__()
 
@@ -344,13 +872,67 @@ class StateDump:
         return self.__data._repr_html_()
 
 
-def dump_machine() -> StateDump:
+def check_equivalence(
+    operation_a: str,
+    operation_b: str,
+    num_qubits: int,
+    *,
+    samples: Optional[int] = None,
+) -> bool:
+    """
+    Checks whether two operations that take an array of qubits implement the
+    same unitary up to a global phase.
+
+    By default the full matrices of the operations are compared, which takes
+    memory exponential in twice the number of qubits. For larger registers,
+    set `samples` to instead apply both operations to that many random states
+    and compare the results, which tells operations that differ apart with high
+    probability. The random states are derived from the quantum seed if set.
+
+    :param operation_a: The first operation.
+    :param operation_b: The second operation.
+    :param num_qubits: The number of qubits the operations are applied to.
+    :param samples: If set, the number of random states to compare the
+        operations on.
+
+    :returns: Whether the operations are equivalent.
+
+    :raises QSharpError: If either operation fails to compile or run, or if it
+        measures qubits.
+    """
+    return get_interpreter().check_equivalence(
+        operation_a, operation_b, num_qubits, samples
+    )
+
+
+def dump_machine(
+    *, min_probability: Optional[float] = None, top: Optional[int] = None
+) -> StateDump:
     """
     Returns the sparse state vector of the simulator as a StateDump object.
+    The filters are applied before the state is passed to Python, which keeps
+    dumps of large states fast.
+
+    :param min_probability: If set, basis states with a smaller probability are dropped.
+    :param top: If set, only this many of the most probable basis states are kept.
 
     :returns: The state of the simulator.
     """
-    return StateDump(get_interpreter().dump_machine())
+    return StateDump(get_interpreter().dump_machine(min_probability, top))
+
+
+def set_state_filter(
+    *, min_probability: Optional[float] = None, top: Optional[int] = None
+) -> None:
+    """
+    Sets the filter applied to the states reported by `DumpMachine` and
+    `DumpRegister` calls in Q# code, so that only significant amplitudes are
+    displayed. Calling this without arguments removes the filter.
+
+    :param min_probability: If set, basis states with a smaller probability are dropped.
+    :param top: If set, only this many of the most probable basis states are kept.
+    """
+    get_interpreter().set_state_filter(min_probability, top)
 
 
 def dump_circuit() -> Circuit:
@@ -3,7 +3,7 @@
 import re
 from typing import Any, Dict, List, Optional, Union
 from dataclasses import dataclass, field
-from .._native import physical_estimates
+from .._native import physical_estimates, LogicalCounts as _LogicalCounts
 
 import json
 
@@ -624,6 +624,55 @@ class EstimatorResult(dict):
         else:
             return LogicalCounts(self.data(0)["logicalCounts"])
 
+    @property
+    def error_budget(self) -> ErrorBudgetPartition:
+        """
+        Returns how the error budget was partitioned into logical errors, T
+        state distillation and rotation synthesis. For a frontier estimation,
+        this is the partition of the point with the shortest runtime.
+        """
+        if not self._is_simple:
+            msg = "Cannot get the error budget of a batching job"
+            raise ValueError(msg)
+        if self._error:
+            raise self._error
+
+        budget = self["errorBudget"]
+        return ErrorBudgetPartition(
+            logical=budget["logical"],
+            t_states=budget["tstates"],
+            rotations=budget["rotations"],
+        )
+
+    @property
+    def frontier(self) -> List[Dict[str, Any]]:
+        """
+        Returns the points of the Pareto frontier of a frontier estimation, in
+        order of increasing runtime, or the only point of a single estimate.
+
+        Each point is a dictionary with the keys `runtime` (in nanoseconds),
+        `physical_qubits`, `code_distance`, `num_tfactories` and `rqops`, and
+        `data`, the raw estimate of the point.
+        """
+        if not self._is_simple:
+            msg = "Cannot get the frontier of a batching job"
+            raise ValueError(msg)
+        if self._error:
+            raise self._error
+
+        entries = self.data().get("frontierEntries") or [self.data()]
+        return [
+            {
+                "runtime": entry["physicalCounts"]["runtime"],
+                "physical_qubits": entry["physicalCounts"]["physicalQubits"],
+                "code_distance": entry["logicalQubit"]["codeDistance"],
+                "num_tfactories": entry["physicalCounts"]["breakdown"]["numTfactories"],
+                "rqops": entry["physicalCounts"]["rqops"],
+                "data": entry,
+            }
+            for entry in entries
+        ]
+
     def _repr_html_(self):
         """
         HTML table representation of the result.
@@ -1032,6 +1081,13 @@ class LogicalCounts(dict):
     """
 
     def __init__(self, data: Dict):
+        """
+        :param data: The counts, keyed by `numQubits`, `tCount`, `rotationCount`,
+            `rotationDepth`, `cczCount`, `ccixCount` and `measurementCount`.
+
+        :raises ValueError: If the counts are negative or inconsistent, such as
+            a rotation depth that exceeds the rotation count.
+        """
         self._data = {}
         self._data["numQubits"] = data.get("numQubits", 0)
         self._data["tCount"] = data.get("tCount", 0)
@@ -1040,6 +1096,18 @@ class LogicalCounts(dict):
         self._data["cczCount"] = data.get("cczCount", 0)
         self._data["ccixCount"] = data.get("ccixCount", 0)
         self._data["measurementCount"] = data.get("measurementCount", 0)
+        try:
+            _LogicalCounts(
+                self._data["numQubits"],
+                t_count=self._data["tCount"],
+                rotation_count=self._data["rotationCount"],
+                rotation_depth=self._data["rotationDepth"],
+                ccz_count=self._data["cczCount"],
+                ccix_count=self._data["ccixCount"],
+                measurement_count=self._data["measurementCount"],
+            )
+        except OverflowError:
+            raise ValueError("The logical counts must be non-negative integers.")
         super().__init__(self._data)
 
     @property
@@ -13,6 +13,37 @@ use std::fmt::Write;
 pub struct DisplayableState(pub Vec<(BigUint, Complex64)>, pub usize);
 
 impl DisplayableState {
+    /// Returns the state restricted to the basis states whose probability is at least
+    /// `min_probability`.
+    #[must_use]
+    pub fn filter(&self, min_probability: f64) -> Self {
+        Self(
+            self.0
+                .iter()
+                .filter(|(_, amplitude)| amplitude.norm_sqr() >= min_probability)
+                .cloned()
+                .collect(),
+            self.1,
+        )
+    }
+
+    /// Returns the state restricted to the `k` most probable basis states, kept in their
+    /// original order. Ties are broken in favor of the basis states that come first.
+    #[must_use]
+    pub fn top(&self, k: usize) -> Self {
+        let mut by_probability = (0..self.0.len()).collect::<Vec<_>>();
+        by_probability.sort_by(|&a, &b| self.0[b].1.norm_sqr().total_cmp(&self.0[a].1.norm_sqr()));
+        by_probability.truncate(k);
+        by_probability.sort_unstable();
+        Self(
+            by_probability
+                .into_iter()
+                .map(|index| self.0[index].clone())
+                .collect(),
+            self.1,
+        )
+    }
+
     pub fn to_plain(&self) -> String {
         format!(
             "STATE:{}",
@@ -53,7 +84,70 @@ impl DisplayableState {
     }
 }
 
+/// Limits a state dump to its significant amplitudes, so that large states aren't transferred
+/// to Python in full.
+#[derive(Clone, Copy, Default)]
+pub struct StateFilter {
+    /// The smallest probability of a basis state that is kept.
+    pub min_probability: Option<f64>,
+    /// The number of most probable basis states that are kept.
+    pub top: Option<usize>,
+}
+
+impl StateFilter {
+    #[must_use]
+    pub fn apply(&self, state: DisplayableState) -> DisplayableState {
+        let state = match self.min_probability {
+            Some(min_probability) => state.filter(min_probability),
+            None => state,
+        };
+        match self.top {
+            Some(k) => state.top(k),
+            None => state,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DisplayableMatrix(pub Vec<Vec<Complex64>>);
+
+impl DisplayableMatrix {
+    pub fn to_plain(&self) -> String {
+        format!(
+            "MATRIX:{}",
+            self.0.iter().fold(String::new(), |mut output, row| {
+                let _ = write!(
+                    output,
+                    "\n{}",
+                    row.iter().map(fmt_complex).collect::<Vec<_>>().join(" ")
+                );
+                output
+            })
+        )
+    }
+
+    pub fn to_html(&self) -> String {
+        format!(
+            "<table>{}</table>",
+            self.0.iter().fold(String::new(), |mut output, row| {
+                let _ = write!(
+                    output,
+                    "<tr>{}</tr>",
+                    row.iter().fold(String::new(), |mut cells, entry| {
+                        let _ = write!(cells, "<td>{}</td>", fmt_complex(entry));
+                        cells
+                    })
+                );
+                output
+            })
+        )
+    }
+}
+
 pub enum DisplayableOutput {
-    State(DisplayableState),
+    /// A state dump, along with the ids of the qubits it is restricted to if it is the dump of
+    /// a register.
+    State(DisplayableState, Option<Vec<usize>>),
+    Matrix(DisplayableMatrix),
     Message(String),
 }
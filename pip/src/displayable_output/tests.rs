@@ -4,7 +4,7 @@
 use num_bigint::BigUint;
 use num_complex::Complex;
 
-use crate::displayable_output::DisplayableState;
+use crate::displayable_output::{DisplayableMatrix, DisplayableState};
 
 #[test]
 fn display_neg_zero() {
@@ -39,3 +39,59 @@ fn display_preserves_order() {
         s.to_plain()
     );
 }
+
+fn state_of(amplitudes: &[(u64, f64)]) -> DisplayableState {
+    DisplayableState(
+        amplitudes
+            .iter()
+            .map(|&(id, re)| (BigUint::from(id), Complex::new(re, 0.0)))
+            .collect(),
+        2,
+    )
+}
+
+#[test]
+fn filter_drops_improbable_states() {
+    let s = state_of(&[(0, 0.1), (1, 0.7), (2, 0.0), (3, 0.7)]).filter(0.25);
+    assert_eq!(
+        "STATE:\n|01⟩: 0.7000+0.0000𝑖\n|11⟩: 0.7000+0.0000𝑖",
+        s.to_plain()
+    );
+    assert_eq!(2, s.1);
+}
+
+#[test]
+fn top_keeps_most_probable_states_in_order() {
+    let s = state_of(&[(0, 0.1), (1, -0.8), (2, 0.3), (3, 0.5)]).top(2);
+    assert_eq!(
+        "STATE:\n|01⟩: −0.8000+0.0000𝑖\n|11⟩: 0.5000+0.0000𝑖",
+        s.to_plain()
+    );
+}
+
+#[test]
+fn top_breaks_ties_by_basis_state() {
+    let s = state_of(&[(0, 0.5), (1, 0.5), (2, 0.5), (3, 0.5)]).top(3);
+    assert_eq!(
+        "STATE:\n|00⟩: 0.5000+0.0000𝑖\n|01⟩: 0.5000+0.0000𝑖\n|10⟩: 0.5000+0.0000𝑖",
+        s.to_plain()
+    );
+}
+
+#[test]
+fn top_larger_than_state_keeps_everything() {
+    let s = state_of(&[(0, 0.6), (3, 0.8)]).top(10);
+    assert_eq!(2, s.0.len());
+}
+
+#[test]
+fn display_matrix_rows() {
+    let m = DisplayableMatrix(vec![
+        vec![Complex::new(0.0, 0.0), Complex::new(1.0, 0.0)],
+        vec![Complex::new(0.0, -1.0), Complex::new(-0.00001, 0.0)],
+    ]);
+    assert_eq!(
+        "MATRIX:\n0.0000+0.0000𝑖 1.0000+0.0000𝑖\n0.0000−1.0000𝑖 0.0000+0.0000𝑖",
+        m.to_plain()
+    );
+}
@@ -2,32 +2,48 @@
 // Licensed under the MIT License.
 
 use crate::{
-    displayable_output::{DisplayableOutput, DisplayableState},
+    displayable_output::{DisplayableMatrix, DisplayableOutput, DisplayableState, StateFilter},
     fs::file_system,
+    numpy_interop::{matrix_to_numpy, state_to_numpy},
+    runtime_flags::{RuntimeCapability, RuntimeFeature},
+    timing::{profile_compilation, set_trace_callback, CompilationProfile},
 };
 use miette::Report;
-use num_bigint::BigUint;
+use num_bigint::{BigInt, BigUint};
 use num_complex::Complex64;
 use pyo3::{
     create_exception,
-    exceptions::PyException,
+    exceptions::{PyException, PyValueError},
     prelude::*,
     pyclass::CompareOp,
-    types::{PyComplex, PyDict, PyList, PyString, PyTuple},
+    types::{PyBool, PyComplex, PyDict, PyList, PyString, PyTuple},
 };
 use qsc::{
+    circuit::{ConditionalRender, Operation, QubitLifetime, Register, SourceLocation},
+    compile::{self, StdLib},
     fir,
+    hints::HintKind,
     interpret::{
         self,
         output::{Error, Receiver},
-        CircuitEntryPoint, Value,
+        CircuitEntryPoint, DumpFormat, EquivalenceMethod, ForcedOutcomes, GateSet, Limits,
+        Recording, SimulatorKind, Value,
     },
+    language_features::KNOWN_LANGUAGE_FEATURES,
+    line_column::{Encoding, Range},
+    linter,
     project::{FileSystem, Manifest, ManifestDescriptor},
     target::Profile,
-    LanguageFeatures, PackageType, SourceMap,
+    LanguageFeatures, PackageStore, PackageType, PassConfig, RuntimeCapabilityFlags,
+    SourceContents, SourceMap, SourceName,
 };
-use resource_estimator::{self as re, estimate_expr};
-use std::fmt::Write;
+use resource_estimator::{self as re, estimate_call, estimate_expr};
+use rustc_hash::FxHashMap;
+use std::{collections::BTreeMap, fmt::Write, num::NonZeroUsize, rc::Rc, time::Duration};
+
+/// The time limit of each run of an interpreter for untrusted code, on top of the limits of
+/// [`Limits::untrusted`].
+const UNTRUSTED_MAX_DURATION: Duration = Duration::from_secs(30);
 
 #[pymodule]
 fn _native(py: Python, m: &PyModule) -> PyResult<()> {
@@ -37,8 +53,19 @@ fn _native(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Pauli>()?;
     m.add_class::<Output>()?;
     m.add_class::<StateDumpData>()?;
+    m.add_class::<MatrixDumpData>()?;
     m.add_class::<Circuit>()?;
+    m.add_class::<LogicalCounts>()?;
+    m.add_class::<RuntimeCapability>()?;
+    m.add_class::<RuntimeFeature>()?;
     m.add_function(wrap_pyfunction!(physical_estimates, m)?)?;
+    m.add_function(wrap_pyfunction!(hints, m)?)?;
+    m.add_function(wrap_pyfunction!(fix, m)?)?;
+    m.add_function(wrap_pyfunction!(supported_language_features, m)?)?;
+    m.add_function(wrap_pyfunction!(set_trace_callback, m)?)?;
+    // Errors that don't come from running Q# code have no call stack.
+    py.get_type::<QSharpError>()
+        .setattr("stack_frames", PyTuple::empty(py))?;
     m.add("QSharpError", py.get_type::<QSharpError>())?;
 
     Ok(())
@@ -62,8 +89,125 @@ pub(crate) enum TargetProfile {
 }
 
 #[pyclass(unsendable)]
+#[allow(clippy::struct_field_names)]
 pub(crate) struct Interpreter {
     pub(crate) interpreter: interpret::Interpreter,
+    config: InterpreterConfig,
+    /// The recording of the most recent call to `run`, which may have failed.
+    last_recording: Option<Recording>,
+    /// The time and memory that compiling the sources took.
+    compilation: CompilationProfile,
+}
+
+/// The settings an interpreter session was created with, kept so that the
+/// session can be inspected and recreated.
+#[derive(Clone)]
+struct InterpreterConfig {
+    target: TargetProfile,
+    language_features: Vec<String>,
+    manifest_descriptor: Option<ManifestDescriptor>,
+    /// The sources loaded from the project. These are shared with sessions
+    /// created by `clone_with` so the project isn't read from disk again.
+    sources: Vec<(SourceName, SourceContents)>,
+    /// The standard library the sources are compiled against.
+    std: StdLib,
+    quantum_seed: Option<u64>,
+    classical_seed: Option<u64>,
+    /// The kind of simulator the programs run on.
+    simulator: SimulatorKind,
+    /// The limits on the resources used by the programs the interpreter runs.
+    limits: Limits,
+    /// The measurement outcomes forced on the programs the interpreter runs.
+    forced_outcomes: ForcedOutcomes,
+    /// Applied to the states reported by `DumpMachine` and `DumpRegister` calls.
+    state_filter: StateFilter,
+    /// Embedded into the QIR and circuits generated by the interpreter.
+    metadata: BTreeMap<String, String>,
+    /// The native gates that the QIR and circuits generated by the interpreter use.
+    gate_set: GateSet,
+}
+
+/// A recording returned by `last_recording`, passed back to `run` to replay it.
+struct PyRecording(Recording);
+
+impl FromPyObject<'_> for PyRecording {
+    fn extract(ob: &PyAny) -> PyResult<Self> {
+        let dict = ob.downcast::<PyDict>()?;
+        let get = |key: &str| {
+            dict.get_item(key)?.ok_or(PyException::new_err(format!(
+                "missing key `{key}` in recording"
+            )))
+        };
+        let measurements = get("measurements")?
+            .downcast::<PyList>()?
+            .iter()
+            .map(|item| Ok(*item.extract::<PyRef<Result>>()? == Result::One))
+            .collect::<PyResult<_>>()?;
+        Ok(Self(Recording {
+            quantum_seed: get("quantum_seed")?.extract()?,
+            classical_seed: get("classical_seed")?.extract()?,
+            measurements,
+        }))
+    }
+}
+
+/// An argument passed to `run_entry`, or a value returned by the callback of a custom
+/// intrinsic, converted to the Q# value it stands for.
+struct PyValue(Value);
+
+impl FromPyObject<'_> for PyValue {
+    fn extract(ob: &PyAny) -> PyResult<Self> {
+        if ob.is_none() {
+            return Ok(Self(Value::unit()));
+        }
+        // `bool` is a subclass of `int` in Python, so it must be checked first.
+        if let Ok(val) = ob.downcast::<PyBool>() {
+            return Ok(Self(Value::Bool(val.is_true())));
+        }
+        if let Ok(val) = ob.extract::<i64>() {
+            return Ok(Self(Value::Int(val)));
+        }
+        if let Ok(val) = ob.extract::<BigInt>() {
+            return Ok(Self(Value::BigInt(val)));
+        }
+        if let Ok(val) = ob.extract::<f64>() {
+            return Ok(Self(Value::Double(val)));
+        }
+        if let Ok(val) = ob.downcast::<PyString>() {
+            return Ok(Self(Value::String(val.to_str()?.into())));
+        }
+        if let Ok(val) = ob.extract::<PyRef<Result>>() {
+            return Ok(Self(Value::Result(interpret::Result::Val(
+                *val == Result::One,
+            ))));
+        }
+        if let Ok(val) = ob.extract::<PyRef<Pauli>>() {
+            return Ok(Self(Value::Pauli(match *val {
+                Pauli::I => fir::Pauli::I,
+                Pauli::X => fir::Pauli::X,
+                Pauli::Y => fir::Pauli::Y,
+                Pauli::Z => fir::Pauli::Z,
+            })));
+        }
+        if let Ok(val) = ob.downcast::<PyList>() {
+            let items = val
+                .iter()
+                .map(|item| Ok(item.extract::<PyValue>()?.0))
+                .collect::<PyResult<Vec<_>>>()?;
+            return Ok(Self(Value::Array(items.into())));
+        }
+        if let Ok(val) = ob.downcast::<PyTuple>() {
+            let items = val
+                .iter()
+                .map(|item| Ok(item.extract::<PyValue>()?.0))
+                .collect::<PyResult<Vec<_>>>()?;
+            return Ok(Self(Value::Tuple(items.into())));
+        }
+        Err(QSharpError::new_err(format!(
+            "unsupported argument type `{}`",
+            ob.get_type().name()?
+        )))
+    }
 }
 
 pub(crate) struct PyManifestDescriptor(ManifestDescriptor);
@@ -82,6 +226,7 @@ impl FromPyObject<'_> for PyManifestDescriptor {
             .downcast::<PyDict>()?;
 
         let language_features = get_dict_opt_list_string(manifest, "features")?;
+        let config = get_dict_opt_list_string(manifest, "config")?;
 
         Ok(Self(ManifestDescriptor {
             manifest: Manifest {
@@ -89,6 +234,7 @@ impl FromPyObject<'_> for PyManifestDescriptor {
                 license: get_dict_opt_string(manifest, "license")?,
                 language_features,
                 lints: vec![],
+                config,
             },
             manifest_dir: manifest_dir.into(),
         }))
@@ -108,15 +254,20 @@ impl Interpreter {
         manifest_descriptor: Option<PyManifestDescriptor>,
         read_file: Option<PyObject>,
         list_directory: Option<PyObject>,
+        simulator: Option<&str>,
+        untrusted: Option<bool>,
     ) -> PyResult<Self> {
-        let target = match target {
-            TargetProfile::Unrestricted => Profile::Unrestricted,
-            TargetProfile::Base => Profile::Base,
+        let simulator = simulator.map_or(Ok(SimulatorKind::default()), parse_simulator)?;
+        let limits = if untrusted.unwrap_or_default() {
+            Limits {
+                max_duration: Some(UNTRUSTED_MAX_DURATION),
+                ..Limits::untrusted()
+            }
+        } else {
+            Limits::default()
         };
-        let language_features = language_features.unwrap_or_default();
-
-        let sources = if let Some(manifest_descriptor) = manifest_descriptor {
-            let project = file_system(
+        let sources = if let Some(manifest_descriptor) = &manifest_descriptor {
+            file_system(
                 py,
                 read_file.expect(
                     "file system hooks should have been passed in with a manifest descriptor",
@@ -126,23 +277,125 @@ impl Interpreter {
                 ),
             )
             .load_project(&manifest_descriptor.0)
-            .map_py_err()?;
-            SourceMap::new(project.sources, None)
+            .map_py_err()?
+            .sources
         } else {
-            SourceMap::default()
+            Vec::new()
         };
 
-        let language_features = LanguageFeatures::from_iter(language_features);
-
-        match interpret::Interpreter::new(
-            true,
+        Self::from_config(InterpreterConfig {
+            target,
+            language_features: language_features.unwrap_or_default(),
+            manifest_descriptor: manifest_descriptor.map(|descriptor| descriptor.0),
             sources,
-            PackageType::Lib,
-            target.into(),
-            language_features,
-        ) {
-            Ok(interpreter) => Ok(Self { interpreter }),
-            Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+            std: StdLib::Builtin,
+            quantum_seed: None,
+            classical_seed: None,
+            simulator,
+            limits,
+            forced_outcomes: ForcedOutcomes::default(),
+            state_filter: StateFilter::default(),
+            metadata: BTreeMap::new(),
+            gate_set: GateSet::default(),
+        })
+    }
+
+    /// Returns the effective configuration of the interpreter.
+    ///
+    /// :returns config: A dictionary with the keys `target_profile`, `language_features`,
+    /// `manifest`, `stdlib`, `quantum_seed`, `classical_seed`, `simulator`, `metadata`
+    /// and `gate_set`.
+    fn config(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let config = PyDict::new(py);
+        config.set_item("target_profile", self.config.target.into_py(py))?;
+        config.set_item("language_features", self.config.language_features.clone())?;
+        let manifest = match &self.config.manifest_descriptor {
+            Some(descriptor) => {
+                let manifest = PyDict::new(py);
+                manifest.set_item("author", descriptor.manifest.author.clone())?;
+                manifest.set_item("license", descriptor.manifest.license.clone())?;
+                manifest.set_item("features", descriptor.manifest.language_features.clone())?;
+                let manifest_descriptor = PyDict::new(py);
+                manifest_descriptor.set_item(
+                    "manifest_dir",
+                    descriptor.manifest_dir.to_string_lossy().to_string(),
+                )?;
+                manifest_descriptor.set_item("manifest", manifest)?;
+                manifest_descriptor.into_py(py)
+            }
+            None => py.None(),
+        };
+        config.set_item("manifest", manifest)?;
+        let stdlib = match &self.config.std {
+            StdLib::Builtin => true.into_py(py),
+            StdLib::None => false.into_py(py),
+            StdLib::Custom(sources) => sources
+                .iter()
+                .map(|source| (source.name.to_string(), source.contents.to_string()))
+                .collect::<BTreeMap<_, _>>()
+                .into_py(py),
+        };
+        config.set_item("stdlib", stdlib)?;
+        config.set_item("quantum_seed", self.config.quantum_seed)?;
+        config.set_item("classical_seed", self.config.classical_seed)?;
+        config.set_item("simulator", self.config.simulator.to_string())?;
+        config.set_item("metadata", self.config.metadata.clone())?;
+        config.set_item("gate_set", self.config.gate_set.to_string())?;
+        Ok(config.into())
+    }
+
+    /// Creates a new interpreter with the same configuration as this one, except
+    /// for the given overrides. The project sources already loaded by this
+    /// interpreter are reused, but fragments evaluated in this session are not
+    /// carried over to the new one. Unless the target profile, language features
+    /// or standard library are overridden, the new interpreter shares the
+    /// compiled standard library and sources of this one instead of compiling
+    /// them again.
+    ///
+    /// :param overrides: Any of `target_profile`, `language_features`, `stdlib`,
+    /// `quantum_seed`, `classical_seed`, `simulator`, `metadata` and `gate_set`.
+    ///
+    /// :returns interpreter: The new interpreter.
+    ///
+    /// :raises QSharpError: If the sources fail to compile with the new configuration.
+    #[pyo3(signature = (**overrides))]
+    fn clone_with(&self, overrides: Option<&PyDict>) -> PyResult<Self> {
+        let mut config = self.config.clone();
+        let mut recompile = false;
+        for (key, value) in overrides.into_iter().flatten() {
+            match key.extract::<&str>()? {
+                "target_profile" => {
+                    config.target = value.extract()?;
+                    recompile = true;
+                }
+                "language_features" => {
+                    config.language_features = value.extract()?;
+                    recompile = true;
+                }
+                "stdlib" => {
+                    config.std = extract_std_lib(value)?;
+                    recompile = true;
+                }
+                "quantum_seed" => config.quantum_seed = value.extract()?,
+                "classical_seed" => config.classical_seed = value.extract()?,
+                "simulator" => config.simulator = parse_simulator(value.extract()?)?,
+                "metadata" => config.metadata = value.extract()?,
+                "gate_set" => config.gate_set = parse_gate_set(value.extract()?)?,
+                key => {
+                    return Err(PyException::new_err(format!(
+                        "unknown configuration override `{key}`"
+                    )))
+                }
+            }
+        }
+        if recompile {
+            Self::from_config(config)
+        } else {
+            Ok(Self::with_config(
+                self.interpreter.fork(),
+                config,
+                self.compilation.clone(),
+            ))
         }
     }
 
@@ -160,29 +413,275 @@ impl Interpreter {
         input: &str,
         callback: Option<PyObject>,
     ) -> PyResult<PyObject> {
-        let mut receiver = OptionalCallbackReceiver { callback, py };
+        let mut receiver = OptionalCallbackReceiver {
+            callback,
+            py,
+            state_filter: self.config.state_filter,
+            quiet: false,
+        };
         match self.interpreter.eval_fragments(&mut receiver, input) {
             Ok(value) => Ok(ValueWrapper(value).into_py(py)),
-            Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+            Err(errors) => Err(run_error(py, errors)),
         }
     }
 
     /// Sets the quantum seed for the interpreter.
     fn set_quantum_seed(&mut self, seed: Option<u64>) {
+        self.config.quantum_seed = seed;
         self.interpreter.set_quantum_seed(seed);
     }
 
     /// Sets the classical seed for the interpreter.
     fn set_classical_seed(&mut self, seed: Option<u64>) {
+        self.config.classical_seed = seed;
         self.interpreter.set_classical_seed(seed);
     }
 
+    /// Sets the limits on the resources used by the programs the interpreter runs.
+    /// A program that exceeds a limit fails with a runtime error. A limit that is
+    /// `None` isn't enforced, except for the call depth, which falls back to the
+    /// default limit so that runaway recursion can't overflow the stack.
+    ///
+    /// :param max_qubits: The maximum number of qubits allocated at the same time.
+    /// :param max_call_depth: The maximum number of nested calls.
+    /// :param max_statements: The maximum number of statements executed per run.
+    /// :param max_outputs: The maximum number of messages and state dumps printed per run.
+    /// :param max_seconds: The maximum time in seconds that each run takes.
+    #[pyo3(signature = (max_qubits=None, max_call_depth=None, max_statements=None, max_outputs=None, max_seconds=None))]
+    fn set_limits(
+        &mut self,
+        max_qubits: Option<usize>,
+        max_call_depth: Option<usize>,
+        max_statements: Option<u64>,
+        max_outputs: Option<u64>,
+        max_seconds: Option<f64>,
+    ) -> PyResult<()> {
+        let max_duration = max_seconds
+            .map(|seconds| {
+                Duration::try_from_secs_f64(seconds).map_err(|_| {
+                    PyValueError::new_err("the time limit must be a non-negative number of seconds")
+                })
+            })
+            .transpose()?;
+        self.config.limits = Limits {
+            max_qubits,
+            max_call_depth: max_call_depth.or(Limits::default().max_call_depth),
+            max_statements,
+            max_outputs,
+            max_duration,
+        };
+        self.interpreter.set_limits(self.config.limits);
+        Ok(())
+    }
+
+    /// Forces the outcomes of measurements, so that tests can exercise both
+    /// branches of measurement-conditioned logic. Calling this again replaces
+    /// the forced outcomes.
+    ///
+    /// :param sequence: The outcomes of the next measurements, in the order they
+    /// are made. `None` leaves a measurement unforced.
+    /// :param qubits: The outcome of every other measurement of the given qubits,
+    /// keyed by qubit id.
+    #[allow(clippy::needless_pass_by_value)]
+    #[pyo3(signature = (sequence=None, qubits=None))]
+    fn set_forced_outcomes(
+        &mut self,
+        sequence: Option<Vec<Option<PyRef<Result>>>>,
+        qubits: Option<FxHashMap<usize, PyRef<Result>>>,
+    ) {
+        self.config.forced_outcomes = ForcedOutcomes {
+            sequence: sequence
+                .unwrap_or_default()
+                .iter()
+                .map(|outcome| outcome.as_ref().map(|outcome| **outcome == Result::One))
+                .collect(),
+            qubits: qubits
+                .unwrap_or_default()
+                .iter()
+                .map(|(&qubit, outcome)| (qubit, **outcome == Result::One))
+                .collect(),
+        };
+        self.interpreter
+            .set_forced_outcomes(self.config.forced_outcomes.clone());
+    }
+
+    /// Sets the key/value metadata embedded into the QIR and circuits generated
+    /// by the interpreter.
+    fn set_metadata(&mut self, metadata: BTreeMap<String, String>) {
+        self.config.metadata.clone_from(&metadata);
+        self.interpreter.set_metadata(metadata);
+    }
+
+    /// Registers a Python function that simulates a custom intrinsic, which is
+    /// called with the arguments of the intrinsic and returns its result.
+    ///
+    /// :param name: The QIR name given by the `@CustomIntrinsic` attribute of the
+    /// intrinsic, or the name of the intrinsic callable if it has none.
+    /// :param callback: The function that simulates the intrinsic.
+    fn register_intrinsic(&mut self, name: &str, callback: PyObject) {
+        self.interpreter.register_intrinsic(
+            name,
+            Rc::new(move |arg| {
+                Python::with_gil(|py| {
+                    callback
+                        .call1(py, (ValueWrapper(arg).into_py(py),))
+                        .and_then(|value| value.extract::<PyValue>(py))
+                        .map(|value| value.0)
+                        .map_err(|error| error.to_string())
+                })
+            }),
+        );
+    }
+
+    /// Registers a Python function as the implementation of a new Q# function,
+    /// which Q# code can call to run classical logic, such as an optimizer,
+    /// while it is simulated.
+    ///
+    /// :param name: The name of the Q# function.
+    /// :param params: The names and Q# types of the parameters of the function.
+    /// :param output: The Q# return type of the function.
+    /// :param callback: The Python function, which is called with an argument for
+    /// each parameter and returns the result of the Q# function.
+    fn register_function(
+        &mut self,
+        name: &str,
+        params: Vec<(String, String)>,
+        output: &str,
+        callback: PyObject,
+    ) -> PyResult<()> {
+        self.interpreter
+            .register_function(name, &params, output, move |args| {
+                Python::with_gil(|py| {
+                    let args = PyTuple::new(
+                        py,
+                        args.into_iter().map(|arg| ValueWrapper(arg).into_py(py)),
+                    );
+                    callback
+                        .call1(py, args)
+                        .and_then(|value| value.extract::<PyValue>(py))
+                        .map(|value| value.0)
+                        .map_err(|error| error.to_string())
+                })
+            })
+            .map_err(|errors| QSharpError::new_err(format_errors(errors)))
+    }
+
+    /// Sets the native gates of the target, which the intrinsics in the QIR and
+    /// circuits generated by the interpreter are decomposed into.
+    ///
+    /// :param gate_set: `full` for every intrinsic, or the comma-separated names
+    /// of a supported gate set, `rz,sx,cx` or `rx,ry,rzz`.
+    fn set_gate_set(&mut self, gate_set: &str) -> PyResult<()> {
+        self.config.gate_set = parse_gate_set(gate_set)?;
+        self.interpreter.set_gate_set(self.config.gate_set);
+        Ok(())
+    }
+
+    /// Sets the filter applied to the states reported by `DumpMachine` and
+    /// `DumpRegister` calls in Q# code, so that only significant amplitudes are
+    /// passed to the output callback.
+    ///
+    /// :param min_probability: If set, basis states with a smaller probability are dropped.
+    /// :param top: If set, only this many of the most probable basis states are kept.
+    #[pyo3(signature = (min_probability=None, top=None))]
+    fn set_state_filter(&mut self, min_probability: Option<f64>, top: Option<usize>) {
+        self.config.state_filter = StateFilter {
+            min_probability,
+            top,
+        };
+    }
+
     /// Dumps the quantum state of the interpreter.
     /// Returns a tuple of (amplitudes, num_qubits), where amplitudes is a dictionary from integer indices to
     /// pairs of real and imaginary amplitudes.
-    fn dump_machine(&mut self) -> StateDumpData {
+    ///
+    /// :param min_probability: If set, basis states with a smaller probability are dropped.
+    /// :param top: If set, only this many of the most probable basis states are kept.
+    #[pyo3(signature = (min_probability=None, top=None))]
+    fn dump_machine(&mut self, min_probability: Option<f64>, top: Option<usize>) -> StateDumpData {
         let (state, qubit_count) = self.interpreter.get_quantum_state();
-        StateDumpData(DisplayableState(state, qubit_count))
+        let filter = StateFilter {
+            min_probability,
+            top,
+        };
+        StateDumpData(filter.apply(DisplayableState(state, qubit_count)), None)
+    }
+
+    /// Computes the unitary matrix of an operation that takes an array of qubits
+    /// by simulating it on a new instance of the simulator.
+    ///
+    /// :param operation: The operation, which must take an array of qubits and
+    ///     must not measure them.
+    /// :param num_qubits: The number of qubits the operation is applied to.
+    ///
+    /// :returns: The rows of the matrix as lists of complex entries.
+    fn dump_operation(
+        &mut self,
+        py: Python,
+        operation: &str,
+        num_qubits: usize,
+    ) -> PyResult<Py<PyList>> {
+        match self.interpreter.dump_operation(operation, num_qubits) {
+            Ok(matrix) => Ok(MatrixDumpData(DisplayableMatrix(matrix)).get_matrix(py)),
+            Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+        }
+    }
+
+    /// Checks whether two operations that take an array of qubits implement the
+    /// same unitary up to a global phase.
+    ///
+    /// :param operation_a: The first operation.
+    /// :param operation_b: The second operation.
+    /// :param num_qubits: The number of qubits the operations are applied to.
+    /// :param samples: If set, the operations are applied to this many random
+    ///     states instead of comparing their full matrices.
+    ///
+    /// :returns: Whether the operations are equivalent.
+    #[pyo3(signature = (operation_a, operation_b, num_qubits, samples=None))]
+    fn check_equivalence(
+        &mut self,
+        operation_a: &str,
+        operation_b: &str,
+        num_qubits: usize,
+        samples: Option<u32>,
+    ) -> PyResult<bool> {
+        let method = samples.map_or(EquivalenceMethod::Matrix, EquivalenceMethod::Sampling);
+        self.interpreter
+            .check_equivalence(operation_a, operation_b, num_qubits, method)
+            .map_err(|errors| QSharpError::new_err(format_errors(errors)))
+    }
+
+    /// Parses the given source and dumps its AST without compiling it.
+    ///
+    /// :param source: The source, which may contain namespaces and statements.
+    /// :param json: Whether to dump the tree as JSON instead of text.
+    ///
+    /// :returns: The dump of the AST.
+    #[pyo3(signature = (source, json=false))]
+    fn dump_ast(&self, source: &str, json: bool) -> PyResult<String> {
+        self.interpreter
+            .dump_ast(source, dump_format(json))
+            .map_err(|errors| QSharpError::new_err(format_errors(errors)))
+    }
+
+    /// Dumps the HIR of the sources and the code evaluated so far.
+    ///
+    /// :param json: Whether to dump the tree as JSON instead of text.
+    ///
+    /// :returns: The dump of the HIR.
+    #[pyo3(signature = (json=false))]
+    fn dump_hir(&self, json: bool) -> String {
+        self.interpreter.dump_hir(dump_format(json))
+    }
+
+    /// Dumps the FIR of the sources and the code evaluated so far.
+    ///
+    /// :param json: Whether to dump the tree as JSON instead of text.
+    ///
+    /// :returns: The dump of the FIR.
+    #[pyo3(signature = (json=false))]
+    fn dump_fir(&self, json: bool) -> String {
+        self.interpreter.dump_fir(dump_format(json))
     }
 
     /// Dumps the current circuit state of the interpreter.
@@ -193,29 +692,426 @@ impl Interpreter {
         Circuit(self.interpreter.get_circuit()).into_py(py)
     }
 
+    #[allow(clippy::needless_pass_by_value)]
+    #[pyo3(signature = (entry_expr, callback=None, replay=None, quiet=false))]
     fn run(
         &mut self,
         py: Python,
         entry_expr: &str,
         callback: Option<PyObject>,
+        replay: Option<PyRecording>,
+        quiet: bool,
     ) -> PyResult<PyObject> {
-        let mut receiver = OptionalCallbackReceiver { callback, py };
-        match self.interpreter.run(&mut receiver, entry_expr) {
+        let mut receiver = OptionalCallbackReceiver {
+            callback,
+            py,
+            state_filter: self.config.state_filter,
+            quiet,
+        };
+        match self.interpreter.run_recorded(
+            &mut receiver,
+            entry_expr,
+            replay.as_ref().map(|replay| &replay.0),
+        ) {
+            Ok((result, recording)) => {
+                self.last_recording = Some(recording);
+                match result {
+                    Ok(v) => Ok(ValueWrapper(v).into_py(py)),
+                    Err(errors) => Err(run_error(py, errors)),
+                }
+            }
+            Err(errors) => Err(run_error(py, errors)),
+        }
+    }
+
+    /// Runs the entry point of the compiled sources, the callable with the
+    /// `@EntryPoint()` attribute, with the given arguments.
+    ///
+    /// :param args: The arguments to pass to the entry point, which are checked
+    /// against the types of its parameters.
+    /// :param callback: A callback function that will be called with each output.
+    ///
+    /// :returns: The value returned by the entry point.
+    ///
+    /// :raises QSharpError: If there is no single entry point, the arguments
+    /// don't match its parameters, or there is an error running it.
+    #[allow(clippy::needless_pass_by_value)]
+    #[pyo3(signature = (args=None, callback=None))]
+    fn run_entry(
+        &mut self,
+        py: Python,
+        args: Option<Vec<PyValue>>,
+        callback: Option<PyObject>,
+    ) -> PyResult<PyObject> {
+        let mut receiver = OptionalCallbackReceiver {
+            callback,
+            py,
+            state_filter: self.config.state_filter,
+            quiet: false,
+        };
+        let args = args
+            .unwrap_or_default()
+            .into_iter()
+            .map(|arg| arg.0)
+            .collect();
+        match self.interpreter.run_entry(&mut receiver, args) {
             Ok(result) => match result {
                 Ok(v) => Ok(ValueWrapper(v).into_py(py)),
-                Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+                Err(errors) => Err(run_error(py, errors)),
             },
-            Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+            Err(errors) => Err(run_error(py, errors)),
+        }
+    }
+
+    /// Runs the given entry expression for the given number of shots on a pool
+    /// of threads, each shot with an independent instance of the simulator and
+    /// a seed derived from the quantum seed of the interpreter, or from a random
+    /// seed if none is set. Each thread compiles its own copy of the program,
+    /// and the GIL is released while the shots run. Output of the program is
+    /// discarded.
+    ///
+    /// :param entry_expr: An entry expression.
+    /// :param shots: The number of shots to run.
+    /// :param workers: The number of threads to run the shots on. Defaults to
+    /// the available parallelism of the machine.
+    /// :param measurement_statistics: Whether to record the outcome of each
+    /// measured qubit and compute statistics of the outcomes.
+    ///
+    /// :returns: A dictionary with the keys `seed`, `counts`, which maps each
+    /// returned value as a string to the number of shots that returned it,
+    /// `failures`, a list of dictionaries with the keys `shot`, `seed` and `errors`,
+    /// and `measurement_statistics`, a dictionary with the keys `qubits`, `shots`,
+    /// `marginals`, `joint` and `correlations`, or `None` if not requested.
+    ///
+    /// :raises QSharpError: If there is an error compiling the entry expression.
+    #[pyo3(signature = (entry_expr, shots, workers=None, measurement_statistics=false))]
+    fn run_shots(
+        &mut self,
+        py: Python,
+        entry_expr: &str,
+        shots: u32,
+        workers: Option<usize>,
+        measurement_statistics: bool,
+    ) -> PyResult<Py<PyDict>> {
+        let workers = match workers {
+            Some(workers) => NonZeroUsize::new(workers)
+                .ok_or(PyException::new_err("`workers` must be at least 1"))?,
+            None => std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+        };
+        let mut runner = self
+            .interpreter
+            .shot_runner(entry_expr)
+            .map_err(|errors| QSharpError::new_err(format_errors(errors)))?;
+        runner.set_measurement_statistics(measurement_statistics);
+        let results = py.allow_threads(|| runner.run(shots, workers));
+
+        let dict = PyDict::new(py);
+        dict.set_item("seed", results.seed)?;
+        dict.set_item("counts", results.counts)?;
+        let failures = PyList::empty(py);
+        for failure in results.failures {
+            let failure_dict = PyDict::new(py);
+            failure_dict.set_item("shot", failure.shot)?;
+            failure_dict.set_item("seed", failure.seed)?;
+            failure_dict.set_item("errors", format_errors(failure.errors))?;
+            failures.append(failure_dict)?;
         }
+        dict.set_item("failures", failures)?;
+        let statistics = match results.measurement_statistics {
+            Some(statistics) => {
+                let statistics_dict = PyDict::new(py);
+                statistics_dict.set_item("qubits", statistics.qubits)?;
+                statistics_dict.set_item("shots", statistics.shots)?;
+                statistics_dict.set_item("marginals", statistics.marginals)?;
+                statistics_dict.set_item("joint", statistics.joint)?;
+                statistics_dict.set_item("correlations", statistics.correlations)?;
+                statistics_dict.into_py(py)
+            }
+            None => py.None(),
+        };
+        dict.set_item("measurement_statistics", statistics)?;
+        Ok(dict.into())
     }
 
-    fn qir(&mut self, _py: Python, entry_expr: &str) -> PyResult<String> {
-        match self.interpreter.qirgen(entry_expr) {
+    /// The measurement outcomes and seeds of the most recent run, including a
+    /// run that failed, which replays the run when passed to `run`.
+    ///
+    /// :returns: A dictionary with the keys `quantum_seed`, `classical_seed` and
+    /// `measurements`, or `None` if nothing has been run.
+    fn last_recording(&self, py: Python) -> PyResult<Option<Py<PyDict>>> {
+        let Some(recording) = &self.last_recording else {
+            return Ok(None);
+        };
+        let dict = PyDict::new(py);
+        dict.set_item("quantum_seed", recording.quantum_seed)?;
+        dict.set_item("classical_seed", recording.classical_seed)?;
+        let measurements = PyList::empty(py);
+        for &outcome in &recording.measurements {
+            measurements.append(if outcome { Result::One } else { Result::Zero }.into_py(py))?;
+        }
+        dict.set_item("measurements", measurements)?;
+        Ok(Some(dict.into()))
+    }
+
+    /// The resources the simulator used in the most recent run, including a run
+    /// that failed, to anticipate whether a larger instance of the program will
+    /// fit in memory.
+    ///
+    /// :returns: A dictionary with the keys `qubit_high_water_mark`,
+    /// `reused_qubit_high_water_mark`, the most qubits that held state at once,
+    /// `peak_amplitudes`, `peak_memory_bytes`, `samples`, a list of
+    /// dictionaries with the keys `operation`, `qubits` and `amplitudes`, and
+    /// `qubit_lifetimes`, or `None` if nothing has been run.
+    fn last_run_statistics(&self, py: Python) -> PyResult<Option<Py<PyDict>>> {
+        let Some(statistics) = self.interpreter.last_run_statistics() else {
+            return Ok(None);
+        };
+        let dict = PyDict::new(py);
+        dict.set_item("qubit_high_water_mark", statistics.qubit_high_water_mark)?;
+        dict.set_item(
+            "reused_qubit_high_water_mark",
+            statistics.reused_qubit_high_water_mark,
+        )?;
+        dict.set_item("peak_amplitudes", statistics.peak_amplitudes)?;
+        dict.set_item("peak_memory_bytes", statistics.peak_memory)?;
+        let samples = PyList::empty(py);
+        for sample in &statistics.samples {
+            let sample_dict = PyDict::new(py);
+            sample_dict.set_item("operation", sample.operation)?;
+            sample_dict.set_item("qubits", sample.qubits)?;
+            sample_dict.set_item("amplitudes", sample.amplitudes)?;
+            samples.append(sample_dict)?;
+        }
+        dict.set_item("samples", samples)?;
+        dict.set_item(
+            "qubit_lifetimes",
+            lifetimes_to_list(py, &statistics.qubit_lifetimes)?,
+        )?;
+        Ok(Some(dict.into()))
+    }
+
+    /// Returns statistics about the compilation of the sources the interpreter
+    /// was created with, to diagnose why a project is slow to load.
+    ///
+    /// :returns stats: A dictionary with the keys `total_ms`, `phases`, a
+    /// dictionary of the milliseconds spent in each phase of the compiler,
+    /// `memory_bytes` and `peak_memory_bytes`, the growth of the memory of the
+    /// process over the compilation and its peak, or `None` where they can't
+    /// be measured, `packages`, a list of dictionaries with the keys `name`,
+    /// `items`, `callables`, `blocks`, `stmts` and `exprs`, and `rca`, a
+    /// dictionary with the keys `ms`, `callables`, `quantum_callables` and
+    /// `runtime_feature_callables` from running the runtime capabilities
+    /// analysis.
+    fn compilation_stats(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("total_ms", self.compilation.total_ms)?;
+        let phases = PyDict::new(py);
+        for (name, ms) in &self.compilation.phases {
+            phases.set_item(name, ms)?;
+        }
+        dict.set_item("phases", phases)?;
+        dict.set_item("memory_bytes", self.compilation.memory_bytes)?;
+        dict.set_item("peak_memory_bytes", self.compilation.peak_memory_bytes)?;
+
+        let packages = PyList::empty(py);
+        for package in self.interpreter.package_stats() {
+            let package_dict = PyDict::new(py);
+            package_dict.set_item("name", package.name)?;
+            package_dict.set_item("items", package.items)?;
+            package_dict.set_item("callables", package.callables)?;
+            package_dict.set_item("blocks", package.blocks)?;
+            package_dict.set_item("stmts", package.stmts)?;
+            package_dict.set_item("exprs", package.exprs)?;
+            packages.append(package_dict)?;
+        }
+        dict.set_item("packages", packages)?;
+
+        let rca = self.interpreter.rca_stats();
+        let rca_dict = PyDict::new(py);
+        rca_dict.set_item("ms", rca.duration.as_secs_f64() * 1000.0)?;
+        rca_dict.set_item("callables", rca.callables)?;
+        rca_dict.set_item("quantum_callables", rca.quantum_callables)?;
+        rca_dict.set_item("runtime_feature_callables", rca.runtime_feature_callables)?;
+        dict.set_item("rca", rca_dict)?;
+        Ok(dict.into())
+    }
+
+    /// Returns the call graph of the callables declared in the sources and
+    /// the fragments evaluated so far, along with the library callables they
+    /// refer to, annotated with the runtime capabilities each of them needs.
+    ///
+    /// :returns graph: A dictionary with the keys `nodes`, a list of
+    /// dictionaries with the keys `name`, `capabilities`, `introduced`,
+    /// `runtime_features` and `location`, and `edges`, a list of dictionaries
+    /// with the keys `caller` and `callee`, indices into the nodes, and
+    /// `calls`, the locations of the references. Locations are dictionaries
+    /// with the keys `file`, `line` and `column`, or `None` where they can't
+    /// be resolved.
+    fn call_graph(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let graph = self.interpreter.call_graph();
+        let location = |package, span| -> PyResult<PyObject> {
+            let Some((file, line, column)) = self.interpreter.resolve_source(package, span) else {
+                return Ok(py.None());
+            };
+            let dict = PyDict::new(py);
+            dict.set_item("file", file)?;
+            dict.set_item("line", line)?;
+            dict.set_item("column", column)?;
+            Ok(dict.into_py(py))
+        };
+
+        let nodes = PyList::empty(py);
+        for node in &graph.nodes {
+            let package = usize::from(node.package);
+            let node_dict = PyDict::new(py);
+            node_dict.set_item("name", &*node.name)?;
+            node_dict.set_item(
+                "capabilities",
+                Py::new(py, RuntimeCapability(node.capabilities))?,
+            )?;
+            node_dict.set_item(
+                "introduced",
+                Py::new(py, RuntimeCapability(node.introduced))?,
+            )?;
+            node_dict.set_item(
+                "runtime_features",
+                Py::new(py, RuntimeFeature(node.runtime_features))?,
+            )?;
+            node_dict.set_item("location", location(package, node.span)?)?;
+            nodes.append(node_dict)?;
+        }
+
+        let edges = PyList::empty(py);
+        for edge in &graph.edges {
+            let package = usize::from(graph.nodes[edge.caller].package);
+            let edge_dict = PyDict::new(py);
+            edge_dict.set_item("caller", edge.caller)?;
+            edge_dict.set_item("callee", edge.callee)?;
+            let calls = PyList::empty(py);
+            for span in &edge.spans {
+                calls.append(location(package, *span)?)?;
+            }
+            edge_dict.set_item("calls", calls)?;
+            edges.append(edge_dict)?;
+        }
+
+        let dict = PyDict::new(py);
+        dict.set_item("nodes", nodes)?;
+        dict.set_item("edges", edges)?;
+        Ok(dict.into())
+    }
+
+    #[pyo3(signature = (entry_expr, debug_info=false))]
+    fn qir(&mut self, _py: Python, entry_expr: &str, debug_info: bool) -> PyResult<String> {
+        let qir = if debug_info {
+            self.interpreter.qirgen_with_debug_info(entry_expr)
+        } else {
+            self.interpreter.qirgen(entry_expr)
+        };
+        match qir {
             Ok(qir) => Ok(qir),
             Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
         }
     }
 
+    /// Compiles and runs the given entry expression repeatedly and collects
+    /// timing statistics and gate counts.
+    ///
+    /// :param entry_expr: An entry expression.
+    /// :param repetitions: The number of times to compile and run the entry expression.
+    ///
+    /// :returns: A dictionary with the keys `repetitions`, `compile`, `classical`,
+    /// `simulator`, `total` and `gate_counts`.
+    ///
+    /// :raises QSharpError: If there is an error compiling or running the entry expression.
+    fn benchmark(
+        &mut self,
+        py: Python,
+        entry_expr: &str,
+        repetitions: u32,
+    ) -> PyResult<Py<PyDict>> {
+        let benchmark = self
+            .interpreter
+            .benchmark(entry_expr, repetitions)
+            .map_err(|errors| QSharpError::new_err(format_errors(errors)))?;
+        let dict = PyDict::new(py);
+        dict.set_item("repetitions", benchmark.repetitions)?;
+        for (key, stats) in [
+            ("compile", benchmark.compile),
+            ("classical", benchmark.classical),
+            ("simulator", benchmark.simulator),
+            ("total", benchmark.total),
+        ] {
+            let stats_dict = PyDict::new(py);
+            stats_dict.set_item("mean", stats.mean.as_secs_f64())?;
+            stats_dict.set_item("min", stats.min.as_secs_f64())?;
+            stats_dict.set_item("max", stats.max.as_secs_f64())?;
+            stats_dict.set_item("std_dev", stats.std_dev.as_secs_f64())?;
+            dict.set_item(key, stats_dict)?;
+        }
+        let gate_counts = PyDict::new(py);
+        for (gate, count) in benchmark.gate_counts {
+            gate_counts.set_item(gate, count)?;
+        }
+        dict.set_item("gate_counts", gate_counts)?;
+        Ok(dict.into_py(py))
+    }
+
+    /// Runs the callables with the `@Test()` attribute in the sources the interpreter
+    /// was initialized with, each with an independent instance of the simulator.
+    ///
+    /// :returns: A list of dictionaries with the keys `name`, `passed`, `seed`, `output`
+    /// and `errors`.
+    fn run_tests(&mut self, py: Python) -> PyResult<Py<PyList>> {
+        let list = PyList::empty(py);
+        for result in self.interpreter.run_tests() {
+            let dict = PyDict::new(py);
+            dict.set_item("name", &result.name)?;
+            dict.set_item("passed", result.passed())?;
+            dict.set_item("seed", result.seed)?;
+            dict.set_item("output", &result.output)?;
+            let errors = if result.errors.is_empty() {
+                None
+            } else {
+                Some(format_errors(result.errors))
+            };
+            dict.set_item("errors", errors)?;
+            list.append(dict)?;
+        }
+        Ok(list.into_py(py))
+    }
+
+    /// Suggests rewrites for the constructs reachable from the entry expression
+    /// that require more runtime capabilities than the Base profile provides.
+    ///
+    /// :param entry_expr: An entry expression.
+    ///
+    /// :returns: A list of dictionaries with the keys `code`, `message`, `before`,
+    /// `after` and `diagnostic`.
+    ///
+    /// :raises QSharpError: If there is an error compiling the entry expression.
+    fn advise(&mut self, py: Python, entry_expr: &str) -> PyResult<Py<PyList>> {
+        let advice = self
+            .interpreter
+            .advise(entry_expr)
+            .map_err(|errors| QSharpError::new_err(format_errors(errors)))?;
+        let list = PyList::empty(py);
+        for advice in advice {
+            let dict = PyDict::new(py);
+            let error = advice.error();
+            dict.set_item(
+                "code",
+                miette::Diagnostic::code(error).map(|code| code.to_string()),
+            )?;
+            dict.set_item("message", error.to_string())?;
+            dict.set_item("before", error.before())?;
+            dict.set_item("after", error.after())?;
+            dict.set_item("diagnostic", format!("{:?}", Report::new(advice)))?;
+            list.append(dict)?;
+        }
+        Ok(list.into_py(py))
+    }
+
     /// Synthesizes a circuit for a Q# program. Either an entry
     /// expression or an operation must be provided.
     ///
@@ -225,12 +1121,21 @@ impl Interpreter {
     /// an operation of a lambda expression. The operation must take only
     /// qubits or arrays of qubits as parameters.
     ///
+    /// :param optimize: Whether to cancel adjacent inverse gates and merge
+    /// consecutive rotations on the same qubits.
+    ///
+    /// :param simulate: Whether to run the program on the simulator when it
+    /// compares measurement results in a way that can't be traced without it.
+    ///
     /// :raises QSharpError: If there is an error synthesizing the circuit.
+    #[pyo3(signature = (entry_expr=None, operation=None, optimize=true, simulate=false))]
     fn circuit(
         &mut self,
         py: Python,
         entry_expr: Option<String>,
         operation: Option<String>,
+        optimize: bool,
+        simulate: bool,
     ) -> PyResult<PyObject> {
         let entrypoint = match (entry_expr, operation) {
             (Some(entry_expr), None) => CircuitEntryPoint::EntryExpr(entry_expr),
@@ -242,36 +1147,98 @@ impl Interpreter {
             }
         };
 
-        match self.interpreter.circuit(entrypoint) {
+        match self.interpreter.circuit(entrypoint, optimize, simulate) {
             Ok(circuit) => Ok(Circuit(circuit).into_py(py)),
             Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
         }
     }
 
     fn estimate(&mut self, _py: Python, entry_expr: &str, job_params: &str) -> PyResult<String> {
-        match estimate_expr(&mut self.interpreter, entry_expr, job_params) {
-            Ok(estimate) => Ok(estimate),
-            Err(errors) if matches!(errors[0], re::Error::Interpreter(_)) => {
-                Err(QSharpError::new_err(format_errors(
-                    errors
-                        .into_iter()
-                        .map(|e| match e {
-                            re::Error::Interpreter(e) => e,
-                            re::Error::Estimation(_) => unreachable!(),
-                        })
-                        .collect::<Vec<_>>(),
-                )))
-            }
-            Err(errors) => Err(QSharpError::new_err(
-                errors
-                    .into_iter()
-                    .map(|e| match e {
-                        re::Error::Estimation(e) => e.to_string(),
-                        re::Error::Interpreter(_) => unreachable!(),
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n"),
-            )),
+        estimate_expr(&mut self.interpreter, entry_expr, job_params).map_err(estimation_errors)
+    }
+
+    /// Estimates resources for calling an operation with the given arguments
+    /// the given number of times in a row.
+    ///
+    /// :param operation: The name of the operation.
+    /// :param args: The arguments of the operation.
+    /// :param repetitions: The number of times the operation is called.
+    /// :param job_params: The parameters to configure estimation.
+    ///
+    /// :returns resources: The estimated resources.
+    fn estimate_call(
+        &mut self,
+        operation: &str,
+        args: Vec<PyValue>,
+        repetitions: u32,
+        job_params: &str,
+    ) -> PyResult<String> {
+        let args = args.into_iter().map(|arg| arg.0).collect::<Vec<_>>();
+        estimate_call(
+            &mut self.interpreter,
+            operation,
+            &args,
+            repetitions,
+            job_params,
+        )
+        .map_err(estimation_errors)
+    }
+}
+
+impl Interpreter {
+    fn from_config(config: InterpreterConfig) -> PyResult<Self> {
+        let target = match config.target {
+            TargetProfile::Unrestricted => Profile::Unrestricted,
+            TargetProfile::Base => Profile::Base,
+        };
+        let sources = SourceMap::new(config.sources.iter().cloned(), None).with_config(
+            config
+                .manifest_descriptor
+                .iter()
+                .flat_map(|descriptor| &descriptor.manifest.config)
+                .map(|flag| flag.as_str().into()),
+        );
+        let language_features = LanguageFeatures::try_from_names(&config.language_features)
+            .map_err(|error| QSharpError::new_err(error.to_string()))?;
+
+        let (result, compilation) = profile_compilation(|| {
+            interpret::Interpreter::new_with_passes(
+                config.std.clone(),
+                sources,
+                PackageType::Lib,
+                target.into(),
+                language_features,
+                PassConfig {
+                    constant_folding: true,
+                    ..PassConfig::default()
+                },
+            )
+        });
+        match result {
+            Ok(interpreter) => Ok(Self::with_config(interpreter, config, compilation)),
+            Err(errors) => Err(QSharpError::new_err(format_errors(errors))),
+        }
+    }
+
+    /// Applies the settings of the configuration that don't affect compilation to the
+    /// interpreter.
+    fn with_config(
+        mut interpreter: interpret::Interpreter,
+        config: InterpreterConfig,
+        compilation: CompilationProfile,
+    ) -> Self {
+        interpreter.set_quantum_seed(config.quantum_seed);
+        interpreter.set_classical_seed(config.classical_seed);
+        interpreter.set_simulator(config.simulator);
+        interpreter.set_limits(config.limits);
+        interpreter.set_forced_outcomes(config.forced_outcomes.clone());
+        interpreter.set_metadata(config.metadata.clone());
+        interpreter.set_gate_set(config.gate_set);
+        Self {
+            interpreter,
+            config,
+            last_recording: None,
+            compilation,
         }
     }
 }
@@ -284,6 +1251,181 @@ pub fn physical_estimates(logical_resources: &str, job_params: &str) -> PyResult
     }
 }
 
+#[pyclass(unsendable)]
+#[derive(Clone)]
+/// Pre-calculated logical resource counts of an algorithm, which physical
+/// resources can be estimated from. The counts are validated when they are
+/// created, rather than when they are estimated.
+pub(crate) struct LogicalCounts {
+    #[pyo3(get)]
+    num_qubits: u64,
+    #[pyo3(get)]
+    t_count: u64,
+    #[pyo3(get)]
+    rotation_count: u64,
+    #[pyo3(get)]
+    rotation_depth: u64,
+    #[pyo3(get)]
+    ccz_count: u64,
+    #[pyo3(get)]
+    ccix_count: u64,
+    #[pyo3(get)]
+    measurement_count: u64,
+}
+
+#[pymethods]
+impl LogicalCounts {
+    #[new]
+    #[pyo3(signature = (num_qubits, *, t_count=0, rotation_count=0, rotation_depth=0, ccz_count=0, ccix_count=0, measurement_count=0))]
+    fn new(
+        num_qubits: u64,
+        t_count: u64,
+        rotation_count: u64,
+        rotation_depth: u64,
+        ccz_count: u64,
+        ccix_count: u64,
+        measurement_count: u64,
+    ) -> PyResult<Self> {
+        if num_qubits == 0 {
+            return Err(PyValueError::new_err(
+                "the number of logical qubits must be positive",
+            ));
+        }
+        if rotation_depth > rotation_count {
+            return Err(PyValueError::new_err(format!(
+                "the rotation depth ({rotation_depth}) cannot exceed the rotation count ({rotation_count})"
+            )));
+        }
+        if rotation_count > 0 && rotation_depth == 0 {
+            return Err(PyValueError::new_err(
+                "the rotation depth must be positive when there are rotations",
+            ));
+        }
+        Ok(Self {
+            num_qubits,
+            t_count,
+            rotation_count,
+            rotation_depth,
+            ccz_count,
+            ccix_count,
+            measurement_count,
+        })
+    }
+
+    /// Returns the counts in the JSON schema that `physical_estimates` accepts.
+    fn json(&self) -> String {
+        serde_json::to_string(&self.counts()).expect("serializing counts should succeed")
+    }
+
+    /// Estimates physical resources from the counts.
+    ///
+    /// :param params: The parameters to configure physical estimation.
+    ///
+    /// :returns resources: The estimated resources.
+    fn estimate(&self, params: &str) -> PyResult<String> {
+        re::system::estimate_physical_resources(self.counts(), params)
+            .map_err(|error| QSharpError::new_err(error.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LogicalCounts(num_qubits={}, t_count={}, rotation_count={}, rotation_depth={}, ccz_count={}, ccix_count={}, measurement_count={})",
+            self.num_qubits,
+            self.t_count,
+            self.rotation_count,
+            self.rotation_depth,
+            self.ccz_count,
+            self.ccix_count,
+            self.measurement_count,
+        )
+    }
+}
+
+impl LogicalCounts {
+    fn counts(&self) -> re::system::LogicalResourceCounts {
+        re::system::LogicalResourceCounts {
+            num_qubits: self.num_qubits,
+            t_count: self.t_count,
+            rotation_count: self.rotation_count,
+            rotation_depth: self.rotation_depth,
+            ccz_count: self.ccz_count,
+            ccix_count: self.ccix_count,
+            measurement_count: self.measurement_count,
+        }
+    }
+}
+
+/// Lists the language features that can be enabled, each with its `name`,
+/// `description` and `stability`.
+#[pyfunction]
+pub fn supported_language_features(py: Python) -> PyResult<Py<PyList>> {
+    let features = PyList::empty(py);
+    for info in KNOWN_LANGUAGE_FEATURES {
+        let dict = PyDict::new(py);
+        dict.set_item("name", info.name)?;
+        dict.set_item("description", info.description)?;
+        dict.set_item("stability", info.stability.to_string())?;
+        features.append(dict)?;
+    }
+    Ok(features.into())
+}
+
+/// Annotates a standalone Q# source with the results of the runtime capabilities analysis.
+#[pyfunction]
+pub fn hints(py: Python, source: &str) -> PyResult<Py<PyList>> {
+    let sources = SourceMap::new([("<source>".into(), source.into())], None);
+    let hints = qsc::hints::get_hints_for_sources(sources, LanguageFeatures::default()).map_err(
+        |errors| {
+            QSharpError::new_err(format_errors(
+                errors.into_iter().map(interpret::Error::from).collect(),
+            ))
+        },
+    )?;
+    let list = PyList::empty(py);
+    for hint in hints {
+        let range = Range::from_span(Encoding::Utf8, source, &hint.span);
+        let dict = PyDict::new(py);
+        dict.set_item(
+            "kind",
+            match hint.kind {
+                HintKind::DynamicBinding => "dynamicBinding",
+                HintKind::RequiredCapabilities => "requiredCapabilities",
+            },
+        )?;
+        dict.set_item("label", hint.label)?;
+        dict.set_item("start", (range.start.line, range.start.column))?;
+        dict.set_item("end", (range.end.line, range.end.column))?;
+        list.append(dict)?;
+    }
+    Ok(list.into_py(py))
+}
+
+/// Applies the fixes suggested by the lints to a standalone Q# source, including the lints that
+/// are allowed by default.
+#[pyfunction]
+pub fn fix(source: &str) -> PyResult<String> {
+    let capabilities = RuntimeCapabilityFlags::all();
+    let mut store = PackageStore::new(compile::core());
+    let std_id = store.insert(compile::std(&store, capabilities));
+    let sources = SourceMap::new([("<source>".into(), source.into())], None);
+    let (unit, errors) = compile::compile(
+        &store,
+        &[std_id],
+        sources,
+        PackageType::Lib,
+        capabilities,
+        LanguageFeatures::default(),
+    );
+    // A fix could change the meaning of code that wasn't parsed as intended.
+    if !errors.is_empty() {
+        return Err(QSharpError::new_err(format_errors(
+            errors.into_iter().map(interpret::Error::from).collect(),
+        )));
+    }
+    let lints = linter::run_lints(&store, &unit, Some(&linter::fixable_lints()));
+    Ok(linter::apply_fixes(source, &lints))
+}
+
 create_exception!(
     module,
     QSharpError,
@@ -291,6 +1433,96 @@ create_exception!(
     "An error returned from the Q# interpreter."
 );
 
+/// Reads the `stdlib` configuration, which is either whether to include the builtin
+/// standard library or the sources of a custom one, keyed by name.
+fn extract_std_lib(value: &PyAny) -> PyResult<StdLib> {
+    if let Ok(include_std) = value.extract::<bool>() {
+        return Ok(include_std.into());
+    }
+    let sources: BTreeMap<String, String> = value.extract()?;
+    Ok(StdLib::Custom(SourceMap::new(
+        sources
+            .into_iter()
+            .map(|(name, contents)| (name.into(), contents.into())),
+        None,
+    )))
+}
+
+fn dump_format(json: bool) -> DumpFormat {
+    if json {
+        DumpFormat::Json
+    } else {
+        DumpFormat::Text
+    }
+}
+
+fn estimation_errors(errors: Vec<re::Error>) -> PyErr {
+    if matches!(errors[0], re::Error::Interpreter(_)) {
+        QSharpError::new_err(format_errors(
+            errors
+                .into_iter()
+                .map(|e| match e {
+                    re::Error::Interpreter(e) => e,
+                    re::Error::Estimation(_) => unreachable!(),
+                })
+                .collect::<Vec<_>>(),
+        ))
+    } else {
+        QSharpError::new_err(
+            errors
+                .into_iter()
+                .map(|e| match e {
+                    re::Error::Estimation(e) => e.to_string(),
+                    re::Error::Interpreter(_) => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+}
+
+/// Creates the error raised when running Q# code fails. The frames of the Q# call stack of a
+/// runtime error are attached as the `stack_frames` attribute of the error, as dictionaries with
+/// the `name` and `functor` of the callable and the `source`, `line` and `column` of the call.
+fn run_error(py: Python, errors: Vec<interpret::Error>) -> PyErr {
+    let frames = errors
+        .iter()
+        .flat_map(interpret::Error::stack_frames)
+        .map(|frame| {
+            let location = frame.location(Encoding::Display);
+            let dict = PyDict::new(py);
+            dict.set_item("name", frame.name())?;
+            dict.set_item("functor", frame.functor())?;
+            dict.set_item(
+                "source",
+                location
+                    .as_ref()
+                    .map(|location| location.source.to_string()),
+            )?;
+            dict.set_item(
+                "line",
+                location.as_ref().map(|location| location.range.start.line),
+            )?;
+            dict.set_item(
+                "column",
+                location
+                    .as_ref()
+                    .map(|location| location.range.start.column),
+            )?;
+            Ok(dict)
+        })
+        .collect::<PyResult<Vec<_>>>();
+    let error = QSharpError::new_err(format_errors(errors));
+    match frames.and_then(|frames| {
+        error
+            .value(py)
+            .setattr("stack_frames", PyList::new(py, frames))
+    }) {
+        Ok(()) => error,
+        Err(err) => err,
+    }
+}
+
 fn format_errors(errors: Vec<interpret::Error>) -> String {
     errors
         .into_iter()
@@ -300,7 +1532,8 @@ fn format_errors(errors: Vec<interpret::Error>) -> String {
                 write!(message, "{stack_trace}").unwrap();
             }
             let additional_help = python_help(&e);
-            let report = Report::new(e);
+            // Notebooks and terminals show columns as they are displayed.
+            let report = Report::new(e.with_position_encoding(Encoding::Display));
             write!(message, "{report:?}").unwrap();
             if let Some(additional_help) = additional_help {
                 writeln!(message, "{additional_help}").unwrap();
@@ -324,11 +1557,12 @@ pub(crate) struct Output(DisplayableOutput);
 
 #[pymethods]
 /// An output returned from the Q# interpreter.
-/// Outputs can be a state dumps or messages. These are normally printed to the console.
+/// Outputs can be state dumps, matrix dumps or messages. These are normally printed to the console.
 impl Output {
     fn __repr__(&self) -> String {
         match &self.0 {
-            DisplayableOutput::State(state) => state.to_plain(),
+            DisplayableOutput::State(state, _) => state.to_plain(),
+            DisplayableOutput::Matrix(matrix) => matrix.to_plain(),
             DisplayableOutput::Message(msg) => msg.clone(),
         }
     }
@@ -339,22 +1573,40 @@ impl Output {
 
     fn _repr_html_(&self) -> String {
         match &self.0 {
-            DisplayableOutput::State(state) => state.to_html(),
+            DisplayableOutput::State(state, _) => state.to_html(),
+            DisplayableOutput::Matrix(matrix) => matrix.to_html(),
             DisplayableOutput::Message(msg) => format!("<p>{msg}</p>"),
         }
     }
 
     fn state_dump(&self) -> Option<StateDumpData> {
         match &self.0 {
-            DisplayableOutput::State(state) => Some(StateDumpData(state.clone())),
-            DisplayableOutput::Message(_) => None,
+            DisplayableOutput::State(state, qubits) => {
+                Some(StateDumpData(state.clone(), qubits.clone()))
+            }
+            DisplayableOutput::Matrix(_) | DisplayableOutput::Message(_) => None,
+        }
+    }
+
+    fn matrix_dump(&self) -> Option<MatrixDumpData> {
+        match &self.0 {
+            DisplayableOutput::Matrix(matrix) => Some(MatrixDumpData(matrix.clone())),
+            DisplayableOutput::State(..) | DisplayableOutput::Message(_) => None,
+        }
+    }
+
+    fn message(&self) -> Option<String> {
+        match &self.0 {
+            DisplayableOutput::Message(msg) => Some(msg.clone()),
+            DisplayableOutput::State(..) | DisplayableOutput::Matrix(_) => None,
         }
     }
 }
 
 #[pyclass(unsendable)]
-/// Captured simlation state dump.
-pub(crate) struct StateDumpData(pub(crate) DisplayableState);
+/// Captured simlation state dump, along with the ids of the qubits it is restricted to when it
+/// is the dump of a register.
+pub(crate) struct StateDumpData(pub(crate) DisplayableState, pub(crate) Option<Vec<usize>>);
 
 #[pymethods]
 impl StateDumpData {
@@ -382,11 +1634,80 @@ impl StateDumpData {
         .into_py(py))
     }
 
+    /// Returns the dense state vector as a NumPy array of complex amplitudes
+    /// indexed by basis state, without building a Python object per amplitude.
+    /// Basis states that are absent from the dump have an amplitude of zero.
+    fn as_numpy(&self, py: Python) -> PyResult<PyObject> {
+        state_to_numpy(py, &self.0)
+    }
+
+    /// Returns a state dump with only the `k` most probable basis states.
+    fn top(&self, k: usize) -> StateDumpData {
+        StateDumpData(self.0.top(k), self.1.clone())
+    }
+
+    /// Returns a state dump without the basis states whose probability is
+    /// smaller than `min_probability`.
+    fn filter(&self, min_probability: f64) -> StateDumpData {
+        StateDumpData(self.0.filter(min_probability), self.1.clone())
+    }
+
     #[getter]
     fn get_qubit_count(&self) -> usize {
         self.0 .1
     }
 
+    /// The ids of the qubits of the dumped register, or `None` if the whole
+    /// state of the simulator was dumped.
+    #[getter]
+    fn get_qubits(&self) -> Option<Vec<usize>> {
+        self.1.clone()
+    }
+
+    fn __len__(&self) -> usize {
+        self.0 .0.len()
+    }
+
+    fn __repr__(&self) -> String {
+        self.0.to_plain()
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    fn _repr_html_(&self) -> String {
+        self.0.to_html()
+    }
+}
+
+#[pyclass(unsendable)]
+/// Captured matrix dump.
+pub(crate) struct MatrixDumpData(pub(crate) DisplayableMatrix);
+
+#[pymethods]
+impl MatrixDumpData {
+    /// Returns the rows of the matrix as lists of complex entries.
+    fn get_matrix(&self, py: Python) -> Py<PyList> {
+        PyList::new(
+            py,
+            self.0 .0.iter().map(|row| {
+                PyList::new(
+                    py,
+                    row.iter()
+                        .map(|entry| PyComplex::from_doubles(py, entry.re, entry.im)),
+                )
+            }),
+        )
+        .into_py(py)
+    }
+
+    /// Returns the matrix as a two-dimensional NumPy array of complex entries,
+    /// without building a Python object per entry.
+    fn as_numpy(&self, py: Python) -> PyResult<PyObject> {
+        matrix_to_numpy(py, &self.0)
+    }
+
     fn __len__(&self) -> usize {
         self.0 .0.len()
     }
@@ -498,6 +1819,9 @@ impl IntoPy<PyObject> for ValueWrapper {
 struct OptionalCallbackReceiver<'a> {
     callback: Option<PyObject>,
     py: Python<'a>,
+    state_filter: StateFilter,
+    /// Whether messages are dropped instead of being passed to the callback.
+    quiet: bool,
 }
 
 impl Receiver for OptionalCallbackReceiver<'_> {
@@ -506,24 +1830,42 @@ impl Receiver for OptionalCallbackReceiver<'_> {
         state: Vec<(BigUint, Complex64)>,
         qubit_count: usize,
     ) -> core::result::Result<(), Error> {
-        if let Some(callback) = &self.callback {
-            let out = DisplayableOutput::State(DisplayableState(state, qubit_count));
-            callback
-                .call1(
-                    self.py,
-                    PyTuple::new(
-                        self.py,
-                        &[Py::new(self.py, Output(out)).expect("should be able to create output")],
-                    ),
-                )
-                .map_err(|_| Error)?;
-        }
-        Ok(())
+        let state = self
+            .state_filter
+            .apply(DisplayableState(state, qubit_count));
+        self.output(DisplayableOutput::State(state, None))
+    }
+
+    fn register_state(
+        &mut self,
+        qubits: &[usize],
+        state: Vec<(BigUint, Complex64)>,
+    ) -> core::result::Result<(), Error> {
+        let state = self
+            .state_filter
+            .apply(DisplayableState(state, qubits.len()));
+        self.output(DisplayableOutput::State(state, Some(qubits.to_vec())))
+    }
+
+    fn matrix(&mut self, matrix: Vec<Vec<Complex64>>) -> core::result::Result<(), Error> {
+        self.output(DisplayableOutput::Matrix(DisplayableMatrix(matrix)))
     }
 
     fn message(&mut self, msg: &str) -> core::result::Result<(), Error> {
+        if self.quiet {
+            return Ok(());
+        }
+        self.output(DisplayableOutput::Message(msg.to_owned()))
+    }
+
+    fn drops_messages(&self) -> bool {
+        self.quiet
+    }
+}
+
+impl OptionalCallbackReceiver<'_> {
+    fn output(&self, out: DisplayableOutput) -> core::result::Result<(), Error> {
         if let Some(callback) = &self.callback {
-            let out = DisplayableOutput::Message(msg.to_owned());
             callback
                 .call1(
                     self.py,
@@ -554,6 +1896,164 @@ impl Circuit {
     fn json(&self, _py: Python) -> PyResult<String> {
         serde_json::to_string(&self.0).map_err(|e| PyException::new_err(e.to_string()))
     }
+
+    /// Returns the circuit as a dictionary that can be converted into the circuit
+    /// objects of other frameworks without parsing its JSON.
+    ///
+    /// The dictionary has the keys `qubits`, a list of qubit ids, `num_results`, the
+    /// number of measurement results of each qubit, `operations` and `metadata`.
+    /// Measurement results are identified by `(qubit, index)` pairs. Each operation
+    /// has a `kind`:
+    /// - `gate`: with `gate`, `adjoint`, `controls` and `targets` qubit ids, `params`,
+    ///   the exact parameters of the gate, and `args`, the formatted classical
+    ///   arguments of a custom gate or `None`.
+    /// - `measurement`: with the measured `qubit` and the `result` it is stored in.
+    /// - `conditional`: with the `result` it depends on, and the lists of operations
+    ///   `on_one` and `on_zero` that run for each of its values.
+    fn to_dict(&self, py: Python) -> PyResult<Py<PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item(
+            "qubits",
+            self.0
+                .qubits
+                .iter()
+                .map(|qubit| qubit.id)
+                .collect::<Vec<_>>(),
+        )?;
+        dict.set_item(
+            "num_results",
+            self.0
+                .qubits
+                .iter()
+                .map(|qubit| qubit.num_children)
+                .collect::<Vec<_>>(),
+        )?;
+        dict.set_item("operations", operations_to_list(py, &self.0.operations)?)?;
+        dict.set_item("metadata", &self.0.metadata)?;
+        Ok(dict.into_py(py))
+    }
+
+    #[pyo3(signature = (compact=false, max_width=None))]
+    fn render(&self, compact: bool, max_width: Option<usize>) -> String {
+        self.0
+            .display(qsc::circuit::DisplayOptions { compact, max_width })
+            .to_string()
+    }
+
+    /// Returns the part of the circuit with the operations in the time steps from `start` up to,
+    /// but not including, `stop`.
+    fn slice(&self, start: usize, stop: usize) -> Circuit {
+        Circuit(self.0.slice(start..stop))
+    }
+
+    /// Returns the part of the circuit on the qubits with the given ids, without the operations
+    /// that also act on other qubits.
+    fn filter_qubits(&self, ids: Vec<usize>) -> Circuit {
+        Circuit(self.0.filter_qubits(&ids))
+    }
+
+    /// Returns an equivalent circuit on fewer qubits, where qubits that are reset, such as after
+    /// a measurement, are reused for qubits that are only used after the reset.
+    fn reuse_qubits(&self) -> Circuit {
+        Circuit(self.0.reuse_qubits())
+    }
+
+    /// Returns the lifetime of each qubit of the circuit, with times that are time steps.
+    fn qubit_lifetimes(&self, py: Python) -> PyResult<Py<PyList>> {
+        lifetimes_to_list(py, &self.0.qubit_lifetimes())
+    }
+}
+
+fn operations_to_list<'a>(
+    py: Python,
+    operations: impl IntoIterator<Item = &'a Operation>,
+) -> PyResult<Py<PyList>> {
+    let list = PyList::empty(py);
+    for operation in operations {
+        list.append(operation_to_dict(py, operation)?)?;
+    }
+    Ok(list.into_py(py))
+}
+
+fn operation_to_dict(py: Python, operation: &Operation) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new(py);
+    let result = |register: &Register| (register.q_id, register.c_id.unwrap_or_default());
+    if operation.is_conditional {
+        dict.set_item("kind", "conditional")?;
+        dict.set_item("result", operation.controls.first().map(result))?;
+        let branch = |render: ConditionalRender| {
+            operations_to_list(
+                py,
+                operation
+                    .children
+                    .iter()
+                    .filter(|child| child.conditional_render == Some(render)),
+            )
+        };
+        dict.set_item("on_one", branch(ConditionalRender::OnOne)?)?;
+        dict.set_item("on_zero", branch(ConditionalRender::OnZero)?)?;
+    } else if operation.is_measurement {
+        dict.set_item("kind", "measurement")?;
+        dict.set_item("qubit", operation.controls.first().map(|q| q.q_id))?;
+        dict.set_item("result", operation.targets.first().map(result))?;
+        dict.set_item("source", source_to_dict(py, operation.source.as_ref())?)?;
+    } else {
+        let qubits = |registers: &[Register]| registers.iter().map(|q| q.q_id).collect::<Vec<_>>();
+        dict.set_item("kind", "gate")?;
+        dict.set_item("gate", &operation.gate)?;
+        dict.set_item("adjoint", operation.is_adjoint)?;
+        dict.set_item("controls", qubits(&operation.controls))?;
+        dict.set_item("targets", qubits(&operation.targets))?;
+        dict.set_item("params", &operation.params)?;
+        dict.set_item(
+            "args",
+            if operation.params.is_empty() {
+                operation.display_args.as_deref()
+            } else {
+                None
+            },
+        )?;
+        dict.set_item("source", source_to_dict(py, operation.source.as_ref())?)?;
+    }
+    Ok(dict.into_py(py))
+}
+
+/// The lifetimes of qubits, as a list of dictionaries with the keys `qubit`, `allocated`,
+/// `released`, `idle`, a list of `(start, end)` windows, and `partners`, a list of
+/// `(time, qubit)` pairs.
+fn lifetimes_to_list(py: Python, lifetimes: &[QubitLifetime]) -> PyResult<Py<PyList>> {
+    let list = PyList::empty(py);
+    for lifetime in lifetimes {
+        let dict = PyDict::new(py);
+        dict.set_item("qubit", lifetime.qubit)?;
+        dict.set_item("allocated", lifetime.allocated)?;
+        dict.set_item("released", lifetime.released)?;
+        dict.set_item(
+            "idle",
+            lifetime
+                .idle
+                .iter()
+                .map(|window| (window.start, window.end))
+                .collect::<Vec<_>>(),
+        )?;
+        dict.set_item("partners", &lifetime.partners)?;
+        list.append(dict)?;
+    }
+    Ok(list.into_py(py))
+}
+
+/// The call in the Q# source that an operation comes from, as a dictionary with the keys `file`,
+/// `line`, `column` and `callable`, or `None` if it isn't known.
+fn source_to_dict(py: Python, source: Option<&SourceLocation>) -> PyResult<PyObject> {
+    let Some(source) = source else {
+        return Ok(py.None());
+    };
+    let dict = PyDict::new(py);
+    dict.set_item("file", &source.file)?;
+    dict.set_item("line", source.line)?;
+    dict.set_item("column", source.column)?;
+    dict.set_item("callable", &source.callable)?;
+    Ok(dict.into_py(py))
 }
 
 trait MapPyErr<T, E> {
@@ -592,6 +2092,22 @@ impl IntoPyErr for Vec<interpret::Error> {
     }
 }
 
+fn parse_simulator(name: &str) -> PyResult<SimulatorKind> {
+    name.parse().map_err(|()| {
+        PyException::new_err(format!(
+            "unknown simulator `{name}`, expected `sparse` or `dense`"
+        ))
+    })
+}
+
+fn parse_gate_set(name: &str) -> PyResult<GateSet> {
+    name.parse().map_err(|()| {
+        PyException::new_err(format!(
+            "unknown gate set `{name}`, expected `full`, `rz,sx,cx` or `rx,ry,rzz`"
+        ))
+    })
+}
+
 fn get_dict_opt_string(dict: &PyDict, key: &str) -> PyResult<Option<String>> {
     let value = dict.get_item(key)?;
     Ok(match value {
@@ -6,3 +6,6 @@ allocator::assign_global!();
 mod displayable_output;
 mod fs;
 mod interpreter;
+mod numpy_interop;
+mod runtime_flags;
+mod timing;
@@ -0,0 +1,72 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Conversion of state dumps and matrices into NumPy arrays. The amplitudes are collected into a
+//! single buffer whose ownership is handed over to NumPy, so no Python object is created per
+//! element. The conversion is only available when the crate is built with the `numpy` feature.
+
+use crate::{
+    displayable_output::{DisplayableMatrix, DisplayableState},
+    interpreter::QSharpError,
+};
+use pyo3::prelude::*;
+
+/// Returns the dense state vector of a state dump as a one-dimensional NumPy array of complex
+/// amplitudes indexed by basis state. Basis states that are absent from the dump, for example
+/// because it was filtered, have an amplitude of zero.
+#[cfg(feature = "numpy")]
+pub(crate) fn state_to_numpy(py: Python, state: &DisplayableState) -> PyResult<PyObject> {
+    use num_complex::Complex64;
+    use numpy::PyArray1;
+
+    let len = u32::try_from(state.1)
+        .ok()
+        .and_then(|qubit_count| 1_usize.checked_shl(qubit_count))
+        .ok_or_else(|| {
+            QSharpError::new_err(format!(
+                "a state of {} qubits is too large to convert into an array",
+                state.1
+            ))
+        })?;
+    let mut amplitudes = vec![Complex64::new(0.0, 0.0); len];
+    for (basis_state, amplitude) in &state.0 {
+        let index = usize::try_from(basis_state)
+            .ok()
+            .filter(|index| *index < len)
+            .ok_or_else(|| {
+                QSharpError::new_err(format!("basis state {basis_state} is out of range"))
+            })?;
+        amplitudes[index] = *amplitude;
+    }
+    let array: &PyAny = PyArray1::from_vec(py, amplitudes);
+    Ok(array.into_py(py))
+}
+
+/// Returns a matrix dump as a two-dimensional NumPy array of complex entries.
+#[cfg(feature = "numpy")]
+pub(crate) fn matrix_to_numpy(py: Python, matrix: &DisplayableMatrix) -> PyResult<PyObject> {
+    use numpy::{ndarray::Array2, PyArray2};
+
+    let rows = matrix.0.len();
+    let columns = matrix.0.first().map_or(0, Vec::len);
+    let entries = matrix.0.iter().flatten().copied().collect();
+    let entries = Array2::from_shape_vec((rows, columns), entries)
+        .map_err(|error| QSharpError::new_err(error.to_string()))?;
+    let array: &PyAny = PyArray2::from_owned_array(py, entries);
+    Ok(array.into_py(py))
+}
+
+#[cfg(not(feature = "numpy"))]
+pub(crate) fn state_to_numpy(_py: Python, _state: &DisplayableState) -> PyResult<PyObject> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "numpy"))]
+pub(crate) fn matrix_to_numpy(_py: Python, _matrix: &DisplayableMatrix) -> PyResult<PyObject> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "numpy"))]
+fn unsupported() -> PyErr {
+    QSharpError::new_err("the qsharp package was built without NumPy support")
+}
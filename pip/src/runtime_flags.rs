@@ -0,0 +1,190 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Python classes for the runtime capability and runtime feature bit flags. `pyclass` enums can't
+//! hold a combination of variants, so each class wraps the bits of a set of flags, with a class
+//! attribute for each single flag, and supports the same operators as Python's `enum.Flag`.
+
+use pyo3::{
+    prelude::*,
+    pyclass::CompareOp,
+    types::{PyIterator, PyList},
+};
+use qsc::{hints::RuntimeFeatureFlags, RuntimeCapabilityFlags};
+
+macro_rules! flags_class {
+    (
+        $(#[$meta:meta])*
+        $name:ident($flags:ty) { $($flag:ident),+ $(,)? }
+        $(impl { $($methods:tt)* })?
+    ) => {
+        $(#[$meta])*
+        #[pyclass(unsendable, frozen)]
+        #[derive(Clone, PartialEq, Eq)]
+        pub(crate) struct $name(pub(crate) $flags);
+
+        #[pymethods]
+        #[allow(non_snake_case)]
+        impl $name {
+            $(
+                #[classattr]
+                fn $flag() -> Self {
+                    Self(<$flags>::$flag)
+                }
+            )+
+
+            /// The flags whose bits are set in `value`. Unknown bits are dropped.
+            #[new]
+            fn new(value: u32) -> Self {
+                Self(<$flags>::from_bits_truncate(value))
+            }
+
+            /// The flags that have no bits set.
+            #[staticmethod]
+            fn empty() -> Self {
+                Self(<$flags>::empty())
+            }
+
+            /// The combination of every flag.
+            #[staticmethod]
+            fn all() -> Self {
+                Self(<$flags>::all())
+            }
+
+            #[getter]
+            fn value(&self) -> u32 {
+                self.0.bits()
+            }
+
+            /// The names of the single flags that are set, in bit order.
+            #[getter]
+            fn names(&self) -> Vec<&'static str> {
+                self.0.iter_names().map(|(name, _)| name).collect()
+            }
+
+            fn __or__(&self, other: &Self) -> Self {
+                Self(self.0 | other.0)
+            }
+
+            fn __and__(&self, other: &Self) -> Self {
+                Self(self.0 & other.0)
+            }
+
+            fn __xor__(&self, other: &Self) -> Self {
+                Self(self.0 ^ other.0)
+            }
+
+            fn __sub__(&self, other: &Self) -> Self {
+                Self(self.0 - other.0)
+            }
+
+            fn __invert__(&self) -> Self {
+                Self(!self.0)
+            }
+
+            /// Whether every flag of `other` is also set in these flags.
+            fn __contains__(&self, other: &Self) -> bool {
+                self.0.contains(other.0)
+            }
+
+            fn __bool__(&self) -> bool {
+                !self.0.is_empty()
+            }
+
+            fn __int__(&self) -> u32 {
+                self.0.bits()
+            }
+
+            fn __len__(&self) -> usize {
+                self.0.iter().count()
+            }
+
+            /// Iterates over the single flags that are set, in bit order.
+            fn __iter__(&self, py: Python) -> PyResult<Py<PyIterator>> {
+                let flags = PyList::new(py, self.0.iter().map(|flag| Self(flag).into_py(py)));
+                Ok(PyIterator::from_object(flags)?.into())
+            }
+
+            fn __hash__(&self) -> u32 {
+                self.0.bits()
+            }
+
+            fn __richcmp__(&self, other: &Self, op: CompareOp, py: Python) -> PyObject {
+                match op {
+                    CompareOp::Eq => (self == other).into_py(py),
+                    CompareOp::Ne => (self != other).into_py(py),
+                    _ => py.NotImplemented(),
+                }
+            }
+
+            fn __repr__(&self) -> String {
+                if self.0.is_empty() {
+                    return format!("{}(0)", stringify!($name));
+                }
+                self.0
+                    .iter_names()
+                    .map(|(name, _)| format!("{}.{name}", stringify!($name)))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            }
+
+            fn __str__(&self) -> String {
+                self.__repr__()
+            }
+
+            $($($methods)*)?
+        }
+    };
+}
+
+flags_class! {
+    /// The capabilities a target needs to run a Q# program, beyond executing quantum
+    /// operations on statically allocated qubits.
+    RuntimeCapability(RuntimeCapabilityFlags) {
+        ForwardBranching,
+        IntegerComputations,
+        FloatingPointComputations,
+        BackwardsBranching,
+        HigherLevelConstructs,
+    }
+}
+
+flags_class! {
+    /// The constructs a Q# program uses that are more complex than executing quantum operations
+    /// on statically allocated qubits with constant arguments. Each feature needs one or more
+    /// runtime capabilities.
+    RuntimeFeature(RuntimeFeatureFlags) {
+        UseOfDynamicBool,
+        UseOfDynamicInt,
+        UseOfDynamicPauli,
+        UseOfDynamicRange,
+        UseOfDynamicDouble,
+        UseOfDynamicQubit,
+        UseOfDynamicBigInt,
+        UseOfDynamicString,
+        UseOfDynamicallySizedArray,
+        UseOfDynamicUdt,
+        UseOfDynamicArrowFunction,
+        UseOfDynamicArrowOperation,
+        CallToCyclicFunctionWithDynamicArg,
+        CyclicOperationSpec,
+        CallToCyclicOperation,
+        CallToDynamicCallee,
+        CallToUnresolvedCallee,
+        ForwardBranchingOnDynamicValue,
+        DynamicQubitAllocation,
+        DynamicResultAllocation,
+        UseOfDynamicIndex,
+        ReturnWithinDynamicScope,
+        LoopWithDynamicCondition,
+        UseOfClosure,
+        CallToUnrestrictedIntrinsic,
+        UseOfBorrowedQubit,
+    }
+    impl {
+        /// The minimal runtime capabilities a target needs to support these features.
+        fn runtime_capabilities(&self) -> RuntimeCapability {
+            RuntimeCapability(self.0.runtime_capabilities())
+        }
+    }
+}
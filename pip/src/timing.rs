@@ -0,0 +1,106 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use pyo3::{exceptions::PyException, prelude::*};
+use qsc::timing::SpanTimer;
+use std::{
+    sync::{Mutex, Once, OnceLock},
+    time::Instant,
+};
+
+static TRACE_CALLBACK: Mutex<Option<PyObject>> = Mutex::new(None);
+/// The spans reported while a compilation is being profiled.
+static PROFILED_PHASES: Mutex<Option<Vec<(String, f64)>>> = Mutex::new(None);
+static TRACING_INIT: Once = Once::new();
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// The time and memory a compilation took.
+#[derive(Clone, Default)]
+pub(crate) struct CompilationProfile {
+    pub total_ms: f64,
+    /// The milliseconds spent in each phase of the compiler, in the order the phases first ran.
+    pub phases: Vec<(String, f64)>,
+    /// The growth of the memory committed by the process over the compilation, in bytes.
+    pub memory_bytes: Option<usize>,
+    /// The most memory the process has committed at once, in bytes.
+    pub peak_memory_bytes: Option<usize>,
+}
+
+/// Sets the function that the time spent in each phase of compilation and evaluation is reported
+/// to, with the name of the phase and the elapsed milliseconds. Passing `None` stops the reports.
+#[pyfunction]
+pub fn set_trace_callback(callback: Option<PyObject>) -> PyResult<()> {
+    *TRACE_CALLBACK
+        .lock()
+        .expect("trace callback lock should not be poisoned") = callback;
+    init_tracing()
+}
+
+/// Runs a compilation, recording the time spent in each phase of the compiler.
+pub(crate) fn profile_compilation<T>(compile: impl FnOnce() -> T) -> (T, CompilationProfile) {
+    // Without a subscriber there are no phase times, but the rest of the profile still applies.
+    let _ = init_tracing();
+    *phases() = Some(Vec::new());
+    let memory_before = allocator::committed_memory();
+    let start = now();
+
+    let result = compile();
+
+    let total_ms = now() - start;
+    let memory_after = allocator::committed_memory();
+    let mut phase_totals: Vec<(String, f64)> = Vec::new();
+    for (name, elapsed) in phases().take().unwrap_or_default() {
+        match phase_totals.iter_mut().find(|(phase, _)| *phase == name) {
+            Some((_, total)) => *total += elapsed,
+            None => phase_totals.push((name, elapsed)),
+        }
+    }
+    let profile = CompilationProfile {
+        total_ms,
+        phases: phase_totals,
+        memory_bytes: memory_before
+            .zip(memory_after)
+            .map(|((before, _), (after, _))| after.saturating_sub(before)),
+        peak_memory_bytes: memory_after.map(|(_, peak)| peak),
+    };
+    (result, profile)
+}
+
+fn init_tracing() -> PyResult<()> {
+    let mut result = Ok(());
+    TRACING_INIT.call_once(|| {
+        result = tracing::subscriber::set_global_default(SpanTimer::new(now, report))
+            .map_err(|e| PyException::new_err(e.to_string()));
+    });
+    result
+}
+
+fn phases() -> std::sync::MutexGuard<'static, Option<Vec<(String, f64)>>> {
+    PROFILED_PHASES
+        .lock()
+        .expect("profiled phases lock should not be poisoned")
+}
+
+fn now() -> f64 {
+    START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+fn report(name: &str, elapsed: f64) {
+    if let Some(phases) = phases().as_mut() {
+        phases.push((name.to_string(), elapsed));
+    }
+
+    Python::with_gil(|py| {
+        // The lock is released before the call, so that the callback can replace itself.
+        let callback = TRACE_CALLBACK
+            .lock()
+            .expect("trace callback lock should not be poisoned")
+            .as_ref()
+            .map(|callback| callback.clone_ref(py));
+        if let Some(callback) = callback {
+            if let Err(err) = callback.call1(py, (name, elapsed)) {
+                err.print(py);
+            }
+        }
+    });
+}
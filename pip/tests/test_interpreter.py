@@ -50,6 +50,29 @@ def test_dump_output() -> None:
     assert called
 
 
+def test_dump_output_is_filtered() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.set_state_filter(top=1)
+
+    def callback(output):
+        nonlocal called
+        called = True
+        assert output.__repr__() == "STATE:\n|10⟩: 0.9490+0.0000𝑖"
+
+    called = False
+    e.interpret(
+        """
+    use q1 = Qubit();
+    use q2 = Qubit();
+    Ry(2.5, q1);
+    Microsoft.Quantum.Diagnostics.DumpMachine();
+    ResetAll([q1, q2]);
+    """,
+        callback,
+    )
+    assert called
+
+
 def test_quantum_seed() -> None:
     e = Interpreter(TargetProfile.Unrestricted)
     e.set_quantum_seed(42)
@@ -101,6 +124,21 @@ def test_dump_machine() -> None:
     assert state_dump[2].imag == 0.0
 
 
+def test_dump_machine_as_numpy() -> None:
+    np = pytest.importorskip("numpy")
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    use q1 = Qubit();
+    use q2 = Qubit();
+    X(q1);
+    """
+    )
+    amplitudes = e.dump_machine().as_numpy()
+    assert amplitudes.shape == (4,)
+    assert np.array_equal(amplitudes, np.array([0, 0, 1, 0], dtype=complex))
+
+
 def test_error() -> None:
     e = Interpreter(TargetProfile.Unrestricted)
 
@@ -260,6 +298,46 @@ def test_dump_circuit() -> None:
     )
 
 
+def test_run_entry_with_arguments() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("@EntryPoint() operation Main(n : Int, xs : Int[]) : Int { n + xs[0] }")
+    assert e.run_entry([2, [3]]) == 5
+    with pytest.raises(QSharpError) as excinfo:
+        e.run_entry(["2", [3]])
+    assert "argument `n` of the entry point should be of type `Int`" in str(
+        excinfo.value
+    )
+    with pytest.raises(QSharpError) as excinfo:
+        e.run_entry([2])
+    assert "entry point takes 2 arguments but 1 were given" in str(excinfo.value)
+
+
+def test_custom_intrinsic_is_simulated_by_callback() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        '@CustomIntrinsic("__quantum__qis__pulse__body") operation Pulse(amplitude : Double, q : Qubit) : Unit { body intrinsic; }'
+    )
+    calls = []
+    e.register_intrinsic(
+        "__quantum__qis__pulse__body", lambda args: calls.append(args[0])
+    )
+    e.interpret("{ use q = Qubit(); Pulse(0.5, q); }")
+    assert calls == [0.5]
+
+
+def test_registered_function_is_called_from_qsharp() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    calls = []
+
+    def step(x: float, scale: int) -> float:
+        calls.append((x, scale))
+        return x / scale
+
+    e.register_function("Step", [("x", "Double"), ("scale", "Int")], "Double", step)
+    assert e.interpret("Step(3.0, 2) + Step(1.0, 4)") == 1.75
+    assert calls == [(3.0, 2), (1.0, 4)]
+
+
 def test_entry_expr_circuit() -> None:
     e = Interpreter(TargetProfile.Unrestricted)
     e.interpret("operation Foo() : Result { use q = Qubit(); H(q); return M(q) }")
@@ -272,6 +350,52 @@ def test_entry_expr_circuit() -> None:
     )
 
 
+def test_circuit_to_dict() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        """
+    operation Foo() : Result[] {
+        use qs = Qubit[2];
+        Rx(0.125, qs[0]);
+        CNOT(qs[0], qs[1]);
+        let results = [M(qs[0]), M(qs[1])];
+        ResetAll(qs);
+        results
+    }
+    """
+    )
+    circuit = e.circuit("Foo()").to_dict()
+    assert circuit["qubits"] == [0, 1]
+    assert circuit["num_results"] == [1, 1]
+    operations = circuit["operations"]
+    assert operations[0] == {
+        "kind": "gate",
+        "gate": "rx",
+        "adjoint": False,
+        "controls": [],
+        "targets": [0],
+        "params": [0.125],
+        "args": None,
+        "source": {"file": "line_0", "line": 3, "column": 8, "callable": "Rx"},
+    }
+    assert operations[1]["gate"] == "X"
+    assert operations[1]["controls"] == [0]
+    assert operations[1]["targets"] == [1]
+    assert operations[1]["source"]["callable"] == "CNOT"
+    assert operations[2] == {
+        "kind": "measurement",
+        "qubit": 0,
+        "result": (0, 0),
+        "source": {"file": "line_0", "line": 5, "column": 23, "callable": "M"},
+    }
+    assert operations[3] == {
+        "kind": "measurement",
+        "qubit": 1,
+        "result": (1, 0),
+        "source": {"file": "line_0", "line": 5, "column": 33, "callable": "M"},
+    }
+
+
 def test_operation_circuit() -> None:
     e = Interpreter(TargetProfile.Unrestricted)
     e.interpret("operation Foo(q: Qubit) : Result { H(q); return M(q) }")
@@ -284,6 +408,110 @@ def test_operation_circuit() -> None:
     )
 
 
+def test_circuit_cancels_adjacent_inverse_gates() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("operation Foo(q: Qubit) : Unit { H(q); X(q); X(q); }")
+    circuit = e.circuit(operation="Foo")
+    assert str(circuit) == dedent(
+        """\
+        q_0    ── H ──
+        """
+    )
+    circuit = e.circuit(operation="Foo", optimize=False)
+    assert str(circuit) == dedent(
+        """\
+        q_0    ── H ──── X ──── X ──
+        """
+    )
+
+
+def test_circuit_render_compact_and_wrapped() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        "operation Foo(qs: Qubit[]) : Unit { H(qs[0]); X(qs[0]); Rx(1.0, qs[4]); }"
+    )
+    circuit = e.circuit("{ use qs = Qubit[5]; Foo(qs) }")
+    assert circuit.render(compact=True) == dedent(
+        """\
+        q_0 ───── H ─────── X ─
+        q_1 … q_3
+        q_4 ─ rx(1.0000) ──────
+        """
+    )
+    assert circuit.render(max_width=16) == dedent(
+        """\
+        q_0    ── H ──
+        q_1    ───────
+        q_2    ───────
+        q_3    ───────
+        q_4     rx(1.0000)
+
+        q_0    ── X ──
+        q_1    ───────
+        q_2    ───────
+        q_3    ───────
+        q_4    ───────
+        """
+    )
+
+
+def test_circuit_slice_and_filter_qubits() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        "operation Foo(qs: Qubit[]) : Unit { H(qs[0]); CNOT(qs[0], qs[1]); X(qs[2]); Y(qs[0]); }"
+    )
+    circuit = e.circuit("{ use qs = Qubit[3]; Foo(qs) }")
+    operations = circuit.slice(1, 3).to_dict()["operations"]
+    assert [(op["gate"], op["controls"]) for op in operations] == [
+        ("X", [0]),
+        ("Y", []),
+    ]
+    assert circuit.slice(3, 10).to_dict()["operations"] == []
+    assert str(circuit.filter_qubits([0, 2])) == dedent(
+        """\
+        q_0    ── H ──── Y ──
+        q_2    ── X ─────────
+        """
+    )
+
+
+def test_circuit_reuse_qubits() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        "operation Foo(qs: Qubit[]) : Unit { H(qs[0]); Reset(qs[0]); X(qs[1]); }"
+    )
+    circuit = e.circuit("{ use qs = Qubit[2]; Foo(qs) }")
+    assert str(circuit.reuse_qubits()) == dedent(
+        """\
+        q_0    ── H ─── |0〉 ─── X ──
+        """
+    )
+
+
+def test_circuit_qubit_lifetimes() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret(
+        "operation Foo(qs: Qubit[]) : Unit { X(qs[1]); H(qs[0]); S(qs[0]); CNOT(qs[0], qs[1]); }"
+    )
+    circuit = e.circuit("{ use qs = Qubit[2]; Foo(qs) }")
+    assert circuit.qubit_lifetimes() == [
+        {
+            "qubit": 0,
+            "allocated": 0,
+            "released": 3,
+            "idle": [],
+            "partners": [(2, 1)],
+        },
+        {
+            "qubit": 1,
+            "allocated": 0,
+            "released": 3,
+            "idle": [(1, 2)],
+            "partners": [(2, 0)],
+        },
+    ]
+
+
 def test_unsupported_operation_circuit() -> None:
     e = Interpreter(TargetProfile.Unrestricted)
     e.interpret("operation Foo(n: Int) : Result { return One }")
@@ -295,3 +523,83 @@ def test_unsupported_operation_circuit() -> None:
         )
         != -1
     )
+
+
+def test_config() -> None:
+    e = Interpreter(TargetProfile.Base, ["v2-preview-syntax"])
+    e.set_quantum_seed(42)
+    config = e.config()
+    assert config["target_profile"] == TargetProfile.Base
+    assert config["language_features"] == ["v2-preview-syntax"]
+    assert config["manifest"] is None
+    assert config["stdlib"] == True
+    assert config["quantum_seed"] == 42
+    assert config["classical_seed"] is None
+    assert config["simulator"] == "sparse"
+
+
+def test_unknown_language_feature_raises() -> None:
+    with pytest.raises(QSharpError) as excinfo:
+        Interpreter(TargetProfile.Unrestricted, ["v3-syntax"])
+    assert "unknown language feature `v3-syntax`" in str(excinfo.value)
+
+
+def test_dense_simulator() -> None:
+    e = Interpreter(TargetProfile.Unrestricted, simulator="dense")
+    assert e.config()["simulator"] == "dense"
+    value = e.interpret(
+        "{ use qs = Qubit[4]; ApplyToEach(H, qs); for i in 1..3 { CNOT(qs[0], qs[i]); } ApplyToEach(H, qs); MResetEachZ(qs) }"
+    )
+    assert len(value) == 4
+    clone = e.clone_with(simulator="sparse")
+    assert clone.config()["simulator"] == "sparse"
+    with pytest.raises(Exception) as excinfo:
+        e.clone_with(simulator="gpu")
+    assert str(excinfo.value) == "unknown simulator `gpu`, expected `sparse` or `dense`"
+
+
+def test_clone_with_overrides() -> None:
+    e = Interpreter(TargetProfile.Base)
+    e.set_classical_seed(7)
+    clone = e.clone_with(target_profile=TargetProfile.Unrestricted, quantum_seed=3)
+    config = clone.config()
+    assert config["target_profile"] == TargetProfile.Unrestricted
+    assert config["quantum_seed"] == 3
+    assert config["classical_seed"] == 7
+    assert e.config()["target_profile"] == TargetProfile.Base
+
+
+def test_clone_with_does_not_carry_over_fragments() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    e.interpret("function Foo() : Int { 42 }")
+    clone = e.clone_with()
+    with pytest.raises(QSharpError):
+        clone.interpret("Foo()")
+
+
+def test_clone_with_shares_compilation_unless_it_changes() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    shared = e.clone_with(quantum_seed=3)
+    assert shared.compilation_stats()["total_ms"] == e.compilation_stats()["total_ms"]
+    assert shared.interpret("Microsoft.Quantum.Math.AbsI(-2)") == 2
+    recompiled = e.clone_with(target_profile=TargetProfile.Base)
+    assert recompiled.interpret("Microsoft.Quantum.Math.AbsI(-2)") == 2
+
+
+def test_clone_with_custom_stdlib() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    std = {"std.qs": "namespace Custom { function Answer() : Int { 42 } }"}
+    clone = e.clone_with(stdlib=std)
+    assert clone.config()["stdlib"] == std
+    assert clone.interpret("Custom.Answer()") == 42
+    with pytest.raises(QSharpError):
+        clone.interpret("Microsoft.Quantum.Math.PI()")
+    core_only = e.clone_with(stdlib=False)
+    assert core_only.config()["stdlib"] is False
+
+
+def test_clone_with_unknown_override_raises() -> None:
+    e = Interpreter(TargetProfile.Unrestricted)
+    with pytest.raises(Exception) as excinfo:
+        e.clone_with(target="base")
+    assert str(excinfo.value) == "unknown configuration override `target`"
@@ -24,6 +24,18 @@ def test_project(qsharp) -> None:
     assert result == 4
 
 
+def test_project_run_tests(qsharp) -> None:
+    qsharp.init(project_root="/tests")
+    results = qsharp.run_tests()
+    assert [(r["name"], r["passed"]) for r in results] == [
+        ("Test.Passes", True),
+        ("Test.Fails", False),
+    ]
+    assert results[0]["output"] == "passing\n"
+    assert results[0]["errors"] is None
+    assert "expected three" in results[1]["errors"]
+
+
 def test_project_compile_error(qsharp) -> None:
     with pytest.raises(Exception) as excinfo:
         qsharp.init(project_root="/compile_error")
@@ -58,6 +70,15 @@ memfs = {
             },
             "qsharp.json": "{}",
         },
+        "tests": {
+            "src": {
+                "test.qs": "namespace Test {\n"
+                "    @Test() operation Passes() : Unit { Message(\"passing\"); }\n"
+                "    @Test() function Fails() : Unit { fail \"expected three\"; }\n"
+                "}",
+            },
+            "qsharp.json": "{}",
+        },
         "bad_qsharp_json": {"qsharp.json": "BAD_JSON_CONTENTS"},
         "unreadable_qsharp_json": {
             "qsharp.json": OSError("could not read qsharp.json")
@@ -5,6 +5,8 @@ import qsharp
 import qsharp.utils
 from contextlib import redirect_stdout
 import io
+import json
+import pytest
 
 # Tests for the Python library for Q#
 
@@ -73,6 +75,184 @@ def test_classical_seed() -> None:
     assert value1 != value3
 
 
+def test_limits() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.set_limits(max_qubits=2, max_statements=1000)
+    qsharp.eval("{ use qs = Qubit[2]; }")
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval("{ use qs = Qubit[3]; }")
+    assert "qubit limit exceeded" in str(excinfo.value)
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval("{ mutable i = 0; while true { set i += 1; } }")
+    assert "statement limit exceeded" in str(excinfo.value)
+    qsharp.set_limits()
+    qsharp.eval("{ use qs = Qubit[3]; }")
+
+
+def test_deep_recursion_raises_error_with_call_stack() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.eval("function Deep(n : Int) : Int { Deep(n + 1) }")
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval("Deep(0)")
+    assert "call depth limit exceeded" in str(excinfo.value)
+    assert "Call stack:" in str(excinfo.value)
+    assert "at Deep in line_0" in str(excinfo.value)
+    qsharp.set_limits(max_call_depth=100)
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval("Deep(0)")
+    assert "cannot nest more than 100 calls" in str(excinfo.value)
+    qsharp.set_limits()
+
+
+def test_runtime_error_has_stack_frames() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.eval(
+        "function Get(xs : Int[], i : Int) : Int { xs[i] }\n"
+        "operation Outer() : Int { Get([1, 2], 5) }"
+    )
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval("Outer()")
+    frames = excinfo.value.stack_frames
+    assert [frame["name"] for frame in frames] == ["Get", "Outer"]
+    assert frames[0]["functor"] == ""
+    assert frames[0]["source"] == "line_0"
+    assert frames[0]["line"] == 1
+    assert frames[1]["source"] == "line_1"
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval("let x : Int = 1.0;")
+    assert len(excinfo.value.stack_frames) == 0
+
+
+def test_equality_fact_failure_shows_values() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval(
+            'Microsoft.Quantum.Diagnostics.EqualityFact(2 + 2, 5, "wrong sum")'
+        )
+    assert "fact failed: wrong sum: expected 5, found 4" in str(excinfo.value)
+
+
+def test_untrusted_limits() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted, untrusted=True)
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval('{ while true { Message("spam"); } }')
+    assert "output limit exceeded" in str(excinfo.value)
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval("{ use qs = Qubit[100]; }")
+    assert "qubit limit exceeded" in str(excinfo.value)
+    qsharp.set_limits(max_seconds=0)
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.eval("{ mutable i = 0; while true { set i += 1; } }")
+    assert "time limit exceeded" in str(excinfo.value)
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+
+
+def test_forced_outcomes() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.eval(
+        "operation Branch() : String { use q = Qubit(); H(q); if MResetZ(q) == One { \"one\" } else { \"zero\" } }"
+    )
+    qsharp.set_forced_outcomes(sequence=[qsharp.Result.One])
+    assert qsharp.run("Branch()", 2) == ["one", "one"]
+    qsharp.set_forced_outcomes(qubits={0: qsharp.Result.Zero})
+    assert qsharp.run("Branch()", 2) == ["zero", "zero"]
+    qsharp.set_forced_outcomes()
+
+
+def test_replay() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    assert qsharp.last_recording() is None
+    qsharp.eval(
+        "operation Flips() : Result[] { use qs = Qubit[8]; ApplyToEach(H, qs); MResetEachZ(qs) }"
+    )
+    results = qsharp.run("Flips()", 1)
+    recording = qsharp.last_recording()
+    assert recording["measurements"] == results[0]
+    assert qsharp.run("Flips()", 3, replay=recording) == results * 3
+    recording["measurements"] = [qsharp.Result.Zero]
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.run("Flips()", 1, replay=recording)
+    assert "does not match the replayed recording" in str(excinfo.value)
+
+
+def test_last_run_statistics() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    assert qsharp.last_run_statistics() is None
+    qsharp.run("{ use qs = Qubit[4]; ApplyToEach(H, qs); MResetEachZ(qs) }", 1)
+    statistics = qsharp.last_run_statistics()
+    assert statistics["qubit_high_water_mark"] == 4
+    assert statistics["reused_qubit_high_water_mark"] == 4
+    assert statistics["peak_amplitudes"] == 16
+    assert statistics["peak_memory_bytes"] > 0
+    assert statistics["samples"][0] == {"operation": 8, "qubits": 4, "amplitudes": 16}
+    lifetimes = statistics["qubit_lifetimes"]
+    assert [lifetime["qubit"] for lifetime in lifetimes] == [0, 1, 2, 3]
+    assert lifetimes[0]["allocated"] == 0
+    assert lifetimes[0]["partners"] == []
+
+
+def test_compilation_stats() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    stats = qsharp.compilation_stats()
+    assert stats["total_ms"] >= 0
+    for phase in ["parse", "resolve", "typeck", "lower", "passes"]:
+        assert phase in stats["phases"]
+    names = [package["name"] for package in stats["packages"]]
+    assert "core" in names and "std" in names and "source" in names
+    std = next(p for p in stats["packages"] if p["name"] == "std")
+    assert std["callables"] > 0
+    assert stats["rca"]["callables"] == 0
+
+
+def test_call_graph() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.eval("function Double(x : Int) : Int { x * 2 }")
+    qsharp.eval("function Quadruple(x : Int) : Int { Double(Double(x)) }")
+    graph = qsharp.call_graph()
+    names = [node["name"] for node in graph["nodes"]]
+    assert names == ["Double", "Quadruple"]
+    assert graph["nodes"][0]["capabilities"] == qsharp.RuntimeCapability.empty()
+    assert len(graph["edges"]) == 1
+    edge = graph["edges"][0]
+    assert (edge["caller"], edge["callee"]) == (1, 0)
+    assert [call["column"] for call in edge["calls"]] == [36, 43]
+
+
+def test_run_shots() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.set_quantum_seed(42)
+    qsharp.eval(
+        "operation Flip() : Result { use q = Qubit(); H(q); let r = MResetZ(q); if r == One { fail \"one\"; } r }"
+    )
+    single = qsharp.run_shots("Flip()", 32, workers=1)
+    parallel = qsharp.run_shots("Flip()", 32, workers=4)
+    assert single["seed"] == 42
+    assert single["counts"] == parallel["counts"]
+    assert [f["shot"] for f in single["failures"]] == [
+        f["shot"] for f in parallel["failures"]
+    ]
+    assert single["counts"].get("Zero", 0) + len(single["failures"]) == 32
+    assert "one" in single["failures"][0]["errors"]
+    assert single["measurement_statistics"] is None
+
+
+def test_run_shots_measurement_statistics() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.set_quantum_seed(42)
+    qsharp.eval(
+        "operation Bell() : Unit { use qs = Qubit[2]; H(qs[0]); CNOT(qs[0], qs[1]); let rs = MResetEachZ(qs); }"
+    )
+    stats = qsharp.run_shots("Bell()", 32, measurement_statistics=True)[
+        "measurement_statistics"
+    ]
+    assert stats["qubits"] == [0, 1]
+    assert stats["shots"] == [32, 32]
+    assert 0.0 < stats["marginals"][0] < 1.0
+    assert stats["marginals"][0] == stats["marginals"][1]
+    assert stats["joint"][0][1] == stats["marginals"][0]
+    assert stats["correlations"] == [[1.0, 1.0], [1.0, 1.0]]
+
+
 def test_dump_machine() -> None:
     qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
     qsharp.eval(
@@ -100,6 +280,39 @@ def test_dump_machine() -> None:
         assert idx in state_dump
 
 
+def test_dump_machine_filters() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.eval(
+        """
+    use q1 = Qubit();
+    use q2 = Qubit();
+    Ry(0.5, q1);
+    H(q2);
+    """
+    )
+    assert len(qsharp.dump_machine()) == 4
+    assert len(qsharp.dump_machine(min_probability=0.1)) == 2
+    top = qsharp.dump_machine(top=1)
+    assert len(top) == 1
+    assert top.qubit_count == 2
+    state_dump = qsharp.dump_machine()
+    assert list(state_dump.top(2)) == list(state_dump.filter(0.1))
+
+
+def test_check_equivalence() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.set_quantum_seed(42)
+    x = "qs => X(qs[0])"
+    hzh = "qs => { H(qs[0]); Z(qs[0]); H(qs[0]); }"
+    assert qsharp.check_equivalence(x, hzh, 1)
+    assert qsharp.check_equivalence(x, hzh, 1, samples=4)
+    assert not qsharp.check_equivalence(x, "qs => Z(qs[0])", 1)
+    assert not qsharp.check_equivalence(x, "qs => Z(qs[0])", 1, samples=4)
+    with pytest.raises(qsharp.QSharpError) as excinfo:
+        qsharp.check_equivalence(x, "qs => { let _ = M(qs[0]); }", 1)
+    assert "operation measures qubits" in str(excinfo.value)
+
+
 def test_dump_operation() -> None:
     qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
     res = qsharp.utils.dump_operation("qs => ()", 1)
@@ -237,6 +450,152 @@ def test_compile_qir_str() -> None:
     assert "define void @ENTRYPOINT__main()" in qir
 
 
+def test_metadata_in_qir_module_flags() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Base)
+    qsharp.eval("operation Program() : Result { use q = Qubit(); return M(q) }")
+    qsharp.set_metadata({"experiment": "bell"})
+    qir = str(qsharp.compile("Program()", metadata={"run": "7"}))
+    assert '!4 = !{i32 1, !"user.experiment", !"bell"}' in qir
+    assert '!5 = !{i32 1, !"user.run", !"7"}' in qir
+    # Run metadata only applies to the run it was passed to.
+    qir = str(qsharp.compile("Program()"))
+    assert "!llvm.module.flags = !{!0, !1, !2, !3, !4}" in qir
+
+
+def test_compile_qir_with_debug_info() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Base)
+    qsharp.eval(
+        "operation Program() : Result {\n    use q = Qubit();\n    H(q);\n    return M(q);\n}"
+    )
+    qir = str(qsharp.compile("Program()", debug_info=True))
+    assert "define void @ENTRYPOINT__main() #0 !dbg" in qir
+    assert "!DILocation(line: 3, column: 5" in qir
+    assert '!"Debug Info Version"' in qir
+    assert "!dbg" not in str(qsharp.compile("Program()"))
+
+
+def test_metadata_in_circuit_and_estimate() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.set_metadata({"experiment": "bell"})
+    circuit = qsharp.circuit("{ use q = Qubit(); H(q); }", metadata={"run": "7"})
+    assert json.loads(circuit.json())["metadata"] == {"experiment": "bell", "run": "7"}
+    result = qsharp.estimate("{ use q = Qubit(); T(q); }")
+    assert result["metadata"] == {"experiment": "bell"}
+    assert json.loads(result.json)["metadata"] == {"experiment": "bell"}
+
+
+def test_gate_set_decomposes_qir_and_circuit() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Base)
+    qsharp.eval("operation Program() : Result { use q = Qubit(); H(q); return M(q) }")
+    qsharp.set_gate_set("rz,sx,cx")
+    qir = str(qsharp.compile("Program()"))
+    assert "call void @__quantum__qis__sx__body" in qir
+    assert "call void @__quantum__qis__h__body" not in qir
+    circuit = qsharp.circuit("Program()")
+    assert "SX" in str(circuit)
+    with pytest.raises(Exception):
+        qsharp.set_gate_set("h,t,cx")
+
+
+def test_advise_suggests_deferred_measurement() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.eval(
+        "operation Program() : Result { use (a, b) = (Qubit(), Qubit()); let r = M(a); if r == One { X(b); } MResetZ(b) }"
+    )
+    advice = qsharp.advise("Program()")
+    assert len(advice) == 1
+    assert advice[0]["code"] == "Qsc.Advisor.DeferMeasurement"
+    assert advice[0]["after"] == "CNOT(a, b);"
+
+
+def test_advise_static_program_has_no_advice() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.eval("operation Program() : Result { use q = Qubit(); MResetZ(q) }")
+    assert qsharp.advise("Program()") == []
+
+
+def test_benchmark() -> None:
+    qsharp.init()
+    qsharp.eval(
+        "operation Program() : Result { use (a, b) = (Qubit(), Qubit()); H(a); CNOT(a, b); Reset(a); MResetZ(b) }"
+    )
+    benchmark = qsharp.benchmark("Program()", 3)
+    assert benchmark["repetitions"] == 3
+    for phase in ["compile", "classical", "simulator", "total"]:
+        assert benchmark[phase]["min"] <= benchmark[phase]["max"]
+    assert benchmark["gate_counts"] == {"cx": 1.0, "h": 1.0, "mresetz": 1.0, "reset": 1.0}
+
+
+def test_benchmark_requires_repetitions() -> None:
+    qsharp.init()
+    with pytest.raises(ValueError):
+        qsharp.benchmark("Zero", 0)
+
+
+def test_hints() -> None:
+    hints = qsharp.hints(
+        "namespace Test {\n"
+        "    operation FlipIf(b : Bool, q : Qubit) : Unit { if b { X(q); } }\n"
+        "    operation Main() : Unit {\n"
+        "        use q = Qubit();\n"
+        "        let r = M(q);\n"
+        "        FlipIf(r == One, q);\n"
+        "    }\n"
+        "}"
+    )
+    assert hints == [
+        {
+            "kind": "dynamicBinding",
+            "label": "dynamic Result",
+            "start": (4, 12),
+            "end": (4, 13),
+        },
+        {
+            "kind": "requiredCapabilities",
+            "label": "requires ForwardBranching",
+            "start": (5, 8),
+            "end": (5, 27),
+        },
+    ]
+
+
+def test_supported_language_features() -> None:
+    features = qsharp.supported_language_features()
+    preview = next(f for f in features if f["name"] == "v2-preview-syntax")
+    assert preview["stability"] == "preview"
+    assert preview["description"] != ""
+
+
+def test_trace_callback_reports_phases() -> None:
+    phases = []
+    qsharp.set_trace_callback(lambda name, elapsed: phases.append((name, elapsed)))
+    try:
+        qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+        qsharp.eval("1 + 1")
+    finally:
+        qsharp.set_trace_callback(None)
+    names = [name for (name, _) in phases]
+    for phase in ["parse", "resolve", "typeck", "lower", "passes", "eval"]:
+        assert phase in names
+    assert all(elapsed >= 0 for (_, elapsed) in phases)
+
+
+def test_hints_static_source_has_no_hints() -> None:
+    assert qsharp.hints("operation Main() : Result { use q = Qubit(); MResetZ(q) }") == []
+
+
+def test_fix() -> None:
+    assert (
+        qsharp.fix("function Foo() : Int { let x = (1);; x + (2 * 3) }")
+        == "function Foo() : Int { let x = 1; x + 2 * 3 }"
+    )
+
+
+def test_fix_with_compilation_error_raises() -> None:
+    with pytest.raises(qsharp.QSharpError):
+        qsharp.fix("function Foo() : Int { let x = ; }")
+
+
 def test_run_with_result(capsys) -> None:
     qsharp.init()
     qsharp.eval('operation Foo() : Result { Message("Hello, world!"); Zero }')
@@ -246,6 +605,38 @@ def test_run_with_result(capsys) -> None:
     assert stdout == "Hello, world!\nHello, world!\nHello, world!\n"
 
 
+def test_run_quiet_drops_messages(capsys) -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    results = qsharp.run(
+        '{ for i in 1..3 { Message($"step {i}"); } Microsoft.Quantum.Diagnostics.DumpMachine(); 7 }',
+        2,
+        save_events=True,
+        quiet=True,
+    )
+    assert [shot["result"] for shot in results] == [7, 7]
+    for shot in results:
+        [machine] = shot["events"]
+        assert machine.state_dump() is not None
+    assert capsys.readouterr().out == ""
+
+
+def test_run_events_are_typed() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    results = qsharp.run(
+        '{ use qs = Qubit[3]; X(qs[2]); Microsoft.Quantum.Diagnostics.DumpRegister([qs[2]]); Microsoft.Quantum.Diagnostics.DumpMachine(); Message("done"); }',
+        1,
+        save_events=True,
+    )
+    [register, machine, message] = results[0]["events"]
+    assert register.state_dump().qubits == [2]
+    assert register.state_dump().qubit_count == 1
+    assert register.matrix_dump() is None
+    assert machine.state_dump().qubits is None
+    assert machine.state_dump().qubit_count == 3
+    assert message.state_dump() is None
+    assert message.message() == "done"
+
+
 def test_run_with_result_callback(capsys) -> None:
     def on_result(result):
         nonlocal called
@@ -264,3 +655,44 @@ def test_run_with_result_callback(capsys) -> None:
     stdout = capsys.readouterr().out
     assert stdout == ""
     assert called
+
+
+def test_runtime_feature_flags() -> None:
+    features = (
+        qsharp.RuntimeFeature.UseOfDynamicInt
+        | qsharp.RuntimeFeature.ForwardBranchingOnDynamicValue
+    )
+    assert qsharp.RuntimeFeature.ForwardBranchingOnDynamicValue in features
+    assert qsharp.RuntimeFeature.UseOfDynamicBool not in features
+    assert (
+        repr(features)
+        == "RuntimeFeature.UseOfDynamicInt | RuntimeFeature.ForwardBranchingOnDynamicValue"
+    )
+    assert features.names == ["UseOfDynamicInt", "ForwardBranchingOnDynamicValue"]
+    assert list(features) == [
+        qsharp.RuntimeFeature.UseOfDynamicInt,
+        qsharp.RuntimeFeature.ForwardBranchingOnDynamicValue,
+    ]
+    assert features & qsharp.RuntimeFeature.UseOfDynamicInt == (
+        qsharp.RuntimeFeature.UseOfDynamicInt
+    )
+    assert qsharp.RuntimeFeature(features.value) == features
+    assert not qsharp.RuntimeFeature.empty()
+    assert repr(qsharp.RuntimeFeature.empty()) == "RuntimeFeature(0)"
+    assert features.runtime_capabilities() == (
+        qsharp.RuntimeCapability.ForwardBranching
+        | qsharp.RuntimeCapability.IntegerComputations
+    )
+
+
+def test_runtime_capability_flags() -> None:
+    capabilities = qsharp.RuntimeCapability.all()
+    assert len(capabilities) == 5
+    assert qsharp.RuntimeCapability.BackwardsBranching in capabilities
+    assert (
+        capabilities - qsharp.RuntimeCapability.HigherLevelConstructs
+        == ~qsharp.RuntimeCapability.HigherLevelConstructs
+    )
+    assert {qsharp.RuntimeCapability.ForwardBranching: 1}[
+        qsharp.RuntimeCapability(1)
+    ] == 1
@@ -1,8 +1,17 @@
 # Copyright (c) Microsoft Corporation.
 # Licensed under the MIT License.
 
+import json
+import pytest
 import qsharp
-from qsharp.estimator import EstimatorParams, QubitParams, QECScheme, LogicalCounts
+from qsharp._native import LogicalCounts as NativeLogicalCounts
+from qsharp.estimator import (
+    EstimatorParams,
+    ErrorBudgetPartition,
+    QubitParams,
+    QECScheme,
+    LogicalCounts,
+)
 
 
 def test_qsharp_estimation() -> None:
@@ -30,6 +39,93 @@ def test_qsharp_estimation() -> None:
     )
 
 
+def test_qsharp_estimation_of_repeated_call() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    qsharp.eval(
+        """
+        operation Layer(n : Int) : Unit {
+            use qs = Qubit[n];
+            for q in qs {
+                T(q);
+                M(q);
+            }
+        }
+        """
+    )
+    res = qsharp.estimate("Layer", args=[4], repetitions=3)
+    assert res["status"] == "success"
+    assert res.logical_counts == LogicalCounts(
+        {
+            "numQubits": 4,
+            "tCount": 12,
+            "rotationCount": 0,
+            "rotationDepth": 0,
+            "cczCount": 0,
+            "measurementCount": 12,
+        }
+    )
+
+
+def test_qsharp_estimation_frontier_mode() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    res = qsharp.estimate(
+        """{{
+        use qs = Qubit[10];
+        for q in qs {{
+            T(q);
+            M(q);
+        }}
+        }}""",
+        mode="frontier",
+    )
+    assert res["status"] == "success"
+    assert res["jobParams"]["estimateType"] == "frontier"
+    points = res.frontier
+    assert len(points) == len(res["frontierEntries"])
+    runtimes = [point["runtime"] for point in points]
+    assert runtimes == sorted(runtimes)
+    for point, entry in zip(points, res["frontierEntries"]):
+        assert point["physical_qubits"] == entry["physicalCounts"]["physicalQubits"]
+        assert point["code_distance"] == entry["logicalQubit"]["codeDistance"]
+        assert point["data"] == entry
+
+
+def test_qsharp_estimation_single_point_frontier() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    res = qsharp.estimate("{ use q = Qubit(); T(q); }")
+    assert len(res.frontier) == 1
+    assert res.frontier[0]["runtime"] == res["physicalCounts"]["runtime"]
+
+
+def test_qsharp_estimation_with_error_budget_partition() -> None:
+    qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
+    program = """{
+        use qs = Qubit[10];
+        for q in qs {
+            T(q);
+            M(q);
+        }
+    }"""
+
+    res = qsharp.estimate(program, error_budget=0.01)
+    assert res["jobParams"]["errorBudget"] == 0.01
+    budget = res.error_budget
+    assert budget.logical == pytest.approx(0.005)
+    assert budget.t_states == pytest.approx(0.005)
+    assert budget.rotations == 0
+
+    res = qsharp.estimate(
+        program,
+        error_budget=ErrorBudgetPartition(logical=0.008, t_states=0.002, rotations=0),
+    )
+    budget = res.error_budget
+    assert budget.logical == pytest.approx(0.008)
+    assert budget.t_states == pytest.approx(0.002)
+
+    with pytest.raises(ValueError):
+        qsharp.estimate(program, error_budget=2.0)
+
+
 def test_qsharp_estimation_from_precalculated_counts() -> None:
     qsharp.init(target_profile=qsharp.TargetProfile.Unrestricted)
     res = qsharp.estimate(
@@ -255,3 +351,29 @@ def test_building_frontier_from_logical_counts_with_single_params() -> None:
     first_entry = res["frontierEntries"][0]
     assert first_entry["physicalCounts"] is not None
     assert first_entry["physicalCountsFormatted"] is not None
+
+
+def test_logical_counts_are_validated_at_construction() -> None:
+    with pytest.raises(ValueError):
+        LogicalCounts({"numQubits": 0, "tCount": 10})
+    with pytest.raises(ValueError):
+        LogicalCounts({"numQubits": 10, "rotationCount": 2, "rotationDepth": 3})
+    with pytest.raises(ValueError):
+        LogicalCounts({"numQubits": 10, "rotationCount": 2})
+    with pytest.raises(ValueError):
+        LogicalCounts({"numQubits": 10, "tCount": -1})
+
+
+def test_native_logical_counts_estimate() -> None:
+    counts = NativeLogicalCounts(12581, t_count=12, rotation_count=12, rotation_depth=12)
+    assert json.loads(counts.json()) == {
+        "numQubits": 12581,
+        "tCount": 12,
+        "rotationCount": 12,
+        "rotationDepth": 12,
+        "cczCount": 0,
+        "ccixCount": 0,
+        "measurementCount": 0,
+    }
+    res = json.loads(counts.estimate("[{}]"))
+    assert res[0]["status"] == "success"
@@ -241,11 +241,17 @@ function registerMonacoLanguageServiceProviders(
             case "property":
               kind = monaco.languages.CompletionItemKind.Property;
               break;
+            case "snippet":
+              kind = monaco.languages.CompletionItemKind.Snippet;
+              break;
           }
           return {
             label: i.label,
             kind: kind,
-            insertText: i.label,
+            insertText: i.insertText ?? i.label,
+            insertTextRules: i.insertText
+              ? monaco.languages.CompletionItemInsertTextRule.InsertAsSnippet
+              : undefined,
             sortText: i.sortText,
             detail: i.detail,
             additionalTextEdits: i.additionalTextEdits?.map((edit) => {
@@ -5,7 +5,7 @@ import { QscEventTarget, ShotResult, VSDiagnostic } from "qsharp-lang";
 import { useEffect, useState } from "preact/hooks";
 
 import { Histogram } from "qsharp-lang/ux";
-import { StateTable } from "./state.js";
+import { MatrixTable, StateTable } from "./state.js";
 import { ActiveTab } from "./main.js";
 
 function resultToLabel(result: string | VSDiagnostic): string {
@@ -240,6 +240,8 @@ export function ResultsTab(props: {
             {resultState.currResult?.events.map((evt) => {
               return evt.type === "Message" ? (
                 <div class="message-output">&gt; {evt.message}</div>
+              ) : evt.type === "Matrix" ? (
+                <MatrixTable matrix={evt.matrix}></MatrixTable>
               ) : (
                 <div>
                   <StateTable
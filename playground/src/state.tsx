@@ -2,7 +2,7 @@
 // Licensed under the MIT License.
 
 import { useEffect, useRef } from "preact/hooks";
-import { Dump } from "qsharp-lang";
+import { Dump, Matrix } from "qsharp-lang";
 
 function probability(real: number, imag: number) {
   return real * real + imag * imag;
@@ -72,3 +72,22 @@ export function StateTable(props: { dump: Dump; latexDump: string }) {
     </div>
   );
 }
+
+export function MatrixTable(props: { matrix: Matrix }) {
+  return (
+    <div>
+      <table class="state-table">
+        <tbody>
+          {props.matrix.map((row) => (
+            <tr>
+              {row.map(([real, imag]) => (
+                <td style="text-align: right">{formatComplex(real, imag)}</td>
+              ))}
+            </tr>
+          ))}
+        </tbody>
+      </table>
+      <br></br>
+    </div>
+  );
+}
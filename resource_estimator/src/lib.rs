@@ -25,7 +25,7 @@ pub use system::estimate_physical_resources_from_json;
 
 use counts::LogicalCounter;
 use miette::Diagnostic;
-use qsc::interpret::{self, GenericReceiver, Interpreter};
+use qsc::interpret::{self, GenericReceiver, Interpreter, Value};
 use system::estimate_physical_resources;
 use thiserror::Error;
 
@@ -63,3 +63,23 @@ pub fn estimate_expr(
     estimate_physical_resources(counter.logical_resources(), params)
         .map_err(|e| vec![Error::Estimation(e)])
 }
+
+/// Estimates the resources of calling the given operation with the given arguments `repetitions`
+/// times in a row, without generating an entry expression with a call for each repetition.
+pub fn estimate_call(
+    interpreter: &mut Interpreter,
+    operation: &str,
+    args: &[Value],
+    repetitions: u32,
+    params: &str,
+) -> Result<String, Vec<Error>> {
+    let mut counter = LogicalCounter::default();
+    let mut stdout = std::io::sink();
+    let mut out = GenericReceiver::new(&mut stdout);
+    interpreter
+        .run_call_with_sim(&mut counter, &mut out, operation, args, repetitions)
+        .map_err(|e| e.into_iter().map(Error::Interpreter).collect::<Vec<_>>())?
+        .map_err(|e| vec![Error::Interpreter(e[0].clone())])?;
+    estimate_physical_resources(counter.logical_resources(), params)
+        .map_err(|e| vec![Error::Estimation(e)])
+}
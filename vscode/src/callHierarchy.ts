@@ -0,0 +1,74 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { ILanguageService } from "qsharp-lang";
+import * as vscode from "vscode";
+import { ICallHierarchyItem } from "../../npm/lib/web/qsc_wasm";
+import { toVscodeRange } from "./common";
+
+export function createCallHierarchyProvider(
+  languageService: ILanguageService,
+) {
+  return new QSharpCallHierarchyProvider(languageService);
+}
+
+class QSharpCallHierarchyProvider implements vscode.CallHierarchyProvider {
+  constructor(public languageService: ILanguageService) {}
+
+  async prepareCallHierarchy(
+    document: vscode.TextDocument,
+    position: vscode.Position,
+  ): Promise<vscode.CallHierarchyItem | undefined> {
+    const item = await this.languageService.prepareCallHierarchy(
+      document.uri.toString(),
+      position,
+    );
+    return item && toVscodeCallHierarchyItem(item);
+  }
+
+  async provideCallHierarchyIncomingCalls(
+    item: vscode.CallHierarchyItem,
+  ): Promise<vscode.CallHierarchyIncomingCall[]> {
+    // The callable is identified by the position of its name.
+    const calls = await this.languageService.getIncomingCalls(
+      item.uri.toString(),
+      item.selectionRange.start,
+    );
+    return calls.map(
+      (call) =>
+        new vscode.CallHierarchyIncomingCall(
+          toVscodeCallHierarchyItem(call.from),
+          call.fromRanges.map(toVscodeRange),
+        ),
+    );
+  }
+
+  async provideCallHierarchyOutgoingCalls(
+    item: vscode.CallHierarchyItem,
+  ): Promise<vscode.CallHierarchyOutgoingCall[]> {
+    const calls = await this.languageService.getOutgoingCalls(
+      item.uri.toString(),
+      item.selectionRange.start,
+    );
+    return calls.map(
+      (call) =>
+        new vscode.CallHierarchyOutgoingCall(
+          toVscodeCallHierarchyItem(call.to),
+          call.fromRanges.map(toVscodeRange),
+        ),
+    );
+  }
+}
+
+function toVscodeCallHierarchyItem(
+  item: ICallHierarchyItem,
+): vscode.CallHierarchyItem {
+  return new vscode.CallHierarchyItem(
+    vscode.SymbolKind.Function,
+    item.name,
+    item.detail,
+    vscode.Uri.parse(item.location.source),
+    toVscodeRange(item.location.span),
+    toVscodeRange(item.selectionRange),
+  );
+}
@@ -0,0 +1,57 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { ILanguageService } from "qsharp-lang";
+import * as vscode from "vscode";
+import { ICodeAction } from "../../npm/lib/web/qsc_wasm";
+import { toVscodeRange } from "./common";
+
+export function createCodeActionsProvider(languageService: ILanguageService) {
+  return new QSharpCodeActionsProvider(languageService);
+}
+
+class QSharpCodeActionsProvider implements vscode.CodeActionProvider {
+  constructor(public languageService: ILanguageService) {}
+  async provideCodeActions(
+    document: vscode.TextDocument,
+    range: vscode.Range | vscode.Selection,
+  ): Promise<vscode.CodeAction[]> {
+    const codeActions = await this.languageService.getCodeActions(
+      document.uri.toString(),
+      { start: range.start, end: range.end },
+    );
+
+    return codeActions.map((action) => mapCodeAction(document, action));
+  }
+}
+
+function mapCodeAction(
+  document: vscode.TextDocument,
+  action: ICodeAction,
+): vscode.CodeAction {
+  const codeAction = new vscode.CodeAction(
+    action.title,
+    mapCodeActionKind(action.kind),
+  );
+  codeAction.edit = new vscode.WorkspaceEdit();
+  codeAction.edit.set(
+    document.uri,
+    action.edits.map(
+      (edit) => new vscode.TextEdit(toVscodeRange(edit.range), edit.newText),
+    ),
+  );
+  return codeAction;
+}
+
+function mapCodeActionKind(kind: ICodeAction["kind"]): vscode.CodeActionKind {
+  switch (kind) {
+    case "refactor.extract":
+      return vscode.CodeActionKind.RefactorExtract;
+    case "refactor.rewrite":
+      return vscode.CodeActionKind.RefactorRewrite;
+    case "source.organizeImports":
+      return vscode.CodeActionKind.SourceOrganizeImports;
+    default:
+      throw new Error(`Unknown code action kind: ${kind}`);
+  }
+}
@@ -73,10 +73,16 @@ class QSharpCompletionItemProvider implements vscode.CompletionItemProvider {
         case "property":
           kind = vscode.CompletionItemKind.Property;
           break;
+        case "snippet":
+          kind = vscode.CompletionItemKind.Snippet;
+          break;
       }
       const item = new CompletionItem(c.label, kind);
       item.sortText = c.sortText;
       item.detail = c.detail;
+      if (c.insertText) {
+        item.insertText = new vscode.SnippetString(c.insertText);
+      }
       item.additionalTextEdits = c.additionalTextEdits?.map((edit) => {
         return new vscode.TextEdit(toVscodeRange(edit.range), edit.newText);
       });
@@ -3,6 +3,7 @@
 
 import { TargetProfile, log } from "qsharp-lang";
 import * as vscode from "vscode";
+import { IInlayHintsConfiguration } from "../../npm/lib/web/qsc_wasm";
 
 export function getTarget(): TargetProfile {
   const target = vscode.workspace
@@ -32,3 +33,13 @@ export function getEnableFormating(): boolean {
     .getConfiguration("Q#")
     .get<boolean>("enableFormatting", true);
 }
+
+export function getInlayHints(): IInlayHintsConfiguration {
+  const config = vscode.workspace.getConfiguration("Q#.inlayHints");
+  return {
+    dynamicBindings: config.get<boolean>("dynamicBindings", true),
+    requiredCapabilities: config.get<boolean>("requiredCapabilities", true),
+    inferredTypes: config.get<boolean>("inferredTypes", true),
+    qubitCounts: config.get<boolean>("qubitCounts", true),
+  };
+}
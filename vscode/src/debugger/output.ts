@@ -58,6 +58,13 @@ export function createDebugConsoleEventTarget(out: (message: string) => void) {
     out(out_str);
   });
 
+  eventTarget.addEventListener("Matrix", (evt) => {
+    const rows = evt.detail.map((row) =>
+      row.map(([real, imag]) => `[${real}, ${imag}]`).join(" "),
+    );
+    out(`\nMatrix:\n\n${rows.join("\n")}\n`);
+  });
+
   eventTarget.addEventListener("Result", (evt) => {
     const resultJson = JSON.stringify(evt.detail.value, null, 2);
     out(`Result: ${resultJson}`);
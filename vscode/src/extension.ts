@@ -11,6 +11,8 @@ import {
 } from "qsharp-lang";
 import * as vscode from "vscode";
 import { initAzureWorkspaces } from "./azure/commands.js";
+import { createCallHierarchyProvider } from "./callHierarchy.js";
+import { createCodeActionsProvider } from "./codeActions.js";
 import { createCodeLensProvider } from "./codeLens.js";
 import {
   isQsharpDocument,
@@ -18,11 +20,12 @@ import {
   qsharpLanguageId,
 } from "./common.js";
 import { createCompletionItemProvider } from "./completion";
-import { getEnableFormating, getTarget } from "./config";
+import { getEnableFormating, getInlayHints, getTarget } from "./config";
 import { activateDebugger } from "./debugger/activate";
 import { createDefinitionProvider } from "./definition";
 import { startCheckingQSharp } from "./diagnostics";
 import { createHoverProvider } from "./hover";
+import { createInlayHintsProvider } from "./inlayHints.js";
 import {
   Logging,
   initLogForwarder,
@@ -268,6 +271,37 @@ async function activateLanguageService(extensionUri: vscode.Uri) {
     ),
   );
 
+  // inlay hints
+  subscriptions.push(
+    vscode.languages.registerInlayHintsProvider(
+      qsharpLanguageId,
+      createInlayHintsProvider(languageService),
+    ),
+  );
+
+  // call hierarchy
+  subscriptions.push(
+    vscode.languages.registerCallHierarchyProvider(
+      qsharpLanguageId,
+      createCallHierarchyProvider(languageService),
+    ),
+  );
+
+  // code actions
+  subscriptions.push(
+    vscode.languages.registerCodeActionsProvider(
+      qsharpLanguageId,
+      createCodeActionsProvider(languageService),
+      {
+        providedCodeActionKinds: [
+          vscode.CodeActionKind.RefactorExtract,
+          vscode.CodeActionKind.RefactorRewrite,
+          vscode.CodeActionKind.SourceOrganizeImports,
+        ],
+      },
+    ),
+  );
+
   // add the language service dispose handler as well
   subscriptions.push(languageService);
 
@@ -291,6 +325,17 @@ async function updateLanguageServiceProfile(languageService: ILanguageService) {
   });
 }
 
+async function updateLanguageServiceInlayHints(
+  languageService: ILanguageService,
+) {
+  const inlayHints = getInlayHints();
+  log.debug("Inlay hints set to: " + JSON.stringify(inlayHints));
+
+  languageService.updateConfiguration({
+    inlayHints: inlayHints,
+  });
+}
+
 async function updateLanguageServiceEnableFormatting(
   languageService: ILanguageService,
   formatterHandle: any,
@@ -327,6 +372,7 @@ async function loadLanguageService(baseUri: vscode.Uri) {
     getManifest,
   );
   await updateLanguageServiceProfile(languageService);
+  await updateLanguageServiceInlayHints(languageService);
   const end = performance.now();
   sendTelemetryEvent(
     EventType.LoadLanguageService,
@@ -345,6 +391,8 @@ function registerConfigurationChangeHandlers(
       updateLanguageServiceProfile(languageService);
     } else if (event.affectsConfiguration("Q#.enableFormatting")) {
       updateLanguageServiceEnableFormatting(languageService, formatterHandle);
+    } else if (event.affectsConfiguration("Q#.inlayHints")) {
+      updateLanguageServiceInlayHints(languageService);
     }
   });
 }
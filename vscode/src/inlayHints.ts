@@ -0,0 +1,67 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+import { ILanguageService } from "qsharp-lang";
+import * as vscode from "vscode";
+import { IInlayHint } from "../../npm/lib/web/qsc_wasm";
+
+export function createInlayHintsProvider(languageService: ILanguageService) {
+  return new QSharpInlayHintsProvider(languageService);
+}
+
+class QSharpInlayHintsProvider implements vscode.InlayHintsProvider {
+  constructor(public languageService: ILanguageService) {}
+  async provideInlayHints(
+    document: vscode.TextDocument,
+    range: vscode.Range,
+  ): Promise<vscode.InlayHint[]> {
+    const inlayHints = await this.languageService.getInlayHints(
+      document.uri.toString(),
+    );
+
+    return inlayHints
+      .map((hint) => mapInlayHint(hint))
+      .filter((hint) => range.contains(hint.position));
+  }
+}
+
+function mapInlayHint(hint: IInlayHint): vscode.InlayHint {
+  const position = new vscode.Position(
+    hint.position.line,
+    hint.position.character,
+  );
+  switch (hint.kind) {
+    case "dynamicBinding": {
+      // Shown after the binding name, like a type annotation.
+      const inlayHint = new vscode.InlayHint(
+        position,
+        `: ${hint.label}`,
+        vscode.InlayHintKind.Type,
+      );
+      inlayHint.tooltip = "The value of this binding is only known at runtime";
+      return inlayHint;
+    }
+    case "requiredCapabilities": {
+      const inlayHint = new vscode.InlayHint(position, hint.label);
+      inlayHint.paddingLeft = true;
+      inlayHint.tooltip =
+        "This call needs runtime capabilities beyond the Base profile";
+      return inlayHint;
+    }
+    case "inferredType": {
+      return new vscode.InlayHint(
+        position,
+        `: ${hint.label}`,
+        vscode.InlayHintKind.Type,
+      );
+    }
+    case "qubitCount": {
+      const inlayHint = new vscode.InlayHint(position, hint.label);
+      inlayHint.paddingLeft = true;
+      inlayHint.tooltip = "The number of qubits allocated";
+      return inlayHint;
+    }
+    default:
+      throw new Error(`Unknown inlay hint kind: ${hint.kind}`);
+  }
+}
@@ -15,6 +15,7 @@ export async function getManifest(uri: string): Promise<{
   manifestDirectory: string;
   languageFeatures: string[] | undefined;
   lints: { lint: string; level: string }[];
+  config: string[];
 } | null> {
   const manifestDocument = await findManifestDocument(uri);
   if (manifestDocument === null) {
@@ -166,11 +167,13 @@ async function getManifestThrowsOnParseFailure(uri: string): Promise<{
   manifestDirectory: string;
   languageFeatures: string[] | undefined;
   lints: { lint: string; level: string }[];
+  config: string[];
 } | null> {
   const manifestDocument = await findManifestDocument(uri);
   let parsedManifest: {
     languageFeatures: string[];
     lints: { lint: string; level: string }[] | undefined;
+    config: string[] | undefined;
   } | null = null;
 
   if (manifestDocument) {
@@ -194,6 +197,7 @@ async function getManifestThrowsOnParseFailure(uri: string): Promise<{
       manifestDirectory: manifestDirectory.toString(),
       languageFeatures: parsedManifest?.languageFeatures,
       lints: parsedManifest?.lints || [],
+      config: parsedManifest?.config || [],
     };
   }
   return null;
@@ -20,7 +20,9 @@ serializable_type! {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub code: Option<String>,
         #[serde(skip_serializing_if = "Vec::is_empty")]
-        pub related: Vec<Related>
+        pub related: Vec<Related>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pub stack: Vec<CallFrame>
     },
     r#"export interface VSDiagnostic {
         range: IRange,
@@ -28,6 +30,7 @@ serializable_type! {
         severity: "error" | "warning" | "info"
         code?: string;
         related?: IRelatedInformation[];
+        stack?: ICallFrame[];
     }"#
 }
 
@@ -43,6 +46,21 @@ serializable_type! {
     }"#
 }
 
+serializable_type! {
+    CallFrame,
+    {
+        pub name: String,
+        pub functor: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub location: Option<Location>,
+    },
+    r#"export interface ICallFrame {
+        name: string;
+        functor: string;
+        location?: ILocation;
+    }"#
+}
+
 impl VSDiagnostic {
     pub fn json(&self) -> serde_json::Value {
         serde_json::to_value(self).expect("serializing VSDiagnostic should succeed")
@@ -56,10 +74,28 @@ impl VSDiagnostic {
             interpret::Error::Eval(e) => error_labels(e.error()),
             interpret::Error::NoEntryPoint
             | interpret::Error::UnsupportedRuntimeCapabilities
-            | interpret::Error::NoCircuitForOperation => Vec::new(),
+            | interpret::Error::NoCircuitForOperation
+            | interpret::Error::ReplayDiverged(_)
+            | interpret::Error::MeasuringOperation
+            | interpret::Error::AmbiguousEntryPoint(_)
+            | interpret::Error::EntryPointArgCount(..)
+            | interpret::Error::EntryPointArgType(..)
+            | interpret::Error::ShotsWithCallbacks => Vec::new(),
         };
 
-        Self::new(labels, source_name, err)
+        let mut diagnostic = Self::new(labels, source_name, err);
+        diagnostic.stack = err
+            .stack_frames()
+            .iter()
+            .map(|frame| CallFrame {
+                name: frame.name().to_string(),
+                functor: frame.functor().to_string(),
+                location: frame
+                    .location(qsc::line_column::Encoding::Utf16)
+                    .map(Into::into),
+            })
+            .collect();
+        diagnostic
     }
 
     /// Creates a [`VSDiagnostic`] from a compiler error. See `VSDiagnostic::new()` for details.
@@ -144,6 +180,7 @@ impl VSDiagnostic {
             message,
             code,
             related,
+            stack: Vec::new(),
         }
     }
 }
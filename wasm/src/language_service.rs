@@ -4,7 +4,7 @@
 use crate::{
     diagnostic::VSDiagnostic,
     into_async_rust_fn_with,
-    line_column::{ILocation, IPosition, Location, Position, Range},
+    line_column::{ILocation, IPosition, IRange, Location, Position, Range},
     project_system::{
         get_manifest_transformer, list_directory_transformer, read_file_transformer,
         GetManifestCallback, ListDirectoryCallback, ReadFileCallback,
@@ -99,6 +99,14 @@ impl LanguageService {
                     "exe" => PackageType::Exe,
                     _ => panic!("invalid package type"),
                 }),
+                inlay_hints: config
+                    .inlayHints
+                    .map(|hints| qsls::protocol::InlayHintsConfig {
+                        dynamic_bindings: hints.dynamicBindings,
+                        required_capabilities: hints.requiredCapabilities,
+                        inferred_types: hints.inferredTypes,
+                        qubit_counts: hints.qubitCounts,
+                    }),
             });
     }
 
@@ -155,10 +163,12 @@ impl LanguageService {
                         qsls::protocol::CompletionItemKind::Property => "property",
                         qsls::protocol::CompletionItemKind::Variable => "variable",
                         qsls::protocol::CompletionItemKind::TypeParameter => "typeParameter",
+                        qsls::protocol::CompletionItemKind::Snippet => "snippet",
                     })
                     .to_string(),
                     sortText: i.sort_text,
                     detail: i.detail,
+                    insertText: i.insert_text,
                     additionalTextEdits: i.additional_text_edits.map(|edits| {
                         edits
                             .into_iter()
@@ -196,6 +206,54 @@ impl LanguageService {
             .collect()
     }
 
+    pub fn prepare_call_hierarchy(
+        &self,
+        uri: &str,
+        position: IPosition,
+    ) -> Option<ICallHierarchyItem> {
+        let position: Position = position.into();
+        let item = self.0.prepare_call_hierarchy(uri, position.into());
+        item.map(|item| CallHierarchyItem::from(item).into())
+    }
+
+    pub fn get_incoming_calls(
+        &self,
+        uri: &str,
+        position: IPosition,
+    ) -> Vec<ICallHierarchyIncomingCall> {
+        let position: Position = position.into();
+        let calls = self.0.get_incoming_calls(uri, position.into());
+        calls
+            .into_iter()
+            .map(|call| {
+                CallHierarchyIncomingCall {
+                    from: call.from.into(),
+                    fromRanges: call.from_ranges.into_iter().map(Into::into).collect(),
+                }
+                .into()
+            })
+            .collect()
+    }
+
+    pub fn get_outgoing_calls(
+        &self,
+        uri: &str,
+        position: IPosition,
+    ) -> Vec<ICallHierarchyOutgoingCall> {
+        let position: Position = position.into();
+        let calls = self.0.get_outgoing_calls(uri, position.into());
+        calls
+            .into_iter()
+            .map(|call| {
+                CallHierarchyOutgoingCall {
+                    to: call.to.into(),
+                    fromRanges: call.from_ranges.into_iter().map(Into::into).collect(),
+                }
+                .into()
+            })
+            .collect()
+    }
+
     pub fn get_format_changes(&self, uri: &str) -> Vec<ITextEdit> {
         let edits = self.0.get_format_changes(uri);
         edits
@@ -310,6 +368,57 @@ impl LanguageService {
             })
             .collect()
     }
+
+    pub fn get_inlay_hints(&self, uri: &str) -> Vec<IInlayHint> {
+        let inlay_hints = self.0.get_inlay_hints(uri);
+        inlay_hints
+            .into_iter()
+            .map(|hint| {
+                let kind = match hint.kind {
+                    qsls::protocol::InlayHintKind::DynamicBinding => "dynamicBinding",
+                    qsls::protocol::InlayHintKind::RequiredCapabilities => "requiredCapabilities",
+                    qsls::protocol::InlayHintKind::InferredType => "inferredType",
+                    qsls::protocol::InlayHintKind::QubitCount => "qubitCount",
+                };
+                InlayHint {
+                    position: hint.position.into(),
+                    label: hint.label,
+                    kind: kind.to_string(),
+                }
+                .into()
+            })
+            .collect()
+    }
+
+    pub fn get_code_actions(&self, uri: &str, range: IRange) -> Vec<ICodeAction> {
+        let range: Range = range.into();
+        let code_actions = self.0.get_code_actions(uri, range.into());
+        code_actions
+            .into_iter()
+            .map(|action| {
+                let kind = match action.kind {
+                    qsls::protocol::CodeActionKind::RefactorExtract => "refactor.extract",
+                    qsls::protocol::CodeActionKind::RefactorRewrite => "refactor.rewrite",
+                    qsls::protocol::CodeActionKind::SourceOrganizeImports => {
+                        "source.organizeImports"
+                    }
+                };
+                CodeAction {
+                    title: action.title,
+                    kind: kind.to_string(),
+                    edits: action
+                        .edits
+                        .into_iter()
+                        .map(|edit| TextEdit {
+                            range: edit.range.into(),
+                            newText: edit.new_text,
+                        })
+                        .collect(),
+                }
+                .into()
+            })
+            .collect()
+    }
 }
 
 serializable_type! {
@@ -317,14 +426,32 @@ serializable_type! {
     {
         pub targetProfile: Option<String>,
         pub packageType: Option<String>,
+        pub inlayHints: Option<InlayHintsConfiguration>,
     },
     r#"export interface IWorkspaceConfiguration {
         targetProfile?: TargetProfile;
         packageType?: "exe" | "lib";
+        inlayHints?: IInlayHintsConfiguration;
     }"#,
     IWorkspaceConfiguration
 }
 
+serializable_type! {
+    InlayHintsConfiguration,
+    {
+        pub dynamicBindings: bool,
+        pub requiredCapabilities: bool,
+        pub inferredTypes: bool,
+        pub qubitCounts: bool,
+    },
+    r#"export interface IInlayHintsConfiguration {
+        dynamicBindings: boolean;
+        requiredCapabilities: boolean;
+        inferredTypes: boolean;
+        qubitCounts: boolean;
+    }"#
+}
+
 serializable_type! {
     CompletionList,
     {
@@ -343,13 +470,15 @@ serializable_type! {
         pub kind: String,
         pub sortText: Option<String>,
         pub detail: Option<String>,
+        pub insertText: Option<String>,
         pub additionalTextEdits: Option<Vec<TextEdit>>,
     },
     r#"export interface ICompletionItem {
         label: string;
-        kind: "function" | "interface" | "keyword" | "module" | "property" | "variable" | "typeParameter";
+        kind: "function" | "interface" | "keyword" | "module" | "property" | "variable" | "typeParameter" | "snippet";
         sortText?: string;
         detail?: string;
+        insertText?: string;
         additionalTextEdits?: ITextEdit[];
     }"#
 }
@@ -437,6 +566,36 @@ serializable_type! {
     ICodeLens
 }
 
+serializable_type! {
+    InlayHint,
+    {
+        position: Position,
+        label: String,
+        kind: String,
+    },
+    r#"export interface IInlayHint {
+        position: IPosition;
+        label: string;
+        kind: "dynamicBinding" | "requiredCapabilities" | "inferredType" | "qubitCount";
+    }"#,
+    IInlayHint
+}
+
+serializable_type! {
+    CodeAction,
+    {
+        title: String,
+        kind: String,
+        edits: Vec<TextEdit>,
+    },
+    r#"export interface ICodeAction {
+        title: string;
+        kind: "refactor.extract" | "refactor.rewrite" | "source.organizeImports";
+        edits: ITextEdit[];
+    }"#,
+    ICodeAction
+}
+
 serializable_type! {
     WorkspaceEdit,
     {
@@ -483,3 +642,57 @@ extern "C" {
     )]
     pub type DiagnosticsCallback;
 }
+
+serializable_type! {
+    CallHierarchyItem,
+    {
+        name: String,
+        detail: String,
+        location: Location,
+        selectionRange: Range,
+    },
+    r#"export interface ICallHierarchyItem {
+        name: string;
+        detail: string;
+        location: ILocation;
+        selectionRange: IRange;
+    }"#,
+    ICallHierarchyItem
+}
+
+impl From<qsls::protocol::CallHierarchyItem> for CallHierarchyItem {
+    fn from(item: qsls::protocol::CallHierarchyItem) -> Self {
+        CallHierarchyItem {
+            name: item.name,
+            detail: item.detail,
+            location: item.location.into(),
+            selectionRange: item.selection_range.into(),
+        }
+    }
+}
+
+serializable_type! {
+    CallHierarchyIncomingCall,
+    {
+        from: CallHierarchyItem,
+        fromRanges: Vec<Range>,
+    },
+    r#"export interface ICallHierarchyIncomingCall {
+        from: ICallHierarchyItem;
+        fromRanges: IRange[];
+    }"#,
+    ICallHierarchyIncomingCall
+}
+
+serializable_type! {
+    CallHierarchyOutgoingCall,
+    {
+        to: CallHierarchyItem,
+        fromRanges: Vec<Range>,
+    },
+    r#"export interface ICallHierarchyOutgoingCall {
+        to: ICallHierarchyItem;
+        fromRanges: IRange[];
+    }"#,
+    ICallHierarchyOutgoingCall
+}
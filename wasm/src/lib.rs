@@ -5,24 +5,27 @@
 
 use diagnostic::VSDiagnostic;
 use katas::check_solution;
+use line_column::{Location, Range};
 use num_bigint::BigUint;
 use num_complex::Complex64;
 use project_system::into_async_rust_fn_with;
 use qsc::{
     compile, format_state_id, get_latex,
+    hints::{get_requirements_for_sources, CallableRequirements},
     hir::PackageId,
     interpret::{
         self,
         output::{self, Receiver},
     },
+    line_column::Encoding,
     target::Profile,
     LanguageFeatures, PackageStore, PackageType, SourceContents, SourceMap, SourceName, SparseSim,
 };
 use qsc_codegen::qir_base::generate_qir;
 use resource_estimator::{self as re, estimate_entry};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::{fmt::Write, sync::Arc};
+use std::{fmt::Write, str::FromStr, sync::Arc};
 use wasm_bindgen::prelude::*;
 
 mod debug_service;
@@ -133,6 +136,108 @@ pub fn get_estimates(
     })
 }
 
+serializable_type! {
+    CallableCapabilities,
+    {
+        pub name: String,
+        pub location: Location,
+        pub capabilities: Vec<String>,
+        pub unsupported: Vec<UnsupportedConstruct>,
+    },
+    r"export interface ICallableCapabilities {
+        name: string;
+        location: ILocation;
+        capabilities: string[];
+        unsupported: IUnsupportedConstruct[];
+    }",
+    ICallableCapabilities
+}
+
+serializable_type! {
+    UnsupportedConstruct,
+    {
+        pub location: Location,
+        pub capabilities: Vec<String>,
+    },
+    r#"export interface IUnsupportedConstruct {
+        location: ILocation;
+        capabilities: string[];
+    }"#
+}
+
+/// Runs the runtime capabilities analysis on the sources and returns the capabilities each callable needs,
+/// along with the constructs that need capabilities the given target profile doesn't have.
+#[wasm_bindgen]
+pub fn get_runtime_capabilities(
+    sources: Vec<js_sys::Array>,
+    profile: &str,
+    language_features: Vec<String>,
+) -> Result<Vec<ICallableCapabilities>, String> {
+    let profile =
+        Profile::from_str(profile).map_err(|()| format!("Invalid target profile {profile}"))?;
+    let sources = get_source_map(sources, &None);
+    let language_features = LanguageFeatures::from_iter(language_features);
+    Ok(
+        _get_runtime_capabilities(sources, profile, language_features)?
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    )
+}
+
+// allows testing without wasm bindings.
+fn _get_runtime_capabilities(
+    sources: SourceMap,
+    profile: Profile,
+    language_features: LanguageFeatures,
+) -> Result<Vec<CallableCapabilities>, String> {
+    let source_map = SourceMap::new(
+        sources
+            .iter()
+            .map(|source| (source.name.clone(), source.contents.clone())),
+        None,
+    );
+    let requirements = get_requirements_for_sources(sources, language_features, profile.into())
+        .map_err(|e| e[0].to_string())?;
+
+    let location = |span: qsc::Span| {
+        let source = source_map
+            .find_by_offset(span.lo)
+            .expect("source should exist for offset");
+        Location {
+            source: source.name.to_string(),
+            span: Range::from(qsc::line_column::Range::from_span(
+                Encoding::Utf16,
+                &source.contents,
+                &(span - source.offset),
+            )),
+        }
+    };
+    let names = |capabilities: qsc::RuntimeCapabilityFlags| {
+        capabilities
+            .iter_names()
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    Ok(requirements
+        .into_iter()
+        .map(|callable: CallableRequirements| CallableCapabilities {
+            name: callable.name.to_string(),
+            location: location(callable.span),
+            capabilities: names(callable.capabilities),
+            unsupported: callable
+                .unsupported
+                .into_iter()
+                .map(|construct| UnsupportedConstruct {
+                    location: location(construct.span),
+                    capabilities: names(construct.capabilities),
+                })
+                .collect(),
+        })
+        .collect())
+}
+
 #[wasm_bindgen]
 #[must_use]
 pub fn get_library_source_content(name: &str) -> Option<String> {
@@ -219,6 +324,20 @@ where
         Ok(())
     }
 
+    fn matrix(&mut self, matrix: Vec<Vec<Complex64>>) -> Result<(), output::Error> {
+        let matrix = matrix
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|entry| [entry.re, entry.im])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let matrix_json = json!({"type": "Matrix", "matrix": matrix});
+        (self.event_cb)(&matrix_json.to_string());
+        Ok(())
+    }
+
     fn message(&mut self, msg: &str) -> Result<(), output::Error> {
         let msg_json = json!({"type": "Message", "message": msg});
         (self.event_cb)(&msg_json.to_string());
@@ -230,6 +349,7 @@ fn run_internal_with_features<F>(
     event_cb: F,
     shots: u32,
     language_features: LanguageFeatures,
+    limits: interpret::Limits,
 ) -> Result<(), Box<interpret::Error>>
 where
     F: FnMut(&str),
@@ -260,6 +380,7 @@ where
             return Err(Box::new(e));
         }
     };
+    interpreter.set_limits(limits);
 
     for _ in 0..shots {
         let result = interpreter.eval_entry_with_sim(&mut SparseSim::new(), &mut out);
@@ -287,19 +408,27 @@ pub fn run(
     event_cb: &js_sys::Function,
     shots: u32,
     language_features: Vec<String>,
+    untrusted: bool,
 ) -> Result<bool, JsValue> {
     if !event_cb.is_function() {
         return Err(JsError::new("Events callback function must be provided").into());
     }
 
     let language_features = LanguageFeatures::from_iter(language_features);
+    // Untrusted code isn't limited in time here, since the worker it runs in is terminated
+    // instead.
+    let limits = if untrusted {
+        interpret::Limits::untrusted()
+    } else {
+        interpret::Limits::default()
+    };
 
     let sources = get_source_map(sources, &Some(expr.into()));
     let event_cb = |msg: &str| {
         // See example at https://rustwasm.github.io/wasm-bindgen/reference/receiving-js-closures-in-rust.html
         let _ = event_cb.call1(&JsValue::null(), &JsValue::from(msg));
     };
-    match run_internal_with_features(sources, event_cb, shots, language_features) {
+    match run_internal_with_features(sources, event_cb, shots, language_features, limits) {
         Ok(()) => Ok(true),
         Err(e) => Err(JsError::from(e).into()),
     }
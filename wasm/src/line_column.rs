@@ -28,7 +28,8 @@ serializable_type! {
     r#"export interface IRange {
         start: IPosition;
         end: IPosition;
-    }"#
+    }"#,
+    IRange
 }
 
 serializable_type! {
@@ -62,6 +63,15 @@ impl From<line_column::Position> for Position {
     }
 }
 
+impl From<Range> for line_column::Range {
+    fn from(range: Range) -> Self {
+        line_column::Range {
+            start: range.start.into(),
+            end: range.end.into(),
+        }
+    }
+}
+
 impl From<qsc::line_column::Range> for Range {
     fn from(range: qsc::line_column::Range) -> Self {
         Range {
@@ -1,12 +1,13 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::cell::RefCell;
+use std::{cell::RefCell, sync::Once};
 
 use wasm_bindgen::prelude::*;
 
-use js_sys::Function;
+use js_sys::{Date, Function};
 use log::LevelFilter;
+use qsc::timing::SpanTimer;
 
 #[wasm_bindgen]
 extern "C" {
@@ -30,8 +31,12 @@ static MY_LOGGER: MyLogger = MyLogger;
 thread_local! {
     // Will hold a reference to the JS logging function that was passed in
     static LOG_JS_FN: RefCell<Option<Function>> = const { RefCell::new(None) };
+    // Will hold a reference to the JS function that span timings are reported to
+    static TRACE_JS_FN: RefCell<Option<Function>> = const { RefCell::new(None) };
 }
 
+static TRACING_INIT: Once = Once::new();
+
 struct MyLogger;
 
 impl log::Log for MyLogger {
@@ -123,3 +128,37 @@ pub fn set_log_level(level: i32) {
     });
     log::info!("Log level set to {}", level);
 }
+
+/// Reports the time spent in each phase of compilation and evaluation to `callback`, which is
+/// called with the name of the phase and the elapsed milliseconds. Calling this again replaces
+/// the callback.
+#[wasm_bindgen(js_name=initTracing)]
+pub fn init_tracing(callback: JsValue) -> Result<(), JsError> {
+    if !callback.is_function() {
+        return Err(JsError::new("Invalid callback"));
+    }
+
+    let thefn: Function = callback.dyn_into().expect("should already be a function");
+    TRACE_JS_FN.with(|f| {
+        *f.borrow_mut() = Option::Some(thefn);
+    });
+
+    let mut result = Ok(());
+    TRACING_INIT.call_once(|| {
+        let timer = SpanTimer::new(Date::now, |name, elapsed| {
+            TRACE_JS_FN.with(|f| {
+                if let Some(js_fn) = f.borrow().as_ref() {
+                    // Ignore any errors calling the JavaScript provided handler
+                    let _ = js_fn.call2(
+                        &JsValue::NULL,
+                        &JsValue::from(name),
+                        &JsValue::from(elapsed),
+                    );
+                }
+            });
+        });
+        result = tracing::subscriber::set_global_default(timer)
+            .map_err(|e| JsError::new(&e.to_string()));
+    });
+    result
+}
@@ -199,6 +199,23 @@ pub(crate) fn get_manifest_transformer(js_val: JsValue, _: String) -> Option<Man
         _ => Vec::new(),
     };
 
+    let config = match js_sys::Reflect::get(&js_val, &JsValue::from_str("config")) {
+        Ok(v) => match v.dyn_into::<js_sys::Array>() {
+            Ok(arr) => arr
+                .into_iter()
+                .map(|x| {
+                    x.as_string().unwrap_or_else(|| {
+                        panic!(
+                            "manifest callback returned {x:?}, but we expected a string representing a config flag"
+                        )
+                    })
+                })
+                .collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+
     log::trace!("found manifest at {manifest_dir:?}");
 
     let manifest_dir = PathBuf::from(manifest_dir);
@@ -207,6 +224,7 @@ pub(crate) fn get_manifest_transformer(js_val: JsValue, _: String) -> Option<Man
         manifest: Manifest {
             language_features,
             lints,
+            config,
             author: Option::default(),
             license: Option::default(),
         },
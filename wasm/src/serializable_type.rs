@@ -127,8 +127,10 @@ macro_rules! serializable_type {
 
         #[wasm_bindgen]
         extern "C" {
+            // The documentation is the TypeScript type definition, not Markdown.
             #[wasm_bindgen(typescript_type = $typescript_type_ident)]
             #[doc=$typescript]
+            #[allow(clippy::doc_markdown)]
             pub type $typescript_type_ident;
         }
 
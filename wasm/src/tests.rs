@@ -3,7 +3,7 @@
 
 use expect_test::expect;
 use indoc::indoc;
-use qsc::{interpret, LanguageFeatures, SourceMap};
+use qsc::{interpret, target::Profile, LanguageFeatures, SourceMap};
 
 use super::run_internal_with_features;
 
@@ -11,7 +11,13 @@ fn run_internal<F>(sources: SourceMap, event_cb: F, shots: u32) -> Result<(), Bo
 where
     F: FnMut(&str),
 {
-    run_internal_with_features(sources, event_cb, shots, LanguageFeatures::default())
+    run_internal_with_features(
+        sources,
+        event_cb,
+        shots,
+        LanguageFeatures::default(),
+        interpret::Limits::default(),
+    )
 }
 
 #[test]
@@ -46,6 +52,101 @@ fn test_compile() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_runtime_capabilities() {
+    let code = "namespace Test { operation Foo() : Unit {
+    use q = Qubit();
+    if M(q) == One { X(q); }
+    }}";
+
+    let capabilities = crate::_get_runtime_capabilities(
+        SourceMap::new([("test.qs".into(), code.into())], None),
+        Profile::Base,
+        LanguageFeatures::default(),
+    )
+    .expect("analysis should succeed");
+    expect![[r#"
+        [
+            CallableCapabilities {
+                name: "Foo",
+                location: Location {
+                    source: "test.qs",
+                    span: Range {
+                        start: Position {
+                            line: 0,
+                            character: 27,
+                        },
+                        end: Position {
+                            line: 0,
+                            character: 30,
+                        },
+                    },
+                },
+                capabilities: [
+                    "ForwardBranching",
+                ],
+                unsupported: [
+                    UnsupportedConstruct {
+                        location: Location {
+                            source: "test.qs",
+                            span: Range {
+                                start: Position {
+                                    line: 2,
+                                    character: 4,
+                                },
+                                end: Position {
+                                    line: 2,
+                                    character: 28,
+                                },
+                            },
+                        },
+                        capabilities: [
+                            "ForwardBranching",
+                        ],
+                    },
+                    UnsupportedConstruct {
+                        location: Location {
+                            source: "test.qs",
+                            span: Range {
+                                start: Position {
+                                    line: 2,
+                                    character: 7,
+                                },
+                                end: Position {
+                                    line: 2,
+                                    character: 18,
+                                },
+                            },
+                        },
+                        capabilities: [
+                            "ForwardBranching",
+                        ],
+                    },
+                    UnsupportedConstruct {
+                        location: Location {
+                            source: "test.qs",
+                            span: Range {
+                                start: Position {
+                                    line: 2,
+                                    character: 21,
+                                },
+                                end: Position {
+                                    line: 2,
+                                    character: 25,
+                                },
+                            },
+                        },
+                        capabilities: [
+                            "ForwardBranching",
+                        ],
+                    },
+                ],
+            },
+        ]
+    "#]]
+    .assert_debug_eq(&capabilities);
+}
+
 #[test]
 fn test_run_two_shots() {
     let code = "
@@ -201,6 +302,35 @@ fn test_missing_entrypoint() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_runtime_error_has_stack() {
+    let code = "namespace Test {
+        function Div(a : Int, b : Int) : Int { a / b }
+        function Main() : Int { Div(1, 0) }
+    }";
+    let count = std::cell::Cell::new(0);
+    let _ = run_internal(
+        SourceMap::new(
+            [("test.qs".into(), code.into())],
+            Some("Test.Main()".into()),
+        ),
+        |msg| {
+            assert!(msg.contains(r#""code":"Qsc.Eval.DivZero""#));
+            assert!(msg.contains(r#""stack":[{"functor":"","location":{"source":"test.qs","#));
+            let div = msg
+                .find(r#""name":"Test.Div""#)
+                .expect("frame of Div should be in the stack");
+            let main = msg
+                .find(r#""name":"Test.Main""#)
+                .expect("frame of Main should be in the stack");
+            assert!(div < main);
+            count.set(count.get() + 1);
+        },
+        1,
+    );
+    assert_eq!(count.get(), 1);
+}
+
 #[test]
 fn test_run_simple_program_multiple_shots() {
     let mut output = Vec::new();
@@ -242,9 +372,9 @@ fn test_run_error_program_multiple_shots() {
     )
     .expect("code should compile and run");
     expect![[r#"
-        {"result":{"code":"Qsc.Eval.QubitUniqueness","message":"runtime error: qubits in invocation are not unique","range":{"end":{"character":1,"line":0},"start":{"character":0,"line":0}},"severity":"error"},"success":false,"type":"Result"}
-        {"result":{"code":"Qsc.Eval.QubitUniqueness","message":"runtime error: qubits in invocation are not unique","range":{"end":{"character":1,"line":0},"start":{"character":0,"line":0}},"severity":"error"},"success":false,"type":"Result"}
-        {"result":{"code":"Qsc.Eval.QubitUniqueness","message":"runtime error: qubits in invocation are not unique","range":{"end":{"character":1,"line":0},"start":{"character":0,"line":0}},"severity":"error"},"success":false,"type":"Result"}"#]]
+        {"result":{"code":"Qsc.Eval.QubitUniqueness","message":"runtime error: qubits in invocation are not unique","range":{"end":{"character":1,"line":0},"start":{"character":0,"line":0}},"severity":"error","stack":[{"functor":"","location":{"source":"intrinsic.qs","span":{"end":{"character":53,"line":66},"start":{"character":12,"line":66}}},"name":"QIR.Intrinsic.__quantum__qis__cx__body"},{"functor":"","location":{"source":"test.qs","span":{"end":{"character":18,"line":4},"start":{"character":8,"line":4}}},"name":"Microsoft.Quantum.Intrinsic.CNOT"},{"functor":"","location":{"source":"test.qs","span":{"end":{"character":5,"line":5},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}
+        {"result":{"code":"Qsc.Eval.QubitUniqueness","message":"runtime error: qubits in invocation are not unique","range":{"end":{"character":1,"line":0},"start":{"character":0,"line":0}},"severity":"error","stack":[{"functor":"","location":{"source":"intrinsic.qs","span":{"end":{"character":53,"line":66},"start":{"character":12,"line":66}}},"name":"QIR.Intrinsic.__quantum__qis__cx__body"},{"functor":"","location":{"source":"test.qs","span":{"end":{"character":18,"line":4},"start":{"character":8,"line":4}}},"name":"Microsoft.Quantum.Intrinsic.CNOT"},{"functor":"","location":{"source":"test.qs","span":{"end":{"character":5,"line":5},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}
+        {"result":{"code":"Qsc.Eval.QubitUniqueness","message":"runtime error: qubits in invocation are not unique","range":{"end":{"character":1,"line":0},"start":{"character":0,"line":0}},"severity":"error","stack":[{"functor":"","location":{"source":"intrinsic.qs","span":{"end":{"character":53,"line":66},"start":{"character":12,"line":66}}},"name":"QIR.Intrinsic.__quantum__qis__cx__body"},{"functor":"","location":{"source":"test.qs","span":{"end":{"character":18,"line":4},"start":{"character":8,"line":4}}},"name":"Microsoft.Quantum.Intrinsic.CNOT"},{"functor":"","location":{"source":"test.qs","span":{"end":{"character":5,"line":5},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}"#]]
     .assert_eq(&output.join("\n"));
 }
 
@@ -270,11 +400,11 @@ fn test_run_error_program_multiple_shots_qubit_leak() {
     .expect("code should compile and run");
 
     // Spot check the results to make sure we're getting the right error.
-    expect![[r#"{"result":{"code":"Qsc.Eval.ReleasedQubitNotZero","message":"runtime error: Qubit0 released while not in |0⟩ state\n\nhelp: qubits should be returned to the |0⟩ state before being released to satisfy the assumption that allocated qubits start in the |0⟩ state","range":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"code","span":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}}},"message":"Qubit0"}],"severity":"error"},"success":false,"type":"Result"}"#]]
+    expect![[r#"{"result":{"code":"Qsc.Eval.ReleasedQubitNotZero","message":"runtime error: Qubit0 released while not in |0⟩ state\n\nhelp: qubits should be returned to the |0⟩ state before being released to satisfy the assumption that allocated qubits start in the |0⟩ state","range":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"code","span":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}}},"message":"Qubit0"}],"severity":"error","stack":[{"functor":"","name":"QIR.Runtime.__quantum__rt__qubit_release"},{"functor":"","location":{"source":"code","span":{"end":{"character":5,"line":5},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}"#]]
         .assert_eq(&output[0]);
-    expect![[r#"{"result":{"code":"Qsc.Eval.ReleasedQubitNotZero","message":"runtime error: Qubit0 released while not in |0⟩ state\n\nhelp: qubits should be returned to the |0⟩ state before being released to satisfy the assumption that allocated qubits start in the |0⟩ state","range":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"code","span":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}}},"message":"Qubit0"}],"severity":"error"},"success":false,"type":"Result"}"#]]
+    expect![[r#"{"result":{"code":"Qsc.Eval.ReleasedQubitNotZero","message":"runtime error: Qubit0 released while not in |0⟩ state\n\nhelp: qubits should be returned to the |0⟩ state before being released to satisfy the assumption that allocated qubits start in the |0⟩ state","range":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"code","span":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}}},"message":"Qubit0"}],"severity":"error","stack":[{"functor":"","name":"QIR.Runtime.__quantum__rt__qubit_release"},{"functor":"","location":{"source":"code","span":{"end":{"character":5,"line":5},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}"#]]
         .assert_eq(&output[50]);
-    expect![[r#"{"result":{"code":"Qsc.Eval.ReleasedQubitNotZero","message":"runtime error: Qubit0 released while not in |0⟩ state\n\nhelp: qubits should be returned to the |0⟩ state before being released to satisfy the assumption that allocated qubits start in the |0⟩ state","range":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"code","span":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}}},"message":"Qubit0"}],"severity":"error"},"success":false,"type":"Result"}"#]]
+    expect![[r#"{"result":{"code":"Qsc.Eval.ReleasedQubitNotZero","message":"runtime error: Qubit0 released while not in |0⟩ state\n\nhelp: qubits should be returned to the |0⟩ state before being released to satisfy the assumption that allocated qubits start in the |0⟩ state","range":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"code","span":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}}},"message":"Qubit0"}],"severity":"error","stack":[{"functor":"","name":"QIR.Runtime.__quantum__rt__qubit_release"},{"functor":"","location":{"source":"code","span":{"end":{"character":5,"line":5},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}"#]]
         .assert_eq(&output[99]);
 }
 
@@ -296,9 +426,9 @@ fn test_runtime_error_with_span() {
     )
     .expect("code should compile and run");
     expect![[r#"
-        {"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: hello","range":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"test.qs","span":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}}},"message":"explicit fail"}],"severity":"error"},"success":false,"type":"Result"}
-        {"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: hello","range":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"test.qs","span":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}}},"message":"explicit fail"}],"severity":"error"},"success":false,"type":"Result"}
-        {"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: hello","range":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"test.qs","span":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}}},"message":"explicit fail"}],"severity":"error"},"success":false,"type":"Result"}"#]]
+        {"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: hello","range":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"test.qs","span":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}}},"message":"explicit fail"}],"severity":"error","stack":[{"functor":"","location":{"source":"test.qs","span":{"end":{"character":5,"line":4},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}
+        {"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: hello","range":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"test.qs","span":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}}},"message":"explicit fail"}],"severity":"error","stack":[{"functor":"","location":{"source":"test.qs","span":{"end":{"character":5,"line":4},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}
+        {"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: hello","range":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"test.qs","span":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}}},"message":"explicit fail"}],"severity":"error","stack":[{"functor":"","location":{"source":"test.qs","span":{"end":{"character":5,"line":4},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}"#]]
     .assert_eq(&output.join("\n"));
 }
 
@@ -333,7 +463,7 @@ fn test_runtime_error_in_another_file_with_project() {
         1,
     )
     .expect("code should compile and run");
-    expect![[r#"{"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: hello","range":{"end":{"character":1,"line":0},"start":{"character":0,"line":0}},"related":[{"location":{"source":"test2.qs","span":{"end":{"character":20,"line":2},"start":{"character":8,"line":2}}},"message":"explicit fail"}],"severity":"error"},"success":false,"type":"Result"}"#]]
+    expect![[r#"{"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: hello","range":{"end":{"character":1,"line":0},"start":{"character":0,"line":0}},"related":[{"location":{"source":"test2.qs","span":{"end":{"character":20,"line":2},"start":{"character":8,"line":2}}},"message":"explicit fail"}],"severity":"error","stack":[{"functor":"","location":{"source":"test1.qs","span":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}}},"name":"Test.other"},{"functor":"","location":{"source":"test1.qs","span":{"end":{"character":5,"line":4},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}"#]]
     .assert_eq(&output.join("\n"));
 }
 
@@ -370,7 +500,7 @@ fn test_runtime_error_with_failure_in_main_file_project() {
         1,
     )
     .expect("code should compile and run");
-    expect![[r#"{"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: hello","range":{"end":{"character":20,"line":6},"start":{"character":8,"line":6}},"related":[{"location":{"source":"test1.qs","span":{"end":{"character":20,"line":6},"start":{"character":8,"line":6}}},"message":"explicit fail"}],"severity":"error"},"success":false,"type":"Result"}"#]]
+    expect![[r#"{"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: hello","range":{"end":{"character":20,"line":6},"start":{"character":8,"line":6}},"related":[{"location":{"source":"test1.qs","span":{"end":{"character":20,"line":6},"start":{"character":8,"line":6}}},"message":"explicit fail"}],"severity":"error","stack":[{"functor":"","location":{"source":"test2.qs","span":{"end":{"character":27,"line":2},"start":{"character":8,"line":2}}},"name":"Test.failing_call"},{"functor":"","location":{"source":"test1.qs","span":{"end":{"character":20,"line":3},"start":{"character":8,"line":3}}},"name":"Test.other"},{"functor":"","location":{"source":"test1.qs","span":{"end":{"character":5,"line":4},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}"#]]
     .assert_eq(&output.join("\n"));
 }
 
@@ -418,7 +548,7 @@ fn test_runtime_error_related_spans() {
         1,
     )
     .expect("code should compile and run");
-    expect![[r#"{"result":{"code":"Qsc.Eval.ReleasedQubitNotZero","message":"runtime error: Qubit0 released while not in |0⟩ state\n\nhelp: qubits should be returned to the |0⟩ state before being released to satisfy the assumption that allocated qubits start in the |0⟩ state","range":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"test.qs","span":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}}},"message":"Qubit0"}],"severity":"error"},"success":false,"type":"Result"}"#]]
+    expect![[r#"{"result":{"code":"Qsc.Eval.ReleasedQubitNotZero","message":"runtime error: Qubit0 released while not in |0⟩ state\n\nhelp: qubits should be returned to the |0⟩ state before being released to satisfy the assumption that allocated qubits start in the |0⟩ state","range":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}},"related":[{"location":{"source":"test.qs","span":{"end":{"character":24,"line":3},"start":{"character":8,"line":3}}},"message":"Qubit0"}],"severity":"error","stack":[{"functor":"","name":"QIR.Runtime.__quantum__rt__qubit_release"},{"functor":"","location":{"source":"test.qs","span":{"end":{"character":5,"line":5},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}"#]]
     .assert_eq(&output.join("\n"));
 }
 
@@ -440,7 +570,7 @@ fn test_runtime_error_default_span() {
         1,
     )
     .expect("code should compile and run");
-    expect![[r#"{"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: Cannot allocate qubit array with a negative length","range":{"end":{"character":1,"line":0},"start":{"character":0,"line":0}},"related":[{"location":{"source":"core/qir.qs","span":{"end":{"character":69,"line":14},"start":{"character":12,"line":14}}},"message":"explicit fail"}],"severity":"error"},"success":false,"type":"Result"}"#]]
+    expect![[r#"{"result":{"code":"Qsc.Eval.UserFail","message":"runtime error: program failed: Cannot allocate qubit array with a negative length","range":{"end":{"character":1,"line":0},"start":{"character":0,"line":0}},"related":[{"location":{"source":"core/qir.qs","span":{"end":{"character":69,"line":14},"start":{"character":12,"line":14}}},"message":"explicit fail"}],"severity":"error","stack":[{"functor":"","location":{"source":"test.qs","span":{"end":{"character":27,"line":3},"start":{"character":8,"line":3}}},"name":"QIR.Runtime.AllocateQubitArray"},{"functor":"","location":{"source":"test.qs","span":{"end":{"character":5,"line":4},"start":{"character":4,"line":2}}},"name":"Test.Main"}]},"success":false,"type":"Result"}"#]]
     .assert_eq(&output.join("\n"));
 }
 